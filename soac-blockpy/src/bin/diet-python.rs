@@ -1,17 +1,29 @@
 use std::{env, fs, process};
 
 use serde_json::json;
-use soac_blockpy::{lower_python_to_blockpy_for_testing, ruff_ast_to_string};
+use soac_blockpy::{
+    lower_python_to_blockpy_for_testing_with_options, ruff_ast_to_string, LoweringOptions,
+};
 
-const USAGE: &str = "usage: diet-python [--timing] <python-file>";
+const USAGE: &str = "usage: diet-python [--timing] [--trace <trace.json>] <python-file>";
 
 fn main() {
     let mut timing = false;
+    let mut trace_path: Option<String> = None;
     let mut path: Option<String> = None;
 
-    for arg in env::args().skip(1) {
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
         match arg.as_str() {
             "--timing" => timing = true,
+            "--trace" => match args.next() {
+                Some(value) => trace_path = Some(value),
+                None => {
+                    eprintln!("--trace needs an output path");
+                    eprintln!("{}", USAGE);
+                    process::exit(1);
+                }
+            },
             "--help" | "-h" => {
                 eprintln!("{}", USAGE);
                 return;
@@ -46,10 +58,13 @@ fn main() {
         }
     };
 
-    let result = match lower_python_to_blockpy_for_testing(&source) {
+    let result = match lower_python_to_blockpy_for_testing_with_options(
+        &source,
+        &LoweringOptions::from_env(),
+    ) {
         Ok(result) => result,
         Err(err) => {
-            eprintln!("failed to parse {}: {}", path, err);
+            eprint!("{}", err.to_diagnostic().render(&path, &source));
             process::exit(1);
         }
     };
@@ -65,10 +80,18 @@ fn main() {
         let pass_timings = pass_timings
             .into_iter()
             .map(|pass| {
-                json!({
+                let mut entry = json!({
                     "name": pass.name,
                     "elapsed_ns": pass.elapsed.as_nanos(),
-                })
+                });
+                if let Some(parallel) = pass.parallel {
+                    entry["threads"] = json!(parallel.threads);
+                    entry["busy_ns"] = json!(parallel.busy.as_nanos());
+                }
+                if let Some(stats) = result.pass_tracker.pass_ir_stats(&pass.name) {
+                    entry["ir"] = stats.to_json();
+                }
+                entry
             })
             .collect::<Vec<_>>();
         eprintln!(
@@ -79,4 +102,12 @@ fn main() {
             })
         );
     }
+
+    if let Some(trace_path) = trace_path {
+        let trace = result.pass_tracker.chrome_trace().to_string();
+        if let Err(err) = fs::write(&trace_path, trace) {
+            eprintln!("failed to write {}: {}", trace_path, err);
+            process::exit(1);
+        }
+    }
 }
@@ -0,0 +1,665 @@
+//! Control-flow analyses over the blocks of one `BlockPyFunction`: the CFG
+//! itself, dominator and post-dominator trees, natural loops and name
+//! liveness.
+//!
+//! Blocks are identified by their index in `function.blocks`, and block 0 is
+//! the entry. An `exc_edge` is a real edge: the handler is a successor of
+//! every block that can raise into it. Because the raise can happen part-way
+//! through the source block, dataflow across an exception edge sees the
+//! source block's entry state rather than its exit state.
+
+#![allow(dead_code)]
+
+use std::collections::{HashMap, HashSet};
+
+use super::{
+    walk_expr, walk_term, Block, BlockArg, BlockLabel, BlockPyFunction, BlockPyNameLike,
+    BlockPyPass, BlockTerm, ChildVisitable, CodegenBlockPyExpr, CoreBlockPyExpr, Instr, Visit,
+};
+use crate::passes::{CoreBlockPyExprWithAwaitAndYield, CoreBlockPyExprWithYield};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) enum EdgeKind {
+    /// A jump, branch or branch-table target.
+    Normal,
+    /// The block's `exc_edge`.
+    Exception,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) struct CfgEdge {
+    pub(crate) from: usize,
+    pub(crate) to: usize,
+    pub(crate) kind: EdgeKind,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct Cfg {
+    labels: Vec<BlockLabel>,
+    successors: Vec<Vec<CfgEdge>>,
+    predecessors: Vec<Vec<CfgEdge>>,
+}
+
+impl Cfg {
+    pub(crate) fn new<P: BlockPyPass, S>(function: &BlockPyFunction<P, S>) -> Self {
+        Self::from_blocks(&function.blocks)
+    }
+
+    /// Edges to labels outside `blocks`, such as a structured fallthrough,
+    /// are dropped.
+    pub(crate) fn from_blocks<S, I: Instr>(blocks: &[Block<S, I>]) -> Self {
+        let index_by_label = blocks
+            .iter()
+            .enumerate()
+            .map(|(index, block)| (block.label, index))
+            .collect::<HashMap<_, _>>();
+        let successors = blocks
+            .iter()
+            .map(|block| {
+                let normal = normal_targets(&block.term)
+                    .into_iter()
+                    .map(|label| (label, EdgeKind::Normal));
+                let exception = block
+                    .exc_edge
+                    .as_ref()
+                    .map(|edge| (edge.target, EdgeKind::Exception));
+                normal
+                    .chain(exception)
+                    .filter_map(|(label, kind)| index_by_label.get(&label).map(|&to| (to, kind)))
+                    .collect()
+            })
+            .collect::<Vec<Vec<_>>>();
+        let mut cfg = Self::from_successors(successors);
+        cfg.labels = blocks.iter().map(|block| block.label).collect();
+        cfg
+    }
+
+    /// Builds a CFG over blocks `0..successors.len()`, with block 0 as the
+    /// entry. Duplicate edges are merged.
+    pub(crate) fn from_successors(successors: Vec<Vec<(usize, EdgeKind)>>) -> Self {
+        let len = successors.len();
+        let mut out = vec![Vec::<CfgEdge>::new(); len];
+        let mut predecessors = vec![Vec::<CfgEdge>::new(); len];
+        for (from, targets) in successors.into_iter().enumerate() {
+            for (to, kind) in targets {
+                let edge = CfgEdge { from, to, kind };
+                if out[from].contains(&edge) {
+                    continue;
+                }
+                out[from].push(edge);
+                predecessors[to].push(edge);
+            }
+        }
+        Self {
+            labels: (0..len).map(BlockLabel::from_index).collect(),
+            successors: out,
+            predecessors,
+        }
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.successors.len()
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.successors.is_empty()
+    }
+
+    pub(crate) fn label(&self, block: usize) -> BlockLabel {
+        self.labels[block]
+    }
+
+    pub(crate) fn successors(&self, block: usize) -> &[CfgEdge] {
+        &self.successors[block]
+    }
+
+    pub(crate) fn predecessors(&self, block: usize) -> &[CfgEdge] {
+        &self.predecessors[block]
+    }
+
+    /// Blocks with no successors: returns, and raises with no handler.
+    pub(crate) fn exits(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.len()).filter(|&block| self.successors[block].is_empty())
+    }
+
+    /// Reachable blocks in reverse postorder from the entry.
+    pub(crate) fn reverse_postorder(&self) -> Vec<usize> {
+        if self.is_empty() {
+            return Vec::new();
+        }
+        reverse_postorder(0, &self.successor_indices())
+    }
+
+    pub(crate) fn reachable(&self) -> Vec<bool> {
+        let mut reachable = vec![false; self.len()];
+        for block in self.reverse_postorder() {
+            reachable[block] = true;
+        }
+        reachable
+    }
+
+    fn successor_indices(&self) -> Vec<Vec<usize>> {
+        self.successors
+            .iter()
+            .map(|edges| edges.iter().map(|edge| edge.to).collect())
+            .collect()
+    }
+
+    fn predecessor_indices(&self) -> Vec<Vec<usize>> {
+        self.predecessors
+            .iter()
+            .map(|edges| edges.iter().map(|edge| edge.from).collect())
+            .collect()
+    }
+}
+
+fn normal_targets<I: Instr>(term: &BlockTerm<I>) -> Vec<BlockLabel> {
+    match term {
+        BlockTerm::Jump(edge) => vec![edge.target],
+        BlockTerm::IfTerm(if_term) => vec![if_term.then_label, if_term.else_label],
+        BlockTerm::BranchTable(branch) => {
+            let mut targets = branch.targets.clone();
+            targets.push(branch.default_label);
+            targets
+        }
+        BlockTerm::Raise(_) | BlockTerm::Return(_) => Vec::new(),
+    }
+}
+
+fn reverse_postorder(root: usize, successors: &[Vec<usize>]) -> Vec<usize> {
+    let mut visited = vec![false; successors.len()];
+    let mut postorder = Vec::new();
+    let mut stack = vec![(root, 0)];
+    visited[root] = true;
+    while let Some(top) = stack.len().checked_sub(1) {
+        let (node, next) = stack[top];
+        if let Some(&successor) = successors[node].get(next) {
+            stack[top].1 += 1;
+            if !visited[successor] {
+                visited[successor] = true;
+                stack.push((successor, 0));
+            }
+        } else {
+            postorder.push(node);
+            stack.pop();
+        }
+    }
+    postorder.reverse();
+    postorder
+}
+
+/// Immediate dominators by Cooper, Harvey and Kennedy's iterative algorithm.
+/// Nodes unreachable from `root`, and `root` itself, get `None`.
+fn immediate_dominators(
+    root: usize,
+    successors: &[Vec<usize>],
+    predecessors: &[Vec<usize>],
+) -> (Vec<Option<usize>>, Vec<bool>) {
+    let order = reverse_postorder(root, successors);
+    let mut rpo_index = vec![usize::MAX; successors.len()];
+    for (index, &node) in order.iter().enumerate() {
+        rpo_index[node] = index;
+    }
+    let mut idom = vec![None; successors.len()];
+    idom[root] = Some(root);
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for &node in order.iter().skip(1) {
+            let mut new_idom = None;
+            for &pred in &predecessors[node] {
+                if idom[pred].is_none() {
+                    continue;
+                }
+                new_idom = Some(match new_idom {
+                    None => pred,
+                    Some(current) => intersect(&idom, &rpo_index, pred, current),
+                });
+            }
+            if new_idom != idom[node] {
+                idom[node] = new_idom;
+                changed = true;
+            }
+        }
+    }
+    idom[root] = None;
+    let in_tree = rpo_index.iter().map(|&index| index != usize::MAX).collect();
+    (idom, in_tree)
+}
+
+fn intersect(idom: &[Option<usize>], rpo_index: &[usize], mut a: usize, mut b: usize) -> usize {
+    while a != b {
+        while rpo_index[a] > rpo_index[b] {
+            a = idom[a].expect("processed nodes have an immediate dominator");
+        }
+        while rpo_index[b] > rpo_index[a] {
+            b = idom[b].expect("processed nodes have an immediate dominator");
+        }
+    }
+    a
+}
+
+/// A dominator or post-dominator tree.
+#[derive(Debug, Clone)]
+pub(crate) struct DominatorTree {
+    idom: Vec<Option<usize>>,
+    in_tree: Vec<bool>,
+    children: Vec<Vec<usize>>,
+}
+
+impl DominatorTree {
+    /// Dominators from the entry block. Unreachable blocks are not in the
+    /// tree.
+    pub(crate) fn dominators(cfg: &Cfg) -> Self {
+        if cfg.is_empty() {
+            return Self::from_idoms(Vec::new(), Vec::new());
+        }
+        let (idom, in_tree) =
+            immediate_dominators(0, &cfg.successor_indices(), &cfg.predecessor_indices());
+        Self::from_idoms(idom, in_tree)
+    }
+
+    /// Post-dominators towards a virtual exit that follows every block in
+    /// `cfg.exits()`. Exit blocks are the roots; blocks that cannot reach an
+    /// exit, such as the body of a `while True` with no `break`, are not in
+    /// the tree.
+    pub(crate) fn post_dominators(cfg: &Cfg) -> Self {
+        let len = cfg.len();
+        let mut reversed_successors = cfg.predecessor_indices();
+        let mut reversed_predecessors = cfg.successor_indices();
+        let exits = cfg.exits().collect::<Vec<_>>();
+        for &exit in &exits {
+            reversed_predecessors[exit].push(len);
+        }
+        reversed_successors.push(exits);
+        reversed_predecessors.push(Vec::new());
+        let (mut idom, mut in_tree) =
+            immediate_dominators(len, &reversed_successors, &reversed_predecessors);
+        idom.truncate(len);
+        in_tree.truncate(len);
+        for parent in &mut idom {
+            if *parent == Some(len) {
+                *parent = None;
+            }
+        }
+        Self::from_idoms(idom, in_tree)
+    }
+
+    fn from_idoms(idom: Vec<Option<usize>>, in_tree: Vec<bool>) -> Self {
+        let mut children = vec![Vec::new(); idom.len()];
+        for (node, parent) in idom.iter().enumerate() {
+            if let Some(parent) = parent {
+                children[*parent].push(node);
+            }
+        }
+        Self {
+            idom,
+            in_tree,
+            children,
+        }
+    }
+
+    /// The closest strict (post-)dominator of `block`, or `None` for a root
+    /// or a block outside the tree.
+    pub(crate) fn idom(&self, block: usize) -> Option<usize> {
+        self.idom[block]
+    }
+
+    pub(crate) fn contains(&self, block: usize) -> bool {
+        self.in_tree[block]
+    }
+
+    pub(crate) fn children(&self, block: usize) -> &[usize] {
+        &self.children[block]
+    }
+
+    /// Whether every path through `block` passes `dominator` first (or, for
+    /// post-dominators, afterwards). A block dominates itself.
+    pub(crate) fn dominates(&self, dominator: usize, block: usize) -> bool {
+        if !self.in_tree[dominator] || !self.in_tree[block] {
+            return false;
+        }
+        let mut current = block;
+        loop {
+            if current == dominator {
+                return true;
+            }
+            match self.idom[current] {
+                Some(parent) => current = parent,
+                None => return false,
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Loop {
+    pub(crate) header: usize,
+    /// Sorted, including the header.
+    pub(crate) blocks: Vec<usize>,
+    /// Index of the innermost enclosing loop in `LoopNest::loops`.
+    pub(crate) parent: Option<usize>,
+    /// 1 for an outermost loop.
+    pub(crate) depth: usize,
+}
+
+/// Natural loops, one per header, merging every back edge into that header.
+/// A back edge is an edge whose target dominates its source; cycles with no
+/// such header (irreducible control flow) are not reported.
+#[derive(Debug, Clone)]
+pub(crate) struct LoopNest {
+    loops: Vec<Loop>,
+    innermost: Vec<Option<usize>>,
+}
+
+impl LoopNest {
+    pub(crate) fn new(cfg: &Cfg, dominators: &DominatorTree) -> Self {
+        let mut latches = HashMap::<usize, Vec<usize>>::new();
+        for block in 0..cfg.len() {
+            for edge in cfg.successors(block) {
+                if dominators.dominates(edge.to, edge.from) {
+                    latches.entry(edge.to).or_default().push(edge.from);
+                }
+            }
+        }
+
+        let mut loops = latches
+            .into_iter()
+            .map(|(header, latches)| {
+                let mut body = HashSet::from([header]);
+                let mut worklist = latches;
+                while let Some(block) = worklist.pop() {
+                    if !body.insert(block) {
+                        continue;
+                    }
+                    worklist.extend(
+                        cfg.predecessors(block)
+                            .iter()
+                            .map(|edge| edge.from)
+                            .filter(|&pred| dominators.contains(pred)),
+                    );
+                }
+                let mut blocks = body.into_iter().collect::<Vec<_>>();
+                blocks.sort_unstable();
+                Loop {
+                    header,
+                    blocks,
+                    parent: None,
+                    depth: 1,
+                }
+            })
+            .collect::<Vec<_>>();
+        // Enclosing loops are strictly larger, so they come first.
+        loops.sort_by_key(|lp| (std::cmp::Reverse(lp.blocks.len()), lp.header));
+
+        let mut innermost = vec![None; cfg.len()];
+        for index in 0..loops.len() {
+            let header = loops[index].header;
+            if let Some(parent) = innermost[header] {
+                loops[index].parent = Some(parent);
+                loops[index].depth = loops[parent].depth + 1;
+            }
+            for &block in &loops[index].blocks {
+                innermost[block] = Some(index);
+            }
+        }
+        Self { loops, innermost }
+    }
+
+    /// Outer loops before the loops they contain.
+    pub(crate) fn loops(&self) -> &[Loop] {
+        &self.loops
+    }
+
+    pub(crate) fn innermost_loop(&self, block: usize) -> Option<&Loop> {
+        self.innermost[block].map(|index| &self.loops[index])
+    }
+
+    /// How many loops contain `block`.
+    pub(crate) fn depth(&self, block: usize) -> usize {
+        self.innermost_loop(block).map_or(0, |lp| lp.depth)
+    }
+
+    pub(crate) fn is_header(&self, block: usize) -> bool {
+        self.innermost_loop(block)
+            .is_some_and(|lp| lp.header == block)
+    }
+}
+
+/// The CFG-shape analyses of one function, computed together.
+#[derive(Debug, Clone)]
+pub(crate) struct CfgAnalysis {
+    pub(crate) cfg: Cfg,
+    pub(crate) dominators: DominatorTree,
+    pub(crate) post_dominators: DominatorTree,
+    pub(crate) loops: LoopNest,
+}
+
+impl CfgAnalysis {
+    pub(crate) fn new<P: BlockPyPass, S>(function: &BlockPyFunction<P, S>) -> Self {
+        Self::from_cfg(Cfg::new(function))
+    }
+
+    pub(crate) fn from_cfg(cfg: Cfg) -> Self {
+        let dominators = DominatorTree::dominators(&cfg);
+        let post_dominators = DominatorTree::post_dominators(&cfg);
+        let loops = LoopNest::new(&cfg, &dominators);
+        Self {
+            cfg,
+            dominators,
+            post_dominators,
+            loops,
+        }
+    }
+
+    /// Whether `from -> to` closes a loop.
+    pub(crate) fn is_back_edge(&self, from: usize, to: usize) -> bool {
+        self.cfg.successors(from).iter().any(|edge| edge.to == to)
+            && self.dominators.dominates(to, from)
+    }
+}
+
+/// How an instruction touches a named variable.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum NameAccess<'a> {
+    Read(&'a str),
+    /// A store or delete; either way the old value is dead.
+    Write(&'a str),
+}
+
+/// Instructions whose own operation reads or writes a variable by name.
+/// Runtime names are not variables.
+pub(crate) trait NameAccessInstr: Instr + ChildVisitable<Self> {
+    fn name_access(&self) -> Option<NameAccess<'_>>;
+}
+
+macro_rules! impl_name_access_instr {
+    ($(impl$([$($generics:tt)*])? for $ty:ty { $($extra:tt)* })*) => {
+        $(
+            impl$(<$($generics)*>)? NameAccessInstr for $ty {
+                fn name_access(&self) -> Option<NameAccess<'_>> {
+                    match self {
+                        $($extra)*
+                        Self::Load(op) if !op.name.is_runtime_name() => {
+                            Some(NameAccess::Read(op.name.id_str()))
+                        }
+                        Self::Store(op) if !op.name.is_runtime_name() => {
+                            Some(NameAccess::Write(op.name.id_str()))
+                        }
+                        Self::Del(op) if !op.name.is_runtime_name() => {
+                            Some(NameAccess::Write(op.name.id_str()))
+                        }
+                        _ => None,
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_name_access_instr! {
+    impl for CoreBlockPyExprWithAwaitAndYield {}
+    impl for CoreBlockPyExprWithYield {}
+    impl[N: BlockPyNameLike] for CoreBlockPyExpr<N> {}
+    impl for CodegenBlockPyExpr {
+        // The global itself is only peeked at through its cache slot; the
+        // variable a preload writes and a guarded load reads is the local
+        // holding the hoisted value.
+        Self::PreloadGlobal(op) => Some(NameAccess::Write(op.hoisted.id_str())),
+        Self::GuardedGlobalLoad(op) => Some(NameAccess::Read(op.hoisted.id_str())),
+    }
+}
+
+/// The names a block reads before writing them, and the names it writes.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct BlockAccesses {
+    pub(crate) upward_exposed: HashSet<String>,
+    pub(crate) written: HashSet<String>,
+}
+
+impl BlockAccesses {
+    /// Block params are written on entry. Names passed along the exception
+    /// edge may be read before anything in the block runs.
+    pub(crate) fn of_block<I: NameAccessInstr>(block: &Block<I, I>) -> Self {
+        let mut collector = AccessCollector {
+            accesses: Self::default(),
+            pending_writes: Vec::new(),
+        };
+        collector
+            .accesses
+            .written
+            .extend(block.params.iter().map(|param| param.name.clone()));
+        if let Some(edge) = &block.exc_edge {
+            for arg in &edge.args {
+                if let BlockArg::Name(name) = arg {
+                    if !collector.accesses.written.contains(name) {
+                        collector.accesses.upward_exposed.insert(name.clone());
+                    }
+                }
+            }
+        }
+        for stmt in &block.body {
+            collector.visit_instr(stmt);
+            collector.flush_writes();
+        }
+        walk_term(&mut collector, &block.term);
+        collector.accesses
+    }
+}
+
+struct AccessCollector {
+    accesses: BlockAccesses,
+    /// A statement's writes land after all of its reads.
+    pending_writes: Vec<String>,
+}
+
+impl AccessCollector {
+    fn read(&mut self, name: &str) {
+        if !self.accesses.written.contains(name) {
+            self.accesses.upward_exposed.insert(name.to_string());
+        }
+    }
+
+    fn flush_writes(&mut self) {
+        self.accesses.written.extend(self.pending_writes.drain(..));
+    }
+}
+
+impl<I: NameAccessInstr> Visit<I> for AccessCollector {
+    fn visit_instr(&mut self, expr: &I) {
+        walk_expr(self, expr);
+        match expr.name_access() {
+            Some(NameAccess::Read(name)) => self.read(name),
+            Some(NameAccess::Write(name)) => self.pending_writes.push(name.to_string()),
+            None => {}
+        }
+    }
+
+    fn visit_block_arg(&mut self, arg: &BlockArg) {
+        if let BlockArg::Name(name) = arg {
+            self.read(name);
+        }
+    }
+}
+
+/// Names live on entry to and exit from each block.
+///
+/// `live_in(b) = upward_exposed(b) ∪ (live_out_normal(b) − written(b)) ∪
+/// live_in(handler)`: the handler's live names are live through the whole
+/// source block, since it can raise before any of its writes.
+#[derive(Debug, Clone)]
+pub(crate) struct Liveness {
+    live_in: Vec<HashSet<String>>,
+    live_out: Vec<HashSet<String>>,
+}
+
+impl Liveness {
+    pub(crate) fn new<P>(function: &BlockPyFunction<P>, cfg: &Cfg) -> Self
+    where
+        P: BlockPyPass,
+        P::Expr: NameAccessInstr,
+    {
+        let accesses = function
+            .blocks
+            .iter()
+            .map(BlockAccesses::of_block)
+            .collect::<Vec<_>>();
+        Self::from_accesses(cfg, &accesses)
+    }
+
+    pub(crate) fn from_accesses(cfg: &Cfg, accesses: &[BlockAccesses]) -> Self {
+        let len = cfg.len();
+        let mut live_in = accesses
+            .iter()
+            .map(|block| block.upward_exposed.clone())
+            .collect::<Vec<_>>();
+        let mut live_out = vec![HashSet::new(); len];
+        // Postorder visits successors first, so most blocks settle in one
+        // round; unreachable blocks are appended so they are solved too.
+        let mut order = cfg.reverse_postorder();
+        order.reverse();
+        let reachable = cfg.reachable();
+        order.extend((0..len).filter(|&block| !reachable[block]));
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for &block in &order {
+                let mut normal_out = HashSet::new();
+                let mut handler_in = HashSet::new();
+                for edge in cfg.successors(block) {
+                    match edge.kind {
+                        EdgeKind::Normal => normal_out.extend(live_in[edge.to].iter().cloned()),
+                        EdgeKind::Exception => handler_in.extend(live_in[edge.to].iter().cloned()),
+                    }
+                }
+                let mut new_in = accesses[block].upward_exposed.clone();
+                new_in.extend(
+                    normal_out
+                        .iter()
+                        .filter(|name| !accesses[block].written.contains(*name))
+                        .cloned(),
+                );
+                new_in.extend(handler_in.iter().cloned());
+                normal_out.extend(handler_in);
+                live_out[block] = normal_out;
+                if new_in != live_in[block] {
+                    live_in[block] = new_in;
+                    changed = true;
+                }
+            }
+        }
+        Self { live_in, live_out }
+    }
+
+    pub(crate) fn live_in(&self, block: usize) -> &HashSet<String> {
+        &self.live_in[block]
+    }
+
+    /// Names live on any edge out of `block`, normal or exceptional.
+    pub(crate) fn live_out(&self, block: usize) -> &HashSet<String> {
+        &self.live_out[block]
+    }
+}
+
+#[cfg(test)]
+mod test;
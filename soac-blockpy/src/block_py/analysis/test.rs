@@ -0,0 +1,328 @@
+use super::*;
+use crate::lower_python_to_blockpy_for_testing;
+use crate::passes::ResolvedStorageBlockPyPass;
+
+/// Deterministic xorshift64, so failures reproduce from the printed seed.
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, bound: usize) -> usize {
+        (self.next() % bound as u64) as usize
+    }
+
+    fn chance(&mut self, numerator: u64, denominator: u64) -> bool {
+        self.next() % denominator < numerator
+    }
+}
+
+const NAMES: [&str; 3] = ["a", "b", "c"];
+
+struct Generated {
+    seed: u64,
+    successors: Vec<Vec<(usize, EdgeKind)>>,
+    accesses: Vec<BlockAccesses>,
+}
+
+impl Generated {
+    fn new(seed: u64) -> Self {
+        let mut rng = Rng(seed);
+        let len = 1 + rng.below(12);
+        let mut successors = Vec::with_capacity(len);
+        let mut accesses = Vec::with_capacity(len);
+        for _ in 0..len {
+            let mut targets = Vec::new();
+            // Targets are uniform, so self-loops, back edges and
+            // irreducible cycles all show up.
+            for _ in 0..rng.below(3) {
+                targets.push((rng.below(len), EdgeKind::Normal));
+            }
+            if rng.chance(1, 4) {
+                targets.push((rng.below(len), EdgeKind::Exception));
+            }
+            successors.push(targets);
+
+            let mut block = BlockAccesses::default();
+            for name in NAMES {
+                if rng.chance(1, 3) {
+                    block.upward_exposed.insert(name.to_string());
+                }
+                if rng.chance(1, 3) {
+                    block.written.insert(name.to_string());
+                }
+            }
+            accesses.push(block);
+        }
+        Self {
+            seed,
+            successors,
+            accesses,
+        }
+    }
+
+    fn cfg(&self) -> Cfg {
+        Cfg::from_successors(self.successors.clone())
+    }
+}
+
+fn seeds() -> impl Iterator<Item = u64> {
+    (1..=200u64).map(|case| case.wrapping_mul(0x9e37_79b9_7f4a_7c15))
+}
+
+/// Blocks reachable from `from` along `cfg` edges without entering `avoid`.
+fn reachable_avoiding(cfg: &Cfg, from: usize, avoid: Option<usize>) -> Vec<bool> {
+    let mut seen = vec![false; cfg.len()];
+    if Some(from) == avoid {
+        return seen;
+    }
+    let mut stack = vec![from];
+    seen[from] = true;
+    while let Some(block) = stack.pop() {
+        for edge in cfg.successors(block) {
+            if Some(edge.to) != avoid && !seen[edge.to] {
+                seen[edge.to] = true;
+                stack.push(edge.to);
+            }
+        }
+    }
+    seen
+}
+
+fn reaches_exit_avoiding(cfg: &Cfg, from: usize, avoid: Option<usize>) -> bool {
+    let seen = reachable_avoiding(cfg, from, avoid);
+    cfg.exits().any(|exit| seen[exit])
+}
+
+#[test]
+fn dominators_match_path_definition() {
+    for seed in seeds() {
+        let cfg = Generated::new(seed).cfg();
+        let dominators = DominatorTree::dominators(&cfg);
+        let reachable = reachable_avoiding(&cfg, 0, None);
+        for dominator in 0..cfg.len() {
+            let without = reachable_avoiding(&cfg, 0, Some(dominator));
+            for block in 0..cfg.len() {
+                let expected = reachable[block] && (dominator == block || !without[block]);
+                assert_eq!(
+                    dominators.dominates(dominator, block),
+                    expected,
+                    "seed {seed}: {dominator} dom {block}"
+                );
+            }
+        }
+        for block in 0..cfg.len() {
+            assert_eq!(dominators.contains(block), reachable[block], "seed {seed}");
+            if let Some(idom) = dominators.idom(block) {
+                assert!(dominators.children(idom).contains(&block), "seed {seed}");
+            }
+        }
+    }
+}
+
+#[test]
+fn post_dominators_match_path_definition() {
+    for seed in seeds() {
+        let cfg = Generated::new(seed).cfg();
+        let post_dominators = DominatorTree::post_dominators(&cfg);
+        for block in 0..cfg.len() {
+            let reaches_exit = reaches_exit_avoiding(&cfg, block, None);
+            assert_eq!(post_dominators.contains(block), reaches_exit, "seed {seed}");
+            for dominator in 0..cfg.len() {
+                let expected = reaches_exit
+                    && (dominator == block || !reaches_exit_avoiding(&cfg, block, Some(dominator)));
+                assert_eq!(
+                    post_dominators.dominates(dominator, block),
+                    expected,
+                    "seed {seed}: {dominator} post-dom {block}"
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn loops_match_natural_loop_definition() {
+    for seed in seeds() {
+        let cfg = Generated::new(seed).cfg();
+        let analysis = CfgAnalysis::from_cfg(cfg);
+        let cfg = &analysis.cfg;
+        let reachable = reachable_avoiding(cfg, 0, None);
+
+        let mut expected = Vec::new();
+        for header in 0..cfg.len() {
+            let latches = cfg
+                .predecessors(header)
+                .iter()
+                .map(|edge| edge.from)
+                .filter(|&from| analysis.is_back_edge(from, header))
+                .collect::<Vec<_>>();
+            if latches.is_empty() {
+                continue;
+            }
+            let blocks = (0..cfg.len())
+                .filter(|&block| {
+                    reachable[block]
+                        && (block == header
+                            || latches
+                                .iter()
+                                .any(|&latch| reachable_avoiding(cfg, block, Some(header))[latch]))
+                })
+                .collect::<Vec<_>>();
+            expected.push((header, blocks));
+        }
+        let mut actual = analysis
+            .loops
+            .loops()
+            .iter()
+            .map(|lp| (lp.header, lp.blocks.clone()))
+            .collect::<Vec<_>>();
+        actual.sort();
+        assert_eq!(actual, expected, "seed {seed}");
+
+        for block in 0..cfg.len() {
+            let containing = expected
+                .iter()
+                .filter(|(_, blocks)| blocks.contains(&block))
+                .count();
+            assert_eq!(analysis.loops.depth(block), containing, "seed {seed}");
+        }
+        for lp in analysis.loops.loops() {
+            if let Some(parent) = lp.parent {
+                let parent = &analysis.loops.loops()[parent];
+                assert!(
+                    lp.blocks.iter().all(|block| parent.blocks.contains(block)),
+                    "seed {seed}"
+                );
+                assert_eq!(lp.depth, parent.depth + 1, "seed {seed}");
+            }
+        }
+    }
+}
+
+#[test]
+fn liveness_matches_path_definition() {
+    for seed in seeds() {
+        let generated = Generated::new(seed);
+        let cfg = generated.cfg();
+        let liveness = Liveness::from_accesses(&cfg, &generated.accesses);
+        for name in NAMES {
+            // A name is live into a block when some path reaches a use of it
+            // and every normal edge on the way leaves a block that does not
+            // write it. Exception edges leave before the block's writes.
+            let mut live = generated
+                .accesses
+                .iter()
+                .map(|block| block.upward_exposed.contains(name))
+                .collect::<Vec<_>>();
+            let mut worklist = (0..cfg.len()).filter(|&b| live[b]).collect::<Vec<_>>();
+            while let Some(block) = worklist.pop() {
+                for edge in cfg.predecessors(block) {
+                    let passes = edge.kind == EdgeKind::Exception
+                        || !generated.accesses[edge.from].written.contains(name);
+                    if passes && !live[edge.from] {
+                        live[edge.from] = true;
+                        worklist.push(edge.from);
+                    }
+                }
+            }
+            for block in 0..cfg.len() {
+                assert_eq!(
+                    liveness.live_in(block).contains(name),
+                    live[block],
+                    "seed {}: {name} live into {block}",
+                    generated.seed
+                );
+                let live_out = cfg.successors(block).iter().any(|edge| live[edge.to]);
+                assert_eq!(
+                    liveness.live_out(block).contains(name),
+                    live_out,
+                    "seed {}: {name} live out of {block}",
+                    generated.seed
+                );
+            }
+        }
+    }
+}
+
+fn with_lowered_function(
+    source: &str,
+    qualname: &str,
+    check: impl FnOnce(&BlockPyFunction<ResolvedStorageBlockPyPass>),
+) {
+    let lowered = lower_python_to_blockpy_for_testing(source).expect("transform should succeed");
+    let module = lowered
+        .pass_tracker
+        .pass_name_binding()
+        .expect("name_binding pass should be tracked");
+    let function = module
+        .callable_defs
+        .iter()
+        .find(|function| function.names.qualname == qualname)
+        .expect("function should be lowered");
+    check(function)
+}
+
+#[test]
+fn while_loop_header_keeps_loop_carried_names_live() {
+    let source = "\
+def f(n):
+    total = 0
+    while n:
+        total = total + n
+        n = n - 1
+    return total
+";
+    with_lowered_function(source, "f", |function| {
+        let analysis = CfgAnalysis::new(function);
+        let liveness = Liveness::new(function, &analysis.cfg);
+        let headers = analysis
+            .loops
+            .loops()
+            .iter()
+            .map(|lp| lp.header)
+            .collect::<Vec<_>>();
+        assert_eq!(headers.len(), 1, "{headers:?}");
+        let live = liveness.live_in(headers[0]);
+        assert!(live.contains("n") && live.contains("total"), "{live:?}");
+        assert!(!liveness.live_in(0).contains("total"));
+        assert!(analysis.post_dominators.dominates(headers[0], 0));
+    });
+}
+
+#[test]
+fn exception_edges_keep_handler_names_live_through_the_try_body() {
+    let source = "\
+def f(g):
+    x = 1
+    try:
+        x = g()
+    except Exception:
+        return x
+    return 0
+";
+    with_lowered_function(source, "f", |function| {
+        let analysis = CfgAnalysis::new(function);
+        let liveness = Liveness::new(function, &analysis.cfg);
+        let protected = (0..analysis.cfg.len())
+            .filter(|&block| {
+                analysis
+                    .cfg
+                    .successors(block)
+                    .iter()
+                    .any(|edge| edge.kind == EdgeKind::Exception)
+            })
+            .collect::<Vec<_>>();
+        assert!(
+            protected
+                .iter()
+                .any(|&block| liveness.live_in(block).contains("x")),
+            "{protected:?}"
+        );
+    });
+}
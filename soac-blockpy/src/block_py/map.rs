@@ -38,7 +38,7 @@ where
     }
 }
 
-pub(crate) trait MapTerm<In, Out>: MapInstr<In, Out>
+pub trait MapTerm<In, Out>: MapInstr<In, Out>
 where
     In: Instr,
     Out: Instr,
@@ -75,7 +75,7 @@ where
 {
 }
 
-pub(crate) trait MapBlock<In, Out>: MapTerm<In, Out>
+pub trait MapBlock<In, Out>: MapTerm<In, Out>
 where
     In: Instr,
     Out: Instr,
@@ -103,7 +103,7 @@ where
 {
 }
 
-pub(crate) trait MapFunction<PIn, POut>: MapBlock<PIn::Expr, POut::Expr>
+pub trait MapFunction<PIn, POut>: MapBlock<PIn::Expr, POut::Expr>
 where
     PIn: BlockPyPass,
     POut: BlockPyPass,
@@ -135,7 +135,7 @@ where
 {
 }
 
-pub(crate) trait MapModule<PIn, POut>: MapFunction<PIn, POut>
+pub trait MapModule<PIn, POut>: MapFunction<PIn, POut>
 where
     PIn: BlockPyPass,
     POut: BlockPyPass,
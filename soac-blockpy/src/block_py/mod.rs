@@ -12,15 +12,18 @@ pub use self::scope::{
 };
 use crate::py_expr;
 pub use operation::{
-    Await, BinOp, BinOpKind, CalleeFunctionId, Call, CallDirect, CellRef, CellRefForName, Del,
-    DelItem, GetAttr, GetItem, Load, MakeCell, MakeFunction, SetAttr, SetItem, Store, UnaryOp,
-    UnaryOpKind, Yield, YieldFrom,
+    Await, BinOp, BinOpKind, BuildDict, BuildDictItem, BuildElement, BuildList, BuildSet,
+    BuildString, BuildTuple, Call, CallDirect, CalleeFunctionId, CellRef, CellRefForName,
+    CreateClass, Del, DelItem, ForIter, FormatConversion, FormatValue, GetAttr, GetItem, GetIter,
+    ImportFrom, ImportName, InlineGuard, Load, MakeCell, MakeFunction, SetAttr, SetItem, Store,
+    UnaryOp, UnaryOpKind, Unpack, Yield, YieldFrom,
 };
 pub use ruff_python_ast::Expr;
 use ruff_python_ast::{self as ast};
 use soac_macros::enum_broadcast;
 use std::fmt;
 
+pub(crate) mod analysis;
 pub(crate) mod cfg;
 mod map;
 mod meta;
@@ -30,26 +33,24 @@ mod operation_macro;
 pub(crate) mod param_specs;
 pub mod pretty;
 pub(crate) mod scope;
+pub(crate) mod stats;
 pub(crate) mod validate;
 mod visit;
 pub use crate::passes::{
     CoreBlockPyExpr, CoreBlockPyExprWithAwaitAndYield, CoreBlockPyExprWithYield,
     LocatedCoreBlockPyExpr,
 };
+pub use map::{MapBlock, MapFunction, MapInstr, MapModule, MapTerm, TryMapInstr};
 #[allow(unused_imports)]
-pub(crate) use map::{
-    MapBlock, MapFunction, MapModule, MapTerm, TryMapBlock, TryMapFunction, TryMapModule,
-    TryMapTerm,
-};
-pub use map::{MapInstr, TryMapInstr};
+pub(crate) use map::{TryMapBlock, TryMapFunction, TryMapModule, TryMapTerm};
 pub use name_gen::{BlockLabel, FunctionId, FunctionNameGen, ModuleNameGen};
+pub use stats::{IrStats, OperationKind};
 pub(crate) use validate::validate_module;
-#[allow(unused_imports)]
-pub(crate) use visit::{
-    instr_any, walk_block, walk_block_mut, walk_expr, walk_expr_mut, walk_fn, walk_fn_mut,
-    walk_module, walk_module_mut, walk_stmt, walk_stmt_mut, walk_term, walk_term_mut,
+pub(crate) use visit::instr_any;
+pub use visit::{
+    walk_block, walk_block_mut, walk_expr, walk_expr_mut, walk_fn, walk_fn_mut, walk_module,
+    walk_module_mut, walk_stmt, walk_stmt_mut, walk_term, walk_term_mut, Visit, VisitMut,
 };
-pub use visit::{Visit, VisitMut};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct CounterId(pub usize);
@@ -281,7 +282,6 @@ where
     {
         self.try_map_children(map)
     }
-
 }
 
 pub trait Instr: Clone + fmt::Debug + Sized {
@@ -352,7 +352,6 @@ impl Mappable<Expr> for Expr {
     {
         map.try_map_instr(self)
     }
-
 }
 
 impl Instr for Expr {
@@ -465,7 +464,6 @@ impl Mappable<RuffExpr> for RuffExpr {
     {
         map.try_map_instr(self)
     }
-
 }
 
 #[derive(Clone, PartialEq, Eq, Hash)]
@@ -674,23 +672,38 @@ impl<P: BlockPyPass, S> BlockPyModule<P, S> {
 }
 
 #[derive(Clone, derive_more::From)]
-#[enum_broadcast(HasMeta, WithMeta, ChildVisitable, Mappable, Debug)]
+#[enum_broadcast(HasMeta, WithMeta, ChildVisitable, Mappable, Debug, OperationKind)]
 pub enum CodegenBlockPyExpr {
     BinOp(BinOp<Self>),
     UnaryOp(UnaryOp<Self>),
     CalleeFunctionId(CalleeFunctionId<Self>),
+    InlineGuard(InlineGuard<Self>),
     Call(Call<Self>),
     CallDirect(CallDirect<Self>),
+    BuildTuple(BuildTuple<Self>),
+    BuildList(BuildList<Self>),
+    BuildSet(BuildSet<Self>),
+    BuildDict(BuildDict<Self>),
     GetAttr(GetAttr<Self>),
     SetAttr(SetAttr<Self>),
     GetItem(GetItem<Self>),
     SetItem(SetItem<Self>),
     DelItem(DelItem<Self>),
+    GetIter(GetIter<Self>),
+    ForIter(ForIter<Self>),
+    Unpack(Unpack<Self>),
+    ImportName(ImportName<Self>),
+    ImportFrom(ImportFrom<Self>),
+    CreateClass(CreateClass<Self>),
+    FormatValue(FormatValue<Self>),
+    BuildString(BuildString<Self>),
     Load(Load<Self>),
     Store(Store<Self>),
     Del(Del<Self>),
     MakeCell(MakeCell<Self>),
     IncrementCounter(IncrementCounter),
+    PreloadGlobal(PreloadGlobal),
+    GuardedGlobalLoad(GuardedGlobalLoad),
     CellRef(CellRef),
     MakeFunction(MakeFunction<Self>),
 }
@@ -701,6 +714,26 @@ define_operation! {
     }
 }
 
+// Binds `hoisted` to a global's cached value (the deleted sentinel when the
+// module cache slot is empty) and records the cache version alongside it.
+// Never raises and never resolves the global.
+define_operation! {
+    pub struct PreloadGlobal {
+        name: LocatedName,
+        hoisted: LocatedName,
+    }
+}
+
+// Loads `name`, reusing `hoisted` while the module cache version recorded by
+// `PreloadGlobal` is unchanged. Otherwise does the ordinary global load and
+// rebinds `hoisted` to its result.
+define_operation! {
+    pub struct GuardedGlobalLoad {
+        name: LocatedName,
+        hoisted: LocatedName,
+    }
+}
+
 #[derive(Clone, derive_more::From)]
 pub enum BlockPyLiteral {
     StringLiteral(CoreStringLiteral),
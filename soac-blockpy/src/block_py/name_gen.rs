@@ -138,17 +138,27 @@ impl FunctionNameGen {
     }
 }
 
+#[derive(Debug)]
+struct ModuleNameGenState {
+    next_function_id: AtomicU32,
+    next_tmp_id: AtomicUsize,
+}
+
+/// Clones share counters, so generated names depend only on the lowered source.
 #[derive(Debug)]
 pub struct ModuleNameGen {
     module_id: u32,
-    state: Arc<AtomicU32>,
+    state: Arc<ModuleNameGenState>,
 }
 
 impl ModuleNameGen {
     pub fn new(module_id: u32) -> Self {
         Self {
             module_id,
-            state: Arc::new(AtomicU32::new(0)),
+            state: Arc::new(ModuleNameGenState {
+                next_function_id: AtomicU32::new(0),
+                next_tmp_id: AtomicUsize::new(0),
+            }),
         }
     }
 
@@ -157,10 +167,17 @@ impl ModuleNameGen {
     }
 
     pub fn next_function_name_gen(&self) -> FunctionNameGen {
-        let function_id =
-            FunctionId::new(self.module_id, self.state.fetch_add(1, Ordering::Relaxed));
+        let function_id = FunctionId::new(
+            self.module_id,
+            self.state.next_function_id.fetch_add(1, Ordering::Relaxed),
+        );
         FunctionNameGen::new(function_id)
     }
+
+    pub fn next_tmp_name(&self, prefix: &str) -> String {
+        let current = self.state.next_tmp_id.fetch_add(1, Ordering::Relaxed) + 1;
+        format!("_dp_{prefix}_{current}")
+    }
 }
 
 impl Clone for ModuleNameGen {
@@ -52,6 +52,15 @@ pub enum UnaryOpKind {
     Truth,
 }
 
+/// The `!s` / `!r` / `!a` conversion of an f-string interpolation.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum FormatConversion {
+    None,
+    Str,
+    Repr,
+    Ascii,
+}
+
 define_operation! {
     pub struct BinOp<E> {
         kind: BinOpKind,
@@ -208,9 +217,24 @@ impl<E: Instr> Mappable<E> for Call<E> {
     }
 }
 
+// Evaluates to the position of the callee's `FunctionId` in `candidates`, or
+// `candidates.len()` when it matches none of them, so it can index a
+// `BranchTable` whose targets line up with `candidates`.
 define_operation! {
     pub struct CalleeFunctionId<E> {
         value: Box<E>,
+        candidates: Vec<FunctionId>,
+    }
+}
+
+// Evaluates to 0 when the callee is exactly a plain function object for
+// `function_id` that reads the caller's module globals and has no closure,
+// so a copy of its body can run in place of the call, and to 1 otherwise.
+// Bound methods and other callables wrapping the function do not match.
+define_operation! {
+    pub struct InlineGuard<E> {
+        value: Box<E>,
+        function_id: FunctionId,
     }
 }
 
@@ -353,6 +377,318 @@ impl<E: Instr> Mappable<E> for CallDirect<E> {
     }
 }
 
+/// One element of a tuple, list or set display.
+#[derive(Debug, Clone)]
+pub enum BuildElement<E> {
+    Value(E),
+    /// `*iterable`, spliced in place.
+    Unpack(E),
+}
+
+impl<E> BuildElement<E> {
+    pub fn expr(&self) -> &E {
+        match self {
+            Self::Value(expr) | Self::Unpack(expr) => expr,
+        }
+    }
+
+    pub fn expr_mut(&mut self) -> &mut E {
+        match self {
+            Self::Value(expr) | Self::Unpack(expr) => expr,
+        }
+    }
+
+    pub fn map_instr<T>(self, f: impl FnOnce(E) -> T) -> BuildElement<T> {
+        match self {
+            Self::Value(expr) => BuildElement::Value(f(expr)),
+            Self::Unpack(expr) => BuildElement::Unpack(f(expr)),
+        }
+    }
+
+    pub fn try_map_instr<T, Error>(
+        self,
+        f: impl FnOnce(E) -> Result<T, Error>,
+    ) -> Result<BuildElement<T>, Error> {
+        match self {
+            Self::Value(expr) => f(expr).map(BuildElement::Value),
+            Self::Unpack(expr) => f(expr).map(BuildElement::Unpack),
+        }
+    }
+}
+
+impl<E: fmt::Debug> BuildElement<E> {
+    fn fmt_item(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Value(expr) => write!(f, "{expr:?}"),
+            Self::Unpack(expr) => write!(f, "*{expr:?}"),
+        }
+    }
+}
+
+/// One item of a dict display. Keys are evaluated before their values, and a
+/// later key replaces an equal earlier one in its original position.
+#[derive(Debug, Clone)]
+pub enum BuildDictItem<E> {
+    Pair {
+        key: E,
+        value: E,
+    },
+    /// `**mapping`, merged in place.
+    Unpack(E),
+}
+
+impl<E> BuildDictItem<E> {
+    pub fn map_instr<T>(self, mut f: impl FnMut(E) -> T) -> BuildDictItem<T> {
+        match self {
+            Self::Pair { key, value } => {
+                let key = f(key);
+                BuildDictItem::Pair {
+                    key,
+                    value: f(value),
+                }
+            }
+            Self::Unpack(expr) => BuildDictItem::Unpack(f(expr)),
+        }
+    }
+
+    pub fn try_map_instr<T, Error>(
+        self,
+        mut f: impl FnMut(E) -> Result<T, Error>,
+    ) -> Result<BuildDictItem<T>, Error> {
+        match self {
+            Self::Pair { key, value } => {
+                let key = f(key)?;
+                Ok(BuildDictItem::Pair {
+                    key,
+                    value: f(value)?,
+                })
+            }
+            Self::Unpack(expr) => f(expr).map(BuildDictItem::Unpack),
+        }
+    }
+}
+
+// `define_operation!` only knows about boxed children, so the display
+// builders spell out the same impls by hand, like `Call`.
+macro_rules! define_build_sequence {
+    ($name:ident, $open:literal, $close:literal) => {
+        #[derive(Clone)]
+        pub struct $name<E> {
+            _meta: Meta,
+            pub elts: Vec<BuildElement<E>>,
+        }
+
+        impl<E: fmt::Debug> fmt::Debug for $name<E> {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "{}{}", stringify!($name), $open)?;
+                for (index, elt) in self.elts.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, ", ")?;
+                    }
+                    elt.fmt_item(f)?;
+                }
+                write!(f, "{}", $close)
+            }
+        }
+
+        impl<E> $name<E> {
+            pub fn new(elts: impl Into<Vec<BuildElement<E>>>) -> Self {
+                Self {
+                    _meta: Meta::default(),
+                    elts: elts.into(),
+                }
+            }
+        }
+
+        impl<E> HasMeta for $name<E> {
+            fn meta(&self) -> Meta {
+                self._meta.clone()
+            }
+        }
+
+        impl<E> WithMeta for $name<E> {
+            fn with_meta(mut self, meta: Meta) -> Self {
+                self._meta = meta;
+                self
+            }
+        }
+
+        impl<E> ChildVisitable<E> for $name<E>
+        where
+            E: Instr + ChildVisitable<E>,
+        {
+            fn visit_children_mut<V>(&mut self, visitor: &mut V)
+            where
+                V: crate::block_py::VisitMut<E> + ?Sized,
+            {
+                for elt in &mut self.elts {
+                    visitor.visit_instr_mut(elt.expr_mut());
+                }
+            }
+
+            fn visit_children<V>(&self, visitor: &mut V)
+            where
+                V: crate::block_py::Visit<E> + ?Sized,
+            {
+                for elt in &self.elts {
+                    visitor.visit_instr(elt.expr());
+                }
+            }
+        }
+
+        impl<E: Instr> Mappable<E> for $name<E> {
+            type Mapped<T: Instr> = $name<T>;
+
+            fn map_children<T, M>(self, map: &mut M) -> Self::Mapped<T>
+            where
+                T: Instr,
+                M: MapInstr<E, T>,
+            {
+                $name {
+                    _meta: self._meta,
+                    elts: self
+                        .elts
+                        .into_iter()
+                        .map(|elt| elt.map_instr(|expr| map.map_instr(expr)))
+                        .collect(),
+                }
+            }
+
+            fn try_map_children<T, Error, M>(self, map: &mut M) -> Result<Self::Mapped<T>, Error>
+            where
+                T: Instr,
+                M: TryMapInstr<E, T, Error>,
+            {
+                Ok($name {
+                    _meta: self._meta,
+                    elts: self
+                        .elts
+                        .into_iter()
+                        .map(|elt| elt.try_map_instr(|expr| map.try_map_instr(expr)))
+                        .collect::<Result<Vec<_>, _>>()?,
+                })
+            }
+        }
+    };
+}
+
+define_build_sequence!(BuildTuple, "(", ")");
+define_build_sequence!(BuildList, "[", "]");
+define_build_sequence!(BuildSet, "{", "}");
+
+#[derive(Clone)]
+pub struct BuildDict<E> {
+    _meta: Meta,
+    pub items: Vec<BuildDictItem<E>>,
+}
+
+impl<E: fmt::Debug> fmt::Debug for BuildDict<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "BuildDict{{")?;
+        for (index, item) in self.items.iter().enumerate() {
+            if index > 0 {
+                write!(f, ", ")?;
+            }
+            match item {
+                BuildDictItem::Pair { key, value } => write!(f, "{key:?}: {value:?}")?,
+                BuildDictItem::Unpack(mapping) => write!(f, "**{mapping:?}")?,
+            }
+        }
+        write!(f, "}}")
+    }
+}
+
+impl<E> BuildDict<E> {
+    pub fn new(items: impl Into<Vec<BuildDictItem<E>>>) -> Self {
+        Self {
+            _meta: Meta::default(),
+            items: items.into(),
+        }
+    }
+}
+
+impl<E> HasMeta for BuildDict<E> {
+    fn meta(&self) -> Meta {
+        self._meta.clone()
+    }
+}
+
+impl<E> WithMeta for BuildDict<E> {
+    fn with_meta(mut self, meta: Meta) -> Self {
+        self._meta = meta;
+        self
+    }
+}
+
+impl<E> ChildVisitable<E> for BuildDict<E>
+where
+    E: Instr + ChildVisitable<E>,
+{
+    fn visit_children_mut<V>(&mut self, visitor: &mut V)
+    where
+        V: crate::block_py::VisitMut<E> + ?Sized,
+    {
+        for item in &mut self.items {
+            match item {
+                BuildDictItem::Pair { key, value } => {
+                    visitor.visit_instr_mut(key);
+                    visitor.visit_instr_mut(value);
+                }
+                BuildDictItem::Unpack(mapping) => visitor.visit_instr_mut(mapping),
+            }
+        }
+    }
+
+    fn visit_children<V>(&self, visitor: &mut V)
+    where
+        V: crate::block_py::Visit<E> + ?Sized,
+    {
+        for item in &self.items {
+            match item {
+                BuildDictItem::Pair { key, value } => {
+                    visitor.visit_instr(key);
+                    visitor.visit_instr(value);
+                }
+                BuildDictItem::Unpack(mapping) => visitor.visit_instr(mapping),
+            }
+        }
+    }
+}
+
+impl<E: Instr> Mappable<E> for BuildDict<E> {
+    type Mapped<T: Instr> = BuildDict<T>;
+
+    fn map_children<T, M>(self, map: &mut M) -> Self::Mapped<T>
+    where
+        T: Instr,
+        M: MapInstr<E, T>,
+    {
+        BuildDict {
+            _meta: self._meta,
+            items: self
+                .items
+                .into_iter()
+                .map(|item| item.map_instr(|expr| map.map_instr(expr)))
+                .collect(),
+        }
+    }
+
+    fn try_map_children<T, Error, M>(self, map: &mut M) -> Result<Self::Mapped<T>, Error>
+    where
+        T: Instr,
+        M: TryMapInstr<E, T, Error>,
+    {
+        Ok(BuildDict {
+            _meta: self._meta,
+            items: self
+                .items
+                .into_iter()
+                .map(|item| item.try_map_instr(|expr| map.try_map_instr(expr)))
+                .collect::<Result<Vec<_>, _>>()?,
+        })
+    }
+}
+
 define_operation! {
     pub struct GetAttr<E> {
         value: Box<E>,
@@ -390,6 +726,279 @@ define_operation! {
     }
 }
 
+// `iter(value)` for a `for` loop. Kept separate from a plain helper call so
+// the JIT can hand back a specialized iterator for the common container types.
+// Only `for` loop lowering emits it, as `__soac__.loop_iter`: other `iter`
+// calls, like the ones behind `yield from` and generator expressions, hand
+// their iterator to Python code and stay plain calls.
+define_operation! {
+    pub struct GetIter<E> {
+        value: Box<E>,
+    }
+}
+
+// One step of a `for` loop: the next item, or `ITER_COMPLETE` once the
+// iterator is exhausted.
+define_operation! {
+    pub struct ForIter<E> {
+        iter: Box<E>,
+    }
+}
+
+// Sequence assignment `a, *b, c = value`: evaluates to a tuple of `count`
+// items. When `starred` is set, the item at that position is a new list
+// holding everything between the leading and trailing targets.
+define_operation! {
+    pub struct Unpack<E> {
+        value: Box<E>,
+        count: usize,
+        starred: Option<usize>,
+    }
+}
+
+// `import module` / `from module import ...`: the module `__import__` hands
+// back for `module`, `fromlist` and `level`, resolved against `spec`.
+define_operation! {
+    pub struct ImportName<E> {
+        module: String,
+        fromlist: Vec<String>,
+        level: u32,
+        spec: Box<E>,
+    }
+}
+
+// `attr` read off an imported module for `from module import attr`, falling
+// back to `sys.modules` for submodules still being initialized.
+define_operation! {
+    pub struct ImportFrom<E> {
+        module: Box<E>,
+        attr: String,
+    }
+}
+
+// `class` statement: runs the `__build_class__` protocol for `bases` and the
+// `kwds` dict, filling the prepared namespace with `namespace_fn(ns, cell)`.
+// `firstlineno` and `static_attributes` seed the dunders of the same name
+// when the body did not set them.
+define_operation! {
+    pub struct CreateClass<E> {
+        name: String,
+        namespace_fn: Box<E>,
+        bases: Box<E>,
+        kwds: Box<E>,
+        requires_class_cell: bool,
+        firstlineno: u32,
+        static_attributes: Box<E>,
+    }
+}
+
+// One f-string interpolation: `format(conversion(value), format_spec)`, with
+// no spec meaning the empty one.
+#[derive(Clone)]
+pub struct FormatValue<E> {
+    _meta: Meta,
+    pub value: Box<E>,
+    pub conversion: FormatConversion,
+    pub format_spec: Option<Box<E>>,
+}
+
+impl<E: fmt::Debug> fmt::Debug for FormatValue<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut debug = f.debug_tuple("FormatValue");
+        debug.field(&self.value).field(&self.conversion);
+        if let Some(format_spec) = &self.format_spec {
+            debug.field(format_spec);
+        }
+        debug.finish()
+    }
+}
+
+impl<E> FormatValue<E> {
+    pub fn new(
+        value: impl Into<Box<E>>,
+        conversion: FormatConversion,
+        format_spec: Option<Box<E>>,
+    ) -> Self {
+        Self {
+            _meta: Meta::default(),
+            value: value.into(),
+            conversion,
+            format_spec,
+        }
+    }
+}
+
+impl<E> HasMeta for FormatValue<E> {
+    fn meta(&self) -> Meta {
+        self._meta.clone()
+    }
+}
+
+impl<E> WithMeta for FormatValue<E> {
+    fn with_meta(mut self, meta: Meta) -> Self {
+        self._meta = meta;
+        self
+    }
+}
+
+impl<E> ChildVisitable<E> for FormatValue<E>
+where
+    E: Instr + ChildVisitable<E>,
+{
+    fn visit_children_mut<V>(&mut self, visitor: &mut V)
+    where
+        V: crate::block_py::VisitMut<E> + ?Sized,
+    {
+        visitor.visit_instr_mut(&mut self.value);
+        if let Some(format_spec) = &mut self.format_spec {
+            visitor.visit_instr_mut(format_spec);
+        }
+    }
+
+    fn visit_children<V>(&self, visitor: &mut V)
+    where
+        V: crate::block_py::Visit<E> + ?Sized,
+    {
+        visitor.visit_instr(&self.value);
+        if let Some(format_spec) = &self.format_spec {
+            visitor.visit_instr(format_spec);
+        }
+    }
+}
+
+impl<E: Instr> Mappable<E> for FormatValue<E> {
+    type Mapped<T: Instr> = FormatValue<T>;
+
+    fn map_children<T, M>(self, map: &mut M) -> Self::Mapped<T>
+    where
+        T: Instr,
+        M: MapInstr<E, T>,
+    {
+        let value = Box::new(map.map_instr(*self.value));
+        FormatValue {
+            _meta: self._meta,
+            value,
+            conversion: self.conversion,
+            format_spec: self
+                .format_spec
+                .map(|format_spec| Box::new(map.map_instr(*format_spec))),
+        }
+    }
+
+    fn try_map_children<T, Error, M>(self, map: &mut M) -> Result<Self::Mapped<T>, Error>
+    where
+        T: Instr,
+        M: TryMapInstr<E, T, Error>,
+    {
+        let value = Box::new(map.try_map_instr(*self.value)?);
+        Ok(FormatValue {
+            _meta: self._meta,
+            value,
+            conversion: self.conversion,
+            format_spec: self
+                .format_spec
+                .map(|format_spec| map.try_map_instr(*format_spec).map(Box::new))
+                .transpose()?,
+        })
+    }
+}
+
+// Concatenation of the `str` parts of an f-string, in order.
+#[derive(Clone)]
+pub struct BuildString<E> {
+    _meta: Meta,
+    pub parts: Vec<E>,
+}
+
+impl<E: fmt::Debug> fmt::Debug for BuildString<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut debug = f.debug_tuple("BuildString");
+        for part in &self.parts {
+            debug.field(part);
+        }
+        debug.finish()
+    }
+}
+
+impl<E> BuildString<E> {
+    pub fn new(parts: impl Into<Vec<E>>) -> Self {
+        Self {
+            _meta: Meta::default(),
+            parts: parts.into(),
+        }
+    }
+}
+
+impl<E> HasMeta for BuildString<E> {
+    fn meta(&self) -> Meta {
+        self._meta.clone()
+    }
+}
+
+impl<E> WithMeta for BuildString<E> {
+    fn with_meta(mut self, meta: Meta) -> Self {
+        self._meta = meta;
+        self
+    }
+}
+
+impl<E> ChildVisitable<E> for BuildString<E>
+where
+    E: Instr + ChildVisitable<E>,
+{
+    fn visit_children_mut<V>(&mut self, visitor: &mut V)
+    where
+        V: crate::block_py::VisitMut<E> + ?Sized,
+    {
+        for part in &mut self.parts {
+            visitor.visit_instr_mut(part);
+        }
+    }
+
+    fn visit_children<V>(&self, visitor: &mut V)
+    where
+        V: crate::block_py::Visit<E> + ?Sized,
+    {
+        for part in &self.parts {
+            visitor.visit_instr(part);
+        }
+    }
+}
+
+impl<E: Instr> Mappable<E> for BuildString<E> {
+    type Mapped<T: Instr> = BuildString<T>;
+
+    fn map_children<T, M>(self, map: &mut M) -> Self::Mapped<T>
+    where
+        T: Instr,
+        M: MapInstr<E, T>,
+    {
+        BuildString {
+            _meta: self._meta,
+            parts: self
+                .parts
+                .into_iter()
+                .map(|part| map.map_instr(part))
+                .collect(),
+        }
+    }
+
+    fn try_map_children<T, Error, M>(self, map: &mut M) -> Result<Self::Mapped<T>, Error>
+    where
+        T: Instr,
+        M: TryMapInstr<E, T, Error>,
+    {
+        Ok(BuildString {
+            _meta: self._meta,
+            parts: self
+                .parts
+                .into_iter()
+                .map(|part| map.try_map_instr(part))
+                .collect::<Result<Vec<_>, _>>()?,
+        })
+    }
+}
+
 #[derive(Clone)]
 pub struct Load<I: Instr> {
     _meta: Meta,
@@ -0,0 +1,113 @@
+//! IR size metrics for the output of one pipeline stage.
+
+use super::analysis::{NameAccess, NameAccessInstr};
+use super::{walk_expr, BlockEdge, BlockParam, BlockPyModule, BlockPyPass, Visit};
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, HashSet};
+
+/// The operation an instruction performs, named after its variant
+/// (`"Call"`, `"Load"`, ...).
+pub trait OperationKind {
+    fn operation_kind(&self) -> &'static str;
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IrStats {
+    pub functions: usize,
+    pub blocks: usize,
+    /// Every instruction, operands of other instructions included.
+    pub instructions: usize,
+    pub instructions_by_kind: BTreeMap<&'static str, usize>,
+    /// Blocks whose exceptions go to a handler block.
+    pub exception_edges: usize,
+    /// Distinct generated (`_dp_`) names each function writes or receives as
+    /// a block param, summed over functions.
+    pub temporaries: usize,
+}
+
+impl IrStats {
+    pub(crate) fn of_module<P>(module: &BlockPyModule<P>) -> Self
+    where
+        P: BlockPyPass,
+        P::Expr: NameAccessInstr + OperationKind,
+    {
+        let mut collector = StatsCollector {
+            stats: Self::default(),
+            temporaries: HashSet::new(),
+        };
+        for function in &module.callable_defs {
+            collector.stats.functions += 1;
+            collector.stats.blocks += function.blocks.len();
+            collector.visit_fn(function);
+            collector.stats.temporaries += collector.temporaries.len();
+            collector.temporaries.clear();
+        }
+        collector.stats
+    }
+
+    pub fn to_json(&self) -> Value {
+        json!({
+            "functions": self.functions,
+            "blocks": self.blocks,
+            "instructions": self.instructions,
+            "instructions_by_kind": self.instructions_by_kind,
+            "exception_edges": self.exception_edges,
+            "temporaries": self.temporaries,
+        })
+    }
+}
+
+/// Stage outputs the pass tracker records IR stats for.
+pub(crate) trait StageStats {
+    fn ir_stats(&self) -> Option<IrStats>;
+}
+
+impl<P> StageStats for BlockPyModule<P>
+where
+    P: BlockPyPass,
+    P::Expr: NameAccessInstr + OperationKind,
+{
+    fn ir_stats(&self) -> Option<IrStats> {
+        Some(IrStats::of_module(self))
+    }
+}
+
+struct StatsCollector {
+    stats: IrStats,
+    temporaries: HashSet<String>,
+}
+
+impl StatsCollector {
+    fn note_written(&mut self, name: &str) {
+        if name.starts_with("_dp_") && !self.temporaries.contains(name) {
+            self.temporaries.insert(name.to_string());
+        }
+    }
+}
+
+impl<I> Visit<I> for StatsCollector
+where
+    I: NameAccessInstr + OperationKind,
+{
+    fn visit_instr(&mut self, expr: &I) {
+        self.stats.instructions += 1;
+        *self
+            .stats
+            .instructions_by_kind
+            .entry(expr.operation_kind())
+            .or_default() += 1;
+        if let Some(NameAccess::Write(name)) = expr.name_access() {
+            self.note_written(name);
+        }
+        walk_expr(self, expr);
+    }
+
+    fn visit_block_param(&mut self, param: &BlockParam) {
+        self.note_written(param.name.as_str());
+    }
+
+    fn visit_exception_edge(&mut self, edge: &BlockEdge) {
+        self.stats.exception_edges += 1;
+        self.visit_edge(edge);
+    }
+}
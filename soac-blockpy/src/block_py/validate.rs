@@ -1,7 +1,71 @@
 use crate::block_py::{
-    compute_storage_layout_from_scope, Block, BlockArg, BlockEdge, BlockLabel, BlockParam,
-    BlockPyFunction, BlockPyModule, BlockPyPass, BlockTerm, ScopeExprNode,
+    compute_storage_layout_from_scope, walk_expr, walk_fn, Block, BlockArg, BlockEdge, BlockLabel,
+    BlockParam, BlockPyFunction, BlockPyModule, BlockPyPass, BlockTerm, ChildVisitable,
+    CodegenBlockPyExpr, Instr, LocatedCoreBlockPyExpr, LocatedName, NameLocation, ScopeExprNode,
+    StorageLayout, Visit,
 };
+use crate::passes::{
+    CodegenBlockPyPass, CoreBlockPyPass, CoreBlockPyPassWithAwaitAndYield,
+    CoreBlockPyPassWithYield, ResolvedStorageBlockPyPass,
+};
+
+/// Invariants that hold for the output of one pipeline stage.
+///
+/// `Await` has no variant after await lowering and `Yield`/`YieldFrom` have
+/// none after generator lowering, so the expression types already rule those
+/// out; these checks cover what the types cannot.
+pub(crate) trait VerifyStage {
+    fn verify_stage(&self) -> Result<(), String>;
+}
+
+impl VerifyStage for BlockPyModule<CoreBlockPyPassWithAwaitAndYield> {
+    fn verify_stage(&self) -> Result<(), String> {
+        for function in &self.callable_defs {
+            validate_stage_edges(function, false)?;
+        }
+        Ok(())
+    }
+}
+
+impl VerifyStage for BlockPyModule<CoreBlockPyPassWithYield> {
+    fn verify_stage(&self) -> Result<(), String> {
+        for function in &self.callable_defs {
+            validate_stage_edges(function, false)?;
+        }
+        Ok(())
+    }
+}
+
+impl VerifyStage for BlockPyModule<CoreBlockPyPass> {
+    fn verify_stage(&self) -> Result<(), String> {
+        for function in &self.callable_defs {
+            validate_stage_edges(function, false)?;
+        }
+        Ok(())
+    }
+}
+
+impl VerifyStage for BlockPyModule<ResolvedStorageBlockPyPass> {
+    fn verify_stage(&self) -> Result<(), String> {
+        for function in &self.callable_defs {
+            validate_storage_layout_scoping(function, function.names.qualname.as_str())?;
+            validate_stage_edges(function, true)?;
+            validate_resolved_names(self, function)?;
+        }
+        Ok(())
+    }
+}
+
+impl VerifyStage for BlockPyModule<CodegenBlockPyPass> {
+    fn verify_stage(&self) -> Result<(), String> {
+        validate_module(self)?;
+        for function in &self.callable_defs {
+            validate_stage_edges(function, true)?;
+            validate_resolved_names(self, function)?;
+        }
+        Ok(())
+    }
+}
 
 pub(crate) fn validate_module<P: BlockPyPass, S>(module: &BlockPyModule<P, S>) -> Result<(), String>
 where
@@ -62,50 +126,205 @@ where
                 }
             }
         }
-        match &block.term {
-            BlockTerm::Jump(target) => {
-                validate_non_exception_edge(function, block, target, qualname, "jump target")?;
-            }
-            BlockTerm::IfTerm(if_term) => {
-                validate_non_exception_edge(
-                    function,
-                    block,
-                    &BlockEdge::new(if_term.then_label),
-                    qualname,
-                    "then target",
-                )?;
-                validate_non_exception_edge(
-                    function,
-                    block,
-                    &BlockEdge::new(if_term.else_label),
+        for (edge, label_kind) in term_edges(&block.term) {
+            validate_non_exception_edge(function, block, &edge, qualname, label_kind)?;
+        }
+    }
+    Ok(())
+}
+
+/// Edge checks that hold from the first BlockPy stage on: every edge targets
+/// a block of the function and passes at most as many args as the target has
+/// params, and exception edges land on a block that binds the exception.
+/// Exception edge args are only complete once exception flow is lowered, so
+/// their exact arity is left to `validate_function`.
+fn validate_stage_edges<P: BlockPyPass>(
+    function: &BlockPyFunction<P>,
+    check_forwarding: bool,
+) -> Result<(), String> {
+    let qualname = function.names.qualname.as_str();
+    for block in &function.blocks {
+        if let Some(exc_edge) = block.exc_edge.as_ref() {
+            let target_block = lookup_known_block(
+                function,
+                exc_edge.target,
+                qualname,
+                block.label,
+                "exception target",
+            )?;
+            if exc_edge.args.len() > target_block.params.len() {
+                return Err(format!(
+                    "exception dispatch from {}:{} has {} explicit edge args for target {} with {} full params",
                     qualname,
-                    "else target",
-                )?;
+                    block.label,
+                    exc_edge.args.len(),
+                    target_block.label,
+                    target_block.params.len()
+                ));
             }
-            BlockTerm::BranchTable(branch) => {
-                for target in &branch.targets {
-                    validate_non_exception_edge(
-                        function,
-                        block,
-                        &BlockEdge::new(*target),
-                        qualname,
-                        "br_table target",
-                    )?;
-                }
-                validate_non_exception_edge(
-                    function,
-                    block,
-                    &BlockEdge::new(branch.default_label),
+            if let Some(kind) = exc_edge.args.iter().find_map(|arg| match arg {
+                BlockArg::AbruptKind(kind) => Some(kind),
+                _ => None,
+            }) {
+                return Err(format!(
+                    "exception dispatch from {}:{} uses abrupt-kind edge arg {:?}",
+                    qualname, block.label, kind
+                ));
+            }
+            if target_block.exception_param().is_none() {
+                return Err(format!(
+                    "exception dispatch from {}:{} targets {} which has no exception param",
+                    qualname, block.label, target_block.label
+                ));
+            }
+        }
+        for (edge, label_kind) in term_edges(&block.term) {
+            if check_forwarding {
+                validate_non_exception_edge(function, block, &edge, qualname, label_kind)?;
+                continue;
+            }
+            let target_block =
+                lookup_known_block(function, edge.target, qualname, block.label, label_kind)?;
+            if edge.args.len() > target_block.params.len() {
+                return Err(format!(
+                    "{} from {}:{} has {} explicit edge args for target {} with {} full params",
+                    label_kind,
                     qualname,
-                    "br_table default target",
-                )?;
+                    block.label,
+                    edge.args.len(),
+                    target_block.label,
+                    target_block.params.len()
+                ));
             }
-            BlockTerm::Raise(_) | BlockTerm::Return(_) => {}
         }
     }
     Ok(())
 }
 
+fn term_edges<I: Instr>(term: &BlockTerm<I>) -> Vec<(BlockEdge, &'static str)> {
+    match term {
+        BlockTerm::Jump(target) => vec![(target.clone(), "jump target")],
+        BlockTerm::IfTerm(if_term) => vec![
+            (BlockEdge::new(if_term.then_label), "then target"),
+            (BlockEdge::new(if_term.else_label), "else target"),
+        ],
+        BlockTerm::BranchTable(branch) => branch
+            .targets
+            .iter()
+            .map(|target| (BlockEdge::new(*target), "br_table target"))
+            .chain([(
+                BlockEdge::new(branch.default_label),
+                "br_table default target",
+            )])
+            .collect(),
+        BlockTerm::Raise(_) | BlockTerm::Return(_) => Vec::new(),
+    }
+}
+
+/// Instructions whose names have been bound to storage.
+pub(crate) trait ResolvedNameInstr:
+    Instr<Name = LocatedName> + ChildVisitable<Self>
+{
+    fn resolved_name(&self) -> Option<&LocatedName>;
+}
+
+impl ResolvedNameInstr for LocatedCoreBlockPyExpr {
+    fn resolved_name(&self) -> Option<&LocatedName> {
+        match self {
+            Self::Load(op) => Some(&op.name),
+            Self::Store(op) => Some(&op.name),
+            Self::Del(op) => Some(&op.name),
+            _ => None,
+        }
+    }
+}
+
+impl ResolvedNameInstr for CodegenBlockPyExpr {
+    fn resolved_name(&self) -> Option<&LocatedName> {
+        match self {
+            Self::Load(op) => Some(&op.name),
+            Self::Store(op) => Some(&op.name),
+            Self::Del(op) => Some(&op.name),
+            _ => None,
+        }
+    }
+}
+
+/// Every name bound to storage must point at a slot that exists: a stack
+/// slot or cell of its function, a module global, or a module constant.
+fn validate_resolved_names<P>(
+    module: &BlockPyModule<P>,
+    function: &BlockPyFunction<P>,
+) -> Result<(), String>
+where
+    P: BlockPyPass,
+    P::Expr: ResolvedNameInstr,
+{
+    let mut checker = ResolvedNameChecker {
+        qualname: function.names.qualname.as_str(),
+        layout: function.storage_layout.as_ref(),
+        global_count: module.global_names.len(),
+        constant_count: module.module_constants.len(),
+        error: None,
+    };
+    walk_fn(&mut checker, function);
+    match checker.error {
+        Some(error) => Err(error),
+        None => Ok(()),
+    }
+}
+
+struct ResolvedNameChecker<'a> {
+    qualname: &'a str,
+    layout: Option<&'a StorageLayout>,
+    global_count: usize,
+    constant_count: usize,
+    error: Option<String>,
+}
+
+impl ResolvedNameChecker<'_> {
+    fn check(&self, name: &LocatedName) -> Result<(), String> {
+        let known = match name.location {
+            NameLocation::Local(location) => self
+                .layout
+                .is_some_and(|layout| (location.slot() as usize) < layout.stack_slots().len()),
+            NameLocation::Global(slot) => (slot.slot() as usize) < self.global_count,
+            NameLocation::Cell(location) if location.is_owned() => self
+                .layout
+                .is_some_and(|layout| layout.local_cell_slot(location.slot()).is_some()),
+            NameLocation::Cell(location) => self
+                .layout
+                .is_some_and(|layout| layout.freevar_slot(location.slot()).is_some()),
+            NameLocation::Constant(index) => (index as usize) < self.constant_count,
+            NameLocation::RuntimeName => true,
+        };
+        if known {
+            return Ok(());
+        }
+        Err(format!(
+            "{} in {} resolves to {}, which has no storage",
+            name.id,
+            self.qualname,
+            name.resolved_pretty_id()
+        ))
+    }
+}
+
+impl<I: ResolvedNameInstr> Visit<I> for ResolvedNameChecker<'_> {
+    fn visit_instr(&mut self, expr: &I) {
+        if self.error.is_some() {
+            return;
+        }
+        if let Some(name) = expr.resolved_name() {
+            if let Err(error) = self.check(name) {
+                self.error = Some(error);
+                return;
+            }
+        }
+        walk_expr(self, expr);
+    }
+}
+
 fn validate_non_exception_edge<P: BlockPyPass, S>(
     function: &BlockPyFunction<P, S>,
     source_block: &Block<S, P::Expr>,
@@ -313,17 +532,22 @@ fn lookup_known_block<'a, P: BlockPyPass>(
     block_label: BlockLabel,
     label_kind: &str,
 ) -> Result<&'a Block<P::Expr, P::Expr>, String> {
-    let Some(target_block) = function.blocks.get(label.index()) else {
-        return Err(format!(
-            "unknown {label_kind} {label} in {}:{}",
-            qualname, block_label
-        ));
-    };
-    if target_block.label == label {
+    if let Some(target_block) = function
+        .blocks
+        .get(label.index())
+        .filter(|block| block.label == label)
+    {
         return Ok(target_block);
     }
-    Err(format!(
-        "unknown {label_kind} {label} in {}:{}",
-        qualname, block_label
-    ))
+    // Before `relabel_dense_bb_module` labels are not block indices.
+    function
+        .blocks
+        .iter()
+        .find(|block| block.label == label)
+        .ok_or_else(|| {
+            format!(
+                "unknown {label_kind} {label} in {}:{}",
+                qualname, block_label
+            )
+        })
 }
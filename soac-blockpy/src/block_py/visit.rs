@@ -234,7 +234,7 @@ pub trait VisitMut<I: Instr> {
     }
 }
 
-pub(crate) fn walk_module<V, P>(visitor: &mut V, module: &BlockPyModule<P>)
+pub fn walk_module<V, P>(visitor: &mut V, module: &BlockPyModule<P>)
 where
     V: Visit<P::Expr> + ?Sized,
     P: BlockPyPass,
@@ -245,7 +245,7 @@ where
     }
 }
 
-pub(crate) fn walk_module_mut<V, P>(visitor: &mut V, module: &mut BlockPyModule<P>)
+pub fn walk_module_mut<V, P>(visitor: &mut V, module: &mut BlockPyModule<P>)
 where
     V: VisitMut<P::Expr> + ?Sized,
     P: BlockPyPass,
@@ -256,7 +256,7 @@ where
     }
 }
 
-pub(crate) fn walk_fn<V, P>(visitor: &mut V, func: &BlockPyFunction<P>)
+pub fn walk_fn<V, P>(visitor: &mut V, func: &BlockPyFunction<P>)
 where
     V: Visit<P::Expr> + ?Sized,
     P: BlockPyPass,
@@ -267,7 +267,7 @@ where
     }
 }
 
-pub(crate) fn walk_fn_mut<V, P>(visitor: &mut V, func: &mut BlockPyFunction<P>)
+pub fn walk_fn_mut<V, P>(visitor: &mut V, func: &mut BlockPyFunction<P>)
 where
     V: VisitMut<P::Expr> + ?Sized,
     P: BlockPyPass,
@@ -278,7 +278,7 @@ where
     }
 }
 
-pub(crate) fn walk_block<V, I>(visitor: &mut V, block: &Block<I, I>)
+pub fn walk_block<V, I>(visitor: &mut V, block: &Block<I, I>)
 where
     V: Visit<I> + ?Sized,
     I: Instr + ChildVisitable<I>,
@@ -295,7 +295,7 @@ where
     visitor.visit_term(&block.term);
 }
 
-pub(crate) fn walk_block_mut<V, I>(visitor: &mut V, block: &mut Block<I, I>)
+pub fn walk_block_mut<V, I>(visitor: &mut V, block: &mut Block<I, I>)
 where
     V: VisitMut<I> + ?Sized,
     I: Instr + ChildVisitable<I>,
@@ -312,7 +312,7 @@ where
     visitor.visit_term_mut(&mut block.term);
 }
 
-pub(crate) fn walk_stmt<V, I>(visitor: &mut V, stmt: &I)
+pub fn walk_stmt<V, I>(visitor: &mut V, stmt: &I)
 where
     V: Visit<I> + ?Sized,
     I: Instr + ChildVisitable<I>,
@@ -320,7 +320,7 @@ where
     visitor.visit_instr(stmt);
 }
 
-pub(crate) fn walk_stmt_mut<V, I>(visitor: &mut V, stmt: &mut I)
+pub fn walk_stmt_mut<V, I>(visitor: &mut V, stmt: &mut I)
 where
     V: VisitMut<I> + ?Sized,
     I: Instr + ChildVisitable<I>,
@@ -350,7 +350,7 @@ where
     }
 }
 
-pub(crate) fn walk_term<V, I>(visitor: &mut V, term: &BlockTerm<I>)
+pub fn walk_term<V, I>(visitor: &mut V, term: &BlockTerm<I>)
 where
     V: Visit<I> + ?Sized,
     I: Instr + ChildVisitable<I>,
@@ -364,7 +364,7 @@ where
     }
 }
 
-pub(crate) fn walk_term_mut<V, I>(visitor: &mut V, term: &mut BlockTerm<I>)
+pub fn walk_term_mut<V, I>(visitor: &mut V, term: &mut BlockTerm<I>)
 where
     V: VisitMut<I> + ?Sized,
     I: Instr + ChildVisitable<I>,
@@ -442,7 +442,7 @@ where
     }
 }
 
-pub(crate) fn walk_expr<V, I>(visitor: &mut V, expr: &I)
+pub fn walk_expr<V, I>(visitor: &mut V, expr: &I)
 where
     V: Visit<I> + ?Sized,
     I: Instr + ChildVisitable<I>,
@@ -450,7 +450,7 @@ where
     expr.visit_children(visitor);
 }
 
-pub(crate) fn walk_expr_mut<V, I>(visitor: &mut V, expr: &mut I)
+pub fn walk_expr_mut<V, I>(visitor: &mut V, expr: &mut I)
 where
     V: VisitMut<I> + ?Sized,
     I: Instr + ChildVisitable<I>,
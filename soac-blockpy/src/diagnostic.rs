@@ -0,0 +1,161 @@
+use ruff_text_size::{TextRange, TextSize};
+use std::any::Any;
+use std::fmt::Write as _;
+use std::panic::{self, AssertUnwindSafe};
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum DiagnosticKind {
+    /// The input is not valid Python; CPython would reject it too.
+    Syntax,
+    /// Valid Python that the lowering pipeline cannot handle yet.
+    Unsupported,
+    /// A lowering invariant failed; this is a bug in the pipeline.
+    Internal,
+}
+
+impl DiagnosticKind {
+    pub fn code(self) -> &'static str {
+        match self {
+            Self::Syntax => "syntax-error",
+            Self::Unsupported => "unsupported-syntax",
+            Self::Internal => "internal-error",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub kind: DiagnosticKind,
+    pub message: String,
+    /// Primary span in the lowered source. `None` only for internal errors
+    /// that fired without a node in hand.
+    pub range: Option<TextRange>,
+    pub notes: Vec<String>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SourceLocation {
+    /// One-based line number.
+    pub line: usize,
+    /// One-based column, counted in characters.
+    pub column: usize,
+}
+
+impl Diagnostic {
+    pub fn new(kind: DiagnosticKind, message: impl Into<String>, range: Option<TextRange>) -> Self {
+        Self {
+            kind,
+            message: message.into(),
+            range,
+            notes: Vec::new(),
+        }
+    }
+
+    pub fn syntax(message: impl Into<String>, range: TextRange) -> Self {
+        Self::new(DiagnosticKind::Syntax, message, Some(range))
+    }
+
+    pub fn unsupported(message: impl Into<String>, range: TextRange) -> Self {
+        Self::new(DiagnosticKind::Unsupported, message, Some(range))
+    }
+
+    pub fn internal(message: impl Into<String>) -> Self {
+        Self::new(DiagnosticKind::Internal, message, None)
+    }
+
+    pub fn with_note(mut self, note: impl Into<String>) -> Self {
+        self.notes.push(note.into());
+        self
+    }
+
+    pub fn code(&self) -> &'static str {
+        self.kind.code()
+    }
+
+    pub fn location(&self, source: &str) -> Option<SourceLocation> {
+        self.range
+            .map(|range| source_location(source, range.start()))
+    }
+
+    /// Renders the diagnostic as an annotated snippet of `source`, in the
+    /// same layout ruff uses for its own diagnostics.
+    pub fn render(&self, filename: &str, source: &str) -> String {
+        let mut out = format!("error[{}]: {}\n", self.code(), self.message);
+        if let Some(range) = self.range {
+            let start = source_location(source, range.start());
+            let offset = usize::from(range.start()).min(source.len());
+            let line_start = source[..offset].rfind('\n').map_or(0, |index| index + 1);
+            let line_end = source[offset..]
+                .find('\n')
+                .map_or(source.len(), |index| offset + index);
+            let line_text = source[line_start..line_end].trim_end_matches('\r');
+            let underline_end = usize::from(range.end()).clamp(offset, line_end);
+            let width = source[offset..underline_end].chars().count().max(1);
+            let gutter = " ".repeat(start.line.to_string().len());
+            let _ = writeln!(
+                out,
+                "{gutter}--> {filename}:{}:{}",
+                start.line, start.column
+            );
+            let _ = writeln!(out, "{gutter} |");
+            let _ = writeln!(out, "{} | {line_text}", start.line);
+            let _ = writeln!(
+                out,
+                "{gutter} | {}{}",
+                " ".repeat(start.column - 1),
+                "^".repeat(width)
+            );
+            let _ = writeln!(out, "{gutter} |");
+            for note in &self.notes {
+                let _ = writeln!(out, "{gutter} = note: {note}");
+            }
+        } else {
+            let _ = writeln!(out, " --> {filename}");
+            for note in &self.notes {
+                let _ = writeln!(out, "  = note: {note}");
+            }
+        }
+        out
+    }
+}
+
+impl std::fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.code(), self.message)
+    }
+}
+
+impl std::error::Error for Diagnostic {}
+
+pub fn source_location(source: &str, offset: TextSize) -> SourceLocation {
+    let offset = usize::from(offset).min(source.len());
+    let prefix = &source[..offset];
+    let line_start = prefix.rfind('\n').map_or(0, |index| index + 1);
+    SourceLocation {
+        line: prefix.matches('\n').count() + 1,
+        column: prefix[line_start..].chars().count() + 1,
+    }
+}
+
+/// Runs `lower`, reporting a panic as an internal error. Passes return
+/// input errors as `Err(Diagnostic)`; this only catches broken invariants, so
+/// a lowering bug fails the one module instead of the whole process.
+pub(crate) fn catch_internal_errors<T>(lower: impl FnOnce() -> T) -> Result<T, Diagnostic> {
+    panic::catch_unwind(AssertUnwindSafe(lower)).map_err(|payload| {
+        Diagnostic::internal(panic_message(payload.as_ref()))
+            .with_note("this is a bug in diet-python lowering, not in the input program")
+    })
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        (*message).to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "lowering panicked".to_string()
+    }
+}
+
+#[cfg(test)]
+mod test;
@@ -0,0 +1,57 @@
+use super::{catch_internal_errors, source_location, Diagnostic, DiagnosticKind};
+use ruff_text_size::{TextRange, TextSize};
+
+fn range(start: u32, end: u32) -> TextRange {
+    TextRange::new(TextSize::new(start), TextSize::new(end))
+}
+
+#[test]
+fn source_location_is_one_based_and_counts_characters() {
+    let source = "x = 1\ny = 'é' + z\n";
+    let z = source.find('z').unwrap() as u32;
+
+    let location = source_location(source, TextSize::new(z));
+    assert_eq!((location.line, location.column), (2, 11));
+    let location = source_location(source, TextSize::new(0));
+    assert_eq!((location.line, location.column), (1, 1));
+}
+
+#[test]
+fn renders_annotated_snippet_with_notes() {
+    let source = "x = 1\ny = 2j + x\n";
+    let diagnostic = Diagnostic::unsupported("complex literals are not supported", range(10, 12))
+        .with_note("rewrite as complex(0, 2)");
+
+    assert_eq!(
+        diagnostic.render("mod.py", source),
+        "\
+error[unsupported-syntax]: complex literals are not supported
+ --> mod.py:2:5
+  |
+2 | y = 2j + x
+  |     ^^
+  |
+  = note: rewrite as complex(0, 2)
+"
+    );
+}
+
+#[test]
+fn underline_stops_at_end_of_first_line() {
+    let source = "f(a,\n  b)\n";
+    let diagnostic = Diagnostic::syntax("bad call", range(0, 9));
+
+    let rendered = diagnostic.render("mod.py", source);
+    assert!(rendered.contains("1 | f(a,\n  | ^^^^\n"), "{rendered}");
+}
+
+#[test]
+fn catch_internal_errors_reports_panics_as_internal_errors() {
+    assert_eq!(catch_internal_errors(|| 7), Ok(7));
+    let caught = catch_internal_errors(|| -> i32 { panic!("invariant broke") }).unwrap_err();
+
+    assert_eq!(caught.kind, DiagnosticKind::Internal);
+    assert_eq!(caught.message, "invariant broke");
+    assert_eq!(caught.range, None);
+    assert_eq!(caught.to_string(), "internal-error: invariant broke");
+}
@@ -1,22 +1,32 @@
 use crate::block_py::pretty::BlockPyPrettyPrint;
-use crate::block_py::{BlockPyModule, ModuleNameGen};
-use crate::pass_tracker::PassTracker;
+use crate::block_py::stats::StageStats;
+use crate::block_py::validate::VerifyStage;
+use crate::block_py::{BlockPyModule, BlockPyPass, IrStats, ModuleNameGen};
+use crate::diagnostic::{catch_internal_errors, Diagnostic};
+use crate::pass_registry::{ModulePass, PassRegistry};
+use crate::pass_tracker::{NoopPassTracker, PassTracker, VerifyingPassTracker};
 use crate::passes::ast_to_ast::ast_rewrite::rewrite_with_pass;
 use crate::passes::ast_to_ast::context::Context;
 use crate::passes::ast_to_ast::rewrite_class_def;
 use crate::passes::ast_to_ast::rewrite_expr::ScopedHelperExprPass;
 use crate::passes::ast_to_ast::{
-    body::Suite, rewrite_future_annotations, rewrite_stmt, semantic::SemanticAstState,
+    body::Suite, rewrite_future_annotations, rewrite_lazy_imports, rewrite_optimize, rewrite_stmt,
+    semantic::SemanticAstState,
 };
 use crate::passes::core_await_lower::lower_awaits_in_core_blockpy_module;
+use crate::passes::parallel::{lowering_threads, CallablePool};
 use crate::passes::ruff_to_blockpy::rewrite_ast_to_core_blockpy_module_with_module;
+use crate::passes::syntax_check;
 use crate::passes::{
     self, CodegenBlockPyPass, CoreBlockPyPass, CoreBlockPyPassWithAwaitAndYield,
-    CoreBlockPyPassWithYield, ResolvedStorageBlockPyPass,
+    CoreBlockPyPassWithYield, MaybeUnboundLocal, ResolvedStorageBlockPyPass,
 };
-use crate::{ParseError, Result};
+use crate::symtable::SymbolTable;
+use crate::{LoweringOptions, Result};
 use ruff_python_ast::{self as ast, Stmt};
 use ruff_python_parser::parse_module;
+use std::collections::HashSet;
+use std::sync::Arc;
 
 #[derive(Clone)]
 pub(crate) struct AstToAstPassResult {
@@ -30,7 +40,25 @@ impl BlockPyPrettyPrint for AstToAstPassResult {
     }
 }
 
+impl VerifyStage for AstToAstPassResult {
+    fn verify_stage(&self) -> std::result::Result<(), String> {
+        Ok(())
+    }
+}
+
+impl StageStats for AstToAstPassResult {
+    fn ir_stats(&self) -> Option<IrStats> {
+        None
+    }
+}
+
 fn rewrite_ast_to_ast_module(context: &Context, mut module: Suite) -> AstToAstPassResult {
+    // Under -O/-OO, drop asserts, `if __debug__:` blocks and docstrings
+    rewrite_optimize::rewrite(context.options().optimization, &mut module);
+
+    // Bind opted-in top-level imports to proxies resolved on first use
+    rewrite_lazy_imports::rewrite(&context.source, context.options(), &mut module);
+
     // Rewrite names like "__foo" in class bodies to "_<class_name>__foo"
     rewrite_class_def::private::rewrite_private_names(context, &mut module);
 
@@ -68,23 +96,105 @@ fn rewrite_ast_to_ast_module(context: &Context, mut module: Suite) -> AstToAstPa
 pub(crate) fn rewrite_module_with_tracker(
     source: &str,
     module_name_gen: ModuleNameGen,
+    options: &LoweringOptions,
     pass_tracker: &mut impl PassTracker,
 ) -> Result<BlockPyModule<CodegenBlockPyPass>> {
-    let module =
-        pass_tracker.record_timing("parse", || -> std::result::Result<_, ParseError> {
-            let mut module = parse_module(source).map(|module| module.into_syntax())?;
-            rewrite_future_annotations::rewrite(&mut module.body)?;
-            Ok(module)
-        })?;
+    let pass_tracker = &mut VerifyingPassTracker::new(pass_tracker, options.validation);
+    let module = parse_and_check_module(source, pass_tracker)?;
+
+    // Passes report unsupported input through `Result`; a panic is a lowering
+    // bug and is reported as an internal error.
+    catch_internal_errors(|| {
+        lower_parsed_module(source, module, module_name_gen, options, pass_tracker)
+    })?
+}
+
+/// Parses `source` and rejects everything CPython would reject before
+/// compiling it.
+pub(crate) fn parse_and_check_module(
+    source: &str,
+    pass_tracker: &mut impl PassTracker,
+) -> Result<ast::ModModule> {
+    let module = pass_tracker.record_timing("parse", || -> Result<_> {
+        let mut module = parse_module(source)
+            .map_err(syntax_check::from_parse_error)?
+            .into_syntax();
+        rewrite_future_annotations::rewrite(&mut module.body)?;
+        Ok(module)
+    })?;
+    pass_tracker.record_timing("syntax_check", || syntax_check::check_module(&module.body))?;
+    Ok(module)
+}
+
+/// Runs the AST-to-AST rewrites on `source` and exports the scopes the later
+/// passes lower against.
+pub(crate) fn symbol_table_for_source(source: &str) -> Result<SymbolTable> {
+    let module = parse_and_check_module(source, &mut NoopPassTracker::new())?;
+    let context = Context::new(source, ModuleNameGen::new(0));
+    let result = catch_internal_errors(|| rewrite_ast_to_ast_module(&context, module.body))?;
+    Ok(result.semantic_state.symbol_table())
+}
+
+/// Lowers `source` as far as core BlockPy and lists the loads of function
+/// locals that may run before the local is assigned.
+pub(crate) fn maybe_unbound_locals_for_source(source: &str) -> Result<Vec<MaybeUnboundLocal>> {
+    let options = LoweringOptions::default();
+    let mut pass_tracker = NoopPassTracker::new();
+    let pass_tracker = &mut VerifyingPassTracker::new(&mut pass_tracker, options.validation);
+    let module = parse_and_check_module(source, pass_tracker)?;
+    let pool = CallablePool::new(lowering_threads(options.lowering_threads));
+    catch_internal_errors(|| -> Result<_> {
+        let core_blockpy = lower_parsed_module_to_core_blockpy(
+            source,
+            module,
+            ModuleNameGen::new(0),
+            &options,
+            pass_tracker,
+            &pool,
+        )?;
+        Ok(passes::maybe_unbound_locals_in_core_blockpy_module(
+            core_blockpy,
+            &pool,
+        ))
+    })?
+}
 
-    let context = Context::new(source);
+fn lower_parsed_module(
+    source: &str,
+    module: ast::ModModule,
+    module_name_gen: ModuleNameGen,
+    options: &LoweringOptions,
+    pass_tracker: &mut impl PassTracker,
+) -> Result<BlockPyModule<CodegenBlockPyPass>> {
+    let pool = CallablePool::new(lowering_threads(options.lowering_threads));
+    let core_blockpy = lower_parsed_module_to_core_blockpy(
+        source,
+        module,
+        module_name_gen,
+        options,
+        pass_tracker,
+        &pool,
+    )?;
+    lower_core_blockpy_module(core_blockpy, options, pass_tracker, &pool)
+}
+
+fn lower_parsed_module_to_core_blockpy(
+    source: &str,
+    module: ast::ModModule,
+    module_name_gen: ModuleNameGen,
+    options: &LoweringOptions,
+    pass_tracker: &mut impl PassTracker,
+    pool: &CallablePool,
+) -> Result<BlockPyModule<CoreBlockPyPass>> {
+    check_registered_pass_names(&options.passes)?;
+    let context = Context::new(source, module_name_gen.clone()).with_options(options.clone());
 
     let AstToAstPassResult {
         module,
         semantic_state,
     } = pass_tracker.run_pass("ast-to-ast", || {
         rewrite_ast_to_ast_module(&context, module.body)
-    });
+    })?;
 
     /*
 
@@ -126,14 +236,14 @@ pub(crate) fn rewrite_module_with_tracker(
     */
 
     let core_blockpy: BlockPyModule<CoreBlockPyPassWithAwaitAndYield> =
-        pass_tracker.run_pass("core_blockpy_with_await_and_yield", || {
+        pass_tracker.try_run_pass("core_blockpy_with_await_and_yield", || {
             rewrite_ast_to_core_blockpy_module_with_module(
                 &context,
                 module,
                 &semantic_state,
                 module_name_gen,
             )
-        });
+        })?;
 
     /*
       A very simple pass to rewrite `await foo` into `yield from __soac__.await_iter(foo)`
@@ -141,7 +251,7 @@ pub(crate) fn rewrite_module_with_tracker(
     let core_blockpy_without_await: BlockPyModule<CoreBlockPyPassWithYield> = pass_tracker
         .run_pass("core_blockpy_with_yield", || {
             lower_awaits_in_core_blockpy_module(core_blockpy)
-        });
+        })?;
 
     /*
      Convert generators into a state machine, driven by an internal `resume(send, throw)` function.
@@ -151,9 +261,25 @@ pub(crate) fn rewrite_module_with_tracker(
     */
     let core_blockpy_without_await_or_yield: BlockPyModule<CoreBlockPyPass> = pass_tracker
         .run_pass("core_blockpy", || {
-            passes::lower_yield_in_lowered_core_blockpy_module_bundle(core_blockpy_without_await)
-        });
+            passes::lower_yield_in_lowered_core_blockpy_module_bundle(
+                core_blockpy_without_await,
+                pool,
+            )
+        })?;
+    pass_tracker.record_parallel_timing("core_blockpy", pool.take_timing());
+    Ok(run_registered_passes(
+        options.passes.after_core_blockpy_passes(),
+        core_blockpy_without_await_or_yield,
+        pass_tracker,
+    )?)
+}
 
+fn lower_core_blockpy_module(
+    core_blockpy_without_await_or_yield: BlockPyModule<CoreBlockPyPass>,
+    options: &LoweringOptions,
+    pass_tracker: &mut impl PassTracker,
+    pool: &CallablePool,
+) -> Result<BlockPyModule<CodegenBlockPyPass>> {
     /*
      Resolve Names into specific storage operations:
        - globals become LoadName / StoreName / DelName
@@ -163,43 +289,83 @@ pub(crate) fn rewrite_module_with_tracker(
        - locals are assigned stack slots, and become LoadLocation / StoreLocation / DelLocation with local-slot locations.
 
     */
-    let name_binding: BlockPyModule<ResolvedStorageBlockPyPass> = pass_tracker
-        .run_pass("name_binding", || {
-            passes::lower_name_binding_in_core_blockpy_module(core_blockpy_without_await_or_yield)
-        });
+    let name_binding: BlockPyModule<ResolvedStorageBlockPyPass> =
+        pass_tracker.run_pass("name_binding", || {
+            passes::lower_name_binding_in_core_blockpy_module(
+                core_blockpy_without_await_or_yield,
+                pool,
+            )
+        })?;
+    pass_tracker.record_parallel_timing("name_binding", pool.take_timing());
 
     let bb_prepared: BlockPyModule<ResolvedStorageBlockPyPass> = pass_tracker
         .run_pass("bb_prepared", || {
-            passes::lower_try_jump_exception_flow(&name_binding)
-        });
-    let bb_codegen: BlockPyModule<CodegenBlockPyPass> = pass_tracker.run_pass("bb_codegen", || {
-        let mut bb_codegen = passes::normalize_bb_module_strings(&bb_prepared);
-        passes::relabel_dense_bb_module(&mut bb_codegen);
-        passes::assign_module_instr_ids(&mut bb_codegen);
-        bb_codegen
-    });
-
-    let bb_traced: BlockPyModule<CodegenBlockPyPass> =
-        if let Some(config) = passes::parse_trace_env() {
-            pass_tracker.run_pass("bb_trace", || {
-                let mut traced = bb_codegen;
-                passes::instrument_bb_module_for_trace(&mut traced, &config);
-                traced
-            })
-        } else {
+            passes::lower_try_jump_exception_flow_with_pool(&name_binding, pool)
+        })?;
+    pass_tracker.record_parallel_timing("bb_prepared", pool.take_timing());
+    let bb_codegen: BlockPyModule<CodegenBlockPyPass> =
+        pass_tracker.run_pass("bb_codegen", || {
+            let mut bb_codegen = passes::normalize_bb_module_strings_with_pool(&bb_prepared, pool);
+            passes::relabel_dense_bb_module(&mut bb_codegen);
+            passes::assign_module_instr_ids(&mut bb_codegen);
             bb_codegen
-        };
-
-    let bb_counted: BlockPyModule<CodegenBlockPyPass> =
-        if passes::global_load_counter_instrumentation_enabled() {
-            pass_tracker.run_pass("bb_global_load_counters", || {
-                let mut counted = bb_traced;
-                passes::instrument_bb_module_with_global_load_counters(&mut counted);
-                counted
-            })
-        } else {
-            bb_traced
-        };
+        })?;
+    pass_tracker.record_parallel_timing("bb_codegen", pool.take_timing());
+    let bb_codegen = run_registered_passes(
+        options.passes.after_codegen_passes(),
+        bb_codegen,
+        pass_tracker,
+    )?;
+
+    let bb_inlined: BlockPyModule<CodegenBlockPyPass> = if options.inline {
+        pass_tracker.run_pass("bb_inline", || {
+            let mut inlined = bb_codegen;
+            passes::inline_direct_calls_in_bb_module(&mut inlined);
+            passes::relabel_dense_bb_module(&mut inlined);
+            passes::assign_module_instr_ids(&mut inlined);
+            inlined
+        })?
+    } else {
+        bb_codegen
+    };
+
+    let bb_hoisted: BlockPyModule<CodegenBlockPyPass> = if options.hoist_global_loads {
+        pass_tracker.run_pass("bb_hoist_global_loads", || {
+            let mut hoisted = bb_inlined;
+            passes::hoist_loop_invariant_global_loads_in_bb_module(&mut hoisted);
+            passes::relabel_dense_bb_module(&mut hoisted);
+            passes::assign_module_instr_ids(&mut hoisted);
+            hoisted
+        })?
+    } else {
+        bb_inlined
+    };
+
+    let bb_traced: BlockPyModule<CodegenBlockPyPass> = if let Some(config) = &options.trace {
+        pass_tracker.run_pass("bb_trace", || {
+            let mut traced = bb_hoisted;
+            passes::instrument_bb_module_for_trace(&mut traced, config);
+            traced
+        })?
+    } else {
+        bb_hoisted
+    };
+
+    let bb_counted: BlockPyModule<CodegenBlockPyPass> = if options.global_load_counters {
+        pass_tracker.run_pass("bb_global_load_counters", || {
+            let mut counted = bb_traced;
+            passes::instrument_bb_module_with_global_load_counters(&mut counted);
+            counted
+        })?
+    } else {
+        bb_traced
+    };
+
+    let bb_counted = run_registered_passes(
+        options.passes.before_validate_passes(),
+        bb_counted,
+        pass_tracker,
+    )?;
 
     pass_tracker.record_timing("validate", || {
         crate::block_py::validate_module(&bb_counted).map_err(anyhow::Error::msg)
@@ -208,6 +374,79 @@ pub(crate) fn rewrite_module_with_tracker(
     Ok(bb_counted)
 }
 
+/// Names the driver tracks its own stages under.
+const BUILTIN_STAGE_NAMES: &[&str] = &[
+    "parse",
+    "syntax_check",
+    "ast-to-ast",
+    "core_blockpy_with_await_and_yield",
+    "core_blockpy_with_yield",
+    "core_blockpy",
+    "name_binding",
+    "bb_prepared",
+    "bb_codegen",
+    "bb_inline",
+    "bb_hoist_global_loads",
+    "bb_trace",
+    "bb_global_load_counters",
+    "validate",
+];
+
+/// Rejects registered passes whose names collide with each other or with a
+/// built-in stage, since each pass is tracked under its own name.
+fn check_registered_pass_names(registry: &PassRegistry) -> std::result::Result<(), Diagnostic> {
+    let names = registry
+        .after_core_blockpy_passes()
+        .iter()
+        .map(|pass| pass.name())
+        .chain(
+            registry
+                .after_codegen_passes()
+                .iter()
+                .map(|pass| pass.name()),
+        )
+        .chain(
+            registry
+                .before_validate_passes()
+                .iter()
+                .map(|pass| pass.name()),
+        );
+    let mut seen = HashSet::new();
+    for name in names {
+        if BUILTIN_STAGE_NAMES.contains(&name) {
+            return Err(Diagnostic::internal(format!(
+                "registered pass `{name}` has the name of a built-in stage"
+            )));
+        }
+        if !seen.insert(name) {
+            return Err(Diagnostic::internal(format!(
+                "more than one registered pass is named `{name}`"
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Runs each registered pass over `module` in turn, tracked under the pass's
+/// own name.
+fn run_registered_passes<P>(
+    passes: &[Arc<dyn ModulePass<P>>],
+    module: BlockPyModule<P>,
+    pass_tracker: &mut impl PassTracker,
+) -> std::result::Result<BlockPyModule<P>, Diagnostic>
+where
+    P: BlockPyPass + 'static,
+    BlockPyModule<P>: BlockPyPrettyPrint + VerifyStage + StageStats,
+{
+    passes.iter().try_fold(module, |module, pass| {
+        pass_tracker.try_run_pass(pass.name(), || {
+            let mut module = module;
+            pass.run(&mut module)?;
+            Ok(module)
+        })
+    })
+}
+
 pub(crate) fn wrap_module_init(semantic_state: &mut SemanticAstState, module: &mut Suite) {
     let mut init_body = std::mem::take(module);
     if init_body.is_empty() {
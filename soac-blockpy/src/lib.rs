@@ -1,8 +1,14 @@
 use crate::block_py::{BlockPyModule, ModuleNameGen};
-use crate::driver::rewrite_module_with_tracker;
+use crate::diagnostic::Diagnostic;
+use crate::driver::{
+    maybe_unbound_locals_for_source, parse_and_check_module, rewrite_module_with_tracker,
+    symbol_table_for_source,
+};
 use crate::pass_tracker::{NoopPassTracker, PassTracker, RecordingPassTracker};
-use crate::passes::CodegenBlockPyPass;
+use crate::passes::{CodegenBlockPyPass, MaybeUnboundLocal};
 use anyhow::Error as AnyhowError;
+pub use options::{LoweringOptions, OptimizationLevel, ValidationLevel};
+pub use pass_registry::{ModulePass, PassRegistry};
 use ruff_python_ast::{self as ast, Expr, Stmt};
 use ruff_python_codegen::{Generator, Indentation};
 pub use ruff_python_parser::ParseError;
@@ -12,11 +18,14 @@ use std::sync::Once;
 use std::time::{Duration, Instant};
 
 pub mod block_py;
+pub mod diagnostic;
 mod driver;
 pub mod fixture;
-mod namegen;
+pub mod options;
+pub mod pass_registry;
 pub mod pass_tracker;
 pub mod passes;
+pub mod symtable;
 mod template;
 #[cfg(test)]
 mod test_util;
@@ -25,15 +34,28 @@ pub(crate) mod transformer;
 #[derive(Debug)]
 pub enum LoweringError {
     Parse(ParseError),
+    Diagnostic(Diagnostic),
     Other(AnyhowError),
 }
 
+impl LoweringError {
+    /// Presents every lowering failure as a located diagnostic.
+    pub fn to_diagnostic(&self) -> Diagnostic {
+        match self {
+            Self::Parse(err) => Diagnostic::syntax(err.error.to_string(), err.location),
+            Self::Diagnostic(diagnostic) => diagnostic.clone(),
+            Self::Other(err) => Diagnostic::internal(err.to_string()),
+        }
+    }
+}
+
 pub type Result<T> = std::result::Result<T, LoweringError>;
 
 impl std::fmt::Display for LoweringError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Self::Parse(err) => err.fmt(f),
+            Self::Diagnostic(diagnostic) => diagnostic.fmt(f),
             Self::Other(err) => err.fmt(f),
         }
     }
@@ -43,6 +65,7 @@ impl std::error::Error for LoweringError {
     fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
         match self {
             Self::Parse(err) => Some(err),
+            Self::Diagnostic(diagnostic) => Some(diagnostic),
             Self::Other(err) => Some(err.as_ref()),
         }
     }
@@ -54,6 +77,12 @@ impl From<ParseError> for LoweringError {
     }
 }
 
+impl From<Diagnostic> for LoweringError {
+    fn from(value: Diagnostic) -> Self {
+        Self::Diagnostic(value)
+    }
+}
+
 impl From<AnyhowError> for LoweringError {
     fn from(value: AnyhowError) -> Self {
         Self::Other(value)
@@ -82,16 +111,17 @@ pub struct LoweringResult<P = RecordingPassTracker> {
 fn lower_python_to_blockpy_with_tracker<P>(
     source: &str,
     module_name_gen: ModuleNameGen,
+    options: &LoweringOptions,
     mut pass_tracker: P,
 ) -> Result<LoweringResult<P>>
 where
     P: PassTracker,
 {
     init_logging();
-    namegen::reset_namegen_state();
     let total_start = Instant::now();
 
-    let codegen_module = rewrite_module_with_tracker(source, module_name_gen, &mut pass_tracker)?;
+    let codegen_module =
+        rewrite_module_with_tracker(source, module_name_gen, options, &mut pass_tracker)?;
 
     Ok(LoweringResult {
         total_time: total_start.elapsed(),
@@ -100,15 +130,47 @@ where
     })
 }
 
+/// Lowers `source` with default options, recording every pass.
 pub fn lower_python_to_blockpy_for_testing(source: &str) -> Result<LoweringResult> {
-    lower_python_to_blockpy_with_tracker(source, ModuleNameGen::new(0), RecordingPassTracker::new())
+    lower_python_to_blockpy_for_testing_with_options(source, &LoweringOptions::default())
+}
+
+pub fn lower_python_to_blockpy_for_testing_with_options(
+    source: &str,
+    options: &LoweringOptions,
+) -> Result<LoweringResult> {
+    lower_python_to_blockpy_with_tracker(
+        source,
+        ModuleNameGen::new(0),
+        options,
+        RecordingPassTracker::new(),
+    )
 }
 
 pub fn lower_python_to_blockpy(
     source: &str,
     module_name_gen: ModuleNameGen,
+    options: &LoweringOptions,
 ) -> Result<LoweringResult<NoopPassTracker>> {
-    lower_python_to_blockpy_with_tracker(source, module_name_gen, NoopPassTracker::new())
+    lower_python_to_blockpy_with_tracker(source, module_name_gen, options, NoopPassTracker::new())
+}
+
+/// Reports the first error CPython's parser, symbol table or compiler would
+/// raise for `source`, without lowering it.
+pub fn check_syntax(source: &str) -> Result<()> {
+    parse_and_check_module(source, &mut NoopPassTracker::new()).map(|_| ())
+}
+
+/// Returns the scope tree the pipeline computes for `source`, shaped like
+/// CPython's `symtable.symtable(source, "<module>", "exec")`.
+pub fn symbol_table(source: &str) -> Result<symtable::SymbolTable> {
+    symbol_table_for_source(source)
+}
+
+/// Lists the loads of function locals that some path reaches before the
+/// local is assigned, where CPython would raise `UnboundLocalError`.
+pub fn maybe_unbound_locals(source: &str) -> Result<Vec<MaybeUnboundLocal>> {
+    maybe_unbound_locals_for_source(source)
 }
 
 pub trait ToRuffAst {
@@ -0,0 +1,157 @@
+//! Settings that change what the lowering pipeline produces.
+//!
+//! The library never reads the process environment while lowering; callers
+//! build a [`LoweringOptions`] up front, usually with
+//! [`LoweringOptions::from_env`] at the outermost layer (the import hook, the
+//! command-line tools), and pass it down.
+
+use crate::pass_registry::PassRegistry;
+use crate::passes::{parse_trace_config, TraceConfig};
+use std::env;
+
+/// The `-O` level a module is lowered for, mirroring `sys.flags.optimize`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum OptimizationLevel {
+    #[default]
+    None,
+    /// `-O`: `__debug__` is false, so asserts and `if __debug__:` blocks go.
+    Asserts,
+    /// `-OO`: as `-O`, and docstrings are dropped as well.
+    Docstrings,
+}
+
+impl OptimizationLevel {
+    /// Maps a `sys.flags.optimize` value; anything above 2 behaves like `-OO`.
+    pub fn from_flag(flag: i64) -> Self {
+        match flag {
+            i64::MIN..=0 => Self::None,
+            1 => Self::Asserts,
+            _ => Self::Docstrings,
+        }
+    }
+}
+
+/// How much of the pipeline's output is checked for internal consistency.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValidationLevel {
+    /// Only the final codegen module is validated.
+    Final,
+    /// Every tracked pass output is checked with `VerifyStage` as well.
+    EveryPass,
+}
+
+impl Default for ValidationLevel {
+    /// Unit tests of this crate always verify every pass.
+    fn default() -> Self {
+        if cfg!(test) {
+            Self::EveryPass
+        } else {
+            Self::Final
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct LoweringOptions {
+    pub optimization: OptimizationLevel,
+    pub validation: ValidationLevel,
+    /// Block-entry tracing (`DIET_PYTHON_BB_TRACE`).
+    pub trace: Option<TraceConfig>,
+    /// Per-site global load counters (`DIET_PYTHON_GLOBAL_LOAD_COUNTERS`).
+    pub global_load_counters: bool,
+    /// Inlining of small direct callees (`DIET_PYTHON_INLINE`).
+    pub inline: bool,
+    /// Hoisting of loop-invariant global loads into loop preheaders; on by
+    /// default, `DIET_PYTHON_HOIST_GLOBALS=0` turns it off.
+    pub hoist_global_loads: bool,
+    /// Lazy top-level imports for every module, not just those with the
+    /// `# soac: lazy-imports` pragma (`DIET_PYTHON_LAZY_IMPORTS`).
+    pub lazy_imports: bool,
+    /// Modules that stay eager under lazy imports (`DIET_PYTHON_EAGER_IMPORTS`).
+    pub eager_imports: Vec<String>,
+    /// Worker threads for per-callable passes; `None` uses every available
+    /// core (`DIET_PYTHON_LOWERING_THREADS`).
+    pub lowering_threads: Option<usize>,
+    /// Extra passes run at the driver's hook points.
+    pub passes: PassRegistry,
+}
+
+impl Default for LoweringOptions {
+    fn default() -> Self {
+        Self {
+            optimization: OptimizationLevel::default(),
+            validation: ValidationLevel::default(),
+            trace: None,
+            global_load_counters: false,
+            inline: false,
+            hoist_global_loads: true,
+            lazy_imports: false,
+            eager_imports: Vec::new(),
+            lowering_threads: None,
+            passes: PassRegistry::default(),
+        }
+    }
+}
+
+impl LoweringOptions {
+    /// Options from the `DIET_PYTHON_*` environment variables.
+    pub fn from_env() -> Self {
+        let mut options = Self {
+            trace: env::var("DIET_PYTHON_BB_TRACE")
+                .ok()
+                .and_then(|raw| parse_trace_config(raw.as_str())),
+            global_load_counters: env_flag("DIET_PYTHON_GLOBAL_LOAD_COUNTERS"),
+            inline: env_flag("DIET_PYTHON_INLINE"),
+            hoist_global_loads: env_flag_or("DIET_PYTHON_HOIST_GLOBALS", true),
+            lazy_imports: env_flag("DIET_PYTHON_LAZY_IMPORTS"),
+            eager_imports: env::var("DIET_PYTHON_EAGER_IMPORTS")
+                .map(|raw| split_module_list(raw.as_str()))
+                .unwrap_or_default(),
+            lowering_threads: env::var("DIET_PYTHON_LOWERING_THREADS")
+                .ok()
+                .and_then(|raw| raw.trim().parse::<usize>().ok())
+                .filter(|threads| *threads > 0),
+            ..Self::default()
+        };
+        if env_flag("DIET_PYTHON_VERIFY_PASSES") {
+            options.validation = ValidationLevel::EveryPass;
+        }
+        options
+    }
+
+    pub fn with_optimization(mut self, optimization: OptimizationLevel) -> Self {
+        self.optimization = optimization;
+        self
+    }
+
+    pub fn with_passes(mut self, passes: PassRegistry) -> Self {
+        self.passes = passes;
+        self
+    }
+}
+
+fn env_flag(name: &str) -> bool {
+    env_flag_or(name, false)
+}
+
+/// `default` when `name` is unset; otherwise false only for an empty or `0`
+/// value.
+fn env_flag_or(name: &str, default: bool) -> bool {
+    env::var(name)
+        .map(|raw| {
+            let trimmed = raw.trim();
+            !(trimmed.is_empty() || trimmed == "0")
+        })
+        .unwrap_or(default)
+}
+
+pub(crate) fn split_module_list(raw: &str) -> Vec<String> {
+    raw.split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+#[cfg(test)]
+mod test;
@@ -0,0 +1,39 @@
+use super::{split_module_list, OptimizationLevel, ValidationLevel};
+use crate::LoweringOptions;
+
+#[test]
+fn optimization_level_follows_sys_flags_optimize() {
+    assert_eq!(OptimizationLevel::from_flag(0), OptimizationLevel::None);
+    assert_eq!(OptimizationLevel::from_flag(1), OptimizationLevel::Asserts);
+    assert_eq!(
+        OptimizationLevel::from_flag(2),
+        OptimizationLevel::Docstrings
+    );
+    assert_eq!(
+        OptimizationLevel::from_flag(5),
+        OptimizationLevel::Docstrings
+    );
+    assert!(OptimizationLevel::Asserts < OptimizationLevel::Docstrings);
+}
+
+#[test]
+fn default_options_enable_no_instrumentation() {
+    let options = LoweringOptions::default();
+    assert_eq!(options.optimization, OptimizationLevel::None);
+    assert_eq!(options.validation, ValidationLevel::EveryPass);
+    assert_eq!(options.trace, None);
+    assert!(!options.global_load_counters);
+    assert!(!options.inline);
+    assert!(options.hoist_global_loads);
+    assert!(!options.lazy_imports);
+    assert!(options.eager_imports.is_empty());
+    assert_eq!(options.lowering_threads, None);
+}
+
+#[test]
+fn module_lists_skip_blank_entries() {
+    assert_eq!(
+        split_module_list(" a, ,b.c ,"),
+        vec!["a".to_string(), "b.c".to_string()]
+    );
+}
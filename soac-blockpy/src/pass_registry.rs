@@ -0,0 +1,95 @@
+//! Extra passes supplied by code embedding the lowering pipeline.
+//!
+//! A [`PassRegistry`] travels in [`LoweringOptions`](crate::LoweringOptions)
+//! and holds passes that the driver runs at fixed hook points:
+//!
+//! - after `core_blockpy`: control flow, `await` and generators are lowered,
+//!   but names are still unresolved, so calls to a given builtin are easy to
+//!   spot;
+//! - after `bb_codegen`: the module is in its codegen form, before inlining
+//!   and the built-in instrumentation passes;
+//! - before `validate`: the module is exactly what will be compiled.
+//!
+//! Each pass is tracked under its own name like a built-in stage, so it shows
+//! up in recorded pass dumps and timings and is verified after it runs under
+//! [`ValidationLevel::EveryPass`](crate::ValidationLevel::EveryPass). Names
+//! must not collide with each other or with a built-in stage; lowering fails
+//! with an internal error before running anything if they do. Passes
+//! typically drive a [`Visit`](crate::block_py::Visit) or
+//! [`VisitMut`](crate::block_py::VisitMut) over the stage's expression type,
+//! or rebuild callables with [`MapInstr`](crate::block_py::MapInstr).
+
+use crate::block_py::{BlockPyModule, BlockPyPass};
+use crate::diagnostic::Diagnostic;
+use crate::passes::{CodegenBlockPyPass, CoreBlockPyPass};
+use std::fmt;
+use std::sync::Arc;
+
+/// A whole-module pass over the BlockPy stage `P`.
+pub trait ModulePass<P: BlockPyPass>: Send + Sync {
+    /// The name the pass is tracked under.
+    fn name(&self) -> &str;
+
+    /// Rewrites `module` in place, or rejects it; the diagnostic fails the
+    /// lowering like one raised by a built-in pass.
+    fn run(&self, module: &mut BlockPyModule<P>) -> Result<(), Diagnostic>;
+}
+
+#[derive(Clone, Default)]
+pub struct PassRegistry {
+    after_core_blockpy: Vec<Arc<dyn ModulePass<CoreBlockPyPass>>>,
+    after_codegen: Vec<Arc<dyn ModulePass<CodegenBlockPyPass>>>,
+    before_validate: Vec<Arc<dyn ModulePass<CodegenBlockPyPass>>>,
+}
+
+impl PassRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Runs `pass` once generators are lowered, before name binding.
+    pub fn after_core_blockpy(mut self, pass: impl ModulePass<CoreBlockPyPass> + 'static) -> Self {
+        self.after_core_blockpy.push(Arc::new(pass));
+        self
+    }
+
+    /// Runs `pass` on the codegen module, before inlining and instrumentation.
+    pub fn after_codegen(mut self, pass: impl ModulePass<CodegenBlockPyPass> + 'static) -> Self {
+        self.after_codegen.push(Arc::new(pass));
+        self
+    }
+
+    /// Runs `pass` last, on the module that is about to be validated.
+    pub fn before_validate(mut self, pass: impl ModulePass<CodegenBlockPyPass> + 'static) -> Self {
+        self.before_validate.push(Arc::new(pass));
+        self
+    }
+
+    pub(crate) fn after_core_blockpy_passes(&self) -> &[Arc<dyn ModulePass<CoreBlockPyPass>>] {
+        &self.after_core_blockpy
+    }
+
+    pub(crate) fn after_codegen_passes(&self) -> &[Arc<dyn ModulePass<CodegenBlockPyPass>>] {
+        &self.after_codegen
+    }
+
+    pub(crate) fn before_validate_passes(&self) -> &[Arc<dyn ModulePass<CodegenBlockPyPass>>] {
+        &self.before_validate
+    }
+}
+
+impl fmt::Debug for PassRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fn names<P: BlockPyPass>(passes: &[Arc<dyn ModulePass<P>>]) -> Vec<&str> {
+            passes.iter().map(|pass| pass.name()).collect()
+        }
+        f.debug_struct("PassRegistry")
+            .field("after_core_blockpy", &names(&self.after_core_blockpy))
+            .field("after_codegen", &names(&self.after_codegen))
+            .field("before_validate", &names(&self.before_validate))
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod test;
@@ -0,0 +1,182 @@
+use super::{ModulePass, PassRegistry};
+use crate::block_py::{
+    walk_expr, BlockPyModule, BlockPyNameLike, CodegenBlockPyExpr, CoreBlockPyExpr, HasMeta, Visit,
+};
+use crate::diagnostic::{Diagnostic, DiagnosticKind};
+use crate::passes::{CodegenBlockPyPass, CoreBlockPyPass};
+use crate::{lower_python_to_blockpy_for_testing_with_options, LoweringOptions};
+use ruff_text_size::TextRange;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+struct EvalCalls(Vec<TextRange>);
+
+impl Visit<CoreBlockPyExpr> for EvalCalls {
+    fn visit_instr(&mut self, expr: &CoreBlockPyExpr) {
+        if let CoreBlockPyExpr::Call(call) = expr {
+            if matches!(
+                call.func.as_ref(),
+                CoreBlockPyExpr::Load(op) if !op.name.is_runtime_name() && op.name.id_str() == "eval"
+            ) {
+                self.0.push(call.meta().range);
+            }
+        }
+        walk_expr(self, expr);
+    }
+}
+
+struct ForbidEval;
+
+impl ModulePass<CoreBlockPyPass> for ForbidEval {
+    fn name(&self) -> &str {
+        "forbid_eval"
+    }
+
+    fn run(&self, module: &mut BlockPyModule<CoreBlockPyPass>) -> Result<(), Diagnostic> {
+        let mut calls = EvalCalls(Vec::new());
+        calls.visit_module(module);
+        match calls.0.first() {
+            Some(range) => Err(Diagnostic::unsupported("eval() is not allowed", *range)),
+            None => Ok(()),
+        }
+    }
+}
+
+struct CallCounter(usize);
+
+impl Visit<CodegenBlockPyExpr> for CallCounter {
+    fn visit_instr(&mut self, expr: &CodegenBlockPyExpr) {
+        if matches!(expr, CodegenBlockPyExpr::Call(_)) {
+            self.0 += 1;
+        }
+        walk_expr(self, expr);
+    }
+}
+
+struct CountCalls(Arc<AtomicUsize>);
+
+impl ModulePass<CodegenBlockPyPass> for CountCalls {
+    fn name(&self) -> &str {
+        "count_calls"
+    }
+
+    fn run(&self, module: &mut BlockPyModule<CodegenBlockPyPass>) -> Result<(), Diagnostic> {
+        let mut counter = CallCounter(0);
+        counter.visit_module(module);
+        self.0.fetch_add(counter.0, Ordering::Relaxed);
+        Ok(())
+    }
+}
+
+struct StripDocs;
+
+impl ModulePass<CodegenBlockPyPass> for StripDocs {
+    fn name(&self) -> &str {
+        "strip_docs"
+    }
+
+    fn run(&self, module: &mut BlockPyModule<CodegenBlockPyPass>) -> Result<(), Diagnostic> {
+        for func in &mut module.callable_defs {
+            func.doc = None;
+        }
+        Ok(())
+    }
+}
+
+struct Renamed(&'static str);
+
+impl ModulePass<CodegenBlockPyPass> for Renamed {
+    fn name(&self) -> &str {
+        self.0
+    }
+
+    fn run(&self, _module: &mut BlockPyModule<CodegenBlockPyPass>) -> Result<(), Diagnostic> {
+        Ok(())
+    }
+}
+
+#[test]
+fn registered_pass_can_reject_module_with_diagnostic() {
+    let options =
+        LoweringOptions::default().with_passes(PassRegistry::new().after_core_blockpy(ForbidEval));
+
+    lower_python_to_blockpy_for_testing_with_options("def f(x):\n    return len(x)\n", &options)
+        .expect("module without eval should lower");
+    let err = match lower_python_to_blockpy_for_testing_with_options(
+        "def f(src):\n    return eval(src)\n",
+        &options,
+    ) {
+        Ok(_) => panic!("eval call should be rejected"),
+        Err(err) => err,
+    };
+    let diagnostic = err.to_diagnostic();
+
+    assert_eq!(diagnostic.kind, DiagnosticKind::Unsupported);
+    assert_eq!(diagnostic.message, "eval() is not allowed");
+}
+
+#[test]
+fn registered_passes_run_as_tracked_stages_at_their_hook_points() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let options = LoweringOptions::default().with_passes(
+        PassRegistry::new()
+            .before_validate(StripDocs)
+            .after_codegen(CountCalls(calls.clone())),
+    );
+    let source = "def f(x):\n    \"\"\"doc\"\"\"\n    print(x)\n    return len(x)\n";
+
+    let lowered = lower_python_to_blockpy_for_testing_with_options(source, &options)
+        .expect("lowering should succeed");
+    let names = lowered.pass_tracker.pass_names().collect::<Vec<_>>();
+    let codegen = names
+        .iter()
+        .position(|name| *name == "bb_codegen")
+        .expect("bb_codegen should be tracked");
+
+    assert_eq!(
+        names[codegen..],
+        [
+            "bb_codegen",
+            "count_calls",
+            "bb_hoist_global_loads",
+            "strip_docs"
+        ]
+    );
+    assert!(calls.load(Ordering::Relaxed) >= 2);
+    assert!(lowered
+        .codegen_module
+        .callable_defs
+        .iter()
+        .all(|func| func.doc.is_none()));
+    assert!(lowered
+        .pass_tracker
+        .get::<BlockPyModule<CodegenBlockPyPass>>("count_calls")
+        .is_some());
+    assert!(format!("{options:?}").contains("after_codegen: [\"count_calls\"]"));
+}
+
+#[test]
+fn registered_pass_names_must_be_unique_and_not_shadow_stages() {
+    for (registry, message) in [
+        (
+            PassRegistry::new()
+                .after_codegen(StripDocs)
+                .before_validate(Renamed("strip_docs")),
+            "more than one registered pass is named `strip_docs`",
+        ),
+        (
+            PassRegistry::new().after_codegen(Renamed("bb_codegen")),
+            "registered pass `bb_codegen` has the name of a built-in stage",
+        ),
+    ] {
+        let options = LoweringOptions::default().with_passes(registry);
+        let err = match lower_python_to_blockpy_for_testing_with_options("x = 1\n", &options) {
+            Ok(_) => panic!("colliding pass names should be rejected"),
+            Err(err) => err,
+        };
+        let diagnostic = err.to_diagnostic();
+
+        assert_eq!(diagnostic.kind, DiagnosticKind::Internal);
+        assert_eq!(diagnostic.message, message);
+    }
+}
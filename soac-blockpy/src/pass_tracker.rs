@@ -1,23 +1,40 @@
 use crate::block_py::pretty::BlockPyPrettyPrint;
-use crate::block_py::BlockPyModule;
+use crate::block_py::stats::StageStats;
+use crate::block_py::validate::VerifyStage;
+use crate::block_py::{BlockPyModule, IrStats};
+use crate::diagnostic::Diagnostic;
 use crate::passes::ast_to_ast::body::Suite;
 use crate::passes::{
     CoreBlockPyPass, CoreBlockPyPassWithAwaitAndYield, ResolvedStorageBlockPyPass,
 };
+use crate::ValidationLevel;
 use ruff_python_ast::{self as ast, ModModule};
 use ruff_text_size::TextRange;
+use serde_json::{json, Value};
 use std::any::Any;
 use std::time::{Duration, Instant};
 
 #[derive(Debug, Clone)]
 pub struct PassTiming {
     pub name: String,
+    /// When the pass started, relative to the tracker's creation.
+    pub start: Duration,
     pub elapsed: Duration,
+    pub parallel: Option<ParallelTiming>,
+}
+
+/// Worker time spent in a pass that lowers callables in parallel. The
+/// speedup over a sequential run is roughly `busy / elapsed`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParallelTiming {
+    pub threads: usize,
+    pub busy: Duration,
 }
 
 struct TrackedPass {
     name: String,
     value: Box<dyn Any>,
+    ir_stats: Option<IrStats>,
     render_text: Option<fn(&dyn Any) -> String>,
     render_debug_text: Option<fn(&dyn Any) -> String>,
 }
@@ -26,19 +43,45 @@ struct TrackedPass {
 pub struct NoopPassTracker;
 
 pub struct RecordingPassTracker {
+    origin: Instant,
     passes: Vec<TrackedPass>,
     timings: Vec<PassTiming>,
 }
 
+/// Wraps the caller's tracker for one lowering run. With
+/// [`ValidationLevel::EveryPass`], a stage whose output breaks its invariants
+/// fails with an internal diagnostic naming the stage.
+pub(crate) struct VerifyingPassTracker<'a, P> {
+    inner: &'a mut P,
+    validation: ValidationLevel,
+}
+
 pub(crate) trait PassTracker {
-    fn run_pass<T, F>(&mut self, name: &str, build: F) -> T
+    /// Runs one pipeline stage that may reject its input. A stage that fails
+    /// keeps its timing but records no output.
+    fn try_run_pass<T, E, F>(&mut self, name: &str, build: F) -> Result<T, E>
     where
-        T: Clone + Any + BlockPyPrettyPrint,
-        F: FnOnce() -> T;
+        T: Clone + Any + BlockPyPrettyPrint + VerifyStage + StageStats,
+        E: From<Diagnostic>,
+        F: FnOnce() -> Result<T, E>;
+
+    /// Runs one pipeline stage. It fails only if the tracker rejects the
+    /// stage's output.
+    fn run_pass<T, F>(&mut self, name: &str, build: F) -> Result<T, Diagnostic>
+    where
+        T: Clone + Any + BlockPyPrettyPrint + VerifyStage + StageStats,
+        F: FnOnce() -> T,
+    {
+        self.try_run_pass(name, || Ok(build()))
+    }
 
     fn record_timing<T, F>(&mut self, name: &str, build: F) -> T
     where
         F: FnOnce() -> T;
+
+    /// Attaches worker timings to the already-recorded pass `name`. Trackers
+    /// with no timing for that pass ignore the call.
+    fn record_parallel_timing(&mut self, name: &str, timing: ParallelTiming);
 }
 
 impl BlockPyPrettyPrint for Suite {
@@ -47,6 +90,18 @@ impl BlockPyPrettyPrint for Suite {
     }
 }
 
+impl VerifyStage for Suite {
+    fn verify_stage(&self) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+impl StageStats for Suite {
+    fn ir_stats(&self) -> Option<IrStats> {
+        None
+    }
+}
+
 impl BlockPyPrettyPrint for ModModule {
     fn pretty_print(&self) -> String {
         crate::ruff_ast_to_string(&self.body)
@@ -73,6 +128,43 @@ where
         .debug_pretty_print()
 }
 
+impl<'a, P: PassTracker> VerifyingPassTracker<'a, P> {
+    pub(crate) fn new(inner: &'a mut P, validation: ValidationLevel) -> Self {
+        Self { inner, validation }
+    }
+}
+
+impl<P: PassTracker> PassTracker for VerifyingPassTracker<'_, P> {
+    fn try_run_pass<T, E, F>(&mut self, name: &str, build: F) -> Result<T, E>
+    where
+        T: Clone + Any + BlockPyPrettyPrint + VerifyStage + StageStats,
+        E: From<Diagnostic>,
+        F: FnOnce() -> Result<T, E>,
+    {
+        let value = self.inner.try_run_pass(name, build)?;
+        if self.validation == ValidationLevel::EveryPass {
+            if let Err(err) = value.verify_stage() {
+                return Err(Diagnostic::internal(format!(
+                    "pass {name} produced invalid output: {err}"
+                ))
+                .into());
+            }
+        }
+        Ok(value)
+    }
+
+    fn record_timing<T, F>(&mut self, name: &str, build: F) -> T
+    where
+        F: FnOnce() -> T,
+    {
+        self.inner.record_timing(name, build)
+    }
+
+    fn record_parallel_timing(&mut self, name: &str, timing: ParallelTiming) {
+        self.inner.record_parallel_timing(name, timing);
+    }
+}
+
 impl NoopPassTracker {
     pub fn new() -> Self {
         Self
@@ -80,10 +172,11 @@ impl NoopPassTracker {
 }
 
 impl PassTracker for NoopPassTracker {
-    fn run_pass<T, F>(&mut self, _name: &str, build: F) -> T
+    fn try_run_pass<T, E, F>(&mut self, _name: &str, build: F) -> Result<T, E>
     where
-        T: Clone + Any + BlockPyPrettyPrint,
-        F: FnOnce() -> T,
+        T: Clone + Any + BlockPyPrettyPrint + VerifyStage + StageStats,
+        E: From<Diagnostic>,
+        F: FnOnce() -> Result<T, E>,
     {
         build()
     }
@@ -94,24 +187,29 @@ impl PassTracker for NoopPassTracker {
     {
         build()
     }
+
+    fn record_parallel_timing(&mut self, _name: &str, _timing: ParallelTiming) {}
 }
 
 impl RecordingPassTracker {
     pub fn new() -> Self {
         Self {
+            origin: Instant::now(),
             passes: Vec::new(),
             timings: Vec::new(),
         }
     }
 
-    fn record_pass_timing(&mut self, name: &str, elapsed: Duration) {
+    fn record_pass_timing(&mut self, name: &str, start: Instant, elapsed: Duration) {
         assert!(
             !self.timings.iter().any(|timing| timing.name == name),
             "PassTracker already contains a pass named {name}",
         );
         self.timings.push(PassTiming {
             name: name.to_string(),
+            start: start.duration_since(self.origin),
             elapsed,
+            parallel: None,
         });
     }
 
@@ -165,22 +263,82 @@ impl RecordingPassTracker {
     pub fn pass_timings(&self) -> impl Iterator<Item = PassTiming> + '_ {
         self.timings.iter().cloned()
     }
+
+    /// IR size metrics of a tracked pass's output; AST-level passes have none.
+    pub fn pass_ir_stats(&self, name: &str) -> Option<&IrStats> {
+        self.passes
+            .iter()
+            .find(|pass| pass.name == name)
+            .and_then(|pass| pass.ir_stats.as_ref())
+    }
+
+    /// The recorded pipeline in the Chrome trace-event format, for
+    /// `chrome://tracing` or Perfetto. Every timed pass is a complete event
+    /// carrying its worker time and IR stats; the IR size after each BlockPy
+    /// pass is also emitted as a counter track.
+    pub fn chrome_trace(&self) -> Value {
+        let mut events = Vec::new();
+        for timing in &self.timings {
+            let stats = self.pass_ir_stats(&timing.name);
+            let mut args = json!({});
+            if let Some(parallel) = timing.parallel {
+                args["threads"] = json!(parallel.threads);
+                args["busy_us"] = json!(trace_micros(parallel.busy));
+            }
+            if let Some(stats) = stats {
+                args["ir"] = stats.to_json();
+            }
+            events.push(json!({
+                "name": timing.name,
+                "cat": "pass",
+                "ph": "X",
+                "ts": trace_micros(timing.start),
+                "dur": trace_micros(timing.elapsed),
+                "pid": 1,
+                "tid": 1,
+                "args": args,
+            }));
+            if let Some(stats) = stats {
+                events.push(json!({
+                    "name": "ir size",
+                    "ph": "C",
+                    "ts": trace_micros(timing.start + timing.elapsed),
+                    "pid": 1,
+                    "args": {
+                        "functions": stats.functions,
+                        "blocks": stats.blocks,
+                        "instructions": stats.instructions,
+                    },
+                }));
+            }
+        }
+        json!({
+            "traceEvents": events,
+            "displayTimeUnit": "ms",
+        })
+    }
+}
+
+fn trace_micros(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1_000_000.0
 }
 
 impl PassTracker for RecordingPassTracker {
-    fn run_pass<T, F>(&mut self, name: &str, build: F) -> T
+    fn try_run_pass<T, E, F>(&mut self, name: &str, build: F) -> Result<T, E>
     where
-        T: Clone + Any + BlockPyPrettyPrint,
-        F: FnOnce() -> T,
+        T: Clone + Any + BlockPyPrettyPrint + VerifyStage + StageStats,
+        E: From<Diagnostic>,
+        F: FnOnce() -> Result<T, E>,
     {
-        let value = self.record_timing(name, build);
+        let value = self.record_timing(name, build)?;
         self.passes.push(TrackedPass {
             name: name.to_string(),
             value: Box::new(value.clone()),
+            ir_stats: value.ir_stats(),
             render_text: Some(render_tracked_pass_value::<T>),
             render_debug_text: Some(render_tracked_pass_debug_value::<T>),
         });
-        value
+        Ok(value)
     }
 
     fn record_timing<T, F>(&mut self, name: &str, build: F) -> T
@@ -190,7 +348,13 @@ impl PassTracker for RecordingPassTracker {
         let start = Instant::now();
         let value = build();
         let elapsed = start.elapsed();
-        self.record_pass_timing(name, elapsed);
+        self.record_pass_timing(name, start, elapsed);
         value
     }
+
+    fn record_parallel_timing(&mut self, name: &str, timing: ParallelTiming) {
+        if let Some(pass) = self.timings.iter_mut().find(|pass| pass.name == name) {
+            pass.parallel = Some(timing);
+        }
+    }
 }
@@ -1,4 +1,5 @@
 use super::{rewrite_with_pass, ExprRewritePass, LoweredExpr};
+use crate::block_py::ModuleNameGen;
 use crate::passes::ast_to_ast::context::Context;
 use crate::py_expr;
 use ruff_python_ast::Expr;
@@ -24,7 +25,7 @@ def f():
     return x
 "#;
     let mut module = parse_module(source).unwrap().into_syntax().body;
-    let context = Context::new(source);
+    let context = Context::new(source, ModuleNameGen::default());
 
     rewrite_with_pass(&context, None, Some(&RenameXExprPass), &mut module);
 
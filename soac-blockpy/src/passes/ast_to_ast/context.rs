@@ -1,9 +1,9 @@
 use std::cell::RefCell;
 use std::collections::HashSet;
 
+use crate::block_py::ModuleNameGen;
 use crate::passes::ast_to_ast::scope_helpers::ScopeKind;
-
-use crate::namegen::fresh_name;
+use crate::LoweringOptions;
 
 #[derive(Clone, Debug)]
 pub struct ScopeFrame {
@@ -35,17 +35,30 @@ impl ScopeFrame {
 
 pub struct Context {
     pub source: String,
+    name_gen: ModuleNameGen,
+    options: LoweringOptions,
     scope_stack: RefCell<Vec<ScopeFrame>>,
 }
 
 impl Context {
-    pub fn new(source: &str) -> Self {
+    pub fn new(source: &str, name_gen: ModuleNameGen) -> Self {
         Self {
             source: source.to_string(),
+            name_gen,
+            options: LoweringOptions::default(),
             scope_stack: RefCell::new(vec![ScopeFrame::module()]),
         }
     }
 
+    pub fn with_options(mut self, options: LoweringOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    pub fn options(&self) -> &LoweringOptions {
+        &self.options
+    }
+
     pub fn line_number_at(&self, offset: usize) -> usize {
         self.source[..offset]
             .bytes()
@@ -55,7 +68,11 @@ impl Context {
     }
 
     pub fn fresh(&self, name: &str) -> String {
-        fresh_name(name)
+        self.name_gen.next_tmp_name(name)
+    }
+
+    pub fn name_gen(&self) -> &ModuleNameGen {
+        &self.name_gen
     }
 
     pub fn push_scope(&self, frame: ScopeFrame) {
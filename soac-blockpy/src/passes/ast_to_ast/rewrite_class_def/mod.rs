@@ -160,7 +160,6 @@ def _dp_define_class_{class_name:id}(_dp_class_ns_fn, _dp_class_ns_outer, _dp_pr
     )
 "#,
         class_name = class_name.as_str(),
-        type_param_bindings = type_param_bindings.clone(),
         requires_class_cell = needs_class_cell,
         type_param_bindings = type_param_bindings,
         firstlineno = class_firstlineno,
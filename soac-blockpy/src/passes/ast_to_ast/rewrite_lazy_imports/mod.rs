@@ -0,0 +1,236 @@
+//! Opt-in lazy binding of top-level imports.
+//!
+//! A module opts in with a `# soac: lazy-imports` comment, or every module
+//! does with `LoweringOptions::lazy_imports` (`DIET_PYTHON_LAZY_IMPORTS`).
+//! Each `import` statement directly in the module body then registers a
+//! pending `__soac__.lazy_import(...)` for its binding instead of running the
+//! import. Pending imports live in a side table, never in the module's
+//! `__dict__`. The first load of the name, from the module's own code or a
+//! class body, or as an attribute of the module from outside, resolves the
+//! import and binds the result in the globals dict, so the global slot cache
+//! holds the real module from then on.
+//!
+//! `dir()` of the module lists pending imports, and a star import from a
+//! module without `__all__` resolves them first (see
+//! `soac.runtime._LazyModule`). The module's `globals()` dict cannot show them,
+//! so a module that calls `globals()`, or `locals()` or `vars()` at module
+//! level, keeps every import eager.
+//!
+//! Imports nested in `try`, `if`, `with` and friends stay eager, since the
+//! surrounding code usually relies on the import running there. So do star
+//! imports, dotted imports without `as`, and modules named by
+//! `# soac: eager-imports a, b.c` or `LoweringOptions::eager_imports` (a name
+//! also covers its submodules), for imports whose side effects have to happen
+//! at module init.
+
+use crate::options::split_module_list;
+use crate::passes::ast_to_ast::body::Suite;
+use crate::transformer::{walk_expr, walk_stmt, Transformer};
+use crate::LoweringOptions;
+use ruff_python_ast::{self as ast, Expr, Stmt};
+use ruff_python_parser::parse_module;
+
+const LAZY_IMPORTS_PRAGMA: &str = "# soac: lazy-imports";
+const EAGER_IMPORTS_PRAGMA: &str = "# soac: eager-imports";
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct LazyImportConfig {
+    pub(crate) eager_modules: Vec<String>,
+}
+
+impl LazyImportConfig {
+    /// Lazy import settings for `source`, or `None` when neither its pragma
+    /// nor `options` asks for lazy imports.
+    pub(crate) fn for_source(source: &str, options: &LoweringOptions) -> Option<Self> {
+        let mut config = Self::from_pragmas(source);
+        if config.is_none() && options.lazy_imports {
+            config = Some(Self::default());
+        }
+        let mut config = config?;
+        config
+            .eager_modules
+            .extend(options.eager_imports.iter().cloned());
+        Some(config)
+    }
+
+    pub(crate) fn from_pragmas(source: &str) -> Option<Self> {
+        let mut enabled = false;
+        let mut eager_modules = Vec::new();
+        for line in source.lines().map(str::trim) {
+            if line == LAZY_IMPORTS_PRAGMA {
+                enabled = true;
+            } else if let Some(modules) = line.strip_prefix(EAGER_IMPORTS_PRAGMA) {
+                eager_modules.extend(split_module_list(modules));
+            }
+        }
+        enabled.then_some(Self { eager_modules })
+    }
+
+    fn is_eager(&self, module_name: &str) -> bool {
+        self.eager_modules.iter().any(|eager| {
+            module_name == eager
+                || module_name
+                    .strip_prefix(eager.as_str())
+                    .is_some_and(|rest| rest.starts_with('.'))
+        })
+    }
+}
+
+pub fn rewrite(source: &str, options: &LoweringOptions, body: &mut Suite) {
+    if let Some(config) = LazyImportConfig::for_source(source, options) {
+        rewrite_with_config(&config, body);
+    }
+}
+
+pub(crate) fn rewrite_with_config(config: &LazyImportConfig, body: &mut Suite) {
+    if reads_module_namespace(body) {
+        return;
+    }
+    let mut index = 0;
+    while index < body.len() {
+        let replacement = match &body[index] {
+            Stmt::Import(import) => lazy_import(config, import),
+            Stmt::ImportFrom(import_from) => lazy_import_from(config, import_from),
+            _ => None,
+        };
+        match replacement {
+            Some(stmts) => {
+                let count = stmts.len();
+                body.splice(index..=index, stmts);
+                index += count;
+            }
+            None => index += 1,
+        }
+    }
+}
+
+/// Whether the module reads its namespace as a dict, through `globals()`
+/// anywhere or `locals()` / `vars()` outside any function or class.
+fn reads_module_namespace(body: &mut Suite) -> bool {
+    #[derive(Default)]
+    struct NamespaceReads {
+        nested: usize,
+        found: bool,
+    }
+
+    impl Transformer for NamespaceReads {
+        fn visit_stmt(&mut self, stmt: &mut Stmt) {
+            let scope = matches!(stmt, Stmt::FunctionDef(_) | Stmt::ClassDef(_));
+            self.nested += usize::from(scope);
+            walk_stmt(self, stmt);
+            self.nested -= usize::from(scope);
+        }
+
+        fn visit_expr(&mut self, expr: &mut Expr) {
+            let scope = matches!(expr, Expr::Lambda(_));
+            if let Expr::Name(name) = expr {
+                match name.id.as_str() {
+                    "globals" => self.found = true,
+                    "locals" | "vars" if self.nested == 0 => self.found = true,
+                    _ => {}
+                }
+            }
+            self.nested += usize::from(scope);
+            walk_expr(self, expr);
+            self.nested -= usize::from(scope);
+        }
+    }
+
+    let mut reads = NamespaceReads::default();
+    for stmt in body.iter_mut() {
+        reads.visit_stmt(stmt);
+    }
+    reads.found
+}
+
+fn lazy_import(config: &LazyImportConfig, import: &ast::StmtImport) -> Option<Vec<Stmt>> {
+    // `import a.b` and `import a.c` both bind `a`, and a proxy for the second
+    // would drop the first, so dotted imports without `as` stay eager.
+    if import.names.iter().any(|alias| {
+        config.is_eager(alias.name.id.as_str())
+            || (alias.asname.is_none() && alias.name.id.as_str().contains('.'))
+    }) {
+        return None;
+    }
+    let source = import
+        .names
+        .iter()
+        .map(|alias| {
+            let module_name = alias.name.id.as_str();
+            match &alias.asname {
+                // `import a.b as c` binds the submodule itself.
+                Some(asname) => {
+                    let attrs = module_name.split('.').skip(1).collect::<Vec<_>>();
+                    format!(
+                        "__soac__.lazy_import(globals(), {asname:?}, {module_name:?}, __spec__, None, 0, {attrs})",
+                        asname = asname.id.as_str(),
+                        attrs = tuple_literal(&attrs),
+                    )
+                }
+                None => format!(
+                    "__soac__.lazy_import(globals(), {module_name:?}, {module_name:?}, __spec__)"
+                ),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    Some(parse_stmts(source.as_str()))
+}
+
+fn lazy_import_from(
+    config: &LazyImportConfig,
+    import_from: &ast::StmtImportFrom,
+) -> Option<Vec<Stmt>> {
+    let module_name = import_from
+        .module
+        .as_ref()
+        .map(|name| name.id.as_str())
+        .unwrap_or("");
+    let is_eager = config.is_eager(module_name)
+        || import_from.names.iter().any(|alias| {
+            alias.name.id.as_str() == "*"
+                || config.is_eager(format!("{module_name}.{}", alias.name.id.as_str()).as_str())
+        });
+    if is_eager {
+        return None;
+    }
+    let source = import_from
+        .names
+        .iter()
+        .map(|alias| {
+            let attr = alias.name.id.as_str();
+            format!(
+                "__soac__.lazy_import(globals(), {binding:?}, {module_name:?}, __spec__, [{attr:?}], {level}, {attrs})",
+                binding = alias.asname.as_ref().unwrap_or(&alias.name).id.as_str(),
+                level = import_from.level,
+                attrs = tuple_literal(&[attr]),
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    Some(parse_stmts(source.as_str()))
+}
+
+fn tuple_literal(items: &[&str]) -> String {
+    match items {
+        [item] => format!("({item:?},)"),
+        _ => format!(
+            "({})",
+            items
+                .iter()
+                .map(|item| format!("{item:?}"))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+    }
+}
+
+fn parse_stmts(source: &str) -> Vec<Stmt> {
+    parse_module(source)
+        .expect("failed to parse rewritten lazy import")
+        .into_syntax()
+        .body
+}
+
+#[cfg(test)]
+mod test;
@@ -0,0 +1,117 @@
+use super::{rewrite_with_config, LazyImportConfig};
+use crate::LoweringOptions;
+use ruff_python_parser::parse_module;
+
+fn rewrite_module(source: &str) -> String {
+    let config = LazyImportConfig::from_pragmas(source).expect("source should opt in");
+    let mut module = parse_module(source)
+        .expect("parse should succeed")
+        .into_syntax();
+    rewrite_with_config(&config, &mut module.body);
+    crate::ruff_ast_to_string(&module.body)
+}
+
+#[test]
+fn pragma_is_required() {
+    assert_eq!(LazyImportConfig::from_pragmas("import json\n"), None);
+    assert_eq!(
+        LazyImportConfig::from_pragmas("# soac: lazy-imports\n# soac: eager-imports a, b.c\n"),
+        Some(LazyImportConfig {
+            eager_modules: vec!["a".to_string(), "b.c".to_string()],
+        })
+    );
+}
+
+#[test]
+fn options_enable_lazy_imports_without_pragma() {
+    let options = LoweringOptions {
+        lazy_imports: true,
+        eager_imports: vec!["json".to_string()],
+        ..LoweringOptions::default()
+    };
+    assert_eq!(
+        LazyImportConfig::for_source("import json\n", &LoweringOptions::default()),
+        None
+    );
+    assert_eq!(
+        LazyImportConfig::for_source("# soac: eager-imports os\nimport json\n", &options),
+        Some(LazyImportConfig {
+            eager_modules: vec!["os".to_string(), "json".to_string()],
+        })
+    );
+}
+
+#[test]
+fn top_level_imports_register_lazy_imports() {
+    let rendered = rewrite_module(concat!(
+        "# soac: lazy-imports\n",
+        "import json\n",
+        "import os.path as osp\n",
+        "from collections import OrderedDict as OD, deque\n",
+        "from . import sibling\n",
+    ));
+
+    assert!(
+        rendered.contains("__soac__.lazy_import(globals(), \"json\", \"json\", __spec__)"),
+        "{rendered}"
+    );
+    assert!(
+        rendered.contains(
+            "__soac__.lazy_import(globals(), \"osp\", \"os.path\", __spec__, None, 0, (\"path\",))"
+        ),
+        "{rendered}"
+    );
+    assert!(
+        rendered.contains(
+            "__soac__.lazy_import(globals(), \"OD\", \"collections\", __spec__, [\"OrderedDict\"], 0, (\"OrderedDict\",))"
+        ),
+        "{rendered}"
+    );
+    assert!(
+        rendered.contains(
+            "__soac__.lazy_import(globals(), \"deque\", \"collections\", __spec__, [\"deque\"], 0, (\"deque\",))"
+        ),
+        "{rendered}"
+    );
+    assert!(
+        rendered.contains(
+            "__soac__.lazy_import(globals(), \"sibling\", \"\", __spec__, [\"sibling\"], 1, (\"sibling\",))"
+        ),
+        "{rendered}"
+    );
+}
+
+#[test]
+fn nested_star_dotted_and_eager_imports_stay_eager() {
+    let rendered = rewrite_module(concat!(
+        "# soac: lazy-imports\n",
+        "# soac: eager-imports logging, pkg.plugins\n",
+        "import logging.handlers as handlers\n",
+        "from pkg import plugins\n",
+        "from os import *\n",
+        "import os.path\n",
+        "try:\n",
+        "    import json\n",
+        "except ImportError:\n",
+        "    json = None\n",
+    ));
+
+    assert!(!rendered.contains("lazy_import"), "{rendered}");
+}
+
+#[test]
+fn modules_reading_their_namespace_stay_eager() {
+    for source in [
+        "# soac: lazy-imports\nimport json\n\ndef names():\n    return globals()\n",
+        "# soac: lazy-imports\nimport json\nNAMES = vars()\n",
+        "# soac: lazy-imports\nimport json\nNAMES = locals()\n",
+    ] {
+        let rendered = rewrite_module(source);
+        assert!(!rendered.contains("lazy_import"), "{rendered}");
+    }
+
+    let rendered = rewrite_module(
+        "# soac: lazy-imports\nimport json\n\ndef names():\n    return locals(), vars()\n",
+    );
+    assert!(rendered.contains("lazy_import"), "{rendered}");
+}
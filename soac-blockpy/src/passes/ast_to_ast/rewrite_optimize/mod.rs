@@ -0,0 +1,110 @@
+//! Compile-time effects of `-O` and `-OO`.
+//!
+//! Like CPython's compiler, `-O` treats `__debug__` as the constant `False`:
+//! `assert` statements disappear, `if __debug__:` keeps only its `else`
+//! branch and any other `__debug__` load becomes a `False` literal. `-OO`
+//! also drops module, class and function docstrings, leaving `pass` in their
+//! place so a following string statement does not become the docstring.
+
+use crate::passes::ast_to_ast::body::Suite;
+use crate::py_stmt;
+use crate::transformer::{walk_expr, walk_stmt, Transformer};
+use crate::OptimizationLevel;
+use ruff_python_ast::{self as ast, Expr, Stmt};
+use ruff_text_size::Ranged;
+
+pub fn rewrite(level: OptimizationLevel, body: &mut Suite) {
+    if level == OptimizationLevel::None {
+        return;
+    }
+    let mut rewriter = OptimizeRewriter { level };
+    rewriter.strip_docstring(body);
+    rewriter.visit_body(body);
+}
+
+struct OptimizeRewriter {
+    level: OptimizationLevel,
+}
+
+impl OptimizeRewriter {
+    fn strip_docstring(&self, body: &mut Suite) {
+        if self.level < OptimizationLevel::Docstrings {
+            return;
+        }
+        if let Some(Stmt::Expr(ast::StmtExpr { value, .. })) = body.first() {
+            if matches!(value.as_ref(), Expr::StringLiteral(_)) {
+                body[0] = py_stmt!("pass");
+            }
+        }
+    }
+}
+
+fn is_debug_name(expr: &Expr) -> bool {
+    matches!(expr, Expr::Name(name) if name.id.as_str() == "__debug__")
+}
+
+/// What is left of `if __debug__: ... elif ...: ... else: ...` once the first
+/// test is known to be false.
+fn debug_if_fallback(if_stmt: ast::StmtIf) -> Suite {
+    let mut clauses = if_stmt.elif_else_clauses.into_iter();
+    let Some(clause) = clauses.next() else {
+        return Vec::new();
+    };
+    match clause.test {
+        None => clause.body,
+        Some(test) => vec![Stmt::If(ast::StmtIf {
+            node_index: ast::AtomicNodeIndex::default(),
+            range: clause.range,
+            test: Box::new(test),
+            body: clause.body,
+            elif_else_clauses: clauses.collect(),
+        })],
+    }
+}
+
+impl Transformer for OptimizeRewriter {
+    fn visit_body(&mut self, body: &mut Suite) {
+        let mut pending = std::mem::take(body);
+        let was_empty = pending.is_empty();
+        pending.reverse();
+        while let Some(stmt) = pending.pop() {
+            match stmt {
+                Stmt::Assert(_) => {}
+                Stmt::If(if_stmt) if is_debug_name(&if_stmt.test) => {
+                    pending.extend(debug_if_fallback(if_stmt).into_iter().rev());
+                }
+                mut stmt => {
+                    self.visit_stmt(&mut stmt);
+                    body.push(stmt);
+                }
+            }
+        }
+        if body.is_empty() && !was_empty {
+            body.push(py_stmt!("pass"));
+        }
+    }
+
+    fn visit_stmt(&mut self, stmt: &mut Stmt) {
+        match stmt {
+            Stmt::FunctionDef(ast::StmtFunctionDef { body, .. })
+            | Stmt::ClassDef(ast::StmtClassDef { body, .. }) => self.strip_docstring(body),
+            _ => {}
+        }
+        walk_stmt(self, stmt);
+    }
+
+    fn visit_expr(&mut self, expr: &mut Expr) {
+        if is_debug_name(expr) {
+            *expr = Expr::BooleanLiteral(ast::ExprBooleanLiteral {
+                node_index: ast::AtomicNodeIndex::default(),
+                range: expr.range(),
+                value: false,
+            });
+            return;
+        }
+        walk_expr(self, expr);
+    }
+}
+
+#[cfg(test)]
+mod test;
@@ -0,0 +1,76 @@
+use super::rewrite;
+use crate::OptimizationLevel;
+use ruff_python_parser::parse_module;
+
+fn rewrite_module(level: OptimizationLevel, source: &str) -> String {
+    let mut module = parse_module(source)
+        .expect("parse should succeed")
+        .into_syntax();
+    rewrite(level, &mut module.body);
+    crate::ruff_ast_to_string(&module.body)
+}
+
+#[test]
+fn leaves_module_untouched_without_optimization() {
+    let source = concat!(
+        "\"\"\"doc\"\"\"\n",
+        "assert x, 'msg'\n",
+        "if __debug__:\n",
+        "    check()\n",
+    );
+
+    let rendered = rewrite_module(OptimizationLevel::None, source);
+
+    assert!(rendered.contains("assert x"), "{rendered}");
+    assert!(rendered.contains("if __debug__:"), "{rendered}");
+    assert!(rendered.contains("\"doc\""), "{rendered}");
+}
+
+#[test]
+fn optimize_drops_asserts_and_debug_blocks_but_keeps_docstrings() {
+    let source = concat!(
+        "\"\"\"doc\"\"\"\n",
+        "def f(x):\n",
+        "    assert x > 0\n",
+        "if __debug__:\n",
+        "    check()\n",
+        "elif ready:\n",
+        "    go()\n",
+        "else:\n",
+        "    stop()\n",
+        "flag = __debug__\n",
+    );
+
+    let rendered = rewrite_module(OptimizationLevel::Asserts, source);
+
+    assert!(rendered.contains("\"doc\""), "{rendered}");
+    assert!(!rendered.contains("assert"), "{rendered}");
+    assert!(!rendered.contains("check()"), "{rendered}");
+    assert!(
+        rendered.contains("if ready:\n    go()\nelse:\n    stop()"),
+        "{rendered}"
+    );
+    assert!(rendered.contains("flag = False"), "{rendered}");
+    assert!(rendered.contains("def f(x):\n    pass"), "{rendered}");
+}
+
+#[test]
+fn optimize_docstrings_replaces_docstrings_with_pass() {
+    let source = concat!(
+        "\"\"\"module doc\"\"\"\n",
+        "class C:\n",
+        "    \"\"\"class doc\"\"\"\n",
+        "    def method(self):\n",
+        "        \"\"\"method doc\"\"\"\n",
+        "        \"not a docstring\"\n",
+        "        return 1\n",
+    );
+
+    let rendered = rewrite_module(OptimizationLevel::Docstrings, source);
+
+    assert!(!rendered.contains("doc\""), "{rendered}");
+    assert!(
+        rendered.contains("pass\n        \"not a docstring\""),
+        "{rendered}"
+    );
+}
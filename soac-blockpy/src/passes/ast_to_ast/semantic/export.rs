@@ -0,0 +1,364 @@
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+
+use super::{
+    SemanticAstState, SemanticBindingKind, SemanticScopeId, SemanticScopeKind, SemanticSnapshot,
+};
+use crate::passes::ast_to_ast::scope_helpers::is_internal_symbol;
+use crate::symtable::{Symbol, SymbolScope, SymbolTable, SymbolTableKind};
+
+/// The names one of CPython's annotation scopes loads, resolved when the
+/// builder reaches the def, class or type alias it belongs to.
+pub(super) type LazyLoads = BTreeMap<String, SymbolScope>;
+
+#[derive(Clone, Debug)]
+pub(super) struct TypeParamScopes {
+    pub(super) name: String,
+    /// The bound or constraints, then the default, each a `type variable`
+    /// table of its own.
+    pub(super) evaluated: Vec<LazyLoads>,
+}
+
+/// The tables CPython builds around a def, class or type alias.
+#[derive(Clone, Debug, Default)]
+pub(super) struct HeaderScopes {
+    /// Empty unless the definition is generic.
+    pub(super) type_params: Vec<TypeParamScopes>,
+    /// Generic class bases and keywords, which the `type parameters` table
+    /// evaluates.
+    pub(super) type_params_loads: LazyLoads,
+    /// The `__annotate__` table of a def, or the value of a type alias.
+    pub(super) body_loads: Option<LazyLoads>,
+}
+
+#[derive(Clone, Debug)]
+pub(super) struct TypeAliasScopes {
+    pub(super) name: String,
+    /// How many child scopes precede the alias, to keep tables in source order.
+    pub(super) position: usize,
+    pub(super) header: HeaderScopes,
+}
+
+/// Scope details only the exported symbol table uses.
+#[derive(Clone, Debug, Default)]
+pub(super) struct SymtableScopeInfo {
+    /// Type parameters of the scope or of a nested definition that nothing
+    /// else binds here.
+    pub(super) type_param_only_names: HashSet<String>,
+    pub(super) explicit_nonlocals: HashSet<String>,
+    pub(super) header: Option<HeaderScopes>,
+    pub(super) type_aliases: Vec<TypeAliasScopes>,
+    /// Set on the `__annotate__` helpers the annotation rewrite synthesizes
+    /// for module and class annotations. Their free names still count.
+    pub(super) hidden: bool,
+}
+
+/// How a scope synthesized by the comprehension rewrite maps back onto
+/// CPython's tables.
+enum HelperScope {
+    /// Folded into the enclosing table, like PEP 709 inlined comprehensions.
+    Inlined,
+    GeneratorExpression,
+}
+
+fn helper_scope(name: &str) -> Option<HelperScope> {
+    if !is_internal_symbol(name) {
+        return None;
+    }
+    if name.starts_with("_dp_genexpr_") {
+        Some(HelperScope::GeneratorExpression)
+    } else {
+        Some(HelperScope::Inlined)
+    }
+}
+
+struct Table {
+    kind: SymbolTableKind,
+    name: String,
+    symbols: BTreeMap<String, Symbol>,
+    children: Vec<Table>,
+    hidden: bool,
+}
+
+impl SemanticAstState {
+    /// Exports the scope tree the later passes lower against.
+    pub(crate) fn symbol_table(&self) -> SymbolTable {
+        let mut table = self.export_scope(SemanticScopeId(0), "top".to_string(), false);
+        resolve_free_names(&mut table);
+        into_symbol_table(table)
+    }
+
+    fn export_scope(&self, scope_id: SemanticScopeId, name: String, in_class: bool) -> Table {
+        let snapshot = &self.inner.snapshot;
+        let scope = snapshot.scope(scope_id);
+        let kind = match scope.kind {
+            SemanticScopeKind::Module => SymbolTableKind::Module,
+            SemanticScopeKind::Class => SymbolTableKind::Class,
+            SemanticScopeKind::Function => SymbolTableKind::Function,
+        };
+        let mut symbols = BTreeMap::new();
+        for symbol_name in scope.bindings.keys().chain(&scope.referenced_names) {
+            if is_internal_symbol(symbol_name) || symbols.contains_key(symbol_name) {
+                continue;
+            }
+            let Some(symbol_scope) = export_symbol_scope(snapshot, scope_id, symbol_name, in_class)
+            else {
+                continue;
+            };
+            symbols.insert(
+                symbol_name.clone(),
+                Symbol {
+                    name: symbol_name.clone(),
+                    scope: symbol_scope,
+                    is_parameter: scope.parameter_names.contains(symbol_name),
+                    is_referenced: scope.referenced_names.contains(symbol_name),
+                },
+            );
+        }
+        let mut table = Table {
+            kind,
+            name,
+            symbols,
+            children: Vec::new(),
+            hidden: scope.symtable.hidden,
+        };
+
+        let child_in_class = in_class || scope.kind == SemanticScopeKind::Class;
+        let children = snapshot
+            .scopes
+            .iter()
+            .enumerate()
+            .filter(|(_, child)| child.parent == Some(scope_id) && !child.reuses_child_scopes)
+            .map(|(index, child)| (SemanticScopeId(index), scope_name(&child.qualname)))
+            .collect::<Vec<_>>();
+        let mut type_aliases = scope.symtable.type_aliases.iter().peekable();
+        for (position, (child_id, child_name)) in children.into_iter().enumerate() {
+            while let Some(alias) = type_aliases.next_if(|alias| alias.position <= position) {
+                table.children.extend(type_alias_tables(alias));
+            }
+            match helper_scope(child_name) {
+                Some(HelperScope::Inlined) => {
+                    let inlined = self.export_scope(child_id, child_name.to_string(), in_class);
+                    for (symbol_name, symbol) in inlined.symbols {
+                        table.symbols.entry(symbol_name).or_insert(symbol);
+                    }
+                    table.children.extend(inlined.children);
+                }
+                Some(HelperScope::GeneratorExpression) => {
+                    table.children.push(self.export_scope(
+                        child_id,
+                        "genexpr".to_string(),
+                        child_in_class,
+                    ));
+                }
+                None => {
+                    let name = match child_name {
+                        "<lambda>" => "lambda",
+                        name => name,
+                    };
+                    let child_table = self.export_scope(child_id, name.to_string(), child_in_class);
+                    let header = snapshot.scope(child_id).symtable.header.as_ref();
+                    table
+                        .children
+                        .extend(with_header_tables(name, header, child_table));
+                }
+            }
+        }
+        for alias in type_aliases {
+            table.children.extend(type_alias_tables(alias));
+        }
+        table
+    }
+}
+
+/// Where `name` resolves in `scope`, or `None` if CPython doesn't list it
+/// there.
+fn export_symbol_scope(
+    snapshot: &SemanticSnapshot,
+    scope_id: SemanticScopeId,
+    name: &str,
+    in_class: bool,
+) -> Option<SymbolScope> {
+    let scope = snapshot.scope(scope_id);
+    let referenced = scope.referenced_names.contains(name);
+    // Type parameters are bound in the `type parameters` table, which the
+    // lowering folds into the def or class they belong to.
+    let unbound = if snapshot.enclosing_type_param(scope_id, name) {
+        SymbolScope::Free
+    } else {
+        SymbolScope::GlobalImplicit
+    };
+    if scope.symtable.type_param_only_names.contains(name) {
+        return referenced.then_some(unbound);
+    }
+    if scope.local_defs.contains(name) {
+        return Some(SymbolScope::Local);
+    }
+    match scope.bindings.get(name) {
+        Some(SemanticBindingKind::Global) => Some(SymbolScope::GlobalExplicit),
+        Some(SemanticBindingKind::Local) => Some(SymbolScope::Local),
+        // Captures only needed by a nested table come back through
+        // `resolve_free_names`.
+        Some(SemanticBindingKind::Nonlocal)
+            if !referenced && !scope.symtable.explicit_nonlocals.contains(name) =>
+        {
+            None
+        }
+        Some(SemanticBindingKind::Nonlocal) | None
+            if name == "__class__" && scope.kind == SemanticScopeKind::Function =>
+        {
+            Some(if in_class {
+                SymbolScope::Free
+            } else {
+                SymbolScope::GlobalImplicit
+            })
+        }
+        Some(SemanticBindingKind::Nonlocal) => Some(SymbolScope::Free),
+        None => Some(unbound),
+    }
+}
+
+/// Puts a def's or class's table after its `__annotate__` table and inside
+/// its `type parameters` table, as CPython does.
+fn with_header_tables(name: &str, header: Option<&HeaderScopes>, table: Table) -> Vec<Table> {
+    let Some(header) = header else {
+        return vec![table];
+    };
+    let mut tables = Vec::new();
+    if let Some(loads) = &header.body_loads {
+        tables.push(lazy_table(
+            SymbolTableKind::Annotation,
+            "__annotate__",
+            loads,
+        ));
+    }
+    tables.push(table);
+    with_type_params_table(name, header, tables)
+}
+
+fn type_alias_tables(alias: &TypeAliasScopes) -> Vec<Table> {
+    let value = alias
+        .header
+        .body_loads
+        .as_ref()
+        .map(|loads| lazy_table(SymbolTableKind::TypeAlias, &alias.name, loads));
+    with_type_params_table(&alias.name, &alias.header, value.into_iter().collect())
+}
+
+fn with_type_params_table(name: &str, header: &HeaderScopes, tables: Vec<Table>) -> Vec<Table> {
+    if header.type_params.is_empty() {
+        return tables;
+    }
+    let mut symbols = lazy_symbols(&header.type_params_loads);
+    let mut children = Vec::new();
+    for type_param in &header.type_params {
+        symbols.insert(
+            type_param.name.clone(),
+            Symbol {
+                name: type_param.name.clone(),
+                scope: SymbolScope::Local,
+                is_parameter: false,
+                is_referenced: header.type_params_loads.contains_key(&type_param.name),
+            },
+        );
+        children.extend(
+            type_param
+                .evaluated
+                .iter()
+                .map(|loads| lazy_table(SymbolTableKind::TypeVariable, &type_param.name, loads)),
+        );
+    }
+    children.extend(tables);
+    vec![Table {
+        kind: SymbolTableKind::TypeParameters,
+        name: name.to_string(),
+        symbols,
+        children,
+        hidden: false,
+    }]
+}
+
+fn lazy_table(kind: SymbolTableKind, name: &str, loads: &LazyLoads) -> Table {
+    Table {
+        kind,
+        name: name.to_string(),
+        symbols: lazy_symbols(loads),
+        children: Vec::new(),
+        hidden: false,
+    }
+}
+
+fn lazy_symbols(loads: &LazyLoads) -> BTreeMap<String, Symbol> {
+    loads
+        .iter()
+        .map(|(name, scope)| {
+            (
+                name.clone(),
+                Symbol {
+                    name: name.clone(),
+                    scope: *scope,
+                    is_parameter: false,
+                    is_referenced: true,
+                },
+            )
+        })
+        .collect()
+}
+
+fn scope_name(qualname: &str) -> &str {
+    qualname.rsplit('.').next().unwrap_or(qualname)
+}
+
+/// Marks locals captured by nested tables as cells and threads free names
+/// through the tables in between, as CPython's `analyze_block` does. Returns
+/// the names still unresolved in `table`.
+fn resolve_free_names(table: &mut Table) -> BTreeSet<String> {
+    let mut child_free = BTreeSet::new();
+    for child in &mut table.children {
+        child_free.extend(resolve_free_names(child));
+    }
+    for name in child_free {
+        let existing = table.symbols.get_mut(&name);
+        match (table.kind, existing) {
+            (SymbolTableKind::Module, _) | (SymbolTableKind::Class, Some(_)) => {}
+            // `__class__` never passes through a class; the class gets an
+            // implicit cell that its table doesn't list.
+            (SymbolTableKind::Class, None) if name == "__class__" => {}
+            (_, Some(symbol)) => {
+                if symbol.scope == SymbolScope::Local {
+                    symbol.scope = SymbolScope::Cell;
+                }
+            }
+            (_, None) => {
+                table.symbols.insert(
+                    name.clone(),
+                    Symbol {
+                        name,
+                        scope: SymbolScope::Free,
+                        is_parameter: false,
+                        is_referenced: false,
+                    },
+                );
+            }
+        }
+    }
+    table
+        .symbols
+        .values()
+        .filter(|symbol| symbol.scope == SymbolScope::Free)
+        .map(|symbol| symbol.name.clone())
+        .collect()
+}
+
+fn into_symbol_table(table: Table) -> SymbolTable {
+    SymbolTable {
+        kind: table.kind,
+        name: table.name,
+        symbols: table.symbols.into_values().collect(),
+        children: table
+            .children
+            .into_iter()
+            .filter(|child| !child.hidden)
+            .map(into_symbol_table)
+            .collect(),
+    }
+}
@@ -16,8 +16,11 @@ use crate::passes::ast_symbol_analysis::CurrentScopeNameTraversal;
 use crate::passes::ast_to_ast::body::Suite;
 use crate::passes::ast_to_ast::scope_helpers::is_internal_symbol;
 use crate::passes::ast_to_ast::util::is_noarg_call;
+use crate::symtable::SymbolScope;
 use crate::transformer::Transformer;
 
+use export::{HeaderScopes, LazyLoads, SymtableScopeInfo, TypeAliasScopes, TypeParamScopes};
+
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub(crate) enum SemanticBindingKind {
     Local,
@@ -47,6 +50,8 @@ struct SemanticScopeData {
     bindings: HashMap<String, SemanticBindingKind>,
     local_defs: HashSet<String>,
     type_param_names: HashSet<String>,
+    parameter_names: HashSet<String>,
+    referenced_names: HashSet<String>,
     local_cell_bindings: HashSet<String>,
     cell_storage_names: HashMap<String, String>,
     parent: Option<SemanticScopeId>,
@@ -54,6 +59,7 @@ struct SemanticScopeData {
     reuses_child_scopes: bool,
     function_children: HashMap<NodeIndex, SemanticScopeId>,
     class_children: HashMap<NodeIndex, SemanticScopeId>,
+    symtable: SymtableScopeInfo,
 }
 
 #[derive(Clone, Debug)]
@@ -69,6 +75,31 @@ impl SemanticSnapshot {
     fn scope_mut(&mut self, scope_id: SemanticScopeId) -> &mut SemanticScopeData {
         &mut self.scopes[scope_id.0]
     }
+
+    /// Whether `name` loaded in `scope_id` resolves to a type parameter of
+    /// the scope or an enclosing generic def or class.
+    fn enclosing_type_param(&self, scope_id: SemanticScopeId, name: &str) -> bool {
+        let mut current = Some(scope_id);
+        while let Some(scope_id) = current {
+            let scope = self.scope(scope_id);
+            if scope.symtable.header.as_ref().is_some_and(|header| {
+                header
+                    .type_params
+                    .iter()
+                    .any(|type_param| type_param.name == name)
+            }) {
+                return true;
+            }
+            let shadowed = scope.kind != SemanticScopeKind::Class
+                && scope.local_defs.contains(name)
+                && !scope.symtable.type_param_only_names.contains(name);
+            if shadowed || scope.kind == SemanticScopeKind::Module {
+                return false;
+            }
+            current = scope.parent;
+        }
+        false
+    }
 }
 
 #[derive(Debug, Default)]
@@ -333,6 +364,11 @@ struct RuffScopeBindingCollector {
     explicit_globals: Vec<(String, TextRange)>,
     explicit_nonlocals: Vec<(String, TextRange)>,
     load_names: HashSet<String>,
+    /// Loads outside annotations, type-parameter bounds and defaults, generic
+    /// class bases and type alias values, which CPython evaluates in scopes
+    /// of their own.
+    eager_load_names: HashSet<String>,
+    lazy_depth: usize,
 }
 
 #[derive(Default)]
@@ -409,8 +445,20 @@ fn expr_uses_implicit_class_cell(expr: &mut ast::Expr) -> bool {
     detector.uses_class_cell
 }
 
+impl RuffScopeBindingCollector {
+    fn visit_lazy(&mut self, visit: impl FnOnce(&mut Self)) {
+        self.lazy_depth += 1;
+        visit(self);
+        self.lazy_depth -= 1;
+    }
+}
+
 impl Transformer for RuffScopeBindingCollector {
     fn visit_stmt(&mut self, stmt: &mut ast::Stmt) {
+        if matches!(&*stmt, ast::Stmt::FunctionDef(func_def) if is_annotate_helper(func_def)) {
+            self.visit_lazy(|this| this.visit_current_scope_stmt_impl(stmt));
+            return;
+        }
         match stmt {
             ast::Stmt::Global(global_stmt) => {
                 for name in &global_stmt.names {
@@ -424,6 +472,25 @@ impl Transformer for RuffScopeBindingCollector {
                         .push((name.id.to_string(), name.range()));
                 }
             }
+            ast::Stmt::ClassDef(class_def) if class_def.type_params.is_some() => {
+                self.record_bound_name(class_def.name.id.as_str());
+                for decorator in &mut class_def.decorator_list {
+                    self.visit_decorator(decorator);
+                }
+                if let Some(type_params) = class_def.type_params.as_mut() {
+                    self.visit_type_params(type_params);
+                }
+                if let Some(arguments) = class_def.arguments.as_mut() {
+                    self.visit_lazy(|this| this.visit_arguments(arguments));
+                }
+            }
+            ast::Stmt::TypeAlias(type_alias) => {
+                self.visit_lazy(|this| this.visit_expr(&mut type_alias.value));
+                if let Some(type_params) = type_alias.type_params.as_mut() {
+                    self.visit_type_params(type_params);
+                }
+                self.visit_expr(&mut type_alias.name);
+            }
             _ => self.visit_current_scope_stmt_impl(stmt),
         }
     }
@@ -432,16 +499,19 @@ impl Transformer for RuffScopeBindingCollector {
         self.visit_current_scope_expr_impl(expr);
     }
 
+    fn visit_annotation(&mut self, expr: &mut ast::Expr) {
+        self.visit_lazy(|this| crate::transformer::walk_annotation(this, expr));
+    }
+
     fn visit_type_param(&mut self, type_param: &mut ast::TypeParam) {
         match type_param {
             ast::TypeParam::TypeVar(ast::TypeParamTypeVar { name, .. })
             | ast::TypeParam::TypeVarTuple(ast::TypeParamTypeVarTuple { name, .. })
             | ast::TypeParam::ParamSpec(ast::TypeParamParamSpec { name, .. }) => {
-                self.bound_names.insert(name.id.to_string());
                 self.type_param_names.insert(name.id.to_string());
             }
         }
-        crate::transformer::walk_type_param(self, type_param);
+        self.visit_lazy(|this| crate::transformer::walk_type_param(this, type_param));
     }
 }
 
@@ -457,10 +527,50 @@ impl CurrentScopeNameTraversal for RuffScopeBindingCollector {
     fn record_loaded_name(&mut self, name: &str) {
         if !is_internal_symbol(name) {
             self.load_names.insert(name.to_string());
+            if self.lazy_depth == 0 {
+                self.eager_load_names.insert(name.to_string());
+            }
         }
     }
 }
 
+/// The `__annotate__` functions the annotation rewrite synthesizes for module
+/// and class annotations.
+fn is_annotate_helper(func_def: &StmtFunctionDef) -> bool {
+    matches!(
+        func_def.name.id.as_str(),
+        "__annotate__" | "__annotate_func__"
+    ) && func_def
+        .parameters
+        .args
+        .first()
+        .is_some_and(|parameter| parameter.parameter.name.id.as_str() == "_dp_format")
+}
+
+fn expr_load_names(expr: &ast::Expr) -> HashSet<String> {
+    collect_scope_expr_bindings(&mut expr.clone()).load_names
+}
+
+fn type_param_names(type_params: Option<&ast::TypeParams>) -> Vec<String> {
+    type_params
+        .map(|type_params| {
+            type_params
+                .type_params
+                .iter()
+                .map(|type_param| type_param_name(type_param).to_string())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn type_param_name(type_param: &ast::TypeParam) -> &str {
+    match type_param {
+        ast::TypeParam::TypeVar(ast::TypeParamTypeVar { name, .. })
+        | ast::TypeParam::TypeVarTuple(ast::TypeParamTypeVarTuple { name, .. })
+        | ast::TypeParam::ParamSpec(ast::TypeParamParamSpec { name, .. }) => name.id.as_str(),
+    }
+}
+
 fn collect_scope_bindings(
     body: &mut Suite,
     type_params: Option<&mut ast::TypeParams>,
@@ -520,7 +630,11 @@ struct ScopePreparation {
     bindings: HashMap<String, SemanticBindingKind>,
     local_defs: HashSet<String>,
     type_param_names: HashSet<String>,
+    parameter_names: HashSet<String>,
+    referenced_names: HashSet<String>,
     cell_storage_names: HashMap<String, String>,
+    type_param_only_names: HashSet<String>,
+    explicit_nonlocals: HashSet<String>,
 }
 
 struct RuffSemanticSnapshotBuilder {
@@ -553,6 +667,8 @@ impl RuffSemanticSnapshotBuilder {
                     bindings: HashMap::new(),
                     local_defs: HashSet::new(),
                     type_param_names: HashSet::new(),
+                    parameter_names: HashSet::new(),
+                    referenced_names: HashSet::new(),
                     local_cell_bindings: HashSet::new(),
                     cell_storage_names: HashMap::new(),
                     parent: None,
@@ -560,6 +676,7 @@ impl RuffSemanticSnapshotBuilder {
                     reuses_child_scopes: false,
                     function_children: HashMap::new(),
                     class_children: HashMap::new(),
+                    symtable: SymtableScopeInfo::default(),
                 }],
             },
             scope_stack: vec![(SemanticScopeId(0), RuffScopeId::global())],
@@ -573,7 +690,10 @@ impl RuffSemanticSnapshotBuilder {
             module_scope.bindings = module_preparation.bindings;
             module_scope.local_defs = module_preparation.local_defs;
             module_scope.type_param_names = module_preparation.type_param_names;
+            module_scope.parameter_names = module_preparation.parameter_names;
+            module_scope.referenced_names = module_preparation.referenced_names;
             module_scope.cell_storage_names = module_preparation.cell_storage_names;
+            module_scope.symtable.type_param_only_names = module_preparation.type_param_only_names;
         }
         builder.visit_body(module_for_build);
         builder.propagate_nonlocal_roots();
@@ -629,10 +749,18 @@ impl RuffSemanticSnapshotBuilder {
 
     fn prepare_scope_from_collector(
         &mut self,
-        collector: RuffScopeBindingCollector,
+        mut collector: RuffScopeBindingCollector,
         uses_class_cell: bool,
         parameters: &[(String, TextRange)],
     ) -> ScopePreparation {
+        let type_param_only_names = collector
+            .type_param_names
+            .difference(&collector.bound_names)
+            .cloned()
+            .collect::<HashSet<_>>();
+        collector
+            .bound_names
+            .extend(collector.type_param_names.iter().cloned());
         let explicit_globals = collector
             .explicit_globals
             .iter()
@@ -718,6 +846,11 @@ impl RuffSemanticSnapshotBuilder {
                 local_defs.insert(name.clone());
             }
         }
+        let mut referenced_names = collector.eager_load_names;
+        // CPython records a use of `__class__` wherever a function loads `super`.
+        if self.current_scope_is_function() && referenced_names.contains("super") {
+            referenced_names.insert("__class__".to_string());
+        }
         for name in collector.load_names {
             if bindings.contains_key(name.as_str()) {
                 continue;
@@ -755,7 +888,11 @@ impl RuffSemanticSnapshotBuilder {
             bindings,
             local_defs,
             type_param_names: collector.type_param_names,
+            parameter_names: parameters.iter().map(|(name, _)| name.clone()).collect(),
+            referenced_names,
             cell_storage_names,
+            type_param_only_names,
+            explicit_nonlocals,
         }
     }
 
@@ -789,6 +926,159 @@ impl RuffSemanticSnapshotBuilder {
         None
     }
 
+    /// Resolves a load in one of the annotation scopes CPython wraps around a
+    /// def, class or type alias in the current scope, as `analyze_name` does.
+    fn lazy_load_scope(&self, name: &str, type_params: &[String]) -> SymbolScope {
+        let scope = self.snapshot.scope(self.current_ids().0);
+        if scope.kind == SemanticScopeKind::Class
+            && !scope.symtable.type_param_only_names.contains(name)
+        {
+            if matches!(scope.bindings.get(name), Some(SemanticBindingKind::Global)) {
+                return SymbolScope::GlobalExplicit;
+            }
+            if scope.local_defs.contains(name) {
+                return SymbolScope::GlobalImplicit;
+            }
+        }
+        if type_params.iter().any(|type_param| type_param == name)
+            || self.enclosing_function_capture_storage_name(name).is_some()
+            || self
+                .snapshot
+                .enclosing_type_param(self.current_ids().0, name)
+        {
+            SymbolScope::Free
+        } else {
+            SymbolScope::GlobalImplicit
+        }
+    }
+
+    fn lazy_loads<'a>(
+        &self,
+        exprs: impl IntoIterator<Item = &'a ast::Expr>,
+        type_params: &[String],
+    ) -> LazyLoads {
+        exprs
+            .into_iter()
+            .flat_map(expr_load_names)
+            .map(|name| {
+                let scope = self.lazy_load_scope(name.as_str(), type_params);
+                (name, scope)
+            })
+            .collect()
+    }
+
+    fn header_scopes(
+        &self,
+        type_params: Option<&ast::TypeParams>,
+        type_params_exprs: Vec<&ast::Expr>,
+        body_exprs: Option<Vec<&ast::Expr>>,
+    ) -> HeaderScopes {
+        let names = type_param_names(type_params);
+        let type_param_scopes = type_params
+            .map(|type_params| {
+                type_params
+                    .type_params
+                    .iter()
+                    .map(|type_param| {
+                        let (bound, default) = match type_param {
+                            ast::TypeParam::TypeVar(ast::TypeParamTypeVar {
+                                bound,
+                                default,
+                                ..
+                            }) => (bound.as_deref(), default.as_deref()),
+                            ast::TypeParam::TypeVarTuple(ast::TypeParamTypeVarTuple {
+                                default,
+                                ..
+                            })
+                            | ast::TypeParam::ParamSpec(ast::TypeParamParamSpec {
+                                default, ..
+                            }) => (None, default.as_deref()),
+                        };
+                        TypeParamScopes {
+                            name: type_param_name(type_param).to_string(),
+                            evaluated: bound
+                                .into_iter()
+                                .chain(default)
+                                .map(|expr| self.lazy_loads([expr], &names))
+                                .collect(),
+                        }
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        HeaderScopes {
+            type_params: type_param_scopes,
+            type_params_loads: self.lazy_loads(type_params_exprs, &names),
+            body_loads: body_exprs.map(|exprs| self.lazy_loads(exprs, &names)),
+        }
+    }
+
+    fn function_header_scopes(&self, func_def: &StmtFunctionDef) -> Option<HeaderScopes> {
+        if is_internal_symbol(func_def.name.id.as_str()) || is_annotate_helper(func_def) {
+            return None;
+        }
+        let parameters = &func_def.parameters;
+        let annotations = parameters
+            .posonlyargs
+            .iter()
+            .chain(&parameters.args)
+            .chain(&parameters.kwonlyargs)
+            .map(|parameter| &parameter.parameter)
+            .chain(parameters.vararg.as_deref())
+            .chain(parameters.kwarg.as_deref())
+            .filter_map(|parameter| parameter.annotation.as_deref())
+            .chain(func_def.returns.as_deref())
+            .collect::<Vec<_>>();
+        Some(self.header_scopes(
+            func_def.type_params.as_deref(),
+            Vec::new(),
+            Some(annotations),
+        ))
+    }
+
+    fn class_header_scopes(&self, class_def: &StmtClassDef) -> Option<HeaderScopes> {
+        let type_params = class_def.type_params.as_deref()?;
+        let bases = class_def
+            .arguments
+            .as_deref()
+            .map(|arguments| {
+                arguments
+                    .args
+                    .iter()
+                    .chain(arguments.keywords.iter().map(|keyword| &keyword.value))
+                    .collect()
+            })
+            .unwrap_or_default();
+        Some(self.header_scopes(Some(type_params), bases, None))
+    }
+
+    fn record_type_alias(&mut self, type_alias: &ast::StmtTypeAlias) {
+        let ast::Expr::Name(name) = type_alias.name.as_ref() else {
+            return;
+        };
+        let header = self.header_scopes(
+            type_alias.type_params.as_deref(),
+            Vec::new(),
+            Some(vec![type_alias.value.as_ref()]),
+        );
+        let scope_id = self.current_ids().0;
+        let position = self
+            .snapshot
+            .scopes
+            .iter()
+            .filter(|scope| scope.parent == Some(scope_id))
+            .count();
+        self.snapshot
+            .scope_mut(scope_id)
+            .symtable
+            .type_aliases
+            .push(TypeAliasScopes {
+                name: name.id.to_string(),
+                position,
+                header,
+            });
+    }
+
     fn push_snapshot_scope(
         &mut self,
         kind: SemanticScopeKind,
@@ -804,6 +1094,8 @@ impl RuffSemanticSnapshotBuilder {
             bindings: preparation.bindings,
             local_defs: preparation.local_defs,
             type_param_names: preparation.type_param_names,
+            parameter_names: preparation.parameter_names,
+            referenced_names: preparation.referenced_names,
             local_cell_bindings: HashSet::new(),
             cell_storage_names: preparation.cell_storage_names,
             parent: Some(parent_id),
@@ -811,6 +1103,11 @@ impl RuffSemanticSnapshotBuilder {
             reuses_child_scopes: false,
             function_children: HashMap::new(),
             class_children: HashMap::new(),
+            symtable: SymtableScopeInfo {
+                type_param_only_names: preparation.type_param_only_names,
+                explicit_nonlocals: preparation.explicit_nonlocals,
+                ..SymtableScopeInfo::default()
+            },
         });
         match kind {
             SemanticScopeKind::Function => {
@@ -922,12 +1219,16 @@ impl Transformer for RuffSemanticSnapshotBuilder {
                     func_def.type_params.as_deref_mut(),
                     &parameters,
                 );
+                let header = self.function_header_scopes(func_def);
                 let scope_id = self.push_snapshot_scope(
                     SemanticScopeKind::Function,
                     func_def.name.id.as_str(),
                     node_index,
                     preparation,
                 );
+                let symtable = &mut self.snapshot.scope_mut(scope_id).symtable;
+                symtable.header = header;
+                symtable.hidden = is_annotate_helper(func_def);
                 let ruff_scope_id = self.semantic.scope_id;
                 self.scope_stack.push((scope_id, ruff_scope_id));
                 self.visit_body(&mut func_def.body);
@@ -943,18 +1244,28 @@ impl Transformer for RuffSemanticSnapshotBuilder {
                     class_def.type_params.as_deref_mut(),
                     &[],
                 );
+                let header = self.class_header_scopes(class_def);
                 let scope_id = self.push_snapshot_scope(
                     SemanticScopeKind::Class,
                     class_def.name.id.as_str(),
                     node_index,
                     preparation,
                 );
+                self.snapshot.scope_mut(scope_id).symtable.header = header;
                 let ruff_scope_id = self.semantic.scope_id;
                 self.scope_stack.push((scope_id, ruff_scope_id));
                 self.visit_body(&mut class_def.body);
                 self.scope_stack.pop();
                 self.semantic.pop_scope();
             }
+            ast::Stmt::TypeAlias(type_alias) => {
+                self.record_type_alias(type_alias);
+                self.visit_expr(&mut type_alias.value);
+                if let Some(type_params) = type_alias.type_params.as_mut() {
+                    self.visit_type_params(type_params);
+                }
+                self.visit_expr(&mut type_alias.name);
+            }
             _ => crate::transformer::walk_stmt(self, stmt),
         }
     }
@@ -1112,6 +1423,8 @@ impl SemanticAstState {
                 bindings: translated_bindings,
                 local_defs: HashSet::new(),
                 type_param_names: HashSet::new(),
+                parameter_names: HashSet::new(),
+                referenced_names: HashSet::new(),
                 local_cell_bindings: HashSet::new(),
                 cell_storage_names: HashMap::new(),
                 parent: Some(module_scope.scope_id),
@@ -1119,6 +1432,7 @@ impl SemanticAstState {
                 reuses_child_scopes: true,
                 function_children: module_scope.data().function_children.clone(),
                 class_children: module_scope.data().class_children.clone(),
+                symtable: SymtableScopeInfo::default(),
             });
             scope_id
         };
@@ -1162,5 +1476,6 @@ impl SemanticAstState {
     }
 }
 
+mod export;
 #[cfg(test)]
 mod test;
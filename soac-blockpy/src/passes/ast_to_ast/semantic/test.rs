@@ -1,9 +1,11 @@
 use super::{
     SemanticAstState, SemanticBindingKind, SemanticBindingUse, SemanticScope, SemanticScopeKind,
 };
-use crate::lower_python_to_blockpy_for_testing;
+use crate::block_py::ModuleNameGen;
 use crate::passes::ast_to_ast::context::Context;
 use crate::passes::ast_to_ast::rewrite_class_def::class_body::rewrite_class_body_scopes;
+use crate::symtable::{SymbolScope, SymbolTable, SymbolTableKind};
+use crate::{lower_python_to_blockpy_for_testing, symbol_table};
 use ruff_python_ast::{self as ast, Stmt};
 use ruff_python_parser::parse_module;
 
@@ -91,7 +93,7 @@ fn semantic_state_keeps_class_helper_scope_overrides_transformable() {
         "            return shared\n",
         "    return Box\n",
     );
-    let context = Context::new(source);
+    let context = Context::new(source, ModuleNameGen::default());
     let mut module = parse_module(source).unwrap().into_syntax().body;
     let mut semantic_state = SemanticAstState::from_ruff(&mut module);
     rewrite_class_body_scopes(&context, &mut semantic_state, &mut module);
@@ -509,3 +511,166 @@ fn semantic_state_keeps_genexpr_iter_once_shape_transformable() {
     );
     let _ = lower_python_to_blockpy_for_testing(source).expect("transform should succeed");
 }
+
+fn symbol_scope(table: &SymbolTable, name: &str) -> SymbolScope {
+    table
+        .lookup(name)
+        .unwrap_or_else(|| panic!("missing symbol {name} in {}", table.name))
+        .scope
+}
+
+#[test]
+fn symbol_table_marks_captured_locals_as_cells() {
+    let source = concat!(
+        "def f(a):\n",
+        "    b = 1\n",
+        "    def g():\n",
+        "        def h():\n",
+        "            return a + b + c\n",
+        "        return h\n",
+        "    return g\n",
+    );
+    let table = symbol_table(source).expect("symbol table");
+    let f = table.child("f").expect("f table");
+    let g = f.child("g").expect("g table");
+    let h = g.child("h").expect("h table");
+
+    assert_eq!(table.kind, SymbolTableKind::Module);
+    assert_eq!(symbol_scope(&table, "f"), SymbolScope::Local);
+    assert_eq!(symbol_scope(f, "a"), SymbolScope::Cell);
+    assert!(f.lookup("a").unwrap().is_parameter);
+    assert_eq!(symbol_scope(f, "b"), SymbolScope::Cell);
+    assert_eq!(symbol_scope(g, "a"), SymbolScope::Free);
+    assert_eq!(symbol_scope(g, "b"), SymbolScope::Free);
+    assert_eq!(symbol_scope(h, "a"), SymbolScope::Free);
+    assert_eq!(symbol_scope(h, "c"), SymbolScope::GlobalImplicit);
+}
+
+#[test]
+fn symbol_table_class_bodies_do_not_enclose_methods() {
+    let source = concat!(
+        "x = 1\n",
+        "class C:\n",
+        "    x = 2\n",
+        "    def m(self):\n",
+        "        return x, super().m()\n",
+    );
+    let table = symbol_table(source).expect("symbol table");
+    let class_table = table.child("C").expect("C table");
+    let method = class_table.child("m").expect("m table");
+
+    assert_eq!(class_table.kind, SymbolTableKind::Class);
+    assert_eq!(symbol_scope(class_table, "x"), SymbolScope::Local);
+    assert!(class_table.lookup("__class__").is_none());
+    assert_eq!(symbol_scope(method, "x"), SymbolScope::GlobalImplicit);
+    assert_eq!(symbol_scope(method, "__class__"), SymbolScope::Free);
+    assert!(method.lookup("__class__").unwrap().is_referenced);
+}
+
+#[test]
+fn symbol_table_inlines_comprehensions_and_keeps_genexpr_tables() {
+    let source = concat!(
+        "def f(y, z):\n",
+        "    global g\n",
+        "    return [x + z for x in y], (w for w in y)\n",
+    );
+    let table = symbol_table(source).expect("symbol table");
+    let f = table.child("f").expect("f table");
+
+    assert_eq!(symbol_scope(f, "x"), SymbolScope::Local);
+    assert_eq!(symbol_scope(f, "z"), SymbolScope::Local);
+    assert_eq!(symbol_scope(f, "g"), SymbolScope::GlobalExplicit);
+    let names = f
+        .children
+        .iter()
+        .map(|child| child.name.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(names, ["genexpr"]);
+    assert_eq!(
+        symbol_scope(f.child("genexpr").unwrap(), "w"),
+        SymbolScope::Local
+    );
+}
+
+#[test]
+fn symbol_table_keeps_nonlocal_targets_local_to_their_defining_scope() {
+    let source = concat!(
+        "def f():\n",
+        "    x = 1\n",
+        "    def g():\n",
+        "        nonlocal x\n",
+        "        x = 2\n",
+        "    return g\n",
+    );
+    let table = symbol_table(source).expect("symbol table");
+    let f = table.child("f").expect("f table");
+
+    assert_eq!(symbol_scope(f, "x"), SymbolScope::Cell);
+    assert!(!f.lookup("x").unwrap().is_referenced);
+    assert_eq!(symbol_scope(f.child("g").unwrap(), "x"), SymbolScope::Free);
+}
+
+fn child_tables(table: &SymbolTable) -> Vec<(SymbolTableKind, &str)> {
+    table
+        .children
+        .iter()
+        .map(|child| (child.kind, child.name.as_str()))
+        .collect()
+}
+
+#[test]
+fn symbol_table_wraps_defs_in_annotation_and_type_param_tables() {
+    let source = concat!(
+        "def f(x):\n",
+        "    y = 1\n",
+        "    def g[T: int](a: T, b: y) -> T:\n",
+        "        return a\n",
+        "    return g\n",
+        "class C[U](Base[U]):\n",
+        "    pass\n",
+    );
+    let table = symbol_table(source).expect("symbol table");
+
+    assert_eq!(
+        child_tables(&table),
+        [
+            (SymbolTableKind::Function, "f"),
+            (SymbolTableKind::TypeParameters, "C"),
+        ]
+    );
+    assert!(table.lookup("Base").is_none());
+    assert!(table.lookup("U").is_none());
+
+    let f = table.child("f").expect("f table");
+    assert_eq!(symbol_scope(f, "y"), SymbolScope::Cell);
+    assert!(f.lookup("T").is_none());
+    assert!(f.lookup("int").is_none());
+    let g_params = f.child("g").expect("g type parameters table");
+    assert_eq!(
+        child_tables(g_params),
+        [
+            (SymbolTableKind::TypeVariable, "T"),
+            (SymbolTableKind::Annotation, "__annotate__"),
+            (SymbolTableKind::Function, "g"),
+        ]
+    );
+    assert_eq!(symbol_scope(g_params, "T"), SymbolScope::Cell);
+    assert_eq!(symbol_scope(g_params, "y"), SymbolScope::Free);
+    assert_eq!(
+        symbol_scope(g_params.child("T").unwrap(), "int"),
+        SymbolScope::GlobalImplicit
+    );
+    let annotate = g_params.child("__annotate__").unwrap();
+    assert_eq!(symbol_scope(annotate, "T"), SymbolScope::Free);
+    assert_eq!(symbol_scope(annotate, "y"), SymbolScope::Free);
+    let g = g_params.children.last().unwrap();
+    assert!(g.lookup("T").is_none());
+    assert!(g.lookup("a").unwrap().is_referenced);
+    assert!(!g.lookup("b").unwrap().is_referenced);
+
+    let c_params = table.child("C").expect("C type parameters table");
+    assert_eq!(child_tables(c_params), [(SymbolTableKind::Class, "C")]);
+    assert_eq!(symbol_scope(c_params, "U"), SymbolScope::Local);
+    assert!(c_params.lookup("U").unwrap().is_referenced);
+    assert_eq!(symbol_scope(c_params, "Base"), SymbolScope::GlobalImplicit);
+}
@@ -16,7 +16,29 @@ fn lower_string_templates_keeps_fstring_debug_output_correct() {
     lower_string_templates_in_expr(assign.value.as_mut());
     let rendered = ruff_ast_to_string(&module.body);
     assert!(rendered.contains("value="), "{rendered}");
-    assert!(rendered.contains("__soac__.repr(value)"), "{rendered}");
+    assert!(
+        rendered.contains("__soac__.format_value(value, \"r\")"),
+        "{rendered}"
+    );
+}
+
+#[test]
+fn lower_string_templates_merges_literals_around_formatted_values() {
+    let mut module = parse_assign_module("x = f\"a{b}c\" \"d\" f\"{e:>{width}}\"\n");
+    let Stmt::Assign(assign) = &mut module.body[0] else {
+        panic!("expected first statement to be an assignment");
+    };
+    lower_string_templates_in_expr(assign.value.as_mut());
+    let rendered = ruff_ast_to_string(&module.body);
+    assert!(
+        rendered.contains(
+            "__soac__.build_string(\"a\", __soac__.format_value(b, None), \"cd\", \
+             __soac__.format_value(e, None, __soac__.build_string(\">\", \
+             __soac__.format_value(width, None))))"
+        ),
+        "{rendered}"
+    );
+    assert!(!rendered.contains(".join("), "{rendered}");
 }
 
 #[test]
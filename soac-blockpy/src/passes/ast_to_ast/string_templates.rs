@@ -2,13 +2,43 @@ use crate::transformer::{walk_expr, Transformer};
 use crate::{passes::ast_to_ast::expr_utils::make_tuple, py_expr};
 use ruff_python_ast::{self as ast, Expr};
 
-fn join_parts(parts: Vec<Expr>, force_join: bool) -> Expr {
-    match parts.len() {
+fn string_literal_text(expr: &Expr) -> Option<&str> {
+    match expr {
+        Expr::StringLiteral(literal) => Some(literal.value.to_str()),
+        _ => None,
+    }
+}
+
+// Every part is a `str`, either a literal or a `format_value` result, so
+// adjacent literals fold together and a lone part needs no join.
+fn join_parts(parts: Vec<Expr>) -> Expr {
+    let mut merged: Vec<Expr> = Vec::with_capacity(parts.len());
+    for part in parts {
+        let Some(text) = string_literal_text(&part) else {
+            merged.push(part);
+            continue;
+        };
+        if text.is_empty() {
+            continue;
+        }
+        if let Some(previous) = merged.last_mut() {
+            if let Some(previous_text) = string_literal_text(previous) {
+                let joined = format!("{previous_text}{text}");
+                *previous = py_expr!("{literal:literal}", literal = joined.as_str());
+                continue;
+            }
+        }
+        merged.push(part);
+    }
+    match merged.len() {
         0 => py_expr!("\"\""),
-        1 if !force_join => parts.into_iter().next().unwrap(),
+        1 => merged.into_iter().next().unwrap(),
         _ => {
-            let tuple = make_tuple(parts);
-            py_expr!("\"\".join({tuple:expr})", tuple = tuple)
+            let Expr::Call(mut call) = py_expr!("__soac__.build_string()") else {
+                panic!("expected call expression for __soac__.build_string");
+            };
+            call.arguments.args = merged.into();
+            Expr::Call(call)
         }
     }
 }
@@ -36,7 +66,7 @@ fn strip_debug_comment(trailing: &str) -> String {
 fn rewrite_interpolation(interp: &ast::InterpolatedElement, is_raw: bool) -> Vec<Expr> {
     let mut parts = Vec::new();
 
-    let mut value = (*interp.expression).clone();
+    let value = (*interp.expression).clone();
     let conversion = if let Some(debug) = &interp.debug_text {
         let has_format_spec = interp.format_spec.is_some();
         let trailing_has_format = debug.trailing.contains(':');
@@ -53,11 +83,11 @@ fn rewrite_interpolation(interp: &ast::InterpolatedElement, is_raw: bool) -> Vec
     } else {
         interp.conversion
     };
-    value = match conversion {
-        ast::ConversionFlag::Ascii => py_expr!("__soac__.ascii({value:expr})", value = value),
-        ast::ConversionFlag::Repr => py_expr!("__soac__.repr({value:expr})", value = value),
-        ast::ConversionFlag::Str => py_expr!("__soac__.str({value:expr})", value = value),
-        ast::ConversionFlag::None => value,
+    let conversion = match conversion {
+        ast::ConversionFlag::Ascii => py_expr!("{literal:literal}", literal = "a"),
+        ast::ConversionFlag::Repr => py_expr!("{literal:literal}", literal = "r"),
+        ast::ConversionFlag::Str => py_expr!("{literal:literal}", literal = "s"),
+        ast::ConversionFlag::None => py_expr!("None"),
     };
 
     if let Some(debug) = &interp.debug_text {
@@ -72,28 +102,27 @@ fn rewrite_interpolation(interp: &ast::InterpolatedElement, is_raw: bool) -> Vec
     }
 
     let formatted = if let Some(format_spec) = &interp.format_spec {
-        let (parts, _) = rewrite_elements(&format_spec.elements, is_raw);
-        let spec = if parts.is_empty() {
-            py_expr!("\"\"")
-        } else {
-            join_parts(parts, false)
-        };
+        let spec = join_parts(rewrite_elements(&format_spec.elements, is_raw));
         py_expr!(
-            "__soac__.format({value:expr}, {format_spec:expr})",
+            "__soac__.format_value({value:expr}, {conversion:expr}, {format_spec:expr})",
             value = value,
+            conversion = conversion,
             format_spec = spec
         )
     } else {
-        py_expr!("__soac__.format({value:expr})", value = value)
+        py_expr!(
+            "__soac__.format_value({value:expr}, {conversion:expr})",
+            value = value,
+            conversion = conversion
+        )
     };
 
     parts.push(formatted);
     parts
 }
 
-fn rewrite_elements(elements: &ast::InterpolatedStringElements, is_raw: bool) -> (Vec<Expr>, bool) {
+fn rewrite_elements(elements: &ast::InterpolatedStringElements, is_raw: bool) -> Vec<Expr> {
     let mut parts = Vec::new();
-    let mut has_interpolation = false;
     for element in elements.iter() {
         match element {
             ast::InterpolatedStringElement::Literal(lit) => {
@@ -101,11 +130,10 @@ fn rewrite_elements(elements: &ast::InterpolatedStringElements, is_raw: bool) ->
             }
             ast::InterpolatedStringElement::Interpolation(interp) => {
                 parts.extend(rewrite_interpolation(interp, is_raw));
-                has_interpolation = true;
             }
         }
     }
-    (parts, has_interpolation)
+    parts
 }
 
 fn rewrite_tstring_interpolation(interp: &ast::InterpolatedElement) -> Expr {
@@ -120,12 +148,7 @@ fn rewrite_tstring_interpolation(interp: &ast::InterpolatedElement) -> Expr {
         ast::ConversionFlag::Ascii => py_expr!("{literal:literal}", literal = "a"),
     };
     let format_spec = if let Some(format_spec) = &interp.format_spec {
-        let (parts, _) = rewrite_elements(&format_spec.elements, false);
-        if parts.is_empty() {
-            py_expr!("{literal:literal}", literal = "")
-        } else {
-            join_parts(parts, false)
-        }
+        join_parts(rewrite_elements(&format_spec.elements, false))
     } else {
         py_expr!("{literal:literal}", literal = "")
     };
@@ -140,21 +163,17 @@ fn rewrite_tstring_interpolation(interp: &ast::InterpolatedElement) -> Expr {
 
 pub fn rewrite_fstring(expr: ast::ExprFString) -> Expr {
     let mut parts = Vec::new();
-    let mut has_interpolation = false;
     for part in expr.value.iter() {
         match part {
             ast::FStringPart::Literal(lit) => {
                 parts.push(rewrite_string_literal(lit));
             }
             ast::FStringPart::FString(f) => {
-                let (elements, has_interp) =
-                    rewrite_elements(&f.elements, f.flags.prefix().is_raw());
-                parts.extend(elements);
-                has_interpolation |= has_interp;
+                parts.extend(rewrite_elements(&f.elements, f.flags.prefix().is_raw()));
             }
         }
     }
-    join_parts(parts, !has_interpolation)
+    join_parts(parts)
 }
 
 pub fn rewrite_tstring(expr: ast::ExprTString) -> Expr {
@@ -1,78 +1,48 @@
 use super::ast_to_ast::string_templates::lower_string_templates_in_expr;
+use super::ruff_to_blockpy::expr_lowering::{
+    create_class_operation_from_helper_args, import_operation_from_helper_args,
+    string_operation_from_helper_args, unpack_spec_from_literal,
+};
 use crate::block_py::{
-    core_call_expr_with_meta, core_runtime_name_expr_with_meta,
-    core_runtime_positional_call_expr_with_meta, literal_expr, operation, Await, CallArgKeyword,
+    core_call_expr_with_meta, core_runtime_name_expr_with_meta, literal_expr, operation, Await,
+    BuildDict, BuildDictItem, BuildElement, BuildList, BuildSet, BuildTuple, CallArgKeyword,
     CallArgPositional, CoreBlockPyExprWithAwaitAndYield, CoreBytesLiteral, CoreNumberLiteral,
     CoreNumberLiteralValue, CoreStringLiteral, HasMeta, ImplicitNoneExpr, Meta, WithMeta, Yield,
     YieldFrom,
 };
-use crate::passes::ast_to_ast::expr_utils::make_tuple;
+use crate::diagnostic::Diagnostic;
 use crate::py_expr;
 use ruff_python_ast::{self as ast, Expr};
+use ruff_text_size::Ranged;
 
 fn core_builtin_name(id: &str) -> CoreBlockPyExprWithAwaitAndYield {
     core_runtime_name_expr_with_meta(id, Default::default(), Default::default())
 }
 
-fn reduce_core_blockpy_dict(items: Box<[ast::DictItem]>) -> CoreBlockPyExprWithAwaitAndYield {
-    let mut segments: Vec<CoreBlockPyExprWithAwaitAndYield> = Vec::new();
-    let mut keyed_pairs = Vec::new();
-
-    for item in items {
-        match item {
-            ast::DictItem {
-                key: Some(key),
-                value,
-            } => {
-                keyed_pairs.push(py_expr!(
-                    "({key:expr}, {value:expr})",
-                    key = key,
-                    value = value,
-                ));
-            }
-            ast::DictItem { key: None, value } => {
-                if !keyed_pairs.is_empty() {
-                    let tuple = make_tuple(std::mem::take(&mut keyed_pairs));
-                    segments.push(CoreBlockPyExprWithAwaitAndYield::from(py_expr!(
-                        "__soac__.dict({tuple:expr})",
-                        tuple = tuple
-                    )));
-                }
-                segments.push(CoreBlockPyExprWithAwaitAndYield::from(py_expr!(
-                    "__soac__.dict({mapping:expr})",
-                    mapping = value
-                )));
+fn build_elements(elts: Vec<Expr>) -> Vec<BuildElement<CoreBlockPyExprWithAwaitAndYield>> {
+    elts.into_iter()
+        .map(|elt| match elt {
+            Expr::Starred(starred) => {
+                BuildElement::Unpack(CoreBlockPyExprWithAwaitAndYield::from(*starred.value))
             }
-        }
-    }
-
-    if !keyed_pairs.is_empty() {
-        let tuple = make_tuple(keyed_pairs);
-        segments.push(CoreBlockPyExprWithAwaitAndYield::from(py_expr!(
-            "__soac__.dict({tuple:expr})",
-            tuple = tuple
-        )));
-    }
+            other => BuildElement::Value(CoreBlockPyExprWithAwaitAndYield::from(other)),
+        })
+        .collect()
+}
 
-    let expr = match segments.len() {
-        0 => core_runtime_positional_call_expr_with_meta(
-            "dict",
-            ast::AtomicNodeIndex::default(),
-            Default::default(),
-            Vec::new(),
-        ),
-        _ => segments
-            .into_iter()
-            .reduce(|left, right| {
-                core_operation_expr(operation::BinOp::new(
-                    operation::BinOpKind::Or,
-                    Box::new(left),
-                    Box::new(right),
-                ))
-            })
-            .expect("dict segments are non-empty"),
-    };
-    expr
+fn build_dict_items(
+    items: Vec<ast::DictItem>,
+) -> Vec<BuildDictItem<CoreBlockPyExprWithAwaitAndYield>> {
+    items
+        .into_iter()
+        .map(|item| match item.key {
+            Some(key) => BuildDictItem::Pair {
+                key: CoreBlockPyExprWithAwaitAndYield::from(key),
+                value: CoreBlockPyExprWithAwaitAndYield::from(item.value),
+            },
+            None => BuildDictItem::Unpack(CoreBlockPyExprWithAwaitAndYield::from(item.value)),
+        })
+        .collect()
 }
 
 fn core_operation_expr(
@@ -257,18 +227,6 @@ fn add_op_expr_with_meta(
     binop_expr_with_meta(node_index, range, operation::BinOpKind::Add, left, right)
 }
 
-fn add_op_expr(
-    left: CoreBlockPyExprWithAwaitAndYield,
-    right: CoreBlockPyExprWithAwaitAndYield,
-) -> CoreBlockPyExprWithAwaitAndYield {
-    add_op_expr_with_meta(
-        ast::AtomicNodeIndex::default(),
-        Default::default(),
-        left,
-        right,
-    )
-}
-
 fn lower_core_call_args(
     args: Vec<Expr>,
 ) -> Vec<CallArgPositional<CoreBlockPyExprWithAwaitAndYield>> {
@@ -355,6 +313,12 @@ fn non_operator_operation_from_helper_call(
         "cell_ref" => operation::CellRefForName::new(string_arg_from_core_expr(args.next()?)?)
             .with_meta(meta)
             .into(),
+        "loop_iter" => operation::GetIter::new(Box::new(args.next()?))
+            .with_meta(meta)
+            .into(),
+        "next_or_sentinel" => operation::ForIter::new(Box::new(args.next()?))
+            .with_meta(meta)
+            .into(),
         _ => return None,
     };
     if args.next().is_some() {
@@ -391,6 +355,52 @@ fn lower_core_call_expr_with_meta(
                     );
                 }
             }
+            if matches!(attr.value.as_ref(), Expr::Name(base) if base.id.as_str() == "__soac__") {
+                if let Some(operation) = import_operation_from_helper_args(
+                    attr.attr.id.as_str(),
+                    &args,
+                    Meta::new(node_index.clone(), range),
+                    CoreBlockPyExprWithAwaitAndYield::from,
+                ) {
+                    return operation;
+                }
+            }
+            if matches!(attr.value.as_ref(), Expr::Name(base) if base.id.as_str() == "__soac__") {
+                if let Some(operation) = string_operation_from_helper_args(
+                    attr.attr.id.as_str(),
+                    &args,
+                    Meta::new(node_index.clone(), range),
+                    CoreBlockPyExprWithAwaitAndYield::from,
+                ) {
+                    return operation;
+                }
+            }
+            if matches!(attr.value.as_ref(), Expr::Name(base) if base.id.as_str() == "__soac__")
+                && attr.attr.id.as_str() == "create_class"
+            {
+                if let Some(operation) = create_class_operation_from_helper_args(
+                    &args,
+                    Meta::new(node_index.clone(), range),
+                    CoreBlockPyExprWithAwaitAndYield::from,
+                ) {
+                    return operation;
+                }
+            }
+            if matches!(attr.value.as_ref(), Expr::Name(base) if base.id.as_str() == "__soac__")
+                && attr.attr.id.as_str() == "unpack"
+                && args.len() == 2
+            {
+                if let Some((count, starred)) = unpack_spec_from_literal(&args[1]) {
+                    return core_operation_expr(
+                        operation::Unpack::new(
+                            Box::new(CoreBlockPyExprWithAwaitAndYield::from(args[0].clone())),
+                            count,
+                            starred,
+                        )
+                        .with_meta(Meta::new(node_index, range)),
+                    );
+                }
+            }
         }
         if let Expr::Attribute(attr) = &func {
             if matches!(attr.value.as_ref(), Expr::Name(base) if base.id.as_str() == "__soac__") {
@@ -428,56 +438,6 @@ fn lower_core_call_expr_with_meta(
     )
 }
 
-fn reduce_core_tuple_splat(elts: Vec<Expr>) -> CoreBlockPyExprWithAwaitAndYield {
-    let mut segments: Vec<CoreBlockPyExprWithAwaitAndYield> = Vec::new();
-    let mut values: Vec<CoreBlockPyExprWithAwaitAndYield> = Vec::new();
-
-    for elt in elts {
-        match elt {
-            Expr::Starred(ast::ExprStarred {
-                value,
-                node_index,
-                range,
-                ..
-            }) => {
-                if !values.is_empty() {
-                    segments.push(core_runtime_positional_call_expr_with_meta(
-                        "tuple_values",
-                        ast::AtomicNodeIndex::default(),
-                        Default::default(),
-                        std::mem::take(&mut values),
-                    ));
-                }
-                segments.push(core_runtime_positional_call_expr_with_meta(
-                    "tuple_from_iter",
-                    node_index,
-                    range,
-                    vec![CoreBlockPyExprWithAwaitAndYield::from(*value)],
-                ));
-            }
-            other => values.push(CoreBlockPyExprWithAwaitAndYield::from(other)),
-        }
-    }
-
-    if !values.is_empty() {
-        segments.push(core_runtime_positional_call_expr_with_meta(
-            "tuple_values",
-            ast::AtomicNodeIndex::default(),
-            Default::default(),
-            values,
-        ));
-    }
-
-    segments.into_iter().reduce(add_op_expr).unwrap_or_else(|| {
-        core_runtime_positional_call_expr_with_meta(
-            "tuple_values",
-            ast::AtomicNodeIndex::default(),
-            Default::default(),
-            Vec::new(),
-        )
-    })
-}
-
 impl From<Expr> for CoreBlockPyExprWithAwaitAndYield {
     fn from(value: Expr) -> Self {
         let mut value = value;
@@ -600,39 +560,18 @@ impl From<Expr> for CoreBlockPyExprWithAwaitAndYield {
                 )
             }
             Expr::Tuple(node) if matches!(node.ctx, ast::ExprContext::Load) => {
-                let tuple = if node.elts.iter().any(Expr::is_starred_expr) {
-                    return reduce_core_tuple_splat(node.elts);
-                } else {
-                    make_tuple(node.elts)
-                };
-                Self::from(tuple)
+                BuildTuple::new(build_elements(node.elts))
+                    .with_meta(Meta::new(node.node_index, node.range))
+                    .into()
             }
             Expr::List(node) if matches!(node.ctx, ast::ExprContext::Load) => {
-                let tuple = if node.elts.iter().any(Expr::is_starred_expr) {
-                    reduce_core_tuple_splat(node.elts)
-                } else {
-                    Self::from(make_tuple(node.elts))
-                };
-                core_runtime_positional_call_expr_with_meta(
-                    "list",
-                    node.node_index,
-                    node.range,
-                    vec![tuple],
-                )
-            }
-            Expr::Set(node) => {
-                let tuple = if node.elts.iter().any(Expr::is_starred_expr) {
-                    reduce_core_tuple_splat(node.elts)
-                } else {
-                    Self::from(make_tuple(node.elts))
-                };
-                core_runtime_positional_call_expr_with_meta(
-                    "set",
-                    node.node_index,
-                    node.range,
-                    vec![tuple],
-                )
+                BuildList::new(build_elements(node.elts))
+                    .with_meta(Meta::new(node.node_index, node.range))
+                    .into()
             }
+            Expr::Set(node) => BuildSet::new(build_elements(node.elts))
+                .with_meta(Meta::new(node.node_index, node.range))
+                .into(),
             Expr::Slice(node) => Self::from(py_expr!(
                 "__soac__.slice({lower:expr}, {upper:expr}, {step:expr})",
                 lower = node
@@ -648,7 +587,9 @@ impl From<Expr> for CoreBlockPyExprWithAwaitAndYield {
                     .map(|expr| *expr)
                     .unwrap_or_else(|| py_expr!("None")),
             )),
-            Expr::Dict(node) => reduce_core_blockpy_dict(node.items.into()),
+            Expr::Dict(node) => BuildDict::new(build_dict_items(node.items))
+                .with_meta(Meta::new(node.node_index, node.range))
+                .into(),
             Expr::Name(node) => {
                 let meta = node.meta();
                 CoreBlockPyExprWithAwaitAndYield::Load(operation::Load::new(node).with_meta(meta))
@@ -661,5 +602,85 @@ impl From<Expr> for CoreBlockPyExprWithAwaitAndYield {
     }
 }
 
+/// Reports the first subexpression of `expr` that the `From<Expr>`
+/// conversion above cannot lower. Run it on user expressions before
+/// converting them, so unsupported input surfaces as a located diagnostic.
+pub(crate) fn check_lowerable_expr(expr: &Expr) -> Result<(), Diagnostic> {
+    match expr {
+        Expr::NumberLiteral(node) => match node.value {
+            ast::Number::Complex { .. } => Err(Diagnostic::unsupported(
+                "complex literals are not supported",
+                node.range,
+            )),
+            ast::Number::Int(_) | ast::Number::Float(_) => Ok(()),
+        },
+        Expr::StringLiteral(_)
+        | Expr::BytesLiteral(_)
+        | Expr::BooleanLiteral(_)
+        | Expr::NoneLiteral(_)
+        | Expr::EllipsisLiteral(_)
+        | Expr::Name(_) => Ok(()),
+        Expr::Await(node) => check_lowerable_expr(&node.value),
+        Expr::Yield(node) => node.value.as_deref().map_or(Ok(()), check_lowerable_expr),
+        Expr::YieldFrom(node) => check_lowerable_expr(&node.value),
+        Expr::Attribute(node) if matches!(node.ctx, ast::ExprContext::Load) => {
+            check_lowerable_expr(&node.value)
+        }
+        Expr::Subscript(node) if matches!(node.ctx, ast::ExprContext::Load) => {
+            check_lowerable_expr(&node.value)?;
+            check_lowerable_expr(&node.slice)
+        }
+        Expr::UnaryOp(node) => check_lowerable_expr(&node.operand),
+        Expr::BinOp(node) => {
+            check_lowerable_expr(&node.left)?;
+            check_lowerable_expr(&node.right)
+        }
+        Expr::Compare(node) if node.ops.len() == 1 && node.comparators.len() == 1 => {
+            check_lowerable_expr(&node.left)?;
+            check_lowerable_expr(&node.comparators[0])
+        }
+        Expr::Tuple(node) if matches!(node.ctx, ast::ExprContext::Load) => {
+            check_lowerable_elements(&node.elts)
+        }
+        Expr::List(node) if matches!(node.ctx, ast::ExprContext::Load) => {
+            check_lowerable_elements(&node.elts)
+        }
+        Expr::Set(node) => check_lowerable_elements(&node.elts),
+        Expr::Slice(node) => [&node.lower, &node.upper, &node.step]
+            .into_iter()
+            .flatten()
+            .try_for_each(|bound| check_lowerable_expr(bound)),
+        Expr::Dict(node) => node.items.iter().try_for_each(|item| {
+            if let Some(key) = &item.key {
+                check_lowerable_expr(key)?;
+            }
+            check_lowerable_expr(&item.value)
+        }),
+        Expr::Call(node) => {
+            check_lowerable_expr(&node.func)?;
+            check_lowerable_elements(&node.arguments.args)?;
+            node.arguments
+                .keywords
+                .iter()
+                .try_for_each(|keyword| check_lowerable_expr(&keyword.value))
+        }
+        other => Err(Diagnostic::unsupported(
+            "expression is not supported by BlockPy lowering",
+            other.range(),
+        )
+        .with_note(format!(
+            "reached the late core BlockPy boundary as `{}`",
+            crate::ruff_ast_to_string(other).trim_end()
+        ))),
+    }
+}
+
+fn check_lowerable_elements(elts: &[Expr]) -> Result<(), Diagnostic> {
+    elts.iter().try_for_each(|elt| match elt {
+        Expr::Starred(starred) => check_lowerable_expr(&starred.value),
+        other => check_lowerable_expr(other),
+    })
+}
+
 #[cfg(test)]
 mod test;
@@ -1,6 +1,8 @@
 use super::*;
 
-use crate::block_py::{BinOpKind, BlockPyLiteral, BlockPyNameLike, UnaryOpKind};
+use crate::block_py::{
+    BinOpKind, BlockPyLiteral, BlockPyNameLike, BuildDictItem, BuildElement, UnaryOpKind,
+};
 
 fn lower_semantic_expr_without_setup(expr: &Expr) -> CoreBlockPyExprWithAwaitAndYield {
     CoreBlockPyExprWithAwaitAndYield::from(expr.clone())
@@ -143,64 +145,71 @@ fn core_blockpy_expr_reduces_operator_helper_families_to_intrinsics() {
 }
 
 #[test]
-fn core_blockpy_expr_keeps_non_intrinsic_helper_families_as_named_calls() {
-    for (expr, helper_name) in [
-        ("(x, y)", "tuple_values"),
-        ("[x, y]", "list"),
-        ("{x, y}", "set"),
-        ("{x: y}", "dict"),
-    ] {
+fn core_blockpy_expr_lowers_displays_to_build_operations() {
+    let lower = |expr: &str| {
         let parsed = *parse_expression(expr).unwrap().into_syntax().body;
-        let CoreBlockPyExprWithAwaitAndYield::Call(call) =
-            CoreBlockPyExprWithAwaitAndYield::from(parsed)
-        else {
-            panic!("expected call-shaped reduced expr for {expr}");
-        };
-        assert!(
-            matches!(
-                &*call.func,
-                CoreBlockPyExprWithAwaitAndYield::Load(op)
-                    if op.name.is_runtime_symbol(helper_name)
-            ),
-            "{call:?}",
-        );
-    }
-}
-
-#[test]
-fn core_blockpy_expr_reuses_shared_tuple_splat_intrinsic_shape() {
-    let parsed = *parse_expression("(x, *xs, y)").unwrap().into_syntax().body;
-    let lowered = CoreBlockPyExprWithAwaitAndYield::from(parsed);
-    let CoreBlockPyExprWithAwaitAndYield::BinOp(op) = &lowered else {
-        panic!("expected operation-shaped reduced tuple expr");
+        CoreBlockPyExprWithAwaitAndYield::from(parsed)
     };
-    assert_eq!(op.kind, BinOpKind::Add);
-    let rendered = format!("{lowered:?}");
-    assert!(rendered.contains("tuple_from_iter(xs)"), "{rendered}");
+    assert!(matches!(
+        lower("(x, y)"),
+        CoreBlockPyExprWithAwaitAndYield::BuildTuple(op) if op.elts.len() == 2
+    ));
+    assert!(matches!(
+        lower("[x, y]"),
+        CoreBlockPyExprWithAwaitAndYield::BuildList(op) if op.elts.len() == 2
+    ));
+    assert!(matches!(
+        lower("{x, y}"),
+        CoreBlockPyExprWithAwaitAndYield::BuildSet(op) if op.elts.len() == 2
+    ));
+    assert!(matches!(
+        lower("{x: y}"),
+        CoreBlockPyExprWithAwaitAndYield::BuildDict(op) if op.items.len() == 1
+    ));
 }
 
 #[test]
-fn core_blockpy_expr_reuses_shared_tuple_splat_for_list_and_set() {
-    for (expr, intrinsic) in [("[x, *xs, y]", "list"), ("{x, *xs, y}", "set")] {
-        let parsed = *parse_expression(expr).unwrap().into_syntax().body;
-        let CoreBlockPyExprWithAwaitAndYield::Call(call) =
-            CoreBlockPyExprWithAwaitAndYield::from(parsed)
-        else {
-            panic!("expected call-shaped reduced expr for {expr}");
+fn core_blockpy_expr_keeps_star_segments_in_display_order() {
+    for expr in ["(x, *xs, y)", "[x, *xs, y]", "{x, *xs, y}"] {
+        let elts = match CoreBlockPyExprWithAwaitAndYield::from(
+            *parse_expression(expr).unwrap().into_syntax().body,
+        ) {
+            CoreBlockPyExprWithAwaitAndYield::BuildTuple(op) => op.elts,
+            CoreBlockPyExprWithAwaitAndYield::BuildList(op) => op.elts,
+            CoreBlockPyExprWithAwaitAndYield::BuildSet(op) => op.elts,
+            other => panic!("expected build operation for {expr}, got {other:?}"),
         };
-        assert!(matches!(
-            &*call.func,
-            CoreBlockPyExprWithAwaitAndYield::Load(op)
-                if op.name.is_runtime_symbol(intrinsic)
-        ));
-        let [CallArgPositional::Positional(tupleish)] = &call.args[..] else {
-            panic!("expected one positional arg for {expr}");
+        let [BuildElement::Value(x), BuildElement::Unpack(xs), BuildElement::Value(y)] = &elts[..]
+        else {
+            panic!("expected value/unpack/value segments for {expr}");
         };
-        let rendered = format!("{tupleish:?}");
-        assert!(rendered.contains("tuple_from_iter(xs)"), "{rendered}");
+        assert!(is_raw_load_name_expr(x, "x"));
+        assert!(is_raw_load_name_expr(xs, "xs"));
+        assert!(is_raw_load_name_expr(y, "y"));
     }
 }
 
+#[test]
+fn core_blockpy_expr_keeps_dict_unpack_segments_in_display_order() {
+    let parsed = *parse_expression("{a: 1, **m, a: 2}")
+        .unwrap()
+        .into_syntax()
+        .body;
+    let CoreBlockPyExprWithAwaitAndYield::BuildDict(op) =
+        CoreBlockPyExprWithAwaitAndYield::from(parsed)
+    else {
+        panic!("expected BuildDict for dict display");
+    };
+    let [BuildDictItem::Pair { key: first, .. }, BuildDictItem::Unpack(m), BuildDictItem::Pair { key: second, .. }] =
+        &op.items[..]
+    else {
+        panic!("expected pair/unpack/pair items, got {op:?}");
+    };
+    assert!(is_raw_load_name_expr(first, "a"));
+    assert!(is_raw_load_name_expr(m, "m"));
+    assert!(is_raw_load_name_expr(second, "a"));
+}
+
 #[test]
 fn helper_scoped_families_do_not_reach_core_blockpy_boundary() {
     for expr in [
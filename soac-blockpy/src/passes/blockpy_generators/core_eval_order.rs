@@ -1,16 +1,15 @@
 use crate::block_py::{
-    instr_any, Block, BlockPyFunction, BlockPyNameLike, BlockTerm, CoreBlockPyExprWithYield,
-    Del, HasMeta, Instr, Load, MapInstr, MapTerm, Mappable, Store, UnresolvedName, WithMeta,
-    Yield, YieldFrom,
+    instr_any, Block, BlockPyFunction, BlockPyNameLike, BlockTerm, CoreBlockPyExprWithYield, Del,
+    FunctionNameGen, HasMeta, Instr, Load, MapInstr, MapTerm, Mappable, Store, UnresolvedName,
+    WithMeta, Yield, YieldFrom,
 };
-use crate::namegen::fresh_name;
 use crate::passes::CoreBlockPyPassWithYield;
 use crate::py_expr;
 use ruff_python_ast as ast;
 use soac_macros::match_default;
 
-fn fresh_eval_name() -> ast::ExprName {
-    let name = fresh_name("eval");
+fn fresh_eval_name(name_gen: &FunctionNameGen) -> ast::ExprName {
+    let name = name_gen.next_tmp_name("eval");
     let ast::Expr::Name(expr) = py_expr!("{name:id}", name = name.as_str()) else {
         unreachable!();
     };
@@ -55,13 +54,14 @@ fn expr_contains_yield(expr: &CoreBlockPyExprWithYield) -> bool {
 }
 
 fn hoist_core_expr_if_contains_yield(
+    name_gen: &FunctionNameGen,
     expr: CoreBlockPyExprWithYield,
     out: &mut Vec<CoreBlockPyExprWithYield>,
     cleanup: &mut Vec<ast::ExprName>,
 ) -> CoreBlockPyExprWithYield {
-    let expr = make_eval_order_explicit_in_core_expr(expr, out, cleanup);
+    let expr = make_eval_order_explicit_in_core_expr(name_gen, expr, out, cleanup);
     if expr_contains_yield(&expr) {
-        let target = fresh_eval_name();
+        let target = fresh_eval_name(name_gen);
         out.push(typed_store_expr(target.clone(), expr));
         cleanup.push(target.clone());
         let meta = target.meta();
@@ -72,6 +72,7 @@ fn hoist_core_expr_if_contains_yield(
 }
 
 fn make_eval_order_explicit_in_core_expr(
+    name_gen: &FunctionNameGen,
     expr: CoreBlockPyExprWithYield,
     out: &mut Vec<CoreBlockPyExprWithYield>,
     cleanup: &mut Vec<ast::ExprName>,
@@ -82,6 +83,7 @@ fn make_eval_order_explicit_in_core_expr(
             let meta = yield_expr.meta();
             CoreBlockPyExprWithYield::Yield(
                 Yield::new(hoist_core_expr_if_contains_yield(
+                    name_gen,
                     *yield_expr.value,
                     out,
                     cleanup,
@@ -93,6 +95,7 @@ fn make_eval_order_explicit_in_core_expr(
             let meta = yield_from_expr.meta();
             CoreBlockPyExprWithYield::YieldFrom(
                 YieldFrom::new(hoist_core_expr_if_contains_yield(
+                    name_gen,
                     *yield_from_expr.value,
                     out,
                     cleanup,
@@ -102,13 +105,14 @@ fn make_eval_order_explicit_in_core_expr(
         },
         rest => rest
             .map_same_children(&mut |value| {
-            hoist_core_expr_if_contains_yield(value, out, cleanup)
+            hoist_core_expr_if_contains_yield(name_gen, value, out, cleanup)
         })
             .into(),
     })
 }
 
 struct HoistYieldAtomsInCoreTerm<'a, 'b> {
+    name_gen: &'a FunctionNameGen,
     out: &'a mut Vec<CoreBlockPyExprWithYield>,
     cleanup: &'b mut Vec<ast::ExprName>,
 }
@@ -117,7 +121,7 @@ impl MapInstr<CoreBlockPyExprWithYield, CoreBlockPyExprWithYield>
     for HoistYieldAtomsInCoreTerm<'_, '_>
 {
     fn map_instr(&mut self, expr: CoreBlockPyExprWithYield) -> CoreBlockPyExprWithYield {
-        hoist_core_expr_if_contains_yield(expr, self.out, self.cleanup)
+        hoist_core_expr_if_contains_yield(self.name_gen, expr, self.out, self.cleanup)
     }
 
     fn map_name(&mut self, name: UnresolvedName) -> UnresolvedName {
@@ -126,11 +130,13 @@ impl MapInstr<CoreBlockPyExprWithYield, CoreBlockPyExprWithYield>
 }
 
 fn make_eval_order_explicit_in_core_term(
+    name_gen: &FunctionNameGen,
     term: BlockTerm<CoreBlockPyExprWithYield>,
     out: &mut Vec<CoreBlockPyExprWithYield>,
 ) -> BlockTerm<CoreBlockPyExprWithYield> {
     let mut cleanup = Vec::new();
     let mut map = HoistYieldAtomsInCoreTerm {
+        name_gen,
         out,
         cleanup: &mut cleanup,
     };
@@ -140,6 +146,7 @@ fn make_eval_order_explicit_in_core_term(
 pub(crate) fn make_eval_order_explicit_in_core_callable_def(
     callable_def: BlockPyFunction<CoreBlockPyPassWithYield>,
 ) -> BlockPyFunction<CoreBlockPyPassWithYield> {
+    let name_gen = callable_def.name_gen.share();
     callable_def.map_blocks(|block| {
         let Block {
             label,
@@ -153,11 +160,7 @@ pub(crate) fn make_eval_order_explicit_in_core_callable_def(
             let mut setup = Vec::new();
             let mut cleanup = Vec::new();
             let expr = if expr_contains_yield(&expr) {
-                make_eval_order_explicit_in_core_expr(
-                    expr,
-                    &mut setup,
-                    &mut cleanup,
-                )
+                make_eval_order_explicit_in_core_expr(&name_gen, expr, &mut setup, &mut cleanup)
             } else {
                 expr
             };
@@ -165,7 +168,7 @@ pub(crate) fn make_eval_order_explicit_in_core_callable_def(
             body.push(expr);
             append_stmt_cleanup(&mut body, cleanup);
         }
-        let term = make_eval_order_explicit_in_core_term(input_term, &mut body);
+        let term = make_eval_order_explicit_in_core_term(&name_gen, input_term, &mut body);
         Block {
             label,
             body,
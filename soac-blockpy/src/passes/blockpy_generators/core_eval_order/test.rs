@@ -2,8 +2,8 @@ use super::*;
 use crate::block_py::{
     BinOp, BinOpKind, Block, BlockLabel, BlockPyFunction, BlockPyModule, BlockTerm,
     CallArgPositional, CallableScopeInfo, CoreBlockPyExprWithAwaitAndYield,
-    CoreBlockPyExprWithYield, FunctionId, FunctionKind, FunctionName, Meta, ModuleNameGen,
-    Store, UnresolvedName, WithMeta, YieldFrom,
+    CoreBlockPyExprWithYield, FunctionId, FunctionKind, FunctionName, Meta, ModuleNameGen, Store,
+    UnresolvedName, WithMeta, YieldFrom,
 };
 use crate::passes::core_await_lower::lower_awaits_in_core_blockpy_module;
 use crate::passes::{CoreBlockPyPassWithAwaitAndYield, CoreBlockPyPassWithYield};
@@ -68,10 +68,10 @@ fn lower_yield_block(
     make_eval_order_explicit_in_core_callable_def(test_callable_def_with_yield_block(
         lower_awaits_in_test_block(block),
     ))
-        .blocks
-        .into_iter()
-        .next()
-        .expect("test callable should have one block")
+    .blocks
+    .into_iter()
+    .next()
+    .expect("test callable should have one block")
 }
 
 #[test]
@@ -127,7 +127,7 @@ fn eval_order_hoists_nested_call_in_assignment_rhs() {
     let block = Block {
         label: BlockLabel::from_index(0),
         body: vec![Store::new(
-            fresh_eval_name(),
+            fresh_eval_name(&test_name_gen()),
             Box::new(CoreBlockPyExprWithAwaitAndYield::from(crate::py_expr!(
                 "f(g(x))"
             ))),
@@ -211,8 +211,10 @@ fn eval_order_hoists_yield_from_in_assignment_call_argument() {
                 BinOpKind::InplaceAdd,
                 CoreBlockPyExprWithAwaitAndYield::from(crate::py_expr!("total")),
                 CoreBlockPyExprWithAwaitAndYield::YieldFrom(
-                    YieldFrom::new(CoreBlockPyExprWithAwaitAndYield::from(crate::py_expr!("it")))
-                        .with_meta(Meta::default()),
+                    YieldFrom::new(CoreBlockPyExprWithAwaitAndYield::from(crate::py_expr!(
+                        "it"
+                    )))
+                    .with_meta(Meta::default()),
                 ),
             ))),
         )
@@ -11,11 +11,11 @@ use crate::block_py::{
     CallableScopeInfo, CellBindingKind, CellRefForName, ClosureInit, ClosureSlot, CoreBlockPyExpr,
     CoreBlockPyExprWithYield, CoreNumberLiteral, CoreNumberLiteralValue, CoreStringLiteral,
     FunctionId, FunctionKind, FunctionName, FunctionNameGen, GetAttr, ImplicitNoneExpr, Instr,
-    Load, MakeFunction, Mappable, ModuleNameGen, ScopeExprNode, StorageLayout, Store,
-    TermBranchTable, TermIf, TermRaise, TryMapFunction, TryMapInstr, TryMapTerm, UnaryOp,
-    UnaryOpKind, UnresolvedName,
+    Load, MakeFunction, Mappable, ScopeExprNode, StorageLayout, Store, TermBranchTable, TermIf,
+    TermRaise, TryMapFunction, TryMapInstr, TryMapTerm, UnaryOp, UnaryOpKind, UnresolvedName,
 };
 use crate::passes::ast_to_ast::scope_helpers::is_internal_symbol;
+use crate::passes::parallel::CallablePool;
 use crate::passes::ruff_to_blockpy::{attach_exception_edges_to_blocks, lowered_exception_edges};
 use crate::passes::{CoreBlockPyPass, CoreBlockPyPassWithYield};
 use ruff_python_ast::{self as ast};
@@ -1665,13 +1665,12 @@ fn ordered_resume_binding_logical_names(
 
 pub(crate) fn lower_generator_like_function(
     callable: BlockPyFunction<CoreBlockPyPassWithYield>,
-    module_name_gen: &ModuleNameGen,
+    resume_name_gen: FunctionNameGen,
 ) -> Vec<BlockPyFunction<CoreBlockPyPass>> {
     assert!(
         is_generator_like(callable.kind),
         "generator lowering only applies to generator-like callables"
     );
-    let resume_name_gen = module_name_gen.next_function_name_gen();
     let resume_function_id = resume_name_gen.function_id();
     let storage_layout = build_generator_storage_layout(&callable);
     let persistent_state_order = persistent_generator_state_order(&storage_layout);
@@ -1740,30 +1739,58 @@ pub(crate) fn lower_generator_like_function(
     vec![visible_function, resume_function]
 }
 
+fn lower_yield_in_callable(
+    callable: BlockPyFunction<CoreBlockPyPassWithYield>,
+    resume_name_gen: Option<FunctionNameGen>,
+) -> Vec<BlockPyFunction<CoreBlockPyPass>> {
+    let callable = make_eval_order_explicit_in_core_callable_def(callable);
+    match callable.kind {
+        FunctionKind::Function => {
+            let qualname = callable.names.qualname.clone();
+            let mut mapper = ErrOnYield;
+            vec![mapper.try_map_fn(callable).unwrap_or_else(|_| {
+                panic!(
+                    "core BlockPy yield lowering is not explicit yet: yield-family expr reached the core no-yield boundary for {}",
+                    qualname
+                )
+            })]
+        }
+        FunctionKind::Generator | FunctionKind::Coroutine | FunctionKind::AsyncGenerator => {
+            lower_generator_like_function(
+                callable,
+                resume_name_gen.expect("generator-like callable should have a resume name gen"),
+            )
+        }
+    }
+}
+
 pub(crate) fn lower_yield_in_lowered_core_blockpy_module_bundle(
     module: BlockPyModule<CoreBlockPyPassWithYield>,
+    pool: &CallablePool,
 ) -> BlockPyModule<CoreBlockPyPass> {
-    let module =
-        module.map_callable_defs(make_eval_order_explicit_in_core_callable_def);
-    let module_name_gen = module.module_name_gen.clone();
-    let mut callable_defs = Vec::new();
-    for callable in module.callable_defs {
-        match callable.kind {
-            FunctionKind::Function => {
-                let qualname = callable.names.qualname.clone();
-                let mut mapper = ErrOnYield;
-                callable_defs.push(mapper.try_map_fn(callable).unwrap_or_else(|_| {
-                    panic!(
-                        "core BlockPy yield lowering is not explicit yet: yield-family expr reached the core no-yield boundary for {}",
-                        qualname
-                    )
-                }));
-            }
-            FunctionKind::Generator | FunctionKind::Coroutine | FunctionKind::AsyncGenerator => {
-                callable_defs.extend(lower_generator_like_function(callable, &module_name_gen));
-            }
-        }
-    }
+    debug_assert!(
+        module.module_constants.is_empty() && module.counter_defs.is_empty(),
+        "yield lowering runs before module constants and counters are populated"
+    );
+    let module_name_gen = module.module_name_gen;
+    // Resume functions get their ids up front, in callable order, so the
+    // lowered module does not depend on which worker reaches a generator first.
+    let work = module
+        .callable_defs
+        .into_iter()
+        .map(|callable| {
+            let resume_name_gen =
+                is_generator_like(callable.kind).then(|| module_name_gen.next_function_name_gen());
+            (callable, resume_name_gen)
+        })
+        .collect::<Vec<_>>();
+    let callable_defs = pool
+        .map(work, |(callable, resume_name_gen)| {
+            lower_yield_in_callable(callable, resume_name_gen)
+        })
+        .into_iter()
+        .flatten()
+        .collect();
     BlockPyModule {
         module_name_gen,
         global_names: Vec::new(),
@@ -1,18 +1,24 @@
 use crate::block_py::{
     BlockEdge, BlockPyFunction, BlockPyModule, BlockTerm, CoreBlockPyExpr, ResolvedStorageBlock,
 };
+use crate::passes::parallel::CallablePool;
 use crate::passes::ruff_to_blockpy::populate_exception_edge_args;
 use crate::passes::ResolvedStorageBlockPyPass;
 
 pub fn lower_try_jump_exception_flow(
     module: &BlockPyModule<ResolvedStorageBlockPyPass>,
 ) -> BlockPyModule<ResolvedStorageBlockPyPass> {
-    let callable_defs = module
-        .callable_defs
-        .iter()
-        .cloned()
-        .map(lower_function_try_jump_exception_flow)
-        .collect();
+    lower_try_jump_exception_flow_with_pool(module, &CallablePool::sequential())
+}
+
+pub(crate) fn lower_try_jump_exception_flow_with_pool(
+    module: &BlockPyModule<ResolvedStorageBlockPyPass>,
+    pool: &CallablePool,
+) -> BlockPyModule<ResolvedStorageBlockPyPass> {
+    let callable_defs = pool.map(
+        module.callable_defs.clone(),
+        lower_function_try_jump_exception_flow,
+    );
     BlockPyModule {
         module_name_gen: module.module_name_gen.clone(),
         global_names: module.global_names.clone(),
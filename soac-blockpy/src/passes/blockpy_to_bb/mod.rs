@@ -2,6 +2,8 @@ mod exception_pass;
 mod strings;
 
 pub use exception_pass::lower_try_jump_exception_flow;
+pub(crate) use exception_pass::lower_try_jump_exception_flow_with_pool;
 pub use strings::normalize_bb_module_strings;
+pub(crate) use strings::normalize_bb_module_strings_with_pool;
 #[cfg(test)]
 mod test;
@@ -1,22 +1,58 @@
 use crate::block_py::{
-    BlockPyFunction, BlockPyModule, CodegenBlockPyExpr, HasMeta, LiteralValue, Load,
-    LocatedCoreBlockPyExpr, LocatedName, MapFunction, MapInstr, Mappable, NameLocation, WithMeta,
+    walk_expr_mut, BlockPyModule, CodegenBlockPyExpr, HasMeta, LiteralValue, Load,
+    LocatedCoreBlockPyExpr, LocatedName, MapFunction, MapInstr, Mappable, NameLocation, VisitMut,
+    WithMeta,
 };
+use crate::passes::parallel::CallablePool;
 use crate::passes::{CodegenBlockPyPass, CoreBlockPyExpr, ResolvedStorageBlockPyPass};
 use soac_macros::match_default;
 
 pub fn normalize_bb_module_strings(
     module: &BlockPyModule<ResolvedStorageBlockPyPass>,
 ) -> BlockPyModule<CodegenBlockPyPass> {
-    let mut normalizer = CodegenExprNormalizer::default();
+    normalize_bb_module_strings_with_pool(module, &CallablePool::sequential())
+}
+
+pub(crate) fn normalize_bb_module_strings_with_pool(
+    module: &BlockPyModule<ResolvedStorageBlockPyPass>,
+    pool: &CallablePool,
+) -> BlockPyModule<CodegenBlockPyPass> {
     let module = module.clone();
     let mut module_constants = module.module_constants;
-    let callable_defs = module
-        .callable_defs
-        .into_iter()
-        .map(|function| normalizer.map_fn(function))
-        .collect::<Vec<BlockPyFunction<CodegenBlockPyPass>>>();
-    module_constants.extend(normalizer.module_constants);
+    let first_new_constant =
+        u32::try_from(module_constants.len()).expect("module constant count should fit in u32");
+    // Every callable numbers its literals from `first_new_constant`; once the
+    // workers are done, each callable's literals are shifted past those the
+    // callables before it actually pushed, so indices match a sequential walk.
+    let normalized = pool.map(module.callable_defs, |function| {
+        let mut normalizer = CodegenExprNormalizer {
+            first_constant_index: first_new_constant,
+            module_constants: Vec::new(),
+        };
+        let function = normalizer.map_fn(function);
+        (function, normalizer.module_constants)
+    });
+    let mut shift = 0u32;
+    let mut work = Vec::with_capacity(normalized.len());
+    for (function, constants) in normalized {
+        work.push((function, shift));
+        shift = u32::try_from(constants.len())
+            .ok()
+            .and_then(|count| shift.checked_add(count))
+            .filter(|shift| first_new_constant.checked_add(*shift).is_some())
+            .expect("module constant count should fit in u32");
+        module_constants.extend(constants);
+    }
+    let callable_defs = pool.map(work, |(mut function, shift)| {
+        if shift != 0 {
+            ConstantIndexShifter {
+                first_new_constant,
+                shift,
+            }
+            .visit_fn_mut(&mut function);
+        }
+        function
+    });
     BlockPyModule {
         module_name_gen: module.module_name_gen,
         global_names: module.global_names,
@@ -26,14 +62,43 @@ pub fn normalize_bb_module_strings(
     }
 }
 
-#[derive(Default)]
+fn constant_name(constant_index: u32) -> LocatedName {
+    LocatedName {
+        id: format!("__dp_constant_{constant_index}").into(),
+        location: NameLocation::Constant(constant_index),
+    }
+}
+
+/// Moves the constants one callable added by `shift`, leaving the ones that
+/// were already in the module before string normalization alone.
+struct ConstantIndexShifter {
+    first_new_constant: u32,
+    shift: u32,
+}
+
+impl VisitMut<CodegenBlockPyExpr> for ConstantIndexShifter {
+    fn visit_instr_mut(&mut self, expr: &mut CodegenBlockPyExpr) {
+        if let CodegenBlockPyExpr::Load(load) = expr {
+            if let NameLocation::Constant(index) = load.name.location {
+                if index >= self.first_new_constant {
+                    load.name = constant_name(index + self.shift);
+                }
+            }
+        }
+        walk_expr_mut(self, expr);
+    }
+}
+
 struct CodegenExprNormalizer {
+    first_constant_index: u32,
     module_constants: Vec<LocatedCoreBlockPyExpr>,
 }
 
 impl CodegenExprNormalizer {
     fn push_module_constant(&mut self, literal: LiteralValue) -> u32 {
         let index = u32::try_from(self.module_constants.len())
+            .ok()
+            .and_then(|offset| self.first_constant_index.checked_add(offset))
             .expect("module constant count should fit in u32");
         self.module_constants
             .push(LocatedCoreBlockPyExpr::Literal(literal));
@@ -47,10 +112,7 @@ impl MapInstr<LocatedCoreBlockPyExpr, CodegenBlockPyExpr> for CodegenExprNormali
             LocatedCoreBlockPyExpr::Literal(literal) => {
                 let meta = literal.meta();
                 let constant_index = self.push_module_constant(literal);
-                Load::new(LocatedName {
-                    id: format!("__dp_constant_{constant_index}").into(),
-                    location: NameLocation::Constant(constant_index),
-                })
+                Load::new(constant_name(constant_index))
                 .with_meta(meta)
                 .into()
             },
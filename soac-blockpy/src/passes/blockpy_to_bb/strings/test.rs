@@ -44,6 +44,9 @@ fn collect_helper_like_names_in_expr(out: &mut Vec<String>, expr: &CodegenBlockP
         CodegenBlockPyExpr::CalleeFunctionId(operation) => {
             operation.visit_children(&mut HelperNameVisitor { out });
         }
+        CodegenBlockPyExpr::InlineGuard(operation) => {
+            operation.visit_children(&mut HelperNameVisitor { out });
+        }
         CodegenBlockPyExpr::GetAttr(operation) => {
             out.push("__dp_getattr".to_string());
             operation.visit_children(&mut HelperNameVisitor { out });
@@ -90,6 +93,12 @@ fn collect_helper_like_names_in_expr(out: &mut Vec<String>, expr: &CodegenBlockP
         CodegenBlockPyExpr::IncrementCounter(operation) => {
             operation.visit_children(&mut HelperNameVisitor { out });
         }
+        CodegenBlockPyExpr::PreloadGlobal(operation) => {
+            operation.visit_children(&mut HelperNameVisitor { out });
+        }
+        CodegenBlockPyExpr::GuardedGlobalLoad(operation) => {
+            operation.visit_children(&mut HelperNameVisitor { out });
+        }
         CodegenBlockPyExpr::CellRef(operation) => {
             operation.visit_children(&mut HelperNameVisitor { out });
         }
@@ -0,0 +1,272 @@
+//! Definite-assignment analysis for plain function locals in core BlockPy.
+//!
+//! Stack slots start out holding the `DELETED` sentinel, so a load of a local
+//! only needs a `load_deleted_name` check if some path from the function entry
+//! reaches it without storing to the local first, or after deleting it. This
+//! is a forward must-analysis over the block CFG: a local is assigned on entry
+//! to a block when it is assigned on every edge into it.
+//!
+//! Exception edges leave a block part-way through, so the state they carry is
+//! the block's entry state minus everything the block deletes.
+//!
+//! Cells are not tracked: nested scopes can bind or delete them behind the
+//! analysis' back.
+
+use std::collections::HashSet;
+
+use ruff_text_size::TextRange;
+
+use crate::block_py::analysis::{Cfg, EdgeKind};
+use crate::block_py::{
+    walk_expr, walk_term, Block, BlockPyFunction, CoreBlockPyExpr, HasMeta, Visit,
+};
+use crate::passes::CoreBlockPyPass;
+
+/// A load of a function local that some path reaches while the local is
+/// unbound, where CPython would raise `UnboundLocalError`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MaybeUnboundLocal {
+    pub qualname: String,
+    pub name: String,
+    pub range: TextRange,
+}
+
+pub(crate) struct DefiniteAssignment {
+    tracked: HashSet<String>,
+    /// Locals assigned on entry to each block, indexed like `blocks`. Blocks
+    /// unreachable from the entry keep every tracked name.
+    block_entry: Vec<HashSet<String>>,
+}
+
+impl DefiniteAssignment {
+    /// Runs the analysis for the locals in `tracked`. Loads and stores are
+    /// matched by name, so `function` must already have gone through name
+    /// binding's rewrite of semantic stores and deletes.
+    pub(crate) fn analyze(
+        function: &BlockPyFunction<CoreBlockPyPass>,
+        tracked: HashSet<String>,
+    ) -> Self {
+        let blocks = &function.blocks;
+        let cfg = Cfg::new(function);
+        let mut block_entry = vec![tracked.clone(); blocks.len()];
+        if blocks.is_empty() {
+            return Self {
+                tracked,
+                block_entry,
+            };
+        }
+
+        let mut analysis = Self {
+            tracked,
+            block_entry: Vec::new(),
+        };
+        let mut reached = vec![false; blocks.len()];
+        let mut entry = analysis.block_params(&blocks[0]);
+        entry.extend(
+            function
+                .params
+                .names()
+                .into_iter()
+                .filter(|name| analysis.tracked.contains(name)),
+        );
+        block_entry[0] = entry;
+        reached[0] = true;
+
+        let mut worklist = vec![0];
+        while let Some(index) = worklist.pop() {
+            let block = &blocks[index];
+            let mut assigned = block_entry[index].clone();
+            let mut deleted = HashSet::new();
+            for stmt in &block.body {
+                let effects = StoreEffects::of(&analysis.tracked, stmt);
+                deleted.extend(effects.killed.iter().cloned());
+                effects.apply(&mut assigned);
+            }
+            let mut on_raise = block_entry[index].clone();
+            on_raise.retain(|name| !deleted.contains(name));
+            for edge in cfg.successors(index) {
+                let target = edge.to;
+                let mut state = match edge.kind {
+                    EdgeKind::Normal => assigned.clone(),
+                    EdgeKind::Exception => on_raise.clone(),
+                };
+                state.extend(analysis.block_params(&blocks[target]));
+                if !reached[target] {
+                    reached[target] = true;
+                    block_entry[target] = state;
+                    worklist.push(target);
+                    continue;
+                }
+                let before = block_entry[target].len();
+                block_entry[target].retain(|name| state.contains(name));
+                if block_entry[target].len() != before {
+                    worklist.push(target);
+                }
+            }
+        }
+
+        analysis.block_entry = block_entry;
+        analysis
+    }
+
+    pub(crate) fn tracked(&self) -> &HashSet<String> {
+        &self.tracked
+    }
+
+    pub(crate) fn block_entry(&self, index: usize) -> &HashSet<String> {
+        &self.block_entry[index]
+    }
+
+    /// The locals that are safe to load while `stmt` runs, given the state
+    /// before it. Stores in `stmt` only count once it completes.
+    pub(crate) fn assigned_during(
+        &self,
+        assigned: &HashSet<String>,
+        stmt: &CoreBlockPyExpr,
+    ) -> HashSet<String> {
+        let effects = StoreEffects::of(&self.tracked, stmt);
+        assigned
+            .iter()
+            .filter(|name| !effects.killed.contains(*name))
+            .cloned()
+            .collect()
+    }
+
+    /// Advances `assigned` past `stmt`.
+    pub(crate) fn transfer(&self, assigned: &mut HashSet<String>, stmt: &CoreBlockPyExpr) {
+        StoreEffects::of(&self.tracked, stmt).apply(assigned);
+    }
+
+    /// The tracked locals missing from `assigned`.
+    pub(crate) fn unassigned<'a>(
+        &'a self,
+        assigned: &'a HashSet<String>,
+    ) -> impl Iterator<Item = &'a String> + 'a {
+        self.tracked.difference(assigned)
+    }
+
+    /// Every load of a tracked local the analysis cannot prove assigned, in
+    /// source order.
+    pub(crate) fn maybe_unbound_locals(
+        &self,
+        function: &BlockPyFunction<CoreBlockPyPass>,
+    ) -> Vec<MaybeUnboundLocal> {
+        let mut collector = UnassignedLoadCollector {
+            tracked: &self.tracked,
+            assigned: HashSet::new(),
+            ranges: Vec::new(),
+        };
+        for (index, block) in function.blocks.iter().enumerate() {
+            let mut assigned = self.block_entry[index].clone();
+            for stmt in &block.body {
+                collector.assigned = self.assigned_during(&assigned, stmt);
+                collector.visit_instr(stmt);
+                self.transfer(&mut assigned, stmt);
+            }
+            collector.assigned = assigned;
+            walk_term(&mut collector, &block.term);
+        }
+        let mut found = collector
+            .ranges
+            .into_iter()
+            .filter(|(_, range)| !range.is_empty())
+            .map(|(name, range)| MaybeUnboundLocal {
+                qualname: function.names.qualname.clone(),
+                name,
+                range,
+            })
+            .collect::<Vec<_>>();
+        found.sort_by_key(|load| (load.range.start(), load.range.end()));
+        found.dedup();
+        found
+    }
+
+    fn block_params(&self, block: &Block<CoreBlockPyExpr, CoreBlockPyExpr>) -> HashSet<String> {
+        block
+            .params
+            .iter()
+            .filter(|param| self.tracked.contains(&param.name))
+            .map(|param| param.name.clone())
+            .collect()
+    }
+}
+
+/// The tracked locals a statement binds and unbinds. Name binding lowers
+/// `del x` on a local to a store of the `DELETED` sentinel.
+#[derive(Default)]
+struct StoreEffects {
+    assigned: Vec<String>,
+    killed: Vec<String>,
+}
+
+impl StoreEffects {
+    fn of(tracked: &HashSet<String>, stmt: &CoreBlockPyExpr) -> Self {
+        let mut collector = StoreEffectCollector {
+            tracked,
+            effects: Self::default(),
+        };
+        collector.visit_instr(stmt);
+        collector.effects
+    }
+
+    fn apply(self, assigned: &mut HashSet<String>) {
+        for name in self.killed {
+            assigned.remove(&name);
+        }
+        assigned.extend(self.assigned);
+    }
+}
+
+struct StoreEffectCollector<'a> {
+    tracked: &'a HashSet<String>,
+    effects: StoreEffects,
+}
+
+impl Visit<CoreBlockPyExpr> for StoreEffectCollector<'_> {
+    fn visit_instr(&mut self, expr: &CoreBlockPyExpr) {
+        walk_expr(self, expr);
+        match expr {
+            CoreBlockPyExpr::Store(op) if self.tracked.contains(op.name.id_str()) => {
+                let name = op.name.id_str().to_string();
+                if is_deleted_sentinel(&op.value) {
+                    self.effects.assigned.retain(|assigned| *assigned != name);
+                    self.effects.killed.push(name);
+                } else {
+                    self.effects.killed.retain(|killed| *killed != name);
+                    self.effects.assigned.push(name);
+                }
+            }
+            CoreBlockPyExpr::Del(op) if self.tracked.contains(op.name.id_str()) => {
+                let name = op.name.id_str().to_string();
+                self.effects.assigned.retain(|assigned| *assigned != name);
+                self.effects.killed.push(name);
+            }
+            _ => {}
+        }
+    }
+}
+
+fn is_deleted_sentinel(expr: &CoreBlockPyExpr) -> bool {
+    matches!(expr, CoreBlockPyExpr::Load(op) if op.name.is_runtime_symbol("DELETED"))
+}
+
+struct UnassignedLoadCollector<'a> {
+    tracked: &'a HashSet<String>,
+    assigned: HashSet<String>,
+    ranges: Vec<(String, TextRange)>,
+}
+
+impl Visit<CoreBlockPyExpr> for UnassignedLoadCollector<'_> {
+    fn visit_instr(&mut self, expr: &CoreBlockPyExpr) {
+        if let CoreBlockPyExpr::Load(op) = expr {
+            let name = op.name.id_str();
+            if self.tracked.contains(name) && !self.assigned.contains(name) {
+                self.ranges.push((name.to_string(), op.meta().range));
+            }
+        }
+        walk_expr(self, expr);
+    }
+}
+
+#[cfg(test)]
+mod test;
@@ -0,0 +1,157 @@
+use crate::block_py::CoreBlockPyExpr;
+use crate::diagnostic::source_location;
+use crate::{lower_python_to_blockpy_for_testing, maybe_unbound_locals};
+
+/// Returns `(qualname, name, line)` for every maybe-unbound load.
+fn unbound(source: &str) -> Vec<(String, String, usize)> {
+    maybe_unbound_locals(source)
+        .expect("lint should succeed")
+        .into_iter()
+        .map(|load| {
+            (
+                load.qualname,
+                load.name,
+                source_location(source, load.range.start()).line,
+            )
+        })
+        .collect()
+}
+
+fn found(qualname: &str, name: &str, line: usize) -> (String, String, usize) {
+    (qualname.to_string(), name.to_string(), line)
+}
+
+/// Counts the `load_deleted_name` checks left after name binding.
+fn deleted_name_checks(source: &str) -> usize {
+    let lowered = lower_python_to_blockpy_for_testing(source).expect("transform should succeed");
+    let module = lowered
+        .pass_tracker
+        .pass_name_binding()
+        .expect("name_binding pass should be tracked");
+    module
+        .module_constants
+        .iter()
+        .filter(|constant| {
+            matches!(
+                constant,
+                CoreBlockPyExpr::Load(op) if op.name.is_runtime_symbol("load_deleted_name")
+            )
+        })
+        .count()
+}
+
+#[test]
+fn store_on_one_branch_is_maybe_unbound() {
+    let source = "\
+def f(flag):
+    if flag:
+        x = 1
+    return x
+";
+    assert_eq!(unbound(source), vec![found("f", "x", 4)]);
+    assert_eq!(deleted_name_checks(source), 1);
+}
+
+#[test]
+fn store_on_every_branch_is_assigned() {
+    let source = "\
+def f(flag):
+    if flag:
+        x = 1
+    else:
+        x = 2
+    return x
+";
+    assert_eq!(unbound(source), Vec::new());
+    assert_eq!(deleted_name_checks(source), 0);
+}
+
+#[test]
+fn parameters_are_assigned_on_entry() {
+    let source = "\
+def f(a, *args, b=1, **kwargs):
+    return a, args, b, kwargs
+";
+    assert_eq!(unbound(source), Vec::new());
+}
+
+#[test]
+fn loop_target_is_maybe_unbound_after_the_loop() {
+    let source = "\
+def f(items):
+    for item in items:
+        last = item
+    return item, last
+";
+    assert_eq!(
+        unbound(source),
+        vec![found("f", "item", 4), found("f", "last", 4)]
+    );
+}
+
+#[test]
+fn delete_unbinds_until_the_next_store() {
+    let rebound = "\
+def f():
+    x = 1
+    del x
+    x = 2
+    return x
+";
+    assert_eq!(unbound(rebound), Vec::new());
+    assert_eq!(deleted_name_checks(rebound), 0);
+
+    let deleted = "\
+def f():
+    x = 1
+    del x
+    return x
+";
+    assert_eq!(unbound(deleted), vec![found("f", "x", 4)]);
+    assert_eq!(deleted_name_checks(deleted), 1);
+}
+
+#[test]
+fn handler_only_sees_stores_the_try_body_finished() {
+    let source = "\
+def f(g):
+    try:
+        x = g()
+    except Exception:
+        return x
+    return x
+";
+    assert_eq!(unbound(source), vec![found("f", "x", 5)]);
+}
+
+#[test]
+fn cells_and_globals_are_not_reported() {
+    let source = "\
+def f(flag):
+    global y
+    if flag:
+        x = 1
+        y = 1
+    def g():
+        return x
+    return g, y
+";
+    assert_eq!(unbound(source), Vec::new());
+}
+
+#[test]
+fn nested_functions_report_their_qualname() {
+    let source = "\
+def outer():
+    def inner(flag):
+        while flag:
+            found = flag
+            flag = False
+        return found
+    return inner
+";
+    assert_eq!(
+        unbound(source),
+        vec![found("outer.<locals>.inner", "found", 6)]
+    );
+}
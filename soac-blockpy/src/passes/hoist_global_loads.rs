@@ -0,0 +1,272 @@
+//! Hoisting of loop-invariant global loads out of loops.
+//!
+//! A global that a loop loads is preloaded into a fresh local by a preheader
+//! block placed in front of the loop header. The preload only peeks at the
+//! global's module cache slot, so it never raises and never resolves a lazy
+//! import, and it records the cache's version next to the local. Each load in
+//! the loop becomes a `GuardedGlobalLoad` that reuses the local, borrowed,
+//! while the version is unchanged. Python code can still rebind the global
+//! from inside the loop (through a call, `globals()` or another thread); that
+//! bumps the version, and the next guarded load does the ordinary load and
+//! rebinds the local to its result. The local is deleted again on every edge
+//! leaving the loop, so it pins a stale value no longer than the loop.
+//!
+//! Only plain functions are handled: generator state does not live in stack
+//! slots. Loops entered through an exception edge, or whose header takes
+//! block params, are left alone, since a preheader would have to forward
+//! them. So are loops that store or delete any global: the version is shared
+//! by the whole module, so each iteration would invalidate every guard.
+//!
+//! A guard hit hands out a borrow of the local, which stays valid until the
+//! local is rebound. Only the guarded load itself rebinds it, so each
+//! statement guards at most one load of each global and leaves the others as
+//! ordinary loads.
+
+use crate::block_py::analysis::{CfgAnalysis, EdgeKind, Loop};
+use crate::block_py::{
+    walk_expr, walk_expr_mut, walk_term_mut, Block, BlockEdge, BlockPyFunction, BlockPyModule,
+    BlockTerm, CodegenBlockPyExpr, Del, FunctionKind, GlobalSlot, GuardedGlobalLoad, HasMeta,
+    LocatedName, Meta, NameLocation, PreloadGlobal, StorageLayout, Visit, VisitMut, WithMeta,
+};
+use crate::passes::CodegenBlockPyPass;
+
+/// Hoists loop-invariant global loads in every function of `module`. New
+/// blocks get labels from each function's name generator, so the module
+/// needs `relabel_dense_bb_module` afterwards.
+pub fn hoist_loop_invariant_global_loads_in_bb_module(
+    module: &mut BlockPyModule<CodegenBlockPyPass>,
+) {
+    for function in &mut module.callable_defs {
+        hoist_global_loads_in_function(function);
+    }
+}
+
+fn hoist_global_loads_in_function(function: &mut BlockPyFunction<CodegenBlockPyPass>) {
+    if function.kind != FunctionKind::Function || function.blocks.is_empty() {
+        return;
+    }
+    let analysis = CfgAnalysis::new(function);
+    // Outer loops come first, so a global invariant in an enclosing loop is
+    // hoisted all the way out; its loads are then no longer plain loads when
+    // the inner loops are visited. Preheaders are appended after the
+    // existing blocks, so the analysis' block indices stay valid.
+    for lp in analysis.loops.loops() {
+        let Some(entries) = preheader_entries(function, &analysis, lp) else {
+            continue;
+        };
+        let hoisted = hoistable_globals(function, lp);
+        if hoisted.is_empty() {
+            continue;
+        }
+        insert_preheader(function, &analysis, lp, &entries, hoisted);
+    }
+}
+
+/// The blocks outside `lp` that jump to its header, or `None` when the loop
+/// cannot take a preheader.
+fn preheader_entries(
+    function: &BlockPyFunction<CodegenBlockPyPass>,
+    analysis: &CfgAnalysis,
+    lp: &Loop,
+) -> Option<Vec<usize>> {
+    if lp.header == 0 || !function.blocks[lp.header].params.is_empty() {
+        return None;
+    }
+    let mut entries = Vec::new();
+    for edge in analysis.cfg.predecessors(lp.header) {
+        if lp.blocks.binary_search(&edge.from).is_ok() {
+            continue;
+        }
+        if edge.kind == EdgeKind::Exception {
+            return None;
+        }
+        if !entries.contains(&edge.from) {
+            entries.push(edge.from);
+        }
+    }
+    Some(entries)
+}
+
+/// Globals loaded somewhere in `lp` and not already guarded by an enclosing
+/// loop, in the order of their first load, or none when the loop stores or
+/// deletes any global.
+fn hoistable_globals(
+    function: &BlockPyFunction<CodegenBlockPyPass>,
+    lp: &Loop,
+) -> Vec<LocatedName> {
+    let mut collector = GlobalAccessCollector::default();
+    for &index in &lp.blocks {
+        collector.visit_block(&function.blocks[index]);
+    }
+    let GlobalAccessCollector {
+        loaded,
+        guarded,
+        writes_global,
+    } = collector;
+    if writes_global {
+        return Vec::new();
+    }
+    loaded
+        .into_iter()
+        .filter(|name| {
+            !guarded
+                .iter()
+                .any(|slot| Some(*slot) == name.location.as_global())
+        })
+        .collect()
+}
+
+#[derive(Default)]
+struct GlobalAccessCollector {
+    loaded: Vec<LocatedName>,
+    guarded: Vec<GlobalSlot>,
+    writes_global: bool,
+}
+
+impl Visit<CodegenBlockPyExpr> for GlobalAccessCollector {
+    fn visit_instr(&mut self, expr: &CodegenBlockPyExpr) {
+        match expr {
+            CodegenBlockPyExpr::Load(op) if op.name.location.is_global() => {
+                if !self
+                    .loaded
+                    .iter()
+                    .any(|name| name.location == op.name.location)
+                {
+                    self.loaded.push(op.name.clone());
+                }
+            }
+            CodegenBlockPyExpr::GuardedGlobalLoad(op) => {
+                self.guarded.extend(op.name.location.as_global());
+            }
+            CodegenBlockPyExpr::Store(op) if op.name.location.is_global() => {
+                self.writes_global = true;
+            }
+            CodegenBlockPyExpr::Del(op) if op.name.location.is_global() => {
+                self.writes_global = true;
+            }
+            _ => {}
+        }
+        walk_expr(self, expr);
+    }
+}
+
+/// Adds a block that preloads `globals` into fresh locals and jumps to the
+/// header of `lp`, sends the loop's `entries` through it, guards the loop's
+/// loads of those globals on the preloaded locals, and deletes the locals
+/// again where the loop exits.
+fn insert_preheader(
+    function: &mut BlockPyFunction<CodegenBlockPyPass>,
+    analysis: &CfgAnalysis,
+    lp: &Loop,
+    entries: &[usize],
+    globals: Vec<LocatedName>,
+) {
+    let layout = function
+        .storage_layout
+        .get_or_insert_with(StorageLayout::default);
+    let hoisted = globals
+        .into_iter()
+        .map(|name| {
+            let local = LocatedName {
+                id: function.name_gen.next_tmp_name("hoisted").as_str().into(),
+                location: NameLocation::local(
+                    u32::try_from(layout.stack_slots.len())
+                        .expect("storage slot count should fit in u32"),
+                ),
+            };
+            layout.stack_slots.push(local.id.to_string());
+            (name, local)
+        })
+        .collect::<Vec<_>>();
+
+    let header_label = analysis.cfg.label(lp.header);
+    let preheader_label = function.name_gen.next_block_name();
+    for &entry in entries {
+        function.blocks[entry]
+            .term
+            .replace_target(header_label, preheader_label);
+    }
+
+    let mut guarder = LoadGuarder {
+        hoisted: &hoisted,
+        guarded: Vec::new(),
+    };
+    for &index in &lp.blocks {
+        guarder.visit_block_mut(&mut function.blocks[index]);
+    }
+
+    let meta = Meta::synthetic();
+    let mut exits = Vec::new();
+    for &index in &lp.blocks {
+        for edge in analysis.cfg.successors(index) {
+            if lp.blocks.binary_search(&edge.to).is_err() && !exits.contains(&edge.to) {
+                exits.push(edge.to);
+            }
+        }
+    }
+    for exit in exits {
+        let releases = hoisted.iter().map(|(_, local)| {
+            CodegenBlockPyExpr::from(Del::new(local.clone(), true).with_meta(meta.clone()))
+        });
+        function.blocks[exit].body.splice(0..0, releases);
+    }
+
+    let body = hoisted
+        .into_iter()
+        .map(|(name, local)| {
+            PreloadGlobal::new(name, local)
+                .with_meta(meta.clone())
+                .into()
+        })
+        .collect();
+    function.blocks.push(Block::new(
+        preheader_label,
+        body,
+        BlockTerm::Jump(BlockEdge::new(header_label)),
+        Vec::new(),
+        None,
+    ));
+}
+
+/// Rewrites loads of hoisted globals into loads guarded on their locals, at
+/// most one per global in each statement or terminator.
+struct LoadGuarder<'a> {
+    hoisted: &'a [(LocatedName, LocatedName)],
+    /// Hoisted locals already guarded in the current statement.
+    guarded: Vec<usize>,
+}
+
+impl VisitMut<CodegenBlockPyExpr> for LoadGuarder<'_> {
+    fn visit_stmt_mut(&mut self, stmt: &mut CodegenBlockPyExpr) {
+        self.guarded.clear();
+        self.visit_instr_mut(stmt);
+    }
+
+    fn visit_term_mut(&mut self, term: &mut BlockTerm<CodegenBlockPyExpr>) {
+        self.guarded.clear();
+        walk_term_mut(self, term);
+    }
+
+    fn visit_instr_mut(&mut self, expr: &mut CodegenBlockPyExpr) {
+        if let CodegenBlockPyExpr::Load(op) = expr {
+            let index = self
+                .hoisted
+                .iter()
+                .position(|(name, _)| name.location == op.name.location);
+            if let Some(index) = index {
+                if !self.guarded.contains(&index) {
+                    self.guarded.push(index);
+                    let local = self.hoisted[index].1.clone();
+                    *expr = GuardedGlobalLoad::new(op.name.clone(), local)
+                        .with_meta(op.meta())
+                        .into();
+                }
+                return;
+            }
+        }
+        walk_expr_mut(self, expr);
+    }
+}
+
+#[cfg(test)]
+mod test;
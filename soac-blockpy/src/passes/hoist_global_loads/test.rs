@@ -0,0 +1,197 @@
+use super::hoist_loop_invariant_global_loads_in_bb_module;
+use crate::block_py::{
+    validate_module, walk_expr, BlockPyFunction, BlockPyModule, CodegenBlockPyExpr, FunctionKind,
+    Visit,
+};
+use crate::passes::{assign_module_instr_ids, relabel_dense_bb_module, CodegenBlockPyPass};
+use crate::{lower_python_to_blockpy_for_testing_with_options, LoweringOptions};
+
+fn hoisted(source: &str) -> BlockPyModule<CodegenBlockPyPass> {
+    let options = LoweringOptions {
+        hoist_global_loads: false,
+        ..LoweringOptions::default()
+    };
+    let mut module = lower_python_to_blockpy_for_testing_with_options(source, &options)
+        .expect("transform should succeed")
+        .codegen_module;
+    hoist_loop_invariant_global_loads_in_bb_module(&mut module);
+    relabel_dense_bb_module(&mut module);
+    assign_module_instr_ids(&mut module);
+    validate_module(&module).expect("hoisted module should validate");
+    module
+}
+
+fn function<'a>(
+    module: &'a BlockPyModule<CodegenBlockPyPass>,
+    qualname: &str,
+) -> &'a BlockPyFunction<CodegenBlockPyPass> {
+    module
+        .callable_defs
+        .iter()
+        .find(|function| function.names.qualname == qualname)
+        .expect("function should be lowered")
+}
+
+/// The global names each kind of global access in a function refers to.
+#[derive(Default)]
+struct GlobalAccesses {
+    preloaded: Vec<String>,
+    guarded: Vec<String>,
+    loaded: Vec<String>,
+    /// Hoisted locals deleted where a loop exits.
+    released: Vec<String>,
+}
+
+impl Visit<CodegenBlockPyExpr> for GlobalAccesses {
+    fn visit_instr(&mut self, expr: &CodegenBlockPyExpr) {
+        match expr {
+            CodegenBlockPyExpr::PreloadGlobal(op) => {
+                assert!(op.hoisted.location.as_local().is_some(), "{op:?}");
+                self.preloaded.push(op.name.id.to_string());
+            }
+            CodegenBlockPyExpr::GuardedGlobalLoad(op) => {
+                assert!(op.hoisted.location.as_local().is_some(), "{op:?}");
+                self.guarded.push(op.name.id.to_string());
+            }
+            CodegenBlockPyExpr::Load(op) if op.name.location.is_global() => {
+                self.loaded.push(op.name.id.to_string());
+            }
+            CodegenBlockPyExpr::Del(op) if op.name.id.as_str().starts_with("_dp_hoisted_") => {
+                self.released.push(op.name.id.to_string());
+            }
+            _ => {}
+        }
+        walk_expr(self, expr);
+    }
+}
+
+fn global_accesses(function: &BlockPyFunction<CodegenBlockPyPass>) -> GlobalAccesses {
+    let mut accesses = GlobalAccesses::default();
+    for block in &function.blocks {
+        accesses.visit_block(block);
+    }
+    accesses
+}
+
+#[test]
+fn hoists_globals_loaded_in_loop() {
+    let module = hoisted(
+        "\
+SCALE = 2
+
+def f(xs):
+    total = 0
+    for x in xs:
+        total = total + len(x) * SCALE
+    return total
+",
+    );
+    let f = function(&module, "f");
+    let accesses = global_accesses(f);
+    assert_eq!(accesses.preloaded, vec!["len", "SCALE"]);
+    assert_eq!(accesses.guarded, vec!["len", "SCALE"]);
+    assert!(accesses.loaded.is_empty(), "{:?}", accesses.loaded);
+
+    let slots = f.storage_layout.as_ref().unwrap().stack_slots();
+    let hoisted_slots = slots
+        .iter()
+        .filter(|slot| slot.starts_with("_dp_hoisted_"))
+        .count();
+    assert_eq!(hoisted_slots, 2, "{slots:?}");
+}
+
+#[test]
+fn leaves_loop_storing_a_global_alone() {
+    let module = hoisted(
+        "\
+count = 0
+
+def f(xs):
+    global count
+    for x in xs:
+        count = count + len(x)
+",
+    );
+    let accesses = global_accesses(function(&module, "f"));
+    assert!(accesses.preloaded.is_empty(), "{:?}", accesses.preloaded);
+    assert!(accesses.guarded.is_empty(), "{:?}", accesses.guarded);
+    assert_eq!(accesses.loaded, vec!["count", "len"]);
+}
+
+#[test]
+fn guards_one_load_per_global_in_each_statement() {
+    let module = hoisted(
+        "\
+def f(xs):
+    total = 0
+    for x in xs:
+        total = total + STEP * STEP
+    return total
+",
+    );
+    let accesses = global_accesses(function(&module, "f"));
+    assert_eq!(accesses.preloaded, vec!["STEP"]);
+    assert_eq!(accesses.guarded, vec!["STEP"]);
+    assert_eq!(accesses.loaded, vec!["STEP"]);
+}
+
+#[test]
+fn releases_hoisted_locals_where_the_loop_exits() {
+    let module = hoisted(
+        "\
+def f(xs):
+    for x in xs:
+        if x:
+            break
+        print(x)
+    return len(xs)
+",
+    );
+    let f = function(&module, "f");
+    let accesses = global_accesses(f);
+    assert_eq!(accesses.preloaded, vec!["print"]);
+    assert_eq!(accesses.loaded, vec!["len"]);
+    assert!(!accesses.released.is_empty());
+    let slots = f.storage_layout.as_ref().unwrap().stack_slots();
+    assert!(
+        accesses.released.iter().all(|name| slots.contains(name)),
+        "{:?}",
+        accesses.released
+    );
+}
+
+#[test]
+fn hoists_out_of_outermost_invariant_loop() {
+    let module = hoisted(
+        "\
+def f(rows):
+    for row in rows:
+        for x in row:
+            print(x)
+",
+    );
+    let accesses = global_accesses(function(&module, "f"));
+    assert_eq!(accesses.preloaded, vec!["print"]);
+    assert_eq!(accesses.guarded, vec!["print"]);
+}
+
+#[test]
+fn leaves_loads_outside_loops_alone() {
+    let module = hoisted("def f(x):\n    return len(x)\n");
+    let accesses = global_accesses(function(&module, "f"));
+    assert!(accesses.preloaded.is_empty());
+    assert_eq!(accesses.loaded, vec!["len"]);
+}
+
+#[test]
+fn leaves_generators_alone() {
+    let module = hoisted("def f(xs):\n    for x in xs:\n        yield len(x)\n");
+    for function in &module.callable_defs {
+        if function.kind == FunctionKind::Function {
+            continue;
+        }
+        let accesses = global_accesses(function);
+        assert!(accesses.preloaded.is_empty(), "{}", function.names.qualname);
+        assert!(accesses.guarded.is_empty(), "{}", function.names.qualname);
+    }
+}
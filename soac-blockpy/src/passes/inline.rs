@@ -0,0 +1,560 @@
+//! Inlining of small functions at call sites whose target is known.
+//!
+//! A call site is direct when it is a `CallDirect`, or a plain call through a
+//! module global whose only binding in the module is one `make_function`.
+//! Python code can still rebind the global behind the module's back, so each
+//! inlined body sits behind an `InlineGuard` branch that falls back to the
+//! original call unless the callable is exactly a function object for the
+//! expected id, with the caller's globals and no closure. Inlined call sites
+//! pass every parameter, so the callee's defaults are never read, and a bound
+//! method of the same function takes the fallback.
+//!
+//! The callee's blocks are copied into the caller under fresh labels, and its
+//! stack slots and owned cells are renamed and appended to the caller's
+//! storage layout. A return stores the result, quietly deletes the callee's
+//! locals so they are released when the call would have released them, and
+//! jumps to the rest of the caller's block. Copied blocks take the call
+//! site's exception edge, so an exception escaping the inlined body lands
+//! where one escaping the call would have, with the same traceback: compiled
+//! bodies add no traceback entries of their own.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::block_py::{
+    walk_expr, walk_expr_mut, Block, BlockArg, BlockEdge, BlockLabel, BlockPyFunction,
+    BlockPyLiteral, BlockPyModule, BlockPyNameLike, BlockTerm, CallArgPositional, CellLocation,
+    ClosureSlot, CodegenBlockPyExpr, CoreNumberLiteralValue, Del, FunctionId, FunctionKind,
+    GlobalSlot, HasMeta, InlineGuard, Load, LocatedCoreBlockPyExpr, LocatedName, NameLocation,
+    ParamKind, StorageLayout, Store, TermBranchTable, Visit, VisitMut, WithMeta,
+};
+use crate::passes::CodegenBlockPyPass;
+
+/// Callees with more statements and terminators than this are left alone.
+const MAX_INLINED_INSTRS: usize = 16;
+
+/// Inlines small functions at the direct call sites of every function in
+/// `module`. New blocks get labels from each function's name generator, so
+/// the module needs `relabel_dense_bb_module` afterwards.
+pub fn inline_direct_calls_in_bb_module(module: &mut BlockPyModule<CodegenBlockPyPass>) {
+    let direct_globals = direct_global_function_ids(module);
+    let callees = module
+        .callable_defs
+        .iter()
+        .filter(|function| is_inlinable(function))
+        .map(|function| (function.function_id, function.clone()))
+        .collect::<HashMap<_, _>>();
+    if callees.is_empty() {
+        return;
+    }
+    for function in &mut module.callable_defs {
+        inline_direct_calls_in_function(function, &callees, &direct_globals);
+    }
+}
+
+/// Module globals whose only binding anywhere in the module is one
+/// `make_function`, mapped to the function it makes.
+fn direct_global_function_ids(
+    module: &BlockPyModule<CodegenBlockPyPass>,
+) -> HashMap<GlobalSlot, FunctionId> {
+    let mut collector = GlobalBindingCollector {
+        module_constants: &module.module_constants,
+        bindings: HashMap::new(),
+    };
+    collector.visit_module(module);
+    collector
+        .bindings
+        .into_iter()
+        .filter_map(|(slot, function_id)| Some((slot, function_id?)))
+        .collect()
+}
+
+struct GlobalBindingCollector<'a> {
+    module_constants: &'a [LocatedCoreBlockPyExpr],
+    /// `None` once a global has any binding besides its first `make_function`.
+    bindings: HashMap<GlobalSlot, Option<FunctionId>>,
+}
+
+impl GlobalBindingCollector<'_> {
+    fn bind(&mut self, slot: GlobalSlot, function_id: Option<FunctionId>) {
+        self.bindings
+            .entry(slot)
+            .and_modify(|bound| *bound = None)
+            .or_insert(function_id);
+    }
+}
+
+impl Visit<CodegenBlockPyExpr> for GlobalBindingCollector<'_> {
+    fn visit_instr(&mut self, expr: &CodegenBlockPyExpr) {
+        match expr {
+            CodegenBlockPyExpr::Store(op) => {
+                if let Some(slot) = op.name.location.as_global() {
+                    let function_id = made_function_id(&op.value, self.module_constants);
+                    self.bind(slot, function_id);
+                }
+            }
+            CodegenBlockPyExpr::Del(op) => {
+                if let Some(slot) = op.name.location.as_global() {
+                    self.bind(slot, None);
+                }
+            }
+            _ => {}
+        }
+        walk_expr(self, expr);
+    }
+}
+
+/// The function a `make_function` runtime call creates, read back from the
+/// packed id constant name binding passes as its first argument.
+fn made_function_id(
+    expr: &CodegenBlockPyExpr,
+    module_constants: &[LocatedCoreBlockPyExpr],
+) -> Option<FunctionId> {
+    let call = match expr {
+        CodegenBlockPyExpr::MakeFunction(op) => return Some(op.function_id),
+        CodegenBlockPyExpr::Call(call) => call,
+        _ => return None,
+    };
+    let CodegenBlockPyExpr::Load(func) = call.func.as_ref() else {
+        return None;
+    };
+    if !func.name.is_runtime_symbol("make_function") {
+        return None;
+    }
+    let Some(CallArgPositional::Positional(CodegenBlockPyExpr::Load(id))) = call.args.first()
+    else {
+        return None;
+    };
+    let constant = module_constants.get(id.name.location.as_constant()? as usize)?;
+    let LocatedCoreBlockPyExpr::Literal(literal) = constant else {
+        return None;
+    };
+    let BlockPyLiteral::NumberLiteral(number) = literal.as_literal() else {
+        return None;
+    };
+    let CoreNumberLiteralValue::Int(value) = &number.value else {
+        return None;
+    };
+    value.to_string().parse().ok().map(FunctionId::from_packed)
+}
+
+/// Whether `function` can be spliced into a caller: a plain function within
+/// the size budget, taking only positional parameters, with no exception
+/// handling of its own and no cells besides the ones it owns.
+fn is_inlinable(function: &BlockPyFunction<CodegenBlockPyPass>) -> bool {
+    if function.kind != FunctionKind::Function || function.blocks.is_empty() {
+        return false;
+    }
+    if !function
+        .params
+        .iter()
+        .all(|param| matches!(param.kind, ParamKind::Any | ParamKind::PosOnly))
+    {
+        return false;
+    }
+    let empty = StorageLayout::default();
+    let layout = function.storage_layout.as_ref().unwrap_or(&empty);
+    if !layout.freevars.is_empty() {
+        return false;
+    }
+    // Parameters are bound by storing the arguments into their stack slots.
+    if function
+        .params
+        .names()
+        .iter()
+        .any(|name| !layout.stack_slots().contains(name) || layout.has_storage_name(name.as_str()))
+    {
+        return false;
+    }
+    let size = function
+        .blocks
+        .iter()
+        .map(|block| block.body.len() + 1)
+        .sum::<usize>();
+    if size > MAX_INLINED_INSTRS {
+        return false;
+    }
+    if function
+        .blocks
+        .iter()
+        .any(|block| !block.params.is_empty() || block.exc_edge.is_some())
+    {
+        return false;
+    }
+    let mut finder = UnownedCellFinder::default();
+    finder.visit_fn(function);
+    !finder.found
+}
+
+#[derive(Default)]
+struct UnownedCellFinder {
+    found: bool,
+}
+
+impl Visit<CodegenBlockPyExpr> for UnownedCellFinder {
+    fn visit_instr(&mut self, expr: &CodegenBlockPyExpr) {
+        let location = match expr {
+            CodegenBlockPyExpr::Load(op) => op.name.cell_location(),
+            CodegenBlockPyExpr::Store(op) => op.name.cell_location(),
+            CodegenBlockPyExpr::Del(op) => op.name.cell_location(),
+            CodegenBlockPyExpr::CellRef(op) => Some(op.location),
+            _ => None,
+        };
+        self.found |= location.is_some_and(|location| !location.is_owned());
+        walk_expr(self, expr);
+    }
+}
+
+fn inline_direct_calls_in_function(
+    function: &mut BlockPyFunction<CodegenBlockPyPass>,
+    callees: &HashMap<FunctionId, BlockPyFunction<CodegenBlockPyPass>>,
+    direct_globals: &HashMap<GlobalSlot, FunctionId>,
+) {
+    // Blocks added for a call site are not revisited, so a recursive callee
+    // is expanded one level deep and the fallback call is left alone.
+    let mut inlined_labels = HashSet::new();
+    let mut index = 0;
+    while index < function.blocks.len() {
+        let block = &function.blocks[index];
+        if !inlined_labels.contains(&block.label) && block.params.is_empty() {
+            if let Some(site) = find_call_site(block, callees, direct_globals) {
+                let callee = &callees[&site.function_id];
+                inlined_labels.extend(inline_call_site(function, index, site, callee));
+            }
+        }
+        index += 1;
+    }
+}
+
+struct CallSite {
+    /// The statement making the call, or `None` when the block returns it.
+    stmt_index: Option<usize>,
+    function_id: FunctionId,
+    target: Option<LocatedName>,
+    callable: CodegenBlockPyExpr,
+    args: Vec<CodegenBlockPyExpr>,
+}
+
+/// The first call in `block` that can be inlined: a bare call or the value
+/// of a store, or the returned value.
+fn find_call_site(
+    block: &Block<CodegenBlockPyExpr, CodegenBlockPyExpr>,
+    callees: &HashMap<FunctionId, BlockPyFunction<CodegenBlockPyPass>>,
+    direct_globals: &HashMap<GlobalSlot, FunctionId>,
+) -> Option<CallSite> {
+    let in_body = block
+        .body
+        .iter()
+        .enumerate()
+        .find_map(|(stmt_index, stmt)| {
+            let (target, call) = match stmt {
+                CodegenBlockPyExpr::Store(op) => (Some(op.name.clone()), op.value.as_ref()),
+                call => (None, call),
+            };
+            let (function_id, callable, args) = direct_call(call, callees, direct_globals)?;
+            Some(CallSite {
+                stmt_index: Some(stmt_index),
+                function_id,
+                target,
+                callable,
+                args,
+            })
+        });
+    if in_body.is_some() {
+        return in_body;
+    }
+    let BlockTerm::Return(value) = &block.term else {
+        return None;
+    };
+    let (function_id, callable, args) = direct_call(value, callees, direct_globals)?;
+    Some(CallSite {
+        stmt_index: None,
+        function_id,
+        target: None,
+        callable,
+        args,
+    })
+}
+
+/// Splits `expr` into its target, callable and arguments if it is a direct
+/// call the matching callee can take inline. The callable must be a plain
+/// load, since the guard and the fallback call both evaluate it.
+fn direct_call(
+    expr: &CodegenBlockPyExpr,
+    callees: &HashMap<FunctionId, BlockPyFunction<CodegenBlockPyPass>>,
+    direct_globals: &HashMap<GlobalSlot, FunctionId>,
+) -> Option<(FunctionId, CodegenBlockPyExpr, Vec<CodegenBlockPyExpr>)> {
+    let (function_id, callable, args, has_keywords) = match expr {
+        CodegenBlockPyExpr::CallDirect(call) => (
+            call.function_id,
+            call.callable.as_ref(),
+            &call.args,
+            !call.keywords.is_empty(),
+        ),
+        CodegenBlockPyExpr::Call(call) => {
+            let CodegenBlockPyExpr::Load(func) = call.func.as_ref() else {
+                return None;
+            };
+            let function_id = *direct_globals.get(&func.name.location.as_global()?)?;
+            (
+                function_id,
+                call.func.as_ref(),
+                &call.args,
+                !call.keywords.is_empty(),
+            )
+        }
+        _ => return None,
+    };
+    if has_keywords || !matches!(callable, CodegenBlockPyExpr::Load(_)) {
+        return None;
+    }
+    let callee = callees.get(&function_id)?;
+    let args = args
+        .iter()
+        .map(|arg| match arg {
+            CallArgPositional::Positional(expr) => Some(expr.clone()),
+            CallArgPositional::Starred(_) => None,
+        })
+        .collect::<Option<Vec<_>>>()?;
+    if args.len() != callee.params.len() {
+        return None;
+    }
+    Some((function_id, callable.clone(), args))
+}
+
+/// Splits the block at `index` around `site` and splices a renamed copy of
+/// `callee` in behind an inline guard. Returns the labels of the added
+/// blocks, apart from the one holding the rest of the split block.
+fn inline_call_site(
+    function: &mut BlockPyFunction<CodegenBlockPyPass>,
+    index: usize,
+    mut site: CallSite,
+    callee: &BlockPyFunction<CodegenBlockPyPass>,
+) -> Vec<BlockLabel> {
+    let prefix = function
+        .name_gen
+        .next_tmp_name("inline")
+        .as_str()
+        .to_owned();
+    let labels = callee
+        .blocks
+        .iter()
+        .map(|block| (block.label, function.name_gen.next_block_name()))
+        .collect::<HashMap<_, _>>();
+    let fallback_label = function.name_gen.next_block_name();
+    let prologue_label = function.name_gen.next_block_name();
+    let continuation_label = function.name_gen.next_block_name();
+
+    let empty = StorageLayout::default();
+    let callee_layout = callee.storage_layout.as_ref().unwrap_or(&empty);
+    let layout = function
+        .storage_layout
+        .get_or_insert_with(StorageLayout::default);
+    let local_base = slot_index(layout.stack_slots.len());
+    let cell_base = slot_index(layout.cellvars.len() + layout.runtime_cells.len());
+    let callee_locals = callee_layout
+        .stack_slots()
+        .iter()
+        .enumerate()
+        .map(|(slot, name)| LocatedName {
+            id: inline_name(&prefix, name).into(),
+            location: NameLocation::local(local_base + slot_index(slot)),
+        })
+        .collect::<Vec<_>>();
+    layout
+        .stack_slots
+        .extend(callee_locals.iter().map(|name| name.id.to_string()));
+    layout.runtime_cells.extend(
+        callee_layout
+            .cellvars
+            .iter()
+            .chain(&callee_layout.runtime_cells)
+            .map(|slot| ClosureSlot {
+                logical_name: inline_name(&prefix, &slot.logical_name),
+                storage_name: inline_name(&prefix, &slot.storage_name),
+                ..slot.clone()
+            }),
+    );
+    if site.stmt_index.is_none() {
+        // A returned call is stored into a fresh local the rest of the block
+        // returns, so both paths rejoin like a store.
+        let result = LocatedName {
+            id: inline_name(&prefix, "result").into(),
+            location: NameLocation::local(slot_index(layout.stack_slots.len())),
+        };
+        layout.stack_slots.push(result.id.to_string());
+        let block = &mut function.blocks[index];
+        let BlockTerm::Return(value) = &mut block.term else {
+            unreachable!("returned call site should end in a return");
+        };
+        let meta = value.meta();
+        let value = std::mem::replace(
+            value,
+            Load::new(result.clone()).with_meta(meta.clone()).into(),
+        );
+        block
+            .body
+            .push(Store::new(result.clone(), value).with_meta(meta).into());
+        site.stmt_index = Some(block.body.len() - 1);
+        site.target = Some(result);
+    }
+
+    let block = &mut function.blocks[index];
+    let stmt_index = site.stmt_index.expect("call site should be a statement");
+    let rest = block.body.split_off(stmt_index + 1);
+    let call_stmt = block.body.pop().expect("call site statement should exist");
+    let meta = call_stmt.meta();
+    let exc_edge = block.exc_edge.clone();
+    let term = std::mem::replace(
+        &mut block.term,
+        BlockTerm::BranchTable(TermBranchTable {
+            index: CodegenBlockPyExpr::InlineGuard(
+                InlineGuard::new(site.callable, site.function_id).with_meta(meta.clone()),
+            ),
+            targets: vec![prologue_label],
+            default_label: fallback_label,
+        }),
+    );
+
+    let prologue = callee
+        .params
+        .names()
+        .iter()
+        .zip(site.args)
+        .map(|(param, arg)| {
+            let slot = callee_layout
+                .stack_slots()
+                .iter()
+                .position(|name| name == param)
+                .expect("inlinable callee params should have stack slots");
+            Store::new(callee_locals[slot].clone(), arg)
+                .with_meta(meta.clone())
+                .into()
+        })
+        .collect();
+    let mut added = vec![
+        Block::new(
+            fallback_label,
+            vec![call_stmt],
+            BlockTerm::Jump(BlockEdge::new(continuation_label)),
+            Vec::new(),
+            exc_edge.clone(),
+        ),
+        Block::new(
+            prologue_label,
+            prologue,
+            BlockTerm::Jump(BlockEdge::new(labels[&callee.blocks[0].label])),
+            Vec::new(),
+            exc_edge.clone(),
+        ),
+    ];
+
+    let mut remapper = CalleeRemapper {
+        labels: &labels,
+        prefix: &prefix,
+        local_base,
+        cell_base,
+    };
+    for callee_block in &callee.blocks {
+        let mut block = callee_block.clone();
+        remapper.visit_block_mut(&mut block);
+        block.label = labels[&callee_block.label];
+        block.exc_edge = exc_edge.clone();
+        let term = std::mem::replace(
+            &mut block.term,
+            BlockTerm::Jump(BlockEdge::new(continuation_label)),
+        );
+        match term {
+            BlockTerm::Return(value) => {
+                match &site.target {
+                    Some(target) => block.body.push(
+                        Store::new(target.clone(), value)
+                            .with_meta(meta.clone())
+                            .into(),
+                    ),
+                    None => block.body.push(value),
+                }
+                block.body.extend(
+                    callee_locals
+                        .iter()
+                        .map(|name| Del::new(name.clone(), true).with_meta(meta.clone()).into()),
+                );
+            }
+            term => block.term = term,
+        }
+        added.push(block);
+    }
+
+    let added_labels = added.iter().map(|block| block.label).collect();
+    added.push(Block::new(
+        continuation_label,
+        rest,
+        term,
+        Vec::new(),
+        exc_edge,
+    ));
+    function.blocks.extend(added);
+    added_labels
+}
+
+fn inline_name(prefix: &str, name: &str) -> String {
+    format!("{prefix}_{name}")
+}
+
+fn slot_index(index: usize) -> u32 {
+    u32::try_from(index).expect("storage slot count should fit in u32")
+}
+
+/// Moves a callee's locals and owned cells into the slots appended to the
+/// caller's storage layout, and its block labels onto fresh caller labels.
+struct CalleeRemapper<'a> {
+    labels: &'a HashMap<BlockLabel, BlockLabel>,
+    prefix: &'a str,
+    local_base: u32,
+    cell_base: u32,
+}
+
+impl CalleeRemapper<'_> {
+    fn remap_name(&self, name: &mut LocatedName) {
+        let location = match name.location {
+            NameLocation::Local(location) => NameLocation::local(self.local_base + location.slot()),
+            NameLocation::Cell(CellLocation::Owned(slot)) => {
+                NameLocation::owned_cell(self.cell_base + slot)
+            }
+            _ => return,
+        };
+        name.id = inline_name(self.prefix, name.id.as_str()).into();
+        name.location = location;
+    }
+}
+
+impl VisitMut<CodegenBlockPyExpr> for CalleeRemapper<'_> {
+    fn visit_instr_mut(&mut self, expr: &mut CodegenBlockPyExpr) {
+        match expr {
+            CodegenBlockPyExpr::Load(op) => self.remap_name(&mut op.name),
+            CodegenBlockPyExpr::Store(op) => self.remap_name(&mut op.name),
+            CodegenBlockPyExpr::Del(op) => self.remap_name(&mut op.name),
+            CodegenBlockPyExpr::CellRef(op) => {
+                if let CellLocation::Owned(slot) = op.location {
+                    op.location = CellLocation::Owned(self.cell_base + slot);
+                }
+            }
+            _ => {}
+        }
+        walk_expr_mut(self, expr);
+    }
+
+    fn visit_label_mut(&mut self, label: &mut BlockLabel) {
+        if let Some(mapped) = self.labels.get(label) {
+            *label = *mapped;
+        }
+    }
+
+    fn visit_block_arg_mut(&mut self, arg: &mut BlockArg) {
+        if let BlockArg::Name(name) = arg {
+            *name = inline_name(self.prefix, name);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test;
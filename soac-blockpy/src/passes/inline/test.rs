@@ -0,0 +1,172 @@
+use super::inline_direct_calls_in_bb_module;
+use crate::block_py::{
+    validate_module, BlockPyFunction, BlockPyModule, BlockTerm, CodegenBlockPyExpr, FunctionId,
+};
+use crate::lower_python_to_blockpy_for_testing;
+use crate::passes::{assign_module_instr_ids, relabel_dense_bb_module, CodegenBlockPyPass};
+
+fn inlined(source: &str) -> BlockPyModule<CodegenBlockPyPass> {
+    let mut module = lower_python_to_blockpy_for_testing(source)
+        .expect("transform should succeed")
+        .codegen_module;
+    inline_direct_calls_in_bb_module(&mut module);
+    relabel_dense_bb_module(&mut module);
+    assign_module_instr_ids(&mut module);
+    validate_module(&module).expect("inlined module should validate");
+    module
+}
+
+fn function<'a>(
+    module: &'a BlockPyModule<CodegenBlockPyPass>,
+    qualname: &str,
+) -> &'a BlockPyFunction<CodegenBlockPyPass> {
+    module
+        .callable_defs
+        .iter()
+        .find(|function| function.names.qualname == qualname)
+        .expect("function should be lowered")
+}
+
+/// The expected callee of every inline guard in `function`.
+fn guards(function: &BlockPyFunction<CodegenBlockPyPass>) -> Vec<FunctionId> {
+    function
+        .blocks
+        .iter()
+        .filter_map(|block| match &block.term {
+            BlockTerm::BranchTable(branch) => match &branch.index {
+                CodegenBlockPyExpr::InlineGuard(guard) => Some(guard.function_id),
+                _ => None,
+            },
+            _ => None,
+        })
+        .collect()
+}
+
+const ADD: &str = "\
+def add(a, b):
+    return a + b
+";
+
+#[test]
+fn inlines_call_through_single_binding_global() {
+    let source = format!("{ADD}\ndef f(x):\n    y = add(x, 1)\n    return y\n");
+    let module = inlined(&source);
+    let add = function(&module, "add");
+    let f = function(&module, "f");
+    assert_eq!(guards(f), vec![add.function_id]);
+
+    let slots = f.storage_layout.as_ref().unwrap().stack_slots();
+    for param in ["_a", "_b"] {
+        let renamed = slots
+            .iter()
+            .any(|slot| slot.starts_with("_dp_inline_") && slot.ends_with(param));
+        assert!(renamed, "{slots:?}");
+    }
+    let mut unique = slots.to_vec();
+    unique.sort();
+    unique.dedup();
+    assert_eq!(unique.len(), slots.len(), "{slots:?}");
+}
+
+#[test]
+fn inlines_returned_call() {
+    let source = format!("{ADD}\ndef f(x):\n    return add(x, 1)\n");
+    let module = inlined(&source);
+    let add = function(&module, "add");
+    assert_eq!(guards(function(&module, "f")), vec![add.function_id]);
+}
+
+#[test]
+fn leaves_rebound_global_alone() {
+    let source = format!("{ADD}\nadd = len\n\ndef f(x):\n    return add(x, 1)\n");
+    let module = inlined(&source);
+    assert!(guards(function(&module, "f")).is_empty());
+}
+
+#[test]
+fn leaves_unsupported_callees_and_call_sites_alone() {
+    let sources = [
+        "def add(a, b):\n    yield a\n\ndef f(x):\n    return add(x, 1)\n".to_string(),
+        "def add(*a):\n    return a\n\ndef f(x):\n    return add(x, 1)\n".to_string(),
+        format!("{ADD}\ndef f(x):\n    return add(a=x, b=1)\n"),
+        format!("{ADD}\ndef f(x):\n    return add(x)\n"),
+        "\
+def add(a, b):
+    try:
+        return a + b
+    except Exception:
+        return 0
+
+def f(x):
+    return add(x, 1)
+"
+        .to_string(),
+    ];
+    for source in sources {
+        let module = inlined(&source);
+        assert!(guards(function(&module, "f")).is_empty(), "{source}");
+    }
+}
+
+#[test]
+fn inlined_blocks_take_the_call_sites_exception_edge() {
+    let source = format!(
+        "{ADD}
+def f(x):
+    try:
+        y = add(x, 1)
+    except Exception:
+        y = 0
+    return y
+"
+    );
+    let module = inlined(&source);
+    let f = function(&module, "f");
+    let block = |label| {
+        f.blocks
+            .iter()
+            .find(|block| block.label == label)
+            .expect("branch target should exist")
+    };
+    let (guard, branch) = f
+        .blocks
+        .iter()
+        .find_map(|block| match &block.term {
+            BlockTerm::BranchTable(branch)
+                if matches!(branch.index, CodegenBlockPyExpr::InlineGuard(_)) =>
+            {
+                Some((block, branch))
+            }
+            _ => None,
+        })
+        .expect("call site should be guarded");
+    let handler = guard
+        .exc_edge
+        .as_ref()
+        .expect("call site should be inside the try")
+        .target;
+    let prologue = block(branch.targets[0]);
+    let BlockTerm::Jump(entry) = &prologue.term else {
+        panic!("prologue should jump to the inlined entry");
+    };
+    for inlined in [prologue, block(entry.target), block(branch.default_label)] {
+        assert_eq!(
+            inlined.exc_edge.as_ref().map(|edge| edge.target),
+            Some(handler)
+        );
+    }
+}
+
+#[test]
+fn expands_recursive_call_one_level() {
+    let source = "\
+def fact(n):
+    if n:
+        rest = fact(n - 1)
+        return n * rest
+    return 1
+";
+    let module = inlined(source);
+    let fact = function(&module, "fact");
+    assert_eq!(guards(fact), vec![fact.function_id]);
+}
@@ -328,7 +328,11 @@ mod tests {
                 Vec::new(),
                 BlockTerm::BranchTable(TermBranchTable {
                     index: CodegenBlockPyExpr::CalleeFunctionId(
-                        CalleeFunctionId::new((*call.func).clone()).with_meta(meta.clone()),
+                        CalleeFunctionId::new(
+                            (*call.func).clone(),
+                            vec![hot_targets.most_frequent, hot_targets.second_most_frequent],
+                        )
+                        .with_meta(meta.clone()),
                     ),
                     targets: vec![self.hot0_label, self.hot1_label],
                     default_label: self.generic_label,
@@ -559,10 +563,13 @@ mod tests {
         let BlockTerm::BranchTable(branch) = &fragment.entry().term else {
             panic!("entry fragment should dispatch with br_table");
         };
-        assert!(matches!(
-            branch.index,
-            CodegenBlockPyExpr::CalleeFunctionId(_)
-        ));
+        let CodegenBlockPyExpr::CalleeFunctionId(callee) = &branch.index else {
+            panic!("entry fragment should dispatch on the callee's function id");
+        };
+        assert_eq!(
+            callee.candidates,
+            vec![FunctionId::new(9, 11), FunctionId::new(9, 12)]
+        );
         assert_eq!(branch.targets, vec![BlockLabel::from_index(11), BlockLabel::from_index(12)]);
         assert_eq!(branch.default_label, BlockLabel::from_index(13));
         assert_eq!(fragment.dependencies().len(), 3);
@@ -4,33 +4,52 @@ pub(crate) mod blockpy_expr_simplify;
 mod blockpy_generators;
 pub mod blockpy_to_bb;
 pub(crate) mod core_await_lower;
-mod instrument;
+mod definite_assignment;
+mod hoist_global_loads;
+mod inline;
 mod instr_id;
+mod instrument;
 mod name_binding;
+pub(crate) mod parallel;
 pub mod ruff_to_blockpy;
+pub(crate) mod syntax_check;
 mod trace;
 
 use crate::block_py::{cfg::relabel_blockpy_blocks_dense, BlockPyModule};
 use crate::block_py::{
-    Await, BinOp, BlockPyNameLike, BlockPyPass, Call, CellRef, CellRefForName, ChildVisitable,
-    CodegenBlockPyExpr, Del, DelItem, GetAttr, GetItem, HasMeta, Instr, LiteralValue, Load,
-    LocatedName, MakeCell, MakeFunction, MapInstr, Mappable, Meta, SetAttr, SetItem, Store,
-    TryMapInstr, UnaryOp, UnresolvedName, WithMeta, Yield, YieldFrom,
+    Await, BinOp, BlockPyNameLike, BlockPyPass, BuildDict, BuildList, BuildSet, BuildString,
+    BuildTuple, Call, CellRef, CellRefForName, ChildVisitable, CodegenBlockPyExpr, CreateClass,
+    Del, DelItem, ForIter, FormatValue, GetAttr, GetItem, GetIter, HasMeta, ImportFrom, ImportName,
+    Instr, LiteralValue, Load, LocatedName, MakeCell, MakeFunction, MapInstr, Mappable, Meta,
+    OperationKind, SetAttr, SetItem, Store, TryMapInstr, UnaryOp, Unpack, UnresolvedName, WithMeta,
+    Yield, YieldFrom,
 };
 use soac_macros::{enum_broadcast, DelegateMatchDefault};
 
 #[derive(Clone, derive_more::From, DelegateMatchDefault)]
-#[enum_broadcast(HasMeta, WithMeta, ChildVisitable, Mappable, Debug)]
+#[enum_broadcast(HasMeta, WithMeta, ChildVisitable, Mappable, Debug, OperationKind)]
 pub enum CoreBlockPyExprWithAwaitAndYield {
     Literal(LiteralValue),
     BinOp(BinOp<Self>),
     UnaryOp(UnaryOp<Self>),
     Call(Call<Self>),
+    BuildTuple(BuildTuple<Self>),
+    BuildList(BuildList<Self>),
+    BuildSet(BuildSet<Self>),
+    BuildDict(BuildDict<Self>),
     GetAttr(GetAttr<Self>),
     SetAttr(SetAttr<Self>),
     GetItem(GetItem<Self>),
     SetItem(SetItem<Self>),
     DelItem(DelItem<Self>),
+    GetIter(GetIter<Self>),
+    ForIter(ForIter<Self>),
+    Unpack(Unpack<Self>),
+    ImportName(ImportName<Self>),
+    ImportFrom(ImportFrom<Self>),
+    CreateClass(CreateClass<Self>),
+    FormatValue(FormatValue<Self>),
+    BuildString(BuildString<Self>),
     Load(Load<Self>),
     Store(Store<Self>),
     Del(Del<Self>),
@@ -44,17 +63,29 @@ pub enum CoreBlockPyExprWithAwaitAndYield {
 }
 
 #[derive(Clone, derive_more::From, DelegateMatchDefault)]
-#[enum_broadcast(HasMeta, WithMeta, ChildVisitable, Mappable, Debug)]
+#[enum_broadcast(HasMeta, WithMeta, ChildVisitable, Mappable, Debug, OperationKind)]
 pub enum CoreBlockPyExprWithYield {
     Literal(LiteralValue),
     BinOp(BinOp<Self>),
     UnaryOp(UnaryOp<Self>),
     Call(Call<Self>),
+    BuildTuple(BuildTuple<Self>),
+    BuildList(BuildList<Self>),
+    BuildSet(BuildSet<Self>),
+    BuildDict(BuildDict<Self>),
     GetAttr(GetAttr<Self>),
     SetAttr(SetAttr<Self>),
     GetItem(GetItem<Self>),
     SetItem(SetItem<Self>),
     DelItem(DelItem<Self>),
+    GetIter(GetIter<Self>),
+    ForIter(ForIter<Self>),
+    Unpack(Unpack<Self>),
+    ImportName(ImportName<Self>),
+    ImportFrom(ImportFrom<Self>),
+    CreateClass(CreateClass<Self>),
+    FormatValue(FormatValue<Self>),
+    BuildString(BuildString<Self>),
     Load(Load<Self>),
     Store(Store<Self>),
     Del(Del<Self>),
@@ -67,17 +98,29 @@ pub enum CoreBlockPyExprWithYield {
 }
 
 #[derive(Clone, derive_more::From, DelegateMatchDefault)]
-#[enum_broadcast(HasMeta, WithMeta, ChildVisitable, Mappable, Debug)]
+#[enum_broadcast(HasMeta, WithMeta, ChildVisitable, Mappable, Debug, OperationKind)]
 pub enum CoreBlockPyExpr<N: BlockPyNameLike = UnresolvedName> {
     Literal(LiteralValue),
     BinOp(BinOp<Self>),
     UnaryOp(UnaryOp<Self>),
     Call(Call<Self>),
+    BuildTuple(BuildTuple<Self>),
+    BuildList(BuildList<Self>),
+    BuildSet(BuildSet<Self>),
+    BuildDict(BuildDict<Self>),
     GetAttr(GetAttr<Self>),
     SetAttr(SetAttr<Self>),
     GetItem(GetItem<Self>),
     SetItem(SetItem<Self>),
     DelItem(DelItem<Self>),
+    GetIter(GetIter<Self>),
+    ForIter(ForIter<Self>),
+    Unpack(Unpack<Self>),
+    ImportName(ImportName<Self>),
+    ImportFrom(ImportFrom<Self>),
+    CreateClass(CreateClass<Self>),
+    FormatValue(FormatValue<Self>),
+    BuildString(BuildString<Self>),
     Load(Load<Self>),
     Store(Store<Self>),
     Del(Del<Self>),
@@ -126,19 +169,25 @@ impl BlockPyPass for CodegenBlockPyPass {
 
 pub(crate) use blockpy_generators::lower_yield_in_lowered_core_blockpy_module_bundle;
 pub use blockpy_to_bb::{lower_try_jump_exception_flow, normalize_bb_module_strings};
+pub(crate) use blockpy_to_bb::{
+    lower_try_jump_exception_flow_with_pool, normalize_bb_module_strings_with_pool,
+};
+pub use definite_assignment::MaybeUnboundLocal;
+pub use hoist_global_loads::hoist_loop_invariant_global_loads_in_bb_module;
+pub use inline::inline_direct_calls_in_bb_module;
 pub use instr_id::{assign_function_instr_ids, assign_module_instr_ids};
 pub use instrument::{
     CounterBuilder, CounterHandle, CounterSpec, InstrumentInstr, OptBlock, OptInstr,
 };
 pub use trace::{
     instrument_bb_module_with_block_entry_counters, instrument_bb_module_with_global_load_counters,
-    instrument_bb_module_with_refcount_counters,
+    instrument_bb_module_with_refcount_counters, parse_trace_config, TraceConfig,
 };
 
-pub(crate) use name_binding::lower_name_binding_in_core_blockpy_module;
-pub(crate) use trace::{
-    global_load_counter_instrumentation_enabled, instrument_bb_module_for_trace, parse_trace_env,
+pub(crate) use name_binding::{
+    lower_name_binding_in_core_blockpy_module, maybe_unbound_locals_in_core_blockpy_module,
 };
+pub(crate) use trace::instrument_bb_module_for_trace;
 
 pub fn relabel_dense_bb_module(module: &mut BlockPyModule<CodegenBlockPyPass>) {
     for callable in &mut module.callable_defs {
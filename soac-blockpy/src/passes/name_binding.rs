@@ -8,8 +8,10 @@ use crate::block_py::{
     CoreNumberLiteral, CoreNumberLiteralValue, CoreStringLiteral, Del, DelItem, EffectiveBinding,
     FunctionId, FunctionKind, HasMeta, Load, LocalLocation, LocatedCoreBlockPyExpr, LocatedName,
     MakeCell, MakeFunction, MapFunction, MapInstr, Mappable, NameLocation, SetItem, StorageLayout,
-    Store, UnresolvedName, WithMeta,
+    Store, UnresolvedName, VisitMut, WithMeta,
 };
+use crate::passes::definite_assignment::{DefiniteAssignment, MaybeUnboundLocal};
+use crate::passes::parallel::CallablePool;
 use crate::passes::ruff_to_blockpy::{
     populate_exception_edge_args, rewrite_current_exception_in_core_blocks,
 };
@@ -287,6 +289,48 @@ fn wrap_deleted_name_load_expr(
     )
 }
 
+/// The local checked by a `load_deleted_name(name, local)` call.
+fn deleted_name_check_target(expr: &CoreBlockPyExpr) -> Option<&str> {
+    let CoreBlockPyExpr::Call(call) = expr else {
+        return None;
+    };
+    if !call.keywords.is_empty()
+        || call.args.len() != 2
+        || !matches!(
+            call.func.as_ref(),
+            CoreBlockPyExpr::Load(op) if op.name.is_runtime_symbol("load_deleted_name")
+        )
+    {
+        return None;
+    }
+    match &call.args[1] {
+        CallArgPositional::Positional(CoreBlockPyExpr::Load(op)) if !op.name.is_runtime_name() => {
+            Some(op.name.id_str())
+        }
+        _ => None,
+    }
+}
+
+/// Unwraps `load_deleted_name` checks on locals that are assigned wherever
+/// the check runs.
+struct RedundantDeletedNameCheckStripper<'a> {
+    assigned: &'a HashSet<String>,
+}
+
+impl VisitMut<CoreBlockPyExpr> for RedundantDeletedNameCheckStripper<'_> {
+    fn visit_instr_mut(&mut self, expr: &mut CoreBlockPyExpr) {
+        if deleted_name_check_target(expr).is_some_and(|name| self.assigned.contains(name)) {
+            if let CoreBlockPyExpr::Call(call) = expr {
+                if let Some(CallArgPositional::Positional(value)) = call.args.pop() {
+                    *expr = value;
+                }
+            }
+            return;
+        }
+        crate::block_py::walk_expr_mut(self, expr);
+    }
+}
+
 fn with_helper_arg_mut<N: BlockPyNameLike + Clone>(
     expr: &mut CoreBlockPyExpr<N>,
     index: usize,
@@ -381,11 +425,17 @@ fn rewrite_deleted_name_loads_in_expr(
     scope: &CallableScopeInfo,
     storage_layout: &StorageLayout,
     resolver: &NameBindingMapper<'_>,
-    deleted_names: &HashSet<String>,
+    checked_names: &HashSet<String>,
     always_unbound_names: &HashSet<String>,
 ) {
+    if let Some(name) = deleted_name_check_target(expr) {
+        // Assignment and `with` targets come out of lowering already checked.
+        if !always_unbound_names.contains(name) {
+            return;
+        }
+    }
     if let Some(logical_name) = cell_load_logical_name(expr, scope, storage_layout) {
-        if deleted_names.contains(logical_name.as_str())
+        if checked_names.contains(logical_name.as_str())
             || always_unbound_names.contains(logical_name.as_str())
         {
             let meta = expr.meta();
@@ -405,8 +455,8 @@ fn rewrite_deleted_name_loads_in_expr(
         CoreBlockPyExpr::Load(op) => {
             let meta = op.meta();
             let always_unbound = always_unbound_names.contains(op.name.id_str());
-            let deleted = deleted_names.contains(op.name.id_str());
-            if always_unbound || deleted {
+            let checked = checked_names.contains(op.name.id_str());
+            if always_unbound || checked {
                 *expr = wrap_deleted_name_load_expr(
                     op.name.id_str().to_string(),
                     meta.node_index.clone(),
@@ -430,8 +480,8 @@ fn rewrite_deleted_name_loads_in_expr(
                         logical_name_for_local_location(storage_layout, location)
                     {
                         let always_unbound = always_unbound_names.contains(logical_name.as_str());
-                        let deleted = deleted_names.contains(logical_name.as_str());
-                        if always_unbound || deleted {
+                        let checked = checked_names.contains(logical_name.as_str());
+                        if always_unbound || checked {
                             *expr = wrap_deleted_name_load_expr(
                                 logical_name,
                                 meta.node_index.clone(),
@@ -450,18 +500,30 @@ fn rewrite_deleted_name_loads_in_expr(
         CoreBlockPyExpr::BinOp(_)
         | CoreBlockPyExpr::UnaryOp(_)
         | CoreBlockPyExpr::Call(_)
+        | CoreBlockPyExpr::BuildTuple(_)
+        | CoreBlockPyExpr::BuildList(_)
+        | CoreBlockPyExpr::BuildSet(_)
+        | CoreBlockPyExpr::BuildDict(_)
         | CoreBlockPyExpr::GetAttr(_)
         | CoreBlockPyExpr::SetAttr(_)
         | CoreBlockPyExpr::GetItem(_)
         | CoreBlockPyExpr::SetItem(_)
         | CoreBlockPyExpr::DelItem(_)
+        | CoreBlockPyExpr::GetIter(_)
+        | CoreBlockPyExpr::ForIter(_)
+        | CoreBlockPyExpr::Unpack(_)
+        | CoreBlockPyExpr::ImportName(_)
+        | CoreBlockPyExpr::ImportFrom(_)
+        | CoreBlockPyExpr::CreateClass(_)
+        | CoreBlockPyExpr::FormatValue(_)
+        | CoreBlockPyExpr::BuildString(_)
         | CoreBlockPyExpr::MakeCell(_)
         | CoreBlockPyExpr::MakeFunction(_) => {
             struct RewriteVisitor<'a> {
                 scope: &'a CallableScopeInfo,
                 storage_layout: &'a StorageLayout,
                 resolver: &'a NameBindingMapper<'a>,
-                deleted_names: &'a HashSet<String>,
+                checked_names: &'a HashSet<String>,
                 always_unbound_names: &'a HashSet<String>,
             }
 
@@ -472,7 +534,7 @@ fn rewrite_deleted_name_loads_in_expr(
                         self.scope,
                         self.storage_layout,
                         self.resolver,
-                        self.deleted_names,
+                        self.checked_names,
                         self.always_unbound_names,
                     );
                 }
@@ -482,7 +544,7 @@ fn rewrite_deleted_name_loads_in_expr(
                 scope,
                 storage_layout,
                 resolver,
-                deleted_names,
+                checked_names,
                 always_unbound_names,
             });
         }
@@ -493,7 +555,7 @@ fn rewrite_deleted_name_loads_in_expr(
                     scope,
                     storage_layout,
                     resolver,
-                    deleted_names,
+                    checked_names,
                     always_unbound_names,
                 );
             });
@@ -1159,7 +1221,7 @@ fn rewrite_deleted_name_loads_in_stmt(
     scope: &CallableScopeInfo,
     storage_layout: &StorageLayout,
     resolver: &NameBindingMapper<'_>,
-    deleted_names: &HashSet<String>,
+    checked_names: &HashSet<String>,
     always_unbound_names: &HashSet<String>,
 ) {
     rewrite_deleted_name_loads_in_expr(
@@ -1167,7 +1229,7 @@ fn rewrite_deleted_name_loads_in_stmt(
         scope,
         storage_layout,
         resolver,
-        deleted_names,
+        checked_names,
         always_unbound_names,
     )
 }
@@ -1177,14 +1239,14 @@ fn rewrite_deleted_name_loads_in_term(
     scope: &CallableScopeInfo,
     storage_layout: &StorageLayout,
     resolver: &NameBindingMapper<'_>,
-    deleted_names: &HashSet<String>,
+    checked_names: &HashSet<String>,
     always_unbound_names: &HashSet<String>,
 ) {
     struct RewriteTermVisitor<'a> {
         scope: &'a CallableScopeInfo,
         storage_layout: &'a StorageLayout,
         resolver: &'a NameBindingMapper<'a>,
-        deleted_names: &'a HashSet<String>,
+        checked_names: &'a HashSet<String>,
         always_unbound_names: &'a HashSet<String>,
     }
 
@@ -1195,7 +1257,7 @@ fn rewrite_deleted_name_loads_in_term(
                 self.scope,
                 self.storage_layout,
                 self.resolver,
-                self.deleted_names,
+                self.checked_names,
                 self.always_unbound_names,
             );
         }
@@ -1206,7 +1268,7 @@ fn rewrite_deleted_name_loads_in_term(
             scope,
             storage_layout,
             resolver,
-            deleted_names,
+            checked_names,
             always_unbound_names,
         },
         term,
@@ -1250,11 +1312,23 @@ fn rewrite_raw_cell_loads_in_expr(
         }
         CoreBlockPyExpr::BinOp(_)
         | CoreBlockPyExpr::UnaryOp(_)
+        | CoreBlockPyExpr::BuildTuple(_)
+        | CoreBlockPyExpr::BuildList(_)
+        | CoreBlockPyExpr::BuildSet(_)
+        | CoreBlockPyExpr::BuildDict(_)
         | CoreBlockPyExpr::GetAttr(_)
         | CoreBlockPyExpr::SetAttr(_)
         | CoreBlockPyExpr::GetItem(_)
         | CoreBlockPyExpr::SetItem(_)
         | CoreBlockPyExpr::DelItem(_)
+        | CoreBlockPyExpr::GetIter(_)
+        | CoreBlockPyExpr::ForIter(_)
+        | CoreBlockPyExpr::Unpack(_)
+        | CoreBlockPyExpr::ImportName(_)
+        | CoreBlockPyExpr::ImportFrom(_)
+        | CoreBlockPyExpr::CreateClass(_)
+        | CoreBlockPyExpr::FormatValue(_)
+        | CoreBlockPyExpr::BuildString(_)
         | CoreBlockPyExpr::Load(_)
         | CoreBlockPyExpr::Store(_)
         | CoreBlockPyExpr::Del(_)
@@ -1461,6 +1535,27 @@ fn collect_always_unbound_local_names(
         .collect()
 }
 
+/// Function locals kept in stack slots, which definite-assignment analysis
+/// can follow.
+fn collect_plain_local_names(callable: &BlockPyFunction<CoreBlockPyPass>) -> HashSet<String> {
+    let scope = &callable.scope;
+    if scope.scope_kind != CallableScopeKind::Function {
+        return HashSet::new();
+    }
+    scope
+        .local_defs
+        .iter()
+        .filter(|name| !is_internal_symbol(name.as_str()))
+        .filter(|name| {
+            matches!(
+                scope.effective_binding(name.as_str(), BindingPurpose::Load),
+                Some(EffectiveBinding::Local)
+            )
+        })
+        .cloned()
+        .collect()
+}
+
 fn collect_remaining_names_in_expr(expr: &CoreBlockPyExpr, names: &mut HashSet<String>) {
     match expr {
         CoreBlockPyExpr::Load(op) => {
@@ -1476,11 +1571,23 @@ fn collect_remaining_names_in_expr(expr: &CoreBlockPyExpr, names: &mut HashSet<S
         | CoreBlockPyExpr::BinOp(_)
         | CoreBlockPyExpr::UnaryOp(_)
         | CoreBlockPyExpr::Call(_)
+        | CoreBlockPyExpr::BuildTuple(_)
+        | CoreBlockPyExpr::BuildList(_)
+        | CoreBlockPyExpr::BuildSet(_)
+        | CoreBlockPyExpr::BuildDict(_)
         | CoreBlockPyExpr::GetAttr(_)
         | CoreBlockPyExpr::SetAttr(_)
         | CoreBlockPyExpr::GetItem(_)
         | CoreBlockPyExpr::SetItem(_)
         | CoreBlockPyExpr::DelItem(_)
+        | CoreBlockPyExpr::GetIter(_)
+        | CoreBlockPyExpr::ForIter(_)
+        | CoreBlockPyExpr::Unpack(_)
+        | CoreBlockPyExpr::ImportName(_)
+        | CoreBlockPyExpr::ImportFrom(_)
+        | CoreBlockPyExpr::CreateClass(_)
+        | CoreBlockPyExpr::FormatValue(_)
+        | CoreBlockPyExpr::BuildString(_)
         | CoreBlockPyExpr::MakeCell(_)
         | CoreBlockPyExpr::CellRefForName(_)
         | CoreBlockPyExpr::CellRef(_)
@@ -2573,11 +2680,10 @@ fn populate_jump_edge_args(blocks: &mut [crate::block_py::ResolvedStorageBlock])
     }
 }
 
-fn lower_name_binding_callable(
+fn bind_storage_in_callable(
     callable: BlockPyFunction<CoreBlockPyPass>,
     callee_make_function_captures: &HashMap<crate::block_py::FunctionId, Vec<CellCaptureBinding>>,
-    global_slots: &mut ModuleGlobalSlots,
-) -> BlockPyFunction<ResolvedStorageBlockPyPass> {
+) -> BlockPyFunction<CoreBlockPyPass> {
     let scope = callable.scope.clone();
     let local_slots = collect_local_slot_locations(&callable);
     let mut mapper = NameBindingMapper {
@@ -2594,27 +2700,52 @@ fn lower_name_binding_callable(
         .expect("name binding should have storage layout before cell-location analysis");
     let deleted_names = collect_deleted_names_in_blocks(&lowered.blocks, &scope, storage_layout);
     let always_unbound_names = collect_always_unbound_local_names(&lowered);
-    if !deleted_names.is_empty() || !always_unbound_names.is_empty() {
-        for block in &mut lowered.blocks {
-            for stmt in &mut block.body {
-                rewrite_deleted_name_loads_in_stmt(
-                    stmt,
-                    &scope,
-                    storage_layout,
-                    &mapper,
-                    &deleted_names,
-                    &always_unbound_names,
-                );
+    // Plain locals are checked wherever they might be unbound; cells keep a
+    // check on every load once anything deletes them.
+    let assignment = DefiniteAssignment::analyze(&lowered, collect_plain_local_names(&lowered));
+    let deleted_cell_names = deleted_names
+        .difference(assignment.tracked())
+        .cloned()
+        .collect::<HashSet<_>>();
+    let checked_names = |assigned: &HashSet<String>| {
+        assignment
+            .unassigned(assigned)
+            .chain(&deleted_cell_names)
+            .cloned()
+            .collect::<HashSet<_>>()
+    };
+    for (index, block) in lowered.blocks.iter_mut().enumerate() {
+        let mut assigned = assignment.block_entry(index).clone();
+        for stmt in &mut block.body {
+            let assigned_here = assignment.assigned_during(&assigned, stmt);
+            assignment.transfer(&mut assigned, stmt);
+            RedundantDeletedNameCheckStripper {
+                assigned: &assigned_here,
             }
-            rewrite_deleted_name_loads_in_term(
-                &mut block.term,
+            .visit_instr_mut(stmt);
+            rewrite_deleted_name_loads_in_stmt(
+                stmt,
                 &scope,
                 storage_layout,
                 &mapper,
-                &deleted_names,
+                &checked_names(&assigned_here),
                 &always_unbound_names,
             );
         }
+        crate::block_py::walk_term_mut(
+            &mut RedundantDeletedNameCheckStripper {
+                assigned: &assigned,
+            },
+            &mut block.term,
+        );
+        rewrite_deleted_name_loads_in_term(
+            &mut block.term,
+            &scope,
+            storage_layout,
+            &mapper,
+            &checked_names(&assigned),
+            &always_unbound_names,
+        );
     }
     rewrite_current_exception_in_core_blocks(&mut lowered.blocks);
     let normal_predecessors = normal_predecessor_exc_param_names(&lowered.blocks);
@@ -2629,9 +2760,39 @@ fn lower_name_binding_callable(
         }
         rewrite_raw_cell_loads_in_term(&mut block.term, &scope, &mapper);
     }
-    let mut lowered = normalize_stmt_ops_in_resolved_callable(refresh_bb_callable_block_params(
-        locate_names_in_callable(lowered, global_slots),
-    ));
+    lowered
+}
+
+/// Lists the loads of function locals that may run before the local is
+/// assigned, across every callable in `module`.
+pub(crate) fn maybe_unbound_locals_in_core_blockpy_module(
+    module: BlockPyModule<CoreBlockPyPass>,
+    pool: &CallablePool,
+) -> Vec<MaybeUnboundLocal> {
+    let callable_defs = ensure_module_storage_layouts(module.callable_defs);
+    let callee_make_function_capture_names =
+        compute_module_make_function_capture_names(&callable_defs);
+    pool.map(callable_defs, |callable| {
+        let scope = callable.scope.clone();
+        let mut mapper = NameBindingMapper {
+            scope: &scope,
+            callee_make_function_captures: &callee_make_function_capture_names,
+            local_slots: collect_local_slot_locations(&callable),
+        };
+        let lowered = mapper.map_fn(callable);
+        DefiniteAssignment::analyze(&lowered, collect_plain_local_names(&lowered))
+            .maybe_unbound_locals(&lowered)
+    })
+    .into_iter()
+    .flatten()
+    .collect()
+}
+
+fn finish_located_callable(
+    located: BlockPyFunction<ResolvedStorageBlockPyPass>,
+) -> BlockPyFunction<ResolvedStorageBlockPyPass> {
+    let mut lowered =
+        normalize_stmt_ops_in_resolved_callable(refresh_bb_callable_block_params(located));
     ensure_storage_layout_covers_block_params(&mut lowered);
     lowered
 }
@@ -2705,24 +2866,26 @@ impl crate::block_py::VisitMut<LocatedCoreBlockPyExpr> for ModuleConstantExtract
 
 pub(crate) fn lower_name_binding_in_core_blockpy_module(
     module: BlockPyModule<CoreBlockPyPass>,
+    pool: &CallablePool,
 ) -> BlockPyModule<ResolvedStorageBlockPyPass> {
     let callable_defs = ensure_module_storage_layouts(module.callable_defs);
     let callee_make_function_capture_names =
         compute_module_make_function_capture_names(&callable_defs);
+    let bound = pool.map(callable_defs, |callable| {
+        bind_storage_in_callable(callable, &callee_make_function_capture_names)
+    });
+    // Global slots are numbered by first use in callable order, so locating
+    // names stays sequential; the per-callable work on either side of it
+    // runs on the pool.
     let mut global_slots = ModuleGlobalSlots::default();
+    let located = bound
+        .into_iter()
+        .map(|callable| locate_names_in_callable(callable, &mut global_slots))
+        .collect();
     let mut lowered = ModuleConstantExtractor::default().extract_module(BlockPyModule {
         module_name_gen: module.module_name_gen,
         global_names: Vec::new(),
-        callable_defs: callable_defs
-            .into_iter()
-            .map(|callable| {
-                lower_name_binding_callable(
-                    callable,
-                    &callee_make_function_capture_names,
-                    &mut global_slots,
-                )
-            })
-            .collect(),
+        callable_defs: pool.map(located, finish_located_callable),
         module_constants: Vec::new(),
         counter_defs: Vec::new(),
     });
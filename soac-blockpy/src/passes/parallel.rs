@@ -0,0 +1,130 @@
+use crate::pass_tracker::ParallelTiming;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Modules with fewer callables than this are lowered on the calling thread;
+/// spawning workers costs more than it saves for typical source files.
+const MIN_PARALLEL_CALLABLES: usize = 32;
+
+/// Lowering recurses over nested expressions, so give workers the same stack
+/// budget as a default main thread.
+const WORKER_STACK_SIZE: usize = 8 * 1024 * 1024;
+
+/// `requested` worker threads, or every available core.
+pub(crate) fn lowering_threads(requested: Option<usize>) -> usize {
+    requested.unwrap_or_else(|| {
+        thread::available_parallelism()
+            .map(|threads| threads.get())
+            .unwrap_or(1)
+    })
+}
+
+/// Runs per-callable lowering stages across a fixed number of worker threads.
+///
+/// `map` always returns results in input order, so the lowered module is
+/// identical regardless of thread count. Worker busy time accumulates until
+/// `take_timing` so the pass tracker can report the parallel speedup.
+pub(crate) struct CallablePool {
+    threads: usize,
+    busy_nanos: AtomicU64,
+}
+
+impl CallablePool {
+    pub(crate) fn new(threads: usize) -> Self {
+        Self {
+            threads: threads.max(1),
+            busy_nanos: AtomicU64::new(0),
+        }
+    }
+
+    pub(crate) fn sequential() -> Self {
+        Self::new(1)
+    }
+
+    pub(crate) fn map<T, U, F>(&self, items: Vec<T>, f: F) -> Vec<U>
+    where
+        T: Send,
+        U: Send,
+        F: Fn(T) -> U + Sync,
+    {
+        let workers = self.threads.min(items.len());
+        if workers <= 1 || items.len() < MIN_PARALLEL_CALLABLES {
+            let start = Instant::now();
+            let out = items.into_iter().map(f).collect();
+            self.add_busy(start.elapsed());
+            return out;
+        }
+
+        let inputs = items
+            .into_iter()
+            .map(|item| Mutex::new(Some(item)))
+            .collect::<Vec<_>>();
+        let outputs = (0..inputs.len())
+            .map(|_| Mutex::new(None))
+            .collect::<Vec<Mutex<Option<U>>>>();
+        let next = AtomicUsize::new(0);
+        let (inputs_ref, outputs_ref, next, f) = (&inputs, &outputs, &next, &f);
+
+        thread::scope(|scope| {
+            let handles = (0..workers)
+                .map(|_| {
+                    thread::Builder::new()
+                        .stack_size(WORKER_STACK_SIZE)
+                        .spawn_scoped(scope, move || {
+                            let start = Instant::now();
+                            loop {
+                                let index = next.fetch_add(1, Ordering::Relaxed);
+                                let Some(input) = inputs_ref.get(index) else {
+                                    break;
+                                };
+                                let item = input
+                                    .lock()
+                                    .unwrap_or_else(|poisoned| poisoned.into_inner())
+                                    .take()
+                                    .expect("each callable should be claimed once");
+                                let output = f(item);
+                                *outputs_ref[index]
+                                    .lock()
+                                    .unwrap_or_else(|poisoned| poisoned.into_inner()) =
+                                    Some(output);
+                            }
+                            self.add_busy(start.elapsed());
+                        })
+                        .expect("failed to spawn lowering worker")
+                })
+                .collect::<Vec<_>>();
+            for handle in handles {
+                if let Err(payload) = handle.join() {
+                    std::panic::resume_unwind(payload);
+                }
+            }
+        });
+
+        outputs
+            .into_iter()
+            .map(|output| {
+                output
+                    .into_inner()
+                    .unwrap_or_else(|poisoned| poisoned.into_inner())
+                    .expect("every callable should be lowered")
+            })
+            .collect()
+    }
+
+    pub(crate) fn take_timing(&self) -> ParallelTiming {
+        ParallelTiming {
+            threads: self.threads,
+            busy: Duration::from_nanos(self.busy_nanos.swap(0, Ordering::Relaxed)),
+        }
+    }
+
+    fn add_busy(&self, elapsed: Duration) {
+        let nanos = u64::try_from(elapsed.as_nanos()).unwrap_or(u64::MAX);
+        self.busy_nanos.fetch_add(nanos, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod test;
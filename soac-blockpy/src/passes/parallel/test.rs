@@ -0,0 +1,72 @@
+use super::{CallablePool, MIN_PARALLEL_CALLABLES};
+use std::thread;
+
+#[test]
+fn map_preserves_input_order_across_workers() {
+    let pool = CallablePool::new(4);
+    let items = (0..MIN_PARALLEL_CALLABLES * 4).collect::<Vec<_>>();
+
+    let out = pool.map(items.clone(), |item| item * 2);
+
+    assert_eq!(out, items.iter().map(|item| item * 2).collect::<Vec<_>>());
+}
+
+#[test]
+fn map_uses_worker_threads_for_large_inputs() {
+    let pool = CallablePool::new(4);
+    let caller = thread::current().id();
+    let items = (0..MIN_PARALLEL_CALLABLES).collect::<Vec<_>>();
+
+    let thread_ids = pool.map(items, |_| thread::current().id());
+
+    assert!(thread_ids.iter().all(|id| *id != caller));
+}
+
+#[test]
+fn map_runs_small_inputs_on_calling_thread() {
+    let pool = CallablePool::new(4);
+    let caller = thread::current().id();
+
+    let thread_ids = pool.map(vec![1, 2, 3], |_| thread::current().id());
+
+    assert!(thread_ids.iter().all(|id| *id == caller));
+}
+
+#[test]
+fn sequential_pool_matches_parallel_pool() {
+    let items = (0..MIN_PARALLEL_CALLABLES * 2)
+        .map(|item| format!("f{item}"))
+        .collect::<Vec<_>>();
+
+    let sequential = CallablePool::sequential().map(items.clone(), |name| name + "_lowered");
+    let parallel = CallablePool::new(8).map(items, |name| name + "_lowered");
+
+    assert_eq!(sequential, parallel);
+}
+
+#[test]
+#[should_panic(expected = "lowering failed for 7")]
+fn map_propagates_worker_panics() {
+    let pool = CallablePool::new(4);
+    let items = (0..MIN_PARALLEL_CALLABLES).collect::<Vec<_>>();
+
+    pool.map(items, |item| {
+        if item == 7 {
+            panic!("lowering failed for {item}");
+        }
+        item
+    });
+}
+
+#[test]
+fn take_timing_reports_threads_and_resets_busy_time() {
+    let pool = CallablePool::new(3);
+    pool.map((0..MIN_PARALLEL_CALLABLES).collect(), |item| item + 1);
+
+    let timing = pool.take_timing();
+    assert_eq!(timing.threads, 3);
+
+    let reset = pool.take_timing();
+    assert_eq!(reset.threads, 3);
+    assert!(reset.busy.is_zero());
+}
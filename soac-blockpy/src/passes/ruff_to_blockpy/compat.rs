@@ -20,22 +20,21 @@ fn with_exc_meta<E: Instr>(
 }
 
 pub(crate) fn compat_block_from_blockpy_with_exc_target_and_expr<E>(
+    context: &Context,
     label: BlockLabel,
     body: Vec<Stmt>,
     term: BlockTerm<E>,
     exc_target: Option<&BlockLabel>,
-) -> LoweredBlockPyBlock<E>
+) -> Result<LoweredBlockPyBlock<E>, Diagnostic>
 where
     E: RuffToBlockPyExpr + ImplicitNoneExpr,
 {
-    let body = lower_stmts_to_blockpy_stmts::<E>(&body).unwrap_or_else(|err| {
-        panic!("failed to convert compatibility block body to BlockPy: {err}")
-    });
+    let body = lower_stmts_to_blockpy_stmts_with_context::<E>(context, &body)?;
     assert!(
         body.term.is_none(),
         "compatibility block body should not contain its own terminator"
     );
-    with_exc_meta(
+    Ok(with_exc_meta(
         Block::from_builder(
             label,
             BlockBuilder::with_term(body.body, Some(term)),
@@ -44,13 +43,13 @@ where
             None,
         ),
         exc_target,
-    )
+    ))
 }
 
 fn compat_block_builder_with_expr_setup_and_expr<E>(
     context: &Context,
     body: Vec<Stmt>,
-) -> Result<BlockPyStmtBuilder<E>, String>
+) -> Result<BlockPyStmtBuilder<E>, Diagnostic>
 where
     E: RuffToBlockPyExpr + ImplicitNoneExpr,
 {
@@ -70,13 +69,14 @@ pub(crate) fn compat_if_jump_block_with_expr_setup_and_exc_target_and_expr<E>(
     then_label: BlockLabel,
     else_label: BlockLabel,
     exc_target: Option<&BlockLabel>,
-) -> Result<LoweredBlockPyBlock<E>, String>
+) -> Result<LoweredBlockPyBlock<E>, Diagnostic>
 where
     E: RuffToBlockPyExpr + ImplicitNoneExpr,
 {
     let mut out = compat_block_builder_with_expr_setup_and_expr::<E>(context, body)?;
     let mut next_label_id = 0usize;
     let test = crate::passes::ruff_to_blockpy::expr_lowering::lower_expr_into_with_setup(
+        context,
         test,
         &mut out,
         None,
@@ -150,22 +150,24 @@ fn rename_exception_edge_args<E: Instr>(
 }
 
 pub(crate) fn emit_sequence_jump_block<E>(
+    context: &Context,
     blocks: &mut Vec<LoweredBlockPyBlock<E>>,
     label: BlockLabel,
     linear: Vec<Stmt>,
     target_label: BlockLabel,
     exc_target: Option<&BlockLabel>,
-) -> BlockLabel
+) -> Result<BlockLabel, Diagnostic>
 where
     E: RuffToBlockPyExpr + ImplicitNoneExpr,
 {
     blocks.push(compat_block_from_blockpy_with_exc_target_and_expr(
+        context,
         label.clone(),
         linear,
         BlockTerm::Jump(BlockEdge::new(target_label)),
         exc_target,
-    ));
-    label
+    )?);
+    Ok(label)
 }
 
 pub(crate) fn emit_sequence_return_block_with_expr_setup_and_expr<E>(
@@ -175,7 +177,7 @@ pub(crate) fn emit_sequence_return_block_with_expr_setup_and_expr<E>(
     linear: Vec<Stmt>,
     value: Option<Expr>,
     exc_target: Option<&BlockLabel>,
-) -> Result<BlockLabel, String>
+) -> Result<BlockLabel, Diagnostic>
 where
     E: RuffToBlockPyExpr + ImplicitNoneExpr,
 {
@@ -184,6 +186,7 @@ where
     let value = value
         .map(|expr| {
             crate::passes::ruff_to_blockpy::expr_lowering::lower_expr_into_with_setup(
+                context,
                 expr,
                 &mut out,
                 None,
@@ -221,7 +224,7 @@ pub(crate) fn emit_sequence_raise_block_with_expr_setup_and_expr<E>(
     linear: Vec<Stmt>,
     exc: TermRaise<Expr>,
     exc_target: Option<&BlockLabel>,
-) -> Result<BlockLabel, String>
+) -> Result<BlockLabel, Diagnostic>
 where
     E: RuffToBlockPyExpr + ImplicitNoneExpr,
 {
@@ -232,6 +235,7 @@ where
             .exc
             .map(|expr| {
                 crate::passes::ruff_to_blockpy::expr_lowering::lower_expr_into_with_setup(
+                    context,
                     expr,
                     &mut out,
                     None,
@@ -267,7 +271,7 @@ pub(crate) fn emit_if_branch_block_with_expr_setup_and_expr<E>(
     then_label: BlockLabel,
     else_label: BlockLabel,
     exc_target: Option<&BlockLabel>,
-) -> Result<BlockLabel, String>
+) -> Result<BlockLabel, Diagnostic>
 where
     E: RuffToBlockPyExpr + ImplicitNoneExpr,
 {
@@ -295,7 +299,7 @@ pub(crate) fn emit_simple_while_blocks_with_expr_setup_and_expr<E>(
     body_entry: BlockLabel,
     cond_false_entry: BlockLabel,
     exc_target: Option<&BlockLabel>,
-) -> Result<BlockLabel, String>
+) -> Result<BlockLabel, Diagnostic>
 where
     E: RuffToBlockPyExpr + ImplicitNoneExpr,
 {
@@ -312,11 +316,12 @@ where
     );
     if let Some(linear_label) = linear_label {
         blocks.push(compat_block_from_blockpy_with_exc_target_and_expr(
+            context,
             linear_label.clone(),
             linear,
             BlockTerm::Jump(BlockEdge::new(test_label)),
             exc_target,
-        ));
+        )?);
         Ok(linear_label)
     } else {
         Ok(test_label)
@@ -324,6 +329,7 @@ where
 }
 
 pub(crate) fn emit_for_loop_blocks<E>(
+    context: &Context,
     blocks: &mut Vec<LoweredBlockPyBlock<E>>,
     setup_label: BlockLabel,
     assign_label: BlockLabel,
@@ -338,7 +344,7 @@ pub(crate) fn emit_for_loop_blocks<E>(
     body_entry: BlockLabel,
     assign_body: Vec<Stmt>,
     exc_target: Option<&BlockLabel>,
-) -> BlockLabel
+) -> Result<BlockLabel, Diagnostic>
 where
     E: RuffToBlockPyExpr + ImplicitNoneExpr,
 {
@@ -346,11 +352,12 @@ where
     let tmp_expr = py_expr!("{tmp:id}", tmp = tmp_name);
 
     blocks.push(compat_block_from_blockpy_with_exc_target_and_expr(
+        context,
         assign_label.clone(),
         assign_body,
         BlockTerm::Jump(BlockEdge::new(body_entry)),
         exc_target,
-    ));
+    )?);
 
     let exhausted_test = py_expr!("{value:expr} is __soac__.ITER_COMPLETE", value = tmp_expr);
     let check_body = if is_async {
@@ -367,6 +374,7 @@ where
         )]
     };
     blocks.push(compat_block_from_blockpy_with_exc_target_and_expr(
+        context,
         loop_check_label.clone(),
         check_body,
         BlockTerm::IfTerm(TermIf {
@@ -375,7 +383,7 @@ where
             else_label: assign_label.clone(),
         }),
         exc_target,
-    ));
+    )?);
 
     let mut setup_body = linear;
     if is_async {
@@ -386,16 +394,17 @@ where
         ));
     } else {
         setup_body.push(py_stmt!(
-            "{iter:id} = __soac__.iter({iterable:expr})",
+            "{iter:id} = __soac__.loop_iter({iterable:expr})",
             iter = iter_name,
             iterable = iterable,
         ));
     }
     blocks.push(compat_block_from_blockpy_with_exc_target_and_expr(
+        context,
         setup_label.clone(),
         setup_body,
         BlockTerm::Jump(BlockEdge::new(loop_continue_label)),
         exc_target,
-    ));
-    setup_label
+    )?);
+    Ok(setup_label)
 }
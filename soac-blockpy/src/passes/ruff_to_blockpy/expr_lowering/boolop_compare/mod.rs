@@ -3,7 +3,7 @@ use crate::block_py::{
     BlockBuilder, BlockPyStmtBuilder, BlockTerm, Instr, Meta, Store, StructuredIf, StructuredInstr,
     WithMeta,
 };
-use crate::passes::ruff_to_blockpy::expr_lowering::fresh_setup_name;
+use crate::diagnostic::Diagnostic;
 use crate::passes::ruff_to_blockpy::LoopContext;
 use crate::py_expr;
 use ruff_python_ast::{self as ast, CmpOp, Expr};
@@ -21,17 +21,17 @@ fn load_name(name: &str) -> Expr {
     py_expr!("{name:id}", name = name)
 }
 
-fn assign_name<E>(target: &str, value: Expr) -> StructuredInstr<E>
+fn assign_name<E>(target: &str, value: Expr) -> Result<StructuredInstr<E>, Diagnostic>
 where
     E: RuffToBlockPyExpr,
 {
     let target = store_name(target);
     let meta = Meta::new(target.node_index.clone(), target.range);
-    StructuredInstr::Expr(
-        Store::new(target, Box::new(E::from_lowered_expr(value)))
+    Ok(StructuredInstr::Expr(
+        Store::new(target, Box::new(E::from_lowered_expr(value)?))
             .with_meta(meta)
             .into(),
-    )
+    ))
 }
 
 fn empty_fragment<E>() -> BlockBuilder<StructuredInstr<E>, BlockTerm<E>>
@@ -47,22 +47,22 @@ pub(super) fn lower_boolop_into<L, E>(
     out: &mut BlockPyStmtBuilder<E>,
     loop_ctx: Option<&LoopContext>,
     next_label_id: &mut usize,
-) -> Result<Expr, String>
+) -> Result<Expr, Diagnostic>
 where
     L: BlockPySetupExprLowerer + ?Sized,
     E: RuffToBlockPyExpr,
 {
     let ast::ExprBoolOp { op, values, .. } = bool_op;
-    let target = fresh_setup_name("target");
+    let target = lowerer.fresh_name("target");
     let mut values = values.into_iter();
     let first = values.next().expect("bool op expects at least one value");
     let first = lowerer.lower_expr_ast_into(first, out, loop_ctx, next_label_id)?;
-    out.push_stmt(assign_name(&target, first));
+    out.push_stmt(assign_name(&target, first)?);
 
     for value in values {
         let mut body = BlockPyStmtBuilder::<E>::new();
         let value = lowerer.lower_expr_ast_into(value, &mut body, loop_ctx, next_label_id)?;
-        body.push_stmt(assign_name(&target, value));
+        body.push_stmt(assign_name(&target, value)?);
         let test = match op {
             ast::BoolOp::And => load_name(&target),
             ast::BoolOp::Or => py_expr!("not {target:id}", target = target.as_str()),
@@ -83,7 +83,7 @@ pub(super) fn lower_compare_into<L, E>(
     out: &mut BlockPyStmtBuilder<E>,
     loop_ctx: Option<&LoopContext>,
     next_label_id: &mut usize,
-) -> Result<Expr, String>
+) -> Result<Expr, Diagnostic>
 where
     L: BlockPySetupExprLowerer + ?Sized,
     E: RuffToBlockPyExpr,
@@ -108,12 +108,12 @@ where
         return Ok(compare_expr(ops[0], left, right));
     }
 
-    let compare_name = fresh_setup_name("compare");
+    let compare_name = lowerer.fresh_name("compare");
     let mut current_left = lowerer.lower_expr_ast_into(*left, out, loop_ctx, next_label_id)?;
-    out.push_stmt(assign_name(&compare_name, current_left));
+    out.push_stmt(assign_name(&compare_name, current_left)?);
     current_left = load_name(&compare_name);
 
-    let target_name = fresh_setup_name("target");
+    let target_name = lowerer.fresh_name("target");
     let mut steps = ops.into_iter().zip(comparators.into_iter()).peekable();
     let Some((first_op, first_comparator)) = steps.next() else {
         unreachable!("compare chain should contain at least one step");
@@ -121,14 +121,14 @@ where
     let mut first_comparator =
         lowerer.lower_expr_ast_into(first_comparator, out, loop_ctx, next_label_id)?;
     if steps.peek().is_some() {
-        let tmp_name = fresh_setup_name("compare");
-        out.push_stmt(assign_name(&tmp_name, first_comparator));
+        let tmp_name = lowerer.fresh_name("compare");
+        out.push_stmt(assign_name(&tmp_name, first_comparator)?);
         first_comparator = load_name(&tmp_name);
     }
     out.push_stmt(assign_name(
         &target_name,
         compare_expr(first_op, current_left.clone(), first_comparator.clone()),
-    ));
+    )?);
     current_left = first_comparator;
 
     while let Some((op, comparator)) = steps.next() {
@@ -136,14 +136,14 @@ where
         let mut comparator_expr =
             lowerer.lower_expr_ast_into(comparator, &mut step_body, loop_ctx, next_label_id)?;
         if steps.peek().is_some() {
-            let tmp_name = fresh_setup_name("compare");
-            step_body.push_stmt(assign_name(&tmp_name, comparator_expr));
+            let tmp_name = lowerer.fresh_name("compare");
+            step_body.push_stmt(assign_name(&tmp_name, comparator_expr)?);
             comparator_expr = load_name(&tmp_name);
         }
         step_body.push_stmt(assign_name(
             &target_name,
             compare_expr(op, current_left.clone(), comparator_expr.clone()),
-        ));
+        )?);
         current_left = comparator_expr;
         out.push_stmt(StructuredInstr::If(StructuredIf {
             test: load_name(&target_name).into(),
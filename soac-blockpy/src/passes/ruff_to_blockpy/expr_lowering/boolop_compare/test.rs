@@ -1,15 +1,24 @@
-use crate::block_py::{BlockPyStmtBuilder, CoreBlockPyExprWithAwaitAndYield, StructuredInstr};
+use crate::block_py::{
+    BlockPyStmtBuilder, CoreBlockPyExprWithAwaitAndYield, ModuleNameGen, StructuredInstr,
+};
+use crate::passes::ast_to_ast::context::Context;
 use crate::passes::ruff_to_blockpy::expr_lowering::lower_expr_into_with_setup;
 use crate::py_expr;
 
 #[test]
 fn boolop_lowering_emits_blockpy_setup_directly() {
+    let context = Context::new("", ModuleNameGen::default());
     let mut out = BlockPyStmtBuilder::<CoreBlockPyExprWithAwaitAndYield>::new();
     let mut next_label_id = 0usize;
 
-    let lowered =
-        lower_expr_into_with_setup(py_expr!("a and b"), &mut out, None, &mut next_label_id)
-            .expect("expr lowering should succeed");
+    let lowered = lower_expr_into_with_setup(
+        &context,
+        py_expr!("a and b"),
+        &mut out,
+        None,
+        &mut next_label_id,
+    )
+    .expect("expr lowering should succeed");
 
     let fragment = out.finish();
     let rendered = format!("{lowered:?}");
@@ -32,11 +41,18 @@ fn boolop_lowering_emits_blockpy_setup_directly() {
 
 #[test]
 fn compare_lowering_keeps_native_compare_expr() {
+    let context = Context::new("", ModuleNameGen::default());
     let mut out = BlockPyStmtBuilder::<CoreBlockPyExprWithAwaitAndYield>::new();
     let mut next_label_id = 0usize;
 
-    let lowered = lower_expr_into_with_setup(py_expr!("a < b"), &mut out, None, &mut next_label_id)
-        .expect("expr lowering should succeed");
+    let lowered = lower_expr_into_with_setup(
+        &context,
+        py_expr!("a < b"),
+        &mut out,
+        None,
+        &mut next_label_id,
+    )
+    .expect("expr lowering should succeed");
 
     assert!(
         out.finish().body.is_empty(),
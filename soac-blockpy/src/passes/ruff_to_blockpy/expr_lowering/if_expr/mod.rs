@@ -1,6 +1,6 @@
 use super::{BlockPySetupExprLowerer, RuffToBlockPyExpr};
 use crate::block_py::{BlockPyStmtBuilder, Meta, Store, StructuredIf, StructuredInstr, WithMeta};
-use crate::passes::ruff_to_blockpy::expr_lowering::fresh_setup_name;
+use crate::diagnostic::Diagnostic;
 use crate::passes::ruff_to_blockpy::LoopContext;
 use crate::py_expr;
 use ruff_python_ast::{self as ast, Expr};
@@ -18,17 +18,17 @@ fn load_name(name: &str) -> Expr {
     py_expr!("{name:id}", name = name)
 }
 
-fn assign_name<E>(target: &str, value: Expr) -> StructuredInstr<E>
+fn assign_name<E>(target: &str, value: Expr) -> Result<StructuredInstr<E>, Diagnostic>
 where
     E: RuffToBlockPyExpr,
 {
     let target = store_name(target);
     let meta = Meta::new(target.node_index.clone(), target.range);
-    StructuredInstr::Expr(
-        Store::new(target, Box::new(E::from_lowered_expr(value)))
+    Ok(StructuredInstr::Expr(
+        Store::new(target, Box::new(E::from_lowered_expr(value)?))
             .with_meta(meta)
             .into(),
-    )
+    ))
 }
 
 pub(super) fn lower_if_expr_into<L, E>(
@@ -37,7 +37,7 @@ pub(super) fn lower_if_expr_into<L, E>(
     out: &mut BlockPyStmtBuilder<E>,
     loop_ctx: Option<&LoopContext>,
     next_label_id: &mut usize,
-) -> Result<Expr, String>
+) -> Result<Expr, Diagnostic>
 where
     L: BlockPySetupExprLowerer + ?Sized,
     E: RuffToBlockPyExpr,
@@ -45,20 +45,20 @@ where
     let ast::ExprIf {
         test, body, orelse, ..
     } = if_expr;
-    let target = fresh_setup_name("tmp");
+    let target = lowerer.fresh_name("tmp");
     let test = lowerer.lower_expr_ast_into(*test, out, loop_ctx, next_label_id)?;
 
     let mut body_out = BlockPyStmtBuilder::<E>::new();
     let body_value = lowerer.lower_expr_ast_into(*body, &mut body_out, loop_ctx, next_label_id)?;
-    body_out.push_stmt(assign_name(&target, body_value));
+    body_out.push_stmt(assign_name(&target, body_value)?);
 
     let mut orelse_out = BlockPyStmtBuilder::<E>::new();
     let orelse_value =
         lowerer.lower_expr_ast_into(*orelse, &mut orelse_out, loop_ctx, next_label_id)?;
-    orelse_out.push_stmt(assign_name(&target, orelse_value));
+    orelse_out.push_stmt(assign_name(&target, orelse_value)?);
 
     out.push_stmt(StructuredInstr::If(StructuredIf {
-        test: test.into(),
+        test: E::from_lowered_expr(test)?,
         body: body_out.finish(),
         orelse: orelse_out.finish(),
     }));
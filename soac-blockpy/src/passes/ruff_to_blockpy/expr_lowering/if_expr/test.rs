@@ -1,13 +1,18 @@
-use crate::block_py::{BlockPyStmtBuilder, CoreBlockPyExprWithAwaitAndYield, StructuredInstr};
+use crate::block_py::{
+    BlockPyStmtBuilder, CoreBlockPyExprWithAwaitAndYield, ModuleNameGen, StructuredInstr,
+};
+use crate::passes::ast_to_ast::context::Context;
 use crate::passes::ruff_to_blockpy::expr_lowering::lower_expr_into_with_setup;
 use crate::py_expr;
 
 #[test]
 fn if_expr_lowering_emits_blockpy_setup_directly() {
+    let context = Context::new("", ModuleNameGen::default());
     let mut out = BlockPyStmtBuilder::<CoreBlockPyExprWithAwaitAndYield>::new();
     let mut next_label_id = 0usize;
 
     let lowered = lower_expr_into_with_setup(
+        &context,
         py_expr!("a if cond else b"),
         &mut out,
         None,
@@ -3,8 +3,10 @@ use crate::block_py::{
     CoreBlockPyExprWithAwaitAndYield, CoreStringLiteral, Del, FunctionId, FunctionKind, Instr,
     Meta, Store, UnresolvedName, WithMeta,
 };
-use crate::namegen::fresh_name;
+use crate::diagnostic::Diagnostic;
+use crate::passes::ast_to_ast::context::Context;
 use crate::passes::ast_to_ast::string_templates::lower_string_templates_in_expr;
+use crate::passes::blockpy_expr_simplify::check_lowerable_expr;
 use crate::passes::ruff_to_blockpy::LoopContext;
 use crate::py_expr;
 use ruff_python_ast::{self as ast, Expr};
@@ -32,8 +34,9 @@ pub(crate) trait RuffToBlockPyExpr:
     + Clone
     + Sized
 {
-    fn from_lowered_expr(expr: Expr) -> Self {
-        expr.into()
+    fn from_lowered_expr(expr: Expr) -> Result<Self, Diagnostic> {
+        check_lowerable_expr(&expr)?;
+        Ok(expr.into())
     }
 
     fn helper_call(
@@ -94,6 +97,14 @@ pub(crate) trait RuffToBlockPyExpr:
         value: Self,
         index: Self,
     ) -> Self;
+
+    fn unpack(
+        node_index: ast::AtomicNodeIndex,
+        range: TextRange,
+        value: Self,
+        count: usize,
+        starred: Option<usize>,
+    ) -> Self;
 }
 
 fn inplace_kind(op: ast::Operator) -> Option<operation::BinOpKind> {
@@ -115,8 +126,9 @@ fn inplace_kind(op: ast::Operator) -> Option<operation::BinOpKind> {
 }
 
 impl RuffToBlockPyExpr for CoreBlockPyExprWithAwaitAndYield {
-    fn from_lowered_expr(expr: Expr) -> Self {
-        lower_direct_core_helper_expr(&expr).unwrap_or_else(|| expr.into())
+    fn from_lowered_expr(expr: Expr) -> Result<Self, Diagnostic> {
+        check_lowerable_expr(&expr)?;
+        Ok(lower_checked_core_expr(expr))
     }
 
     fn helper_call(
@@ -218,16 +230,30 @@ impl RuffToBlockPyExpr for CoreBlockPyExprWithAwaitAndYield {
             .with_meta(Meta::new(node_index, range))
             .into()
     }
+
+    fn unpack(
+        node_index: ast::AtomicNodeIndex,
+        range: TextRange,
+        value: Self,
+        count: usize,
+        starred: Option<usize>,
+    ) -> Self {
+        operation::Unpack::new(Box::new(value), count, starred)
+            .with_meta(Meta::new(node_index, range))
+            .into()
+    }
 }
 
 pub(crate) trait BlockPySetupExprLowerer {
+    fn fresh_name(&self, prefix: &str) -> String;
+
     fn lower_expr_ast_into<E>(
         &self,
         expr: Expr,
         out: &mut BlockPyStmtBuilder<E>,
         loop_ctx: Option<&LoopContext>,
         next_label_id: &mut usize,
-    ) -> Result<Expr, String>
+    ) -> Result<Expr, Diagnostic>
     where
         E: RuffToBlockPyExpr,
     {
@@ -242,37 +268,39 @@ pub(crate) trait BlockPySetupExprLowerer {
         out: &mut BlockPyStmtBuilder<E>,
         loop_ctx: Option<&LoopContext>,
         next_label_id: &mut usize,
-    ) -> Result<E, String>
+    ) -> Result<E, Diagnostic>
     where
         E: RuffToBlockPyExpr,
     {
-        Ok(E::from_lowered_expr(self.lower_expr_ast_into(
-            expr,
-            out,
-            loop_ctx,
-            next_label_id,
-        )?))
+        E::from_lowered_expr(self.lower_expr_ast_into(expr, out, loop_ctx, next_label_id)?)
     }
 }
 
-pub(crate) struct AstSetupExprLowerer;
+pub(crate) struct AstSetupExprLowerer<'a> {
+    context: &'a Context,
+}
 
-impl BlockPySetupExprLowerer for AstSetupExprLowerer {}
+impl BlockPySetupExprLowerer for AstSetupExprLowerer<'_> {
+    fn fresh_name(&self, prefix: &str) -> String {
+        self.context.fresh(prefix)
+    }
+}
 
 pub(crate) fn lower_expr_head_ast_for_blockpy(expr: Expr) -> Expr {
     expr
 }
 
 pub(crate) fn lower_expr_into_with_setup<E>(
+    context: &Context,
     expr: Expr,
     out: &mut BlockPyStmtBuilder<E>,
     loop_ctx: Option<&LoopContext>,
     next_label_id: &mut usize,
-) -> Result<E, String>
+) -> Result<E, Diagnostic>
 where
     E: RuffToBlockPyExpr,
 {
-    AstSetupExprLowerer.lower_expr_into(expr, out, loop_ctx, next_label_id)
+    AstSetupExprLowerer { context }.lower_expr_into(expr, out, loop_ctx, next_label_id)
 }
 
 fn make_function_kind_from_literal(expr: &Expr) -> Option<FunctionKind> {
@@ -305,6 +333,154 @@ fn string_literal_value(expr: &Expr) -> Option<String> {
     Some(string.value.to_str().to_string())
 }
 
+// Reads the `(True, False, True)` spec of `__soac__.unpack(value, spec)`,
+// where `False` marks the starred target, as a target count and star index.
+pub(crate) fn unpack_spec_from_literal(expr: &Expr) -> Option<(usize, Option<usize>)> {
+    let Expr::Tuple(tuple) = expr else {
+        return None;
+    };
+    let mut starred = None;
+    for (index, elt) in tuple.elts.iter().enumerate() {
+        let Expr::BooleanLiteral(flag) = elt else {
+            return None;
+        };
+        if !flag.value {
+            if starred.is_some() {
+                return None;
+            }
+            starred = Some(index);
+        }
+    }
+    Some((tuple.elts.len(), starred))
+}
+
+// `__soac__.import_(module, spec[, fromlist[, level]])` and
+// `__soac__.import_attr(module, attr)` as written by the import rewrite, with
+// literal module names, fromlists and levels.
+pub(crate) fn import_operation_from_helper_args(
+    helper: &str,
+    args: &[Expr],
+    meta: Meta,
+    mut lower: impl FnMut(Expr) -> CoreBlockPyExprWithAwaitAndYield,
+) -> Option<CoreBlockPyExprWithAwaitAndYield> {
+    match (helper, args) {
+        ("import_", [module, spec, rest @ ..]) if rest.len() <= 2 => {
+            let module = string_literal_value(module)?;
+            let fromlist = match rest.first() {
+                Some(Expr::List(list)) => list
+                    .elts
+                    .iter()
+                    .map(string_literal_value)
+                    .collect::<Option<Vec<_>>>()?,
+                Some(_) => return None,
+                None => Vec::new(),
+            };
+            let level: u32 = match rest.get(1) {
+                Some(Expr::NumberLiteral(ast::ExprNumberLiteral {
+                    value: ast::Number::Int(level),
+                    ..
+                })) => level.to_string().parse().ok()?,
+                Some(_) => return None,
+                None => 0,
+            };
+            Some(
+                operation::ImportName::new(module, fromlist, level, Box::new(lower(spec.clone())))
+                    .with_meta(meta)
+                    .into(),
+            )
+        }
+        ("import_attr", [module, attr]) => Some(
+            operation::ImportFrom::new(
+                Box::new(lower(module.clone())),
+                string_literal_value(attr)?,
+            )
+            .with_meta(meta)
+            .into(),
+        ),
+        _ => None,
+    }
+}
+
+// `__soac__.create_class(name, namespace_fn, bases, kwds, requires_class_cell,
+// firstlineno, static_attributes)` as written by the class rewrite.
+pub(crate) fn create_class_operation_from_helper_args(
+    args: &[Expr],
+    meta: Meta,
+    mut lower: impl FnMut(Expr) -> CoreBlockPyExprWithAwaitAndYield,
+) -> Option<CoreBlockPyExprWithAwaitAndYield> {
+    let [name, namespace_fn, bases, kwds, requires_class_cell, firstlineno, static_attributes] =
+        args
+    else {
+        return None;
+    };
+    let name = string_literal_value(name)?;
+    let Expr::BooleanLiteral(requires_class_cell) = requires_class_cell else {
+        return None;
+    };
+    let firstlineno: u32 = match firstlineno {
+        Expr::NumberLiteral(ast::ExprNumberLiteral {
+            value: ast::Number::Int(firstlineno),
+            ..
+        }) => firstlineno.to_string().parse().ok()?,
+        _ => return None,
+    };
+    Some(
+        operation::CreateClass::new(
+            name,
+            Box::new(lower(namespace_fn.clone())),
+            Box::new(lower(bases.clone())),
+            Box::new(lower(kwds.clone())),
+            requires_class_cell.value,
+            firstlineno,
+            Box::new(lower(static_attributes.clone())),
+        )
+        .with_meta(meta)
+        .into(),
+    )
+}
+
+// `__soac__.format_value(value[, conversion[, format_spec]])` and
+// `__soac__.build_string(*parts)` as written by the f-string rewrite, with a
+// literal `"s"` / `"r"` / `"a"` / `None` conversion.
+pub(crate) fn string_operation_from_helper_args(
+    helper: &str,
+    args: &[Expr],
+    meta: Meta,
+    mut lower: impl FnMut(Expr) -> CoreBlockPyExprWithAwaitAndYield,
+) -> Option<CoreBlockPyExprWithAwaitAndYield> {
+    if args.iter().any(|arg| matches!(arg, Expr::Starred(_))) {
+        return None;
+    }
+    match (helper, args) {
+        ("format_value", [value, rest @ ..]) if rest.len() <= 2 => {
+            let conversion = match rest.first() {
+                None | Some(Expr::NoneLiteral(_)) => operation::FormatConversion::None,
+                Some(conversion) => match string_literal_value(conversion)?.as_str() {
+                    "s" => operation::FormatConversion::Str,
+                    "r" => operation::FormatConversion::Repr,
+                    "a" => operation::FormatConversion::Ascii,
+                    _ => return None,
+                },
+            };
+            let value = Box::new(lower(value.clone()));
+            let format_spec = rest
+                .get(1)
+                .map(|format_spec| Box::new(lower(format_spec.clone())));
+            Some(
+                operation::FormatValue::new(value, conversion, format_spec)
+                    .with_meta(meta)
+                    .into(),
+            )
+        }
+        ("build_string", parts) => Some(
+            operation::BuildString::new(parts.iter().cloned().map(&mut lower).collect::<Vec<_>>())
+                .with_meta(meta)
+                .into(),
+        ),
+        _ => None,
+    }
+}
+
 fn lowered_helper_call<'a>(
     expr: &'a Expr,
     expected_name: &str,
@@ -327,11 +503,11 @@ fn lowered_helper_call<'a>(
     Some(call)
 }
 
-fn lower_direct_core_helper_expr(expr: &Expr) -> Option<CoreBlockPyExprWithAwaitAndYield> {
-    fn lowered(expr: Expr) -> CoreBlockPyExprWithAwaitAndYield {
-        <CoreBlockPyExprWithAwaitAndYield as RuffToBlockPyExpr>::from_lowered_expr(expr)
-    }
+fn lower_checked_core_expr(expr: Expr) -> CoreBlockPyExprWithAwaitAndYield {
+    lower_direct_core_helper_expr(&expr).unwrap_or_else(|| expr.into())
+}
 
+fn lower_direct_core_helper_expr(expr: &Expr) -> Option<CoreBlockPyExprWithAwaitAndYield> {
     if let Some(call) = lowered_helper_call(expr, "make_function", 5) {
         let function_id = make_function_id_from_literal(&call.arguments.args[0])?;
         let kind = make_function_kind_from_literal(&call.arguments.args[1])?;
@@ -339,8 +515,8 @@ fn lower_direct_core_helper_expr(expr: &Expr) -> Option<CoreBlockPyExprWithAwait
             operation::MakeFunction::new(
                 function_id,
                 kind,
-                Box::new(lowered(call.arguments.args[3].clone())),
-                Box::new(lowered(call.arguments.args[4].clone())),
+                Box::new(lower_checked_core_expr(call.arguments.args[3].clone())),
+                Box::new(lower_checked_core_expr(call.arguments.args[4].clone())),
             )
             .with_meta(Meta::new(call.node_index.clone(), call.range))
             .into(),
@@ -356,7 +532,64 @@ fn lower_direct_core_helper_expr(expr: &Expr) -> Option<CoreBlockPyExprWithAwait
                     node_index: call.node_index.clone(),
                     range: call.range,
                 },
-                Box::new(lowered(call.arguments.args[2].clone())),
+                Box::new(lower_checked_core_expr(call.arguments.args[2].clone())),
+            )
+            .with_meta(Meta::new(call.node_index.clone(), call.range))
+            .into(),
+        );
+    }
+
+    for (helper, arity) in [
+        ("import_", 2),
+        ("import_", 3),
+        ("import_", 4),
+        ("import_attr", 2),
+    ] {
+        if let Some(call) = lowered_helper_call(expr, helper, arity) {
+            return import_operation_from_helper_args(
+                helper,
+                &call.arguments.args,
+                Meta::new(call.node_index.clone(), call.range),
+                lowered,
+            );
+        }
+    }
+
+    let build_string_arity = match expr {
+        Expr::Call(call) => call.arguments.args.len(),
+        _ => 0,
+    };
+    for (helper, arity) in [
+        ("format_value", 1),
+        ("format_value", 2),
+        ("format_value", 3),
+        ("build_string", build_string_arity),
+    ] {
+        if let Some(call) = lowered_helper_call(expr, helper, arity) {
+            return string_operation_from_helper_args(
+                helper,
+                &call.arguments.args,
+                Meta::new(call.node_index.clone(), call.range),
+                lowered,
+            );
+        }
+    }
+
+    if let Some(call) = lowered_helper_call(expr, "create_class", 7) {
+        return create_class_operation_from_helper_args(
+            &call.arguments.args,
+            Meta::new(call.node_index.clone(), call.range),
+            lowered,
+        );
+    }
+
+    if let Some(call) = lowered_helper_call(expr, "unpack", 2) {
+        let (count, starred) = unpack_spec_from_literal(&call.arguments.args[1])?;
+        return Some(
+            operation::Unpack::new(
+                Box::new(lower_checked_core_expr(call.arguments.args[0].clone())),
+                count,
+                starred,
             )
             .with_meta(Meta::new(call.node_index.clone(), call.range))
             .into(),
@@ -373,7 +606,3 @@ fn lower_direct_core_helper_expr(expr: &Expr) -> Option<CoreBlockPyExprWithAwait
 
     None
 }
-
-pub(crate) fn fresh_setup_name(prefix: &str) -> String {
-    fresh_name(prefix)
-}
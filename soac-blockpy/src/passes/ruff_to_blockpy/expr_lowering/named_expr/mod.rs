@@ -1,7 +1,9 @@
 use super::{BlockPySetupExprLowerer, RuffToBlockPyExpr};
 use crate::block_py::{BlockPyStmtBuilder, Meta, Store, StructuredInstr, WithMeta};
+use crate::diagnostic::Diagnostic;
 use crate::passes::ruff_to_blockpy::LoopContext;
 use ruff_python_ast::{self as ast, Expr};
+use ruff_text_size::Ranged;
 
 fn into_store_name(name: ast::ExprName) -> ast::ExprName {
     ast::ExprName {
@@ -23,17 +25,21 @@ pub(super) fn lower_named_expr_into<L, E>(
     out: &mut BlockPyStmtBuilder<E>,
     loop_ctx: Option<&LoopContext>,
     next_label_id: &mut usize,
-) -> Result<Expr, String>
+) -> Result<Expr, Diagnostic>
 where
     L: BlockPySetupExprLowerer + ?Sized,
     E: RuffToBlockPyExpr,
 {
     let ast::ExprNamed { target, value, .. } = named_expr;
+    let target_range = target.range();
     let Expr::Name(target_name) = *target else {
-        return Err("named expression lowering expected a name target".to_string());
+        return Err(Diagnostic::syntax(
+            "assignment expression target must be a name",
+            target_range,
+        ));
     };
     let value =
-        E::from_lowered_expr(lowerer.lower_expr_ast_into(*value, out, loop_ctx, next_label_id)?);
+        E::from_lowered_expr(lowerer.lower_expr_ast_into(*value, out, loop_ctx, next_label_id)?)?;
     let load_target = target_name.clone();
     let target_name = into_store_name(target_name);
     let meta = Meta::new(target_name.node_index.clone(), target_name.range);
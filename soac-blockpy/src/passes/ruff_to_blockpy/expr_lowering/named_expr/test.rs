@@ -1,17 +1,25 @@
 use crate::block_py::{
-    BlockPyNameLike, BlockPyStmtBuilder, CoreBlockPyExprWithAwaitAndYield, StructuredInstr,
+    BlockPyNameLike, BlockPyStmtBuilder, CoreBlockPyExprWithAwaitAndYield, ModuleNameGen,
+    StructuredInstr,
 };
+use crate::passes::ast_to_ast::context::Context;
 use crate::passes::ruff_to_blockpy::expr_lowering::lower_expr_into_with_setup;
 use crate::py_expr;
 
 #[test]
 fn named_expr_lowering_emits_blockpy_assign_directly() {
+    let context = Context::new("", ModuleNameGen::default());
     let mut out = BlockPyStmtBuilder::<CoreBlockPyExprWithAwaitAndYield>::new();
     let mut next_label_id = 0usize;
 
-    let _lowered =
-        lower_expr_into_with_setup(py_expr!("(x := y)"), &mut out, None, &mut next_label_id)
-            .expect("expr lowering should succeed");
+    let _lowered = lower_expr_into_with_setup(
+        &context,
+        py_expr!("(x := y)"),
+        &mut out,
+        None,
+        &mut next_label_id,
+    )
+    .expect("expr lowering should succeed");
 
     let fragment = out.finish();
     let [StructuredInstr::Expr(CoreBlockPyExprWithAwaitAndYield::Store(assign))] =
@@ -1,5 +1,6 @@
 use super::{BlockPySetupExprLowerer, RuffToBlockPyExpr};
 use crate::block_py::BlockPyStmtBuilder;
+use crate::diagnostic::Diagnostic;
 use crate::passes::ruff_to_blockpy::expr_lowering::boolop_compare::{
     lower_boolop_into, lower_compare_into,
 };
@@ -14,7 +15,7 @@ pub(super) fn lower_expr_ast_recursive<L, E>(
     out: &mut BlockPyStmtBuilder<E>,
     loop_ctx: Option<&LoopContext>,
     next_label_id: &mut usize,
-) -> Result<Expr, String>
+) -> Result<Expr, Diagnostic>
 where
     L: BlockPySetupExprLowerer + ?Sized,
     E: RuffToBlockPyExpr,
@@ -108,7 +109,7 @@ where
                     })),
                     other => lower_expr_ast_recursive(lowerer, other, out, loop_ctx, next_label_id),
                 })
-                .collect::<Result<Vec<_>, String>>()?
+                .collect::<Result<Vec<_>, Diagnostic>>()?
                 .into();
             let keywords = keywords
                 .into_vec()
@@ -127,7 +128,7 @@ where
                         node_index: keyword.node_index,
                     })
                 })
-                .collect::<Result<Vec<_>, String>>()?
+                .collect::<Result<Vec<_>, Diagnostic>>()?
                 .into();
             Ok(Expr::Call(ast::ExprCall {
                 func: Box::new(func),
@@ -218,7 +219,7 @@ where
                     })),
                     other => lower_expr_ast_recursive(lowerer, other, out, loop_ctx, next_label_id),
                 })
-                .collect::<Result<Vec<_>, String>>()?
+                .collect::<Result<Vec<_>, Diagnostic>>()?
                 .into(),
             ctx,
             range,
@@ -253,7 +254,7 @@ where
                     })),
                     other => lower_expr_ast_recursive(lowerer, other, out, loop_ctx, next_label_id),
                 })
-                .collect::<Result<Vec<_>, String>>()?
+                .collect::<Result<Vec<_>, Diagnostic>>()?
                 .into(),
             ctx,
             range,
@@ -267,7 +268,7 @@ where
             elts: elts
                 .into_iter()
                 .map(|elt| lower_expr_ast_recursive(lowerer, elt, out, loop_ctx, next_label_id))
-                .collect::<Result<Vec<_>, String>>()?
+                .collect::<Result<Vec<_>, Diagnostic>>()?
                 .into(),
             range,
             node_index,
@@ -300,7 +301,7 @@ where
                         )?,
                     })
                 })
-                .collect::<Result<Vec<_>, String>>()?
+                .collect::<Result<Vec<_>, Diagnostic>>()?
                 .into(),
             range,
             node_index,
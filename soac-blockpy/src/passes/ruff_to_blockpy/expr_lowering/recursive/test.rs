@@ -1,16 +1,25 @@
-use crate::block_py::{BlockPyStmtBuilder, CoreBlockPyExprWithAwaitAndYield, StructuredInstr};
+use crate::block_py::{
+    BlockPyStmtBuilder, CoreBlockPyExprWithAwaitAndYield, ModuleNameGen, StructuredInstr,
+};
+use crate::passes::ast_to_ast::context::Context;
 use crate::passes::ruff_to_blockpy::expr_lowering::lower_expr_into_with_setup;
 use crate::py_expr;
 use ruff_python_parser::parse_expression;
 
 #[test]
 fn nested_boolop_in_call_argument_emits_setup_via_expr_lowering() {
+    let context = Context::new("", ModuleNameGen::default());
     let mut out = BlockPyStmtBuilder::<CoreBlockPyExprWithAwaitAndYield>::new();
     let mut next_label_id = 0usize;
 
-    let lowered: CoreBlockPyExprWithAwaitAndYield =
-        lower_expr_into_with_setup(py_expr!("f(a and b)"), &mut out, None, &mut next_label_id)
-            .expect("expr lowering should succeed");
+    let lowered: CoreBlockPyExprWithAwaitAndYield = lower_expr_into_with_setup(
+        &context,
+        py_expr!("f(a and b)"),
+        &mut out,
+        None,
+        &mut next_label_id,
+    )
+    .expect("expr lowering should succeed");
 
     let fragment = out.finish();
     assert!(
@@ -26,10 +35,11 @@ fn nested_boolop_in_call_argument_emits_setup_via_expr_lowering() {
 
 #[test]
 fn direct_core_expr_lowering_materializes_make_function_operation() {
+    let context = Context::new("", ModuleNameGen::default());
     let mut out = BlockPyStmtBuilder::<CoreBlockPyExprWithAwaitAndYield>::new();
     let mut next_label_id = 0usize;
 
-    let lowered = lower_expr_into_with_setup(
+    let lowered = lower_expr_into_with_setup(&context,
         py_expr!(
             "__soac__.make_function(7, \"function\", __soac__.tuple_values(), __soac__.tuple_values(), None)"
         ),
@@ -50,6 +60,7 @@ fn direct_core_expr_lowering_materializes_make_function_operation() {
 
 #[test]
 fn direct_core_expr_lowering_materializes_live_operation_helpers() {
+    let context = Context::new("", ModuleNameGen::default());
     for (source, expected) in [
         (
             "__soac__.store_global(_dp_class_ns, \"caught\", value)",
@@ -61,6 +72,7 @@ fn direct_core_expr_lowering_materializes_live_operation_helpers() {
         let mut next_label_id = 0usize;
 
         let lowered = lower_expr_into_with_setup(
+            &context,
             *parse_expression(source).unwrap().into_syntax().body,
             &mut out,
             None,
@@ -7,13 +7,14 @@ use crate::block_py::{
     BlockPyFunction, BlockPyModule, BlockTerm, CallableScopeInfo, FunctionKind, FunctionName,
     FunctionNameGen, Instr, StructuredInstr,
 };
-use crate::namegen::fresh_name;
+use crate::diagnostic::Diagnostic;
 use crate::passes::ast_to_ast::context::Context;
 use crate::passes::CoreBlockPyPassWithAwaitAndYield;
 use crate::ruff_ast_to_string;
 use crate::template::is_simple;
 use crate::{py_expr, py_stmt};
 use ruff_python_ast::{self as ast, Expr, Stmt};
+use ruff_text_size::Ranged;
 use std::collections::HashMap;
 mod bb_shape;
 mod compat;
@@ -44,9 +45,7 @@ pub(crate) use stmt_lowering::{
     build_for_target_assign_body, lower_star_try_stmt_sequence, lower_try_stmt_sequence,
     lower_with_stmt_sequence,
 };
-pub(crate) use stmt_sequences::{
-    lower_expanded_stmt_sequence, lower_stmt_sequence_with_state, lower_stmts_to_blockpy_stmts,
-};
+pub(crate) use stmt_sequences::{lower_expanded_stmt_sequence, lower_stmt_sequence_with_state};
 pub(crate) use try_regions::{
     block_references_label, build_try_plan, finalize_try_regions, lower_try_regions,
     prepare_except_body, prepare_finally_body, TryPlan,
@@ -59,7 +58,7 @@ pub(crate) fn rewrite_ast_to_core_blockpy_module_with_module(
     module: Vec<Stmt>,
     semantic_state: &crate::passes::ast_to_ast::semantic::SemanticAstState,
     module_name_gen: crate::block_py::ModuleNameGen,
-) -> BlockPyModule<CoreBlockPyPassWithAwaitAndYield> {
+) -> Result<BlockPyModule<CoreBlockPyPassWithAwaitAndYield>, Diagnostic> {
     rewrite_ast_to_core_blockpy_module_plan_with_module(
         context,
         module,
@@ -135,7 +134,7 @@ pub(crate) fn build_core_blockpy_callable_def_from_runtime_input(
     end_label: BlockLabel,
     blockpy_kind: FunctionKind,
     scope: &CallableScopeInfo,
-) -> BlockPyFunction<CoreBlockPyPassWithAwaitAndYield> {
+) -> Result<BlockPyFunction<CoreBlockPyPassWithAwaitAndYield>, Diagnostic> {
     let function_id = name_gen.function_id();
     let mut blocks = Vec::new();
     let entry_label =
@@ -145,7 +144,7 @@ pub(crate) fn build_core_blockpy_callable_def_from_runtime_input(
             RegionTargets::new(end_label.clone(), None),
             &mut blocks,
             &name_gen,
-        );
+        )?;
     move_entry_block_to_front(&mut blocks, entry_label.clone());
     for block in &blocks {
         assert_blockpy_block_normalized(block);
@@ -173,7 +172,7 @@ pub(crate) fn build_core_blockpy_callable_def_from_runtime_input(
     if matches!(blockpy_kind, FunctionKind::Function) {
         rewrite_current_exception_in_core_blocks_with_await_and_yield(&mut blocks[..]);
     }
-    BlockPyFunction {
+    Ok(BlockPyFunction {
         function_id,
         name_gen,
         names,
@@ -183,7 +182,7 @@ pub(crate) fn build_core_blockpy_callable_def_from_runtime_input(
         doc,
         storage_layout: None,
         scope: scope.clone(),
-    }
+    })
 }
 
 #[derive(Clone)]
@@ -235,8 +234,11 @@ impl RegionTargets {
     }
 }
 
-fn assign_delete_error(message: &str, stmt: &Stmt) -> String {
-    format!("{message}\nstmt:\n{}", ruff_ast_to_string(stmt).trim_end())
+fn assign_delete_error(message: &str, stmt: &Stmt) -> Diagnostic {
+    Diagnostic::unsupported(message, stmt.range()).with_note(format!(
+        "while lowering:\n{}",
+        ruff_ast_to_string(stmt).trim_end()
+    ))
 }
 
 #[cfg(test)]
@@ -3,6 +3,7 @@ use crate::block_py::{
     BindingKind, BlockPyFunction, BlockPyModule, BlockPyPass, CallableScopeInfo, CellBindingKind,
     FunctionKind, FunctionNameGen, ModuleNameGen,
 };
+use crate::diagnostic::Diagnostic;
 use crate::passes::ast_to_ast::body::{split_docstring, Suite};
 use crate::passes::ast_to_ast::context::Context;
 use crate::passes::ast_to_ast::rewrite_stmt;
@@ -28,14 +29,19 @@ struct BlockPyModuleRewriter<'a, P: BlockPyPass> {
     module_name_gen: ModuleNameGen,
     function_scope_stack: Vec<FunctionScopeFrame>,
     callable_defs: Vec<BlockPyFunction<P>>,
-    lower_function_to_blockpy: fn(
-        &Context,
-        &ast::StmtFunctionDef,
-        &CallableScopeInfo,
-        FunctionNameGen,
-    ) -> BlockPyFunction<P>,
+    lower_function_to_blockpy: LowerFunctionToBlockPy<P>,
+    /// First diagnostic raised while lowering a callable; the rest of the
+    /// walk is skipped once it is set.
+    error: Option<Diagnostic>,
 }
 
+type LowerFunctionToBlockPy<P> = fn(
+    &Context,
+    &ast::StmtFunctionDef,
+    &CallableScopeInfo,
+    FunctionNameGen,
+) -> Result<BlockPyFunction<P>, Diagnostic>;
+
 #[derive(Default)]
 struct YieldFamilyDetector {
     found: bool,
@@ -46,7 +52,7 @@ pub(crate) fn rewrite_ast_to_core_blockpy_module_plan_with_module(
     mut module: Suite,
     semantic_state: &SemanticAstState,
     module_name_gen: ModuleNameGen,
-) -> BlockPyModule<CoreBlockPyPassWithAwaitAndYield> {
+) -> Result<BlockPyModule<CoreBlockPyPassWithAwaitAndYield>, Diagnostic> {
     crate::passes::ast_to_ast::simplify::flatten(&mut module);
     let mut rewriter = BlockPyModuleRewriter {
         context,
@@ -55,19 +61,23 @@ pub(crate) fn rewrite_ast_to_core_blockpy_module_plan_with_module(
         function_scope_stack: Vec::new(),
         callable_defs: Vec::new(),
         lower_function_to_blockpy: try_lower_function_to_core_blockpy_bundle,
+        error: None,
     };
     let module_init =
         BlockPyModuleRewriter::<CoreBlockPyPassWithAwaitAndYield>::root_module_init_stmt(
             &mut module,
         );
     rewriter.lower_root_function_def(module_init);
-    BlockPyModule {
+    if let Some(error) = rewriter.error {
+        return Err(error);
+    }
+    Ok(BlockPyModule {
         module_name_gen: rewriter.module_name_gen,
         global_names: Vec::new(),
         callable_defs: rewriter.callable_defs,
         module_constants: Vec::new(),
         counter_defs: Vec::new(),
-    }
+    })
 }
 
 impl Transformer for YieldFamilyDetector {
@@ -110,7 +120,7 @@ fn try_lower_function_to_core_blockpy_bundle(
     func: &ast::StmtFunctionDef,
     callable_scope: &CallableScopeInfo,
     name_gen: FunctionNameGen,
-) -> BlockPyFunction<CoreBlockPyPassWithAwaitAndYield> {
+) -> Result<BlockPyFunction<CoreBlockPyPassWithAwaitAndYield>, Diagnostic> {
     let (docstring, lowered_input_body) = split_docstring(&func.body);
     let lowered_input_body = lowered_input_body.to_vec();
     let (param_spec, _param_defaults) = collect_param_spec_and_defaults(&func.parameters);
@@ -164,15 +174,10 @@ fn rewrite_function_def_stmt_via_blockpy_with_pass<P: BlockPyPass>(
     function_hoisted: Vec<Stmt>,
     module_name_gen: &mut ModuleNameGen,
     callable_defs: &mut Vec<BlockPyFunction<P>>,
-    lower_function_to_blockpy: fn(
-        &Context,
-        &ast::StmtFunctionDef,
-        &CallableScopeInfo,
-        FunctionNameGen,
-    ) -> BlockPyFunction<P>,
-) -> Vec<Stmt> {
+    lower_function_to_blockpy: LowerFunctionToBlockPy<P>,
+) -> Result<Vec<Stmt>, Diagnostic> {
     let name_gen = module_name_gen.next_function_name_gen();
-    let lowered_plan = lower_function_to_blockpy(context, func, callable_scope, name_gen);
+    let lowered_plan = lower_function_to_blockpy(context, func, callable_scope, name_gen)?;
     let bind_name = lowered_plan.names.bind_name.clone();
     let (_, param_defaults) = collect_param_spec_and_defaults(&func.parameters);
     let decorated = build_lowered_function_instantiation_expr(
@@ -191,10 +196,10 @@ fn rewrite_function_def_stmt_via_blockpy_with_pass<P: BlockPyPass>(
     if bind_name.starts_with("_dp_class_ns_") || bind_name.starts_with("_dp_define_class_") {
         let mut replacement = function_hoisted;
         replacement.extend(binding_stmt);
-        replacement
+        Ok(replacement)
     } else {
         parent_hoisted.extend(function_hoisted);
-        binding_stmt
+        Ok(binding_stmt)
     }
 }
 
@@ -234,12 +239,18 @@ def {func:id}():
             }
         }
 
-        let lowered_plan = (self.lower_function_to_blockpy)(
+        let lowered_plan = match (self.lower_function_to_blockpy)(
             self.context,
             &func_def,
             &state.callable_scope,
             self.module_name_gen.next_function_name_gen(),
-        );
+        ) {
+            Ok(lowered_plan) => lowered_plan,
+            Err(error) => {
+                self.fail(error);
+                return py_expr!("None");
+            }
+        };
         let (_, param_defaults) = collect_param_spec_and_defaults(&func_def.parameters);
         let lowered_expr = build_lowered_function_instantiation_expr(
             lowered_plan.function_id,
@@ -314,13 +325,15 @@ def {func:id}():
             state.hoisted_to_parent.is_empty(),
             "root _dp_module_init should not produce hoisted statements"
         );
-        let lowered_plan = (self.lower_function_to_blockpy)(
+        match (self.lower_function_to_blockpy)(
             self.context,
             func,
             &state.callable_scope,
             self.module_name_gen.next_function_name_gen(),
-        );
-        self.callable_defs.push(lowered_plan);
+        ) {
+            Ok(lowered_plan) => self.callable_defs.push(lowered_plan),
+            Err(error) => self.fail(error),
+        }
     }
 
     fn rewrite_visited_function_def(
@@ -333,7 +346,7 @@ def {func:id}():
             .last_mut()
             .expect("nested function rewrite should always have a parent hoist buffer");
         let parent_hoisted = &mut parent_frame.hoisted_to_parent;
-        rewrite_function_def_stmt_via_blockpy_with_pass(
+        match rewrite_function_def_stmt_via_blockpy_with_pass(
             self.context,
             parent_hoisted,
             func,
@@ -342,12 +355,27 @@ def {func:id}():
             &mut self.module_name_gen,
             &mut self.callable_defs,
             self.lower_function_to_blockpy,
-        )
+        ) {
+            Ok(replacement) => replacement,
+            Err(error) => {
+                self.fail(error);
+                Vec::new()
+            }
+        }
+    }
+
+    fn fail(&mut self, error: Diagnostic) {
+        if self.error.is_none() {
+            self.error = Some(error);
+        }
     }
 }
 
 impl<P: BlockPyPass> Transformer for BlockPyModuleRewriter<'_, P> {
     fn visit_body(&mut self, body: &mut Suite) {
+        if self.error.is_some() {
+            return;
+        }
         let mut rewritten = Vec::with_capacity(body.len());
         for stmt in std::mem::take(body) {
             let mut stmt = stmt;
@@ -42,6 +42,7 @@ fn lower_test_module_plan(
         &semantic_state,
         ModuleNameGen::new(0),
     )
+    .unwrap()
 }
 
 #[test]
@@ -124,7 +125,7 @@ fn recursive_local_function_bindings_are_cell_owned_in_parent_scope() {
         "        return recurse()\n",
         "    return recurse\n",
     );
-    let context = Context::new(source);
+    let context = Context::new(source, ModuleNameGen::default());
     let mut module = parse_module(source).unwrap().into_syntax().body;
     let semantic_state = SemanticAstState::from_ruff(&mut module);
     let Stmt::FunctionDef(outer) = &mut module[0] else {
@@ -150,6 +151,7 @@ fn recursive_local_function_bindings_are_cell_owned_in_parent_scope() {
         }],
         callable_defs: Vec::new(),
         lower_function_to_blockpy: try_lower_function_to_core_blockpy_bundle,
+        error: None,
     };
     let nested_stmt = &mut outer
         .body
@@ -443,7 +445,7 @@ fn lowering_recursive_local_function_with_finally_keeps_plain_binding_before_nam
         "    finally:\n",
         "        sys.setrecursionlimit(original_limit)\n",
     );
-    let context = Context::new(source);
+    let context = Context::new(source, ModuleNameGen::default());
     let module = parse_module(source).unwrap().into_syntax().body;
     let blockpy = lower_test_module_plan(&context, module);
     let exercise = blockpy
@@ -487,7 +489,7 @@ fn lowering_recursive_local_function_treats_recurse_cell_as_local_state() {
         "    finally:\n",
         "        sys.setrecursionlimit(original_limit)\n",
     );
-    let context = Context::new(source);
+    let context = Context::new(source, ModuleNameGen::default());
     let module = parse_module(source).unwrap().into_syntax().body;
     let blockpy = lower_test_module_plan(&context, module);
     let exercise = blockpy
@@ -523,7 +525,7 @@ fn lowering_recursive_local_function_finally_return_preserves_liveins() {
         "    finally:\n",
         "        sys.setrecursionlimit(original_limit)\n",
     );
-    let context = Context::new(source);
+    let context = Context::new(source, ModuleNameGen::default());
     let module = parse_module(source).unwrap().into_syntax().body;
     let blockpy = lower_test_module_plan(&context, module);
     let exercise = blockpy
@@ -561,7 +563,7 @@ fn lowering_nonlocal_inner_captures_outer_cell() {
         "        return x\n",
         "    return inner()\n",
     );
-    let context = Context::new(source);
+    let context = Context::new(source, ModuleNameGen::default());
     let module = parse_module(source).unwrap().into_syntax().body;
     let blockpy = lower_test_module_plan(&context, module);
     let inner = blockpy
@@ -25,8 +25,8 @@ if __debug__:
 }
 
 impl StmtLowerer for ast::StmtAssert {
-    fn simplify_ast(self, _context: &Context) -> Vec<Stmt> {
-        stmts_from_rewrite(rewrite_assert_stmt(self))
+    fn simplify_ast(self, _context: &Context) -> Result<Vec<Stmt>, Diagnostic> {
+        Ok(stmts_from_rewrite(rewrite_assert_stmt(self)))
     }
 
     fn to_blockpy<E>(
@@ -35,7 +35,7 @@ impl StmtLowerer for ast::StmtAssert {
         out: &mut BlockPyStmtBuilder<E>,
         loop_ctx: Option<&LoopContext>,
         next_label_id: &mut usize,
-    ) -> Result<(), String>
+    ) -> Result<(), Diagnostic>
     where
         E: RuffToBlockPyExpr,
     {
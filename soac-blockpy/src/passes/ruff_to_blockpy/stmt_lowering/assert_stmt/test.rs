@@ -1,6 +1,6 @@
 use super::super::{simplify_stmt_ast_once_for_blockpy, BlockPyStmtBuilder};
 use super::*;
-use crate::block_py::CoreBlockPyExprWithAwaitAndYield;
+use crate::block_py::{CoreBlockPyExprWithAwaitAndYield, ModuleNameGen};
 use crate::passes::ast_to_ast::context::Context;
 
 #[test]
@@ -10,8 +10,9 @@ fn stmt_assert_simplify_ast_desugars_before_blockpy_lowering() {
         panic!("expected assert stmt");
     };
 
-    let context = Context::new("");
-    let simplified = simplify_stmt_ast_once_for_blockpy(&context, Stmt::Assert(assert_stmt));
+    let context = Context::new("", ModuleNameGen::default());
+    let simplified =
+        simplify_stmt_ast_once_for_blockpy(&context, Stmt::Assert(assert_stmt)).unwrap();
 
     assert!(!matches!(simplified.as_slice(), [Stmt::Assert(_)]));
 }
@@ -22,7 +23,7 @@ fn stmt_assert_to_blockpy_uses_trait_owned_simplification_path() {
     let Stmt::Assert(assert_stmt) = stmt else {
         panic!("expected assert stmt");
     };
-    let context = Context::new("");
+    let context = Context::new("", ModuleNameGen::default());
     let mut out = BlockPyStmtBuilder::<CoreBlockPyExprWithAwaitAndYield>::new();
     let mut next_label_id = 0usize;
 
@@ -37,14 +37,16 @@ fn delete_temp<E: RuffToBlockPyExpr>(out: &mut BlockPyStmtBuilder<E>, name: Stri
 }
 
 pub(super) fn lower_target_object_with_setup<E: RuffToBlockPyExpr>(
+    context: &Context,
     target_value: Expr,
     out: &mut BlockPyStmtBuilder<E>,
     loop_ctx: Option<&LoopContext>,
     next_label_id: &mut usize,
-) -> Result<E, String> {
+) -> Result<E, Diagnostic> {
     let meta = target_value.meta();
     let maybe_name = target_value.as_name_expr().map(|name| name.id.to_string());
     let value = crate::passes::ruff_to_blockpy::expr_lowering::lower_expr_into_with_setup(
+        context,
         target_value,
         out,
         loop_ctx,
@@ -63,29 +65,17 @@ fn lower_assignment_target_into<E>(
     out: &mut BlockPyStmtBuilder<E>,
     loop_ctx: Option<&LoopContext>,
     next_label_id: &mut usize,
-) -> Result<(), String>
+) -> Result<(), Diagnostic>
 where
     E: RuffToBlockPyExpr,
 {
     match target {
-        Expr::Tuple(tuple) => lower_unpack_target_into(
-            context,
-            tuple.elts,
-            rhs,
-            out,
-            loop_ctx,
-            next_label_id,
-            UnpackTargetKind::Tuple,
-        ),
-        Expr::List(list) => lower_unpack_target_into(
-            context,
-            list.elts,
-            rhs,
-            out,
-            loop_ctx,
-            next_label_id,
-            UnpackTargetKind::List,
-        ),
+        Expr::Tuple(tuple) => {
+            lower_unpack_target_into(context, tuple.elts, rhs, out, loop_ctx, next_label_id)
+        }
+        Expr::List(list) => {
+            lower_unpack_target_into(context, list.elts, rhs, out, loop_ctx, next_label_id)
+        }
         Expr::Subscript(ast::ExprSubscript {
             value,
             slice,
@@ -94,10 +84,11 @@ where
             ..
         }) => {
             let object_value =
-                lower_target_object_with_setup(*value, out, loop_ctx, next_label_id)?;
+                lower_target_object_with_setup(context, *value, out, loop_ctx, next_label_id)?;
             let object_temp = bind_temp(out, context.fresh("assign_obj"), object_value);
             let index_value =
                 crate::passes::ruff_to_blockpy::expr_lowering::lower_expr_into_with_setup(
+                    context,
                     *slice,
                     out,
                     loop_ctx,
@@ -121,7 +112,7 @@ where
             ..
         }) => {
             let object_value =
-                lower_target_object_with_setup(*value, out, loop_ctx, next_label_id)?;
+                lower_target_object_with_setup(context, *value, out, loop_ctx, next_label_id)?;
             let object_temp = bind_temp(out, context.fresh("assign_obj"), object_value);
             out.push_stmt(StructuredInstr::Expr(E::set_attr(
                 node_index,
@@ -139,17 +130,13 @@ where
             ));
             Ok(())
         }
-        other => Err(format!(
-            "unsupported assignment target reached BlockPy conversion: {other:?}"
+        other => Err(Diagnostic::unsupported(
+            "unsupported assignment target reached BlockPy conversion",
+            other.range(),
         )),
     }
 }
 
-enum UnpackTargetKind {
-    Tuple,
-    List,
-}
-
 fn lower_unpack_target_into<E>(
     context: &Context,
     elts: Vec<Expr>,
@@ -157,81 +144,47 @@ fn lower_unpack_target_into<E>(
     out: &mut BlockPyStmtBuilder<E>,
     loop_ctx: Option<&LoopContext>,
     next_label_id: &mut usize,
-    kind: UnpackTargetKind,
-) -> Result<(), String>
+) -> Result<(), Diagnostic>
 where
     E: RuffToBlockPyExpr,
 {
-    let mut starred_seen = false;
-    let mut spec_elts = Vec::new();
-    for elt in &elts {
-        match elt {
-            Expr::Starred(_) => {
-                if starred_seen {
-                    return Err("unsupported starred assignment target".to_string());
-                }
-                starred_seen = true;
-                spec_elts.push(py_expr!("False"));
+    let mut starred = None;
+    for (index, elt) in elts.iter().enumerate() {
+        if matches!(elt, Expr::Starred(_)) {
+            if starred.is_some() {
+                return Err(Diagnostic::syntax(
+                    "multiple starred expressions in assignment",
+                    elt.range(),
+                ));
             }
-            _ => spec_elts.push(py_expr!("True")),
+            starred = Some(index);
         }
     }
 
-    let spec_expr = make_tuple(spec_elts);
-    let unpack_meta = spec_expr.meta();
     let unpacked_name = context.fresh("unpack");
-    let unpacked_value = E::helper_call(
-        unpack_meta.node_index,
-        unpack_meta.range,
-        "unpack",
-        vec![value, E::from(spec_expr)],
+    let unpacked_value = E::unpack(
+        Default::default(),
+        Default::default(),
+        value,
+        elts.len(),
+        starred,
     );
     let unpacked_temp = bind_temp(out, unpacked_name.clone(), unpacked_value);
 
     for (index, elt) in elts.into_iter().enumerate() {
         let index_expr = E::from(py_expr!("{index:literal}", index = index as i64));
-        match elt {
-            Expr::Starred(ast::ExprStarred { value, .. }) => {
-                let item_expr = E::get_item(
-                    Default::default(),
-                    Default::default(),
-                    unpacked_temp.clone(),
-                    index_expr,
-                );
-                let collection_expr = match kind {
-                    UnpackTargetKind::Tuple | UnpackTargetKind::List => E::helper_call(
-                        Default::default(),
-                        Default::default(),
-                        "list",
-                        vec![item_expr],
-                    ),
-                };
-                lower_assignment_target_into(
-                    context,
-                    *value,
-                    collection_expr,
-                    out,
-                    loop_ctx,
-                    next_label_id,
-                )?;
-            }
-            other => {
-                let item_expr = E::get_item(
-                    Default::default(),
-                    Default::default(),
-                    unpacked_temp.clone(),
-                    index_expr,
-                );
-                lower_assignment_target_into(
-                    context,
-                    other,
-                    item_expr,
-                    out,
-                    loop_ctx,
-                    next_label_id,
-                )?;
-            }
-        }
+        let item_expr = E::get_item(
+            Default::default(),
+            Default::default(),
+            unpacked_temp.clone(),
+            index_expr,
+        );
+        // The starred slot of an `Unpack` result is already a fresh list.
+        let target = match elt {
+            Expr::Starred(ast::ExprStarred { value, .. }) => *value,
+            other => other,
+        };
+        lower_assignment_target_into(context, target, item_expr, out, loop_ctx, next_label_id)?;
     }
 
     delete_temp(out, unpacked_name);
@@ -239,13 +192,36 @@ where
     Ok(())
 }
 
+fn sequence_elts(expr: &Expr) -> Option<&[Expr]> {
+    let elts = match expr {
+        Expr::Tuple(tuple) => &tuple.elts,
+        Expr::List(list) => &list.elts,
+        _ => return None,
+    };
+    (!elts.iter().any(|elt| matches!(elt, Expr::Starred(_)))).then_some(elts.as_slice())
+}
+
+// `a, b = b, a` pairs each target with its value directly, so no tuple is
+// built only to be unpacked again.
+fn parallel_assignment_pairs<'a>(
+    targets: &'a [Expr],
+    value: &'a Expr,
+) -> Option<(&'a [Expr], &'a [Expr])> {
+    let [target] = targets else {
+        return None;
+    };
+    let target_elts = sequence_elts(target)?;
+    let value_elts = sequence_elts(value)?;
+    (target_elts.len() == value_elts.len()).then_some((target_elts, value_elts))
+}
+
 fn should_bind_assignment_value(targets: &[Expr]) -> bool {
     targets.len() > 1 || !matches!(targets, [Expr::Name(_)])
 }
 
 impl StmtLowerer for ast::StmtAssign {
-    fn simplify_ast(self, _context: &Context) -> Vec<Stmt> {
-        single_stmt(self)
+    fn simplify_ast(self, _context: &Context) -> Result<Vec<Stmt>, Diagnostic> {
+        Ok(single_stmt(self))
     }
 
     fn to_blockpy<E>(
@@ -254,11 +230,31 @@ impl StmtLowerer for ast::StmtAssign {
         out: &mut BlockPyStmtBuilder<E>,
         loop_ctx: Option<&LoopContext>,
         next_label_id: &mut usize,
-    ) -> Result<(), String>
+    ) -> Result<(), Diagnostic>
     where
         E: RuffToBlockPyExpr,
     {
+        if let Some((targets, values)) = parallel_assignment_pairs(&self.targets, &self.value) {
+            let mut value_temps = Vec::with_capacity(values.len());
+            for value in values {
+                let value =
+                    crate::passes::ruff_to_blockpy::expr_lowering::lower_expr_into_with_setup(
+                        context,
+                        value.clone(),
+                        out,
+                        loop_ctx,
+                        next_label_id,
+                    )?;
+                value_temps.push(bind_temp(out, context.fresh("assign_value"), value));
+            }
+            for (target, value) in targets.iter().cloned().zip(value_temps) {
+                lower_assignment_target_into(context, target, value, out, loop_ctx, next_label_id)?;
+            }
+            return Ok(());
+        }
+
         let mut value = crate::passes::ruff_to_blockpy::expr_lowering::lower_expr_into_with_setup(
+            context,
             (*self.value).clone(),
             out,
             loop_ctx,
@@ -301,12 +297,13 @@ pub(super) fn rewrite_assignment_target<F>(
     rhs: Expr,
     out: &mut Vec<Stmt>,
     next_temp: &mut F,
-) where
+) -> Result<(), Diagnostic>
+where
     F: FnMut(&str) -> String,
 {
     match target {
-        Expr::Tuple(tuple) => rewrite_unpack_target(tuple.elts, rhs, out, next_temp),
-        Expr::List(list) => rewrite_unpack_target(list.elts, rhs, out, next_temp),
+        Expr::Tuple(tuple) => rewrite_unpack_target(tuple.elts, rhs, out, next_temp)?,
+        Expr::List(list) => rewrite_unpack_target(list.elts, rhs, out, next_temp)?,
         Expr::Subscript(ast::ExprSubscript { value, slice, .. }) => {
             out.push(py_stmt!(
                 "{obj:expr}[{key:expr}] = {rhs:expr}",
@@ -331,12 +328,21 @@ pub(super) fn rewrite_assignment_target<F>(
             ));
         }
         other => {
-            panic!("unsupported assignment target in Ruff AST -> BlockPy lowering: {other:?}");
+            return Err(Diagnostic::unsupported(
+                "unsupported assignment target in Ruff AST -> BlockPy lowering",
+                other.range(),
+            ));
         }
     }
+    Ok(())
 }
 
-fn rewrite_unpack_target<F>(elts: Vec<Expr>, value: Expr, out: &mut Vec<Stmt>, next_temp: &mut F)
+fn rewrite_unpack_target<F>(
+    elts: Vec<Expr>,
+    value: Expr,
+    out: &mut Vec<Stmt>,
+    next_temp: &mut F,
+) -> Result<(), Diagnostic>
 where
     F: FnMut(&str) -> String,
 {
@@ -349,7 +355,10 @@ where
         match elt {
             Expr::Starred(_) => {
                 if starred_seen {
-                    panic!("unsupported starred with-target assignment");
+                    return Err(Diagnostic::syntax(
+                        "multiple starred expressions in assignment",
+                        elt.range(),
+                    ));
                 }
                 starred_seen = true;
                 spec_elts.push(py_expr!("False"));
@@ -372,13 +381,13 @@ where
                 rewrite_assignment_target(
                     *value,
                     py_expr!(
-                        "__soac__.list({tmp:expr}[{idx:literal}])",
+                        "{tmp:expr}[{idx:literal}]",
                         tmp = unpacked_tmp.clone(),
                         idx = idx as i64,
                     ),
                     out,
                     next_temp,
-                );
+                )?;
             }
             other => {
                 rewrite_assignment_target(
@@ -390,12 +399,13 @@ where
                     ),
                     out,
                     next_temp,
-                );
+                )?;
             }
         }
     }
 
     out.push(py_stmt!("del {tmp:id}", tmp = unpacked_name.as_str()));
+    Ok(())
 }
 
 pub(crate) fn build_for_target_assign_body<F>(
@@ -403,16 +413,16 @@ pub(crate) fn build_for_target_assign_body<F>(
     rhs: Expr,
     tmp_name: &str,
     next_temp: &mut F,
-) -> Vec<Stmt>
+) -> Result<Vec<Stmt>, Diagnostic>
 where
     F: FnMut(&str) -> String,
 {
     let mut out = Vec::new();
     let tmp_expr = py_expr!("{tmp:id}", tmp = tmp_name);
     out.push(py_stmt!("{tmp:id} = {rhs:expr}", tmp = tmp_name, rhs = rhs));
-    rewrite_assignment_target(target.clone(), tmp_expr, &mut out, next_temp);
+    rewrite_assignment_target(target.clone(), tmp_expr, &mut out, next_temp)?;
     out.push(py_stmt!("del {tmp:id}", tmp = tmp_name));
-    out
+    Ok(out)
 }
 
 #[cfg(test)]
@@ -1,6 +1,6 @@
 use super::super::BlockPyStmtBuilder;
 use super::*;
-use crate::block_py::{CoreBlockPyExprWithAwaitAndYield, StructuredInstr};
+use crate::block_py::{CoreBlockPyExprWithAwaitAndYield, ModuleNameGen, StructuredInstr};
 use crate::passes::ast_to_ast::context::Context;
 
 #[test]
@@ -9,7 +9,7 @@ fn stmt_assign_to_blockpy_emits_direct_core_setitem() {
     let Stmt::Assign(assign_stmt) = stmt else {
         panic!("expected assign stmt");
     };
-    let context = Context::new("");
+    let context = Context::new("", ModuleNameGen::default());
     let mut out = BlockPyStmtBuilder::<CoreBlockPyExprWithAwaitAndYield>::new();
     let mut next_label_id = 0usize;
 
@@ -39,7 +39,7 @@ fn rewrite_assignment_target_unpack_uses_native_subscript_ast() {
         name
     };
 
-    rewrite_assignment_target(target, rhs, &mut out, &mut next_temp);
+    rewrite_assignment_target(target, rhs, &mut out, &mut next_temp).unwrap();
 
     let rendered = out
         .iter()
@@ -49,7 +49,8 @@ fn rewrite_assignment_target_unpack_uses_native_subscript_ast() {
 
     assert!(!rendered.contains("__dp_getitem("), "{rendered}");
     assert!(rendered.contains("[0]"), "{rendered}");
-    assert!(rendered.contains("__soac__.list("), "{rendered}");
+    assert!(rendered.contains("__soac__.unpack("), "{rendered}");
+    assert!(!rendered.contains("__soac__.list("), "{rendered}");
 }
 
 #[test]
@@ -70,7 +71,7 @@ fn rewrite_assignment_target_uses_native_store_targets() {
             name
         };
 
-        rewrite_assignment_target(target, rhs, &mut out, &mut next_temp);
+        rewrite_assignment_target(target, rhs, &mut out, &mut next_temp).unwrap();
 
         let rendered = out
             .iter()
@@ -83,3 +84,44 @@ fn rewrite_assignment_target_uses_native_store_targets() {
         assert!(rendered.contains(expected), "{rendered}");
     }
 }
+
+#[test]
+fn stmt_assign_to_blockpy_lowers_sequence_targets_to_unpack() {
+    let stmt = py_stmt!("a, *b, c = value");
+    let Stmt::Assign(assign_stmt) = stmt else {
+        panic!("expected assign stmt");
+    };
+    let context = Context::new("", ModuleNameGen::default());
+    let mut out = BlockPyStmtBuilder::<CoreBlockPyExprWithAwaitAndYield>::new();
+    let mut next_label_id = 0usize;
+
+    assign_stmt
+        .to_blockpy(&context, &mut out, None, &mut next_label_id)
+        .expect("assign lowering should succeed");
+
+    let rendered = format!("{:?}", out.finish());
+
+    assert!(rendered.contains("Unpack("), "{rendered}");
+    assert!(rendered.contains("3, Some(1))"), "{rendered}");
+    assert_eq!(rendered.matches("GetItem(").count(), 3, "{rendered}");
+}
+
+#[test]
+fn stmt_assign_to_blockpy_pairs_swap_targets_without_a_tuple() {
+    let stmt = py_stmt!("a, b = b, a");
+    let Stmt::Assign(assign_stmt) = stmt else {
+        panic!("expected assign stmt");
+    };
+    let context = Context::new("", ModuleNameGen::default());
+    let mut out = BlockPyStmtBuilder::<CoreBlockPyExprWithAwaitAndYield>::new();
+    let mut next_label_id = 0usize;
+
+    assign_stmt
+        .to_blockpy(&context, &mut out, None, &mut next_label_id)
+        .expect("assign lowering should succeed");
+
+    let rendered = format!("{:?}", out.finish());
+
+    assert!(!rendered.contains("Unpack("), "{rendered}");
+    assert!(!rendered.contains("BuildTuple("), "{rendered}");
+}
@@ -3,8 +3,8 @@ use super::*;
 use crate::block_py::{HasMeta, Meta, Store, StructuredInstr, WithMeta};
 
 impl StmtLowerer for ast::StmtAugAssign {
-    fn simplify_ast(self, _context: &Context) -> Vec<Stmt> {
-        single_stmt(self)
+    fn simplify_ast(self, _context: &Context) -> Result<Vec<Stmt>, Diagnostic> {
+        Ok(single_stmt(self))
     }
 
     fn to_blockpy<E>(
@@ -13,7 +13,7 @@ impl StmtLowerer for ast::StmtAugAssign {
         out: &mut BlockPyStmtBuilder<E>,
         loop_ctx: Option<&LoopContext>,
         next_label_id: &mut usize,
-    ) -> Result<(), String>
+    ) -> Result<(), Diagnostic>
     where
         E: RuffToBlockPyExpr,
     {
@@ -31,6 +31,7 @@ impl StmtLowerer for ast::StmtAugAssign {
                 );
                 let rhs =
                     crate::passes::ruff_to_blockpy::expr_lowering::lower_expr_into_with_setup(
+                        context,
                         (*self.value).clone(),
                         out,
                         loop_ctx,
@@ -55,6 +56,7 @@ impl StmtLowerer for ast::StmtAugAssign {
             }
             Expr::Attribute(target) => {
                 let object_value = lower_target_object_with_setup(
+                    context,
                     (*target.value).clone(),
                     out,
                     loop_ctx,
@@ -73,6 +75,7 @@ impl StmtLowerer for ast::StmtAugAssign {
                 );
                 let rhs =
                     crate::passes::ruff_to_blockpy::expr_lowering::lower_expr_into_with_setup(
+                        context,
                         (*self.value).clone(),
                         out,
                         loop_ctx,
@@ -96,6 +99,7 @@ impl StmtLowerer for ast::StmtAugAssign {
             }
             Expr::Subscript(target) => {
                 let object_value = lower_target_object_with_setup(
+                    context,
                     (*target.value).clone(),
                     out,
                     loop_ctx,
@@ -104,6 +108,7 @@ impl StmtLowerer for ast::StmtAugAssign {
                 let object_temp = bind_temp(out, context.fresh("augassign_obj"), object_value);
                 let index_value =
                     crate::passes::ruff_to_blockpy::expr_lowering::lower_expr_into_with_setup(
+                        context,
                         (*target.slice).clone(),
                         out,
                         loop_ctx,
@@ -122,6 +127,7 @@ impl StmtLowerer for ast::StmtAugAssign {
                 );
                 let rhs =
                     crate::passes::ruff_to_blockpy::expr_lowering::lower_expr_into_with_setup(
+                        context,
                         (*self.value).clone(),
                         out,
                         loop_ctx,
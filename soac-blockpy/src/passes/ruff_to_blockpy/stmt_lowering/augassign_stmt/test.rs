@@ -1,6 +1,6 @@
 use super::super::BlockPyStmtBuilder;
 use super::*;
-use crate::block_py::{CoreBlockPyExprWithAwaitAndYield, StructuredInstr};
+use crate::block_py::{CoreBlockPyExprWithAwaitAndYield, ModuleNameGen, StructuredInstr};
 use crate::passes::ast_to_ast::context::Context;
 
 #[test]
@@ -10,8 +10,9 @@ fn stmt_augassign_simplify_ast_keeps_stmt_for_direct_lowering() {
         panic!("expected augassign stmt");
     };
 
-    let context = Context::new("");
-    let simplified = simplify_stmt_ast_once_for_blockpy(&context, Stmt::AugAssign(aug_stmt));
+    let context = Context::new("", ModuleNameGen::default());
+    let simplified =
+        simplify_stmt_ast_once_for_blockpy(&context, Stmt::AugAssign(aug_stmt)).unwrap();
 
     assert!(matches!(simplified.as_slice(), [Stmt::AugAssign(_)]));
 }
@@ -22,7 +23,7 @@ fn stmt_augassign_to_blockpy_emits_direct_core_operations() {
     let Stmt::AugAssign(aug_stmt) = stmt else {
         panic!("expected augassign stmt");
     };
-    let context = Context::new("");
+    let context = Context::new("", ModuleNameGen::default());
     let mut out = BlockPyStmtBuilder::<CoreBlockPyExprWithAwaitAndYield>::new();
     let mut next_label_id = 0usize;
 
@@ -48,7 +49,7 @@ fn stmt_pow_augassign_to_blockpy_uses_inplace_pow() {
     let Stmt::AugAssign(aug_stmt) = stmt else {
         panic!("expected augassign stmt");
     };
-    let context = Context::new("");
+    let context = Context::new("", ModuleNameGen::default());
     let mut out = BlockPyStmtBuilder::<CoreBlockPyExprWithAwaitAndYield>::new();
     let mut next_label_id = 0usize;
 
@@ -8,7 +8,7 @@ fn lower_delete_target_into<E>(
     out: &mut BlockPyStmtBuilder<E>,
     loop_ctx: Option<&LoopContext>,
     next_label_id: &mut usize,
-) -> Result<(), String>
+) -> Result<(), Diagnostic>
 where
     E: RuffToBlockPyExpr,
 {
@@ -21,10 +21,11 @@ where
             ..
         }) => {
             let object_value =
-                lower_target_object_with_setup(*value, out, loop_ctx, next_label_id)?;
+                lower_target_object_with_setup(context, *value, out, loop_ctx, next_label_id)?;
             let object_temp = bind_temp(out, context.fresh("delete_obj"), object_value);
             let index_value =
                 crate::passes::ruff_to_blockpy::expr_lowering::lower_expr_into_with_setup(
+                    context,
                     *slice,
                     out,
                     loop_ctx,
@@ -47,7 +48,7 @@ where
             ..
         }) => {
             let object_value =
-                lower_target_object_with_setup(*value, out, loop_ctx, next_label_id)?;
+                lower_target_object_with_setup(context, *value, out, loop_ctx, next_label_id)?;
             let object_temp = bind_temp(out, context.fresh("delete_obj"), object_value);
             let attr_expr: E = Expr::from(py_expr!("{attr:literal}", attr = attr.as_str())).into();
             out.push_stmt(StructuredInstr::Expr(E::helper_call(
@@ -68,8 +69,8 @@ where
         other => Err(assign_delete_error(
             "unsupported delete target reached BlockPy conversion",
             &Stmt::Delete(ast::StmtDelete {
+                range: other.range(),
                 targets: vec![other].into(),
-                range: Default::default(),
                 node_index: Default::default(),
             }),
         )),
@@ -77,8 +78,8 @@ where
 }
 
 impl StmtLowerer for ast::StmtDelete {
-    fn simplify_ast(self, _context: &Context) -> Vec<Stmt> {
-        single_stmt(self)
+    fn simplify_ast(self, _context: &Context) -> Result<Vec<Stmt>, Diagnostic> {
+        Ok(single_stmt(self))
     }
 
     fn to_blockpy<E>(
@@ -87,7 +88,7 @@ impl StmtLowerer for ast::StmtDelete {
         out: &mut BlockPyStmtBuilder<E>,
         loop_ctx: Option<&LoopContext>,
         next_label_id: &mut usize,
-    ) -> Result<(), String>
+    ) -> Result<(), Diagnostic>
     where
         E: RuffToBlockPyExpr,
     {
@@ -1,6 +1,6 @@
 use super::super::BlockPyStmtBuilder;
 use super::*;
-use crate::block_py::{CoreBlockPyExprWithAwaitAndYield, StructuredInstr};
+use crate::block_py::{CoreBlockPyExprWithAwaitAndYield, ModuleNameGen, StructuredInstr};
 use crate::passes::ast_to_ast::context::Context;
 
 #[test]
@@ -9,7 +9,7 @@ fn stmt_delete_to_blockpy_emits_direct_core_delitem() {
     let Stmt::Delete(delete_stmt) = stmt else {
         panic!("expected delete stmt");
     };
-    let context = Context::new("");
+    let context = Context::new("", ModuleNameGen::default());
     let mut out = BlockPyStmtBuilder::<CoreBlockPyExprWithAwaitAndYield>::new();
     let mut next_label_id = 0usize;
 
@@ -23,8 +23,8 @@ pub(crate) fn rewrite_raise_stmt(mut raise: ast::StmtRaise) -> Rewrite {
 }
 
 impl StmtLowerer for ast::StmtGlobal {
-    fn simplify_ast(self, _context: &Context) -> Vec<Stmt> {
-        single_stmt(Stmt::Global(self))
+    fn simplify_ast(self, _context: &Context) -> Result<Vec<Stmt>, Diagnostic> {
+        Ok(single_stmt(Stmt::Global(self)))
     }
 
     fn to_blockpy<E>(
@@ -33,7 +33,7 @@ impl StmtLowerer for ast::StmtGlobal {
         _out: &mut BlockPyStmtBuilder<E>,
         _loop_ctx: Option<&LoopContext>,
         _next_label_id: &mut usize,
-    ) -> Result<(), String>
+    ) -> Result<(), Diagnostic>
     where
         E: RuffToBlockPyExpr,
     {
@@ -42,8 +42,8 @@ impl StmtLowerer for ast::StmtGlobal {
 }
 
 impl StmtLowerer for ast::StmtNonlocal {
-    fn simplify_ast(self, _context: &Context) -> Vec<Stmt> {
-        single_stmt(Stmt::Nonlocal(self))
+    fn simplify_ast(self, _context: &Context) -> Result<Vec<Stmt>, Diagnostic> {
+        Ok(single_stmt(Stmt::Nonlocal(self)))
     }
 
     fn to_blockpy<E>(
@@ -52,7 +52,7 @@ impl StmtLowerer for ast::StmtNonlocal {
         _out: &mut BlockPyStmtBuilder<E>,
         _loop_ctx: Option<&LoopContext>,
         _next_label_id: &mut usize,
-    ) -> Result<(), String>
+    ) -> Result<(), Diagnostic>
     where
         E: RuffToBlockPyExpr,
     {
@@ -61,8 +61,8 @@ impl StmtLowerer for ast::StmtNonlocal {
 }
 
 impl StmtLowerer for ast::StmtPass {
-    fn simplify_ast(self, _context: &Context) -> Vec<Stmt> {
-        single_stmt(Stmt::Pass(self))
+    fn simplify_ast(self, _context: &Context) -> Result<Vec<Stmt>, Diagnostic> {
+        Ok(single_stmt(Stmt::Pass(self)))
     }
 
     fn to_blockpy<E>(
@@ -71,7 +71,7 @@ impl StmtLowerer for ast::StmtPass {
         _out: &mut BlockPyStmtBuilder<E>,
         _loop_ctx: Option<&LoopContext>,
         _next_label_id: &mut usize,
-    ) -> Result<(), String>
+    ) -> Result<(), Diagnostic>
     where
         E: RuffToBlockPyExpr,
     {
@@ -80,21 +80,22 @@ impl StmtLowerer for ast::StmtPass {
 }
 
 impl StmtLowerer for ast::StmtExpr {
-    fn simplify_ast(self, _context: &Context) -> Vec<Stmt> {
-        single_stmt(Stmt::Expr(self))
+    fn simplify_ast(self, _context: &Context) -> Result<Vec<Stmt>, Diagnostic> {
+        Ok(single_stmt(Stmt::Expr(self)))
     }
 
     fn to_blockpy<E>(
         &self,
-        _context: &Context,
+        context: &Context,
         out: &mut BlockPyStmtBuilder<E>,
         loop_ctx: Option<&LoopContext>,
         next_label_id: &mut usize,
-    ) -> Result<(), String>
+    ) -> Result<(), Diagnostic>
     where
         E: RuffToBlockPyExpr,
     {
         let value = crate::passes::ruff_to_blockpy::expr_lowering::lower_expr_into_with_setup(
+            context,
             (*self.value).clone(),
             out,
             loop_ctx,
@@ -106,8 +107,8 @@ impl StmtLowerer for ast::StmtExpr {
 }
 
 impl StmtLowerer for ast::StmtBreak {
-    fn simplify_ast(self, _context: &Context) -> Vec<Stmt> {
-        single_stmt(Stmt::Break(self))
+    fn simplify_ast(self, _context: &Context) -> Result<Vec<Stmt>, Diagnostic> {
+        Ok(single_stmt(Stmt::Break(self)))
     }
 
     fn to_blockpy<E>(
@@ -116,7 +117,7 @@ impl StmtLowerer for ast::StmtBreak {
         out: &mut BlockPyStmtBuilder<E>,
         loop_ctx: Option<&LoopContext>,
         _next_label_id: &mut usize,
-    ) -> Result<(), String>
+    ) -> Result<(), Diagnostic>
     where
         E: RuffToBlockPyExpr,
     {
@@ -132,8 +133,8 @@ impl StmtLowerer for ast::StmtBreak {
 }
 
 impl StmtLowerer for ast::StmtContinue {
-    fn simplify_ast(self, _context: &Context) -> Vec<Stmt> {
-        single_stmt(Stmt::Continue(self))
+    fn simplify_ast(self, _context: &Context) -> Result<Vec<Stmt>, Diagnostic> {
+        Ok(single_stmt(Stmt::Continue(self)))
     }
 
     fn to_blockpy<E>(
@@ -142,7 +143,7 @@ impl StmtLowerer for ast::StmtContinue {
         out: &mut BlockPyStmtBuilder<E>,
         loop_ctx: Option<&LoopContext>,
         _next_label_id: &mut usize,
-    ) -> Result<(), String>
+    ) -> Result<(), Diagnostic>
     where
         E: RuffToBlockPyExpr,
     {
@@ -158,23 +159,24 @@ impl StmtLowerer for ast::StmtContinue {
 }
 
 impl StmtLowerer for ast::StmtReturn {
-    fn simplify_ast(self, _context: &Context) -> Vec<Stmt> {
-        single_stmt(Stmt::Return(self))
+    fn simplify_ast(self, _context: &Context) -> Result<Vec<Stmt>, Diagnostic> {
+        Ok(single_stmt(Stmt::Return(self)))
     }
 
     fn to_blockpy<E>(
         &self,
-        _context: &Context,
+        context: &Context,
         out: &mut BlockPyStmtBuilder<E>,
         loop_ctx: Option<&LoopContext>,
         next_label_id: &mut usize,
-    ) -> Result<(), String>
+    ) -> Result<(), Diagnostic>
     where
         E: RuffToBlockPyExpr,
     {
         let value = match self.value.as_ref() {
             Some(value) => {
                 crate::passes::ruff_to_blockpy::expr_lowering::lower_expr_into_with_setup(
+                    context,
                     (**value).clone(),
                     out,
                     loop_ctx,
@@ -189,17 +191,17 @@ impl StmtLowerer for ast::StmtReturn {
 }
 
 impl StmtLowerer for ast::StmtRaise {
-    fn simplify_ast(self, _context: &Context) -> Vec<Stmt> {
-        stmts_from_rewrite(rewrite_raise_stmt(self))
+    fn simplify_ast(self, _context: &Context) -> Result<Vec<Stmt>, Diagnostic> {
+        Ok(stmts_from_rewrite(rewrite_raise_stmt(self)))
     }
 
     fn to_blockpy<E>(
         &self,
-        _context: &Context,
+        context: &Context,
         out: &mut BlockPyStmtBuilder<E>,
         loop_ctx: Option<&LoopContext>,
         next_label_id: &mut usize,
-    ) -> Result<(), String>
+    ) -> Result<(), Diagnostic>
     where
         E: RuffToBlockPyExpr,
     {
@@ -209,6 +211,7 @@ impl StmtLowerer for ast::StmtRaise {
         let exc = match self.exc.as_ref() {
             Some(exc) => Some(
                 crate::passes::ruff_to_blockpy::expr_lowering::lower_expr_into_with_setup(
+                    context,
                     (**exc).clone(),
                     out,
                     loop_ctx,
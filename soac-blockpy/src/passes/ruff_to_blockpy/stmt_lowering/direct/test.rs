@@ -1,6 +1,6 @@
 use super::super::{simplify_stmt_ast_once_for_blockpy, BlockPyStmtBuilder};
 use super::*;
-use crate::block_py::CoreBlockPyExprWithAwaitAndYield;
+use crate::block_py::{CoreBlockPyExprWithAwaitAndYield, ModuleNameGen};
 use crate::passes::ast_to_ast::context::Context;
 
 #[test]
@@ -10,8 +10,8 @@ fn stmt_raise_simplify_ast_desugars_raise_from_before_blockpy_lowering() {
         panic!("expected raise stmt");
     };
 
-    let context = Context::new("");
-    let simplified = simplify_stmt_ast_once_for_blockpy(&context, Stmt::Raise(raise_stmt));
+    let context = Context::new("", ModuleNameGen::default());
+    let simplified = simplify_stmt_ast_once_for_blockpy(&context, Stmt::Raise(raise_stmt)).unwrap();
 
     assert!(!matches!(
         simplified.as_slice(),
@@ -25,7 +25,7 @@ fn stmt_raise_to_blockpy_handles_bare_raise_directly() {
     let Stmt::Raise(raise_stmt) = stmt else {
         panic!("expected raise stmt");
     };
-    let context = Context::new("");
+    let context = Context::new("", ModuleNameGen::default());
     let mut out = BlockPyStmtBuilder::<CoreBlockPyExprWithAwaitAndYield>::new();
     let mut next_label_id = 0usize;
 
@@ -43,7 +43,7 @@ fn stmt_expr_to_blockpy_emits_setup_for_named_exprs() {
     let Stmt::Expr(expr_stmt) = stmt else {
         panic!("expected expr stmt");
     };
-    let context = Context::new("");
+    let context = Context::new("", ModuleNameGen::default());
     let mut out = BlockPyStmtBuilder::<CoreBlockPyExprWithAwaitAndYield>::new();
     let mut next_label_id = 0usize;
 
@@ -67,7 +67,7 @@ fn stmt_return_to_blockpy_emits_setup_for_if_exprs() {
     let Stmt::Return(return_stmt) = stmt else {
         panic!("expected return stmt");
     };
-    let context = Context::new("");
+    let context = Context::new("", ModuleNameGen::default());
     let mut out = BlockPyStmtBuilder::<CoreBlockPyExprWithAwaitAndYield>::new();
     let mut next_label_id = 0usize;
 
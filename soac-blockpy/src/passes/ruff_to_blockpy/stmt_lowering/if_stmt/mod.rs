@@ -58,8 +58,8 @@ pub(crate) fn expand_if_chain(mut if_stmt: ast::StmtIf) -> Rewrite {
 }
 
 impl StmtLowerer for ast::StmtIf {
-    fn simplify_ast(self, _context: &Context) -> Vec<Stmt> {
-        stmts_from_rewrite(expand_if_chain(self))
+    fn simplify_ast(self, _context: &Context) -> Result<Vec<Stmt>, Diagnostic> {
+        Ok(stmts_from_rewrite(expand_if_chain(self)))
     }
 
     fn to_blockpy<E>(
@@ -68,11 +68,11 @@ impl StmtLowerer for ast::StmtIf {
         out: &mut BlockPyStmtBuilder<E>,
         loop_ctx: Option<&LoopContext>,
         next_label_id: &mut usize,
-    ) -> Result<(), String>
+    ) -> Result<(), Diagnostic>
     where
         E: RuffToBlockPyExpr,
     {
-        match simplify_stmt_head_ast_for_blockpy(context, Stmt::If(self.clone())).as_slice() {
+        match simplify_stmt_head_ast_for_blockpy(context, Stmt::If(self.clone()))?.as_slice() {
             [Stmt::If(simplified_if)] => {
                 let body = lower_nested_body_to_stmts_with_expr(
                     context,
@@ -89,6 +89,7 @@ impl StmtLowerer for ast::StmtIf {
                 )?;
                 let test =
                     crate::passes::ruff_to_blockpy::expr_lowering::lower_expr_into_with_setup(
+                        context,
                         (*simplified_if.test).clone(),
                         out,
                         loop_ctx,
@@ -112,7 +113,7 @@ fn lower_nested_body_to_stmts_with_expr<E>(
     body: &Suite,
     loop_ctx: Option<&LoopContext>,
     next_label_id: &mut usize,
-) -> Result<crate::block_py::BlockBuilder<StructuredInstr<E>, BlockTerm<E>>, String>
+) -> Result<crate::block_py::BlockBuilder<StructuredInstr<E>, BlockTerm<E>>, Diagnostic>
 where
     E: RuffToBlockPyExpr,
 {
@@ -129,7 +130,7 @@ fn lower_orelse_to_stmts_with_expr<E>(
     stmt: &Stmt,
     loop_ctx: Option<&LoopContext>,
     next_label_id: &mut usize,
-) -> Result<crate::block_py::BlockBuilder<StructuredInstr<E>, BlockTerm<E>>, String>
+) -> Result<crate::block_py::BlockBuilder<StructuredInstr<E>, BlockTerm<E>>, Diagnostic>
 where
     E: RuffToBlockPyExpr,
 {
@@ -141,10 +142,10 @@ where
         [clause] if clause.test.is_none() => {
             lower_nested_body_to_stmts_with_expr(context, &clause.body, loop_ctx, next_label_id)
         }
-        _ => Err(format!(
+        _ => Err(Diagnostic::internal(format!(
             "`elif` chain reached Ruff AST -> BlockPy conversion\nstmt:\n{}",
             ruff_ast_to_string(stmt).trim_end()
-        )),
+        ))),
     }
 }
 
@@ -1,6 +1,6 @@
 use super::super::{simplify_stmt_ast_once_for_blockpy, BlockPyStmtBuilder};
 use super::*;
-use crate::block_py::CoreBlockPyExprWithAwaitAndYield;
+use crate::block_py::{CoreBlockPyExprWithAwaitAndYield, ModuleNameGen};
 use crate::passes::ast_to_ast::context::Context;
 
 #[test]
@@ -10,8 +10,8 @@ fn stmt_if_simplify_ast_expands_elif_chain_before_blockpy_lowering() {
         panic!("expected if stmt");
     };
 
-    let context = Context::new("");
-    let simplified = simplify_stmt_ast_once_for_blockpy(&context, Stmt::If(if_stmt));
+    let context = Context::new("", ModuleNameGen::default());
+    let simplified = simplify_stmt_ast_once_for_blockpy(&context, Stmt::If(if_stmt)).unwrap();
     let [Stmt::If(simplified_if)] = simplified.as_slice() else {
         panic!("if simplification should remain an if stmt");
     };
@@ -28,7 +28,7 @@ fn stmt_if_to_blockpy_uses_trait_owned_simplification_path_for_elif() {
     let Stmt::If(if_stmt) = stmt else {
         panic!("expected if stmt");
     };
-    let context = Context::new("");
+    let context = Context::new("", ModuleNameGen::default());
     let mut out = BlockPyStmtBuilder::<CoreBlockPyExprWithAwaitAndYield>::new();
     let mut next_label_id = 0usize;
 
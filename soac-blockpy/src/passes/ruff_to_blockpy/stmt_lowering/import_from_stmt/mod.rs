@@ -1,9 +1,9 @@
 use super::*;
 
 impl StmtLowerer for ast::StmtImportFrom {
-    fn simplify_ast(self, context: &Context) -> Vec<Stmt> {
-        stmts_from_rewrite(crate::passes::ast_to_ast::rewrite_import::rewrite_from(
-            context, self,
+    fn simplify_ast(self, context: &Context) -> Result<Vec<Stmt>, Diagnostic> {
+        Ok(stmts_from_rewrite(
+            crate::passes::ast_to_ast::rewrite_import::rewrite_from(context, self),
         ))
     }
 
@@ -13,7 +13,7 @@ impl StmtLowerer for ast::StmtImportFrom {
         out: &mut BlockPyStmtBuilder<E>,
         loop_ctx: Option<&LoopContext>,
         next_label_id: &mut usize,
-    ) -> Result<(), String>
+    ) -> Result<(), Diagnostic>
     where
         E: RuffToBlockPyExpr,
     {
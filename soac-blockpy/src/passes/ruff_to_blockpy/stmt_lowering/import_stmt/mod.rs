@@ -1,8 +1,10 @@
 use super::*;
 
 impl StmtLowerer for ast::StmtImport {
-    fn simplify_ast(self, _context: &Context) -> Vec<Stmt> {
-        stmts_from_rewrite(crate::passes::ast_to_ast::rewrite_import::rewrite(self))
+    fn simplify_ast(self, _context: &Context) -> Result<Vec<Stmt>, Diagnostic> {
+        Ok(stmts_from_rewrite(
+            crate::passes::ast_to_ast::rewrite_import::rewrite(self),
+        ))
     }
 
     fn to_blockpy<E>(
@@ -11,7 +13,7 @@ impl StmtLowerer for ast::StmtImport {
         out: &mut BlockPyStmtBuilder<E>,
         loop_ctx: Option<&LoopContext>,
         next_label_id: &mut usize,
-    ) -> Result<(), String>
+    ) -> Result<(), Diagnostic>
     where
         E: RuffToBlockPyExpr,
     {
@@ -5,6 +5,7 @@ use ruff_python_ast::{self as ast, name::Name, Expr, Pattern, Stmt};
 use ruff_python_parser::parse_expression;
 use ruff_text_size::TextRange;
 
+use crate::transformer::{walk_expr, Transformer};
 use crate::{py_expr, py_stmt, ruff_ast_to_string};
 
 enum PatternTest {
@@ -18,7 +19,11 @@ fn body_to_vec(body: Suite) -> Vec<Stmt> {
 
 fn fold_exprs(exprs: Vec<Expr>, op: ast::BoolOp) -> Expr {
     if exprs.is_empty() {
-        panic!("Empty expression list");
+        // An empty conjunction always holds and an empty disjunction never does.
+        match op {
+            ast::BoolOp::And => py_expr!("True"),
+            ast::BoolOp::Or => py_expr!("False"),
+        }
     } else {
         Expr::BoolOp(ast::ExprBoolOp {
             range: TextRange::default(),
@@ -40,12 +45,16 @@ fn trace_expr(label: &str, expr: &Expr) {
     if !log_enabled!(Level::Trace) {
         return;
     }
+    trace!("{label}: {}", expr_source(expr));
+}
+
+fn expr_source(expr: &Expr) -> String {
     let stmt = Stmt::Expr(ast::StmtExpr {
         value: Box::new(expr.clone()),
         range: TextRange::default(),
         node_index: ast::AtomicNodeIndex::default(),
     });
-    trace!("{label}: {}", ruff_ast_to_string(&stmt).trim_end());
+    ruff_ast_to_string(&stmt).trim_end().to_string()
 }
 
 fn test_for_pattern(pattern: &Pattern, subject: Expr) -> PatternTest {
@@ -116,232 +125,17 @@ else:
                 assigns,
             }
         }
-        Pattern::MatchSequence(PatternMatchSequence { patterns, .. }) => {
-            let mut tests = vec![
-                py_expr!(
-                    "hasattr({subject:expr}, '__len__')",
-                    subject = subject.clone()
-                ),
-                py_expr!(
-                    "hasattr({subject:expr}, '__getitem__')",
-                    subject = subject.clone()
-                ),
-                py_expr!(
-                    "not isinstance({subject:expr}, (str, bytes, bytearray))",
-                    subject = subject.clone()
-                ),
-            ];
-            let mut assigns = Vec::new();
-            let len_expr = py_expr!("len({subject:expr})", subject = subject.clone());
-
-            if patterns.is_empty() {
-                tests.push(py_expr!(
-                    "{len:expr} == {count:expr}",
-                    len = len_expr.clone(),
-                    count = integer_expr(0)
-                ));
-            } else if let Some(star_index) = patterns
-                .iter()
-                .position(|pattern| matches!(pattern, Pattern::MatchStar(_)))
-            {
-                let before = star_index;
-                let after = patterns.len() - star_index - 1;
-                tests.push(py_expr!(
-                    "{len:expr} >= {count:expr}",
-                    len = len_expr.clone(),
-                    count = integer_expr(before + after)
-                ));
-
-                for (index, pattern) in patterns.iter().enumerate() {
-                    if index < star_index {
-                        let element = py_expr!(
-                            "{subject:expr}[{idx:expr}]",
-                            subject = subject.clone(),
-                            idx = integer_expr(index)
-                        );
-                        match test_for_pattern(pattern, element) {
-                            Test {
-                                expr,
-                                assigns: mut sub_assigns,
-                            } => {
-                                tests.push(expr);
-                                assigns.append(&mut sub_assigns);
-                            }
-                            Wildcard {
-                                assigns: mut sub_assigns,
-                            } => {
-                                assigns.append(&mut sub_assigns);
-                            }
-                        }
-                    } else if index == star_index {
-                        if let Pattern::MatchStar(ast::PatternMatchStar {
-                            name: Some(name), ..
-                        }) = pattern
-                        {
-                            let start_expr = integer_expr(before);
-                            let end_expr = py_expr!(
-                                "{len:expr} - {offset:expr}",
-                                len = len_expr.clone(),
-                                offset = integer_expr(after)
-                            );
-                            let slice_expr = py_expr!(
-                                "{subject:expr}[{start:expr}:{end:expr}]",
-                                subject = subject.clone(),
-                                start = start_expr,
-                                end = end_expr
-                            );
-                            let list_expr =
-                                py_expr!("__soac__.list({value:expr})", value = slice_expr);
-                            assigns.push(py_stmt!(
-                                "{name:id} = {value:expr}",
-                                name = name.as_str(),
-                                value = list_expr
-                            ));
-                        }
-                    } else {
-                        let distance = patterns.len() - index;
-                        let offset_expr = integer_expr(distance);
-                        let index_expr = py_expr!(
-                            "{len:expr} - {offset:expr}",
-                            len = len_expr.clone(),
-                            offset = offset_expr
-                        );
-                        let element = py_expr!(
-                            "{subject:expr}[{idx:expr}]",
-                            subject = subject.clone(),
-                            idx = index_expr
-                        );
-                        match test_for_pattern(pattern, element) {
-                            Test {
-                                expr,
-                                assigns: mut sub_assigns,
-                            } => {
-                                tests.push(expr);
-                                assigns.append(&mut sub_assigns);
-                            }
-                            Wildcard {
-                                assigns: mut sub_assigns,
-                            } => {
-                                assigns.append(&mut sub_assigns);
-                            }
-                        }
-                    }
-                }
-            } else {
-                tests.push(py_expr!(
-                    "{len:expr} == {count:expr}",
-                    len = len_expr.clone(),
-                    count = integer_expr(patterns.len())
-                ));
-                for (index, pattern) in patterns.iter().enumerate() {
-                    let element = py_expr!(
-                        "{subject:expr}[{idx:expr}]",
-                        subject = subject.clone(),
-                        idx = integer_expr(index)
-                    );
-                    match test_for_pattern(pattern, element) {
-                        Test {
-                            expr,
-                            assigns: mut sub_assigns,
-                        } => {
-                            tests.push(expr);
-                            assigns.append(&mut sub_assigns);
-                        }
-                        Wildcard {
-                            assigns: mut sub_assigns,
-                        } => {
-                            assigns.append(&mut sub_assigns);
-                        }
-                    }
-                }
-            }
-
-            let test = fold_exprs(tests, ast::BoolOp::And);
-            Test {
-                expr: test,
-                assigns,
-            }
-        }
-        Pattern::MatchMapping(ast::PatternMatchMapping {
-            keys,
+        Pattern::MatchSequence(PatternMatchSequence { patterns, .. }) => sequence_test(
             patterns,
-            rest,
-            ..
-        }) => {
-            let mut tests = vec![
-                py_expr!("hasattr({subject:expr}, 'keys')", subject = subject.clone()),
-                py_expr!(
-                    "hasattr({subject:expr}, '__getitem__')",
-                    subject = subject.clone()
-                ),
-            ];
-            let mut assigns = Vec::new();
-
-            for (key, pattern) in keys.iter().zip(patterns.iter()) {
-                let contains = py_expr!(
-                    "{key:expr} in {subject:expr}",
-                    key = key.clone(),
-                    subject = subject.clone()
-                );
-                tests.push(contains);
-                let value = py_expr!(
-                    "{subject:expr}[{key:expr}]",
-                    subject = subject.clone(),
-                    key = key.clone()
-                );
-                match test_for_pattern(pattern, value) {
-                    Test {
-                        expr,
-                        assigns: mut sub_assigns,
-                    } => {
-                        tests.push(expr);
-                        assigns.append(&mut sub_assigns);
-                    }
-                    Wildcard {
-                        assigns: mut sub_assigns,
-                    } => {
-                        assigns.append(&mut sub_assigns);
-                    }
-                }
-            }
-
-            if let Some(name) = rest {
-                assigns.push(py_stmt!(
-                    "{name:id} = __soac__.dict({subject:expr})",
-                    name = name.as_str(),
-                    subject = subject.clone()
-                ));
-                for key in keys.iter() {
-                    assigns.push(py_stmt!(
-                        "{name:id}.pop({key:expr}, None)",
-                        name = name.as_str(),
-                        key = key.clone()
-                    ));
-                }
-            }
-
-            let test = fold_exprs(tests, ast::BoolOp::And);
-            Test {
-                expr: test,
-                assigns,
-            }
+            subject.clone(),
+            sequence_checks(&subject),
+            py_expr!("len({subject:expr})", subject = subject.clone()),
+        ),
+        Pattern::MatchMapping(mapping) => {
+            mapping_test(mapping, subject.clone(), mapping_checks(&subject))
         }
         Pattern::MatchStar(ast::PatternMatchStar { name, .. }) => {
-            let tests = vec![
-                py_expr!(
-                    "hasattr({subject:expr}, '__len__')",
-                    subject = subject.clone()
-                ),
-                py_expr!(
-                    "hasattr({subject:expr}, '__getitem__')",
-                    subject = subject.clone()
-                ),
-                py_expr!(
-                    "not isinstance({subject:expr}, (str, bytes, bytearray))",
-                    subject = subject.clone()
-                ),
-            ];
-            let expr = fold_exprs(tests, ast::BoolOp::And);
+            let expr = fold_exprs(sequence_checks(&subject), ast::BoolOp::And);
             let mut assigns = Vec::new();
             if let Some(name) = name {
                 let list_expr =
@@ -457,6 +251,704 @@ else:
     }
 }
 
+/// The checks a sequence pattern makes before looking at its elements.
+fn sequence_checks(subject: &Expr) -> Vec<Expr> {
+    vec![
+        py_expr!(
+            "hasattr({subject:expr}, '__len__')",
+            subject = subject.clone()
+        ),
+        py_expr!(
+            "hasattr({subject:expr}, '__getitem__')",
+            subject = subject.clone()
+        ),
+        py_expr!(
+            "not isinstance({subject:expr}, (str, bytes, bytearray))",
+            subject = subject.clone()
+        ),
+    ]
+}
+
+/// Tests a sequence pattern against `subject`, given the checks that it is a
+/// sequence and an expression for its length.
+fn sequence_test(
+    patterns: &[Pattern],
+    subject: Expr,
+    mut tests: Vec<Expr>,
+    len_expr: Expr,
+) -> PatternTest {
+    use PatternTest::*;
+    let mut assigns = Vec::new();
+    if patterns.is_empty() {
+        tests.push(py_expr!(
+            "{len:expr} == {count:expr}",
+            len = len_expr.clone(),
+            count = integer_expr(0)
+        ));
+    } else if let Some(star_index) = patterns
+        .iter()
+        .position(|pattern| matches!(pattern, Pattern::MatchStar(_)))
+    {
+        let before = star_index;
+        let after = patterns.len() - star_index - 1;
+        tests.push(py_expr!(
+            "{len:expr} >= {count:expr}",
+            len = len_expr.clone(),
+            count = integer_expr(before + after)
+        ));
+
+        for (index, pattern) in patterns.iter().enumerate() {
+            if index < star_index {
+                let element = py_expr!(
+                    "{subject:expr}[{idx:expr}]",
+                    subject = subject.clone(),
+                    idx = integer_expr(index)
+                );
+                match test_for_pattern(pattern, element) {
+                    Test {
+                        expr,
+                        assigns: mut sub_assigns,
+                    } => {
+                        tests.push(expr);
+                        assigns.append(&mut sub_assigns);
+                    }
+                    Wildcard {
+                        assigns: mut sub_assigns,
+                    } => {
+                        assigns.append(&mut sub_assigns);
+                    }
+                }
+            } else if index == star_index {
+                if let Pattern::MatchStar(ast::PatternMatchStar {
+                    name: Some(name), ..
+                }) = pattern
+                {
+                    let start_expr = integer_expr(before);
+                    let end_expr = py_expr!(
+                        "{len:expr} - {offset:expr}",
+                        len = len_expr.clone(),
+                        offset = integer_expr(after)
+                    );
+                    let slice_expr = py_expr!(
+                        "{subject:expr}[{start:expr}:{end:expr}]",
+                        subject = subject.clone(),
+                        start = start_expr,
+                        end = end_expr
+                    );
+                    let list_expr = py_expr!("__soac__.list({value:expr})", value = slice_expr);
+                    assigns.push(py_stmt!(
+                        "{name:id} = {value:expr}",
+                        name = name.as_str(),
+                        value = list_expr
+                    ));
+                }
+            } else {
+                let distance = patterns.len() - index;
+                let offset_expr = integer_expr(distance);
+                let index_expr = py_expr!(
+                    "{len:expr} - {offset:expr}",
+                    len = len_expr.clone(),
+                    offset = offset_expr
+                );
+                let element = py_expr!(
+                    "{subject:expr}[{idx:expr}]",
+                    subject = subject.clone(),
+                    idx = index_expr
+                );
+                match test_for_pattern(pattern, element) {
+                    Test {
+                        expr,
+                        assigns: mut sub_assigns,
+                    } => {
+                        tests.push(expr);
+                        assigns.append(&mut sub_assigns);
+                    }
+                    Wildcard {
+                        assigns: mut sub_assigns,
+                    } => {
+                        assigns.append(&mut sub_assigns);
+                    }
+                }
+            }
+        }
+    } else {
+        tests.push(py_expr!(
+            "{len:expr} == {count:expr}",
+            len = len_expr.clone(),
+            count = integer_expr(patterns.len())
+        ));
+        for (index, pattern) in patterns.iter().enumerate() {
+            let element = py_expr!(
+                "{subject:expr}[{idx:expr}]",
+                subject = subject.clone(),
+                idx = integer_expr(index)
+            );
+            match test_for_pattern(pattern, element) {
+                Test {
+                    expr,
+                    assigns: mut sub_assigns,
+                } => {
+                    tests.push(expr);
+                    assigns.append(&mut sub_assigns);
+                }
+                Wildcard {
+                    assigns: mut sub_assigns,
+                } => {
+                    assigns.append(&mut sub_assigns);
+                }
+            }
+        }
+    }
+
+    let test = fold_exprs(tests, ast::BoolOp::And);
+    Test {
+        expr: test,
+        assigns,
+    }
+}
+
+/// The checks a mapping pattern makes before looking up its keys.
+fn mapping_checks(subject: &Expr) -> Vec<Expr> {
+    vec![
+        py_expr!("hasattr({subject:expr}, 'keys')", subject = subject.clone()),
+        py_expr!(
+            "hasattr({subject:expr}, '__getitem__')",
+            subject = subject.clone()
+        ),
+    ]
+}
+
+/// Tests a mapping pattern against `subject`, given the checks that it is a
+/// mapping.
+fn mapping_test(
+    mapping: &ast::PatternMatchMapping,
+    subject: Expr,
+    mut tests: Vec<Expr>,
+) -> PatternTest {
+    use PatternTest::*;
+    let ast::PatternMatchMapping {
+        keys,
+        patterns,
+        rest,
+        ..
+    } = mapping;
+    let mut assigns = Vec::new();
+
+    for (key, pattern) in keys.iter().zip(patterns.iter()) {
+        let contains = py_expr!(
+            "{key:expr} in {subject:expr}",
+            key = key.clone(),
+            subject = subject.clone()
+        );
+        tests.push(contains);
+        let value = py_expr!(
+            "{subject:expr}[{key:expr}]",
+            subject = subject.clone(),
+            key = key.clone()
+        );
+        match test_for_pattern(pattern, value) {
+            Test {
+                expr,
+                assigns: mut sub_assigns,
+            } => {
+                tests.push(expr);
+                assigns.append(&mut sub_assigns);
+            }
+            Wildcard {
+                assigns: mut sub_assigns,
+            } => {
+                assigns.append(&mut sub_assigns);
+            }
+        }
+    }
+
+    if let Some(name) = rest {
+        assigns.push(py_stmt!(
+            "{name:id} = __soac__.dict({subject:expr})",
+            name = name.as_str(),
+            subject = subject.clone()
+        ));
+        for key in keys.iter() {
+            assigns.push(py_stmt!(
+                "{name:id}.pop({key:expr}, None)",
+                name = name.as_str(),
+                key = key.clone()
+            ));
+        }
+    }
+
+    let test = fold_exprs(tests, ast::BoolOp::And);
+    Test {
+        expr: test,
+        assigns,
+    }
+}
+
+/// Consecutive int or str literal cases needed before they dispatch through a
+/// dict lookup instead of comparing the subject against each literal in turn.
+const MIN_LITERAL_DISPATCH_CASES: usize = 4;
+
+#[derive(PartialEq, Eq)]
+enum LiteralKey {
+    Int(u64),
+    Str(String),
+}
+
+fn literal_key(expr: &Expr) -> Option<LiteralKey> {
+    match expr {
+        Expr::NumberLiteral(ast::ExprNumberLiteral {
+            value: ast::Number::Int(value),
+            ..
+        }) => value.to_string().parse().ok().map(LiteralKey::Int),
+        Expr::StringLiteral(ast::ExprStringLiteral { value, .. }) => {
+            Some(LiteralKey::Str(value.to_str().to_string()))
+        }
+        _ => None,
+    }
+}
+
+/// The int and str literals `pattern` compares the subject against, if that
+/// is all it does.
+fn literal_alternatives(pattern: &Pattern) -> Option<Vec<(LiteralKey, Expr)>> {
+    match pattern {
+        Pattern::MatchValue(ast::PatternMatchValue { value, .. }) => {
+            Some(vec![(literal_key(value)?, *value.clone())])
+        }
+        Pattern::MatchOr(ast::PatternMatchOr { patterns, .. }) => {
+            let mut alternatives = Vec::new();
+            for pattern in patterns {
+                alternatives.extend(literal_alternatives(pattern)?);
+            }
+            Some(alternatives)
+        }
+        _ => None,
+    }
+}
+
+/// A run of literal cases sharing one `match_literal_case` lookup. Each
+/// distinct literal maps to its position in `table`, so a literal repeated
+/// in a later case still selects the first case that lists it.
+struct LiteralRun {
+    table: Vec<(LiteralKey, Expr)>,
+    case: Option<Expr>,
+}
+
+/// Which entries of its run's table select a literal case.
+#[derive(Clone)]
+struct LiteralCase {
+    run: usize,
+    ids: Vec<usize>,
+}
+
+/// Groups consecutive literal cases into runs long enough to dispatch, and
+/// places each case in its run, if any.
+fn plan_literal_runs(cases: &[ast::MatchCase]) -> (Vec<LiteralRun>, Vec<Option<LiteralCase>>) {
+    let mut alternatives = cases
+        .iter()
+        .map(|case| literal_alternatives(&case.pattern))
+        .collect::<Vec<_>>();
+    let mut runs = Vec::new();
+    let mut literal_cases = Vec::new();
+    let mut index = 0;
+    while index < alternatives.len() {
+        let end = alternatives[index..]
+            .iter()
+            .position(Option::is_none)
+            .map_or(alternatives.len(), |offset| index + offset);
+        if end - index < MIN_LITERAL_DISPATCH_CASES {
+            let next = end.max(index + 1);
+            literal_cases.resize(next, None);
+            index = next;
+            continue;
+        }
+        let mut table: Vec<(LiteralKey, Expr)> = Vec::new();
+        for case_alternatives in &mut alternatives[index..end] {
+            let mut ids = Vec::new();
+            for (key, literal) in case_alternatives.take().expect("run cases are literal") {
+                let id = match table.iter().position(|(existing, _)| *existing == key) {
+                    Some(id) => id,
+                    None => {
+                        table.push((key, literal));
+                        table.len() - 1
+                    }
+                };
+                if !ids.contains(&id) {
+                    ids.push(id);
+                }
+            }
+            literal_cases.push(Some(LiteralCase {
+                run: runs.len(),
+                ids,
+            }));
+        }
+        runs.push(LiteralRun { table, case: None });
+        index = end;
+    }
+    (runs, literal_cases)
+}
+
+/// The name a dotted class reference like `mod.Point` starts from.
+fn dotted_root(expr: &Expr) -> Option<&Name> {
+    match expr {
+        Expr::Name(ast::ExprName { id, .. }) => Some(id),
+        Expr::Attribute(ast::ExprAttribute { value, .. }) => dotted_root(value),
+        _ => None,
+    }
+}
+
+fn collect_capture_names(pattern: &Pattern, names: &mut Vec<Name>) {
+    match pattern {
+        Pattern::MatchValue(_) | Pattern::MatchSingleton(_) => {}
+        Pattern::MatchSequence(ast::PatternMatchSequence { patterns, .. })
+        | Pattern::MatchOr(ast::PatternMatchOr { patterns, .. }) => {
+            for pattern in patterns {
+                collect_capture_names(pattern, names);
+            }
+        }
+        Pattern::MatchMapping(ast::PatternMatchMapping { patterns, rest, .. }) => {
+            for pattern in patterns {
+                collect_capture_names(pattern, names);
+            }
+            names.extend(rest.iter().map(|name| name.id.clone()));
+        }
+        Pattern::MatchClass(ast::PatternMatchClass { arguments, .. }) => {
+            for pattern in &arguments.patterns {
+                collect_capture_names(pattern, names);
+            }
+            for keyword in &arguments.keywords {
+                collect_capture_names(&keyword.pattern, names);
+            }
+        }
+        Pattern::MatchStar(ast::PatternMatchStar { name, .. }) => {
+            names.extend(name.iter().map(|name| name.id.clone()));
+        }
+        Pattern::MatchAs(ast::PatternMatchAs { pattern, name, .. }) => {
+            if let Some(pattern) = pattern {
+                collect_capture_names(pattern, names);
+            }
+            names.extend(name.iter().map(|name| name.id.clone()));
+        }
+    }
+}
+
+#[derive(Default)]
+struct NamedExprFinder {
+    found: bool,
+}
+
+impl Transformer for NamedExprFinder {
+    fn visit_expr(&mut self, expr: &mut Expr) {
+        if matches!(expr, Expr::Named(_)) {
+            self.found = true;
+        }
+        walk_expr(self, expr);
+    }
+}
+
+/// Subject tests shared between the top-level patterns of one `match`.
+///
+/// Each shared value is computed by a statement in the prelude of the first
+/// case that needs it. Later cases only run from the `else` branch of that
+/// case's test, so the value is assigned by the time they read it, and it is
+/// only computed once every earlier case has failed.
+struct SharedSubjectTests<'a> {
+    context: &'a Context,
+    subject: Expr,
+    sequence: Option<(Expr, Expr)>,
+    mapping: Option<Expr>,
+    class_attrs: Vec<((String, usize, Vec<String>), Expr)>,
+    /// Reusing a class pattern's attributes skips evaluating the class
+    /// expression again, which is only safe while nothing in the `match`
+    /// can rebind it: no guard assigns and no pattern captures its name.
+    guards_assign: bool,
+    captures: Vec<Name>,
+    literal_runs: Vec<LiteralRun>,
+    literal_cases: Vec<Option<LiteralCase>>,
+}
+
+impl<'a> SharedSubjectTests<'a> {
+    fn new(context: &'a Context, subject: Expr, cases: &[ast::MatchCase]) -> Self {
+        let mut captures = Vec::new();
+        let mut finder = NamedExprFinder::default();
+        for case in cases {
+            collect_capture_names(&case.pattern, &mut captures);
+            if let Some(guard) = &case.guard {
+                finder.visit_expr(&mut guard.as_ref().clone());
+            }
+        }
+        let (literal_runs, literal_cases) = plan_literal_runs(cases);
+        Self {
+            context,
+            subject,
+            sequence: None,
+            mapping: None,
+            class_attrs: Vec::new(),
+            guards_assign: finder.found,
+            captures,
+            literal_runs,
+            literal_cases,
+        }
+    }
+
+    fn fresh_assign(&self, prefix: &str, value: Expr, prelude: &mut Vec<Stmt>) -> Expr {
+        let name = self.context.fresh(prefix);
+        prelude.push(py_stmt!(
+            "{name:id} = {value:expr}",
+            name = name.as_str(),
+            value = value,
+        ));
+        py_expr!("{name:id}", name = name.as_str())
+    }
+
+    fn test_case(
+        &mut self,
+        index: usize,
+        pattern: &Pattern,
+        prelude: &mut Vec<Stmt>,
+    ) -> PatternTest {
+        match self.literal_cases[index].clone() {
+            Some(LiteralCase { run, ids }) => self.literal_test(run, &ids, pattern, prelude),
+            None => self.test_pattern(pattern, prelude),
+        }
+    }
+
+    fn test_pattern(&mut self, pattern: &Pattern, prelude: &mut Vec<Stmt>) -> PatternTest {
+        use PatternTest::*;
+        match pattern {
+            Pattern::MatchSequence(ast::PatternMatchSequence { patterns, .. }) => {
+                let (is_sequence, len) = self.sequence(prelude);
+                sequence_test(patterns, self.subject.clone(), vec![is_sequence], len)
+            }
+            Pattern::MatchMapping(mapping) => {
+                let is_mapping = self.mapping(prelude);
+                mapping_test(mapping, self.subject.clone(), vec![is_mapping])
+            }
+            Pattern::MatchClass(class) => {
+                let attrs = self.class_attrs(class, prelude);
+                class_attrs_test(class, attrs)
+            }
+            Pattern::MatchAs(ast::PatternMatchAs {
+                pattern: Some(pattern),
+                name,
+                ..
+            }) => {
+                let test = self.test_pattern(pattern, prelude);
+                let Some(name) = name else {
+                    return test;
+                };
+                let assign = py_stmt!(
+                    "{name:id} = {subject:expr}",
+                    name = name.as_str(),
+                    subject = self.subject.clone(),
+                );
+                match test {
+                    Test { expr, mut assigns } => {
+                        assigns.push(assign);
+                        Test { expr, assigns }
+                    }
+                    Wildcard { mut assigns } => {
+                        assigns.push(assign);
+                        Wildcard { assigns }
+                    }
+                }
+            }
+            _ => test_for_pattern(pattern, self.subject.clone()),
+        }
+    }
+
+    fn sequence(&mut self, prelude: &mut Vec<Stmt>) -> (Expr, Expr) {
+        if let Some(sequence) = &self.sequence {
+            return sequence.clone();
+        }
+        let checks = fold_exprs(sequence_checks(&self.subject), ast::BoolOp::And);
+        let is_sequence = self.fresh_assign("match_seq", checks, prelude);
+        let len = self.fresh_assign(
+            "match_len",
+            py_expr!(
+                "len({subject:expr}) if {is_sequence:expr} else -1",
+                subject = self.subject.clone(),
+                is_sequence = is_sequence.clone(),
+            ),
+            prelude,
+        );
+        self.sequence = Some((is_sequence, len));
+        self.sequence
+            .clone()
+            .expect("sequence tests were just assigned")
+    }
+
+    fn mapping(&mut self, prelude: &mut Vec<Stmt>) -> Expr {
+        if let Some(mapping) = &self.mapping {
+            return mapping.clone();
+        }
+        let checks = fold_exprs(mapping_checks(&self.subject), ast::BoolOp::And);
+        let is_mapping = self.fresh_assign("match_map", checks, prelude);
+        self.mapping = Some(is_mapping.clone());
+        is_mapping
+    }
+
+    fn class_attrs(&mut self, class: &ast::PatternMatchClass, prelude: &mut Vec<Stmt>) -> Expr {
+        let key = (
+            expr_source(&class.cls),
+            class.arguments.patterns.len(),
+            class
+                .arguments
+                .keywords
+                .iter()
+                .map(|keyword| keyword.attr.to_string())
+                .collect::<Vec<_>>(),
+        );
+        let shareable = !self.guards_assign
+            && dotted_root(&class.cls).is_some_and(|root| !self.captures.contains(root));
+        if shareable {
+            if let Some((_, attrs)) = self
+                .class_attrs
+                .iter()
+                .find(|(existing, _)| *existing == key)
+            {
+                return attrs.clone();
+            }
+        }
+        let attrs = self.fresh_assign(
+            "match_attrs",
+            match_class_expr(class, self.subject.clone()),
+            prelude,
+        );
+        if shareable {
+            self.class_attrs.push((key, attrs.clone()));
+        }
+        attrs
+    }
+
+    fn literal_test(
+        &mut self,
+        run: usize,
+        ids: &[usize],
+        pattern: &Pattern,
+        prelude: &mut Vec<Stmt>,
+    ) -> PatternTest {
+        let case = match &self.literal_runs[run].case {
+            Some(case) => case.clone(),
+            None => {
+                let table = Expr::Dict(ast::ExprDict {
+                    node_index: ast::AtomicNodeIndex::default(),
+                    range: TextRange::default(),
+                    items: self.literal_runs[run]
+                        .table
+                        .iter()
+                        .enumerate()
+                        .map(|(id, (_, literal))| ast::DictItem {
+                            key: Some(literal.clone()),
+                            value: integer_expr(id),
+                        })
+                        .collect(),
+                });
+                let case = self.fresh_assign(
+                    "match_case",
+                    py_expr!(
+                        "__soac__.match_literal_case({subject:expr}, {table:expr})",
+                        subject = self.subject.clone(),
+                        table = table,
+                    ),
+                    prelude,
+                );
+                self.literal_runs[run].case = Some(case.clone());
+                case
+            }
+        };
+        let dispatched = ids
+            .iter()
+            .map(|id| {
+                py_expr!(
+                    "{case:expr} == {id:expr}",
+                    case = case.clone(),
+                    id = integer_expr(*id),
+                )
+            })
+            .collect::<Vec<_>>();
+        // Subjects that are not exactly int or str may override `__eq__`, so
+        // they keep comparing against each literal in case order.
+        let PatternTest::Test {
+            expr: compared,
+            assigns,
+        } = test_for_pattern(pattern, self.subject.clone())
+        else {
+            unreachable!("literal patterns always test the subject");
+        };
+        PatternTest::Test {
+            expr: py_expr!(
+                "{dispatched:expr} if {case:expr} is not None else {compared:expr}",
+                dispatched = fold_exprs(dispatched, ast::BoolOp::Or),
+                case = case,
+                compared = compared,
+            ),
+            assigns,
+        }
+    }
+}
+
+/// Fetches everything a class pattern matches its sub-patterns against,
+/// positional attributes first, or `None` when the subject is not an
+/// instance or lacks one of them.
+fn match_class_expr(class: &ast::PatternMatchClass, subject: Expr) -> Expr {
+    let Expr::Call(mut call) = py_expr!(
+        "__soac__.match_class({cls:expr}, {subject:expr}, {total:expr})",
+        cls = *class.cls.clone(),
+        subject = subject,
+        total = integer_expr(class.arguments.patterns.len()),
+    ) else {
+        panic!("expected call expression for __soac__.match_class");
+    };
+    let mut args = call.arguments.args.into_vec();
+    args.extend(
+        class
+            .arguments
+            .keywords
+            .iter()
+            .map(|keyword| py_expr!("{name:literal}", name = keyword.attr.as_str())),
+    );
+    call.arguments.args = args.into();
+    Expr::Call(call)
+}
+
+fn class_attrs_test(class: &ast::PatternMatchClass, attrs: Expr) -> PatternTest {
+    use PatternTest::*;
+    let mut tests = vec![py_expr!("{attrs:expr} is not None", attrs = attrs.clone())];
+    let mut assigns = Vec::new();
+    let sub_patterns = class.arguments.patterns.iter().chain(
+        class
+            .arguments
+            .keywords
+            .iter()
+            .map(|keyword| &keyword.pattern),
+    );
+    for (index, pattern) in sub_patterns.enumerate() {
+        let value = py_expr!(
+            "{attrs:expr}[{idx:expr}]",
+            attrs = attrs.clone(),
+            idx = integer_expr(index)
+        );
+        match test_for_pattern(pattern, value) {
+            Test {
+                expr,
+                assigns: mut sub_assigns,
+            } => {
+                tests.push(expr);
+                assigns.append(&mut sub_assigns);
+            }
+            Wildcard {
+                assigns: mut sub_assigns,
+            } => {
+                assigns.append(&mut sub_assigns);
+            }
+        }
+    }
+    Test {
+        expr: fold_exprs(tests, ast::BoolOp::And),
+        assigns,
+    }
+}
+
 fn assigned_names(stmts: &[Stmt]) -> Vec<Name> {
     let mut names = Vec::new();
     for stmt in stmts {
@@ -488,17 +980,27 @@ pub(crate) fn rewrite_match_stmt(context: &Context, match_stmt: ast::StmtMatch)
     );
     let subject_tmp = tmp_expr;
 
+    // Plan the cases front to back so each shared subject test lands in the
+    // prelude of the first case that needs it.
+    let mut shared = SharedSubjectTests::new(context, subject_tmp, &cases);
+    let planned = cases
+        .into_iter()
+        .enumerate()
+        .map(|(index, case)| {
+            let mut prelude = Vec::new();
+            let test = shared.test_case(index, &case.pattern, &mut prelude);
+            (prelude, test, case)
+        })
+        .collect::<Vec<_>>();
+
     let mut chain = vec![py_stmt!("pass")];
-    for case in cases.into_iter().rev() {
+    for (mut prelude, test, case) in planned.into_iter().rev() {
         let ast::MatchCase {
-            pattern,
-            guard,
-            mut body,
-            ..
+            guard, mut body, ..
         } = case;
         let body = body_to_vec(std::mem::take(&mut body));
         use PatternTest::*;
-        match test_for_pattern(&pattern, subject_tmp.clone()) {
+        match test {
             Wildcard { assigns } => {
                 if let Some(g) = guard {
                     let mut cleanup = assigned_names(&assigns)
@@ -577,6 +1079,10 @@ else:
                 }
             }
         }
+        if !prelude.is_empty() {
+            prelude.append(&mut chain);
+            chain = prelude;
+        }
     }
 
     Rewrite::Walk(crate::py_stmts!(
@@ -589,8 +1095,8 @@ else:
 }
 
 impl StmtLowerer for ast::StmtMatch {
-    fn simplify_ast(self, context: &Context) -> Vec<Stmt> {
-        stmts_from_rewrite(rewrite_match_stmt(context, self))
+    fn simplify_ast(self, context: &Context) -> Result<Vec<Stmt>, Diagnostic> {
+        Ok(stmts_from_rewrite(rewrite_match_stmt(context, self)))
     }
 
     fn to_blockpy<E>(
@@ -599,7 +1105,7 @@ impl StmtLowerer for ast::StmtMatch {
         out: &mut BlockPyStmtBuilder<E>,
         loop_ctx: Option<&LoopContext>,
         next_label_id: &mut usize,
-    ) -> Result<(), String>
+    ) -> Result<(), Diagnostic>
     where
         E: RuffToBlockPyExpr,
     {
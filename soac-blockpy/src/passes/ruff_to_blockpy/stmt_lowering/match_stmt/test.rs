@@ -1,7 +1,8 @@
 use super::super::{simplify_stmt_ast_once_for_blockpy, BlockPyStmtBuilder};
 use super::*;
-use crate::block_py::CoreBlockPyExprWithAwaitAndYield;
+use crate::block_py::{CoreBlockPyExprWithAwaitAndYield, ModuleNameGen};
 use crate::passes::ast_to_ast::context::Context;
+use ruff_python_parser::parse_module;
 
 #[test]
 fn stmt_match_simplify_ast_desugars_before_blockpy_lowering() {
@@ -15,8 +16,8 @@ match x:
         panic!("expected match stmt");
     };
 
-    let context = Context::new("");
-    let simplified = simplify_stmt_ast_once_for_blockpy(&context, Stmt::Match(match_stmt));
+    let context = Context::new("", ModuleNameGen::default());
+    let simplified = simplify_stmt_ast_once_for_blockpy(&context, Stmt::Match(match_stmt)).unwrap();
 
     assert!(!matches!(simplified.as_slice(), [Stmt::Match(_)]));
 }
@@ -32,7 +33,7 @@ match x:
     let Stmt::Match(match_stmt) = stmt else {
         panic!("expected match stmt");
     };
-    let context = Context::new("");
+    let context = Context::new("", ModuleNameGen::default());
     let mut out = BlockPyStmtBuilder::<CoreBlockPyExprWithAwaitAndYield>::new();
     let mut next_label_id = 0usize;
 
@@ -43,3 +44,109 @@ match x:
     let fragment = out.finish();
     assert!(!fragment.body.is_empty() || fragment.term.is_some());
 }
+
+fn simplified_match(source: &str) -> String {
+    let body = parse_module(source).unwrap().into_syntax().body;
+    let [Stmt::Match(match_stmt)] = <[Stmt; 1]>::try_from(body).unwrap() else {
+        panic!("expected match stmt");
+    };
+    let context = Context::new("", ModuleNameGen::default());
+    let simplified = simplify_stmt_ast_once_for_blockpy(&context, Stmt::Match(match_stmt)).unwrap();
+    ruff_ast_to_string(&simplified)
+}
+
+#[test]
+fn stmt_match_dispatches_long_literal_runs_through_one_lookup() {
+    let rendered = simplified_match(
+        "\
+match x:
+    case 1:
+        a = 1
+    case 2 | 3:
+        a = 2
+    case 'four':
+        a = 3
+    case 1 if y:
+        a = 4
+    case 5:
+        a = 5
+",
+    );
+    assert_eq!(
+        rendered.matches("match_literal_case").count(),
+        1,
+        "{rendered}"
+    );
+}
+
+#[test]
+fn stmt_match_keeps_short_literal_runs_as_comparisons() {
+    let rendered = simplified_match(
+        "\
+match x:
+    case 1:
+        a = 1
+    case 2:
+        a = 2
+    case _:
+        a = 3
+",
+    );
+    assert!(!rendered.contains("match_literal_case"), "{rendered}");
+}
+
+#[test]
+fn stmt_match_shares_class_attribute_lookup_across_cases() {
+    let rendered = simplified_match(
+        "\
+match p:
+    case Point(0, y):
+        a = 1
+    case Point(x, 0):
+        a = 2
+    case Point(x, y):
+        a = 3
+",
+    );
+    assert_eq!(rendered.matches("match_class(").count(), 1, "{rendered}");
+}
+
+#[test]
+fn stmt_match_does_not_share_class_lookup_when_names_may_be_rebound() {
+    let sources = [
+        "\
+match p:
+    case Point(0, y) if (Point := y):
+        a = 1
+    case Point(x, y):
+        a = 2
+",
+        "\
+match p:
+    case Point(0, y) as Point:
+        a = 1
+    case Point(x, y):
+        a = 2
+",
+    ];
+    for source in sources {
+        let rendered = simplified_match(source);
+        assert_eq!(rendered.matches("match_class(").count(), 2, "{rendered}");
+    }
+}
+
+#[test]
+fn stmt_match_computes_sequence_length_once() {
+    let rendered = simplified_match(
+        "\
+match s:
+    case [a]:
+        b = 1
+    case [a, 0]:
+        b = 2
+    case [a, b, c]:
+        b = 3
+",
+    );
+    assert_eq!(rendered.matches("len(").count(), 1, "{rendered}");
+}
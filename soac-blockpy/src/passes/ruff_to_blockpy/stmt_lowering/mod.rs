@@ -27,12 +27,12 @@ pub(super) fn lower_stmt_via_simplify<T, E>(
     out: &mut BlockPyStmtBuilder<E>,
     loop_ctx: Option<&LoopContext>,
     next_label_id: &mut usize,
-) -> Result<(), String>
+) -> Result<(), Diagnostic>
 where
     T: StmtLowerer + Clone,
     E: RuffToBlockPyExpr,
 {
-    for simplified in stmt.clone().simplify_ast(context) {
+    for simplified in stmt.clone().simplify_ast(context)? {
         lower_stmt_into_with_expr(context, &simplified, out, loop_ctx, next_label_id)?;
     }
     Ok(())
@@ -44,12 +44,12 @@ pub(crate) fn lower_nested_stmt_into_with_expr<E>(
     out: &mut BlockPyStmtBuilder<E>,
     loop_ctx: Option<&LoopContext>,
     next_label_id: &mut usize,
-) -> Result<(), String>
+) -> Result<(), Diagnostic>
 where
     E: RuffToBlockPyExpr,
 {
     if should_simplify_nested_stmt_head(stmt) {
-        for simplified in simplify_stmt_head_ast_for_blockpy(context, stmt.clone()) {
+        for simplified in simplify_stmt_head_ast_for_blockpy(context, stmt.clone())? {
             lower_stmt_into_with_expr(context, &simplified, out, loop_ctx, next_label_id)?;
         }
         Ok(())
@@ -73,15 +73,18 @@ fn should_simplify_nested_stmt_head(stmt: &Stmt) -> bool {
 }
 
 pub(super) trait StmtLowerer {
-    fn simplify_ast(self, _context: &Context) -> Vec<Stmt>
+    fn simplify_ast(self, _context: &Context) -> Result<Vec<Stmt>, Diagnostic>
     where
         Self: Sized;
 
-    fn plan_head(self, context: &Context) -> StmtSequenceHeadPlan
+    fn plan_head(self, context: &Context) -> Result<StmtSequenceHeadPlan, Diagnostic>
     where
         Self: Sized,
     {
-        plan_simplified_stmt_head_for_blockpy(context, self.simplify_ast(context))
+        Ok(plan_simplified_stmt_head_for_blockpy(
+            context,
+            self.simplify_ast(context)?,
+        ))
     }
 
     fn to_blockpy<E>(
@@ -90,22 +93,22 @@ pub(super) trait StmtLowerer {
         _out: &mut BlockPyStmtBuilder<E>,
         _loop_ctx: Option<&LoopContext>,
         _next_label_id: &mut usize,
-    ) -> Result<(), String>
+    ) -> Result<(), Diagnostic>
     where
         E: RuffToBlockPyExpr,
     {
-        panic!(
+        Err(Diagnostic::internal(format!(
             "{} should have already been reduced before BlockPy lowering",
             std::any::type_name::<Self>()
-        );
+        )))
     }
 }
 
 macro_rules! impl_unreduced_stmt_lowerer {
     ($ty:path, $variant:path, $message:literal) => {
         impl StmtLowerer for $ty {
-            fn simplify_ast(self, _context: &Context) -> Vec<Stmt> {
-                single_stmt($variant(self))
+            fn simplify_ast(self, _context: &Context) -> Result<Vec<Stmt>, Diagnostic> {
+                Ok(single_stmt($variant(self)))
             }
 
             fn to_blockpy<E>(
@@ -114,11 +117,11 @@ macro_rules! impl_unreduced_stmt_lowerer {
                 _out: &mut BlockPyStmtBuilder<E>,
                 _loop_ctx: Option<&LoopContext>,
                 _next_label_id: &mut usize,
-            ) -> Result<(), String>
+            ) -> Result<(), Diagnostic>
             where
                 E: RuffToBlockPyExpr,
             {
-                panic!($message);
+                Err(Diagnostic::internal($message))
             }
         }
     };
@@ -142,7 +145,10 @@ pub(crate) use assign_stmt::build_for_target_assign_body;
 pub(crate) use try_stmt::{lower_star_try_stmt_sequence, lower_try_stmt_sequence};
 pub(crate) use with_stmt::lower_with_stmt_sequence;
 
-fn simplify_stmt_ast_once_for_blockpy(context: &Context, stmt: Stmt) -> Vec<Stmt> {
+fn simplify_stmt_ast_once_for_blockpy(
+    context: &Context,
+    stmt: Stmt,
+) -> Result<Vec<Stmt>, Diagnostic> {
     match stmt {
         Stmt::Global(stmt) => stmt.simplify_ast(context),
         Stmt::Nonlocal(stmt) => stmt.simplify_ast(context),
@@ -172,9 +178,12 @@ fn simplify_stmt_ast_once_for_blockpy(context: &Context, stmt: Stmt) -> Vec<Stmt
     }
 }
 
-pub(super) fn simplify_stmt_head_ast_for_blockpy(context: &Context, stmt: Stmt) -> Vec<Stmt> {
-    let stmts = simplify_stmt_ast_once_for_blockpy(context, stmt);
-    finish_stmt_head_ast_for_blockpy(context, stmts)
+pub(super) fn simplify_stmt_head_ast_for_blockpy(
+    context: &Context,
+    stmt: Stmt,
+) -> Result<Vec<Stmt>, Diagnostic> {
+    let stmts = simplify_stmt_ast_once_for_blockpy(context, stmt)?;
+    Ok(finish_stmt_head_ast_for_blockpy(context, stmts))
 }
 
 fn finish_stmt_head_ast_for_blockpy(context: &Context, stmts: Vec<Stmt>) -> Vec<Stmt> {
@@ -223,7 +232,10 @@ fn plan_simplified_stmt_head_for_blockpy(
     }
 }
 
-pub(crate) fn plan_stmt_head_for_blockpy(context: &Context, stmt: &Stmt) -> StmtSequenceHeadPlan {
+pub(crate) fn plan_stmt_head_for_blockpy(
+    context: &Context,
+    stmt: &Stmt,
+) -> Result<StmtSequenceHeadPlan, Diagnostic> {
     match stmt {
         Stmt::Global(stmt) => stmt.clone().plan_head(context),
         Stmt::Nonlocal(stmt) => stmt.clone().plan_head(context),
@@ -272,7 +284,7 @@ pub(crate) fn lower_stmt_into(
     >,
     loop_ctx: Option<&LoopContext>,
     next_label_id: &mut usize,
-) -> Result<(), String> {
+) -> Result<(), Diagnostic> {
     lower_stmt_into_with_expr(context, stmt, out, loop_ctx, next_label_id)
 }
 
@@ -282,7 +294,7 @@ pub(crate) fn lower_stmt_into_with_expr<E>(
     out: &mut BlockPyStmtBuilder<E>,
     loop_ctx: Option<&LoopContext>,
     next_label_id: &mut usize,
-) -> Result<(), String>
+) -> Result<(), Diagnostic>
 where
     E: RuffToBlockPyExpr,
 {
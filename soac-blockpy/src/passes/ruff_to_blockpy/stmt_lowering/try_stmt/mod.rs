@@ -224,12 +224,13 @@ finally:
 }
 
 impl StmtLowerer for ast::StmtTry {
-    fn simplify_ast(self, _context: &Context) -> Vec<Stmt> {
-        stmts_from_rewrite(rewrite_try_stmt(self))
+    fn simplify_ast(self, _context: &Context) -> Result<Vec<Stmt>, Diagnostic> {
+        Ok(stmts_from_rewrite(rewrite_try_stmt(self)))
     }
 }
 
 pub(crate) fn lower_star_try_stmt_sequence<F, E>(
+    context: &Context,
     try_stmt: ast::StmtTry,
     remaining_stmts: &[Stmt],
     targets: RegionTargets,
@@ -237,9 +238,13 @@ pub(crate) fn lower_star_try_stmt_sequence<F, E>(
     blocks: &mut Vec<LoweredBlockPyBlock<E>>,
     jump_label: Option<BlockLabel>,
     lower_sequence: &mut F,
-) -> BlockLabel
+) -> Result<BlockLabel, Diagnostic>
 where
-    F: FnMut(&[Stmt], RegionTargets, &mut Vec<LoweredBlockPyBlock<E>>) -> BlockLabel,
+    F: FnMut(
+        &[Stmt],
+        RegionTargets,
+        &mut Vec<LoweredBlockPyBlock<E>>,
+    ) -> Result<BlockLabel, Diagnostic>,
     E: RuffToBlockPyExpr + crate::block_py::ImplicitNoneExpr,
 {
     let rewritten_try = match rewrite_try_stmt(try_stmt) {
@@ -247,6 +252,7 @@ where
         Rewrite::Walk(stmts) => stmts,
     };
     lower_expanded_stmt_sequence(
+        context,
         rewritten_try,
         remaining_stmts,
         targets,
@@ -258,6 +264,7 @@ where
 }
 
 pub(crate) fn lower_try_stmt_sequence<F, E>(
+    context: &Context,
     try_stmt: ast::StmtTry,
     remaining_stmts: &[Stmt],
     targets: RegionTargets,
@@ -267,12 +274,16 @@ pub(crate) fn lower_try_stmt_sequence<F, E>(
     label: BlockLabel,
     try_plan: TryPlan,
     lower_sequence: &mut F,
-) -> BlockLabel
+) -> Result<BlockLabel, Diagnostic>
 where
-    F: FnMut(&[Stmt], RegionTargets, &mut Vec<LoweredBlockPyBlock<E>>) -> BlockLabel,
+    F: FnMut(
+        &[Stmt],
+        RegionTargets,
+        &mut Vec<LoweredBlockPyBlock<E>>,
+    ) -> Result<BlockLabel, Diagnostic>,
     E: RuffToBlockPyExpr + crate::block_py::ImplicitNoneExpr,
 {
-    let rest_entry = lower_sequence(remaining_stmts, targets.clone(), blocks);
+    let rest_entry = lower_sequence(remaining_stmts, targets.clone(), blocks)?;
 
     let else_body = try_stmt.orelse.to_vec();
     let try_body = try_stmt.body.to_vec();
@@ -285,6 +296,7 @@ where
     };
 
     let lowered_try = lower_try_regions(
+        context,
         blocks,
         name_gen,
         &try_plan,
@@ -296,9 +308,10 @@ where
         targets.loop_labels.clone(),
         targets.active_exc.clone(),
         lower_sequence,
-    );
+    )?;
 
     finalize_try_regions(
+        context,
         blocks,
         label,
         linear,
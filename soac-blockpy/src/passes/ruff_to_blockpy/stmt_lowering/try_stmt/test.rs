@@ -1,5 +1,6 @@
 use super::super::simplify_stmt_ast_once_for_blockpy;
 use super::*;
+use crate::block_py::ModuleNameGen;
 use crate::passes::ast_to_ast::context::Context;
 
 #[test]
@@ -16,8 +17,8 @@ except ValueError as exc:
         panic!("expected try stmt");
     };
 
-    let context = Context::new("");
-    let simplified = simplify_stmt_ast_once_for_blockpy(&context, Stmt::Try(try_stmt));
+    let context = Context::new("", ModuleNameGen::default());
+    let simplified = simplify_stmt_ast_once_for_blockpy(&context, Stmt::Try(try_stmt)).unwrap();
     let rendered = crate::ruff_ast_to_string(simplified.as_slice());
 
     assert!(
@@ -45,8 +46,8 @@ except* ValueError as exc:
         panic!("expected try stmt");
     };
 
-    let context = Context::new("");
-    let simplified = simplify_stmt_ast_once_for_blockpy(&context, Stmt::Try(try_stmt));
+    let context = Context::new("", ModuleNameGen::default());
+    let simplified = simplify_stmt_ast_once_for_blockpy(&context, Stmt::Try(try_stmt)).unwrap();
     let rendered = crate::ruff_ast_to_string(simplified.as_slice());
 
     assert!(
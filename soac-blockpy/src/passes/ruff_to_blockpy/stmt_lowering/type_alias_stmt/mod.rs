@@ -176,8 +176,8 @@ pub(crate) fn rewrite_type_alias_stmt(
 }
 
 impl StmtLowerer for ast::StmtTypeAlias {
-    fn simplify_ast(self, context: &Context) -> Vec<Stmt> {
-        stmts_from_rewrite(rewrite_type_alias_stmt(context, self))
+    fn simplify_ast(self, context: &Context) -> Result<Vec<Stmt>, Diagnostic> {
+        Ok(stmts_from_rewrite(rewrite_type_alias_stmt(context, self)))
     }
 
     fn to_blockpy<E>(
@@ -186,7 +186,7 @@ impl StmtLowerer for ast::StmtTypeAlias {
         out: &mut BlockPyStmtBuilder<E>,
         loop_ctx: Option<&LoopContext>,
         next_label_id: &mut usize,
-    ) -> Result<(), String>
+    ) -> Result<(), Diagnostic>
     where
         E: RuffToBlockPyExpr,
     {
@@ -1,6 +1,6 @@
 use super::super::{simplify_stmt_ast_once_for_blockpy, BlockPyStmtBuilder};
 use super::*;
-use crate::block_py::CoreBlockPyExprWithAwaitAndYield;
+use crate::block_py::{CoreBlockPyExprWithAwaitAndYield, ModuleNameGen};
 use crate::passes::ast_to_ast::context::Context;
 
 #[test]
@@ -10,8 +10,9 @@ fn stmt_type_alias_simplify_ast_desugars_before_blockpy_lowering() {
         panic!("expected type alias stmt");
     };
 
-    let context = Context::new("");
-    let simplified = simplify_stmt_ast_once_for_blockpy(&context, Stmt::TypeAlias(type_alias));
+    let context = Context::new("", ModuleNameGen::default());
+    let simplified =
+        simplify_stmt_ast_once_for_blockpy(&context, Stmt::TypeAlias(type_alias)).unwrap();
 
     assert!(!matches!(simplified.as_slice(), [Stmt::TypeAlias(_)]));
 }
@@ -22,7 +23,7 @@ fn stmt_type_alias_to_blockpy_uses_trait_owned_simplification_path() {
     let Stmt::TypeAlias(type_alias) = stmt else {
         panic!("expected type alias stmt");
     };
-    let context = Context::new("");
+    let context = Context::new("", ModuleNameGen::default());
     let mut out = BlockPyStmtBuilder::<CoreBlockPyExprWithAwaitAndYield>::new();
     let mut next_label_id = 0usize;
 
@@ -41,7 +42,7 @@ fn stmt_type_alias_rewrite_type_alias_stmt_handles_type_params() {
         panic!("expected type alias stmt");
     };
 
-    let context = Context::new("");
+    let context = Context::new("", ModuleNameGen::default());
     let rewritten = rewrite_type_alias_stmt(&context, type_alias);
     let simplified = stmts_from_rewrite(rewritten);
 
@@ -2,12 +2,12 @@ use super::assign_stmt::rewrite_assignment_target;
 use super::*;
 
 impl StmtLowerer for ast::StmtWith {
-    fn simplify_ast(self, _context: &Context) -> Vec<Stmt> {
-        desugar_structured_with_stmt_for_blockpy(self)
+    fn simplify_ast(self, context: &Context) -> Result<Vec<Stmt>, Diagnostic> {
+        desugar_structured_with_stmt_for_blockpy(context, self)
     }
 
-    fn plan_head(self, _context: &Context) -> StmtSequenceHeadPlan {
-        StmtSequenceHeadPlan::With(self)
+    fn plan_head(self, _context: &Context) -> Result<StmtSequenceHeadPlan, Diagnostic> {
+        Ok(StmtSequenceHeadPlan::With(self))
     }
 
     fn to_blockpy<E>(
@@ -16,7 +16,7 @@ impl StmtLowerer for ast::StmtWith {
         out: &mut BlockPyStmtBuilder<E>,
         loop_ctx: Option<&LoopContext>,
         next_label_id: &mut usize,
-    ) -> Result<(), String>
+    ) -> Result<(), Diagnostic>
     where
         E: RuffToBlockPyExpr,
     {
@@ -24,19 +24,22 @@ impl StmtLowerer for ast::StmtWith {
     }
 }
 
-fn maybe_placeholder(expr: Expr) -> (Vec<Stmt>, Expr, bool) {
+fn maybe_placeholder(context: &Context, expr: Expr) -> (Vec<Stmt>, Expr, bool) {
     if is_simple(&expr) && !matches!(&expr, Expr::StringLiteral(_) | Expr::BytesLiteral(_)) {
         return (Vec::new(), expr, false);
     }
-    let tmp = fresh_name("tmp");
+    let tmp = context.fresh("tmp");
     let stmt = py_stmt!("{tmp:id} = {expr:expr}", tmp = tmp.as_str(), expr = expr);
     (vec![stmt], py_expr!("{tmp:id}", tmp = tmp.as_str()), true)
 }
 
-pub(super) fn desugar_structured_with_stmt_for_blockpy(with_stmt: ast::StmtWith) -> Vec<Stmt> {
+pub(super) fn desugar_structured_with_stmt_for_blockpy(
+    context: &Context,
+    with_stmt: ast::StmtWith,
+) -> Result<Vec<Stmt>, Diagnostic> {
     if with_stmt.items.is_empty() {
         let mut body = with_stmt.body;
-        return std::mem::take(&mut body);
+        return Ok(std::mem::take(&mut body));
     }
 
     let ast::StmtWith {
@@ -56,10 +59,11 @@ pub(super) fn desugar_structured_with_stmt_for_blockpy(with_stmt: ast::StmtWith)
     } in items.into_iter().rev()
     {
         let target = optional_vars.map(|var| *var);
-        let exit_name = fresh_name("with_exit");
-        let ok_name = fresh_name("with_ok");
-        let reraise_name = fresh_name("with_reraise");
-        let (ctx_placeholder_stmt, ctx_expr, ctx_was_placeholder) = maybe_placeholder(context_expr);
+        let exit_name = context.fresh("with_exit");
+        let ok_name = context.fresh("with_ok");
+        let reraise_name = context.fresh("with_reraise");
+        let (ctx_placeholder_stmt, ctx_expr, ctx_was_placeholder) =
+            maybe_placeholder(context, context_expr);
         let ctx_cleanup = if ctx_was_placeholder {
             vec![py_stmt!("{ctx:expr} = None", ctx = ctx_expr.clone())]
         } else {
@@ -79,8 +83,8 @@ pub(super) fn desugar_structured_with_stmt_for_blockpy(with_stmt: ast::StmtWith)
         };
         let enter_stmt = if let Some(target) = target.clone() {
             let mut enter_stmts = Vec::new();
-            let mut next_temp = |prefix: &str| fresh_name(prefix);
-            rewrite_assignment_target(target, enter_value, &mut enter_stmts, &mut next_temp);
+            let mut next_temp = |prefix: &str| context.fresh(prefix);
+            rewrite_assignment_target(target, enter_value, &mut enter_stmts, &mut next_temp)?;
             enter_stmts
         } else {
             vec![py_stmt!("{value:expr}", value = enter_value)]
@@ -144,10 +148,11 @@ finally:
         };
     }
 
-    lowered_body
+    Ok(lowered_body)
 }
 
 pub(crate) fn lower_with_stmt_sequence<F, E>(
+    context: &Context,
     with_stmt: ast::StmtWith,
     remaining_stmts: &[Stmt],
     targets: RegionTargets,
@@ -156,9 +161,13 @@ pub(crate) fn lower_with_stmt_sequence<F, E>(
     name_gen: &FunctionNameGen,
     _needs_finally_return_flow: bool,
     lower_sequence: &mut F,
-) -> BlockLabel
+) -> Result<BlockLabel, Diagnostic>
 where
-    F: FnMut(&[Stmt], RegionTargets, &mut Vec<LoweredBlockPyBlock<E>>) -> BlockLabel,
+    F: FnMut(
+        &[Stmt],
+        RegionTargets,
+        &mut Vec<LoweredBlockPyBlock<E>>,
+    ) -> Result<BlockLabel, Diagnostic>,
     E: RuffToBlockPyExpr + crate::block_py::ImplicitNoneExpr,
 {
     if with_stmt.items.is_empty() {
@@ -168,6 +177,7 @@ where
             Some(name_gen.next_block_name())
         };
         return lower_expanded_stmt_sequence(
+            context,
             {
                 let mut body = with_stmt.body;
                 std::mem::take(&mut body)
@@ -187,7 +197,8 @@ where
         Some(name_gen.next_block_name())
     };
     lower_expanded_stmt_sequence(
-        desugar_structured_with_stmt_for_blockpy(with_stmt),
+        context,
+        desugar_structured_with_stmt_for_blockpy(context, with_stmt)?,
         remaining_stmts,
         targets,
         linear,
@@ -1,6 +1,6 @@
 use super::super::{simplify_stmt_ast_once_for_blockpy, BlockPyStmtBuilder};
 use super::*;
-use crate::block_py::CoreBlockPyExprWithAwaitAndYield;
+use crate::block_py::{CoreBlockPyExprWithAwaitAndYield, ModuleNameGen};
 use crate::passes::ast_to_ast::context::Context;
 
 #[test]
@@ -10,8 +10,8 @@ fn stmt_with_simplify_ast_desugars_before_blockpy_lowering() {
         panic!("expected with stmt");
     };
 
-    let context = Context::new("");
-    let simplified = simplify_stmt_ast_once_for_blockpy(&context, Stmt::With(with_stmt));
+    let context = Context::new("", ModuleNameGen::default());
+    let simplified = simplify_stmt_ast_once_for_blockpy(&context, Stmt::With(with_stmt)).unwrap();
 
     assert!(!matches!(simplified.as_slice(), [Stmt::With(_)]));
 }
@@ -26,8 +26,8 @@ fn stmt_with_simplify_ast_uses_native_identity_test() {
         panic!("expected with stmt");
     };
 
-    let context = Context::new("");
-    let simplified = simplify_stmt_ast_once_for_blockpy(&context, Stmt::With(with_stmt));
+    let context = Context::new("", ModuleNameGen::default());
+    let simplified = simplify_stmt_ast_once_for_blockpy(&context, Stmt::With(with_stmt)).unwrap();
     let rendered = simplified
         .iter()
         .map(crate::ruff_ast_to_string)
@@ -45,7 +45,7 @@ fn stmt_with_to_blockpy_simplifies_before_hitting_sequence_only_try_lowering() {
     let Stmt::With(with_stmt) = stmt else {
         panic!("expected with stmt");
     };
-    let context = Context::new("");
+    let context = Context::new("", ModuleNameGen::default());
     let mut out = BlockPyStmtBuilder::<CoreBlockPyExprWithAwaitAndYield>::new();
     let mut next_label_id = 0usize;
 
@@ -6,7 +6,7 @@ use crate::passes::ast_to_ast::context::Context;
 pub(crate) fn lower_stmts_to_blockpy_stmts_with_context<E>(
     context: &Context,
     stmts: &[Stmt],
-) -> Result<crate::block_py::BlockBuilder<StructuredInstr<E>, BlockTerm<E>>, String>
+) -> Result<crate::block_py::BlockBuilder<StructuredInstr<E>, BlockTerm<E>>, Diagnostic>
 where
     E: RuffToBlockPyExpr,
 {
@@ -18,17 +18,10 @@ where
     Ok(out.finish())
 }
 
-pub(crate) fn lower_stmts_to_blockpy_stmts<E>(
-    stmts: &[Stmt],
-) -> Result<crate::block_py::BlockBuilder<StructuredInstr<E>, BlockTerm<E>>, String>
-where
-    E: RuffToBlockPyExpr,
-{
-    let context = Context::new("");
-    lower_stmts_to_blockpy_stmts_with_context(&context, stmts)
-}
-
-pub(crate) fn plan_stmt_sequence_head(context: &Context, stmt: &Stmt) -> StmtSequenceHeadPlan {
+pub(crate) fn plan_stmt_sequence_head(
+    context: &Context,
+    stmt: &Stmt,
+) -> Result<StmtSequenceHeadPlan, Diagnostic> {
     super::stmt_lowering::plan_stmt_head_for_blockpy(context, stmt)
 }
 
@@ -36,37 +29,37 @@ pub(crate) fn drive_stmt_sequence_until_control(
     context: &Context,
     stmts: &[Stmt],
     mut linear: Vec<Stmt>,
-) -> StmtSequenceDriveResult {
+) -> Result<StmtSequenceDriveResult, Diagnostic> {
     let mut index = 0;
     while index < stmts.len() {
-        match plan_stmt_sequence_head(context, &stmts[index]) {
+        match plan_stmt_sequence_head(context, &stmts[index])? {
             StmtSequenceHeadPlan::Linear(stmt) => {
                 linear.push(stmt);
                 index += 1;
             }
             StmtSequenceHeadPlan::Expanded(stmts) => {
-                return StmtSequenceDriveResult::Break {
+                return Ok(StmtSequenceDriveResult::Break {
                     linear,
                     index,
                     plan: StmtSequenceHeadPlan::Expanded(stmts),
-                };
+                });
             }
             StmtSequenceHeadPlan::FunctionDef(func_def) => {
-                panic!(
+                return Err(Diagnostic::internal(format!(
                     "raw nested FunctionDef {} reached Ruff-to-BlockPy after exec-source fallback removal",
                     func_def.name.id
-                );
+                )));
             }
             plan => {
-                return StmtSequenceDriveResult::Break {
+                return Ok(StmtSequenceDriveResult::Break {
                     linear,
                     index,
                     plan,
-                };
+                });
             }
         }
     }
-    StmtSequenceDriveResult::Exhausted { linear }
+    Ok(StmtSequenceDriveResult::Exhausted { linear })
 }
 
 fn compat_blockpy_raise_from_stmt(raise_stmt: ast::StmtRaise) -> TermRaise<Expr> {
@@ -127,13 +120,17 @@ pub(crate) fn lower_common_stmt_sequence_head<FSeq, E>(
     blocks: &mut Vec<LoweredBlockPyBlock<E>>,
     next_label: &mut dyn FnMut() -> BlockLabel,
     lower_sequence: &mut FSeq,
-) -> Option<BlockLabel>
+) -> Result<Option<BlockLabel>, Diagnostic>
 where
-    FSeq: FnMut(&[Stmt], RegionTargets, &mut Vec<LoweredBlockPyBlock<E>>) -> BlockLabel,
+    FSeq: FnMut(
+        &[Stmt],
+        RegionTargets,
+        &mut Vec<LoweredBlockPyBlock<E>>,
+    ) -> Result<BlockLabel, Diagnostic>,
     E: RuffToBlockPyExpr + ImplicitNoneExpr,
 {
-    match plan {
-        StmtSequenceHeadPlan::Raise(raise_stmt) => Some(
+    let label = match plan {
+        StmtSequenceHeadPlan::Raise(raise_stmt) => {
             emit_sequence_raise_block_with_expr_setup_and_expr(
                 context,
                 blocks,
@@ -141,25 +138,17 @@ where
                 linear,
                 compat_blockpy_raise_from_stmt(raise_stmt),
                 targets.active_exc.as_ref(),
-            )
-            .unwrap_or_else(|err| {
-                panic!("failed to lower sequence raise head through expr seam: {err}")
-            }),
-        ),
-        StmtSequenceHeadPlan::Return(value) => Some(
-            emit_sequence_return_block_with_expr_setup_and_expr(
-                context,
-                blocks,
-                next_label(),
-                linear,
-                value,
-                targets.active_exc.as_ref(),
-            )
-            .unwrap_or_else(|err| {
-                panic!("failed to lower sequence return head through expr seam: {err}")
-            }),
-        ),
-        StmtSequenceHeadPlan::If(if_stmt) => Some(lower_if_stmt_sequence_from_stmt(
+            )?
+        }
+        StmtSequenceHeadPlan::Return(value) => emit_sequence_return_block_with_expr_setup_and_expr(
+            context,
+            blocks,
+            next_label(),
+            linear,
+            value,
+            targets.active_exc.as_ref(),
+        )?,
+        StmtSequenceHeadPlan::If(if_stmt) => lower_if_stmt_sequence_from_stmt(
             context,
             if_stmt,
             remaining_stmts,
@@ -168,7 +157,7 @@ where
             blocks,
             next_label(),
             &mut |stmts, targets, blocks| lower_sequence(stmts, targets, blocks),
-        )),
+        )?,
         StmtSequenceHeadPlan::While(while_stmt) => {
             let test_label = next_label();
             let linear_label = if linear.is_empty() {
@@ -176,7 +165,7 @@ where
             } else {
                 Some(next_label())
             };
-            Some(lower_while_stmt_sequence_from_stmt(
+            lower_while_stmt_sequence_from_stmt(
                 context,
                 while_stmt,
                 remaining_stmts,
@@ -186,34 +175,38 @@ where
                 test_label,
                 linear_label,
                 lower_sequence,
-            ))
+            )?
         }
         StmtSequenceHeadPlan::Break => match targets.loop_labels {
-            Some(loop_labels) => Some(emit_sequence_jump_block(
+            Some(loop_labels) => emit_sequence_jump_block(
+                context,
                 blocks,
                 next_label(),
                 linear,
                 loop_labels.break_label,
                 targets.active_exc.as_ref(),
-            )),
-            None => Some(targets.normal_cont),
+            )?,
+            None => targets.normal_cont,
         },
         StmtSequenceHeadPlan::Continue => match targets.loop_labels {
-            Some(loop_labels) => Some(emit_sequence_jump_block(
+            Some(loop_labels) => emit_sequence_jump_block(
+                context,
                 blocks,
                 next_label(),
                 linear,
                 loop_labels.continue_label,
                 targets.active_exc.as_ref(),
-            )),
-            None => Some(targets.normal_cont),
+            )?,
+            None => targets.normal_cont,
         },
-        _ => None,
-    }
+        _ => return Ok(None),
+    };
+    Ok(Some(label))
 }
 
 #[allow(clippy::too_many_arguments)]
 pub(crate) fn lower_for_stmt_sequence_head<F, E>(
+    context: &Context,
     name_gen: &FunctionNameGen,
     for_stmt: ast::StmtFor,
     remaining_stmts: &[Stmt],
@@ -226,14 +219,19 @@ pub(crate) fn lower_for_stmt_sequence_head<F, E>(
     loop_continue_label: BlockLabel,
     assign_body: Vec<Stmt>,
     lower_region: &mut F,
-) -> BlockLabel
+) -> Result<BlockLabel, Diagnostic>
 where
-    F: FnMut(&[Stmt], RegionTargets, &mut Vec<LoweredBlockPyBlock<E>>) -> BlockLabel,
+    F: FnMut(
+        &[Stmt],
+        RegionTargets,
+        &mut Vec<LoweredBlockPyBlock<E>>,
+    ) -> Result<BlockLabel, Diagnostic>,
     E: RuffToBlockPyExpr + ImplicitNoneExpr,
 {
     let assign_label = name_gen.next_block_name();
     let setup_label = name_gen.next_block_name();
     lower_for_stmt_sequence(
+        context,
         for_stmt,
         remaining_stmts,
         targets,
@@ -256,12 +254,12 @@ pub(crate) fn lower_stmt_sequence_with_state<E>(
     targets: RegionTargets,
     blocks: &mut Vec<LoweredBlockPyBlock<E>>,
     name_gen: &FunctionNameGen,
-) -> BlockLabel
+) -> Result<BlockLabel, Diagnostic>
 where
     E: RuffToBlockPyExpr + ImplicitNoneExpr,
 {
     if stmts.is_empty() {
-        return targets.normal_cont;
+        return Ok(targets.normal_cont);
     }
 
     let mut linear = Vec::new();
@@ -269,10 +267,11 @@ where
     while index < stmts.len() {
         let plan;
         (linear, index, plan) =
-            match drive_stmt_sequence_until_control(context, &stmts[index..], linear) {
+            match drive_stmt_sequence_until_control(context, &stmts[index..], linear)? {
                 StmtSequenceDriveResult::Exhausted { linear } => {
                     let label = name_gen.next_block_name();
                     return emit_sequence_jump_block(
+                        context,
                         blocks,
                         label,
                         linear,
@@ -312,15 +311,16 @@ where
                         );
                         label
                     },
-                );
+                )?;
                 if let Some(label) = label {
-                    return label;
+                    return Ok(label);
                 }
                 unreachable!("common head helper must lower supported head");
             }
             StmtSequenceHeadPlan::With(with_stmt) => {
                 let needs_finally_return_flow = contains_return_stmt_in_body(&with_stmt.body);
                 let entry = lower_with_stmt_sequence(
+                    context,
                     with_stmt,
                     &stmts[index + 1..],
                     targets.clone(),
@@ -352,8 +352,9 @@ where
                     tmp_expr,
                     tmp_name.as_str(),
                     &mut |prefix| name_gen.next_tmp_name(prefix).to_string(),
-                );
+                )?;
                 let label = lower_for_stmt_sequence_head(
+                    context,
                     name_gen,
                     for_stmt,
                     &stmts[index + 1..],
@@ -382,6 +383,7 @@ where
                 let label = if try_stmt.is_star {
                     let jump_label = (!linear.is_empty()).then(|| name_gen.next_block_name());
                     lower_star_try_stmt_sequence(
+                        context,
                         try_stmt,
                         &stmts[index + 1..],
                         targets.clone(),
@@ -408,6 +410,7 @@ where
                     let try_plan = build_try_plan(name_gen, has_finally, needs_finally_return_flow);
                     let label = name_gen.next_block_name();
                     let entry = lower_try_stmt_sequence(
+                        context,
                         try_stmt,
                         &stmts[index + 1..],
                         targets.clone(),
@@ -437,6 +440,7 @@ where
             StmtSequenceHeadPlan::Expanded(expanded_stmts) => {
                 let jump_label = (!linear.is_empty()).then(|| name_gen.next_block_name());
                 return lower_expanded_stmt_sequence(
+                    context,
                     expanded_stmts,
                     &stmts[index + 1..],
                     targets,
@@ -454,12 +458,13 @@ where
                     },
                 );
             }
-            StmtSequenceHeadPlan::Unsupported => return targets.normal_cont,
+            StmtSequenceHeadPlan::Unsupported => return Ok(targets.normal_cont),
         }
     }
 
     let label = name_gen.next_block_name();
     emit_sequence_jump_block(
+        context,
         blocks,
         label,
         linear,
@@ -469,6 +474,7 @@ where
 }
 
 pub(crate) fn lower_expanded_stmt_sequence<F, E>(
+    context: &Context,
     desugared_stmts: Vec<Stmt>,
     remaining_stmts: &[Stmt],
     targets: RegionTargets,
@@ -476,26 +482,31 @@ pub(crate) fn lower_expanded_stmt_sequence<F, E>(
     blocks: &mut Vec<LoweredBlockPyBlock<E>>,
     jump_label: Option<BlockLabel>,
     lower_sequence: &mut F,
-) -> BlockLabel
+) -> Result<BlockLabel, Diagnostic>
 where
-    F: FnMut(&[Stmt], RegionTargets, &mut Vec<LoweredBlockPyBlock<E>>) -> BlockLabel,
+    F: FnMut(
+        &[Stmt],
+        RegionTargets,
+        &mut Vec<LoweredBlockPyBlock<E>>,
+    ) -> Result<BlockLabel, Diagnostic>,
     E: RuffToBlockPyExpr + ImplicitNoneExpr,
 {
     let mut expanded = desugared_stmts;
     expanded.extend_from_slice(remaining_stmts);
     let active_exc = targets.active_exc.clone();
-    let expanded_entry = lower_sequence(&expanded, targets, blocks);
+    let expanded_entry = lower_sequence(&expanded, targets, blocks)?;
     if linear.is_empty() {
-        return expanded_entry;
+        return Ok(expanded_entry);
     }
     let jump_label = jump_label.expect("linear prefix requires a jump label");
     blocks.push(compat_block_from_blockpy_with_exc_target_and_expr(
+        context,
         jump_label.clone(),
         linear,
         BlockTerm::Jump(BlockEdge::new(expanded_entry)),
         active_exc.as_ref(),
-    ));
-    jump_label
+    )?);
+    Ok(jump_label)
 }
 
 pub(crate) fn lower_if_stmt_sequence<F, E>(
@@ -509,9 +520,13 @@ pub(crate) fn lower_if_stmt_sequence<F, E>(
     rest_entry: BlockLabel,
     targets: &RegionTargets,
     lower_region: &mut F,
-) -> BlockLabel
+) -> Result<BlockLabel, Diagnostic>
 where
-    F: FnMut(&[Stmt], RegionTargets, &mut Vec<LoweredBlockPyBlock<E>>) -> BlockLabel,
+    F: FnMut(
+        &[Stmt],
+        RegionTargets,
+        &mut Vec<LoweredBlockPyBlock<E>>,
+    ) -> Result<BlockLabel, Diagnostic>,
     E: RuffToBlockPyExpr + ImplicitNoneExpr,
 {
     let then_entry = lower_region(
@@ -522,7 +537,7 @@ where
             active_exc: targets.active_exc.clone(),
         },
         blocks,
-    );
+    )?;
     let else_entry = lower_region(
         else_body,
         RegionTargets {
@@ -531,7 +546,7 @@ where
             active_exc: targets.active_exc.clone(),
         },
         blocks,
-    );
+    )?;
     emit_if_branch_block_with_expr_setup_and_expr(
         context,
         blocks,
@@ -542,7 +557,6 @@ where
         else_entry,
         targets.active_exc.as_ref(),
     )
-    .unwrap_or_else(|err| panic!("failed to lower sequence if head through expr seam: {err}"))
 }
 
 pub(crate) fn lower_if_stmt_sequence_from_stmt<F, E>(
@@ -554,14 +568,18 @@ pub(crate) fn lower_if_stmt_sequence_from_stmt<F, E>(
     blocks: &mut Vec<LoweredBlockPyBlock<E>>,
     label: BlockLabel,
     lower_region: &mut F,
-) -> BlockLabel
+) -> Result<BlockLabel, Diagnostic>
 where
-    F: FnMut(&[Stmt], RegionTargets, &mut Vec<LoweredBlockPyBlock<E>>) -> BlockLabel,
+    F: FnMut(
+        &[Stmt],
+        RegionTargets,
+        &mut Vec<LoweredBlockPyBlock<E>>,
+    ) -> Result<BlockLabel, Diagnostic>,
     E: RuffToBlockPyExpr + ImplicitNoneExpr,
 {
     let then_body = &if_stmt.body.to_vec();
     let else_body = extract_if_else_body(&if_stmt);
-    let rest_entry = lower_region(remaining_stmts, targets.clone(), blocks);
+    let rest_entry = lower_region(remaining_stmts, targets.clone(), blocks)?;
     lower_if_stmt_sequence(
         context,
         blocks,
@@ -599,16 +617,20 @@ pub(crate) fn lower_while_stmt_sequence<F, E>(
     remaining_stmts: &[Stmt],
     targets: RegionTargets,
     lower_region: &mut F,
-) -> BlockLabel
+) -> Result<BlockLabel, Diagnostic>
 where
-    F: FnMut(&[Stmt], RegionTargets, &mut Vec<LoweredBlockPyBlock<E>>) -> BlockLabel,
+    F: FnMut(
+        &[Stmt],
+        RegionTargets,
+        &mut Vec<LoweredBlockPyBlock<E>>,
+    ) -> Result<BlockLabel, Diagnostic>,
     E: RuffToBlockPyExpr + ImplicitNoneExpr,
 {
-    let rest_entry = lower_region(remaining_stmts, targets.clone(), blocks);
+    let rest_entry = lower_region(remaining_stmts, targets.clone(), blocks)?;
     let cond_false_entry = if else_body.is_empty() {
         rest_entry.clone()
     } else {
-        lower_region(else_body, targets.nested(rest_entry.clone()), blocks)
+        lower_region(else_body, targets.nested(rest_entry.clone()), blocks)?
     };
     let body_entry = lower_region(
         body,
@@ -620,7 +642,7 @@ where
             }),
         ),
         blocks,
-    );
+    )?;
     emit_simple_while_blocks_with_expr_setup_and_expr(
         context,
         blocks,
@@ -632,7 +654,6 @@ where
         cond_false_entry,
         targets.active_exc.as_ref(),
     )
-    .unwrap_or_else(|err| panic!("failed to lower sequence while head through expr seam: {err}"))
 }
 
 pub(crate) fn lower_while_stmt_sequence_from_stmt<F, E>(
@@ -645,9 +666,13 @@ pub(crate) fn lower_while_stmt_sequence_from_stmt<F, E>(
     test_label: BlockLabel,
     linear_label: Option<BlockLabel>,
     lower_region: &mut F,
-) -> BlockLabel
+) -> Result<BlockLabel, Diagnostic>
 where
-    F: FnMut(&[Stmt], RegionTargets, &mut Vec<LoweredBlockPyBlock<E>>) -> BlockLabel,
+    F: FnMut(
+        &[Stmt],
+        RegionTargets,
+        &mut Vec<LoweredBlockPyBlock<E>>,
+    ) -> Result<BlockLabel, Diagnostic>,
     E: RuffToBlockPyExpr + ImplicitNoneExpr,
 {
     let body = &while_stmt.body.to_vec();
@@ -673,18 +698,22 @@ pub(crate) fn lower_for_stmt_exit_entries<F, E>(
     remaining_stmts: &[Stmt],
     targets: RegionTargets,
     lower_region: &mut F,
-) -> (BlockLabel, BlockLabel)
+) -> Result<(BlockLabel, BlockLabel), Diagnostic>
 where
-    F: FnMut(&[Stmt], RegionTargets, &mut Vec<LoweredBlockPyBlock<E>>) -> BlockLabel,
+    F: FnMut(
+        &[Stmt],
+        RegionTargets,
+        &mut Vec<LoweredBlockPyBlock<E>>,
+    ) -> Result<BlockLabel, Diagnostic>,
     E: ImplicitNoneExpr + Instr,
 {
-    let rest_entry = lower_region(remaining_stmts, targets.clone(), blocks);
+    let rest_entry = lower_region(remaining_stmts, targets.clone(), blocks)?;
     let exhausted_entry = if else_body.is_empty() {
         rest_entry.clone()
     } else {
-        lower_region(else_body, targets.nested(rest_entry.clone()), blocks)
+        lower_region(else_body, targets.nested(rest_entry.clone()), blocks)?
     };
-    (rest_entry, exhausted_entry)
+    Ok((rest_entry, exhausted_entry))
 }
 
 pub(crate) fn lower_for_stmt_body_entry<F, E>(
@@ -694,12 +723,16 @@ pub(crate) fn lower_for_stmt_body_entry<F, E>(
     break_label: BlockLabel,
     targets: &RegionTargets,
     lower_region: &mut F,
-) -> BlockLabel
+) -> Result<BlockLabel, Diagnostic>
 where
-    F: FnMut(&[Stmt], RegionTargets, &mut Vec<LoweredBlockPyBlock<E>>) -> BlockLabel,
+    F: FnMut(
+        &[Stmt],
+        RegionTargets,
+        &mut Vec<LoweredBlockPyBlock<E>>,
+    ) -> Result<BlockLabel, Diagnostic>,
     E: ImplicitNoneExpr + Instr,
 {
-    let body_entry = lower_region(
+    lower_region(
         body,
         targets.nested_with_loop(
             loop_continue_label.clone(),
@@ -709,12 +742,12 @@ where
             }),
         ),
         blocks,
-    );
-    body_entry
+    )
 }
 
 #[allow(clippy::too_many_arguments)]
 pub(crate) fn lower_for_stmt_sequence<F, E>(
+    context: &Context,
     for_stmt: ast::StmtFor,
     remaining_stmts: &[Stmt],
     targets: RegionTargets,
@@ -728,9 +761,13 @@ pub(crate) fn lower_for_stmt_sequence<F, E>(
     setup_label: BlockLabel,
     assign_body: Vec<Stmt>,
     lower_region: &mut F,
-) -> BlockLabel
+) -> Result<BlockLabel, Diagnostic>
 where
-    F: FnMut(&[Stmt], RegionTargets, &mut Vec<LoweredBlockPyBlock<E>>) -> BlockLabel,
+    F: FnMut(
+        &[Stmt],
+        RegionTargets,
+        &mut Vec<LoweredBlockPyBlock<E>>,
+    ) -> Result<BlockLabel, Diagnostic>,
     E: RuffToBlockPyExpr + ImplicitNoneExpr,
 {
     let else_body = &for_stmt.orelse.to_vec();
@@ -740,7 +777,7 @@ where
         remaining_stmts,
         targets.clone(),
         lower_region,
-    );
+    )?;
 
     let body = &for_stmt.body.to_vec();
     let body_entry = lower_for_stmt_body_entry(
@@ -750,9 +787,10 @@ where
         rest_entry.clone(),
         &targets,
         lower_region,
-    );
+    )?;
 
     emit_for_loop_blocks(
+        context,
         blocks,
         setup_label,
         assign_label,
@@ -2,7 +2,7 @@ use super::*;
 
 use crate::block_py::{
     BlockEdge, BlockLabel, BlockPyFunction, BlockPyModule, BlockPyPass, BlockTerm,
-    CoreBlockPyExprWithAwaitAndYield, StructuredInstr, TermRaise,
+    CoreBlockPyExprWithAwaitAndYield, ModuleNameGen, StructuredInstr, TermRaise,
 };
 use crate::lower_python_to_blockpy_for_testing;
 use crate::passes::ast_to_ast::context::Context;
@@ -55,7 +55,7 @@ fn function_by_name<'a, P: BlockPyPass>(
 }
 
 fn lower_stmt_for_panic_test(stmt: &Stmt) {
-    let context = Context::new("");
+    let context = Context::new("", ModuleNameGen::default());
     let mut out = crate::block_py::BlockBuilder::<
         StructuredInstr<CoreBlockPyExprWithAwaitAndYield>,
         BlockTerm<CoreBlockPyExprWithAwaitAndYield>,
@@ -65,7 +65,7 @@ fn lower_stmt_for_panic_test(stmt: &Stmt) {
 }
 
 fn test_context() -> Context {
-    Context::new("")
+    Context::new("", ModuleNameGen::default())
 }
 
 fn label(index: u32) -> BlockLabel {
@@ -153,7 +153,7 @@ def gen():
     let stmt = &func.body[0];
 
     assert!(matches!(
-        plan_stmt_sequence_head(&test_context(), stmt),
+        plan_stmt_sequence_head(&test_context(), stmt).unwrap(),
         StmtSequenceHeadPlan::Linear(_)
     ));
 }
@@ -175,7 +175,7 @@ def gen():
     let stmt = &func.body[0];
 
     assert!(matches!(
-        plan_stmt_sequence_head(&test_context(), stmt),
+        plan_stmt_sequence_head(&test_context(), stmt).unwrap(),
         StmtSequenceHeadPlan::Linear(_)
     ));
 }
@@ -197,7 +197,7 @@ def f():
     let stmt = &func.body[0];
 
     assert!(matches!(
-        plan_stmt_sequence_head(&test_context(), stmt),
+        plan_stmt_sequence_head(&test_context(), stmt).unwrap(),
         StmtSequenceHeadPlan::Return(_)
     ));
 }
@@ -219,7 +219,7 @@ def gen(n):
     let stmt = &func.body[0];
 
     assert!(matches!(
-        plan_stmt_sequence_head(&test_context(), stmt),
+        plan_stmt_sequence_head(&test_context(), stmt).unwrap(),
         StmtSequenceHeadPlan::Return(_)
     ));
 }
@@ -241,7 +241,7 @@ def f():
     let stmt = &func.body[0];
 
     assert!(matches!(
-        plan_stmt_sequence_head(&test_context(), stmt),
+        plan_stmt_sequence_head(&test_context(), stmt).unwrap(),
         StmtSequenceHeadPlan::If(_)
     ));
 }
@@ -264,7 +264,8 @@ def f():
     };
     let stmt = &func.body[0];
 
-    let StmtSequenceHeadPlan::Expanded(body) = plan_stmt_sequence_head(&test_context(), stmt)
+    let StmtSequenceHeadPlan::Expanded(body) =
+        plan_stmt_sequence_head(&test_context(), stmt).unwrap()
     else {
         panic!("expected expanded match body");
     };
@@ -292,7 +293,8 @@ def f():
     };
     let stmt = &func.body[0];
 
-    let StmtSequenceHeadPlan::Expanded(body) = plan_stmt_sequence_head(&test_context(), stmt)
+    let StmtSequenceHeadPlan::Expanded(body) =
+        plan_stmt_sequence_head(&test_context(), stmt).unwrap()
     else {
         panic!("expected expanded match body");
     };
@@ -303,7 +305,7 @@ def f():
 
     assert!(
         matches!(
-            plan_stmt_sequence_head(&test_context(), match_if),
+            plan_stmt_sequence_head(&test_context(), match_if).unwrap(),
             StmtSequenceHeadPlan::If(_)
         ),
         "{}",
@@ -355,6 +357,7 @@ def f(xs):
 
     let mut blocks = Vec::new();
     let entry = lower_for_stmt_sequence(
+        &test_context(),
         for_stmt.clone(),
         &[],
         RegionTargets::new(label(99), None),
@@ -368,9 +371,10 @@ def f(xs):
         label(2),
         vec![py_stmt!("x = _dp_tmp_0"), py_stmt!("_dp_tmp_0 = None")],
         &mut |_stmts: &[Stmt], targets: RegionTargets, _blocks: &mut Vec<TestBlock>| {
-            targets.normal_cont
+            Ok(targets.normal_cont)
         },
-    );
+    )
+    .unwrap();
 
     assert_eq!(entry, label(2));
     assert!(blocks.iter().any(|block| block.label == label(1)));
@@ -401,6 +405,7 @@ def f(ctx, value):
     let mut saw_try_stmt = false;
     let mut saw_with_ok_assign = false;
     let entry = lower_with_stmt_sequence(
+        &test_context(),
         with_stmt.clone(),
         &[],
         RegionTargets::new(label(99), None),
@@ -420,9 +425,10 @@ def f(ctx, value):
                     _ => false,
                 }
             });
-            targets.normal_cont
+            Ok(targets.normal_cont)
         },
-    );
+    )
+    .unwrap();
 
     assert_eq!(entry, label(99));
     assert!(blocks.is_empty());
@@ -455,6 +461,7 @@ def f():
     let name_gen = test_name_gen();
     let try_plan = build_try_plan(&name_gen, false, false);
     let entry = lower_try_stmt_sequence(
+        &test_context(),
         try_stmt.clone(),
         &[],
         RegionTargets::new(label(99), None),
@@ -469,15 +476,17 @@ def f():
                 crate::passes::ruff_to_blockpy::compat::compat_block_from_blockpy_with_exc_target_and_expr::<
                     CoreBlockPyExprWithAwaitAndYield,
                 >(
+                    &test_context(),
                     label,
                     Vec::new(),
                     BlockTerm::Jump(BlockEdge::new(targets.normal_cont)),
                     targets.active_exc.as_ref(),
-                ),
+                )?,
             );
-            label
+            Ok(label)
         },
-    );
+    )
+    .unwrap();
 
     assert!(blocks.iter().any(|block| block.label == entry));
     let Some(try_entry_block) = blocks.iter().find(|block| block.label == entry) else {
@@ -508,6 +517,7 @@ fn expanded_stmt_helper_returns_expanded_entry_without_linear_prefix() {
     let mut blocks = Vec::new();
     let mut saw_expanded = false;
     let entry = lower_expanded_stmt_sequence(
+        &test_context(),
         vec![py_stmt!("pass")],
         &[],
         RegionTargets::new(label(99), None),
@@ -518,9 +528,10 @@ fn expanded_stmt_helper_returns_expanded_entry_without_linear_prefix() {
             assert_eq!(expanded.len(), 1);
             assert_eq!(targets.normal_cont, label(99));
             saw_expanded = true;
-            label(100)
+            Ok(label(100))
         },
-    );
+    )
+    .unwrap();
 
     assert!(saw_expanded);
     assert_eq!(entry, label(100));
@@ -531,14 +542,18 @@ fn expanded_stmt_helper_returns_expanded_entry_without_linear_prefix() {
 fn expanded_stmt_helper_emits_linear_jump_prefix() {
     let mut blocks = Vec::new();
     let entry = lower_expanded_stmt_sequence(
+        &test_context(),
         vec![py_stmt!("pass")],
         &[],
         RegionTargets::new(label(99), None),
         vec![py_stmt!("x = 1")],
         &mut blocks,
         Some(label(10)),
-        &mut |_expanded: &[Stmt], _targets: RegionTargets, _blocks: &mut Vec<TestBlock>| label(11),
-    );
+        &mut |_expanded: &[Stmt], _targets: RegionTargets, _blocks: &mut Vec<TestBlock>| {
+            Ok(label(11))
+        },
+    )
+    .unwrap();
 
     assert_eq!(entry, label(10));
     assert_eq!(blocks.len(), 1);
@@ -555,7 +570,7 @@ fn if_stmt_helper_lowers_both_branches_via_callback() {
     let then_body = vec![py_stmt!("x = 1")];
     let else_body = vec![py_stmt!("x = 2")];
     let mut calls = Vec::new();
-    let context = Context::new("");
+    let context = Context::new("", ModuleNameGen::default());
 
     let entry = lower_if_stmt_sequence(
         &context,
@@ -569,9 +584,10 @@ fn if_stmt_helper_lowers_both_branches_via_callback() {
         &RegionTargets::new(label(99), None),
         &mut |stmts: &[Stmt], targets: RegionTargets, _blocks: &mut Vec<TestBlock>| {
             calls.push((stmts.len(), targets.normal_cont.clone()));
-            label(200 + calls.len() as u32)
+            Ok(label(200 + calls.len() as u32))
         },
-    );
+    )
+    .unwrap();
 
     assert_eq!(entry, label(10));
     assert_eq!(
@@ -589,12 +605,14 @@ fn if_stmt_helper_lowers_both_branches_via_callback() {
 fn sequence_jump_helper_emits_jump_block() {
     let mut blocks = Vec::new();
     let entry = emit_sequence_jump_block::<CoreBlockPyExprWithAwaitAndYield>(
+        &test_context(),
         &mut blocks,
         label(10),
         vec![py_stmt!("prefix = 0")],
         label(11).into(),
         None,
-    );
+    )
+    .unwrap();
 
     assert_eq!(entry, label(10));
     assert_eq!(blocks.len(), 1);
@@ -607,7 +625,7 @@ fn sequence_jump_helper_emits_jump_block() {
 #[test]
 fn sequence_return_helper_emits_return_block() {
     let mut blocks = Vec::new();
-    let context = Context::new("");
+    let context = Context::new("", ModuleNameGen::default());
     let entry =
         emit_sequence_return_block_with_expr_setup_and_expr::<CoreBlockPyExprWithAwaitAndYield>(
             &context,
@@ -627,7 +645,7 @@ fn sequence_return_helper_emits_return_block() {
 #[test]
 fn sequence_raise_helper_emits_raise_block() {
     let mut blocks = Vec::new();
-    let context = Context::new("");
+    let context = Context::new("", ModuleNameGen::default());
     let entry =
         emit_sequence_raise_block_with_expr_setup_and_expr::<CoreBlockPyExprWithAwaitAndYield>(
             &context,
@@ -669,7 +687,7 @@ y = 3
     let remaining = vec![module[1].clone()];
     let mut blocks = Vec::new();
     let mut calls = Vec::new();
-    let context = Context::new("");
+    let context = Context::new("", ModuleNameGen::default());
 
     let entry = lower_if_stmt_sequence_from_stmt(
         &context,
@@ -681,9 +699,10 @@ y = 3
         label(10),
         &mut |stmts: &[Stmt], targets: RegionTargets, _blocks: &mut Vec<TestBlock>| {
             calls.push((stmts.len(), targets.normal_cont.clone()));
-            label(200 + calls.len() as u32)
+            Ok(label(200 + calls.len() as u32))
         },
-    );
+    )
+    .unwrap();
 
     assert_eq!(entry, label(10));
     assert_eq!(
@@ -709,7 +728,7 @@ fn while_stmt_helper_lowers_loop_and_else_via_callbacks() {
     let remaining = vec![py_stmt!("x = 3")];
     let mut sequence_calls = Vec::new();
     let mut loop_calls = Vec::new();
-    let context = Context::new("");
+    let context = Context::new("", ModuleNameGen::default());
 
     let entry = lower_while_stmt_sequence(
         &context,
@@ -729,13 +748,14 @@ fn while_stmt_helper_lowers_loop_and_else_via_callbacks() {
                     targets.normal_cont.clone(),
                     loop_labels.break_label,
                 ));
-                label(250)
+                Ok(label(250))
             } else {
                 sequence_calls.push((stmts.len(), targets.normal_cont.clone()));
-                label(200 + sequence_calls.len() as u32)
+                Ok(label(200 + sequence_calls.len() as u32))
             }
         },
-    );
+    )
+    .unwrap();
 
     assert_eq!(entry, label(1));
     assert_eq!(
@@ -778,7 +798,7 @@ y = 3
     let mut blocks = Vec::new();
     let mut sequence_calls = Vec::new();
     let mut loop_calls = Vec::new();
-    let context = Context::new("");
+    let context = Context::new("", ModuleNameGen::default());
 
     let entry = lower_while_stmt_sequence_from_stmt(
         &context,
@@ -796,13 +816,14 @@ y = 3
                     targets.normal_cont.clone(),
                     loop_labels.break_label,
                 ));
-                label(250)
+                Ok(label(250))
             } else {
                 sequence_calls.push((stmts.len(), targets.normal_cont.clone()));
-                label(200 + sequence_calls.len() as u32)
+                Ok(label(200 + sequence_calls.len() as u32))
             }
         },
-    );
+    )
+    .unwrap();
 
     assert_eq!(entry, label(1));
     assert_eq!(
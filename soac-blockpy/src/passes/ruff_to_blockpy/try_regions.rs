@@ -5,6 +5,7 @@ use crate::block_py::{
     HasMeta, Instr, Store, StructuredInstr, TermBranchTable, TermRaise, WithMeta,
 };
 use crate::passes::ast_to_ast::body::Suite;
+use crate::passes::ast_to_ast::context::Context;
 
 fn expr_name(id: &str) -> ast::ExprName {
     let Expr::Name(expr) = py_expr!("{id:id}", id = id) else {
@@ -97,6 +98,7 @@ pub(crate) struct LoweredTryRegions {
 }
 
 pub(crate) fn lower_try_regions<F, E>(
+    context: &Context,
     blocks: &mut Vec<LoweredBlockPyBlock<E>>,
     name_gen: &FunctionNameGen,
     try_plan: &TryPlan,
@@ -108,9 +110,13 @@ pub(crate) fn lower_try_regions<F, E>(
     loop_labels: Option<LoopLabels>,
     active_exc_target: Option<BlockLabel>,
     lower_sequence: &mut F,
-) -> LoweredTryRegions
+) -> Result<LoweredTryRegions, Diagnostic>
 where
-    F: FnMut(&[Stmt], RegionTargets, &mut Vec<LoweredBlockPyBlock<E>>) -> BlockLabel,
+    F: FnMut(
+        &[Stmt],
+        RegionTargets,
+        &mut Vec<LoweredBlockPyBlock<E>>,
+    ) -> Result<BlockLabel, Diagnostic>,
     E: crate::block_py::ImplicitNoneExpr + RuffToBlockPyExpr,
 {
     let finally_label = if let Some(finally_body) = finally_body {
@@ -123,7 +129,7 @@ where
                 active_exc: active_exc_target.clone(),
             },
             blocks,
-        );
+        )?;
         let finally_region_end = blocks.len();
         if let Some(finally_entry) = blocks.iter_mut().find(|block| block.label == finally_label) {
             if let Some(kind_name) = try_plan.finally_abrupt_kind_name.as_ref() {
@@ -184,6 +190,7 @@ where
             try_plan.finally_abrupt_kind_name.as_ref(),
         ) {
             emit_finally_abrupt_dispatch_blocks(
+                context,
                 blocks,
                 finally_return_label,
                 finally_raise_label,
@@ -192,7 +199,7 @@ where
                 kind_name,
                 rest_entry.clone(),
                 active_exc_target.clone(),
-            );
+            )?;
         }
         Some((
             finally_label,
@@ -229,7 +236,7 @@ where
                 active_exc: cleanup_exc_target.clone(),
             },
             blocks,
-        )
+        )?
     };
     let else_region_end = blocks.len();
 
@@ -244,7 +251,7 @@ where
                 active_exc: cleanup_exc_target,
             },
             blocks,
-        );
+        )?;
         let except_region_end = blocks.len();
         except_region_range = Some(except_region_start..except_region_end);
         except_label
@@ -264,27 +271,28 @@ where
             active_exc: Some(except_label.clone()),
         },
         blocks,
-    );
+    )?;
     let body_region_end = blocks.len();
 
-    LoweredTryRegions {
+    Ok(LoweredTryRegions {
         body_label,
         body_region_range: body_region_start..body_region_end,
         else_region_range: else_region_start..else_region_end,
         except_region_range,
         finally_region_range: finally_label.as_ref().map(|(_, range, _, _)| range.clone()),
         finally_label: finally_label.map(|(label, _, _, _)| label),
-    }
+    })
 }
 
 pub(crate) fn finalize_try_regions<E>(
+    context: &Context,
     blocks: &mut Vec<LoweredBlockPyBlock<E>>,
     label: BlockLabel,
     linear: Vec<Stmt>,
     try_plan: TryPlan,
     lowered_try: LoweredTryRegions,
     active_exc_target: Option<BlockLabel>,
-) -> BlockLabel
+) -> Result<BlockLabel, Diagnostic>
 where
     E: crate::block_py::ImplicitNoneExpr + RuffToBlockPyExpr,
 {
@@ -327,6 +335,7 @@ where
         );
     }
     emit_try_jump_entry(
+        context,
         blocks,
         label,
         linear,
@@ -365,6 +374,7 @@ fn rewrite_region_returns_to_finally_blockpy<E>(
 }
 
 pub(crate) fn emit_finally_abrupt_dispatch_blocks<E>(
+    context: &Context,
     blocks: &mut Vec<LoweredBlockPyBlock<E>>,
     finally_return_label: BlockLabel,
     finally_raise_label: BlockLabel,
@@ -373,24 +383,28 @@ pub(crate) fn emit_finally_abrupt_dispatch_blocks<E>(
     kind_name: &str,
     rest_entry: BlockLabel,
     active_exc_target: Option<BlockLabel>,
-) where
+) -> Result<(), Diagnostic>
+where
     E: crate::block_py::ImplicitNoneExpr + RuffToBlockPyExpr,
 {
     blocks.push(compat_block_from_blockpy_with_exc_target_and_expr(
+        context,
         finally_return_label.clone(),
         Vec::new(),
         BlockTerm::Return(py_expr!("{name:id}", name = payload_name).into()),
         active_exc_target.as_ref(),
-    ));
+    )?);
     blocks.push(compat_block_from_blockpy_with_exc_target_and_expr(
+        context,
         finally_raise_label.clone(),
         Vec::new(),
         BlockTerm::Raise(TermRaise {
             exc: Some(py_expr!("{name:id}", name = payload_name).into()),
         }),
         active_exc_target.as_ref(),
-    ));
+    )?);
     blocks.push(compat_block_from_blockpy_with_exc_target_and_expr(
+        context,
         finally_dispatch_label.clone(),
         Vec::new(),
         BlockTerm::BranchTable(TermBranchTable {
@@ -403,26 +417,29 @@ pub(crate) fn emit_finally_abrupt_dispatch_blocks<E>(
             default_label: rest_entry,
         }),
         active_exc_target.as_ref(),
-    ));
+    )?);
+    Ok(())
 }
 
 pub(crate) fn emit_try_jump_entry<E>(
+    context: &Context,
     blocks: &mut Vec<LoweredBlockPyBlock<E>>,
     label: BlockLabel,
     linear: Vec<Stmt>,
     body_label: BlockLabel,
     active_exc_target: Option<BlockLabel>,
-) -> BlockLabel
+) -> Result<BlockLabel, Diagnostic>
 where
     E: crate::block_py::ImplicitNoneExpr + RuffToBlockPyExpr,
 {
     blocks.push(compat_block_from_blockpy_with_exc_target_and_expr(
+        context,
         label.clone(),
         linear,
         BlockTerm::Jump(BlockEdge::new(body_label)),
         active_exc_target.as_ref(),
-    ));
-    label
+    )?);
+    Ok(label)
 }
 
 pub(crate) fn block_references_label<E: Instr>(
@@ -0,0 +1,993 @@
+//! Rejects programs that CPython's symbol table and compiler reject, with the
+//! same `SyntaxError` messages.
+//!
+//! The ruff parser only reports grammar errors; `nonlocal x` at module level
+//! or `yield` inside a comprehension parse fine. The rest of the pipeline
+//! assumes such programs never reach it, so they are checked here, right after
+//! parsing.
+//!
+//! CPython reports errors in three phases, and so does this pass: the first
+//! error found while building the symbol table, then errors from resolving
+//! `nonlocal` declarations, then the first compiler error in source order.
+
+use std::collections::{HashMap, HashSet};
+
+use ruff_python_ast::visitor::{walk_except_handler, walk_expr, walk_pattern, walk_stmt, Visitor};
+use ruff_python_ast::{self as ast, ExceptHandler, Expr, ExprContext, Pattern, Stmt};
+use ruff_text_size::{Ranged, TextRange};
+
+use crate::diagnostic::Diagnostic;
+use crate::{LoweringError, ParseError};
+
+const DEF_GLOBAL: u16 = 1 << 0;
+const DEF_LOCAL: u16 = 1 << 1;
+const DEF_PARAM: u16 = 1 << 2;
+const DEF_NONLOCAL: u16 = 1 << 3;
+const USE: u16 = 1 << 4;
+const DEF_ANNOT: u16 = 1 << 5;
+const DEF_COMP_ITER: u16 = 1 << 6;
+/// Imports bind like assignments but, as in CPython, may precede a `global`
+/// declaration of the same name.
+const DEF_IMPORT: u16 = 1 << 7;
+const DEF_TYPE_PARAM: u16 = 1 << 8;
+
+const DEF_BOUND: u16 = DEF_LOCAL | DEF_PARAM | DEF_IMPORT;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum ComprehensionKind {
+    List,
+    Set,
+    Dict,
+    Generator,
+}
+
+impl ComprehensionKind {
+    fn description(self) -> &'static str {
+        match self {
+            Self::List => "list comprehension",
+            Self::Set => "set comprehension",
+            Self::Dict => "dict comprehension",
+            Self::Generator => "generator expression",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum ScopeKind {
+    Module,
+    Class,
+    Function {
+        is_async: bool,
+    },
+    Lambda,
+    Comprehension(ComprehensionKind),
+    /// The scope a function's parameter and return annotations are
+    /// evaluated in.
+    Annotation,
+    /// Binds the type parameters of a generic function, class or alias.
+    TypeParams,
+    /// A lazily evaluated bound or default, described as in CPython's
+    /// messages ("a TypeVar bound").
+    TypeVariable(&'static str),
+    /// The lazily evaluated value of a `type` statement.
+    TypeAlias,
+}
+
+impl ScopeKind {
+    fn is_function_like(self) -> bool {
+        !matches!(self, Self::Module | Self::Class)
+    }
+
+    /// What CPython calls an annotation or type scope in which `yield`,
+    /// `await` and `:=` may not appear.
+    fn annotation_context(self) -> Option<&'static str> {
+        match self {
+            Self::Annotation => Some("an annotation"),
+            Self::TypeParams => Some("the definition of a generic"),
+            Self::TypeVariable(description) => Some(description),
+            Self::TypeAlias => Some("a type alias"),
+            _ => None,
+        }
+    }
+}
+
+struct Scope {
+    kind: ScopeKind,
+    children: Vec<usize>,
+    /// Symbol flags in first-seen order, which is the order CPython resolves
+    /// them in.
+    symbols: Vec<(String, u16)>,
+    symbol_index: HashMap<String, usize>,
+    /// First `global`/`nonlocal` statement naming each symbol.
+    directives: HashMap<String, TextRange>,
+    coroutine: bool,
+    generator: bool,
+    loop_depth: usize,
+    /// Loop depth on entry to the innermost enclosing `except*` handler.
+    except_star_loop_depth: Option<usize>,
+    value_returns: Vec<TextRange>,
+}
+
+impl Scope {
+    fn new(kind: ScopeKind) -> Self {
+        Self {
+            kind,
+            children: Vec::new(),
+            symbols: Vec::new(),
+            symbol_index: HashMap::new(),
+            directives: HashMap::new(),
+            coroutine: matches!(kind, ScopeKind::Function { is_async: true }),
+            generator: false,
+            loop_depth: 0,
+            except_star_loop_depth: None,
+            value_returns: Vec::new(),
+        }
+    }
+
+    fn lookup(&self, name: &str) -> u16 {
+        self.symbol_index
+            .get(name)
+            .map_or(0, |index| self.symbols[*index].1)
+    }
+
+    fn add(&mut self, name: &str, flags: u16) {
+        match self.symbol_index.get(name) {
+            Some(index) => self.symbols[*index].1 |= flags,
+            None => {
+                self.symbol_index
+                    .insert(name.to_string(), self.symbols.len());
+                self.symbols.push((name.to_string(), flags));
+            }
+        }
+    }
+
+    /// CPython's `IS_ASYNC_DEF`: a function block that has seen `await`
+    /// counts as async even when declared with plain `def`.
+    fn is_async_def(&self) -> bool {
+        matches!(self.kind, ScopeKind::Function { .. } | ScopeKind::Lambda) && self.coroutine
+    }
+}
+
+struct SyntaxChecker {
+    scopes: Vec<Scope>,
+    stack: Vec<usize>,
+    /// Flags given to names in store context; comprehension targets also
+    /// carry `DEF_COMP_ITER`.
+    store_flags: u16,
+    error: Option<Diagnostic>,
+    compile_errors: Vec<Diagnostic>,
+}
+
+/// Checks `module` for errors CPython raises after parsing.
+pub(crate) fn check_module(module: &[Stmt]) -> Result<(), Diagnostic> {
+    let mut checker = SyntaxChecker {
+        scopes: vec![Scope::new(ScopeKind::Module)],
+        stack: vec![0],
+        store_flags: DEF_LOCAL,
+        error: None,
+        compile_errors: Vec::new(),
+    };
+    checker.visit_body(module);
+    if let Some(error) = checker.error {
+        return Err(error);
+    }
+    checker.analyze(0, None, &HashSet::new())?;
+    checker.check_async_generator_returns();
+    match checker
+        .compile_errors
+        .into_iter()
+        .min_by_key(|error| error.range.map(|range| range.start()))
+    {
+        Some(error) => Err(error),
+        None => Ok(()),
+    }
+}
+
+/// The ruff parser already rejects duplicate parameters, in its own words;
+/// report them with CPython's message instead.
+pub(crate) fn from_parse_error(err: ParseError) -> LoweringError {
+    let message = err.error.to_string();
+    match message.strip_prefix("Duplicate parameter ") {
+        Some(name) => Diagnostic::syntax(
+            format!(
+                "duplicate argument '{}' in function definition",
+                name.trim_matches('"')
+            ),
+            err.location,
+        )
+        .into(),
+        None => err.into(),
+    }
+}
+
+impl SyntaxChecker {
+    fn current(&self) -> &Scope {
+        &self.scopes[*self.stack.last().expect("scope stack is never empty")]
+    }
+
+    fn current_mut(&mut self) -> &mut Scope {
+        let id = *self.stack.last().expect("scope stack is never empty");
+        &mut self.scopes[id]
+    }
+
+    fn push_scope(&mut self, kind: ScopeKind) {
+        let id = self.scopes.len();
+        self.current_mut().children.push(id);
+        self.scopes.push(Scope::new(kind));
+        self.stack.push(id);
+    }
+
+    fn pop_scope(&mut self) -> usize {
+        self.stack.pop().expect("popped the module scope")
+    }
+
+    fn fail(&mut self, message: String, range: TextRange) {
+        if self.error.is_none() {
+            self.error = Some(Diagnostic::syntax(message, range));
+        }
+    }
+
+    fn compile_error(&mut self, message: &str, range: TextRange) {
+        self.compile_errors.push(Diagnostic::syntax(message, range));
+    }
+
+    fn add_param(&mut self, parameter: &ast::Parameter) {
+        let name = parameter.name.as_str();
+        if self.current().lookup(name) & DEF_PARAM != 0 {
+            self.fail(
+                format!("duplicate argument '{name}' in function definition"),
+                parameter.range(),
+            );
+            return;
+        }
+        self.current_mut().add(name, DEF_PARAM);
+    }
+
+    fn add_params(&mut self, parameters: &ast::Parameters) {
+        for parameter in parameters
+            .posonlyargs
+            .iter()
+            .chain(&parameters.args)
+            .chain(&parameters.kwonlyargs)
+        {
+            self.add_param(&parameter.parameter);
+        }
+        if let Some(vararg) = &parameters.vararg {
+            self.add_param(vararg);
+        }
+        if let Some(kwarg) = &parameters.kwarg {
+            self.add_param(kwarg);
+        }
+    }
+
+    fn visit_defaults(&mut self, parameters: &ast::Parameters) {
+        for parameter in parameters
+            .posonlyargs
+            .iter()
+            .chain(&parameters.args)
+            .chain(&parameters.kwonlyargs)
+        {
+            if let Some(default) = &parameter.default {
+                self.visit_expr(default);
+            }
+        }
+    }
+
+    fn visit_function_def(&mut self, function_def: &ast::StmtFunctionDef) {
+        self.current_mut()
+            .add(function_def.name.as_str(), DEF_LOCAL);
+        self.visit_defaults(&function_def.parameters);
+        for decorator in &function_def.decorator_list {
+            self.visit_expr(&decorator.expression);
+        }
+        if let Some(type_params) = &function_def.type_params {
+            self.push_scope(ScopeKind::TypeParams);
+            self.visit_type_params(type_params);
+        }
+        self.visit_annotations(&function_def.parameters, function_def.returns.as_deref());
+        self.push_scope(ScopeKind::Function {
+            is_async: function_def.is_async,
+        });
+        self.add_params(&function_def.parameters);
+        self.visit_body(&function_def.body);
+        self.pop_scope();
+        if function_def.type_params.is_some() {
+            self.pop_scope();
+        }
+    }
+
+    /// Parameter and return annotations get their own scope, which CPython
+    /// builds even when there are none.
+    fn visit_annotations(&mut self, parameters: &ast::Parameters, returns: Option<&Expr>) {
+        self.push_scope(ScopeKind::Annotation);
+        let annotations = parameters
+            .posonlyargs
+            .iter()
+            .chain(&parameters.args)
+            .map(|parameter| &parameter.parameter)
+            .chain(parameters.vararg.as_deref())
+            .chain(parameters.kwarg.as_deref())
+            .chain(
+                parameters
+                    .kwonlyargs
+                    .iter()
+                    .map(|parameter| &parameter.parameter),
+            )
+            .filter_map(|parameter| parameter.annotation.as_deref());
+        for annotation in annotations.chain(returns) {
+            self.visit_expr(annotation);
+        }
+        self.pop_scope();
+    }
+
+    /// Binds each type parameter in the current type-parameter scope; bounds
+    /// and defaults are evaluated lazily, each in a scope of its own.
+    fn visit_type_params(&mut self, type_params: &ast::TypeParams) {
+        for type_param in &type_params.type_params {
+            let (name, bound, default, default_context) = match type_param {
+                ast::TypeParam::TypeVar(type_var) => (
+                    &type_var.name,
+                    type_var.bound.as_deref(),
+                    type_var.default.as_deref(),
+                    "a TypeVar default",
+                ),
+                ast::TypeParam::TypeVarTuple(type_var_tuple) => (
+                    &type_var_tuple.name,
+                    None,
+                    type_var_tuple.default.as_deref(),
+                    "a TypeVarTuple default",
+                ),
+                ast::TypeParam::ParamSpec(param_spec) => (
+                    &param_spec.name,
+                    None,
+                    param_spec.default.as_deref(),
+                    "a ParamSpec default",
+                ),
+            };
+            let name = name.as_str();
+            if self.current().lookup(name) & DEF_TYPE_PARAM != 0 {
+                self.fail(
+                    format!("duplicate type parameter '{name}'"),
+                    type_param.range(),
+                );
+                return;
+            }
+            if name == "__debug__" {
+                self.compile_error("cannot assign to __debug__", type_param.range());
+            }
+            self.current_mut().add(name, DEF_LOCAL | DEF_TYPE_PARAM);
+            if let Some(bound) = bound {
+                let context = if bound.is_tuple_expr() {
+                    "a TypeVar constraint"
+                } else {
+                    "a TypeVar bound"
+                };
+                self.visit_lazy_expr(ScopeKind::TypeVariable(context), bound);
+            }
+            if let Some(default) = default {
+                self.visit_lazy_expr(ScopeKind::TypeVariable(default_context), default);
+            }
+        }
+    }
+
+    fn visit_lazy_expr(&mut self, kind: ScopeKind, expr: &Expr) {
+        self.push_scope(kind);
+        self.visit_expr(expr);
+        self.pop_scope();
+    }
+
+    /// CPython rejects `yield`, `await` and `:=` in annotation and type
+    /// scopes before any other check on them.
+    fn reject_in_annotation_scope(&mut self, what: &str, expr: &Expr) -> bool {
+        let Some(context) = self.current().kind.annotation_context() else {
+            return false;
+        };
+        self.fail(
+            format!("{what} cannot be used within {context}"),
+            expr.range(),
+        );
+        true
+    }
+
+    fn visit_class_def(&mut self, class_def: &ast::StmtClassDef) {
+        self.current_mut().add(class_def.name.as_str(), DEF_LOCAL);
+        for decorator in &class_def.decorator_list {
+            self.visit_expr(&decorator.expression);
+        }
+        if let Some(type_params) = &class_def.type_params {
+            self.push_scope(ScopeKind::TypeParams);
+            self.visit_type_params(type_params);
+        }
+        if let Some(arguments) = &class_def.arguments {
+            for arg in arguments.args.iter() {
+                self.visit_expr(arg);
+            }
+            for keyword in arguments.keywords.iter() {
+                self.visit_expr(&keyword.value);
+            }
+        }
+        self.push_scope(ScopeKind::Class);
+        self.visit_body(&class_def.body);
+        self.pop_scope();
+        if class_def.type_params.is_some() {
+            self.pop_scope();
+        }
+    }
+
+    fn visit_type_alias(&mut self, type_alias: &ast::StmtTypeAlias) {
+        self.visit_expr(&type_alias.name);
+        if let Some(type_params) = &type_alias.type_params {
+            self.push_scope(ScopeKind::TypeParams);
+            self.visit_type_params(type_params);
+        }
+        self.visit_lazy_expr(ScopeKind::TypeAlias, &type_alias.value);
+        if type_alias.type_params.is_some() {
+            self.pop_scope();
+        }
+    }
+
+    fn visit_directive(&mut self, stmt: &Stmt, names: &[ast::Identifier], global: bool) {
+        let (kind, flag) = if global {
+            ("global", DEF_GLOBAL)
+        } else {
+            ("nonlocal", DEF_NONLOCAL)
+        };
+        for name in names {
+            let name = name.as_str();
+            let current = self.current().lookup(name);
+            if current & (DEF_PARAM | DEF_LOCAL | USE | DEF_ANNOT) != 0 {
+                let message = if current & DEF_PARAM != 0 {
+                    format!("name '{name}' is parameter and {kind}")
+                } else if current & USE != 0 {
+                    format!("name '{name}' is used prior to {kind} declaration")
+                } else if current & DEF_ANNOT != 0 {
+                    format!("annotated name '{name}' can't be {kind}")
+                } else {
+                    format!("name '{name}' is assigned to before {kind} declaration")
+                };
+                self.fail(message, stmt.range());
+                return;
+            }
+            let scope = self.current_mut();
+            scope.add(name, flag);
+            scope
+                .directives
+                .entry(name.to_string())
+                .or_insert(stmt.range());
+        }
+    }
+
+    fn visit_ann_assign(&mut self, stmt: &Stmt, ann_assign: &ast::StmtAnnAssign) {
+        if let Expr::Name(target) = ann_assign.target.as_ref() {
+            let name = target.id.as_str();
+            let current = self.current().lookup(name);
+            if current & (DEF_GLOBAL | DEF_NONLOCAL) != 0
+                && self.current().kind != ScopeKind::Module
+                && ann_assign.simple
+            {
+                let kind = if current & DEF_GLOBAL != 0 {
+                    "global"
+                } else {
+                    "nonlocal"
+                };
+                self.fail(
+                    format!("annotated name '{name}' can't be {kind}"),
+                    stmt.range(),
+                );
+                return;
+            }
+            if ann_assign.simple {
+                self.current_mut().add(name, DEF_ANNOT | DEF_LOCAL);
+            } else if ann_assign.value.is_some() {
+                self.current_mut().add(name, DEF_LOCAL);
+            }
+        } else {
+            self.visit_expr(&ann_assign.target);
+        }
+        if let Some(value) = &ann_assign.value {
+            self.visit_expr(value);
+        }
+    }
+
+    fn visit_alias(&mut self, stmt: &Stmt, alias: &ast::Alias) {
+        if alias.name.as_str() == "*" {
+            if self.current().kind != ScopeKind::Module {
+                self.fail(
+                    "import * only allowed at module level".to_string(),
+                    stmt.range(),
+                );
+            }
+            return;
+        }
+        let bound = match &alias.asname {
+            Some(asname) => asname.as_str(),
+            None => alias.name.as_str().split('.').next().unwrap_or_default(),
+        };
+        self.current_mut().add(bound, DEF_IMPORT);
+    }
+
+    fn visit_loop_body(&mut self, body: &[Stmt]) {
+        self.current_mut().loop_depth += 1;
+        self.visit_body(body);
+        self.current_mut().loop_depth -= 1;
+    }
+
+    fn visit_try_star(&mut self, try_stmt: &ast::StmtTry) {
+        self.visit_body(&try_stmt.body);
+        let loop_depth = self.current().loop_depth;
+        let outer = self
+            .current_mut()
+            .except_star_loop_depth
+            .replace(loop_depth);
+        for handler in &try_stmt.handlers {
+            self.visit_except_handler(handler);
+        }
+        self.current_mut().except_star_loop_depth = outer;
+        self.visit_body(&try_stmt.orelse);
+        self.visit_body(&try_stmt.finalbody);
+    }
+
+    /// `break`, `continue` and `return` may not leave an `except*` handler.
+    fn check_except_star_exit(&mut self, stmt: &Stmt) -> bool {
+        let scope = self.current();
+        let leaves_handler = match (stmt, scope.except_star_loop_depth) {
+            (_, None) => false,
+            (Stmt::Return(_), Some(_)) => true,
+            (_, Some(depth)) => scope.loop_depth == depth,
+        };
+        if leaves_handler {
+            self.compile_error(
+                "'break', 'continue' and 'return' cannot appear in an except* block",
+                stmt.range(),
+            );
+        }
+        leaves_handler
+    }
+
+    /// Unpacking targets take at most one starred element; any other starred
+    /// store is rejected when `visit_expr` reaches it.
+    fn visit_unpack_target(&mut self, range: TextRange, elts: &[Expr]) {
+        if elts.iter().filter(|elt| elt.is_starred_expr()).count() > 1 {
+            self.compile_error("multiple starred expressions in assignment", range);
+        }
+        for elt in elts {
+            match elt {
+                Expr::Starred(starred) => self.visit_expr(&starred.value),
+                other => self.visit_expr(other),
+            }
+        }
+    }
+
+    fn require_async_function(&mut self, message: &str, range: TextRange) {
+        if !matches!(self.current().kind, ScopeKind::Function { is_async: true }) {
+            self.compile_error(message, range);
+        }
+    }
+
+    fn visit_lambda(&mut self, lambda: &ast::ExprLambda) {
+        if let Some(parameters) = &lambda.parameters {
+            self.visit_defaults(parameters);
+        }
+        self.push_scope(ScopeKind::Lambda);
+        if let Some(parameters) = &lambda.parameters {
+            self.add_params(parameters);
+        }
+        self.visit_expr(&lambda.body);
+        self.pop_scope();
+    }
+
+    fn visit_comprehension_target(&mut self, target: &Expr) {
+        self.store_flags = DEF_LOCAL | DEF_COMP_ITER;
+        self.visit_expr(target);
+        self.store_flags = DEF_LOCAL;
+    }
+
+    fn visit_comprehension_scope(
+        &mut self,
+        expr: &Expr,
+        kind: ComprehensionKind,
+        generators: &[ast::Comprehension],
+        elements: &[&Expr],
+    ) {
+        let Some((outermost, rest)) = generators.split_first() else {
+            return;
+        };
+        // The outermost iterable is evaluated in the enclosing scope.
+        self.visit_expr(&outermost.iter);
+        self.push_scope(ScopeKind::Comprehension(kind));
+        if generators.iter().any(|generator| generator.is_async) {
+            self.current_mut().coroutine = true;
+        }
+        self.visit_comprehension_target(&outermost.target);
+        for condition in &outermost.ifs {
+            self.visit_expr(condition);
+        }
+        for generator in rest {
+            self.visit_expr(&generator.iter);
+            self.visit_comprehension_target(&generator.target);
+            for condition in &generator.ifs {
+                self.visit_expr(condition);
+            }
+        }
+        for element in elements {
+            self.visit_expr(element);
+        }
+        let id = self.pop_scope();
+        let is_async = self.scopes[id].coroutine && kind != ComprehensionKind::Generator;
+        if !is_async {
+            return;
+        }
+        let enclosing = self.current();
+        if !enclosing.is_async_def() && !matches!(enclosing.kind, ScopeKind::Comprehension(_)) {
+            self.fail(
+                "asynchronous comprehension outside of an asynchronous function".to_string(),
+                expr.range(),
+            );
+            return;
+        }
+        self.current_mut().coroutine = true;
+    }
+
+    /// A walrus inside a comprehension binds in the nearest enclosing
+    /// function or module scope.
+    fn visit_named_target(&mut self, named: &ast::ExprNamed) {
+        let Expr::Name(target) = named.target.as_ref() else {
+            self.visit_expr(&named.target);
+            return;
+        };
+        let name = target.id.as_str();
+        if !matches!(self.current().kind, ScopeKind::Comprehension(_)) {
+            self.current_mut().add(name, DEF_LOCAL);
+            return;
+        }
+        for index in (0..self.stack.len()).rev() {
+            let id = self.stack[index];
+            match self.scopes[id].kind {
+                ScopeKind::Comprehension(_) => {
+                    if self.scopes[id].lookup(name) & DEF_COMP_ITER != 0 {
+                        self.fail(
+                            format!(
+                                "assignment expression cannot rebind comprehension iteration variable '{name}'"
+                            ),
+                            named.range(),
+                        );
+                        return;
+                    }
+                }
+                ScopeKind::Class => {
+                    self.fail(
+                        "assignment expression within a comprehension cannot be used in a class body"
+                            .to_string(),
+                        named.range(),
+                    );
+                    return;
+                }
+                ScopeKind::TypeParams | ScopeKind::TypeVariable(_) | ScopeKind::TypeAlias => {
+                    let context = match self.scopes[id].kind {
+                        ScopeKind::TypeParams => "within the definition of a generic",
+                        ScopeKind::TypeAlias => "in a type alias",
+                        _ => "in a TypeVar bound",
+                    };
+                    self.fail(
+                        format!(
+                            "assignment expression within a comprehension cannot be used {context}"
+                        ),
+                        named.range(),
+                    );
+                    return;
+                }
+                // CPython looks straight through annotation scopes here.
+                ScopeKind::Annotation => {}
+                ScopeKind::Module | ScopeKind::Function { .. } | ScopeKind::Lambda => {
+                    self.scopes[id].add(name, DEF_LOCAL);
+                    return;
+                }
+            }
+        }
+    }
+
+    /// Resolves `nonlocal` declarations against the names bound in enclosing
+    /// function scopes, like CPython's `analyze_block`. `type_params` holds
+    /// the enclosing type parameters no nearer scope rebinds.
+    fn analyze(
+        &self,
+        id: usize,
+        bound: Option<&HashSet<String>>,
+        type_params: &HashSet<String>,
+    ) -> Result<(), Diagnostic> {
+        let scope = &self.scopes[id];
+        let mut child_type_params = type_params.clone();
+        let directive = |name: &str| scope.directives.get(name).copied().unwrap_or_default();
+        let mut bound = bound.cloned();
+        let child_bound = if scope.kind == ScopeKind::Class {
+            let mut child_bound = bound.clone().unwrap_or_default();
+            child_bound.insert("__class__".to_string());
+            child_bound.insert("__classdict__".to_string());
+            Some(child_bound)
+        } else {
+            None
+        };
+        for (name, flags) in &scope.symbols {
+            if flags & DEF_GLOBAL != 0 && flags & DEF_NONLOCAL != 0 {
+                return Err(Diagnostic::syntax(
+                    format!("name '{name}' is nonlocal and global"),
+                    directive(name),
+                ));
+            }
+            if flags & DEF_NONLOCAL != 0 {
+                match &bound {
+                    None => {
+                        return Err(Diagnostic::syntax(
+                            "nonlocal declaration not allowed at module level",
+                            directive(name),
+                        ))
+                    }
+                    Some(bound) if !bound.contains(name) => {
+                        return Err(Diagnostic::syntax(
+                            format!("no binding for nonlocal '{name}' found"),
+                            directive(name),
+                        ))
+                    }
+                    Some(_) if type_params.contains(name) => {
+                        return Err(Diagnostic::syntax(
+                            format!("nonlocal binding not allowed for type parameter '{name}'"),
+                            directive(name),
+                        ))
+                    }
+                    Some(_) => {}
+                }
+            }
+            if flags & DEF_GLOBAL != 0 {
+                if let Some(bound) = &mut bound {
+                    bound.remove(name);
+                }
+            } else if flags & DEF_NONLOCAL == 0 && flags & DEF_BOUND != 0 {
+                if flags & DEF_TYPE_PARAM != 0 {
+                    child_type_params.insert(name.clone());
+                } else {
+                    child_type_params.remove(name);
+                }
+            }
+        }
+        let child_bound = child_bound.unwrap_or_else(|| {
+            let mut child_bound = bound.unwrap_or_default();
+            if scope.kind.is_function_like() {
+                child_bound.extend(
+                    scope
+                        .symbols
+                        .iter()
+                        .filter(|(_, flags)| {
+                            flags & DEF_BOUND != 0 && flags & (DEF_GLOBAL | DEF_NONLOCAL) == 0
+                        })
+                        .map(|(name, _)| name.clone()),
+                );
+            }
+            child_bound
+        });
+        for child in &scope.children {
+            self.analyze(*child, Some(&child_bound), &child_type_params)?;
+        }
+        Ok(())
+    }
+
+    fn check_async_generator_returns(&mut self) {
+        let ranges = self
+            .scopes
+            .iter()
+            .filter(|scope| scope.kind == ScopeKind::Function { is_async: true } && scope.generator)
+            .flat_map(|scope| scope.value_returns.iter().copied())
+            .collect::<Vec<_>>();
+        for range in ranges {
+            self.compile_error("'return' with value in async generator", range);
+        }
+    }
+}
+
+impl<'a> Visitor<'a> for SyntaxChecker {
+    fn visit_stmt(&mut self, stmt: &'a Stmt) {
+        if self.error.is_some() {
+            return;
+        }
+        match stmt {
+            Stmt::FunctionDef(function_def) => self.visit_function_def(function_def),
+            Stmt::ClassDef(class_def) => self.visit_class_def(class_def),
+            Stmt::Global(global) => self.visit_directive(stmt, &global.names, true),
+            Stmt::Nonlocal(nonlocal) => self.visit_directive(stmt, &nonlocal.names, false),
+            Stmt::AnnAssign(ann_assign) => self.visit_ann_assign(stmt, ann_assign),
+            Stmt::Import(import) => {
+                for alias in &import.names {
+                    self.visit_alias(stmt, alias);
+                }
+            }
+            Stmt::ImportFrom(import_from) => {
+                for alias in &import_from.names {
+                    self.visit_alias(stmt, alias);
+                }
+            }
+            Stmt::TypeAlias(type_alias) => self.visit_type_alias(type_alias),
+            Stmt::Return(ret) => {
+                if !self.current().kind.is_function_like() {
+                    self.compile_error("'return' outside function", stmt.range());
+                }
+                self.check_except_star_exit(stmt);
+                if let Some(value) = &ret.value {
+                    self.current_mut().value_returns.push(stmt.range());
+                    self.visit_expr(value);
+                }
+            }
+            Stmt::Break(_) => {
+                if !self.check_except_star_exit(stmt) && self.current().loop_depth == 0 {
+                    self.compile_error("'break' outside loop", stmt.range());
+                }
+            }
+            Stmt::Continue(_) => {
+                if !self.check_except_star_exit(stmt) && self.current().loop_depth == 0 {
+                    self.compile_error("'continue' not properly in loop", stmt.range());
+                }
+            }
+            Stmt::For(for_stmt) => {
+                if for_stmt.is_async {
+                    self.require_async_function("'async for' outside async function", stmt.range());
+                }
+                self.visit_expr(&for_stmt.target);
+                self.visit_expr(&for_stmt.iter);
+                self.visit_loop_body(&for_stmt.body);
+                self.visit_body(&for_stmt.orelse);
+            }
+            Stmt::While(while_stmt) => {
+                self.visit_expr(&while_stmt.test);
+                self.visit_loop_body(&while_stmt.body);
+                self.visit_body(&while_stmt.orelse);
+            }
+            Stmt::With(with_stmt) => {
+                if with_stmt.is_async {
+                    self.require_async_function(
+                        "'async with' outside async function",
+                        stmt.range(),
+                    );
+                }
+                walk_stmt(self, stmt);
+            }
+            Stmt::Try(try_stmt) if try_stmt.is_star => self.visit_try_star(try_stmt),
+            _ => walk_stmt(self, stmt),
+        }
+    }
+
+    fn visit_expr(&mut self, expr: &'a Expr) {
+        if self.error.is_some() {
+            return;
+        }
+        match expr {
+            Expr::Name(name) => {
+                let flags = match name.ctx {
+                    ExprContext::Load => USE,
+                    _ => self.store_flags,
+                };
+                self.current_mut().add(name.id.as_str(), flags);
+            }
+            Expr::Named(named) => {
+                if self.reject_in_annotation_scope("named expression", expr) {
+                    return;
+                }
+                self.visit_named_target(named);
+                self.visit_expr(&named.value);
+            }
+            Expr::Lambda(lambda) => self.visit_lambda(lambda),
+            Expr::ListComp(comp) => self.visit_comprehension_scope(
+                expr,
+                ComprehensionKind::List,
+                &comp.generators,
+                &[comp.elt.as_ref()],
+            ),
+            Expr::SetComp(comp) => self.visit_comprehension_scope(
+                expr,
+                ComprehensionKind::Set,
+                &comp.generators,
+                &[comp.elt.as_ref()],
+            ),
+            Expr::DictComp(comp) => self.visit_comprehension_scope(
+                expr,
+                ComprehensionKind::Dict,
+                &comp.generators,
+                &[comp.key.as_ref(), comp.value.as_ref()],
+            ),
+            Expr::Generator(generator) => self.visit_comprehension_scope(
+                expr,
+                ComprehensionKind::Generator,
+                &generator.generators,
+                &[generator.elt.as_ref()],
+            ),
+            Expr::Yield(_) | Expr::YieldFrom(_) => {
+                if self.reject_in_annotation_scope("yield expression", expr) {
+                    return;
+                }
+                match self.current().kind {
+                    ScopeKind::Comprehension(kind) => {
+                        self.fail(
+                            format!("'yield' inside {}", kind.description()),
+                            expr.range(),
+                        );
+                        return;
+                    }
+                    ScopeKind::Module | ScopeKind::Class => {
+                        self.compile_error("'yield' outside function", expr.range());
+                    }
+                    ScopeKind::Function { is_async: true } if expr.is_yield_from_expr() => {
+                        self.compile_error("'yield from' inside async function", expr.range());
+                    }
+                    ScopeKind::Function { .. }
+                    | ScopeKind::Lambda
+                    | ScopeKind::Annotation
+                    | ScopeKind::TypeParams
+                    | ScopeKind::TypeVariable(_)
+                    | ScopeKind::TypeAlias => {}
+                }
+                self.current_mut().generator = true;
+                walk_expr(self, expr);
+            }
+            Expr::Tuple(ast::ExprTuple {
+                elts,
+                ctx: ExprContext::Store,
+                ..
+            })
+            | Expr::List(ast::ExprList {
+                elts,
+                ctx: ExprContext::Store,
+                ..
+            }) => self.visit_unpack_target(expr.range(), elts),
+            Expr::Starred(starred) if starred.ctx.is_store() => {
+                self.compile_error(
+                    "starred assignment target must be in a list or tuple",
+                    expr.range(),
+                );
+                self.visit_expr(&starred.value);
+            }
+            Expr::Await(_) => {
+                if self.reject_in_annotation_scope("await expression", expr) {
+                    return;
+                }
+                match self.current().kind {
+                    ScopeKind::Module | ScopeKind::Class => {
+                        self.compile_error("'await' outside function", expr.range());
+                    }
+                    ScopeKind::Function { is_async: false } | ScopeKind::Lambda => {
+                        self.compile_error("'await' outside async function", expr.range());
+                    }
+                    ScopeKind::Function { is_async: true }
+                    | ScopeKind::Comprehension(_)
+                    | ScopeKind::Annotation
+                    | ScopeKind::TypeParams
+                    | ScopeKind::TypeVariable(_)
+                    | ScopeKind::TypeAlias => {}
+                }
+                self.current_mut().coroutine = true;
+                walk_expr(self, expr);
+            }
+            _ => walk_expr(self, expr),
+        }
+    }
+
+    fn visit_pattern(&mut self, pattern: &'a Pattern) {
+        let name = match pattern {
+            Pattern::MatchAs(pattern) => pattern.name.as_ref(),
+            Pattern::MatchStar(pattern) => pattern.name.as_ref(),
+            Pattern::MatchMapping(pattern) => pattern.rest.as_ref(),
+            _ => None,
+        };
+        if let Some(name) = name {
+            self.current_mut().add(name.as_str(), DEF_LOCAL);
+        }
+        walk_pattern(self, pattern);
+    }
+
+    fn visit_except_handler(&mut self, except_handler: &'a ExceptHandler) {
+        let ExceptHandler::ExceptHandler(handler) = except_handler;
+        if let Some(name) = &handler.name {
+            self.current_mut().add(name.as_str(), DEF_LOCAL);
+        }
+        walk_except_handler(self, except_handler);
+    }
+}
+
+#[cfg(test)]
+mod test;
@@ -0,0 +1,269 @@
+use crate::check_syntax;
+use crate::diagnostic::{source_location, DiagnosticKind};
+
+/// Returns the message and one-based line of the first error, if any.
+fn check(source: &str) -> Option<(String, usize)> {
+    check_syntax(source).err().map(|err| {
+        let diagnostic = err.to_diagnostic();
+        assert_eq!(diagnostic.kind, DiagnosticKind::Syntax);
+        let range = diagnostic.range.expect("syntax errors are located");
+        (
+            diagnostic.message,
+            source_location(source, range.start()).line,
+        )
+    })
+}
+
+fn assert_error(source: &str, message: &str, line: usize) {
+    assert_eq!(check(source), Some((message.to_string(), line)), "{source}");
+}
+
+#[test]
+fn accepts_valid_scoping() {
+    let source = "\
+x = 1
+def outer():
+    y = 1
+    class C:
+        def method(self):
+            nonlocal y
+            global x
+            y = x
+    async def agen():
+        yield [await z for z in y]
+        return
+    def gen():
+        return (yield)
+    for item in y:
+        if item:
+            break
+        continue
+    return [(w := i) for i in y], w
+def imports_then_declares():
+    import os
+    global os
+";
+    assert_eq!(check(source), None);
+}
+
+#[test]
+fn rejects_bad_global_and_nonlocal_declarations() {
+    assert_error(
+        "nonlocal x\n",
+        "nonlocal declaration not allowed at module level",
+        1,
+    );
+    assert_error(
+        "def f():\n    print(x)\n    global x\n",
+        "name 'x' is used prior to global declaration",
+        3,
+    );
+    assert_error(
+        "def f():\n    x = 1\n    global x\n",
+        "name 'x' is assigned to before global declaration",
+        3,
+    );
+    assert_error(
+        "def f(x):\n    global x\n",
+        "name 'x' is parameter and global",
+        2,
+    );
+    assert_error(
+        "def f(x):\n    def g():\n        nonlocal y\n",
+        "no binding for nonlocal 'y' found",
+        3,
+    );
+    assert_error(
+        "def f():\n    x = 1\n    def g():\n        global x\n        nonlocal x\n",
+        "name 'x' is nonlocal and global",
+        4,
+    );
+    assert_error(
+        "class C:\n    x = 1\n    def m(self):\n        nonlocal x\n",
+        "no binding for nonlocal 'x' found",
+        4,
+    );
+}
+
+#[test]
+fn rejects_misplaced_yield_await_and_return() {
+    assert_error(
+        "def f():\n    return [(yield x) for x in y]\n",
+        "'yield' inside list comprehension",
+        2,
+    );
+    assert_error(
+        "def f():\n    return ((yield x) for x in y)\n",
+        "'yield' inside generator expression",
+        2,
+    );
+    assert_error(
+        "async def f():\n    yield 1\n    return 2\n",
+        "'return' with value in async generator",
+        3,
+    );
+    assert_error(
+        "def f():\n    await x\n",
+        "'await' outside async function",
+        2,
+    );
+    assert_error("await x\n", "'await' outside function", 1);
+    assert_error(
+        "def f():\n    return [await x for x in y]\n",
+        "asynchronous comprehension outside of an asynchronous function",
+        2,
+    );
+    assert_error("return 1\n", "'return' outside function", 1);
+    assert_error("class C:\n    yield 1\n", "'yield' outside function", 2);
+    assert_error(
+        "def f():\n    async with x:\n        pass\n",
+        "'async with' outside async function",
+        2,
+    );
+}
+
+#[test]
+fn rejects_duplicate_parameters_and_loop_control() {
+    assert_error(
+        "def f(a, *, b, a=1):\n    pass\n",
+        "duplicate argument 'a' in function definition",
+        1,
+    );
+    assert_error(
+        "f = lambda a, a: a\n",
+        "duplicate argument 'a' in function definition",
+        1,
+    );
+    assert_error(
+        "while x:\n    def f():\n        break\n",
+        "'break' outside loop",
+        3,
+    );
+    assert_error(
+        "for x in y:\n    pass\nelse:\n    continue\n",
+        "'continue' not properly in loop",
+        4,
+    );
+}
+
+#[test]
+fn symbol_table_errors_take_priority_over_compiler_errors() {
+    assert_error(
+        "return 1\ndef f():\n    x = 1\n    global x\n",
+        "name 'x' is assigned to before global declaration",
+        4,
+    );
+}
+
+#[test]
+fn rejects_walrus_rebinding_comprehension_variable() {
+    assert_error(
+        "[i := 0 for i in range(3)]\n",
+        "assignment expression cannot rebind comprehension iteration variable 'i'",
+        1,
+    );
+    assert_error(
+        "class C:\n    [(y := i) for i in range(3)]\n",
+        "assignment expression within a comprehension cannot be used in a class body",
+        2,
+    );
+}
+
+#[test]
+fn rejects_leaving_except_star_handler() {
+    let message = "'break', 'continue' and 'return' cannot appear in an except* block";
+    assert_error(
+        "def f():\n    try:\n        pass\n    except* ValueError:\n        return 1\n",
+        message,
+        5,
+    );
+    assert_error(
+        "for x in y:\n    try:\n        pass\n    except* ValueError:\n        continue\n",
+        message,
+        5,
+    );
+    assert_eq!(
+        check("try:\n    pass\nexcept* ValueError:\n    for x in y:\n        break\n"),
+        None
+    );
+}
+
+#[test]
+fn rejects_invalid_starred_assignment_targets() {
+    assert_error(
+        "a, *b, *c = d\n",
+        "multiple starred expressions in assignment",
+        1,
+    );
+    assert_error(
+        "x = 1\nwith f() as [*a, *b]:\n    pass\n",
+        "multiple starred expressions in assignment",
+        2,
+    );
+    assert_error(
+        "for *a in b:\n    pass\n",
+        "starred assignment target must be in a list or tuple",
+        1,
+    );
+    assert_eq!(check("a, *b = c\n[x, *y], z = w\n"), None);
+}
+
+#[test]
+fn checks_annotations_and_type_params_in_their_own_scopes() {
+    assert_error(
+        "def f(x: (yield)):\n    pass\n",
+        "yield expression cannot be used within an annotation",
+        1,
+    );
+    assert_error(
+        "async def f():\n    def g() -> (await x):\n        pass\n",
+        "await expression cannot be used within an annotation",
+        2,
+    );
+    assert_error(
+        "def f(*args: (x := int)):\n    pass\n",
+        "named expression cannot be used within an annotation",
+        1,
+    );
+    assert_error(
+        "def f[T: (yield)]():\n    pass\n",
+        "yield expression cannot be used within a TypeVar bound",
+        1,
+    );
+    assert_error(
+        "class C[T](Base[(yield)]):\n    pass\n",
+        "yield expression cannot be used within the definition of a generic",
+        1,
+    );
+    assert_error(
+        "type A = [(y := 1) for _ in ()]\n",
+        "assignment expression within a comprehension cannot be used in a type alias",
+        1,
+    );
+    assert_error(
+        "def f[T, U, T]():\n    pass\n",
+        "duplicate type parameter 'T'",
+        1,
+    );
+    assert_error(
+        "class C[__debug__]:\n    pass\n",
+        "cannot assign to __debug__",
+        1,
+    );
+    assert_error(
+        "def f[T]():\n    def g():\n        nonlocal T\n",
+        "nonlocal binding not allowed for type parameter 'T'",
+        3,
+    );
+    assert_eq!(
+        check("def f[T]():\n    T = 1\n    def g():\n        nonlocal T\n"),
+        None
+    );
+    assert_eq!(
+        check("def outer():\n    def f(x: (yield)) -> [y async for y in z]:\n        pass\n"),
+        Some((
+            "yield expression cannot be used within an annotation".to_string(),
+            2
+        ))
+    );
+}
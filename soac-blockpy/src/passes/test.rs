@@ -5,7 +5,10 @@ use crate::block_py::{
     LocatedName, NameLocation, ResolvedStorageBlock,
 };
 use crate::passes::{CoreBlockPyPassWithAwaitAndYield, ResolvedStorageBlockPyPass};
-use crate::{lower_python_to_blockpy_for_testing, LoweringResult};
+use crate::{
+    lower_python_to_blockpy_for_testing, lower_python_to_blockpy_for_testing_with_options,
+    LoweringOptions, LoweringResult, OptimizationLevel,
+};
 
 fn tracked_core_blockpy_with_await_and_yield(
     source: &str,
@@ -216,16 +219,16 @@ def fmt(value):
     let lowered = TrackedLowering::new(source);
     let core_blockpy = lowered.blockpy_text();
     assert!(core_blockpy.contains("\"value=\""), "{core_blockpy}");
-    assert!(core_blockpy.contains("repr(value)"), "{core_blockpy}");
-    assert!(core_blockpy.contains("format("), "{core_blockpy}");
+    assert!(core_blockpy.contains("FormatValue("), "{core_blockpy}");
+    assert!(core_blockpy.contains("Repr"), "{core_blockpy}");
 
     let fmt = lowered.bb_function("fmt");
     assert!(
-        function_or_constants_use_text(lowered.bb_module(), fmt, "repr"),
+        function_or_constants_use_text(lowered.bb_module(), fmt, "Repr"),
         "{fmt:?}"
     );
     assert!(
-        function_or_constants_use_text(lowered.bb_module(), fmt, "format"),
+        function_or_constants_use_text(lowered.bb_module(), fmt, "FormatValue("),
         "{fmt:?}"
     );
 }
@@ -1519,6 +1522,29 @@ def documented():
     assert_eq!(doc, "hello doc");
 }
 
+#[test]
+fn optimize_docstrings_drops_callable_doc_and_asserts() {
+    let source = r#"
+def documented(x):
+    "hello doc"
+    assert x, "x must be set"
+    return 1
+"#;
+
+    let options = LoweringOptions::default().with_optimization(OptimizationLevel::Docstrings);
+    let lowered = lower_python_to_blockpy_for_testing_with_options(source, &options)
+        .expect("transform should succeed");
+    let documented = lowered
+        .codegen_module
+        .callable_defs
+        .iter()
+        .find(|func| func.names.bind_name == "documented")
+        .expect("missing documented function");
+    assert_eq!(documented.doc, None);
+    let rendered = format!("{documented:?}");
+    assert!(!rendered.contains("AssertionError"), "{rendered}");
+}
+
 #[test]
 fn top_level_function_global_binding_moves_to_name_binding_pass() {
     let source = r#"
@@ -2292,11 +2318,11 @@ import pkg.sub as alias
 
     let module_init = lowered.bb_function("_dp_module_init");
     assert!(
-        function_or_constants_use_text(lowered.bb_module(), module_init, "import_"),
+        function_uses_text(module_init, "ImportName("),
         "{module_init:?}"
     );
     assert!(
-        function_or_constants_use_text(lowered.bb_module(), module_init, "import_attr"),
+        function_uses_text(module_init, "ImportFrom("),
         "{module_init:?}"
     );
 }
@@ -2311,15 +2337,52 @@ from pkg.mod import name as alias
 
     let module_init = lowered.bb_function("_dp_module_init");
     assert!(
-        function_or_constants_use_text(lowered.bb_module(), module_init, "import_"),
+        function_uses_text(module_init, "ImportName("),
         "{module_init:?}"
     );
     assert!(
-        function_or_constants_use_text(lowered.bb_module(), module_init, "import_attr"),
+        function_uses_text(module_init, "ImportFrom("),
         "{module_init:?}"
     );
 }
 
+#[test]
+fn class_definition_lowers_to_create_class_operation() {
+    let source = r#"
+class Box(Base, metaclass=Meta):
+    def get(self):
+        return super().get()
+"#;
+
+    let lowered = TrackedLowering::new(source);
+
+    let define_class = lowered.bb_function("_dp_define_class_Box");
+    assert!(
+        function_uses_text(define_class, "CreateClass(\"Box\""),
+        "{define_class:?}"
+    );
+    assert!(
+        !function_uses_text(define_class, "create_class"),
+        "{define_class:?}"
+    );
+}
+
+#[test]
+fn fstrings_lower_to_format_value_and_build_string_operations() {
+    let source = r#"
+def render(name, width):
+    return f"{name!r}: {width:>{width}}"
+"#;
+
+    let lowered = TrackedLowering::new(source);
+
+    let render = lowered.bb_function("render");
+    assert!(function_uses_text(render, "FormatValue("), "{render:?}");
+    assert!(function_uses_text(render, "Repr"), "{render:?}");
+    assert!(function_uses_text(render, "BuildString("), "{render:?}");
+    assert!(!function_uses_text(render, "format_value"), "{render:?}");
+}
+
 #[test]
 fn ast_to_ast_can_lower_type_alias_while_later_passes_still_lower_it() {
     let source = r#"
@@ -2612,12 +2675,10 @@ def run(items):
         .expect("transform should succeed")
         .expect("bb module should be available");
     let run = function_by_name(&bb_module, "run");
+    assert!(function_uses_text(run, "ForIter("), "{run:?}");
+    assert!(function_uses_text(run, "GetIter("), "{run:?}");
     assert!(
-        function_or_constants_use_text(&bb_module, run, "next_or_sentinel"),
-        "{run:?}"
-    );
-    assert!(
-        function_or_constants_use_text(&bb_module, run, "iter"),
+        !function_or_constants_use_text(&bb_module, run, "next_or_sentinel"),
         "{run:?}"
     );
     assert!(
@@ -2628,6 +2689,23 @@ def run(items):
     );
 }
 
+#[test]
+fn iter_calls_outside_for_loops_stay_plain_calls() {
+    let source = r#"
+def delegate(items):
+    yield from items
+
+def outer(items):
+    return list(x for x in items)
+"#;
+    let bb_module = tracked_name_binding_module(source)
+        .expect("transform should succeed")
+        .expect("bb module should be available");
+    for function in &bb_module.callable_defs {
+        assert!(!function_uses_text(function, "GetIter("), "{function:?}");
+    }
+}
+
 #[test]
 fn lowers_async_for_else_directly_without_completed_flag() {
     let source = r#"
@@ -3134,7 +3212,8 @@ def f():
     try:
         raise ExceptionGroup("eg", [ValueError(1)])
     except* ValueError as exc:
-        return exc
+        caught = exc
+    return caught
 "#;
     let bb_module = tracked_name_binding_module(source)
         .expect("transform should succeed")
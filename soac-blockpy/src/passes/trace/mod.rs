@@ -5,29 +5,18 @@ use crate::block_py::{
 };
 use crate::passes::{CodegenBlockPyPass, CounterBuilder};
 use std::collections::HashMap;
-use std::env;
 
+/// Which block entries the `bb_trace` pass reports.
 #[derive(Debug, Clone, PartialEq, Eq)]
-pub(crate) struct TraceConfig {
-    pub(crate) qualname_filter: Option<String>,
-    pub(crate) include_params: bool,
+pub struct TraceConfig {
+    /// Only trace the function with this qualname; `None` traces them all.
+    pub qualname_filter: Option<String>,
+    pub include_params: bool,
 }
 
-pub(crate) fn parse_trace_env() -> Option<TraceConfig> {
-    let raw = env::var("DIET_PYTHON_BB_TRACE").ok()?;
-    parse_trace_config(raw.as_str())
-}
-
-pub(crate) fn global_load_counter_instrumentation_enabled() -> bool {
-    env::var("DIET_PYTHON_GLOBAL_LOAD_COUNTERS")
-        .map(|raw| {
-            let trimmed = raw.trim();
-            !(trimmed.is_empty() || trimmed == "0")
-        })
-        .unwrap_or(false)
-}
-
-pub(crate) fn parse_trace_config(raw: &str) -> Option<TraceConfig> {
+/// Parses a `DIET_PYTHON_BB_TRACE` value: `all`, `*`, `1` or a qualname,
+/// optionally followed by `:params`. Empty and `0` turn tracing off.
+pub fn parse_trace_config(raw: &str) -> Option<TraceConfig> {
     let trimmed = raw.trim();
     if trimmed.is_empty() || trimmed == "0" {
         return None;
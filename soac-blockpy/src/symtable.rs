@@ -0,0 +1,89 @@
+//! The scopes computed by the lowering pipeline, in the shape of CPython's
+//! `symtable` module.
+//!
+//! List, set and dict comprehensions are inlined into their enclosing scope
+//! and generator expressions appear as `genexpr` tables, as in CPython 3.12+.
+//! Defs, generic classes and type aliases get the annotation and
+//! type-parameter tables CPython 3.14 builds for them, even though the pipeline
+//! evaluates those expressions eagerly or drops them. Internal `_dp_` names
+//! introduced by earlier rewrites are omitted.
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SymbolTableKind {
+    Module,
+    Class,
+    Function,
+    Annotation,
+    TypeAlias,
+    TypeParameters,
+    TypeVariable,
+}
+
+impl SymbolTableKind {
+    /// The value `symtable.SymbolTable.get_type()` returns.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Module => "module",
+            Self::Class => "class",
+            Self::Function => "function",
+            Self::Annotation => "annotation",
+            Self::TypeAlias => "type alias",
+            Self::TypeParameters => "type parameters",
+            Self::TypeVariable => "type variable",
+        }
+    }
+}
+
+/// Where a name resolves, matching the scope constants in `_symtable`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SymbolScope {
+    Local,
+    Cell,
+    Free,
+    GlobalExplicit,
+    GlobalImplicit,
+}
+
+impl SymbolScope {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Local => "local",
+            Self::Cell => "cell",
+            Self::Free => "free",
+            Self::GlobalExplicit => "global_explicit",
+            Self::GlobalImplicit => "global_implicit",
+        }
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Symbol {
+    pub name: String,
+    pub scope: SymbolScope,
+    pub is_parameter: bool,
+    pub is_referenced: bool,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SymbolTable {
+    pub kind: SymbolTableKind,
+    /// `top` for the module, `lambda` for lambdas, `__annotate__` for
+    /// annotation tables, otherwise the name of the def, class, type alias or
+    /// type parameter.
+    pub name: String,
+    /// Sorted by name.
+    pub symbols: Vec<Symbol>,
+    /// In source order.
+    pub children: Vec<SymbolTable>,
+}
+
+impl SymbolTable {
+    pub fn lookup(&self, name: &str) -> Option<&Symbol> {
+        self.symbols.iter().find(|symbol| symbol.name == name)
+    }
+
+    /// The first child table called `name`.
+    pub fn child(&self, name: &str) -> Option<&SymbolTable> {
+        self.children.iter().find(|child| child.name == name)
+    }
+}
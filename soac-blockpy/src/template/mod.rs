@@ -1,65 +1,22 @@
 #[macro_export]
 macro_rules! py_stmt_internal {
-    ($template:literal $(, $name:ident = $value:expr)* $(,)?) => {{
-        use std::collections::HashMap;
-        #[allow(unused_imports)]
-        use crate::template::{PlaceholderValue, SyntaxTemplate};
-
-        #[allow(unused_mut)]
-        let mut values: HashMap<&str, PlaceholderValue> = HashMap::new();
-        #[allow(unused_mut)]
-        let mut ids: HashMap<&str, serde_json::Value> = HashMap::new();
-        $(match $crate::template::IntoPlaceholder::into_placeholder($value) {
-            Ok(value) => { values.insert(stringify!($name), value); }
-            Err(id) => { ids.insert(stringify!($name), id); }
-        });*
-
-        static TEMPLATE: ::std::sync::LazyLock<$crate::template::SyntaxTemplate> =
-            ::std::sync::LazyLock::new(|| {
-                $crate::template::SyntaxTemplate::parse($template)
-            });
-
-        use ruff_python_ast::Stmt;
-        let template = (*TEMPLATE).clone();
-        let values = values.into_iter().map(|(name, value)| (name.to_string(), value)).collect();
-        let ids = ids.into_iter().map(|(name, value)| (name.to_string(), value)).collect();
-        Stmt::from(template.instantiate(values, ids))
-    }};
+    ($template:literal $(, $name:ident = $value:expr)* $(,)?) => {
+        ::soac_macros::py_template!($crate, stmt, $template $(, $name = $value)*)
+    };
 }
 
 #[macro_export]
 macro_rules! py_stmts_internal {
-    ($template:literal $(, $name:ident = $value:expr)* $(,)?) => {{
-        use std::collections::HashMap;
-        #[allow(unused_imports)]
-        use crate::template::{PlaceholderValue, SyntaxTemplate};
-
-        #[allow(unused_mut)]
-        let mut values: HashMap<&str, PlaceholderValue> = HashMap::new();
-        #[allow(unused_mut)]
-        let mut ids: HashMap<&str, serde_json::Value> = HashMap::new();
-        $(match $crate::template::IntoPlaceholder::into_placeholder($value) {
-            Ok(value) => { values.insert(stringify!($name), value); }
-            Err(id) => { ids.insert(stringify!($name), id); }
-        });*
-
-        static TEMPLATE: ::std::sync::LazyLock<$crate::template::SyntaxTemplate> =
-            ::std::sync::LazyLock::new(|| {
-                $crate::template::SyntaxTemplate::parse($template)
-            });
-
-        let template = (*TEMPLATE).clone();
-        let values = values.into_iter().map(|(name, value)| (name.to_string(), value)).collect();
-        let ids = ids.into_iter().map(|(name, value)| (name.to_string(), value)).collect();
-        template.instantiate_suite(values, ids)
-    }};
+    ($template:literal $(, $name:ident = $value:expr)* $(,)?) => {
+        ::soac_macros::py_template!($crate, stmts, $template $(, $name = $value)*)
+    };
 }
 
 #[macro_export]
 macro_rules! py_expr {
     ($template:literal $(, $name:ident = $value:expr)* $(,)?) => {{
         use ruff_python_ast::{self as ast, Stmt};
-        let stmt = $crate::py_stmt_internal!($template $(, $name = $value)*);
+        let stmt: Stmt = ::soac_macros::py_template!($crate, expr, $template $(, $name = $value)*);
         match stmt {
             Stmt::Expr(ast::StmtExpr { value, .. }) => *value,
             other => {
@@ -99,8 +56,8 @@ macro_rules! py_stmt_typed {
 }
 
 use crate::passes::ast_to_ast::body::Suite;
+use crate::passes::ast_to_ast::simplify::flatten;
 use crate::transformer::{walk_expr, walk_keyword, walk_parameter, walk_stmt, Transformer};
-use crate::{namegen::fresh_name, passes::ast_to_ast::simplify::flatten};
 use regex::Regex;
 use ruff_python_ast::{self as ast, DictItem, Expr, Stmt};
 use ruff_python_parser::parse_expression;
@@ -203,51 +160,91 @@ fn expand_body_stmt(stmt: Stmt) -> Vec<Stmt> {
     vec![stmt]
 }
 
-pub(crate) trait IntoPlaceholder {
-    fn into_placeholder(self) -> Result<PlaceholderValue, Value>;
+/// Values accepted by `{name:expr}` and `{name:dict}` placeholders.
+pub(crate) trait IntoExprPlaceholder {
+    fn into_expr_placeholder(self) -> PlaceholderValue;
+}
+
+/// Values accepted by `{name:stmt}` placeholders.
+pub(crate) trait IntoStmtPlaceholder {
+    fn into_stmt_placeholder(self) -> PlaceholderValue;
+}
+
+/// Values accepted by `{name:id}` placeholders.
+pub(crate) trait IntoIdPlaceholder {
+    fn into_id_placeholder(self) -> Value;
+}
+
+/// Values accepted by `{name:literal}` placeholders.
+pub(crate) trait IntoLiteralPlaceholder {
+    fn into_literal_placeholder(self) -> Value;
+}
+
+impl IntoExprPlaceholder for Expr {
+    fn into_expr_placeholder(self) -> PlaceholderValue {
+        PlaceholderValue::Expr(Box::new(self))
+    }
+}
+
+impl IntoExprPlaceholder for Box<Expr> {
+    fn into_expr_placeholder(self) -> PlaceholderValue {
+        PlaceholderValue::Expr(self)
+    }
+}
+
+impl IntoStmtPlaceholder for Expr {
+    fn into_stmt_placeholder(self) -> PlaceholderValue {
+        PlaceholderValue::Expr(Box::new(self))
+    }
 }
 
-impl IntoPlaceholder for Expr {
-    fn into_placeholder(self) -> Result<PlaceholderValue, Value> {
-        Ok(PlaceholderValue::Expr(Box::new(self)))
+impl IntoStmtPlaceholder for Box<Expr> {
+    fn into_stmt_placeholder(self) -> PlaceholderValue {
+        PlaceholderValue::Expr(self)
     }
 }
 
-impl IntoPlaceholder for Box<Expr> {
-    fn into_placeholder(self) -> Result<PlaceholderValue, Value> {
-        Ok(PlaceholderValue::Expr(self))
+impl IntoIdPlaceholder for &str {
+    fn into_id_placeholder(self) -> Value {
+        Value::String(self.to_string())
     }
 }
 
-impl IntoPlaceholder for &str {
-    fn into_placeholder(self) -> Result<PlaceholderValue, Value> {
-        Err(Value::String(self.to_string()))
+impl IntoIdPlaceholder for String {
+    fn into_id_placeholder(self) -> Value {
+        Value::String(self)
     }
 }
 
-impl IntoPlaceholder for String {
-    fn into_placeholder(self) -> Result<PlaceholderValue, Value> {
-        Err(Value::String(self))
+impl IntoLiteralPlaceholder for &str {
+    fn into_literal_placeholder(self) -> Value {
+        Value::String(self.to_string())
     }
 }
 
-impl IntoPlaceholder for Stmt {
-    fn into_placeholder(self) -> Result<PlaceholderValue, Value> {
-        Ok(PlaceholderValue::Stmt(expand_body_stmt(self)))
+impl IntoLiteralPlaceholder for String {
+    fn into_literal_placeholder(self) -> Value {
+        Value::String(self)
     }
 }
 
-impl IntoPlaceholder for Box<Stmt> {
-    fn into_placeholder(self) -> Result<PlaceholderValue, Value> {
-        Ok(PlaceholderValue::Stmt(expand_body_stmt(*self)))
+impl IntoStmtPlaceholder for Stmt {
+    fn into_stmt_placeholder(self) -> PlaceholderValue {
+        PlaceholderValue::Stmt(expand_body_stmt(self))
+    }
+}
+
+impl IntoStmtPlaceholder for Box<Stmt> {
+    fn into_stmt_placeholder(self) -> PlaceholderValue {
+        PlaceholderValue::Stmt(expand_body_stmt(*self))
     }
 }
 
 macro_rules! impl_into_placeholder_for_stmt {
     ($($ty:ty),* $(,)?) => {
-        $(impl IntoPlaceholder for $ty {
-            fn into_placeholder(self) -> Result<PlaceholderValue, Value> {
-                Ok(PlaceholderValue::Stmt(expand_body_stmt(self.into())))
+        $(impl IntoStmtPlaceholder for $ty {
+            fn into_stmt_placeholder(self) -> PlaceholderValue {
+                PlaceholderValue::Stmt(expand_body_stmt(self.into()))
             }
         })*
     };
@@ -281,30 +278,30 @@ impl_into_placeholder_for_stmt!(
     ast::StmtIpyEscapeCommand,
 );
 
-impl IntoPlaceholder for &Suite {
-    fn into_placeholder(self) -> Result<PlaceholderValue, Value> {
-        Ok(PlaceholderValue::Stmt(self.to_vec()))
+impl IntoStmtPlaceholder for &Suite {
+    fn into_stmt_placeholder(self) -> PlaceholderValue {
+        PlaceholderValue::Stmt(self.to_vec())
     }
 }
 
-impl IntoPlaceholder for Vec<Stmt> {
-    fn into_placeholder(self) -> Result<PlaceholderValue, Value> {
+impl IntoStmtPlaceholder for Vec<Stmt> {
+    fn into_stmt_placeholder(self) -> PlaceholderValue {
         let stmts = self
             .into_iter()
             .flat_map(expand_body_stmt)
             .collect::<Vec<_>>();
         if stmts.is_empty() {
-            return Ok(PlaceholderValue::Stmt(vec![Stmt::Pass(ast::StmtPass {
+            return PlaceholderValue::Stmt(vec![Stmt::Pass(ast::StmtPass {
                 node_index: Default::default(),
                 range: Default::default(),
-            })]));
+            })]);
         }
-        Ok(PlaceholderValue::Stmt(stmts))
+        PlaceholderValue::Stmt(stmts)
     }
 }
 
-impl IntoPlaceholder for std::vec::IntoIter<Stmt> {
-    fn into_placeholder(self) -> Result<PlaceholderValue, Value> {
+impl IntoStmtPlaceholder for std::vec::IntoIter<Stmt> {
+    fn into_stmt_placeholder(self) -> PlaceholderValue {
         let mut stmts: Vec<Stmt> = self.flat_map(expand_body_stmt).collect();
         if stmts.is_empty() {
             stmts.push(Stmt::Pass(ast::StmtPass {
@@ -312,36 +309,34 @@ impl IntoPlaceholder for std::vec::IntoIter<Stmt> {
                 range: Default::default(),
             }));
         }
-        Ok(PlaceholderValue::Stmt(stmts))
+        PlaceholderValue::Stmt(stmts)
     }
 }
 
-impl<K> IntoPlaceholder for Vec<(K, Expr)>
+impl<K> IntoExprPlaceholder for Vec<(K, Expr)>
 where
     K: Into<String>,
 {
-    fn into_placeholder(self) -> Result<PlaceholderValue, Value> {
-        DictEntries(self).into_placeholder()
+    fn into_expr_placeholder(self) -> PlaceholderValue {
+        DictEntries(self).into_expr_placeholder()
     }
 }
 
-impl<K, I> IntoPlaceholder for DictEntries<I>
+impl<K, I> IntoExprPlaceholder for DictEntries<I>
 where
     I: IntoIterator<Item = (K, Expr)>,
     K: Into<String>,
 {
-    fn into_placeholder(self) -> Result<PlaceholderValue, Value> {
-        Ok(PlaceholderValue::Expr(Box::new(dict_expr_from_entries(
-            self.0,
-        ))))
+    fn into_expr_placeholder(self) -> PlaceholderValue {
+        PlaceholderValue::Expr(Box::new(dict_expr_from_entries(self.0)))
     }
 }
 
 macro_rules! impl_into_placeholder_for_signed {
     ($($ty:ty),*) => {
-        $(impl IntoPlaceholder for $ty {
-            fn into_placeholder(self) -> Result<PlaceholderValue, Value> {
-                Err(Value::Number(serde_json::Number::from(self as i64)))
+        $(impl IntoLiteralPlaceholder for $ty {
+            fn into_literal_placeholder(self) -> Value {
+                Value::Number(serde_json::Number::from(self as i64))
             }
         })*
     };
@@ -349,9 +344,9 @@ macro_rules! impl_into_placeholder_for_signed {
 
 macro_rules! impl_into_placeholder_for_unsigned {
     ($($ty:ty),*) => {
-        $(impl IntoPlaceholder for $ty {
-            fn into_placeholder(self) -> Result<PlaceholderValue, Value> {
-                Err(Value::Number(serde_json::Number::from(self as u64)))
+        $(impl IntoLiteralPlaceholder for $ty {
+            fn into_literal_placeholder(self) -> Value {
+                Value::Number(serde_json::Number::from(self as u64))
             }
         })*
     };
@@ -360,9 +355,9 @@ macro_rules! impl_into_placeholder_for_unsigned {
 impl_into_placeholder_for_signed!(i8, i16, i32, i64, isize);
 impl_into_placeholder_for_unsigned!(u8, u16, u32, u64, usize);
 
-impl IntoPlaceholder for bool {
-    fn into_placeholder(self) -> Result<PlaceholderValue, Value> {
-        Err(Value::Bool(self))
+impl IntoLiteralPlaceholder for bool {
+    fn into_literal_placeholder(self) -> Value {
+        Value::Bool(self)
     }
 }
 
@@ -372,7 +367,6 @@ pub(crate) fn var_for_placeholder(name: &str, ty: PlaceholderType) -> String {
         PlaceholderType::Stmt => format!("_dp_placeholder_stmt_{}__", name),
         PlaceholderType::Identifier => format!("_dp_placeholder_id_{}__", name),
         PlaceholderType::Literal => format!("_dp_placeholder_literal_{}__", name),
-        PlaceholderType::TmpName => format!("_dp_placeholder_tmpname_{}__", name),
         PlaceholderType::Dict => format!("_dp_placeholder_dict_{}__", name),
     }
 }
@@ -383,7 +377,6 @@ pub(crate) enum PlaceholderType {
     Stmt,
     Identifier,
     Literal,
-    TmpName,
     Dict,
 }
 
@@ -403,7 +396,6 @@ impl SyntaxTemplate {
                     "stmt" => PlaceholderType::Stmt,
                     "id" => PlaceholderType::Identifier,
                     "literal" => PlaceholderType::Literal,
-                    "tmpname" => PlaceholderType::TmpName,
                     "dict" => PlaceholderType::Dict,
                     other => panic!("unknown placeholder type `{other}` for `{name}`"),
                 };
@@ -452,7 +444,6 @@ impl SyntaxTemplate {
 struct PlaceholderReplacer {
     values: HashMap<String, PlaceholderValue>,
     ids: HashMap<String, Value>,
-    tmpnames: HashMap<String, String>,
     used_ids: HashSet<String>,
     errors: Vec<String>,
 }
@@ -462,7 +453,6 @@ impl PlaceholderReplacer {
         Self {
             values,
             ids,
-            tmpnames: HashMap::new(),
             used_ids: HashSet::new(),
             errors: Vec::new(),
         }
@@ -475,7 +465,6 @@ impl PlaceholderReplacer {
                 "stmt" => PlaceholderType::Stmt,
                 "id" => PlaceholderType::Identifier,
                 "literal" => PlaceholderType::Literal,
-                "tmpname" => PlaceholderType::TmpName,
                 "dict" => PlaceholderType::Dict,
                 other => panic!("unknown placeholder type `{other}`"),
             };
@@ -506,15 +495,6 @@ impl PlaceholderReplacer {
         }
     }
 
-    fn get_tmpname(&mut self, name: &str) -> String {
-        if let Some(value) = self.tmpnames.get(name) {
-            return value.clone();
-        }
-        let value = fresh_name("tmp");
-        self.tmpnames.insert(name.to_string(), value.clone());
-        value
-    }
-
     fn replace_identifier(&mut self, identifier: &mut ast::Identifier) {
         if let Some((ty, name)) = self.parse_placeholder(identifier.id.as_str()) {
             match ty {
@@ -523,10 +503,6 @@ impl PlaceholderReplacer {
                     identifier.id = identifier_string(name, value).into();
                     return;
                 }
-                PlaceholderType::TmpName => {
-                    identifier.id = self.get_tmpname(name).into();
-                    return;
-                }
                 PlaceholderType::Literal
                 | PlaceholderType::Expr
                 | PlaceholderType::Stmt
@@ -551,10 +527,6 @@ impl PlaceholderReplacer {
                         let value = self.get_id(name);
                         result.push_str(&identifier_string(name, value));
                     }
-                    "tmpname" => {
-                        let value = self.get_tmpname(name);
-                        result.push_str(&value);
-                    }
                     other => panic!("unsupported placeholder type `{other}` in identifier"),
                 }
                 last_end = mat.end();
@@ -586,10 +558,6 @@ impl PlaceholderReplacer {
                         let value = self.get_id(placeholder);
                         result.push_str(&identifier_string(placeholder, value));
                     }
-                    "tmpname" => {
-                        let value = self.get_tmpname(placeholder);
-                        result.push_str(&value);
-                    }
                     other => panic!("unsupported placeholder type `{other}` in name"),
                 }
                 last_end = mat.end();
@@ -645,11 +613,6 @@ impl Transformer for PlaceholderReplacer {
                             *expr = identifier_expr(name, value);
                             return;
                         }
-                        PlaceholderType::TmpName => {
-                            let value = self.get_tmpname(name);
-                            *expr = identifier_expr(name, Value::String(value));
-                            return;
-                        }
                         PlaceholderType::Literal => {
                             let value = self.get_id(name);
                             *expr = literal_expr(name, value);
@@ -756,7 +719,7 @@ impl Transformer for PlaceholderReplacer {
 fn placeholder_regex() -> &'static Regex {
     static REGEX: LazyLock<Regex> = LazyLock::new(|| {
         Regex::new(
-            r"^_dp_placeholder_(?P<ty>expr|stmt|id|literal|tmpname|dict)_(?P<name>[a-zA-Z_][a-zA-Z0-9_]*)__$",
+            r"^_dp_placeholder_(?P<ty>expr|stmt|id|literal|dict)_(?P<name>[a-zA-Z_][a-zA-Z0-9_]*)__$",
         )
         .unwrap()
     });
@@ -773,7 +736,7 @@ fn placeholder_template_regex() -> &'static Regex {
 fn placeholder_text_regex() -> &'static Regex {
     static REGEX: LazyLock<Regex> = LazyLock::new(|| {
         Regex::new(
-            r"_dp_placeholder_(?P<ty>expr|stmt|id|literal|tmpname|dict)_(?P<name>[a-zA-Z_][a-zA-Z0-9_]*)__",
+            r"_dp_placeholder_(?P<ty>expr|stmt|id|literal|dict)_(?P<name>[a-zA-Z_][a-zA-Z0-9_]*)__",
         )
         .unwrap()
     });
@@ -1,3 +1,4 @@
+use super::SyntaxTemplate;
 use crate::{passes::ast_to_ast::simplify::flatten, test_util::assert_ast_eq};
 use ruff_python_ast::{
     self as ast,
@@ -5,6 +6,8 @@ use ruff_python_ast::{
     Stmt,
 };
 use ruff_python_parser::{parse_expression, parse_module};
+use serde_json::Value;
+use std::collections::HashMap;
 
 #[test]
 fn inserts_placeholder() {
@@ -214,11 +217,30 @@ fn inserts_boxed_expr() {
     assert_ast_eq(actual, expected);
 }
 
+#[test]
+fn reinstantiates_cached_template_for_each_call() {
+    let render = |name: &str| py_expr!("{name:id} + 1", name = name);
+
+    let first = render("a");
+    let second = render("b");
+
+    let expected = *parse_expression("b + 1").unwrap().into_syntax().body;
+    assert_eq!(
+        ComparableExpr::from(&second),
+        ComparableExpr::from(&expected)
+    );
+    let expected = *parse_expression("a + 1").unwrap().into_syntax().body;
+    assert_eq!(
+        ComparableExpr::from(&first),
+        ComparableExpr::from(&expected)
+    );
+}
+
 #[test]
 fn reports_missing_and_unused_placeholders_together() {
-    let result = std::panic::catch_unwind(|| {
-        let _ = py_stmt!("{missing:id}", unused = "x");
-    });
+    let template = SyntaxTemplate::parse("{missing:id}");
+    let ids = HashMap::from([("unused".to_string(), Value::String("x".to_string()))]);
+    let result = std::panic::catch_unwind(|| template.instantiate(HashMap::new(), ids));
     let err = result.expect_err("expected template instantiation to panic");
     let msg = err
         .downcast_ref::<String>()
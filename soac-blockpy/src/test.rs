@@ -1,13 +1,20 @@
-use crate::pass_tracker::{PassTracker, RecordingPassTracker};
+use crate::block_py::validate::VerifyStage;
+use crate::block_py::BlockEdge;
+use crate::diagnostic::DiagnosticKind;
+use crate::pass_tracker::{
+    ParallelTiming, PassTracker, RecordingPassTracker, VerifyingPassTracker,
+};
 use crate::passes::ast_to_ast::body::Suite;
 use crate::py_stmt;
+use crate::{lower_python_to_blockpy_for_testing, ValidationLevel};
+use std::time::Duration;
 
 #[test]
 #[should_panic(expected = "PassTracker already contains a pass named one")]
 fn pass_tracker_rejects_duplicate_names() {
     let mut tracker = RecordingPassTracker::new();
-    let _suite: Suite = tracker.run_pass("one", || vec![py_stmt!("x = 1")]);
-    let _suite: Suite = tracker.run_pass("one", || vec![py_stmt!("x = 2")]);
+    let _suite: Suite = tracker.run_pass("one", || vec![py_stmt!("x = 1")]).unwrap();
+    let _suite: Suite = tracker.run_pass("one", || vec![py_stmt!("x = 2")]).unwrap();
 }
 
 #[test]
@@ -30,7 +37,7 @@ fn pass_tracker_records_timing_without_storing_pass_value() {
 #[test]
 fn pass_tracker_renders_tracked_pass_text_for_renderable_passes() {
     let mut tracker = RecordingPassTracker::new();
-    let _suite: Suite = tracker.run_pass("one", || vec![py_stmt!("x = 1")]);
+    let _suite: Suite = tracker.run_pass("one", || vec![py_stmt!("x = 1")]).unwrap();
 
     assert_eq!(tracker.render_pass_text("one").as_deref(), Some("x = 1\n"));
     assert_eq!(
@@ -45,3 +52,239 @@ fn pass_tracker_renders_tracked_pass_text_for_renderable_passes() {
         vec!["one".to_string()]
     );
 }
+
+#[test]
+fn pass_tracker_reports_stage_that_breaks_an_invariant() {
+    let lowered = lower_python_to_blockpy_for_testing("def f(x):\n    return x\n")
+        .expect("lowering should succeed");
+    let mut module = lowered
+        .pass_tracker
+        .pass_name_binding()
+        .expect("name_binding pass should be tracked")
+        .clone();
+    let function = module
+        .callable_defs
+        .iter_mut()
+        .find(|function| function.names.qualname == "f")
+        .expect("must contain f");
+    let entry = function.blocks[0].label;
+    function.blocks[0].exc_edge = Some(BlockEdge::new(entry));
+
+    let mut tracker = RecordingPassTracker::new();
+    let err = VerifyingPassTracker::new(&mut tracker, ValidationLevel::EveryPass)
+        .run_pass("broken", || module)
+        .expect_err("broken pass output should be rejected");
+
+    assert_eq!(err.kind, DiagnosticKind::Internal);
+    assert!(
+        err.message
+            .starts_with("pass broken produced invalid output: exception dispatch"),
+        "{}",
+        err.message
+    );
+}
+
+#[test]
+fn stage_verifier_rejects_names_without_storage() {
+    let lowered = lower_python_to_blockpy_for_testing("def f(x):\n    y = x\n    return y\n")
+        .expect("lowering should succeed");
+    let mut module = lowered
+        .pass_tracker
+        .pass_name_binding()
+        .expect("name_binding pass should be tracked")
+        .clone();
+    assert_eq!(module.verify_stage(), Ok(()));
+
+    let function = module
+        .callable_defs
+        .iter_mut()
+        .find(|function| function.names.qualname == "f")
+        .expect("must contain f");
+    function
+        .storage_layout
+        .as_mut()
+        .expect("name binding should assign a storage layout")
+        .set_stack_slots(Vec::new());
+
+    let err = module
+        .verify_stage()
+        .expect_err("must reject locals without stack slots");
+    assert!(
+        err.contains(" in f resolves to ") && err.contains("which has no storage"),
+        "unexpected error: {err}"
+    );
+}
+
+fn render_lowered_passes(source: &str) -> Vec<Option<String>> {
+    let lowered = lower_python_to_blockpy_for_testing(source).expect("lowering should succeed");
+    ["ast-to-ast", "core_blockpy", "bb_codegen"]
+        .into_iter()
+        .map(|name| lowered.pass_tracker.render_pass_text(name))
+        .collect()
+}
+
+#[test]
+fn lowering_is_deterministic_across_calls_and_threads() {
+    let source = r#"
+def f(a, b, cm):
+    with cm as value:
+        x = a if b else value
+    return 1 < x < 3 and (y := x)
+
+async def g(cm):
+    async with cm:
+        return [i async for i in cm]
+"#;
+    let expected = render_lowered_passes(source);
+    let _ = render_lowered_passes("def unrelated():\n    with a, b:\n        pass\n");
+    assert_eq!(render_lowered_passes(source), expected);
+
+    let handles = (0..4)
+        .map(|_| std::thread::spawn(move || render_lowered_passes(source)))
+        .collect::<Vec<_>>();
+    for handle in handles {
+        assert_eq!(handle.join().expect("lowering thread panicked"), expected);
+    }
+}
+
+#[test]
+fn pass_tracker_ignores_parallel_timing_for_unknown_pass() {
+    let mut tracker = RecordingPassTracker::new();
+    tracker.record_parallel_timing(
+        "missing",
+        ParallelTiming {
+            threads: 2,
+            busy: Duration::ZERO,
+        },
+    );
+    assert_eq!(tracker.pass_timings().count(), 0);
+}
+
+#[test]
+fn parallel_callable_passes_record_worker_timing() {
+    let source = (0..40)
+        .map(|index| format!("def f{index}(x):\n    return x + {index}\n"))
+        .collect::<String>();
+    let lowered = lower_python_to_blockpy_for_testing(&source).expect("lowering should succeed");
+    let timings = lowered.pass_tracker.pass_timings().collect::<Vec<_>>();
+
+    for name in ["core_blockpy", "name_binding", "bb_prepared", "bb_codegen"] {
+        let timing = timings
+            .iter()
+            .find(|timing| timing.name == name)
+            .unwrap_or_else(|| panic!("missing timing for {name}"));
+        assert!(timing.parallel.is_some(), "{name} should record workers");
+    }
+    let parse = timings
+        .iter()
+        .find(|timing| timing.name == "parse")
+        .expect("missing timing for parse");
+    assert_eq!(parse.parallel, None);
+}
+
+#[test]
+fn unsupported_input_reports_located_diagnostic() {
+    let source = "x = 1\ny = 2j\n";
+    let err = match lower_python_to_blockpy_for_testing(source) {
+        Ok(_) => panic!("complex literals should not lower"),
+        Err(err) => err,
+    };
+    let diagnostic = err.to_diagnostic();
+
+    assert_eq!(diagnostic.kind, DiagnosticKind::Unsupported);
+    let location = diagnostic
+        .location(source)
+        .expect("diagnostic should have a span");
+    assert_eq!((location.line, location.column), (2, 5));
+}
+
+#[test]
+fn parse_errors_convert_to_syntax_diagnostics() {
+    let source = "x = (\n";
+    let err = match lower_python_to_blockpy_for_testing(source) {
+        Ok(_) => panic!("unbalanced parenthesis should not parse"),
+        Err(err) => err,
+    };
+    let diagnostic = err.to_diagnostic();
+
+    assert_eq!(diagnostic.kind, DiagnosticKind::Syntax);
+    assert!(diagnostic
+        .render("mod.py", source)
+        .starts_with("error[syntax-error]: "));
+}
+
+#[test]
+fn pass_tracker_records_ir_stats_for_blockpy_passes() {
+    let source = concat!(
+        "def f(xs):\n",
+        "    total = 0\n",
+        "    for x in xs:\n",
+        "        try:\n",
+        "            total += g(x)\n",
+        "        except ValueError:\n",
+        "            pass\n",
+        "    return total\n",
+    );
+    let lowered = lower_python_to_blockpy_for_testing(source).expect("lowering should succeed");
+    let tracker = &lowered.pass_tracker;
+
+    assert_eq!(tracker.pass_ir_stats("ast-to-ast"), None);
+    let core = tracker
+        .pass_ir_stats("core_blockpy")
+        .expect("core_blockpy should record IR stats");
+    assert!(core.functions >= 2, "{core:?}");
+    assert!(core.blocks >= core.functions, "{core:?}");
+    assert_eq!(
+        core.instructions,
+        core.instructions_by_kind.values().sum::<usize>()
+    );
+    assert!(core.instructions_by_kind.contains_key("Call"), "{core:?}");
+    assert!(core.exception_edges > 0, "{core:?}");
+    assert!(core.temporaries > 0, "{core:?}");
+
+    let codegen = tracker
+        .pass_ir_stats("bb_codegen")
+        .expect("bb_codegen should record IR stats");
+    assert_eq!(
+        codegen.functions,
+        lowered.codegen_module.callable_defs.len()
+    );
+}
+
+#[test]
+fn chrome_trace_lists_every_timed_pass_in_order() {
+    let lowered = lower_python_to_blockpy_for_testing("def f(x):\n    return x + 1\n")
+        .expect("lowering should succeed");
+    let trace = lowered.pass_tracker.chrome_trace();
+    let events = trace["traceEvents"]
+        .as_array()
+        .expect("trace should list events");
+
+    let spans = events
+        .iter()
+        .filter(|event| event["ph"] == "X")
+        .collect::<Vec<_>>();
+    let span_names = spans
+        .iter()
+        .map(|event| event["name"].as_str().unwrap_or_default())
+        .collect::<Vec<_>>();
+    let timing_names = lowered
+        .pass_tracker
+        .pass_timings()
+        .map(|timing| timing.name)
+        .collect::<Vec<_>>();
+    assert_eq!(span_names, timing_names);
+    assert!(spans
+        .windows(2)
+        .all(|pair| pair[0]["ts"].as_f64() <= pair[1]["ts"].as_f64()));
+
+    let bb_codegen = spans
+        .iter()
+        .find(|event| event["name"] == "bb_codegen")
+        .expect("bb_codegen span");
+    assert!(bb_codegen["args"]["ir"]["instructions"].as_u64() > Some(0));
+    assert!(bb_codegen["args"]["threads"].as_u64().is_some());
+    assert!(events
+        .iter()
+        .any(|event| event["ph"] == "C" && event["name"] == "ir size"));
+}
@@ -0,0 +1,166 @@
+//! Registry of finalized JIT code ranges.
+//!
+//! Each specialized function tags its emitted instructions with a source
+//! location equal to the ordinal of the BlockPy instruction being lowered
+//! (block order, then body order). After finalization the machine-code
+//! ranges for those ordinals are recorded here so native program counters
+//! can be attributed back to a qualname, block label and `InstrId`.
+
+use super::JitSourceMap;
+use soac_blockpy::block_py::{BlockLabel, BlockPyFunction, HasMeta, InstrId};
+use soac_blockpy::passes::CodegenBlockPyPass;
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, OnceLock};
+
+static NEXT_REGION_ID: AtomicU64 = AtomicU64::new(1);
+
+/// One lowered BlockPy instruction, indexed by its srcloc ordinal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct JitInstrSite {
+    pub block_label: BlockLabel,
+    pub instr_id: Option<InstrId>,
+    pub source_offset: Option<u32>,
+    pub line: Option<u32>,
+}
+
+/// A machine-code range `[start, end)` relative to the function entry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct JitPcRange {
+    pub start: u32,
+    pub end: u32,
+    pub site: u32,
+}
+
+/// Returns the first srcloc ordinal used by each block of `function`.
+pub(crate) fn block_srcloc_bases(function: &BlockPyFunction<CodegenBlockPyPass>) -> Vec<u32> {
+    let mut next = 0u32;
+    function
+        .blocks
+        .iter()
+        .map(|block| {
+            let base = next;
+            next += block.body.len() as u32;
+            base
+        })
+        .collect()
+}
+
+pub(crate) fn instr_sites_for_function(
+    function: &BlockPyFunction<CodegenBlockPyPass>,
+    source_map: Option<&JitSourceMap>,
+) -> Vec<JitInstrSite> {
+    let mut sites = Vec::new();
+    for block in &function.blocks {
+        for expr in &block.body {
+            let meta = expr.meta();
+            let source_offset = if meta.range.is_empty() && meta.range.start().to_u32() == 0 {
+                None
+            } else {
+                Some(meta.range.start().to_u32())
+            };
+            sites.push(JitInstrSite {
+                block_label: block.label,
+                instr_id: meta.instr_id,
+                source_offset,
+                line: source_offset.and_then(|offset| {
+                    source_map.map(|source_map| source_map.line_for_offset(offset))
+                }),
+            });
+        }
+    }
+    sites
+}
+
+#[derive(Debug)]
+pub(crate) struct JitCodeRegion {
+    pub start: usize,
+    pub size: usize,
+    pub symbol_name: String,
+    pub qualname: String,
+    pub file_name: Option<String>,
+    pub pc_ranges: Vec<JitPcRange>,
+    pub sites: Vec<JitInstrSite>,
+}
+
+/// Where a sampled program counter landed inside JIT code.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResolvedJitPc {
+    pub symbol_name: String,
+    pub qualname: String,
+    pub file_name: Option<String>,
+    pub site: Option<JitInstrSite>,
+}
+
+struct RegisteredRegion {
+    id: u64,
+    region: JitCodeRegion,
+}
+
+fn code_regions() -> &'static Mutex<BTreeMap<usize, RegisteredRegion>> {
+    static REGIONS: OnceLock<Mutex<BTreeMap<usize, RegisteredRegion>>> = OnceLock::new();
+    REGIONS.get_or_init(|| Mutex::new(BTreeMap::new()))
+}
+
+/// Keeps a code region registered; dropping it removes the region.
+pub(crate) struct JitCodeRegionHandle {
+    start: usize,
+    id: u64,
+}
+
+pub(crate) fn register_code_region(region: JitCodeRegion) -> JitCodeRegionHandle {
+    let id = NEXT_REGION_ID.fetch_add(1, Ordering::Relaxed);
+    let start = region.start;
+    let mut regions = code_regions()
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    regions.insert(start, RegisteredRegion { id, region });
+    JitCodeRegionHandle { start, id }
+}
+
+impl Drop for JitCodeRegionHandle {
+    fn drop(&mut self) {
+        let mut regions = code_regions()
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        if regions
+            .get(&self.start)
+            .is_some_and(|registered| registered.id == self.id)
+        {
+            regions.remove(&self.start);
+        }
+    }
+}
+
+impl JitCodeRegion {
+    fn resolve(&self, pc: usize) -> ResolvedJitPc {
+        let offset = (pc - self.start) as u32;
+        let index = self
+            .pc_ranges
+            .partition_point(|range| range.start <= offset);
+        let site = index
+            .checked_sub(1)
+            .map(|index| self.pc_ranges[index])
+            .filter(|range| offset < range.end)
+            .and_then(|range| self.sites.get(range.site as usize).copied());
+        ResolvedJitPc {
+            symbol_name: self.symbol_name.clone(),
+            qualname: self.qualname.clone(),
+            file_name: self.file_name.clone(),
+            site,
+        }
+    }
+}
+
+/// Resolves `pc` against the currently registered JIT code regions.
+pub fn resolve_jit_pc(pc: usize) -> Option<ResolvedJitPc> {
+    let regions = code_regions()
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    let (_, registered) = regions.range(..=pc).next_back()?;
+    let region = &registered.region;
+    if pc >= region.start + region.size {
+        return None;
+    }
+    Some(region.resolve(pc))
+}
@@ -0,0 +1,572 @@
+//! Runtime side of the `GetIter` / `ForIter` operations.
+//!
+//! `GetIter` hands back a `FastIter` for ranges whose bounds fit in an `i64`,
+//! exact lists and tuples, and dicts and their key/value/item views. `ForIter`
+//! advances a `FastIter` with a direct call and every other iterator through
+//! its `tp_iternext` slot, so no Python-level helper runs per iteration.
+//!
+//! When the iterator is only ever stepped by its loop, compiled code skips the
+//! iterator object for ranges altogether: `range_counter` fills a
+//! `RangeCounter` on the stack and the loop counts in registers.
+
+use pyo3::ffi;
+use std::ffi::{c_int, c_void};
+use std::ptr;
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicPtr, Ordering};
+
+use super::RangeCounter;
+use crate::module_constants::load_runtime_name_owned;
+
+unsafe extern "C" {
+    static mut PyRange_Type: ffi::PyTypeObject;
+    static mut PyDictKeys_Type: ffi::PyTypeObject;
+    static mut PyDictValues_Type: ffi::PyTypeObject;
+    static mut PyDictItems_Type: ffi::PyTypeObject;
+}
+
+/// Layout shared by `dict_keys`, `dict_values` and `dict_items`.
+#[repr(C)]
+struct DictViewObject {
+    ob_base: ffi::PyObject,
+    dv_dict: *mut ffi::PyObject,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+enum FastIterKind {
+    Range,
+    List,
+    Tuple,
+    DictKeys,
+    DictValues,
+    DictItems,
+    Exhausted,
+}
+
+#[repr(C)]
+struct FastIter {
+    ob_base: ffi::PyObject,
+    kind: FastIterKind,
+    /// The list, tuple or dict being walked; null for ranges and once exhausted.
+    seq: *mut ffi::PyObject,
+    /// Next index for sequences, `PyDict_Next` position for dicts.
+    pos: ffi::Py_ssize_t,
+    /// Dict size when iteration started, or -1 after a size change was reported.
+    dict_used: ffi::Py_ssize_t,
+    next: i64,
+    step: i64,
+    /// Values left in a range, or items left in a dict.
+    remaining: u64,
+}
+
+unsafe extern "C" fn fast_iter_dealloc(obj: *mut ffi::PyObject) {
+    ffi::PyObject_GC_UnTrack(obj as *mut c_void);
+    exhaust(&mut *(obj as *mut FastIter));
+    let ty = ffi::Py_TYPE(obj);
+    if let Some(free) = (*ty).tp_free {
+        free(obj as *mut c_void);
+    }
+    ffi::Py_DECREF(ty as *mut ffi::PyObject);
+}
+
+unsafe extern "C" fn fast_iter_self(obj: *mut ffi::PyObject) -> *mut ffi::PyObject {
+    ffi::Py_INCREF(obj);
+    obj
+}
+
+unsafe extern "C" fn fast_iter_traverse(
+    obj: *mut ffi::PyObject,
+    visit: ffi::visitproc,
+    arg: *mut c_void,
+) -> c_int {
+    let iter = &*(obj as *mut FastIter);
+    if !iter.seq.is_null() {
+        let result = visit(iter.seq, arg);
+        if result != 0 {
+            return result;
+        }
+    }
+    visit(ffi::Py_TYPE(obj) as *mut ffi::PyObject, arg)
+}
+
+unsafe extern "C" fn fast_iter_clear(obj: *mut ffi::PyObject) -> c_int {
+    exhaust(&mut *(obj as *mut FastIter));
+    0
+}
+
+unsafe fn exhaust(iter: &mut FastIter) -> *mut ffi::PyObject {
+    let seq = std::mem::replace(&mut iter.seq, ptr::null_mut());
+    iter.kind = FastIterKind::Exhausted;
+    if !seq.is_null() {
+        ffi::Py_DECREF(seq);
+    }
+    ptr::null_mut()
+}
+
+/// The item a dict iterator of `kind` yields for one entry.
+unsafe fn dict_entry_item(
+    kind: FastIterKind,
+    key: *mut ffi::PyObject,
+    value: *mut ffi::PyObject,
+) -> *mut ffi::PyObject {
+    match kind {
+        FastIterKind::DictKeys => {
+            ffi::Py_INCREF(key);
+            key
+        }
+        FastIterKind::DictValues => {
+            ffi::Py_INCREF(value);
+            value
+        }
+        _ => {
+            let pair = ffi::PyTuple_New(2);
+            if pair.is_null() {
+                return ptr::null_mut();
+            }
+            ffi::Py_INCREF(key);
+            ffi::Py_INCREF(value);
+            ffi::PyTuple_SetItem(pair, 0, key);
+            ffi::PyTuple_SetItem(pair, 1, value);
+            pair
+        }
+    }
+}
+
+/// Whether the dict under a dict iterator still has the size it started with,
+/// raising `RuntimeError` the first time it does not.
+unsafe fn dict_size_unchanged(iter: &mut FastIter) -> bool {
+    if ffi::PyDict_Size(iter.seq) == iter.dict_used {
+        return true;
+    }
+    iter.dict_used = -1;
+    ffi::PyErr_SetString(
+        ffi::PyExc_RuntimeError,
+        c"dictionary changed size during iteration".as_ptr(),
+    );
+    false
+}
+
+unsafe extern "C" fn fast_iter_next(obj: *mut ffi::PyObject) -> *mut ffi::PyObject {
+    let iter = &mut *(obj as *mut FastIter);
+    match iter.kind {
+        FastIterKind::Range => {
+            if iter.remaining == 0 {
+                return exhaust(iter);
+            }
+            let value = iter.next;
+            iter.next = value.wrapping_add(iter.step);
+            iter.remaining -= 1;
+            ffi::PyLong_FromLongLong(value)
+        }
+        FastIterKind::List => {
+            // Lists can change length under the loop, so re-check every step.
+            if iter.pos >= ffi::PyList_Size(iter.seq) {
+                return exhaust(iter);
+            }
+            let item = ffi::PyList_GetItem(iter.seq, iter.pos);
+            iter.pos += 1;
+            ffi::Py_INCREF(item);
+            item
+        }
+        FastIterKind::Tuple => {
+            if iter.pos >= ffi::PyTuple_Size(iter.seq) {
+                return exhaust(iter);
+            }
+            let item = ffi::PyTuple_GetItem(iter.seq, iter.pos);
+            iter.pos += 1;
+            ffi::Py_INCREF(item);
+            item
+        }
+        FastIterKind::DictKeys | FastIterKind::DictValues | FastIterKind::DictItems => {
+            if !dict_size_unchanged(iter) {
+                return ptr::null_mut();
+            }
+            let mut key = ptr::null_mut();
+            let mut value = ptr::null_mut();
+            if ffi::PyDict_Next(iter.seq, &mut iter.pos, &mut key, &mut value) == 0 {
+                return exhaust(iter);
+            }
+            iter.remaining = iter.remaining.saturating_sub(1);
+            dict_entry_item(iter.kind, key, value)
+        }
+        FastIterKind::Exhausted => ptr::null_mut(),
+    }
+}
+
+unsafe extern "C" fn fast_iter_length_hint(
+    obj: *mut ffi::PyObject,
+    _args: *mut ffi::PyObject,
+) -> *mut ffi::PyObject {
+    let iter = &*(obj as *mut FastIter);
+    let hint = match iter.kind {
+        FastIterKind::Range => iter.remaining as ffi::Py_ssize_t,
+        FastIterKind::List => (ffi::PyList_Size(iter.seq) - iter.pos).max(0),
+        FastIterKind::Tuple => (ffi::PyTuple_Size(iter.seq) - iter.pos).max(0),
+        FastIterKind::DictKeys | FastIterKind::DictValues | FastIterKind::DictItems => {
+            if ffi::PyDict_Size(iter.seq) == iter.dict_used {
+                iter.remaining as ffi::Py_ssize_t
+            } else {
+                0
+            }
+        }
+        FastIterKind::Exhausted => 0,
+    };
+    ffi::PyLong_FromSsize_t(hint)
+}
+
+/// A new reference to `builtins.iter`, which every `__reduce__` result calls
+/// to rebuild the iterator.
+unsafe fn builtin_iter() -> *mut ffi::PyObject {
+    let builtins = ffi::PyImport_ImportModule(c"builtins".as_ptr());
+    if builtins.is_null() {
+        return ptr::null_mut();
+    }
+    let iter = ffi::PyObject_GetAttrString(builtins, c"iter".as_ptr());
+    ffi::Py_DECREF(builtins);
+    iter
+}
+
+/// The remaining items of a dict iterator as a new list, without advancing it.
+unsafe fn remaining_dict_items(iter: &mut FastIter) -> *mut ffi::PyObject {
+    if !dict_size_unchanged(iter) {
+        return ptr::null_mut();
+    }
+    let items = ffi::PyList_New(0);
+    if items.is_null() {
+        return ptr::null_mut();
+    }
+    let mut pos = iter.pos;
+    let mut key = ptr::null_mut();
+    let mut value = ptr::null_mut();
+    while ffi::PyDict_Next(iter.seq, &mut pos, &mut key, &mut value) != 0 {
+        let item = dict_entry_item(iter.kind, key, value);
+        if item.is_null() || ffi::PyList_Append(items, item) != 0 {
+            if !item.is_null() {
+                ffi::Py_DECREF(item);
+            }
+            ffi::Py_DECREF(items);
+            return ptr::null_mut();
+        }
+        ffi::Py_DECREF(item);
+    }
+    items
+}
+
+/// Pickles like the builtin iterators: `iter(seq)` positioned at the current
+/// index for lists and tuples, and `iter` over what is left otherwise.
+unsafe extern "C" fn fast_iter_reduce(
+    obj: *mut ffi::PyObject,
+    _args: *mut ffi::PyObject,
+) -> *mut ffi::PyObject {
+    let iter = &mut *(obj as *mut FastIter);
+    let iter_fn = builtin_iter();
+    if iter_fn.is_null() {
+        return ptr::null_mut();
+    }
+    let result = match iter.kind {
+        FastIterKind::List | FastIterKind::Tuple => {
+            ffi::Py_BuildValue(c"O(O)n".as_ptr(), iter_fn, iter.seq, iter.pos)
+        }
+        FastIterKind::Range => {
+            // Stop just past the last value left, which keeps every bound
+            // within the original range and so within an `i64`.
+            let stop = if iter.remaining == 0 {
+                iter.next
+            } else {
+                let last = iter
+                    .next
+                    .wrapping_add(((iter.remaining - 1) as i64).wrapping_mul(iter.step));
+                if iter.step > 0 { last + 1 } else { last - 1 }
+            };
+            let range = ffi::PyObject_CallFunction(
+                ptr::addr_of_mut!(PyRange_Type) as *mut ffi::PyObject,
+                c"LLL".as_ptr(),
+                iter.next,
+                stop,
+                iter.step,
+            );
+            if range.is_null() {
+                ptr::null_mut()
+            } else {
+                ffi::Py_BuildValue(c"O(N)".as_ptr(), iter_fn, range)
+            }
+        }
+        FastIterKind::DictKeys | FastIterKind::DictValues | FastIterKind::DictItems => {
+            let items = remaining_dict_items(iter);
+            if items.is_null() {
+                ptr::null_mut()
+            } else {
+                ffi::Py_BuildValue(c"O(N)".as_ptr(), iter_fn, items)
+            }
+        }
+        FastIterKind::Exhausted => ffi::Py_BuildValue(c"O(())".as_ptr(), iter_fn),
+    };
+    ffi::Py_DECREF(iter_fn);
+    result
+}
+
+unsafe fn create_fast_iter_type() -> *mut ffi::PyTypeObject {
+    let methods = Box::leak(Box::new([
+        ffi::PyMethodDef {
+            ml_name: c"__length_hint__".as_ptr(),
+            ml_meth: ffi::PyMethodDefPointer {
+                PyCFunction: fast_iter_length_hint,
+            },
+            ml_flags: ffi::METH_NOARGS,
+            ml_doc: ptr::null(),
+        },
+        ffi::PyMethodDef {
+            ml_name: c"__reduce__".as_ptr(),
+            ml_meth: ffi::PyMethodDefPointer {
+                PyCFunction: fast_iter_reduce,
+            },
+            ml_flags: ffi::METH_NOARGS,
+            ml_doc: ptr::null(),
+        },
+        ffi::PyMethodDef::zeroed(),
+    ]));
+    let slots = Box::leak(Box::new([
+        ffi::PyType_Slot {
+            slot: ffi::Py_tp_dealloc,
+            pfunc: fast_iter_dealloc as *mut c_void,
+        },
+        ffi::PyType_Slot {
+            slot: ffi::Py_tp_traverse,
+            pfunc: fast_iter_traverse as *mut c_void,
+        },
+        ffi::PyType_Slot {
+            slot: ffi::Py_tp_clear,
+            pfunc: fast_iter_clear as *mut c_void,
+        },
+        ffi::PyType_Slot {
+            slot: ffi::Py_tp_methods,
+            pfunc: methods.as_mut_ptr() as *mut c_void,
+        },
+        ffi::PyType_Slot {
+            slot: ffi::Py_tp_iter,
+            pfunc: fast_iter_self as *mut c_void,
+        },
+        ffi::PyType_Slot {
+            slot: ffi::Py_tp_iternext,
+            pfunc: fast_iter_next as *mut c_void,
+        },
+        ffi::PyType_Slot {
+            slot: 0,
+            pfunc: ptr::null_mut(),
+        },
+    ]));
+    let spec = Box::leak(Box::new(ffi::PyType_Spec {
+        name: c"soac.fast_iterator".as_ptr(),
+        basicsize: std::mem::size_of::<FastIter>() as i32,
+        itemsize: 0,
+        flags: (ffi::Py_TPFLAGS_DEFAULT | ffi::Py_TPFLAGS_HAVE_GC) as u32,
+        slots: slots.as_mut_ptr(),
+    }));
+    ffi::PyType_FromSpec(spec) as *mut ffi::PyTypeObject
+}
+
+/// The `FastIter` type, or null if it could not be created.
+fn fast_iter_type() -> *mut ffi::PyTypeObject {
+    static TYPE: OnceLock<usize> = OnceLock::new();
+    *TYPE.get_or_init(|| unsafe {
+        let ty = create_fast_iter_type();
+        if ty.is_null() {
+            ffi::PyErr_Clear();
+        }
+        ty as usize
+    }) as *mut ffi::PyTypeObject
+}
+
+unsafe fn new_fast_iter(kind: FastIterKind, seq: *mut ffi::PyObject) -> *mut ffi::PyObject {
+    let ty = fast_iter_type();
+    if ty.is_null() {
+        return ptr::null_mut();
+    }
+    let obj = ffi::PyType_GenericAlloc(ty, 0);
+    if obj.is_null() {
+        return ptr::null_mut();
+    }
+    let iter = &mut *(obj as *mut FastIter);
+    iter.kind = kind;
+    if !seq.is_null() {
+        ffi::Py_INCREF(seq);
+    }
+    iter.seq = seq;
+    iter.pos = 0;
+    iter.dict_used = match kind {
+        FastIterKind::DictKeys | FastIterKind::DictValues | FastIterKind::DictItems => {
+            ffi::PyDict_Size(seq)
+        }
+        _ => 0,
+    };
+    iter.remaining = iter.dict_used as u64;
+    obj
+}
+
+unsafe fn range_attr_i64(range: *mut ffi::PyObject, name: &std::ffi::CStr) -> Option<i64> {
+    let value = ffi::PyObject_GetAttrString(range, name.as_ptr());
+    if value.is_null() {
+        ffi::PyErr_Clear();
+        return None;
+    }
+    let mut overflow = 0;
+    let result = ffi::PyLong_AsLongLongAndOverflow(value, &mut overflow);
+    ffi::Py_DECREF(value);
+    if overflow != 0 || (result == -1 && !ffi::PyErr_Occurred().is_null()) {
+        ffi::PyErr_Clear();
+        return None;
+    }
+    Some(result)
+}
+
+/// Start, step and length of `range` when its start, stop and step all fit
+/// in an `i64`; every value it yields then lies between start and stop as
+/// well. Never raises.
+unsafe fn range_bounds(range: *mut ffi::PyObject) -> Option<(i64, i64, u64)> {
+    let (Some(start), Some(_stop), Some(step)) = (
+        range_attr_i64(range, c"start"),
+        range_attr_i64(range, c"stop"),
+        range_attr_i64(range, c"step"),
+    ) else {
+        return None;
+    };
+    let len = ffi::PyObject_Size(range);
+    if len < 0 {
+        ffi::PyErr_Clear();
+        return None;
+    }
+    Some((start, step, len as u64))
+}
+
+/// Builds a counter over `range` when `range_bounds` applies.
+unsafe fn new_range_iter(range: *mut ffi::PyObject) -> *mut ffi::PyObject {
+    let Some((start, step, len)) = range_bounds(range) else {
+        return ptr::null_mut();
+    };
+    let obj = new_fast_iter(FastIterKind::Range, ptr::null_mut());
+    if obj.is_null() {
+        return ptr::null_mut();
+    }
+    let iter = &mut *(obj as *mut FastIter);
+    iter.next = start;
+    iter.step = step;
+    iter.remaining = len;
+    obj
+}
+
+/// Fills `counter` to step through `iterable` when it is a range
+/// `range_bounds` applies to, returning 1; otherwise marks the counter unused
+/// and returns 0 so the loop builds an iterator object. Never raises.
+pub(super) unsafe fn range_counter(
+    iterable: *mut ffi::PyObject,
+    counter: *mut RangeCounter,
+) -> i64 {
+    let counter = &mut *counter;
+    if ffi::Py_TYPE(iterable) == ptr::addr_of_mut!(PyRange_Type) {
+        if let Some((start, step, len)) = range_bounds(iterable) {
+            counter.next = start;
+            counter.step = step;
+            counter.remaining = len as i64;
+            return 1;
+        }
+    }
+    counter.remaining = -1;
+    0
+}
+
+/// `iter(iterable)`, returning a `FastIter` where one applies.
+pub(super) unsafe fn get_iter(iterable: *mut ffi::PyObject) -> *mut ffi::PyObject {
+    let ty = ffi::Py_TYPE(iterable);
+    let fast = if ty == ptr::addr_of_mut!(PyRange_Type) {
+        new_range_iter(iterable)
+    } else if ffi::PyList_CheckExact(iterable) != 0 {
+        new_fast_iter(FastIterKind::List, iterable)
+    } else if ffi::PyTuple_CheckExact(iterable) != 0 {
+        new_fast_iter(FastIterKind::Tuple, iterable)
+    } else if ffi::PyDict_CheckExact(iterable) != 0 {
+        new_fast_iter(FastIterKind::DictKeys, iterable)
+    } else {
+        let kind = if ty == ptr::addr_of_mut!(PyDictKeys_Type) {
+            Some(FastIterKind::DictKeys)
+        } else if ty == ptr::addr_of_mut!(PyDictValues_Type) {
+            Some(FastIterKind::DictValues)
+        } else if ty == ptr::addr_of_mut!(PyDictItems_Type) {
+            Some(FastIterKind::DictItems)
+        } else {
+            None
+        };
+        match kind {
+            Some(kind) => {
+                let dict = (*(iterable as *mut DictViewObject)).dv_dict;
+                if dict.is_null() {
+                    ptr::null_mut()
+                } else {
+                    new_fast_iter(kind, dict)
+                }
+            }
+            None => ptr::null_mut(),
+        }
+    };
+    if !fast.is_null() {
+        return fast;
+    }
+    if !ffi::PyErr_Occurred().is_null() {
+        return ptr::null_mut();
+    }
+    ffi::PyObject_GetIter(iterable)
+}
+
+static ITER_COMPLETE: AtomicPtr<ffi::PyObject> = AtomicPtr::new(ptr::null_mut());
+
+/// A new reference to the runtime's `ITER_COMPLETE` sentinel. The first lookup
+/// is cached for the life of the process.
+pub(super) unsafe fn iter_complete() -> *mut ffi::PyObject {
+    let mut sentinel = ITER_COMPLETE.load(Ordering::Acquire);
+    if sentinel.is_null() {
+        let name = ffi::PyUnicode_FromString(c"ITER_COMPLETE".as_ptr());
+        if name.is_null() {
+            return ptr::null_mut();
+        }
+        sentinel = load_runtime_name_owned(name);
+        ffi::Py_DECREF(name);
+        if sentinel.is_null() {
+            return ptr::null_mut();
+        }
+        ITER_COMPLETE.store(sentinel, Ordering::Release);
+    }
+    ffi::Py_INCREF(sentinel);
+    sentinel
+}
+
+/// The next item of `iter`, or `ITER_COMPLETE` once it is exhausted.
+pub(super) unsafe fn for_iter(iter: *mut ffi::PyObject) -> *mut ffi::PyObject {
+    let ty = ffi::Py_TYPE(iter);
+    let next = if ty == fast_iter_type() {
+        fast_iter_next(iter)
+    } else {
+        match (*ty).tp_iternext {
+            Some(iternext) => iternext(iter),
+            None => {
+                let message = format!(
+                    "'for' received an object from __iter__ that does not implement __next__: {}",
+                    std::ffi::CStr::from_ptr((*ty).tp_name).to_string_lossy()
+                );
+                if let Ok(message) = std::ffi::CString::new(message) {
+                    ffi::PyErr_SetString(ffi::PyExc_TypeError, message.as_ptr());
+                }
+                return ptr::null_mut();
+            }
+        }
+    };
+    if !next.is_null() {
+        return next;
+    }
+    if !ffi::PyErr_Occurred().is_null() {
+        if ffi::PyErr_ExceptionMatches(ffi::PyExc_StopIteration) == 0 {
+            return ptr::null_mut();
+        }
+        ffi::PyErr_Clear();
+    }
+    iter_complete()
+}
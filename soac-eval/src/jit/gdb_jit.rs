@@ -0,0 +1,559 @@
+//! GDB JIT interface support.
+//!
+//! When `RuntimeOptions::gdb_jit` is set (`DIET_PYTHON_GDB_JIT`), each
+//! finalized JIT function is described by a small in-memory ELF object (a
+//! `.text` placeholder at the code address, a function symbol named after the
+//! perf symbol, and DWARF line info derived from instruction source ranges)
+//! and announced to the debugger through the `__jit_debug_register_code`
+//! protocol documented in the GDB manual.
+
+use std::ptr;
+use std::sync::{Mutex, OnceLock};
+
+const JIT_NOACTION: u32 = 0;
+const JIT_REGISTER_FN: u32 = 1;
+const JIT_UNREGISTER_FN: u32 = 2;
+
+#[repr(C)]
+struct JitCodeEntry {
+    next_entry: *mut JitCodeEntry,
+    prev_entry: *mut JitCodeEntry,
+    symfile_addr: *const u8,
+    symfile_size: u64,
+}
+
+#[repr(C)]
+struct JitDescriptor {
+    version: u32,
+    action_flag: u32,
+    relevant_entry: *mut JitCodeEntry,
+    first_entry: *mut JitCodeEntry,
+}
+
+#[allow(non_upper_case_globals)]
+#[unsafe(no_mangle)]
+static mut __jit_debug_descriptor: JitDescriptor = JitDescriptor {
+    version: 1,
+    action_flag: JIT_NOACTION,
+    relevant_entry: ptr::null_mut(),
+    first_entry: ptr::null_mut(),
+};
+
+/// The debugger sets a breakpoint here and reads `__jit_debug_descriptor`.
+#[unsafe(no_mangle)]
+#[inline(never)]
+pub extern "C" fn __jit_debug_register_code() {
+    std::hint::black_box(());
+}
+
+fn descriptor_lock() -> &'static Mutex<()> {
+    static LOCK: OnceLock<Mutex<()>> = OnceLock::new();
+    LOCK.get_or_init(|| Mutex::new(()))
+}
+
+/// Maps source byte offsets to 1-based line numbers for one module.
+#[derive(Debug, Clone)]
+pub struct JitSourceMap {
+    file_name: String,
+    line_starts: Vec<u32>,
+}
+
+impl JitSourceMap {
+    pub fn new(file_name: impl Into<String>, source: &str) -> Self {
+        let mut line_starts = vec![0];
+        for (offset, byte) in source.bytes().enumerate() {
+            if byte == b'\n' {
+                line_starts.push((offset + 1) as u32);
+            }
+        }
+        Self {
+            file_name: file_name.into(),
+            line_starts,
+        }
+    }
+
+    pub fn file_name(&self) -> &str {
+        &self.file_name
+    }
+
+    pub fn line_for_offset(&self, offset: u32) -> u32 {
+        match self.line_starts.binary_search(&offset) {
+            Ok(index) => index as u32 + 1,
+            Err(index) => index as u32,
+        }
+    }
+}
+
+/// A machine-code range tagged with the source offset set via `set_srcloc`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct JitSrcLocRange {
+    pub start: u32,
+    pub end: u32,
+    pub source_offset: u32,
+}
+
+pub(crate) struct JitDebugFunction<'a> {
+    pub symbol_name: &'a str,
+    pub code_ptr: *const u8,
+    pub code_size: usize,
+    pub srclocs: &'a [JitSrcLocRange],
+    pub source_map: Option<&'a JitSourceMap>,
+}
+
+/// Keeps a registered ELF image alive; dropping it unregisters the entry.
+pub(crate) struct GdbJitRegistration {
+    entry: Box<JitCodeEntry>,
+    _image: Box<[u8]>,
+}
+
+pub(crate) fn register_jit_function(function: &JitDebugFunction<'_>) -> Option<GdbJitRegistration> {
+    if function.code_size == 0 {
+        return None;
+    }
+    let image = build_debug_elf(function).into_boxed_slice();
+    let mut entry = Box::new(JitCodeEntry {
+        next_entry: ptr::null_mut(),
+        prev_entry: ptr::null_mut(),
+        symfile_addr: image.as_ptr(),
+        symfile_size: image.len() as u64,
+    });
+    let _guard = descriptor_lock()
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    unsafe {
+        let descriptor = &raw mut __jit_debug_descriptor;
+        let entry_ptr: *mut JitCodeEntry = &mut *entry;
+        let first = (*descriptor).first_entry;
+        (*entry_ptr).next_entry = first;
+        if !first.is_null() {
+            (*first).prev_entry = entry_ptr;
+        }
+        (*descriptor).first_entry = entry_ptr;
+        (*descriptor).relevant_entry = entry_ptr;
+        (*descriptor).action_flag = JIT_REGISTER_FN;
+        __jit_debug_register_code();
+        (*descriptor).action_flag = JIT_NOACTION;
+    }
+    Some(GdbJitRegistration {
+        entry,
+        _image: image,
+    })
+}
+
+impl Drop for GdbJitRegistration {
+    fn drop(&mut self) {
+        let _guard = descriptor_lock()
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        unsafe {
+            let descriptor = &raw mut __jit_debug_descriptor;
+            let entry_ptr: *mut JitCodeEntry = &mut *self.entry;
+            let prev = (*entry_ptr).prev_entry;
+            let next = (*entry_ptr).next_entry;
+            if prev.is_null() {
+                (*descriptor).first_entry = next;
+            } else {
+                (*prev).next_entry = next;
+            }
+            if !next.is_null() {
+                (*next).prev_entry = prev;
+            }
+            (*descriptor).relevant_entry = entry_ptr;
+            (*descriptor).action_flag = JIT_UNREGISTER_FN;
+            __jit_debug_register_code();
+            (*descriptor).action_flag = JIT_NOACTION;
+            (*descriptor).relevant_entry = ptr::null_mut();
+        }
+    }
+}
+
+const ELF_HEADER_SIZE: usize = 64;
+const ELF_SECTION_HEADER_SIZE: usize = 64;
+const ELF_SYMBOL_SIZE: usize = 24;
+
+const SHT_PROGBITS: u32 = 1;
+const SHT_SYMTAB: u32 = 2;
+const SHT_STRTAB: u32 = 3;
+const SHT_NOBITS: u32 = 8;
+const SHF_ALLOC: u64 = 0x2;
+const SHF_EXECINSTR: u64 = 0x4;
+
+#[cfg(target_arch = "aarch64")]
+const ELF_MACHINE: u16 = 183;
+#[cfg(not(target_arch = "aarch64"))]
+const ELF_MACHINE: u16 = 62;
+
+const SECTION_TEXT: u16 = 1;
+
+#[derive(Default)]
+struct ByteWriter {
+    bytes: Vec<u8>,
+}
+
+impl ByteWriter {
+    fn u8(&mut self, value: u8) {
+        self.bytes.push(value);
+    }
+
+    fn u16(&mut self, value: u16) {
+        self.bytes.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn u32(&mut self, value: u32) {
+        self.bytes.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn u64(&mut self, value: u64) {
+        self.bytes.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn cstr(&mut self, value: &str) {
+        self.bytes.extend_from_slice(value.as_bytes());
+        self.bytes.push(0);
+    }
+
+    fn uleb(&mut self, mut value: u64) {
+        loop {
+            let byte = (value & 0x7f) as u8;
+            value >>= 7;
+            if value == 0 {
+                self.bytes.push(byte);
+                return;
+            }
+            self.bytes.push(byte | 0x80);
+        }
+    }
+
+    fn sleb(&mut self, mut value: i64) {
+        loop {
+            let byte = (value & 0x7f) as u8;
+            value >>= 7;
+            let done = (value == 0 && byte & 0x40 == 0) || (value == -1 && byte & 0x40 != 0);
+            if done {
+                self.bytes.push(byte);
+                return;
+            }
+            self.bytes.push(byte | 0x80);
+        }
+    }
+
+    fn patch_u32(&mut self, at: usize, value: u32) {
+        self.bytes[at..at + 4].copy_from_slice(&value.to_le_bytes());
+    }
+
+    fn align(&mut self, alignment: usize) {
+        while self.bytes.len() % alignment != 0 {
+            self.bytes.push(0);
+        }
+    }
+}
+
+struct StringTable {
+    bytes: Vec<u8>,
+}
+
+impl StringTable {
+    fn new() -> Self {
+        Self { bytes: vec![0] }
+    }
+
+    fn add(&mut self, value: &str) -> u32 {
+        let offset = self.bytes.len() as u32;
+        self.bytes.extend_from_slice(value.as_bytes());
+        self.bytes.push(0);
+        offset
+    }
+}
+
+struct SectionSpec {
+    name: u32,
+    kind: u32,
+    flags: u64,
+    addr: u64,
+    data: Vec<u8>,
+    size: u64,
+    link: u32,
+    info: u32,
+    entsize: u64,
+}
+
+/// Collapses code ranges into `(code_offset, line)` rows, dropping ranges
+/// without a source location and consecutive rows on the same line.
+fn line_rows(srclocs: &[JitSrcLocRange], source_map: &JitSourceMap) -> Vec<(u32, u32)> {
+    let mut rows: Vec<(u32, u32)> = Vec::new();
+    for srcloc in srclocs {
+        if srcloc.start >= srcloc.end {
+            continue;
+        }
+        let line = source_map.line_for_offset(srcloc.source_offset);
+        if rows.last().is_some_and(|(_, last_line)| *last_line == line) {
+            continue;
+        }
+        rows.push((srcloc.start, line));
+    }
+    rows
+}
+
+fn debug_abbrev_section() -> Vec<u8> {
+    let mut out = ByteWriter::default();
+    // 1: DW_TAG_compile_unit, has children.
+    out.uleb(1);
+    out.uleb(0x11);
+    out.u8(1);
+    for (attr, form) in [
+        (0x25, 0x08), // DW_AT_producer, DW_FORM_string
+        (0x13, 0x05), // DW_AT_language, DW_FORM_data2
+        (0x03, 0x08), // DW_AT_name, DW_FORM_string
+        (0x11, 0x01), // DW_AT_low_pc, DW_FORM_addr
+        (0x12, 0x01), // DW_AT_high_pc, DW_FORM_addr
+        (0x10, 0x06), // DW_AT_stmt_list, DW_FORM_data4
+    ] {
+        out.uleb(attr);
+        out.uleb(form);
+    }
+    out.u16(0);
+    // 2: DW_TAG_subprogram, no children.
+    out.uleb(2);
+    out.uleb(0x2e);
+    out.u8(0);
+    for (attr, form) in [(0x03, 0x08), (0x11, 0x01), (0x12, 0x01)] {
+        out.uleb(attr);
+        out.uleb(form);
+    }
+    out.u16(0);
+    out.u8(0);
+    out.bytes
+}
+
+fn debug_info_section(function: &JitDebugFunction<'_>, file_name: &str) -> Vec<u8> {
+    let low_pc = function.code_ptr as u64;
+    let high_pc = low_pc + function.code_size as u64;
+    let mut out = ByteWriter::default();
+    out.u32(0);
+    out.u16(2);
+    out.u32(0);
+    out.u8(8);
+    out.uleb(1);
+    out.cstr("soac");
+    out.u16(0x14); // DW_LANG_Python
+    out.cstr(file_name);
+    out.u64(low_pc);
+    out.u64(high_pc);
+    out.u32(0);
+    out.uleb(2);
+    out.cstr(function.symbol_name);
+    out.u64(low_pc);
+    out.u64(high_pc);
+    out.u8(0);
+    let unit_length = (out.bytes.len() - 4) as u32;
+    out.patch_u32(0, unit_length);
+    out.bytes
+}
+
+fn debug_line_section(function: &JitDebugFunction<'_>, source_map: &JitSourceMap) -> Vec<u8> {
+    const LINE_BASE: i8 = -5;
+    const LINE_RANGE: u8 = 14;
+    const OPCODE_BASE: u8 = 13;
+
+    let mut out = ByteWriter::default();
+    out.u32(0);
+    out.u16(2);
+    let header_length_at = out.bytes.len();
+    out.u32(0);
+    let header_start = out.bytes.len();
+    out.u8(1);
+    out.u8(1);
+    out.u8(LINE_BASE as u8);
+    out.u8(LINE_RANGE);
+    out.u8(OPCODE_BASE);
+    out.bytes
+        .extend_from_slice(&[0, 1, 1, 1, 1, 0, 0, 0, 1, 0, 0, 1]);
+    out.u8(0);
+    out.cstr(source_map.file_name());
+    out.uleb(0);
+    out.uleb(0);
+    out.uleb(0);
+    out.u8(0);
+    let header_length = (out.bytes.len() - header_start) as u32;
+    out.patch_u32(header_length_at, header_length);
+
+    // DW_LNE_set_address
+    out.u8(0);
+    out.uleb(9);
+    out.u8(0x02);
+    out.u64(function.code_ptr as u64);
+    let mut address = 0u32;
+    let mut line = 1i64;
+    for (offset, row_line) in line_rows(function.srclocs, source_map) {
+        if offset > address {
+            out.u8(0x02); // DW_LNS_advance_pc
+            out.uleb(u64::from(offset - address));
+            address = offset;
+        }
+        let delta = i64::from(row_line) - line;
+        if delta != 0 {
+            out.u8(0x03); // DW_LNS_advance_line
+            out.sleb(delta);
+            line = i64::from(row_line);
+        }
+        out.u8(0x01); // DW_LNS_copy
+    }
+    let code_size = function.code_size as u32;
+    if code_size > address {
+        out.u8(0x02);
+        out.uleb(u64::from(code_size - address));
+    }
+    // DW_LNE_end_sequence
+    out.u8(0);
+    out.uleb(1);
+    out.u8(0x01);
+    let unit_length = (out.bytes.len() - 4) as u32;
+    out.patch_u32(0, unit_length);
+    out.bytes
+}
+
+/// Builds a relocatable ELF64 object describing one JIT function.
+pub(crate) fn build_debug_elf(function: &JitDebugFunction<'_>) -> Vec<u8> {
+    let mut shstrtab = StringTable::new();
+    let mut strtab = StringTable::new();
+    let file_name = function
+        .source_map
+        .map(JitSourceMap::file_name)
+        .unwrap_or("<soac-jit>");
+    let file_sym_name = strtab.add(file_name);
+    let func_sym_name = strtab.add(function.symbol_name);
+
+    let mut symtab = ByteWriter::default();
+    symtab.bytes.resize(ELF_SYMBOL_SIZE, 0);
+    // STT_FILE, STB_LOCAL, SHN_ABS
+    symtab.u32(file_sym_name);
+    symtab.u8(0x04);
+    symtab.u8(0);
+    symtab.u16(0xfff1);
+    symtab.u64(0);
+    symtab.u64(0);
+    // STT_FUNC, STB_GLOBAL; value is relative to `.text`.
+    symtab.u32(func_sym_name);
+    symtab.u8(0x12);
+    symtab.u8(0);
+    symtab.u16(SECTION_TEXT);
+    symtab.u64(0);
+    symtab.u64(function.code_size as u64);
+
+    let mut sections = vec![SectionSpec {
+        name: shstrtab.add(".text"),
+        kind: SHT_NOBITS,
+        flags: SHF_ALLOC | SHF_EXECINSTR,
+        addr: function.code_ptr as u64,
+        data: Vec::new(),
+        size: function.code_size as u64,
+        link: 0,
+        info: 0,
+        entsize: 0,
+    }];
+    let strtab_index = sections.len() as u32 + 2;
+    sections.push(SectionSpec {
+        name: shstrtab.add(".symtab"),
+        kind: SHT_SYMTAB,
+        flags: 0,
+        addr: 0,
+        size: symtab.bytes.len() as u64,
+        data: symtab.bytes,
+        link: strtab_index,
+        info: 2,
+        entsize: ELF_SYMBOL_SIZE as u64,
+    });
+    sections.push(SectionSpec {
+        name: shstrtab.add(".strtab"),
+        kind: SHT_STRTAB,
+        flags: 0,
+        addr: 0,
+        size: strtab.bytes.len() as u64,
+        data: strtab.bytes,
+        link: 0,
+        info: 0,
+        entsize: 0,
+    });
+    if let Some(source_map) = function.source_map {
+        for (name, data) in [
+            (".debug_info", debug_info_section(function, file_name)),
+            (".debug_abbrev", debug_abbrev_section()),
+            (".debug_line", debug_line_section(function, source_map)),
+        ] {
+            sections.push(SectionSpec {
+                name: shstrtab.add(name),
+                kind: SHT_PROGBITS,
+                flags: 0,
+                addr: 0,
+                size: data.len() as u64,
+                data,
+                link: 0,
+                info: 0,
+                entsize: 0,
+            });
+        }
+    }
+    let shstrtab_name = shstrtab.add(".shstrtab");
+    sections.push(SectionSpec {
+        name: shstrtab_name,
+        kind: SHT_STRTAB,
+        flags: 0,
+        addr: 0,
+        size: shstrtab.bytes.len() as u64,
+        data: shstrtab.bytes,
+        link: 0,
+        info: 0,
+        entsize: 0,
+    });
+
+    let mut out = ByteWriter::default();
+    out.bytes.resize(ELF_HEADER_SIZE, 0);
+    let mut offsets = Vec::with_capacity(sections.len());
+    for section in &sections {
+        out.align(8);
+        offsets.push(out.bytes.len() as u64);
+        out.bytes.extend_from_slice(&section.data);
+    }
+    out.align(8);
+    let section_headers_offset = out.bytes.len() as u64;
+    out.bytes
+        .resize(out.bytes.len() + ELF_SECTION_HEADER_SIZE, 0);
+    for (section, offset) in sections.iter().zip(offsets) {
+        out.u32(section.name);
+        out.u32(section.kind);
+        out.u64(section.flags);
+        out.u64(section.addr);
+        out.u64(offset);
+        out.u64(section.size);
+        out.u32(section.link);
+        out.u32(section.info);
+        out.u64(match section.kind {
+            SHT_NOBITS => 16,
+            SHT_SYMTAB => 8,
+            _ => 1,
+        });
+        out.u64(section.entsize);
+    }
+
+    let section_count = sections.len() as u16 + 1;
+    let mut header = ByteWriter::default();
+    header
+        .bytes
+        .extend_from_slice(&[0x7f, b'E', b'L', b'F', 2, 1, 1, 0]);
+    header.bytes.resize(16, 0);
+    header.u16(1); // ET_REL
+    header.u16(ELF_MACHINE);
+    header.u32(1);
+    header.u64(0);
+    header.u64(0);
+    header.u64(section_headers_offset);
+    header.u32(0);
+    header.u16(ELF_HEADER_SIZE as u16);
+    header.u16(0);
+    header.u16(0);
+    header.u16(ELF_SECTION_HEADER_SIZE as u16);
+    header.u16(section_count);
+    header.u16(section_count - 1);
+    out.bytes[..ELF_HEADER_SIZE].copy_from_slice(&header.bytes);
+    out.bytes
+}
@@ -1,5 +1,6 @@
 use super::{
-    ImportSpec, JitEmitCtx, SigType, emit_increment_counter_ptr,
+    ImportSpec, JitEmitCtx, RANGE_COUNTER_NEXT_OFFSET, RANGE_COUNTER_REMAINING_OFFSET,
+    RANGE_COUNTER_STEP_OFFSET, SigType, emit_increment_counter_ptr,
     emit_owned_module_constant_from_parts,
 };
 use crate::jit::blockpy_intrinsics;
@@ -8,6 +9,8 @@ use cranelift_codegen::ir::InstBuilder;
 use cranelift_frontend::FunctionBuilder;
 use pyo3::ffi;
 use soac_blockpy::block_py::{BlockPyNameLike, CodegenBlockPyExpr, Instr, NameLocation};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
 
 pub(super) trait OperationEmitState<'fb, E> {
     fn ctx(&self) -> &JitEmitCtx<'_>;
@@ -18,6 +21,7 @@ pub(super) trait OperationEmitState<'fb, E> {
     fn finish_owned_result(&mut self, value: ir::Value) -> ir::Value;
     fn emit_owned_bool_from_i32_result(&mut self, result: ir::Value) -> ir::Value;
     fn emit_owned_bool_from_cond(&mut self, cond: ir::Value) -> ir::Value;
+    fn emit_pack_tuple(&mut self, values: &[ir::Value]) -> ir::Value;
 
     fn emit_owned_string_constant(&mut self, value: &str) -> ir::Value {
         let constant_id = self
@@ -280,6 +284,116 @@ define_owned_import_spec!(
     &[SigType::Pointer]
 );
 
+define_owned_import_spec!(DP_JIT_BUILD_LIST_NEW_IMPORT, "dp_jit_build_list_new", &[]);
+define_owned_import_spec!(
+    DP_JIT_BUILD_LIST_APPEND_IMPORT,
+    "dp_jit_build_list_append",
+    &[SigType::Pointer, SigType::Pointer]
+);
+define_owned_import_spec!(
+    DP_JIT_BUILD_LIST_EXTEND_IMPORT,
+    "dp_jit_build_list_extend",
+    &[SigType::Pointer, SigType::Pointer]
+);
+define_owned_import_spec!(
+    DP_JIT_BUILD_LIST_TO_TUPLE_IMPORT,
+    "dp_jit_build_list_to_tuple",
+    &[SigType::Pointer]
+);
+define_owned_import_spec!(DP_JIT_BUILD_SET_NEW_IMPORT, "dp_jit_build_set_new", &[]);
+define_owned_import_spec!(
+    DP_JIT_BUILD_SET_ADD_IMPORT,
+    "dp_jit_build_set_add",
+    &[SigType::Pointer, SigType::Pointer]
+);
+define_owned_import_spec!(
+    DP_JIT_BUILD_SET_UPDATE_IMPORT,
+    "dp_jit_build_set_update",
+    &[SigType::Pointer, SigType::Pointer]
+);
+define_owned_import_spec!(DP_JIT_BUILD_DICT_NEW_IMPORT, "dp_jit_build_dict_new", &[]);
+define_owned_import_spec!(
+    DP_JIT_BUILD_DICT_SET_ITEM_IMPORT,
+    "dp_jit_build_dict_set_item",
+    &[SigType::Pointer, SigType::Pointer, SigType::Pointer]
+);
+define_owned_import_spec!(
+    DP_JIT_BUILD_DICT_UPDATE_IMPORT,
+    "dp_jit_build_dict_update",
+    &[SigType::Pointer, SigType::Pointer]
+);
+define_owned_import_spec!(
+    DP_JIT_GET_ITER_IMPORT,
+    "dp_jit_get_iter",
+    &[SigType::Pointer]
+);
+define_owned_import_spec!(
+    DP_JIT_FOR_ITER_IMPORT,
+    "dp_jit_for_iter",
+    &[SigType::Pointer]
+);
+define_owned_import_spec!(DP_JIT_ITER_COMPLETE_IMPORT, "dp_jit_iter_complete", &[]);
+static DP_JIT_RANGE_COUNTER_IMPORT: ImportSpec = ImportSpec::new(
+    "dp_jit_range_counter",
+    &[SigType::Pointer, SigType::Pointer],
+    &[SigType::I64],
+);
+define_owned_import_spec!(
+    DP_JIT_LONG_FROM_I64_IMPORT,
+    "dp_jit_long_from_i64",
+    &[SigType::I64]
+);
+define_owned_import_spec!(
+    DP_JIT_UNPACK_IMPORT,
+    "dp_jit_unpack",
+    &[SigType::Pointer, SigType::I64, SigType::I64]
+);
+define_owned_import_spec!(
+    DP_JIT_IMPORT_CACHED_IMPORT,
+    "dp_jit_import_cached",
+    &[SigType::Pointer, SigType::Pointer, SigType::I64]
+);
+define_owned_import_spec!(
+    DP_JIT_IMPORT_NAME_IMPORT,
+    "dp_jit_import_name",
+    &[
+        SigType::Pointer,
+        SigType::Pointer,
+        SigType::Pointer,
+        SigType::Pointer,
+        SigType::I64,
+        SigType::I64,
+    ]
+);
+define_owned_import_spec!(
+    DP_JIT_IMPORT_FROM_IMPORT,
+    "dp_jit_import_from",
+    &[SigType::Pointer, SigType::Pointer]
+);
+define_owned_import_spec!(
+    DP_JIT_FORMAT_VALUE_IMPORT,
+    "dp_jit_format_value",
+    &[SigType::Pointer, SigType::I64, SigType::Pointer]
+);
+define_owned_import_spec!(
+    DP_JIT_BUILD_STRING_IMPORT,
+    "dp_jit_build_string",
+    &[SigType::Pointer, SigType::I64]
+);
+define_owned_import_spec!(
+    DP_JIT_CREATE_CLASS_IMPORT,
+    "dp_jit_create_class",
+    &[
+        SigType::Pointer,
+        SigType::Pointer,
+        SigType::Pointer,
+        SigType::Pointer,
+        SigType::I64,
+        SigType::I64,
+        SigType::Pointer,
+    ]
+);
+
 static PYOBJECT_RICHCOMPARE_IMPORT: ImportSpec = ImportSpec::new(
     "PyObject_RichCompare",
     &[SigType::Pointer, SigType::Pointer, SigType::I32],
@@ -603,6 +717,13 @@ fn emit_load<'fb>(
             state.fb().switch_to_block(value_ok_block);
             state.fb().block_params(value_ok_block)[0]
         }
+        // `for` loops test every step against the sentinel, so skip the
+        // runtime module lookup for it.
+        NameLocation::RuntimeName if op.name.id_str() == "ITER_COMPLETE" => {
+            let iter_complete_ref = state.import_func(&DP_JIT_ITER_COMPLETE_IMPORT);
+            let call_inst = state.fb().ins().call(iter_complete_ref, &[]);
+            state.fb().inst_results(call_inst)[0]
+        }
         NameLocation::RuntimeName => {
             let name_obj = state.emit_owned_string_constant(op.name.id_str());
             let call_inst = state.fb().ins().call(func_ref, &[name_obj]);
@@ -668,6 +789,439 @@ fn emit_del<'fb>(
     state.finish_owned_result(result)
 }
 
+/// Calls a `dp_jit_build_*` helper that consumes `container` and hands it
+/// back, so the display's evaluation order is the order of the calls.
+fn emit_container_step<'fb, E>(
+    spec: &'static ImportSpec,
+    container: ir::Value,
+    args: &[&E],
+    state: &mut impl OperationEmitState<'fb, E>,
+) -> ir::Value {
+    let arg_values = state.emit_arg_values(args);
+    let func_ref = state.import_func(spec);
+    let mut values = vec![container];
+    values.extend(arg_values.iter().map(|(value, _)| *value));
+    let call_inst = state.fb().ins().call(func_ref, &values);
+    state.release_arg_values(&arg_values);
+    let result = state.fb().inst_results(call_inst)[0];
+    state.finish_owned_result(result)
+}
+
+fn emit_new_container<'fb, E>(
+    spec: &'static ImportSpec,
+    state: &mut impl OperationEmitState<'fb, E>,
+) -> ir::Value {
+    let func_ref = state.import_func(spec);
+    let call_inst = state.fb().ins().call(func_ref, &[]);
+    let result = state.fb().inst_results(call_inst)[0];
+    state.finish_owned_result(result)
+}
+
+fn emit_build_list_like<'fb, E>(
+    elts: &[blockpy_intrinsics::BuildElement<E>],
+    new_spec: &'static ImportSpec,
+    add_spec: &'static ImportSpec,
+    extend_spec: &'static ImportSpec,
+    state: &mut impl OperationEmitState<'fb, E>,
+) -> ir::Value {
+    let mut container = emit_new_container(new_spec, state);
+    for elt in elts {
+        let spec = match elt {
+            blockpy_intrinsics::BuildElement::Value(_) => add_spec,
+            blockpy_intrinsics::BuildElement::Unpack(_) => extend_spec,
+        };
+        container = emit_container_step(spec, container, &[elt.expr()], state);
+    }
+    container
+}
+
+fn emit_build_tuple<'fb, E>(
+    op: &blockpy_intrinsics::BuildTuple<E>,
+    state: &mut impl OperationEmitState<'fb, E>,
+) -> ir::Value {
+    let has_unpack = op
+        .elts
+        .iter()
+        .any(|elt| matches!(elt, blockpy_intrinsics::BuildElement::Unpack(_)));
+    if !has_unpack {
+        let args = op.elts.iter().map(|elt| elt.expr()).collect::<Vec<_>>();
+        let arg_values = state.emit_arg_values(&args);
+        let values = arg_values
+            .iter()
+            .map(|(value, _)| *value)
+            .collect::<Vec<_>>();
+        let tuple = state.emit_pack_tuple(&values);
+        state.release_arg_values(&arg_values);
+        return tuple;
+    }
+    let list = emit_build_list_like(
+        &op.elts,
+        &DP_JIT_BUILD_LIST_NEW_IMPORT,
+        &DP_JIT_BUILD_LIST_APPEND_IMPORT,
+        &DP_JIT_BUILD_LIST_EXTEND_IMPORT,
+        state,
+    );
+    let func_ref = state.import_func(&DP_JIT_BUILD_LIST_TO_TUPLE_IMPORT);
+    let call_inst = state.fb().ins().call(func_ref, &[list]);
+    let result = state.fb().inst_results(call_inst)[0];
+    state.finish_owned_result(result)
+}
+
+fn emit_build_dict<'fb, E>(
+    op: &blockpy_intrinsics::BuildDict<E>,
+    state: &mut impl OperationEmitState<'fb, E>,
+) -> ir::Value {
+    let mut dict = emit_new_container(&DP_JIT_BUILD_DICT_NEW_IMPORT, state);
+    for item in &op.items {
+        dict = match item {
+            blockpy_intrinsics::BuildDictItem::Pair { key, value } => emit_container_step(
+                &DP_JIT_BUILD_DICT_SET_ITEM_IMPORT,
+                dict,
+                &[key, value],
+                state,
+            ),
+            blockpy_intrinsics::BuildDictItem::Unpack(mapping) => {
+                emit_container_step(&DP_JIT_BUILD_DICT_UPDATE_IMPORT, dict, &[mapping], state)
+            }
+        };
+    }
+    dict
+}
+
+/// `dp_jit_unpack(value, count, starred)`, with `-1` for no starred target.
+fn emit_unpack<'fb, E: Instr>(
+    op: &blockpy_intrinsics::Unpack<E>,
+    state: &mut impl OperationEmitState<'fb, E>,
+) -> ir::Value {
+    let arg_values = state.emit_arg_values(&[op.value.as_ref()]);
+    let func_ref = state.import_func(&DP_JIT_UNPACK_IMPORT);
+    let count = state.fb().ins().iconst(ir::types::I64, op.count as i64);
+    let starred = state
+        .fb()
+        .ins()
+        .iconst(ir::types::I64, op.starred.map_or(-1, |index| index as i64));
+    let call_inst = state
+        .fb()
+        .ins()
+        .call(func_ref, &[arg_values[0].0, count, starred]);
+    state.release_arg_values(&arg_values);
+    let result = state.fb().inst_results(call_inst)[0];
+    state.finish_owned_result(result)
+}
+
+/// `GetIter` for an iterator local backed by a `RangeCounter`: fills the
+/// counter and binds `None` when the iterable is a range that fits, and
+/// otherwise builds the iterator object as usual.
+pub(super) fn emit_range_counter_get_iter<'fb, E: Instr>(
+    op: &blockpy_intrinsics::GetIter<E>,
+    counter: ir::StackSlot,
+    state: &mut impl OperationEmitState<'fb, E>,
+) -> ir::Value {
+    let arg_values = state.emit_arg_values(&[op.value.as_ref()]);
+    let iterable = arg_values[0].0;
+    let range_counter_ref = state.import_func(&DP_JIT_RANGE_COUNTER_IMPORT);
+    let get_iter_ref = state.import_func(&DP_JIT_GET_ITER_IMPORT);
+    let ptr_ty = state.ctx().consts.ptr_ty;
+    let none_const = state.ctx().consts.none_const;
+    let incref_ref = state.ctx().incref_ref;
+    let fb = state.fb();
+    let counter_ptr = fb.ins().stack_addr(ptr_ty, counter, 0);
+    let counted_inst = fb.ins().call(range_counter_ref, &[iterable, counter_ptr]);
+    let counted = fb.inst_results(counted_inst)[0];
+    let counted_block = fb.create_block();
+    let object_block = fb.create_block();
+    let done_block = fb.create_block();
+    fb.append_block_param(done_block, ptr_ty);
+    fb.ins()
+        .brif(counted, counted_block, &[], object_block, &[]);
+
+    fb.switch_to_block(counted_block);
+    fb.ins().call(incref_ref, &[none_const]);
+    fb.ins()
+        .jump(done_block, &[ir::BlockArg::Value(none_const)]);
+
+    fb.switch_to_block(object_block);
+    let iter_inst = fb.ins().call(get_iter_ref, &[iterable]);
+    let iter = fb.inst_results(iter_inst)[0];
+    fb.ins().jump(done_block, &[ir::BlockArg::Value(iter)]);
+
+    fb.switch_to_block(done_block);
+    let result = fb.block_params(done_block)[0];
+    state.release_arg_values(&arg_values);
+    state.finish_owned_result(result)
+}
+
+/// `ForIter` over a `RangeCounter`: steps the counter inline and boxes only
+/// the value handed to the loop body, or steps the iterator object in the
+/// local when `GetIter` could not count the iterable.
+fn emit_range_counter_for_iter<'fb, E: Instr>(
+    op: &blockpy_intrinsics::ForIter<E>,
+    counter: ir::StackSlot,
+    state: &mut impl OperationEmitState<'fb, E>,
+) -> ir::Value {
+    let for_iter_ref = state.import_func(&DP_JIT_FOR_ITER_IMPORT);
+    let iter_complete_ref = state.import_func(&DP_JIT_ITER_COMPLETE_IMPORT);
+    let long_from_i64_ref = state.import_func(&DP_JIT_LONG_FROM_I64_IMPORT);
+    let ptr_ty = state.ctx().consts.ptr_ty;
+    let i64_ty = ir::types::I64;
+    let fb = state.fb();
+    let remaining = fb
+        .ins()
+        .stack_load(i64_ty, counter, RANGE_COUNTER_REMAINING_OFFSET);
+    let object_block = fb.create_block();
+    let counted_block = fb.create_block();
+    let step_block = fb.create_block();
+    let complete_block = fb.create_block();
+    let done_block = fb.create_block();
+    fb.append_block_param(done_block, ptr_ty);
+    let uncounted = fb
+        .ins()
+        .icmp_imm(ir::condcodes::IntCC::SignedLessThan, remaining, 0);
+    fb.ins()
+        .brif(uncounted, object_block, &[], counted_block, &[]);
+
+    fb.switch_to_block(counted_block);
+    fb.ins()
+        .brif(remaining, step_block, &[], complete_block, &[]);
+
+    fb.switch_to_block(step_block);
+    let next = fb
+        .ins()
+        .stack_load(i64_ty, counter, RANGE_COUNTER_NEXT_OFFSET);
+    let step = fb
+        .ins()
+        .stack_load(i64_ty, counter, RANGE_COUNTER_STEP_OFFSET);
+    let following = fb.ins().iadd(next, step);
+    fb.ins()
+        .stack_store(following, counter, RANGE_COUNTER_NEXT_OFFSET);
+    let still_remaining = fb.ins().iadd_imm(remaining, -1);
+    fb.ins()
+        .stack_store(still_remaining, counter, RANGE_COUNTER_REMAINING_OFFSET);
+    let boxed_inst = fb.ins().call(long_from_i64_ref, &[next]);
+    let boxed = fb.inst_results(boxed_inst)[0];
+    fb.ins().jump(done_block, &[ir::BlockArg::Value(boxed)]);
+
+    fb.switch_to_block(complete_block);
+    let complete_inst = fb.ins().call(iter_complete_ref, &[]);
+    let complete = fb.inst_results(complete_inst)[0];
+    fb.ins().jump(done_block, &[ir::BlockArg::Value(complete)]);
+
+    fb.switch_to_block(object_block);
+    let arg_values = state.emit_arg_values(&[op.iter.as_ref()]);
+    let fb = state.fb();
+    let item_inst = fb.ins().call(for_iter_ref, &[arg_values[0].0]);
+    let item = fb.inst_results(item_inst)[0];
+    state.release_arg_values(&arg_values);
+    state
+        .fb()
+        .ins()
+        .jump(done_block, &[ir::BlockArg::Value(item)]);
+
+    state.fb().switch_to_block(done_block);
+    let result = state.fb().block_params(done_block)[0];
+    state.finish_owned_result(result)
+}
+
+/// Stable per-module key for an import site. Sites that import the same
+/// module with the same fromlist and level resolve identically, so they may
+/// share a cache entry.
+fn import_site_key<E>(op: &blockpy_intrinsics::ImportName<E>) -> i64 {
+    let mut hasher = DefaultHasher::new();
+    op.module.hash(&mut hasher);
+    op.fromlist.hash(&mut hasher);
+    op.level.hash(&mut hasher);
+    hasher.finish() as i64
+}
+
+/// `dp_jit_import_cached` first; only a miss evaluates the spec, packs the
+/// fromlist and calls `dp_jit_import_name`.
+fn emit_import_name<'fb, E: Instr>(
+    op: &blockpy_intrinsics::ImportName<E>,
+    state: &mut impl OperationEmitState<'fb, E>,
+) -> ir::Value {
+    let cached_ref = state.import_func(&DP_JIT_IMPORT_CACHED_IMPORT);
+    let import_ref = state.import_func(&DP_JIT_IMPORT_NAME_IMPORT);
+    let decref_ref = state.ctx().decref_ref;
+    let vmctx = state.ctx().consts.vmctx_value;
+    let ptr_ty = state.ctx().consts.ptr_ty;
+    let site_key = state.fb().ins().iconst(ir::types::I64, import_site_key(op));
+    let name_obj = state.emit_owned_string_constant(&op.module);
+    let cached_inst = state
+        .fb()
+        .ins()
+        .call(cached_ref, &[vmctx, name_obj, site_key]);
+    let cached = state.fb().inst_results(cached_inst)[0];
+    let null_ptr = state.fb().ins().iconst(ptr_ty, 0);
+    let cached_is_null = state
+        .fb()
+        .ins()
+        .icmp(ir::condcodes::IntCC::Equal, cached, null_ptr);
+    let slowpath_block = state.fb().create_block();
+    let value_ok_block = state.fb().create_block();
+    state.fb().append_block_param(value_ok_block, ptr_ty);
+    state.fb().ins().brif(
+        cached_is_null,
+        slowpath_block,
+        &[],
+        value_ok_block,
+        &[ir::BlockArg::Value(cached)],
+    );
+
+    state.fb().switch_to_block(slowpath_block);
+    let arg_values = state.emit_arg_values(&[op.spec.as_ref()]);
+    let fromlist_names = op
+        .fromlist
+        .iter()
+        .map(|name| state.emit_owned_string_constant(name))
+        .collect::<Vec<_>>();
+    let fromlist = state.emit_pack_tuple(&fromlist_names);
+    for name in fromlist_names {
+        state.fb().ins().call(decref_ref, &[name]);
+    }
+    let level = state.fb().ins().iconst(ir::types::I64, i64::from(op.level));
+    let import_inst = state.fb().ins().call(
+        import_ref,
+        &[vmctx, name_obj, arg_values[0].0, fromlist, level, site_key],
+    );
+    state.release_arg_values(&arg_values);
+    state.fb().ins().call(decref_ref, &[fromlist]);
+    let imported = state.fb().inst_results(import_inst)[0];
+    state
+        .fb()
+        .ins()
+        .jump(value_ok_block, &[ir::BlockArg::Value(imported)]);
+
+    state.fb().switch_to_block(value_ok_block);
+    state.fb().ins().call(decref_ref, &[name_obj]);
+    let result = state.fb().block_params(value_ok_block)[0];
+    state.finish_owned_result(result)
+}
+
+fn emit_import_from<'fb, E: Instr>(
+    op: &blockpy_intrinsics::ImportFrom<E>,
+    state: &mut impl OperationEmitState<'fb, E>,
+) -> ir::Value {
+    let arg_values = state.emit_arg_values(&[op.module.as_ref()]);
+    let func_ref = state.import_func(&DP_JIT_IMPORT_FROM_IMPORT);
+    let decref_ref = state.ctx().decref_ref;
+    let attr_obj = state.emit_owned_string_constant(&op.attr);
+    let call_inst = state
+        .fb()
+        .ins()
+        .call(func_ref, &[arg_values[0].0, attr_obj]);
+    state.fb().ins().call(decref_ref, &[attr_obj]);
+    state.release_arg_values(&arg_values);
+    let result = state.fb().inst_results(call_inst)[0];
+    state.finish_owned_result(result)
+}
+
+pub(super) const FORMAT_CONVERSION_NONE: i64 = 0;
+pub(super) const FORMAT_CONVERSION_STR: i64 = 1;
+pub(super) const FORMAT_CONVERSION_REPR: i64 = 2;
+pub(super) const FORMAT_CONVERSION_ASCII: i64 = 3;
+
+fn emit_format_value<'fb, E: Instr>(
+    op: &blockpy_intrinsics::FormatValue<E>,
+    state: &mut impl OperationEmitState<'fb, E>,
+) -> ir::Value {
+    let mut args = vec![op.value.as_ref()];
+    if let Some(format_spec) = &op.format_spec {
+        args.push(format_spec.as_ref());
+    }
+    let arg_values = state.emit_arg_values(&args);
+    let func_ref = state.import_func(&DP_JIT_FORMAT_VALUE_IMPORT);
+    let ptr_ty = state.ctx().consts.ptr_ty;
+    let conversion = match op.conversion {
+        blockpy_intrinsics::FormatConversion::None => FORMAT_CONVERSION_NONE,
+        blockpy_intrinsics::FormatConversion::Str => FORMAT_CONVERSION_STR,
+        blockpy_intrinsics::FormatConversion::Repr => FORMAT_CONVERSION_REPR,
+        blockpy_intrinsics::FormatConversion::Ascii => FORMAT_CONVERSION_ASCII,
+    };
+    let conversion = state.fb().ins().iconst(ir::types::I64, conversion);
+    // A null spec is the empty one and lets exact `str` values pass through.
+    let format_spec = match arg_values.get(1) {
+        Some((format_spec, _)) => *format_spec,
+        None => state.fb().ins().iconst(ptr_ty, 0),
+    };
+    let call_inst = state
+        .fb()
+        .ins()
+        .call(func_ref, &[arg_values[0].0, conversion, format_spec]);
+    state.release_arg_values(&arg_values);
+    let result = state.fb().inst_results(call_inst)[0];
+    state.finish_owned_result(result)
+}
+
+fn emit_build_string<'fb, E: Instr>(
+    op: &blockpy_intrinsics::BuildString<E>,
+    state: &mut impl OperationEmitState<'fb, E>,
+) -> ir::Value {
+    let parts = op.parts.iter().collect::<Vec<_>>();
+    let arg_values = state.emit_arg_values(&parts);
+    let func_ref = state.import_func(&DP_JIT_BUILD_STRING_IMPORT);
+    let ptr_ty = state.ctx().consts.ptr_ty;
+    let ptr_size = ptr_ty.bytes();
+    let slot = state.fb().create_sized_stack_slot(ir::StackSlotData::new(
+        ir::StackSlotKind::ExplicitSlot,
+        ptr_size * arg_values.len().max(1) as u32,
+        0,
+    ));
+    for (index, (value, _)) in arg_values.iter().enumerate() {
+        state
+            .fb()
+            .ins()
+            .stack_store(*value, slot, (index as u32 * ptr_size) as i32);
+    }
+    let parts_ptr = state.fb().ins().stack_addr(ptr_ty, slot, 0);
+    let count = state
+        .fb()
+        .ins()
+        .iconst(ir::types::I64, arg_values.len() as i64);
+    let call_inst = state.fb().ins().call(func_ref, &[parts_ptr, count]);
+    state.release_arg_values(&arg_values);
+    let result = state.fb().inst_results(call_inst)[0];
+    state.finish_owned_result(result)
+}
+
+fn emit_create_class<'fb, E: Instr>(
+    op: &blockpy_intrinsics::CreateClass<E>,
+    state: &mut impl OperationEmitState<'fb, E>,
+) -> ir::Value {
+    let arg_values = state.emit_arg_values(&[
+        op.namespace_fn.as_ref(),
+        op.bases.as_ref(),
+        op.kwds.as_ref(),
+        op.static_attributes.as_ref(),
+    ]);
+    let func_ref = state.import_func(&DP_JIT_CREATE_CLASS_IMPORT);
+    let decref_ref = state.ctx().decref_ref;
+    let name_obj = state.emit_owned_string_constant(&op.name);
+    let requires_class_cell = state
+        .fb()
+        .ins()
+        .iconst(ir::types::I64, i64::from(op.requires_class_cell));
+    let firstlineno = state
+        .fb()
+        .ins()
+        .iconst(ir::types::I64, i64::from(op.firstlineno));
+    let call_inst = state.fb().ins().call(
+        func_ref,
+        &[
+            name_obj,
+            arg_values[0].0,
+            arg_values[1].0,
+            arg_values[2].0,
+            requires_class_cell,
+            firstlineno,
+            arg_values[3].0,
+        ],
+    );
+    state.fb().ins().call(decref_ref, &[name_obj]);
+    state.release_arg_values(&arg_values);
+    let result = state.fb().inst_results(call_inst)[0];
+    state.finish_owned_result(result)
+}
+
 pub(super) fn emit_del_deref_raw_cell<'fb, E>(
     cell_obj: ir::Value,
     quietly: bool,
@@ -691,8 +1245,25 @@ pub(super) fn emit_operation<'fb>(
 ) -> Option<ir::Value> {
     match operation {
         CodegenBlockPyExpr::CalleeFunctionId(_) => None,
+        CodegenBlockPyExpr::InlineGuard(_) => None,
         CodegenBlockPyExpr::Call(_) => None,
         CodegenBlockPyExpr::CallDirect(_) => None,
+        CodegenBlockPyExpr::BuildTuple(op) => Some(emit_build_tuple(op, state)),
+        CodegenBlockPyExpr::BuildList(op) => Some(emit_build_list_like(
+            &op.elts,
+            &DP_JIT_BUILD_LIST_NEW_IMPORT,
+            &DP_JIT_BUILD_LIST_APPEND_IMPORT,
+            &DP_JIT_BUILD_LIST_EXTEND_IMPORT,
+            state,
+        )),
+        CodegenBlockPyExpr::BuildSet(op) => Some(emit_build_list_like(
+            &op.elts,
+            &DP_JIT_BUILD_SET_NEW_IMPORT,
+            &DP_JIT_BUILD_SET_ADD_IMPORT,
+            &DP_JIT_BUILD_SET_UPDATE_IMPORT,
+            state,
+        )),
+        CodegenBlockPyExpr::BuildDict(op) => Some(emit_build_dict(op, state)),
         CodegenBlockPyExpr::BinOp(op) => Some(emit_binop(
             op.kind,
             state,
@@ -719,13 +1290,34 @@ pub(super) fn emit_operation<'fb>(
             state,
             &[op.value.as_ref(), op.index.as_ref()],
         )),
+        CodegenBlockPyExpr::GetIter(op) => Some(emit_positional_owned_call(
+            &DP_JIT_GET_ITER_IMPORT,
+            state,
+            &[op.value.as_ref()],
+        )),
+        CodegenBlockPyExpr::ForIter(op) => Some(
+            match super::range_counter_slot(op.iter.as_ref(), state.ctx()) {
+                Some(counter) => emit_range_counter_for_iter(op, counter, state),
+                None => {
+                    emit_positional_owned_call(&DP_JIT_FOR_ITER_IMPORT, state, &[op.iter.as_ref()])
+                }
+            },
+        ),
+        CodegenBlockPyExpr::Unpack(op) => Some(emit_unpack(op, state)),
+        CodegenBlockPyExpr::ImportName(op) => Some(emit_import_name(op, state)),
+        CodegenBlockPyExpr::ImportFrom(op) => Some(emit_import_from(op, state)),
+        CodegenBlockPyExpr::CreateClass(op) => Some(emit_create_class(op, state)),
+        CodegenBlockPyExpr::FormatValue(op) => Some(emit_format_value(op, state)),
+        CodegenBlockPyExpr::BuildString(op) => Some(emit_build_string(op, state)),
         CodegenBlockPyExpr::Load(op) => (op.name.location.is_global()
             || op.name.location.is_runtime_name())
         .then(|| emit_load(op, state)),
         CodegenBlockPyExpr::MakeCell(op) => {
             Some(emit_make_cell(state, &[op.initial_value.as_ref()]))
         }
-        CodegenBlockPyExpr::IncrementCounter(_) => None,
+        CodegenBlockPyExpr::IncrementCounter(_)
+        | CodegenBlockPyExpr::PreloadGlobal(_)
+        | CodegenBlockPyExpr::GuardedGlobalLoad(_) => None,
         CodegenBlockPyExpr::CellRef(_) => None,
         CodegenBlockPyExpr::MakeFunction(_) => None,
         CodegenBlockPyExpr::Store(op) => {
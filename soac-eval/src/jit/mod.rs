@@ -15,10 +15,10 @@ use cranelift_reader::parse_functions;
 use pyo3::ffi;
 use soac_blockpy::block_py::{
     AbruptKind, BlockArg, BlockPyFunction, BlockPyModule, BlockTerm, CallArgKeyword,
-    CallArgPositional, CellLocation, CodegenBlock, CodegenBlockPyExpr, CounterDef, CounterId,
-    ChildVisitable, CounterScope, CounterSite, FunctionId, HasMeta, LocalLocation, LocatedName,
-    NameLocation, ParamDefaultSource, StorageLayout, Visit, WithMeta,
-    operation as blockpy_intrinsics,
+    CallArgPositional, CellLocation, ChildVisitable, CodegenBlock, CodegenBlockPyExpr, CounterDef,
+    CounterId, CounterScope, CounterSite, FunctionId, GlobalSlot, GuardedGlobalLoad, HasMeta,
+    LocalLocation, LocatedName, NameLocation, ParamDefaultSource, PreloadGlobal, StorageLayout,
+    Visit, WithMeta, operation as blockpy_intrinsics,
 };
 use soac_blockpy::passes::CodegenBlockPyPass;
 use std::borrow::Cow;
@@ -26,11 +26,21 @@ use std::collections::{HashMap, HashSet};
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::{Mutex, OnceLock};
 
+mod code_map;
+#[cfg(not(test))]
+mod for_iter;
+mod gdb_jit;
 mod intrinsics;
 mod planning;
 mod specialized_helpers;
+#[cfg(not(test))]
+mod string_format;
 mod vmctx;
 
+use code_map::{JitCodeRegion, JitCodeRegionHandle, JitPcRange};
+pub use code_map::{JitInstrSite, ResolvedJitPc, resolve_jit_pc};
+pub use gdb_jit::JitSourceMap;
+use gdb_jit::{GdbJitRegistration, JitDebugFunction, JitSrcLocRange};
 pub use planning::{
     BlockExcDispatchPlan, exc_dispatch_plan, jit_param_names_for_block, lookup_blockpy_function,
     lookup_blockpy_module, register_clif_module_plans,
@@ -39,7 +49,7 @@ pub use specialized_helpers::ObjPtr;
 use specialized_helpers::register_specialized_jit_symbols;
 use vmctx::{
     DELETED_OBJ_OFFSET, EMPTY_TUPLE_OBJ_OFFSET, FALSE_OBJ_OFFSET, GLOBAL_SLOTS_OFFSET,
-    GLOBALS_OBJ_OFFSET, NONE_OBJ_OFFSET, TRUE_OBJ_OFFSET,
+    GLOBAL_VERSION_OFFSET, GLOBALS_OBJ_OFFSET, NONE_OBJ_OFFSET, TRUE_OBJ_OFFSET,
 };
 pub use vmctx::{JitModuleVmCtx, ModuleRuntimeContext};
 
@@ -230,6 +240,11 @@ static DP_JIT_PYOBJECT_TO_I64_IMPORT: ImportSpec = ImportSpec::new(
 );
 static DP_JIT_CALLEE_FUNCTION_ID_IMPORT: ImportSpec =
     ImportSpec::new("dp_jit_callee_function_id", &[SigType::Pointer], &[SigType::I64]);
+static DP_JIT_INLINE_GUARD_IMPORT: ImportSpec = ImportSpec::new(
+    "dp_jit_inline_guard",
+    &[SigType::Pointer, SigType::Pointer, SigType::I64],
+    &[SigType::I64],
+);
 static DP_JIT_RAISE_DELETED_NAME_ERROR_IMPORT: ImportSpec =
     ImportSpec::new("dp_jit_raise_deleted_name_error", &[SigType::Pointer], &[]);
 static DP_JIT_MAKE_CELL_IMPORT: ImportSpec =
@@ -400,6 +415,8 @@ struct BuiltSpecializedFunction {
 }
 
 struct CompiledSpecializedRunner {
+    _gdb_registration: Option<GdbJitRegistration>,
+    _code_region: Option<JitCodeRegionHandle>,
     _jit_module: JITModule,
     entry: Option<CompiledRunnerEntry>,
 }
@@ -407,6 +424,8 @@ struct CompiledSpecializedRunner {
 pub type VectorcallEntryFn = unsafe extern "C" fn(ObjPtr, *const ObjPtr, usize, ObjPtr) -> ObjPtr;
 
 struct CompiledVectorcallRunner {
+    _gdb_registration: Option<GdbJitRegistration>,
+    _code_region: JitCodeRegionHandle,
     _jit_module: JITModule,
 }
 
@@ -432,6 +451,8 @@ fn codegen_expr_is_borrowable(
             .is_some_and(|name| {
                 local_names.iter().any(|candidate| candidate == name) || stack_slots.has_name(name)
             }),
+        // Borrows the hoisted local, which only this load rebinds.
+        CodegenBlockPyExpr::GuardedGlobalLoad(_) => true,
         _ => false,
     }
 }
@@ -532,9 +553,7 @@ fn emit_codegen_located_name_load(
         NameLocation::Global(slot) => {
             let globals_obj = ctx.consts.block_const;
             let global_slots = ctx.consts.global_slots_const;
-            let slot_offset = i64::from(slot.slot()) * i64::from(ptr_ty.bytes());
-            let slot_addr = fb.ins().iadd_imm(global_slots, slot_offset);
-            let cached = fb.ins().load(ptr_ty, ir::MemFlags::trusted(), slot_addr, 0);
+            let cached = emit_load_global_cache_slot(fb, slot, ctx);
             let cached_is_null = fb.ins().icmp(ir::condcodes::IntCC::Equal, cached, null_ptr);
             let cached_hit_block = fb.create_block();
             let slowpath_block = fb.create_block();
@@ -606,6 +625,158 @@ fn emit_codegen_located_name_load(
     }
 }
 
+/// The raw contents of a global's module cache slot: a borrowed reference,
+/// or null when the slot is empty.
+fn emit_load_global_cache_slot(
+    fb: &mut FunctionBuilder<'_>,
+    slot: GlobalSlot,
+    ctx: &JitEmitCtx<'_>,
+) -> ir::Value {
+    let ptr_ty = ctx.consts.ptr_ty;
+    let slot_offset = i64::from(slot.slot()) * i64::from(ptr_ty.bytes());
+    let slot_addr = fb
+        .ins()
+        .iadd_imm(ctx.consts.global_slots_const, slot_offset);
+    fb.ins().load(ptr_ty, ir::MemFlags::trusted(), slot_addr, 0)
+}
+
+/// The module global cache's current version.
+fn emit_load_global_version(fb: &mut FunctionBuilder<'_>, ctx: &JitEmitCtx<'_>) -> ir::Value {
+    fb.ins().load(
+        ir::types::I64,
+        ir::MemFlags::trusted(),
+        ctx.consts.global_version_ptr,
+        0,
+    )
+}
+
+/// The stack slot holding the cache version `hoisted` was bound at.
+fn hoisted_version_slot(hoisted: &LocatedName, ctx: &JitEmitCtx<'_>) -> ir::StackSlot {
+    let name = hoisted.id.as_str();
+    ctx.hoisted_versions
+        .slot_for_name(name)
+        .unwrap_or_else(|| panic!("missing version slot for hoisted local {name}"))
+}
+
+/// Binds the hoisted local to a global's cached value, or to the deleted
+/// sentinel when the slot is empty, and records the cache version; an empty
+/// slot records a version that never matches. Never raises.
+fn emit_preload_global(
+    fb: &mut FunctionBuilder<'_>,
+    op: &PreloadGlobal,
+    local_names: &mut Vec<String>,
+    local_values: &mut Vec<ir::Value>,
+    ctx: &JitEmitCtx<'_>,
+) -> ir::Value {
+    let slot = op
+        .name
+        .location
+        .as_global()
+        .unwrap_or_else(|| panic!("preload_global should name a global: {op:?}"));
+    let version = emit_load_global_version(fb, ctx);
+    let cached = emit_load_global_cache_slot(fb, slot, ctx);
+    let null_ptr = fb.ins().iconst(ctx.consts.ptr_ty, 0);
+    let cached_is_null = fb.ins().icmp(ir::condcodes::IntCC::Equal, cached, null_ptr);
+    let value = fb
+        .ins()
+        .select(cached_is_null, ctx.consts.deleted_const, cached);
+    fb.ins().call(ctx.incref_ref, &[value]);
+    let never = fb.ins().iconst(ir::types::I64, -1);
+    let version = fb.ins().select(cached_is_null, never, version);
+    fb.ins()
+        .stack_store(version, hoisted_version_slot(&op.hoisted, ctx), 0);
+    bind_local_value(
+        fb,
+        local_names,
+        local_values,
+        op.hoisted.id.as_str(),
+        value,
+        &ctx.stack_slots,
+        ctx.consts.ptr_ty,
+        ctx.incref_ref,
+        ctx.decref_ref,
+    );
+    fb.ins().call(ctx.incref_ref, &[ctx.consts.none_const]);
+    ctx.consts.none_const
+}
+
+/// Reuses the hoisted value while the module cache version is the one it was
+/// bound at, since the cache slot then still holds that same object. On a
+/// miss, does the ordinary global load and rebinds the hoisted local to the
+/// result, recording the version read before the load if the slot now holds
+/// that result. Either way the result can be handed out borrowed from the
+/// hoisted local.
+fn emit_guarded_global_load(
+    fb: &mut FunctionBuilder<'_>,
+    op: &GuardedGlobalLoad,
+    local_names: &mut Vec<String>,
+    local_values: &mut Vec<ir::Value>,
+    ctx: &JitEmitCtx<'_>,
+    borrowed: bool,
+) -> ir::Value {
+    let ptr_ty = ctx.consts.ptr_ty;
+    let slot = op
+        .name
+        .location
+        .as_global()
+        .unwrap_or_else(|| panic!("guarded_global_load should name a global: {op:?}"));
+    let hoisted_location = op
+        .hoisted
+        .local_location()
+        .unwrap_or_else(|| panic!("guarded_global_load should guard on a local: {op:?}"));
+    let version_slot = hoisted_version_slot(&op.hoisted, ctx);
+    let saved_version = fb.ins().stack_load(ir::types::I64, version_slot, 0);
+    let version = emit_load_global_version(fb, ctx);
+    let unchanged = fb
+        .ins()
+        .icmp(ir::condcodes::IntCC::Equal, saved_version, version);
+    let hit_block = fb.create_block();
+    let miss_block = fb.create_block();
+    let done_block = fb.create_block();
+    fb.append_block_param(done_block, ptr_ty);
+    fb.ins().brif(unchanged, hit_block, &[], miss_block, &[]);
+
+    fb.switch_to_block(hit_block);
+    if let Some(counter_ptr) = ctx.consts.global_load_hit_counter_ptr {
+        emit_increment_counter_ptr(fb, ptr_ty, counter_ptr);
+    }
+    let hoisted = emit_codegen_local_name_load(
+        fb,
+        hoisted_location,
+        local_names,
+        local_values,
+        ctx,
+        borrowed,
+    );
+    fb.ins().jump(done_block, &[ir::BlockArg::Value(hoisted)]);
+
+    fb.switch_to_block(miss_block);
+    let value = emit_codegen_located_name_load(fb, &op.name, local_names, local_values, ctx, false);
+    let cached = emit_load_global_cache_slot(fb, slot, ctx);
+    let cached_is_value = fb.ins().icmp(ir::condcodes::IntCC::Equal, cached, value);
+    let never = fb.ins().iconst(ir::types::I64, -1);
+    let refreshed_version = fb.ins().select(cached_is_value, version, never);
+    fb.ins().stack_store(refreshed_version, version_slot, 0);
+    if !borrowed {
+        fb.ins().call(ctx.incref_ref, &[value]);
+    }
+    bind_local_value(
+        fb,
+        local_names,
+        local_values,
+        op.hoisted.id.as_str(),
+        value,
+        &ctx.stack_slots,
+        ptr_ty,
+        ctx.incref_ref,
+        ctx.decref_ref,
+    );
+    fb.ins().jump(done_block, &[ir::BlockArg::Value(value)]);
+
+    fb.switch_to_block(done_block);
+    fb.block_params(done_block)[0]
+}
+
 fn codegen_expr_const_string(
     expr: &CodegenBlockPyExpr,
     module_constants: &ModuleCodegenConstants,
@@ -671,6 +842,7 @@ struct JitEmitConsts {
     empty_tuple_const: ir::Value,
     block_const: ir::Value,
     global_slots_const: ir::Value,
+    global_version_ptr: ir::Value,
     global_load_hit_counter_ptr: Option<*mut u64>,
     global_load_miss_counter_ptr: Option<*mut u64>,
 }
@@ -703,6 +875,10 @@ struct JitEmitCtx<'mc> {
     tuple_new_ref: ir::FuncRef,
     tuple_set_item_ref: ir::FuncRef,
     stack_slots: StackSlots,
+    /// Raw cache versions, not object references, keyed by hoisted local.
+    hoisted_versions: StackSlots,
+    /// Raw `RangeCounter`s keyed by `for` loop iterator local.
+    range_counters: StackSlots,
     direct_call_code_ptrs: &'mc HashMap<FunctionId, ObjPtr>,
 }
 
@@ -723,11 +899,15 @@ struct StackSlots {
 
 impl StackSlots {
     fn new(fb: &mut FunctionBuilder<'_>, slot_names: &[String]) -> Self {
+        Self::new_sized(fb, slot_names, std::mem::size_of::<u64>())
+    }
+
+    fn new_sized(fb: &mut FunctionBuilder<'_>, slot_names: &[String], size: usize) -> Self {
         let mut slots = Vec::with_capacity(slot_names.len());
         for _ in slot_names {
             slots.push(fb.create_sized_stack_slot(ir::StackSlotData::new(
                 ir::StackSlotKind::ExplicitSlot,
-                std::mem::size_of::<u64>() as u32,
+                size as u32,
                 0,
             )));
         }
@@ -796,6 +976,14 @@ impl StackSlots {
         Some(())
     }
 
+    /// Stores `value` at `offset` in every slot without touching refcounts, for
+    /// slots that hold plain integers.
+    fn store_all_raw(&self, fb: &mut FunctionBuilder<'_>, value: ir::Value, offset: i32) {
+        for slot in &self.slots {
+            fb.ins().stack_store(value, *slot, offset);
+        }
+    }
+
     fn decref_all(&self, fb: &mut FunctionBuilder<'_>, ptr_ty: ir::Type, decref_ref: ir::FuncRef) {
         for slot in &self.slots {
             let value = fb.ins().stack_load(ptr_ty, *slot, 0);
@@ -804,6 +992,118 @@ impl StackSlots {
     }
 }
 
+/// The locals `PreloadGlobal` binds in `function`, each of which gets a
+/// stack slot for the cache version it was bound at.
+fn hoisted_global_names(function: &BlockPyFunction<CodegenBlockPyPass>) -> Vec<String> {
+    let mut names = Vec::new();
+    for block in &function.blocks {
+        for expr in &block.body {
+            if let CodegenBlockPyExpr::PreloadGlobal(op) = expr {
+                let name = op.hoisted.id.to_string();
+                if !names.contains(&name) {
+                    names.push(name);
+                }
+            }
+        }
+    }
+    names
+}
+
+/// Integer state for a `for` loop over a range, kept in the iterator local's
+/// stack slot in place of an iterator object. `remaining` is -1 when the loop
+/// is stepping an iterator object held by the local instead. Only compiled code
+/// and `for_iter::range_counter`, through a raw pointer, touch it.
+#[allow(dead_code)]
+#[repr(C)]
+struct RangeCounter {
+    next: i64,
+    step: i64,
+    remaining: i64,
+}
+
+const RANGE_COUNTER_NEXT_OFFSET: i32 = std::mem::offset_of!(RangeCounter, next) as i32;
+const RANGE_COUNTER_STEP_OFFSET: i32 = std::mem::offset_of!(RangeCounter, step) as i32;
+const RANGE_COUNTER_REMAINING_OFFSET: i32 = std::mem::offset_of!(RangeCounter, remaining) as i32;
+
+/// The slot-backed locals in `function` that only ever hold a `for` loop's
+/// iterator: every store to them is a `GetIter` and every read is the
+/// `ForIter` stepping them, so a range loop can run on a `RangeCounter`.
+fn range_counter_names(function: &BlockPyFunction<CodegenBlockPyPass>) -> Vec<String> {
+    #[derive(Default)]
+    struct RangeCounterCollector {
+        stored: Vec<LocalLocation>,
+        escaping: HashSet<LocalLocation>,
+        passed: HashSet<String>,
+    }
+
+    impl Visit<CodegenBlockPyExpr> for RangeCounterCollector {
+        fn visit_instr(&mut self, expr: &CodegenBlockPyExpr) {
+            match expr {
+                CodegenBlockPyExpr::Store(op) => {
+                    if let Some(location) = op.name.local_location() {
+                        if matches!(op.value.as_ref(), CodegenBlockPyExpr::GetIter(_)) {
+                            self.stored.push(location);
+                        } else {
+                            self.escaping.insert(location);
+                        }
+                    }
+                }
+                CodegenBlockPyExpr::Del(op) => {
+                    if let Some(location) = op.name.local_location() {
+                        self.escaping.insert(location);
+                    }
+                }
+                CodegenBlockPyExpr::ForIter(op)
+                    if matches!(op.iter.as_ref(), CodegenBlockPyExpr::Load(_)) =>
+                {
+                    return;
+                }
+                CodegenBlockPyExpr::Load(op) => {
+                    if let Some(location) = op.name.local_location() {
+                        self.escaping.insert(location);
+                    }
+                }
+                _ => {}
+            }
+            expr.visit_children(self);
+        }
+
+        fn visit_block_arg(&mut self, arg: &BlockArg) {
+            if let BlockArg::Name(name) = arg {
+                self.passed.insert(name.clone());
+            }
+        }
+    }
+
+    let Some(layout) = function.storage_layout().as_ref() else {
+        return Vec::new();
+    };
+    let mut collector = RangeCounterCollector::default();
+    collector.visit_fn(function);
+    let mut names = Vec::new();
+    for location in collector.stored {
+        if collector.escaping.contains(&location) {
+            continue;
+        }
+        let name = local_name_for_location(layout, location).to_string();
+        if !collector.passed.contains(&name) && !names.contains(&name) {
+            names.push(name);
+        }
+    }
+    names
+}
+
+/// The `RangeCounter` slot behind the iterator local `expr` loads, if any.
+fn range_counter_slot(expr: &CodegenBlockPyExpr, ctx: &JitEmitCtx<'_>) -> Option<ir::StackSlot> {
+    let CodegenBlockPyExpr::Load(op) = expr else {
+        return None;
+    };
+    let location = op.name.local_location()?;
+    let layout = ctx.storage_layout.as_ref()?;
+    ctx.range_counters
+        .slot_for_name(local_name_for_location(layout, location))
+}
+
 fn bind_local_value(
     fb: &mut FunctionBuilder<'_>,
     local_names: &mut Vec<String>,
@@ -932,6 +1232,10 @@ impl<'a, 'b, 'mc, 'c, 'd> intrinsics::OperationEmitState<'b, CodegenBlockPyExpr>
     fn emit_owned_bool_from_cond(&mut self, cond: ir::Value) -> ir::Value {
         emit_owned_bool_from_cond(self.fb, cond, self.ctx)
     }
+
+    fn emit_pack_tuple(&mut self, values: &[ir::Value]) -> ir::Value {
+        emit_pack_current_values_tuple(self.fb, values, self.ctx)
+    }
 }
 
 fn load_stack_slot_value(
@@ -1586,6 +1890,54 @@ fn emit_branch_index_i64(
             if !callable_is_borrowed {
                 fb.ins().call(ctx.decref_ref, &[callable]);
             }
+            let packed = fb.inst_results(call_inst)[0];
+            // Map the packed id to its position in `candidates`; the error
+            // sentinel passes through so the branch still unwinds.
+            let i64_ty = ctx.consts.i64_ty;
+            let mut index = fb.ins().iconst(i64_ty, op.candidates.len() as i64);
+            for (position, candidate) in op.candidates.iter().enumerate().rev() {
+                let is_candidate = fb.ins().icmp_imm(
+                    ir::condcodes::IntCC::Equal,
+                    packed,
+                    candidate.packed() as i64,
+                );
+                let position = fb.ins().iconst(i64_ty, position as i64);
+                index = fb.ins().select(is_candidate, position, index);
+            }
+            let is_error = fb
+                .ins()
+                .icmp_imm(ir::condcodes::IntCC::Equal, packed, i64::MIN);
+            fb.ins().select(is_error, packed, index)
+        }
+        CodegenBlockPyExpr::InlineGuard(op) => {
+            let callable_is_borrowed = codegen_expr_is_borrowable(
+                op.value.as_ref(),
+                local_names,
+                &ctx.stack_slots,
+                ctx.storage_layout.as_ref(),
+            );
+            let callable = emit_codegen_expr(
+                fb,
+                op.value.as_ref(),
+                local_names,
+                local_values,
+                ctx,
+                callable_is_borrowed,
+                jit_module,
+                func_imports,
+            );
+            let inline_guard_ref =
+                func_imports.get_or_panic(jit_module, &mut fb.func, &DP_JIT_INLINE_GUARD_IMPORT);
+            let function_id = fb
+                .ins()
+                .iconst(ctx.consts.i64_ty, op.function_id.packed() as i64);
+            let call_inst = fb.ins().call(
+                inline_guard_ref,
+                &[callable, ctx.consts.block_const, function_id],
+            );
+            if !callable_is_borrowed {
+                fb.ins().call(ctx.decref_ref, &[callable]);
+            }
             fb.inst_results(call_inst)[0]
         }
         _ => {
@@ -1848,14 +2200,37 @@ fn emit_codegen_expr(
             );
             return emit_increment_counter(fb, op.counter_id, ctx);
         }
+        CodegenBlockPyExpr::PreloadGlobal(op) => {
+            assert!(
+                !borrowed,
+                "preload_global must not request a borrowed result"
+            );
+            return emit_preload_global(fb, op, local_names, local_values, ctx);
+        }
+        CodegenBlockPyExpr::GuardedGlobalLoad(op) => {
+            return emit_guarded_global_load(fb, op, local_names, local_values, ctx, borrowed);
+        }
         expr @ (CodegenBlockPyExpr::BinOp(_)
         | CodegenBlockPyExpr::UnaryOp(_)
         | CodegenBlockPyExpr::CalleeFunctionId(_)
+        | CodegenBlockPyExpr::InlineGuard(_)
+        | CodegenBlockPyExpr::BuildTuple(_)
+        | CodegenBlockPyExpr::BuildList(_)
+        | CodegenBlockPyExpr::BuildSet(_)
+        | CodegenBlockPyExpr::BuildDict(_)
         | CodegenBlockPyExpr::GetAttr(_)
         | CodegenBlockPyExpr::SetAttr(_)
         | CodegenBlockPyExpr::GetItem(_)
         | CodegenBlockPyExpr::SetItem(_)
         | CodegenBlockPyExpr::DelItem(_)
+        | CodegenBlockPyExpr::GetIter(_)
+        | CodegenBlockPyExpr::ForIter(_)
+        | CodegenBlockPyExpr::Unpack(_)
+        | CodegenBlockPyExpr::ImportName(_)
+        | CodegenBlockPyExpr::ImportFrom(_)
+        | CodegenBlockPyExpr::CreateClass(_)
+        | CodegenBlockPyExpr::FormatValue(_)
+        | CodegenBlockPyExpr::BuildString(_)
         | CodegenBlockPyExpr::Store(_)
         | CodegenBlockPyExpr::Del(_)
         | CodegenBlockPyExpr::MakeCell(_)
@@ -1890,21 +2265,32 @@ fn emit_codegen_expr(
                 ),
                 CodegenBlockPyExpr::Store(op) => {
                     if let Some(location) = op.name.local_location() {
-                        let layout =
-                            intrinsic_state.ctx.storage_layout.as_ref().expect(
-                                "Store local slot should have storage layout during codegen",
-                            );
+                        let ctx = intrinsic_state.ctx;
+                        let layout = ctx
+                            .storage_layout
+                            .as_ref()
+                            .expect("Store local slot should have storage layout during codegen");
                         let name = local_name_for_location(layout, location);
-                        let value_obj = emit_codegen_expr(
-                            intrinsic_state.fb,
-                            &op.value,
-                            intrinsic_state.local_names,
-                            intrinsic_state.local_values,
-                            intrinsic_state.ctx,
-                            false,
-                            intrinsic_state.jit_module,
-                            intrinsic_state.func_imports,
-                        );
+                        let counter = ctx.range_counters.slot_for_name(name);
+                        let value_obj = match (op.value.as_ref(), counter) {
+                            (CodegenBlockPyExpr::GetIter(get_iter), Some(counter)) => {
+                                intrinsics::emit_range_counter_get_iter(
+                                    get_iter,
+                                    counter,
+                                    &mut intrinsic_state,
+                                )
+                            }
+                            (value, _) => emit_codegen_expr(
+                                intrinsic_state.fb,
+                                value,
+                                intrinsic_state.local_names,
+                                intrinsic_state.local_values,
+                                intrinsic_state.ctx,
+                                false,
+                                intrinsic_state.jit_module,
+                                intrinsic_state.func_imports,
+                            ),
+                        };
                         bind_local_value(
                             intrinsic_state.fb,
                             intrinsic_state.local_names,
@@ -3209,6 +3595,7 @@ fn emit_truthy_from_owned(
 fn emit_codegen_ops(
     fb: &mut FunctionBuilder<'_>,
     ops: &[CodegenBlockPyExpr],
+    srcloc_base: u32,
     local_names: &mut Vec<String>,
     local_values: &mut Vec<ir::Value>,
     _stack_slots: &StackSlots,
@@ -3216,7 +3603,8 @@ fn emit_codegen_ops(
     jit_module: &mut JITModule,
     func_imports: &mut FuncBuildImports<'_>,
 ) -> Result<(), String> {
-    for expr in ops {
+    for (index, expr) in ops.iter().enumerate() {
+        fb.set_srcloc(ir::SourceLoc::new(srcloc_base + index as u32));
         let value = emit_codegen_expr(
             fb,
             expr,
@@ -3617,12 +4005,17 @@ fn new_jit_module() -> Result<JITModule, String> {
     Ok(jit_module)
 }
 
+struct DefinedFunctionCode {
+    code_size: usize,
+    pc_ranges: Vec<JitPcRange>,
+}
+
 fn define_function_with_incremental_cache(
     jit_module: &mut JITModule,
     func_id: FuncId,
     ctx: &mut cranelift_codegen::Context,
     err_prefix: &str,
-) -> Result<(), String> {
+) -> Result<DefinedFunctionCode, String> {
     inline_runtime_support_calls(jit_module, ctx, err_prefix)?;
     let func_for_relocs = ctx.func.clone();
     let mut ctrl_plane = ControlPlane::default();
@@ -3639,10 +4032,25 @@ fn define_function_with_incremental_cache(
         .iter()
         .map(|reloc| ModuleReloc::from_mach_reloc(reloc, &func_for_relocs, func_id))
         .collect::<Vec<_>>();
+    let pc_ranges = compiled
+        .buffer
+        .get_srclocs_sorted()
+        .iter()
+        .filter(|srcloc| !srcloc.loc.is_default())
+        .map(|srcloc| JitPcRange {
+            start: srcloc.start,
+            end: srcloc.end,
+            site: srcloc.loc.bits(),
+        })
+        .collect();
+    let code_size = compiled.code_buffer().len();
     jit_module
         .define_function_bytes(func_id, alignment, compiled.code_buffer(), &relocs)
         .map_err(|err| format!("{err_prefix}: {err}"))?;
-    Ok(())
+    Ok(DefinedFunctionCode {
+        code_size,
+        pc_ranges,
+    })
 }
 
 const RUNTIME_SUPPORT_INLINE_MAX_INSTS: usize = 32;
@@ -4251,6 +4659,12 @@ fn build_cranelift_run_bb_specialized_function(
                 .map(|layout| layout.stack_slots())
                 .unwrap_or(&[]),
         );
+        let hoisted_versions = StackSlots::new(&mut fb, &hoisted_global_names(function));
+        let range_counters = StackSlots::new_sized(
+            &mut fb,
+            &range_counter_names(function),
+            std::mem::size_of::<RangeCounter>(),
+        );
 
         register_block_display_annotation(
             &mut block_annotations,
@@ -4400,6 +4814,9 @@ fn build_cranelift_run_bb_specialized_function(
 
         let entry_deleted_const = load_vmctx_obj(&mut fb, ptr_ty, vmctx_value, DELETED_OBJ_OFFSET);
         stack_slots.initialize_all_to_value(&mut fb, entry_deleted_const, incref_ref);
+        let never_version = fb.ins().iconst(i64_ty, -1);
+        hoisted_versions.store_all_raw(&mut fb, never_version, 0);
+        range_counters.store_all_raw(&mut fb, never_version, RANGE_COUNTER_REMAINING_OFFSET);
 
         let null_ptr = fb.ins().iconst(ptr_ty, 0);
         let entry_failure_block = cleanup_null_blocks[0];
@@ -4579,8 +4996,10 @@ fn build_cranelift_run_bb_specialized_function(
             }
         }
 
+        let srcloc_bases = code_map::block_srcloc_bases(function);
         for (index, block) in exec_blocks.iter().enumerate() {
             fb.switch_to_block(*block);
+            fb.set_srcloc(ir::SourceLoc::default());
             let block_param_values = fb.block_params(*block).to_vec();
             for (param_name, param_value) in runtime_block_param_names[index]
                 .iter()
@@ -4601,6 +5020,8 @@ fn build_cranelift_run_bb_specialized_function(
             let block_const = load_vmctx_obj(&mut fb, ptr_ty, vmctx_value, GLOBALS_OBJ_OFFSET);
             let global_slots_const =
                 load_vmctx_obj(&mut fb, ptr_ty, vmctx_value, GLOBAL_SLOTS_OFFSET);
+            let global_version_ptr =
+                load_vmctx_obj(&mut fb, ptr_ty, vmctx_value, GLOBAL_VERSION_OFFSET);
             let none_const = load_vmctx_obj(&mut fb, ptr_ty, vmctx_value, NONE_OBJ_OFFSET);
             let true_const = load_vmctx_obj(&mut fb, ptr_ty, vmctx_value, TRUE_OBJ_OFFSET);
             let false_const = load_vmctx_obj(&mut fb, ptr_ty, vmctx_value, FALSE_OBJ_OFFSET);
@@ -4638,6 +5059,7 @@ fn build_cranelift_run_bb_specialized_function(
                     empty_tuple_const,
                     block_const,
                     global_slots_const,
+                    global_version_ptr,
                     global_load_hit_counter_ptr,
                     global_load_miss_counter_ptr,
                 },
@@ -4659,6 +5081,8 @@ fn build_cranelift_run_bb_specialized_function(
                 tuple_new_ref,
                 tuple_set_item_ref,
                 stack_slots: stack_slots.clone(),
+                hoisted_versions: hoisted_versions.clone(),
+                range_counters: range_counters.clone(),
             };
             let block = &function.blocks[index];
             let mut local_names = Vec::new();
@@ -4667,6 +5091,7 @@ fn build_cranelift_run_bb_specialized_function(
             emit_codegen_ops(
                 &mut fb,
                 &block.body,
+                srcloc_bases[index],
                 &mut local_names,
                 &mut local_values,
                 &stack_slots,
@@ -4906,8 +5331,12 @@ pub unsafe fn compile_cranelift_run_bb_specialized_cached(
     counter_defs: &[CounterDef],
     module_constant_ptrs: &[*mut ffi::PyObject],
     counter_ptrs: &[*mut u64],
+    source_map: Option<&JitSourceMap>,
+    gdb_jit_enabled: bool,
 ) -> Result<ObjPtr, String> {
     let mut compiled = Box::new(CompiledSpecializedRunner {
+        _gdb_registration: None,
+        _code_region: None,
         _jit_module: new_jit_module()?,
         entry: None,
     });
@@ -4923,7 +5352,7 @@ pub unsafe fn compile_cranelift_run_bb_specialized_cached(
     )?;
     let mut ctx = built.ctx;
     let main_id = built.main_id;
-    define_function_with_incremental_cache(
+    let defined = define_function_with_incremental_cache(
         &mut compiled._jit_module,
         main_id,
         &mut ctx,
@@ -4935,6 +5364,39 @@ pub unsafe fn compile_cranelift_run_bb_specialized_cached(
         .finalize_definitions()
         .map_err(|err| format!("failed to finalize specialized jit run_bb function: {err}"))?;
     let code_ptr = compiled._jit_module.get_finalized_function(main_id);
+    let symbol_name =
+        jit_python_perf_symbol_name(JIT_PYTHON_PERF_SYMBOL_KIND_DIRECT, &function.names.qualname);
+    let sites = code_map::instr_sites_for_function(function, source_map);
+    let srclocs = defined
+        .pc_ranges
+        .iter()
+        .filter_map(|range| {
+            let source_offset = sites.get(range.site as usize)?.source_offset?;
+            Some(JitSrcLocRange {
+                start: range.start,
+                end: range.end,
+                source_offset,
+            })
+        })
+        .collect::<Vec<_>>();
+    if gdb_jit_enabled {
+        compiled._gdb_registration = gdb_jit::register_jit_function(&JitDebugFunction {
+            symbol_name: &symbol_name,
+            code_ptr,
+            code_size: defined.code_size,
+            srclocs: &srclocs,
+            source_map,
+        });
+    }
+    compiled._code_region = Some(code_map::register_code_region(JitCodeRegion {
+        start: code_ptr as usize,
+        size: defined.code_size,
+        symbol_name,
+        qualname: function.names.qualname.clone(),
+        file_name: source_map.map(|source_map| source_map.file_name().to_string()),
+        pc_ranges: defined.pc_ranges,
+        sites,
+    }));
     compiled.entry = Some(CompiledRunnerEntry::Direct {
         code_ptr,
         param_count: function.params.len(),
@@ -4974,6 +5436,7 @@ pub unsafe fn compile_cranelift_vectorcall_direct_trampoline(
     vmctx_ptr: ObjPtr,
     compiled_handle: ObjPtr,
     symbol_name: &str,
+    gdb_jit_enabled: bool,
 ) -> Result<(ObjPtr, VectorcallEntryFn), String> {
     if data_ptr.is_null() {
         return Err("invalid null vectorcall data pointer".to_string());
@@ -5107,7 +5570,7 @@ pub unsafe fn compile_cranelift_vectorcall_direct_trampoline(
         fb.finalize();
     }
 
-    define_function_with_incremental_cache(
+    let defined = define_function_with_incremental_cache(
         &mut jit_module,
         main_id,
         &mut ctx,
@@ -5120,7 +5583,29 @@ pub unsafe fn compile_cranelift_vectorcall_direct_trampoline(
 
     let code_ptr = jit_module.get_finalized_function(main_id);
     let entry: VectorcallEntryFn = std::mem::transmute(code_ptr);
+    let gdb_registration = if gdb_jit_enabled {
+        gdb_jit::register_jit_function(&JitDebugFunction {
+            symbol_name,
+            code_ptr,
+            code_size: defined.code_size,
+            srclocs: &[],
+            source_map: None,
+        })
+    } else {
+        None
+    };
+    let code_region = code_map::register_code_region(JitCodeRegion {
+        start: code_ptr as usize,
+        size: defined.code_size,
+        symbol_name: symbol_name.to_string(),
+        qualname: symbol_name.to_string(),
+        file_name: None,
+        pc_ranges: Vec::new(),
+        sites: Vec::new(),
+    });
     let compiled = Box::new(CompiledVectorcallRunner {
+        _gdb_registration: gdb_registration,
+        _code_region: code_region,
         _jit_module: jit_module,
     });
     Ok((Box::into_raw(compiled) as ObjPtr, entry))
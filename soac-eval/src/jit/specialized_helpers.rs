@@ -7,15 +7,20 @@ use std::sync::OnceLock;
 
 #[cfg(not(test))]
 use crate::module_globals::ModuleGlobalCache;
+#[cfg(not(test))]
+use crate::module_imports::ImportSiteCache;
 
 use std::sync::atomic::{AtomicPtr, Ordering};
 
 use soac_blockpy::block_py::FunctionId;
 
 use crate::module_constants::raise_name_error_for_missing_name;
+use crate::module_imports;
 use crate::tree_walk;
 use super::vmctx::JitModuleVmCtx;
 
+#[cfg(not(test))]
+use crate::module_classes;
 #[cfg(not(test))]
 use crate::module_constants::load_runtime_name_owned;
 
@@ -117,6 +122,18 @@ unsafe extern "C" fn callee_function_id_hook(callable: ObjPtr) -> i64 {
     packed
 }
 
+#[cfg(not(test))]
+unsafe extern "C" fn inline_guard_hook(callable: ObjPtr, globals: ObjPtr, function_id: i64) -> i64 {
+    match tree_walk::inlinable_clif_function_id(
+        callable as *mut ffi::PyObject,
+        globals as *mut ffi::PyObject,
+    ) {
+        Ok(Some(inlinable)) if inlinable.packed() as i64 == function_id => 0,
+        Ok(_) => 1,
+        Err(()) => i64::MIN,
+    }
+}
+
 #[cfg(not(test))]
 unsafe extern "C" fn lookup_direct_code_ptr_hook(vmctx: ObjPtr, function_id: i64) -> ObjPtr {
     if vmctx.is_null() || function_id < 0 {
@@ -190,7 +207,13 @@ unsafe fn load_global_obj_impl(
         );
         return ptr::null_mut();
     }
-    let value = ffi::PyObject_GetItem(globals_obj as *mut ffi::PyObject, name_obj);
+    let mut value = ffi::PyObject_GetItem(globals_obj as *mut ffi::PyObject, name_obj);
+    if value.is_null() && ffi::PyErr_ExceptionMatches(ffi::PyExc_KeyError) != 0 {
+        // A pending lazy import binds its name on first load; with none,
+        // this leaves the `KeyError` for the builtins fallback below.
+        ffi::PyErr_Clear();
+        value = module_imports::resolve_lazy_global(globals_obj as *mut ffi::PyObject, name_obj);
+    }
     if !value.is_null() {
         if slot_index >= 0 {
             let entry =
@@ -578,6 +601,311 @@ unsafe extern "C" fn pyobject_delitem_hook(obj: ObjPtr, key: ObjPtr) -> ObjPtr {
     }
 }
 
+// The display-building hooks below take ownership of the container they are
+// handed and return it again, so the JIT can thread one owned value through a
+// display. On failure the container is released and NULL is returned.
+
+#[cfg(not(test))]
+unsafe fn finish_container_step(container: ObjPtr, rc: libc::c_int) -> ObjPtr {
+    if rc == 0 {
+        container
+    } else {
+        ffi::Py_DECREF(container as *mut ffi::PyObject);
+        ptr::null_mut()
+    }
+}
+
+#[cfg(not(test))]
+unsafe fn extend_container_from_iterable(
+    container: *mut ffi::PyObject,
+    iterable: *mut ffi::PyObject,
+    add: unsafe extern "C" fn(*mut ffi::PyObject, *mut ffi::PyObject) -> libc::c_int,
+) -> libc::c_int {
+    let iterator = ffi::PyObject_GetIter(iterable);
+    if iterator.is_null() {
+        return -1;
+    }
+    loop {
+        let item = ffi::PyIter_Next(iterator);
+        if item.is_null() {
+            break;
+        }
+        let rc = add(container, item);
+        ffi::Py_DECREF(item);
+        if rc != 0 {
+            ffi::Py_DECREF(iterator);
+            return -1;
+        }
+    }
+    ffi::Py_DECREF(iterator);
+    if ffi::PyErr_Occurred().is_null() {
+        0
+    } else {
+        -1
+    }
+}
+
+#[cfg(not(test))]
+unsafe fn raise_type_error_message(message: String) {
+    if let Ok(c_message) = std::ffi::CString::new(message) {
+        ffi::PyErr_SetString(ffi::PyExc_TypeError, c_message.as_ptr());
+    }
+}
+
+#[cfg(not(test))]
+unsafe extern "C" fn build_list_extend_hook(list: ObjPtr, iterable: ObjPtr) -> ObjPtr {
+    let iterable = iterable as *mut ffi::PyObject;
+    let rc =
+        extend_container_from_iterable(list as *mut ffi::PyObject, iterable, ffi::PyList_Append);
+    if rc != 0
+        && ffi::PyErr_ExceptionMatches(ffi::PyExc_TypeError) != 0
+        && (*ffi::Py_TYPE(iterable)).tp_iter.is_none()
+        && ffi::PySequence_Check(iterable) == 0
+    {
+        ffi::PyErr_Clear();
+        raise_type_error_message(format!(
+            "Value after * must be an iterable, not {}",
+            object_type_name(iterable)
+        ));
+    }
+    finish_container_step(list, rc)
+}
+
+#[cfg(not(test))]
+unsafe extern "C" fn build_list_to_tuple_hook(list: ObjPtr) -> ObjPtr {
+    let tuple = ffi::PyList_AsTuple(list as *mut ffi::PyObject);
+    ffi::Py_DECREF(list as *mut ffi::PyObject);
+    tuple as ObjPtr
+}
+
+#[cfg(not(test))]
+unsafe extern "C" fn build_dict_update_hook(dict: ObjPtr, mapping: ObjPtr) -> ObjPtr {
+    let mapping = mapping as *mut ffi::PyObject;
+    let rc = ffi::PyDict_Update(dict as *mut ffi::PyObject, mapping);
+    if rc != 0 && ffi::PyErr_ExceptionMatches(ffi::PyExc_AttributeError) != 0 {
+        ffi::PyErr_Clear();
+        raise_type_error_message(format!(
+            "'{}' object is not a mapping",
+            object_type_name(mapping)
+        ));
+    }
+    finish_container_step(dict, rc)
+}
+
+#[cfg(not(test))]
+unsafe fn raise_value_error_message(message: String) {
+    if let Ok(c_message) = std::ffi::CString::new(message) {
+        ffi::PyErr_SetString(ffi::PyExc_ValueError, c_message.as_ptr());
+    }
+}
+
+// Sequence assignment `a, *b, c = value`, following CPython's
+// `UNPACK_SEQUENCE` / `UNPACK_EX`. Exact tuples and lists of a usable length
+// are split by index, and an exact tuple that already has the right shape is
+// handed back as is; everything else, including every error, goes through
+// the iterator protocol so the messages match the interpreter's.
+
+#[cfg(not(test))]
+unsafe fn release_owned_items(items: Vec<*mut ffi::PyObject>) {
+    for item in items {
+        ffi::Py_DECREF(item);
+    }
+}
+
+#[cfg(not(test))]
+unsafe fn exact_sequence_item(seq: *mut ffi::PyObject, index: usize) -> *mut ffi::PyObject {
+    if ffi::PyTuple_CheckExact(seq) != 0 {
+        ffi::PyTuple_GET_ITEM(seq, index as ffi::Py_ssize_t)
+    } else {
+        ffi::PyList_GET_ITEM(seq, index as ffi::Py_ssize_t)
+    }
+}
+
+#[cfg(not(test))]
+unsafe fn unpack_exact_sequence(
+    seq: *mut ffi::PyObject,
+    count: usize,
+    starred: Option<usize>,
+) -> *mut ffi::PyObject {
+    let size = ffi::Py_SIZE(seq) as usize;
+    let Some(star) = starred else {
+        if ffi::PyTuple_CheckExact(seq) != 0 {
+            ffi::Py_INCREF(seq);
+            return seq;
+        }
+        return ffi::PyList_AsTuple(seq);
+    };
+    let after = count - star - 1;
+    let middle_end = size - after;
+    let result = ffi::PyTuple_New(count as ffi::Py_ssize_t);
+    if result.is_null() {
+        return ptr::null_mut();
+    }
+    let middle = ffi::PyList_New((middle_end - star) as ffi::Py_ssize_t);
+    if middle.is_null() {
+        ffi::Py_DECREF(result);
+        return ptr::null_mut();
+    }
+    for index in star..middle_end {
+        let value = exact_sequence_item(seq, index);
+        ffi::Py_INCREF(value);
+        ffi::PyList_SetItem(middle, (index - star) as ffi::Py_ssize_t, value);
+    }
+    for index in 0..star {
+        let value = exact_sequence_item(seq, index);
+        ffi::Py_INCREF(value);
+        ffi::PyTuple_SetItem(result, index as ffi::Py_ssize_t, value);
+    }
+    ffi::PyTuple_SetItem(result, star as ffi::Py_ssize_t, middle);
+    for offset in 0..after {
+        let value = exact_sequence_item(seq, middle_end + offset);
+        ffi::Py_INCREF(value);
+        ffi::PyTuple_SetItem(result, (star + 1 + offset) as ffi::Py_ssize_t, value);
+    }
+    result
+}
+
+#[cfg(not(test))]
+unsafe fn unpack_iterable(
+    value: *mut ffi::PyObject,
+    count: usize,
+    starred: Option<usize>,
+) -> *mut ffi::PyObject {
+    let iterator = ffi::PyObject_GetIter(value);
+    if iterator.is_null() {
+        if ffi::PyErr_ExceptionMatches(ffi::PyExc_TypeError) != 0
+            && (*ffi::Py_TYPE(value)).tp_iter.is_none()
+            && ffi::PySequence_Check(value) == 0
+        {
+            ffi::PyErr_Clear();
+            raise_type_error_message(format!(
+                "cannot unpack non-iterable {} object",
+                object_type_name(value)
+            ));
+        }
+        return ptr::null_mut();
+    }
+    let leading = starred.unwrap_or(count);
+    let mut items = Vec::with_capacity(count);
+    while items.len() < leading {
+        let item = ffi::PyIter_Next(iterator);
+        if item.is_null() {
+            if ffi::PyErr_Occurred().is_null() {
+                let got = items.len();
+                raise_value_error_message(match starred {
+                    None => format!("not enough values to unpack (expected {count}, got {got})"),
+                    Some(_) => format!(
+                        "not enough values to unpack (expected at least {}, got {got})",
+                        count - 1
+                    ),
+                });
+            }
+            ffi::Py_DECREF(iterator);
+            release_owned_items(items);
+            return ptr::null_mut();
+        }
+        items.push(item);
+    }
+    let Some(star) = starred else {
+        let extra = ffi::PyIter_Next(iterator);
+        ffi::Py_DECREF(iterator);
+        if !extra.is_null() {
+            ffi::Py_DECREF(extra);
+            if ffi::PyList_CheckExact(value) != 0 || ffi::PyTuple_CheckExact(value) != 0 {
+                raise_value_error_message(format!(
+                    "too many values to unpack (expected {count}, got {})",
+                    ffi::Py_SIZE(value)
+                ));
+            } else if ffi::PyDict_CheckExact(value) != 0 {
+                raise_value_error_message(format!(
+                    "too many values to unpack (expected {count}, got {})",
+                    ffi::PyDict_Size(value)
+                ));
+            } else {
+                raise_value_error_message(format!("too many values to unpack (expected {count})"));
+            }
+        }
+        if !ffi::PyErr_Occurred().is_null() {
+            release_owned_items(items);
+            return ptr::null_mut();
+        }
+        let result = ffi::PyTuple_New(count as ffi::Py_ssize_t);
+        if result.is_null() {
+            release_owned_items(items);
+            return ptr::null_mut();
+        }
+        for (index, item) in items.into_iter().enumerate() {
+            ffi::PyTuple_SetItem(result, index as ffi::Py_ssize_t, item);
+        }
+        return result;
+    };
+    let rest = ffi::PySequence_List(iterator);
+    ffi::Py_DECREF(iterator);
+    if rest.is_null() {
+        release_owned_items(items);
+        return ptr::null_mut();
+    }
+    let after = count - star - 1;
+    let rest_len = ffi::PyList_Size(rest) as usize;
+    if rest_len < after {
+        raise_value_error_message(format!(
+            "not enough values to unpack (expected at least {}, got {})",
+            count - 1,
+            star + rest_len
+        ));
+        ffi::Py_DECREF(rest);
+        release_owned_items(items);
+        return ptr::null_mut();
+    }
+    let result = ffi::PyTuple_New(count as ffi::Py_ssize_t);
+    if result.is_null() {
+        ffi::Py_DECREF(rest);
+        release_owned_items(items);
+        return ptr::null_mut();
+    }
+    for (index, item) in items.into_iter().enumerate() {
+        ffi::PyTuple_SetItem(result, index as ffi::Py_ssize_t, item);
+    }
+    let middle_end = rest_len - after;
+    for offset in 0..after {
+        let value = ffi::PyList_GetItem(rest, (middle_end + offset) as ffi::Py_ssize_t);
+        ffi::Py_INCREF(value);
+        ffi::PyTuple_SetItem(result, (star + 1 + offset) as ffi::Py_ssize_t, value);
+    }
+    if after > 0
+        && ffi::PyList_SetSlice(
+            rest,
+            middle_end as ffi::Py_ssize_t,
+            rest_len as ffi::Py_ssize_t,
+            ptr::null_mut(),
+        ) != 0
+    {
+        ffi::Py_DECREF(rest);
+        ffi::Py_DECREF(result);
+        return ptr::null_mut();
+    }
+    ffi::PyTuple_SetItem(result, star as ffi::Py_ssize_t, rest);
+    result
+}
+
+#[cfg(not(test))]
+unsafe extern "C" fn unpack_hook(value: ObjPtr, count: i64, starred: i64) -> ObjPtr {
+    let value = value as *mut ffi::PyObject;
+    let count = count as usize;
+    let starred = usize::try_from(starred).ok();
+    if ffi::PyTuple_CheckExact(value) != 0 || ffi::PyList_CheckExact(value) != 0 {
+        let size = ffi::Py_SIZE(value) as usize;
+        let fits = match starred {
+            None => size == count,
+            Some(_) => size + 1 >= count,
+        };
+        if fits {
+            return unpack_exact_sequence(value, count, starred) as ObjPtr;
+        }
+    }
+    unpack_iterable(value, count, starred) as ObjPtr
+}
+
 #[cfg(not(test))]
 unsafe extern "C" fn store_global_hook(
     globals_obj: ObjPtr,
@@ -653,6 +981,16 @@ unsafe extern "C" fn del_global_hook(
         );
         return ptr::null_mut();
     }
+    // A pending lazy import is a binding of its name that is not in the dict
+    // yet, so deleting it succeeds even when the dict has no entry.
+    let quietly = match module_imports::discard_lazy_global(
+        globals_obj as *mut ffi::PyObject,
+        key as *mut ffi::PyObject,
+    ) {
+        0 => quietly,
+        1 => true,
+        _ => return ptr::null_mut(),
+    };
     if let Some(cache) = ModuleGlobalCache::lookup(globals_obj as *mut ffi::PyObject) {
         return cache.del_global_write_through(key as *mut ffi::PyObject, slot_index as u32, quietly)
             as ObjPtr;
@@ -922,6 +1260,11 @@ mod test_only_export_stubs {
     ));
     panic_obj_export!(dp_jit_py_call_with_kw(callable: ObjPtr, args: ObjPtr, kw: ObjPtr));
     panic_i64_export!(dp_jit_callee_function_id(callable: ObjPtr));
+    panic_i64_export!(dp_jit_inline_guard(
+        callable: ObjPtr,
+        globals: ObjPtr,
+        function_id: i64,
+    ));
     panic_obj_export!(dp_jit_lookup_direct_code_ptr(vmctx: ObjPtr, function_id: i64));
     panic_obj_export!(dp_jit_get_raised_exception());
     panic_obj_export!(dp_jit_get_arg_item(args: ObjPtr, index: i64));
@@ -967,6 +1310,43 @@ mod test_only_export_stubs {
     panic_obj_export!(dp_jit_tuple_new(size: i64));
     panic_i32_export!(dp_jit_tuple_set_item(tuple_obj: ObjPtr, index: i64, item: ObjPtr));
     panic_i32_export!(dp_jit_is_true(value: ObjPtr));
+    panic_obj_export!(dp_jit_build_list_new());
+    panic_obj_export!(dp_jit_build_list_append(list: ObjPtr, item: ObjPtr));
+    panic_obj_export!(dp_jit_build_list_extend(list: ObjPtr, iterable: ObjPtr));
+    panic_obj_export!(dp_jit_build_list_to_tuple(list: ObjPtr));
+    panic_obj_export!(dp_jit_build_set_new());
+    panic_obj_export!(dp_jit_build_set_add(set: ObjPtr, item: ObjPtr));
+    panic_obj_export!(dp_jit_build_set_update(set: ObjPtr, iterable: ObjPtr));
+    panic_obj_export!(dp_jit_build_dict_new());
+    panic_obj_export!(dp_jit_build_dict_set_item(dict: ObjPtr, key: ObjPtr, value: ObjPtr));
+    panic_obj_export!(dp_jit_build_dict_update(dict: ObjPtr, mapping: ObjPtr));
+    panic_obj_export!(dp_jit_get_iter(iterable: ObjPtr));
+    panic_obj_export!(dp_jit_for_iter(iter: ObjPtr));
+    panic_obj_export!(dp_jit_iter_complete());
+    panic_i64_export!(dp_jit_range_counter(iterable: ObjPtr, counter: *mut c_void));
+    panic_obj_export!(dp_jit_long_from_i64(value: i64));
+    panic_obj_export!(dp_jit_unpack(value: ObjPtr, count: i64, starred: i64));
+    panic_obj_export!(dp_jit_import_cached(vmctx: ObjPtr, name: ObjPtr, site_key: i64));
+    panic_obj_export!(dp_jit_import_name(
+        vmctx: ObjPtr,
+        name: ObjPtr,
+        spec: ObjPtr,
+        fromlist: ObjPtr,
+        level: i64,
+        site_key: i64
+    ));
+    panic_obj_export!(dp_jit_import_from(module: ObjPtr, attr: ObjPtr));
+    panic_obj_export!(dp_jit_format_value(value: ObjPtr, conversion: i64, format_spec: ObjPtr));
+    panic_obj_export!(dp_jit_build_string(parts: ObjPtr, count: i64));
+    panic_obj_export!(dp_jit_create_class(
+        name: ObjPtr,
+        namespace_fn: ObjPtr,
+        bases: ObjPtr,
+        kwds: ObjPtr,
+        requires_class_cell: i64,
+        firstlineno: i64,
+        static_attributes: ObjPtr
+    ));
 }
 
 #[cfg(test)]
@@ -1017,6 +1397,15 @@ pub unsafe extern "C" fn dp_jit_callee_function_id(callable: ObjPtr) -> i64 {
     callee_function_id_hook(callable)
 }
 
+#[cfg(not(test))]
+pub unsafe extern "C" fn dp_jit_inline_guard(
+    callable: ObjPtr,
+    globals: ObjPtr,
+    function_id: i64,
+) -> i64 {
+    inline_guard_hook(callable, globals, function_id)
+}
+
 #[cfg(not(test))]
 pub unsafe extern "C" fn dp_jit_lookup_direct_code_ptr(vmctx: ObjPtr, function_id: i64) -> ObjPtr {
     lookup_direct_code_ptr_hook(vmctx, function_id)
@@ -1093,6 +1482,190 @@ pub unsafe extern "C" fn dp_jit_pyobject_delitem(obj: ObjPtr, key: ObjPtr) -> Ob
     pyobject_delitem_hook(obj, key)
 }
 
+#[cfg(not(test))]
+pub unsafe extern "C" fn dp_jit_build_list_new() -> ObjPtr {
+    ffi::PyList_New(0) as ObjPtr
+}
+
+#[cfg(not(test))]
+pub unsafe extern "C" fn dp_jit_build_list_append(list: ObjPtr, item: ObjPtr) -> ObjPtr {
+    let rc = ffi::PyList_Append(list as *mut ffi::PyObject, item as *mut ffi::PyObject);
+    finish_container_step(list, rc)
+}
+
+#[cfg(not(test))]
+pub unsafe extern "C" fn dp_jit_build_list_extend(list: ObjPtr, iterable: ObjPtr) -> ObjPtr {
+    build_list_extend_hook(list, iterable)
+}
+
+#[cfg(not(test))]
+pub unsafe extern "C" fn dp_jit_build_list_to_tuple(list: ObjPtr) -> ObjPtr {
+    build_list_to_tuple_hook(list)
+}
+
+#[cfg(not(test))]
+pub unsafe extern "C" fn dp_jit_build_set_new() -> ObjPtr {
+    ffi::PySet_New(ptr::null_mut()) as ObjPtr
+}
+
+#[cfg(not(test))]
+pub unsafe extern "C" fn dp_jit_build_set_add(set: ObjPtr, item: ObjPtr) -> ObjPtr {
+    let rc = ffi::PySet_Add(set as *mut ffi::PyObject, item as *mut ffi::PyObject);
+    finish_container_step(set, rc)
+}
+
+#[cfg(not(test))]
+pub unsafe extern "C" fn dp_jit_build_set_update(set: ObjPtr, iterable: ObjPtr) -> ObjPtr {
+    let rc = extend_container_from_iterable(
+        set as *mut ffi::PyObject,
+        iterable as *mut ffi::PyObject,
+        ffi::PySet_Add,
+    );
+    finish_container_step(set, rc)
+}
+
+#[cfg(not(test))]
+pub unsafe extern "C" fn dp_jit_build_dict_new() -> ObjPtr {
+    ffi::PyDict_New() as ObjPtr
+}
+
+#[cfg(not(test))]
+pub unsafe extern "C" fn dp_jit_build_dict_set_item(
+    dict: ObjPtr,
+    key: ObjPtr,
+    value: ObjPtr,
+) -> ObjPtr {
+    let rc = ffi::PyDict_SetItem(
+        dict as *mut ffi::PyObject,
+        key as *mut ffi::PyObject,
+        value as *mut ffi::PyObject,
+    );
+    finish_container_step(dict, rc)
+}
+
+#[cfg(not(test))]
+pub unsafe extern "C" fn dp_jit_build_dict_update(dict: ObjPtr, mapping: ObjPtr) -> ObjPtr {
+    build_dict_update_hook(dict, mapping)
+}
+
+#[cfg(not(test))]
+pub unsafe extern "C" fn dp_jit_get_iter(iterable: ObjPtr) -> ObjPtr {
+    super::for_iter::get_iter(iterable as *mut ffi::PyObject) as ObjPtr
+}
+
+#[cfg(not(test))]
+pub unsafe extern "C" fn dp_jit_for_iter(iter: ObjPtr) -> ObjPtr {
+    super::for_iter::for_iter(iter as *mut ffi::PyObject) as ObjPtr
+}
+
+#[cfg(not(test))]
+pub unsafe extern "C" fn dp_jit_iter_complete() -> ObjPtr {
+    super::for_iter::iter_complete() as ObjPtr
+}
+
+#[cfg(not(test))]
+pub unsafe extern "C" fn dp_jit_range_counter(iterable: ObjPtr, counter: *mut c_void) -> i64 {
+    super::for_iter::range_counter(iterable as *mut ffi::PyObject, counter.cast())
+}
+
+#[cfg(not(test))]
+pub unsafe extern "C" fn dp_jit_long_from_i64(value: i64) -> ObjPtr {
+    ffi::PyLong_FromLongLong(value) as ObjPtr
+}
+
+#[cfg(not(test))]
+pub unsafe extern "C" fn dp_jit_unpack(value: ObjPtr, count: i64, starred: i64) -> ObjPtr {
+    unpack_hook(value, count, starred)
+}
+
+#[cfg(not(test))]
+unsafe fn import_site_cache<'a>(vmctx: ObjPtr) -> Option<&'a ImportSiteCache> {
+    if vmctx.is_null() {
+        return None;
+    }
+    let vmctx = &*(vmctx as *const JitModuleVmCtx);
+    vmctx
+        .shared_module_state
+        .as_ref()
+        .map(|shared_state| shared_state.import_site_cache())
+}
+
+#[cfg(not(test))]
+pub unsafe extern "C" fn dp_jit_import_cached(
+    vmctx: ObjPtr,
+    name: ObjPtr,
+    site_key: i64,
+) -> ObjPtr {
+    module_imports::import_cached(
+        import_site_cache(vmctx),
+        name as *mut ffi::PyObject,
+        site_key as u64,
+    ) as ObjPtr
+}
+
+#[cfg(not(test))]
+pub unsafe extern "C" fn dp_jit_import_name(
+    vmctx: ObjPtr,
+    name: ObjPtr,
+    spec: ObjPtr,
+    fromlist: ObjPtr,
+    level: i64,
+    site_key: i64,
+) -> ObjPtr {
+    module_imports::import_name(
+        import_site_cache(vmctx),
+        name as *mut ffi::PyObject,
+        spec as *mut ffi::PyObject,
+        fromlist as *mut ffi::PyObject,
+        level,
+        site_key as u64,
+    ) as ObjPtr
+}
+
+#[cfg(not(test))]
+pub unsafe extern "C" fn dp_jit_import_from(module: ObjPtr, attr: ObjPtr) -> ObjPtr {
+    module_imports::import_from(module as *mut ffi::PyObject, attr as *mut ffi::PyObject) as ObjPtr
+}
+
+#[cfg(not(test))]
+pub unsafe extern "C" fn dp_jit_format_value(
+    value: ObjPtr,
+    conversion: i64,
+    format_spec: ObjPtr,
+) -> ObjPtr {
+    super::string_format::format_value(
+        value as *mut ffi::PyObject,
+        conversion,
+        format_spec as *mut ffi::PyObject,
+    ) as ObjPtr
+}
+
+#[cfg(not(test))]
+pub unsafe extern "C" fn dp_jit_build_string(parts: ObjPtr, count: i64) -> ObjPtr {
+    super::string_format::build_string(parts as *const *mut ffi::PyObject, count as usize) as ObjPtr
+}
+
+#[cfg(not(test))]
+pub unsafe extern "C" fn dp_jit_create_class(
+    name: ObjPtr,
+    namespace_fn: ObjPtr,
+    bases: ObjPtr,
+    kwds: ObjPtr,
+    requires_class_cell: i64,
+    firstlineno: i64,
+    static_attributes: ObjPtr,
+) -> ObjPtr {
+    module_classes::create_class(
+        name as *mut ffi::PyObject,
+        namespace_fn as *mut ffi::PyObject,
+        bases as *mut ffi::PyObject,
+        kwds as *mut ffi::PyObject,
+        requires_class_cell != 0,
+        firstlineno,
+        static_attributes as *mut ffi::PyObject,
+    ) as ObjPtr
+}
+
 #[cfg(not(test))]
 pub unsafe extern "C" fn dp_jit_load_global_obj(
     globals_obj: ObjPtr,
@@ -1437,6 +2010,7 @@ pub fn register_specialized_jit_symbols(builder: &mut JITBuilder) {
         "dp_jit_callee_function_id",
         dp_jit_callee_function_id as *const u8,
     );
+    builder.symbol("dp_jit_inline_guard", dp_jit_inline_guard as *const u8);
     builder.symbol(
         "dp_jit_lookup_direct_code_ptr",
         dp_jit_lookup_direct_code_ptr as *const u8,
@@ -1456,6 +2030,46 @@ pub fn register_specialized_jit_symbols(builder: &mut JITBuilder) {
     builder.symbol("dp_jit_tuple_new", dp_jit_tuple_new as *const u8);
     builder.symbol("dp_jit_tuple_set_item", dp_jit_tuple_set_item as *const u8);
     builder.symbol("dp_jit_is_true", dp_jit_is_true as *const u8);
+    builder.symbol("dp_jit_build_list_new", dp_jit_build_list_new as *const u8);
+    builder.symbol(
+        "dp_jit_build_list_append",
+        dp_jit_build_list_append as *const u8,
+    );
+    builder.symbol(
+        "dp_jit_build_list_extend",
+        dp_jit_build_list_extend as *const u8,
+    );
+    builder.symbol(
+        "dp_jit_build_list_to_tuple",
+        dp_jit_build_list_to_tuple as *const u8,
+    );
+    builder.symbol("dp_jit_build_set_new", dp_jit_build_set_new as *const u8);
+    builder.symbol("dp_jit_build_set_add", dp_jit_build_set_add as *const u8);
+    builder.symbol(
+        "dp_jit_build_set_update",
+        dp_jit_build_set_update as *const u8,
+    );
+    builder.symbol("dp_jit_build_dict_new", dp_jit_build_dict_new as *const u8);
+    builder.symbol(
+        "dp_jit_build_dict_set_item",
+        dp_jit_build_dict_set_item as *const u8,
+    );
+    builder.symbol(
+        "dp_jit_build_dict_update",
+        dp_jit_build_dict_update as *const u8,
+    );
+    builder.symbol("dp_jit_get_iter", dp_jit_get_iter as *const u8);
+    builder.symbol("dp_jit_for_iter", dp_jit_for_iter as *const u8);
+    builder.symbol("dp_jit_iter_complete", dp_jit_iter_complete as *const u8);
+    builder.symbol("dp_jit_range_counter", dp_jit_range_counter as *const u8);
+    builder.symbol("dp_jit_long_from_i64", dp_jit_long_from_i64 as *const u8);
+    builder.symbol("dp_jit_unpack", dp_jit_unpack as *const u8);
+    builder.symbol("dp_jit_import_cached", dp_jit_import_cached as *const u8);
+    builder.symbol("dp_jit_import_name", dp_jit_import_name as *const u8);
+    builder.symbol("dp_jit_import_from", dp_jit_import_from as *const u8);
+    builder.symbol("dp_jit_format_value", dp_jit_format_value as *const u8);
+    builder.symbol("dp_jit_build_string", dp_jit_build_string as *const u8);
+    builder.symbol("dp_jit_create_class", dp_jit_create_class as *const u8);
     builder.symbol("dp_jit_raise_from_exc", dp_jit_raise_from_exc as *const u8);
     builder.symbol(
         "PyObject_RichCompare",
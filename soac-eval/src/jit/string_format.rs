@@ -0,0 +1,121 @@
+//! Runtime side of the `FormatValue` / `BuildString` operations.
+//!
+//! `FormatValue` returns exact `str` values untouched when there is no format
+//! spec, like CPython's `FORMAT_SIMPLE`, and otherwise goes through
+//! `PyObject_Format`. `BuildString` concatenates the parts the JIT spills to a
+//! stack array natively: with a `PyUnicodeWriter` sized for the result on
+//! 3.14+, and with `PyUnicode_Join` before that, so the parts are never
+//! re-encoded.
+
+use pyo3::ffi;
+use std::ptr;
+
+use super::intrinsics::{FORMAT_CONVERSION_ASCII, FORMAT_CONVERSION_REPR, FORMAT_CONVERSION_STR};
+
+/// `format(conversion(value), format_spec)`; `format_spec` may be null for
+/// the empty spec. Returns a new reference, or null with an exception set.
+pub unsafe fn format_value(
+    value: *mut ffi::PyObject,
+    conversion: i64,
+    format_spec: *mut ffi::PyObject,
+) -> *mut ffi::PyObject {
+    let converted = match conversion {
+        FORMAT_CONVERSION_STR => ffi::PyObject_Str(value),
+        FORMAT_CONVERSION_REPR => ffi::PyObject_Repr(value),
+        FORMAT_CONVERSION_ASCII => ffi::PyObject_ASCII(value),
+        _ => {
+            ffi::Py_INCREF(value);
+            value
+        }
+    };
+    if converted.is_null() {
+        return ptr::null_mut();
+    }
+    if format_spec.is_null() && ffi::PyUnicode_CheckExact(converted) != 0 {
+        return converted;
+    }
+    let formatted = ffi::PyObject_Format(converted, format_spec);
+    ffi::Py_DECREF(converted);
+    formatted
+}
+
+#[cfg(Py_3_14)]
+#[repr(C)]
+struct PyUnicodeWriter {
+    _private: [u8; 0],
+}
+
+#[cfg(Py_3_14)]
+unsafe extern "C" {
+    fn PyUnicodeWriter_Create(length: ffi::Py_ssize_t) -> *mut PyUnicodeWriter;
+    fn PyUnicodeWriter_Discard(writer: *mut PyUnicodeWriter);
+    fn PyUnicodeWriter_Finish(writer: *mut PyUnicodeWriter) -> *mut ffi::PyObject;
+    fn PyUnicodeWriter_WriteSubstring(
+        writer: *mut PyUnicodeWriter,
+        str: *mut ffi::PyObject,
+        start: ffi::Py_ssize_t,
+        end: ffi::Py_ssize_t,
+    ) -> std::ffi::c_int;
+}
+
+/// `"".join(parts)` for the `count` borrowed `str` objects at `parts`.
+pub unsafe fn build_string(parts: *const *mut ffi::PyObject, count: usize) -> *mut ffi::PyObject {
+    let parts = std::slice::from_raw_parts(parts, count);
+    match parts {
+        [] => ffi::PyUnicode_FromStringAndSize(ptr::null(), 0),
+        [part] if ffi::PyUnicode_CheckExact(*part) != 0 => {
+            ffi::Py_INCREF(*part);
+            *part
+        }
+        _ => concat_parts(parts),
+    }
+}
+
+#[cfg(Py_3_14)]
+unsafe fn concat_parts(parts: &[*mut ffi::PyObject]) -> *mut ffi::PyObject {
+    let mut length: ffi::Py_ssize_t = 0;
+    for part in parts {
+        if ffi::PyUnicode_Check(*part) == 0 {
+            // `str.join` raises the same `TypeError` it would for this part.
+            return join_parts(parts);
+        }
+        length += ffi::PyUnicode_GET_LENGTH(*part);
+    }
+    let writer = PyUnicodeWriter_Create(length);
+    if writer.is_null() {
+        return ptr::null_mut();
+    }
+    for part in parts {
+        let end = ffi::PyUnicode_GET_LENGTH(*part);
+        if PyUnicodeWriter_WriteSubstring(writer, *part, 0, end) != 0 {
+            PyUnicodeWriter_Discard(writer);
+            return ptr::null_mut();
+        }
+    }
+    PyUnicodeWriter_Finish(writer)
+}
+
+#[cfg(not(Py_3_14))]
+unsafe fn concat_parts(parts: &[*mut ffi::PyObject]) -> *mut ffi::PyObject {
+    join_parts(parts)
+}
+
+unsafe fn join_parts(parts: &[*mut ffi::PyObject]) -> *mut ffi::PyObject {
+    let tuple = ffi::PyTuple_New(parts.len() as ffi::Py_ssize_t);
+    if tuple.is_null() {
+        return ptr::null_mut();
+    }
+    for (index, part) in parts.iter().enumerate() {
+        ffi::Py_INCREF(*part);
+        ffi::PyTuple_SET_ITEM(tuple, index as ffi::Py_ssize_t, *part);
+    }
+    let separator = ffi::PyUnicode_FromStringAndSize(ptr::null(), 0);
+    if separator.is_null() {
+        ffi::Py_DECREF(tuple);
+        return ptr::null_mut();
+    }
+    let joined = ffi::PyUnicode_Join(separator, tuple);
+    ffi::Py_DECREF(separator);
+    ffi::Py_DECREF(tuple);
+    joined
+}
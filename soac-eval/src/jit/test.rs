@@ -1,11 +1,12 @@
 use super::*;
 use soac_blockpy::block_py::{
     BinOp, BinOpKind, BlockParamRole, BlockPyFunction, BlockPyLiteral, BlockPyModule, BlockTerm,
-    Call, CallArgPositional, CellLocation, ClosureInit, ClosureSlot, CodegenBlock,
-    CodegenBlockPyExpr, CoreBlockPyExpr, CoreNumberLiteral, CoreNumberLiteralValue,
-    CoreStringLiteral, CounterSite, Del, DelItem, FunctionName, LiteralValue, Load,
-    LocatedCoreBlockPyExpr, LocatedName, ModuleNameGen, NameLocation, Param, ParamKind, ParamSpec,
-    StorageLayout, Store,
+    BuildDict, BuildDictItem, BuildElement, BuildSet, BuildString, BuildTuple, Call,
+    CallArgPositional, CellLocation, ClosureInit, ClosureSlot, CodegenBlock, CodegenBlockPyExpr,
+    CoreBlockPyExpr, CoreNumberLiteral, CoreNumberLiteralValue, CoreStringLiteral, CounterSite,
+    CreateClass, Del, DelItem, ForIter, FunctionName, GetIter, ImportFrom, ImportName,
+    LiteralValue, Load, LocatedCoreBlockPyExpr, LocatedName, ModuleNameGen, NameLocation, Param,
+    ParamKind, ParamSpec, StorageLayout, Store, Unpack,
 };
 use soac_blockpy::passes::{
     CodegenBlockPyPass, instrument_bb_module_with_block_entry_counters,
@@ -314,6 +315,7 @@ mod tests {
                 shared_module_state: std::sync::Arc::as_ptr(&shared_state),
                 globals_obj,
                 global_slots: global_cache.slots_ptr().cast::<c_void>(),
+                global_version: global_cache.version_ptr(),
                 true_obj,
                 false_obj,
                 none_obj,
@@ -637,6 +639,8 @@ mod tests {
     fn jit_vectorcall_trampoline_can_link_runtime_decref_clif() {
         unsafe {
             let compiled = Box::new(CompiledSpecializedRunner {
+                _gdb_registration: None,
+                _code_region: None,
                 _jit_module: new_jit_module().expect("compiled runner jit module should construct"),
                 entry: Some(CompiledRunnerEntry::Direct {
                     code_ptr: std::ptr::null(),
@@ -650,6 +654,7 @@ mod tests {
                 1usize as ObjPtr,
                 compiled_handle,
                 "jit_runtime_support_vectorcall_smoke",
+                false,
             );
 
             match result {
@@ -749,6 +754,8 @@ def f():
                     &shared_state.lowered_module.counter_defs,
                     &module_constant_ptrs,
                     &counter_ptrs,
+                    None,
+                    false,
                 )
                 .expect("direct counter test function should compile");
                 let (code_ptr, param_count) = compiled_direct_runner_info(compiled_handle)
@@ -878,6 +885,8 @@ def f(x):
                     &shared_state.lowered_module.counter_defs,
                     &module_constant_ptrs,
                     &counter_ptrs,
+                    None,
+                    false,
                 )
                 .expect("direct refcount counter test function should compile");
                 let (code_ptr, param_count) = compiled_direct_runner_info(compiled_handle)
@@ -918,6 +927,128 @@ def f(x):
         }
     }
 
+    #[test]
+    fn jit_hoisted_global_load_sees_rebinding_inside_the_loop() {
+        let _guard = crate::python_runtime_test_lock().lock().unwrap();
+        unsafe {
+            let python_home = vendored_python_home();
+            let repo_root = repo_root();
+            let soac_py_src = repo_root.join("soac_py").join("src");
+            let ext_staging_dir = ensure_test_extension_staging_dir();
+            let pythonpath = std::env::join_paths([
+                python_home.join("Lib"),
+                vendored_python_build_lib_dir(),
+                soac_py_src.clone(),
+                ext_staging_dir.clone(),
+            ])
+            .expect("test PYTHONPATH should join cleanly");
+            std::env::set_var("PYTHONHOME", &python_home);
+            std::env::set_var("PYTHONPATH", pythonpath);
+            Python::initialize();
+            Python::attach(|py| {
+                let sys = py.import("sys").expect("sys should import");
+                sys.getattr("path")
+                    .expect("sys.path should exist")
+                    .call_method1("insert", (0, ext_staging_dir.to_string_lossy().as_ref()))
+                    .expect("sys.path should accept staged _soac_ext");
+                sys.getattr("path")
+                    .expect("sys.path should exist")
+                    .call_method1("insert", (0, soac_py_src.to_string_lossy().as_ref()))
+                    .expect("sys.path should accept soac_py/src");
+                let lowered = soac_blockpy::lower_python_to_blockpy_for_testing(
+                    r#"
+def f():
+    total = 0
+    for _ in range(3):
+        total = total + STEP
+        ns["STEP"] = STEP + 10
+    return total
+"#,
+                )
+                .expect("lowering should succeed")
+                .codegen_module;
+
+                let function = lowered
+                    .callable_defs
+                    .iter()
+                    .find(|function| function.names.bind_name == "f")
+                    .expect("missing lowered function f")
+                    .clone();
+                assert!(
+                    function
+                        .blocks
+                        .iter()
+                        .any(|block| block.body.iter().any(|expr| matches!(
+                            expr,
+                            CodegenBlockPyExpr::PreloadGlobal(op) if op.name.id.as_str() == "STEP"
+                        ))),
+                    "STEP should be hoisted out of the loop by default"
+                );
+
+                let shared_state = crate::module_type::build_shared_state_for_testing(
+                    py,
+                    lowered,
+                    "hoist_test",
+                    "",
+                )
+                .expect("shared state should build");
+                let runtime = build_test_module_runtime(py, shared_state.clone());
+                let globals = runtime.vmctx.globals_obj.cast::<ffi::PyObject>();
+                let step = ffi::PyLong_FromLongLong(1_000_000_000);
+                assert_eq!(
+                    ffi::PyDict_SetItemString(globals, c"STEP".as_ptr(), step),
+                    0
+                );
+                assert_eq!(
+                    ffi::PyDict_SetItemString(globals, c"ns".as_ptr(), globals),
+                    0
+                );
+                let module_constant_ptrs = shared_state.module_constant_ptrs();
+                let counter_ptrs = shared_state.counter_ptrs();
+                let blocks = vec![std::ptr::null_mut::<c_void>(); function.blocks.len()];
+                let compiled_handle = compile_cranelift_run_bb_specialized_cached(
+                    &blocks,
+                    &shared_state.lowered_module,
+                    &function,
+                    &shared_state.codegen_constants,
+                    &shared_state.lowered_module.counter_defs,
+                    &module_constant_ptrs,
+                    &counter_ptrs,
+                    None,
+                    false,
+                )
+                .expect("hoisted test function should compile");
+                let (code_ptr, param_count) = compiled_direct_runner_info(compiled_handle)
+                    .expect("compiled direct runner should expose entrypoint");
+                assert_eq!(param_count, 0, "test function should not take direct args");
+                let entry: unsafe extern "C" fn(*mut c_void, *mut c_void) -> *mut c_void =
+                    std::mem::transmute(code_ptr);
+
+                let result = entry(
+                    std::ptr::addr_of!(runtime.vmctx).cast_mut().cast(),
+                    std::ptr::null_mut(),
+                )
+                .cast::<ffi::PyObject>();
+                assert!(!result.is_null(), "hoisted test function should not raise");
+                assert_eq!(
+                    ffi::PyLong_AsLongLong(result),
+                    3_000_000_030,
+                    "every iteration after the first should see the rebound STEP"
+                );
+                assert_eq!(
+                    ffi::Py_REFCNT(step),
+                    1,
+                    "the hoisted local should not keep the replaced STEP alive"
+                );
+
+                ffi::Py_DECREF(result);
+                ffi::Py_DECREF(step);
+                assert_eq!(ffi::PyDict_DelItemString(globals, c"ns".as_ptr()), 0);
+                free_cranelift_run_bb_specialized_cached(compiled_handle);
+            });
+        }
+    }
+
     #[test]
     fn render_specialized_jit_clif_smoke() {
         let blocks = [1usize as ObjPtr, 2usize as ObjPtr, 3usize as ObjPtr];
@@ -1197,6 +1328,28 @@ def f(x):
         );
     }
 
+    #[test]
+    fn render_specialized_jit_guarded_global_load_falls_back_to_direct_helper() {
+        let blocks = [1usize as ObjPtr];
+        let mut function = with_single_test_block(
+            test_function(),
+            vec![op_expr(PreloadGlobal::new(
+                test_global_name("x"),
+                test_name("hoisted"),
+            ))],
+            ret_term(op_expr(GuardedGlobalLoad::new(
+                test_global_name("x"),
+                test_name("hoisted"),
+            ))),
+        );
+        set_stack_slots(&mut function, &["hoisted"]);
+        let rendered = render_test_jit_function(&function, &blocks);
+        assert!(
+            rendered.contains("select") && rendered.contains("call dp_jit_load_global_obj"),
+            "guarded global loads should preload the cache slot and version and fall back to the direct helper:\n{rendered}"
+        );
+    }
+
     #[test]
     fn render_specialized_jit_store_global_intrinsic_uses_direct_helper() {
         let blocks = [1usize as ObjPtr];
@@ -1349,6 +1502,302 @@ def f(x):
         );
     }
 
+    #[test]
+    fn render_specialized_jit_display_builds_use_container_helpers() {
+        let blocks = [1usize as ObjPtr];
+        let mut constants = TestConstantPool::default();
+        let function = with_single_test_block(
+            test_function(),
+            vec![
+                expr_stmt(op_expr(BuildTuple::new(vec![
+                    BuildElement::Value(constants.int_expr(1)),
+                    BuildElement::Value(constants.int_expr(2)),
+                ]))),
+                expr_stmt(op_expr(BuildTuple::new(vec![
+                    BuildElement::Value(constants.int_expr(1)),
+                    BuildElement::Unpack(constants.int_expr(2)),
+                ]))),
+                expr_stmt(op_expr(BuildSet::new(vec![
+                    BuildElement::Value(constants.int_expr(1)),
+                    BuildElement::Unpack(constants.int_expr(2)),
+                ]))),
+                expr_stmt(op_expr(BuildDict::new(vec![
+                    BuildDictItem::Pair {
+                        key: constants.int_expr(1),
+                        value: constants.int_expr(2),
+                    },
+                    BuildDictItem::Unpack(constants.int_expr(3)),
+                ]))),
+            ],
+            ret_term(constants.int_expr(0)),
+        );
+        let rendered = render_test_jit_function_with_module_constants(
+            &function,
+            &blocks,
+            constants.module_constants,
+        );
+        for helper in [
+            "dp_jit_tuple_new",
+            "dp_jit_build_list_new",
+            "dp_jit_build_list_append",
+            "dp_jit_build_list_extend",
+            "dp_jit_build_list_to_tuple",
+            "dp_jit_build_set_new",
+            "dp_jit_build_set_add",
+            "dp_jit_build_set_update",
+            "dp_jit_build_dict_new",
+            "dp_jit_build_dict_set_item",
+            "dp_jit_build_dict_update",
+        ] {
+            assert!(
+                rendered.contains(&format!("call {helper}")),
+                "display build should call {helper}:\n{rendered}"
+            );
+        }
+        assert!(
+            !rendered.contains("call dp_jit_py_call"),
+            "display builds should not go through a runtime helper call:\n{rendered}"
+        );
+    }
+
+    #[test]
+    fn render_specialized_jit_for_loop_steps_use_iteration_helpers() {
+        let blocks = [1usize as ObjPtr];
+        let mut constants = TestConstantPool::default();
+        let mut function = with_single_test_block(
+            test_function(),
+            vec![
+                assign_stmt(
+                    test_name("it"),
+                    op_expr(GetIter::new(constants.int_expr(1))),
+                ),
+                expr_stmt(op_expr(BinOp::new(
+                    BinOpKind::Is,
+                    op_expr(ForIter::new(name_expr(test_name("it")))),
+                    name_expr(test_runtime_name("ITER_COMPLETE")),
+                ))),
+            ],
+            ret_term(constants.int_expr(0)),
+        );
+        set_stack_slots(&mut function, &["it"]);
+        let rendered = render_test_jit_function_with_module_constants(
+            &function,
+            &blocks,
+            constants.module_constants,
+        );
+        for helper in ["dp_jit_get_iter", "dp_jit_for_iter", "dp_jit_iter_complete"] {
+            assert!(
+                rendered.contains(&format!("call {helper}")),
+                "for loop step should call {helper}:\n{rendered}"
+            );
+        }
+        assert!(
+            !rendered.contains("call dp_jit_load_runtime_obj"),
+            "the ITER_COMPLETE test should not look the sentinel up per step:\n{rendered}"
+        );
+    }
+
+    #[test]
+    fn render_specialized_jit_for_loop_counts_ranges_without_an_iterator() {
+        let blocks = [1usize as ObjPtr];
+        let mut constants = TestConstantPool::default();
+        let mut function = with_single_test_block(
+            test_function(),
+            vec![
+                assign_stmt(
+                    test_name("it"),
+                    op_expr(GetIter::new(constants.int_expr(1))),
+                ),
+                expr_stmt(op_expr(ForIter::new(name_expr(test_name("it"))))),
+            ],
+            ret_term(constants.int_expr(0)),
+        );
+        set_stack_slots(&mut function, &["it"]);
+        let rendered = render_test_jit_function_with_module_constants(
+            &function,
+            &blocks,
+            constants.module_constants,
+        );
+        for helper in ["dp_jit_range_counter", "dp_jit_long_from_i64"] {
+            assert!(
+                rendered.contains(&format!("call {helper}")),
+                "a loop-only iterator local should count ranges with {helper}:\n{rendered}"
+            );
+        }
+    }
+
+    #[test]
+    fn render_specialized_jit_for_loop_keeps_iterator_read_elsewhere() {
+        let blocks = [1usize as ObjPtr];
+        let mut constants = TestConstantPool::default();
+        let mut function = with_single_test_block(
+            test_function(),
+            vec![
+                assign_stmt(
+                    test_name("it"),
+                    op_expr(GetIter::new(constants.int_expr(1))),
+                ),
+                expr_stmt(op_expr(ForIter::new(name_expr(test_name("it"))))),
+                expr_stmt(name_expr(test_name("it"))),
+            ],
+            ret_term(constants.int_expr(0)),
+        );
+        set_stack_slots(&mut function, &["it"]);
+        let rendered = render_test_jit_function_with_module_constants(
+            &function,
+            &blocks,
+            constants.module_constants,
+        );
+        assert!(
+            !rendered.contains("call dp_jit_range_counter"),
+            "an iterator local read outside its loop must stay an object:\n{rendered}"
+        );
+        assert!(rendered.contains("call dp_jit_get_iter"), "{rendered}");
+    }
+
+    #[test]
+    fn render_specialized_jit_unpack_uses_native_helper() {
+        let blocks = [1usize as ObjPtr];
+        let mut constants = TestConstantPool::default();
+        let function = with_single_test_block(
+            test_function(),
+            vec![expr_stmt(op_expr(Unpack::new(
+                constants.int_expr(1),
+                3usize,
+                Some(1usize),
+            )))],
+            ret_term(constants.int_expr(0)),
+        );
+        let rendered = render_test_jit_function_with_module_constants(
+            &function,
+            &blocks,
+            constants.module_constants,
+        );
+        assert!(
+            rendered.contains("call dp_jit_unpack"),
+            "sequence unpacking should call the native helper:\n{rendered}"
+        );
+        assert!(
+            !rendered.contains("call dp_jit_load_runtime_obj"),
+            "sequence unpacking should not load the runtime unpack helper:\n{rendered}"
+        );
+    }
+
+    #[test]
+    fn render_specialized_jit_imports_check_site_cache_first() {
+        let blocks = [1usize as ObjPtr];
+        let mut constants = TestConstantPool::default();
+        let import_name = op_expr(ImportName::new(
+            "pkg.mod",
+            vec!["first".to_string(), "second".to_string()],
+            0u32,
+            constants.int_expr(0),
+        ));
+        let function = with_single_test_block(
+            test_function(),
+            vec![expr_stmt(op_expr(ImportFrom::new(import_name, "first")))],
+            ret_term(constants.int_expr(0)),
+        );
+        let rendered = render_test_jit_function_with_module_constants(
+            &function,
+            &blocks,
+            constants.module_constants,
+        );
+        let cached_at = rendered
+            .find("call dp_jit_import_cached")
+            .unwrap_or_else(|| panic!("import should consult the site cache:\n{rendered}"));
+        let import_at = rendered
+            .find("call dp_jit_import_name")
+            .unwrap_or_else(|| panic!("import should have a slow path:\n{rendered}"));
+        assert!(
+            cached_at < import_at,
+            "site cache should be checked before the full import:\n{rendered}"
+        );
+        assert!(
+            rendered.contains("call dp_jit_import_from"),
+            "from-import should call the native helper:\n{rendered}"
+        );
+        assert!(
+            !rendered.contains("call dp_jit_load_runtime_obj"),
+            "imports should not load runtime helpers:\n{rendered}"
+        );
+    }
+
+    #[test]
+    fn render_specialized_jit_create_class_calls_native_helper() {
+        let blocks = [1usize as ObjPtr];
+        let mut constants = TestConstantPool::default();
+        let create_class = op_expr(CreateClass::new(
+            "Box",
+            constants.int_expr(0),
+            constants.int_expr(1),
+            constants.int_expr(2),
+            true,
+            3u32,
+            constants.int_expr(4),
+        ));
+        let function = with_single_test_block(
+            test_function(),
+            vec![expr_stmt(create_class)],
+            ret_term(constants.int_expr(0)),
+        );
+        let rendered = render_test_jit_function_with_module_constants(
+            &function,
+            &blocks,
+            constants.module_constants,
+        );
+        assert!(
+            rendered.contains("call dp_jit_create_class"),
+            "class creation should call the native helper:\n{rendered}"
+        );
+        assert!(
+            !rendered.contains("call dp_jit_load_runtime_obj"),
+            "class creation should not load runtime helpers:\n{rendered}"
+        );
+    }
+
+    #[test]
+    fn render_specialized_jit_fstring_parts_call_native_helpers() {
+        let blocks = [1usize as ObjPtr];
+        let mut constants = TestConstantPool::default();
+        let formatted = op_expr(FormatValue::new(
+            constants.int_expr(1),
+            FormatConversion::Repr,
+            None,
+        ));
+        let build_string = op_expr(BuildString::new(vec![
+            constants.int_expr(0),
+            formatted,
+            constants.int_expr(2),
+        ]));
+        let function = with_single_test_block(
+            test_function(),
+            vec![expr_stmt(build_string)],
+            ret_term(constants.int_expr(0)),
+        );
+        let rendered = render_test_jit_function_with_module_constants(
+            &function,
+            &blocks,
+            constants.module_constants,
+        );
+        assert!(
+            rendered.contains("call dp_jit_format_value"),
+            "interpolation should call the native helper:\n{rendered}"
+        );
+        assert!(
+            rendered.contains("call dp_jit_build_string"),
+            "concatenation should call the native helper:\n{rendered}"
+        );
+        assert!(
+            rendered.contains("stack_addr"),
+            "string parts should be passed on the stack:\n{rendered}"
+        );
+        assert!(
+            !rendered.contains("call dp_jit_load_runtime_obj"),
+            "f-strings should not load runtime helpers:\n{rendered}"
+        );
+    }
+
     #[test]
     fn render_specialized_jit_direct_entry_uses_live_positional_defaults() {
         let blocks = [1usize as ObjPtr];
@@ -1511,4 +1960,57 @@ def f(x):
             "delete-backed JIT plans should update mirrored function-state slots:\n{rendered}"
         );
     }
+
+    #[test]
+    fn gdb_jit_source_map_resolves_one_based_lines() {
+        let source_map = JitSourceMap::new("m.py", "a = 1\nb = 2\n\nc = 3\n");
+        assert_eq!(source_map.line_for_offset(0), 1);
+        assert_eq!(source_map.line_for_offset(5), 1);
+        assert_eq!(source_map.line_for_offset(6), 2);
+        assert_eq!(source_map.line_for_offset(13), 4);
+    }
+
+    #[test]
+    fn gdb_jit_debug_elf_names_function_and_carries_line_table() {
+        let source_map = JitSourceMap::new("m.py", "def f():\n    return g()\n");
+        let srclocs = [
+            gdb_jit::JitSrcLocRange {
+                start: 0,
+                end: 8,
+                source_offset: 0,
+            },
+            gdb_jit::JitSrcLocRange {
+                start: 8,
+                end: 24,
+                source_offset: 20,
+            },
+        ];
+        let image = gdb_jit::build_debug_elf(&JitDebugFunction {
+            symbol_name: "py:d:f",
+            code_ptr: 0x1000 as *const u8,
+            code_size: 32,
+            srclocs: &srclocs,
+            source_map: Some(&source_map),
+        });
+        assert_eq!(&image[..4], b"\x7fELF");
+        let shnum = u16::from_le_bytes([image[60], image[61]]);
+        assert_eq!(
+            shnum, 8,
+            "null, text, symtab, strtab, 3 debug sections, shstrtab"
+        );
+        let contains = |needle: &[u8]| image.windows(needle.len()).any(|window| window == needle);
+        assert!(contains(b"py:d:f\0"));
+        assert!(contains(b".debug_line\0"));
+        assert!(contains(b"m.py\0"));
+
+        let without_source = gdb_jit::build_debug_elf(&JitDebugFunction {
+            symbol_name: "py:v:f",
+            code_ptr: 0x1000 as *const u8,
+            code_size: 32,
+            srclocs: &[],
+            source_map: None,
+        });
+        let shnum = u16::from_le_bytes([without_source[60], without_source[61]]);
+        assert_eq!(shnum, 5);
+    }
 }
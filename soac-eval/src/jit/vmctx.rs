@@ -12,6 +12,9 @@ pub struct JitModuleVmCtx {
     pub shared_module_state: *const SharedModuleState,
     pub globals_obj: ObjPtr,
     pub global_slots: ObjPtr,
+    /// The module global cache's version counter, which hoisted global loads
+    /// compare against.
+    pub global_version: *const u64,
     pub true_obj: ObjPtr,
     pub false_obj: ObjPtr,
     pub none_obj: ObjPtr,
@@ -44,6 +47,7 @@ impl Drop for ModuleRuntimeContext {
         self.vmctx.shared_module_state = ptr::null();
         self.vmctx.globals_obj = ptr::null_mut::<c_void>();
         self.vmctx.global_slots = ptr::null_mut::<c_void>();
+        self.vmctx.global_version = ptr::null();
         self.vmctx.true_obj = ptr::null_mut::<c_void>();
         self.vmctx.false_obj = ptr::null_mut::<c_void>();
         self.vmctx.none_obj = ptr::null_mut::<c_void>();
@@ -54,6 +58,7 @@ impl Drop for ModuleRuntimeContext {
 
 pub const GLOBALS_OBJ_OFFSET: i32 = offset_of!(JitModuleVmCtx, globals_obj) as i32;
 pub const GLOBAL_SLOTS_OFFSET: i32 = offset_of!(JitModuleVmCtx, global_slots) as i32;
+pub const GLOBAL_VERSION_OFFSET: i32 = offset_of!(JitModuleVmCtx, global_version) as i32;
 pub const TRUE_OBJ_OFFSET: i32 = offset_of!(JitModuleVmCtx, true_obj) as i32;
 pub const FALSE_OBJ_OFFSET: i32 = offset_of!(JitModuleVmCtx, false_obj) as i32;
 pub const NONE_OBJ_OFFSET: i32 = offset_of!(JitModuleVmCtx, none_obj) as i32;
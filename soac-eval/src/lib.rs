@@ -8,12 +8,17 @@ use std::sync::{Mutex, OnceLock};
 pub mod counter;
 pub mod counter_dump;
 pub mod jit;
+pub mod module_classes;
 pub mod module_constants;
 pub mod module_globals;
+pub mod module_imports;
 pub mod module_type;
+pub mod profile;
+pub mod runtime_options;
 pub mod session;
 pub mod tree_walk;
 
+pub use runtime_options::RuntimeOptions;
 pub use session::{CompileSession, CompileSessionId, allocate_compile_session_id};
 
 #[cfg(test)]
@@ -0,0 +1,248 @@
+//! Runtime side of the `CreateClass` operation.
+//!
+//! This is `soac.runtime.create_class` without the trip through Python:
+//! `__mro_entries__` resolution, metaclass selection and `__prepare__` follow
+//! `types.resolve_bases` / `types.prepare_class`, and the namespace gets the
+//! same `__classcell__`, `__firstlineno__`, `__static_attributes__` and
+//! `__orig_bases__` entries the class rewrite relies on.
+
+use pyo3::exceptions::PyTypeError;
+use pyo3::ffi;
+use pyo3::prelude::*;
+use pyo3::types::PyTuple;
+use std::ffi::CStr;
+use std::ffi::c_int;
+use std::ptr;
+
+type ObjPtr = *mut ffi::PyObject;
+
+unsafe extern "C" {
+    static mut PyCell_Type: ffi::PyTypeObject;
+    fn PyCell_New(obj: *mut ffi::PyObject) -> *mut ffi::PyObject;
+    fn PyCell_Set(cell: *mut ffi::PyObject, value: *mut ffi::PyObject) -> c_int;
+}
+
+unsafe fn owned<'py>(py: Python<'py>, value: ObjPtr) -> PyResult<Bound<'py, PyAny>> {
+    Bound::from_owned_ptr_or_err(py, value)
+}
+
+unsafe fn tuple_items<'py>(tuple: &Bound<'py, PyAny>) -> Vec<Bound<'py, PyAny>> {
+    let count = ffi::PyTuple_GET_SIZE(tuple.as_ptr());
+    let mut items = Vec::with_capacity(count as usize);
+    for index in 0..count {
+        items.push(Bound::from_borrowed_ptr(
+            tuple.py(),
+            ffi::PyTuple_GET_ITEM(tuple.as_ptr(), index),
+        ));
+    }
+    items
+}
+
+unsafe fn type_of<'py>(value: &Bound<'py, PyAny>) -> Bound<'py, PyAny> {
+    Bound::from_borrowed_ptr(value.py(), ffi::Py_TYPE(value.as_ptr()).cast())
+}
+
+/// `getattr(obj, name, None)` that only swallows `AttributeError`.
+unsafe fn optional_attr<'py>(
+    obj: &Bound<'py, PyAny>,
+    name: &CStr,
+) -> PyResult<Option<Bound<'py, PyAny>>> {
+    let value = ffi::PyObject_GetAttrString(obj.as_ptr(), name.as_ptr());
+    if !value.is_null() {
+        return Ok(Some(Bound::from_owned_ptr(obj.py(), value)));
+    }
+    if ffi::PyErr_ExceptionMatches(ffi::PyExc_AttributeError) == 0 {
+        return Err(PyErr::fetch(obj.py()));
+    }
+    ffi::PyErr_Clear();
+    Ok(None)
+}
+
+unsafe fn is_subclass(derived: &Bound<'_, PyAny>, cls: &Bound<'_, PyAny>) -> PyResult<bool> {
+    match ffi::PyObject_IsSubclass(derived.as_ptr(), cls.as_ptr()) {
+        -1 => Err(PyErr::fetch(derived.py())),
+        result => Ok(result == 1),
+    }
+}
+
+/// `types.resolve_bases`: returns `bases` itself unless some base defines
+/// `__mro_entries__`, so callers can test identity like the Python version.
+unsafe fn resolve_bases<'py>(bases: &Bound<'py, PyAny>) -> PyResult<Bound<'py, PyAny>> {
+    let py = bases.py();
+    let items = tuple_items(bases);
+    let mut resolved: Option<Vec<Bound<'py, PyAny>>> = None;
+    for (index, base) in items.iter().enumerate() {
+        let mro_entries = if ffi::PyType_Check(base.as_ptr()) != 0 {
+            None
+        } else {
+            optional_attr(base, c"__mro_entries__")?
+        };
+        let Some(mro_entries) = mro_entries else {
+            if let Some(resolved) = resolved.as_mut() {
+                resolved.push(base.clone());
+            }
+            continue;
+        };
+        let entries = owned(
+            py,
+            ffi::PyObject_CallOneArg(mro_entries.as_ptr(), bases.as_ptr()),
+        )?;
+        if ffi::PyTuple_Check(entries.as_ptr()) == 0 {
+            return Err(PyTypeError::new_err("__mro_entries__ must return a tuple"));
+        }
+        resolved
+            .get_or_insert_with(|| items[..index].to_vec())
+            .extend(tuple_items(&entries));
+    }
+    match resolved {
+        Some(resolved) => Ok(PyTuple::new(py, resolved)?.into_any()),
+        None => Ok(bases.clone()),
+    }
+}
+
+/// `types._calculate_meta`: the most derived of `meta` and the bases' types.
+unsafe fn calculate_meta<'py>(
+    meta: Bound<'py, PyAny>,
+    bases: &Bound<'py, PyAny>,
+) -> PyResult<Bound<'py, PyAny>> {
+    let mut winner = meta;
+    for base in tuple_items(bases) {
+        let base_meta = type_of(&base);
+        if is_subclass(&winner, &base_meta)? {
+            continue;
+        }
+        if is_subclass(&base_meta, &winner)? {
+            winner = base_meta;
+            continue;
+        }
+        return Err(PyTypeError::new_err(
+            "metaclass conflict: the metaclass of a derived class must be a (non-strict) \
+             subclass of the metaclasses of all its bases",
+        ));
+    }
+    Ok(winner)
+}
+
+/// `types.prepare_class`: the metaclass, the prepared namespace and the
+/// keywords left for the metaclass call. `kwds` is a dict or `None`.
+unsafe fn prepare_class<'py>(
+    name: &Bound<'py, PyAny>,
+    bases: &Bound<'py, PyAny>,
+    kwds: &Bound<'py, PyAny>,
+) -> PyResult<(Bound<'py, PyAny>, Bound<'py, PyAny>, Bound<'py, PyAny>)> {
+    let py = name.py();
+    let meta_kwds = owned(py, ffi::PyDict_New())?;
+    if !kwds.is_none() && ffi::PyDict_Merge(meta_kwds.as_ptr(), kwds.as_ptr(), 1) < 0 {
+        return Err(PyErr::fetch(py));
+    }
+    let explicit_meta = ffi::PyDict_GetItemString(meta_kwds.as_ptr(), c"metaclass".as_ptr());
+    let mut meta = if !explicit_meta.is_null() {
+        let meta = Bound::from_borrowed_ptr(py, explicit_meta);
+        if ffi::PyDict_DelItemString(meta_kwds.as_ptr(), c"metaclass".as_ptr()) < 0 {
+            return Err(PyErr::fetch(py));
+        }
+        meta
+    } else if let Some(first) = tuple_items(bases).first() {
+        type_of(first)
+    } else {
+        Bound::from_borrowed_ptr(py, ptr::addr_of_mut!(ffi::PyType_Type).cast())
+    };
+    if ffi::PyType_Check(meta.as_ptr()) != 0 {
+        meta = calculate_meta(meta, bases)?;
+    }
+    let ns = match optional_attr(&meta, c"__prepare__")? {
+        Some(prepare) => {
+            let args = PyTuple::new(py, [name.clone(), bases.clone()])?;
+            owned(
+                py,
+                ffi::PyObject_Call(prepare.as_ptr(), args.as_ptr(), meta_kwds.as_ptr()),
+            )?
+        }
+        None => owned(py, ffi::PyDict_New())?,
+    };
+    Ok((meta, ns, meta_kwds))
+}
+
+unsafe fn build_class<'py>(
+    name: Bound<'py, PyAny>,
+    namespace_fn: Bound<'py, PyAny>,
+    bases: Bound<'py, PyAny>,
+    kwds: Bound<'py, PyAny>,
+    requires_class_cell: bool,
+    firstlineno: i64,
+    static_attributes: Bound<'py, PyAny>,
+) -> PyResult<Bound<'py, PyAny>> {
+    let py = name.py();
+    if ffi::PyTuple_Check(bases.as_ptr()) == 0 {
+        return Err(PyTypeError::new_err("class bases must be a tuple"));
+    }
+    let resolved_bases = resolve_bases(&bases)?;
+    let (meta, ns, meta_kwds) = prepare_class(&name, &resolved_bases, &kwds)?;
+
+    let mut class_cell = ns.call_method1("get", ("__classcell__", py.None()))?;
+    if requires_class_cell && class_cell.is_none() {
+        class_cell = owned(py, PyCell_New(ptr::null_mut()))?;
+        ns.set_item("__classcell__", &class_cell)?;
+    }
+
+    namespace_fn.call1((&ns, &class_cell))?;
+    if !ns.contains("__firstlineno__")? {
+        ns.set_item("__firstlineno__", firstlineno)?;
+    }
+    if !ns.contains("__static_attributes__")? {
+        ns.set_item("__static_attributes__", &static_attributes)?;
+    }
+    if resolved_bases.as_ptr() != bases.as_ptr() && !ns.contains("__orig_bases__")? {
+        ns.set_item("__orig_bases__", &bases)?;
+    }
+
+    let args = PyTuple::new(py, [name, resolved_bases, ns.clone()])?;
+    let cls = owned(
+        py,
+        ffi::PyObject_Call(meta.as_ptr(), args.as_ptr(), meta_kwds.as_ptr()),
+    )?;
+
+    if !cls.is_none() {
+        ns.call_method1("pop", ("__classcell__", py.None()))?;
+        if !class_cell.is_none() {
+            if ffi::Py_TYPE(class_cell.as_ptr()) != ptr::addr_of_mut!(PyCell_Type) {
+                return Err(PyTypeError::new_err("__classcell__ must be a cell"));
+            }
+            if PyCell_Set(class_cell.as_ptr(), cls.as_ptr()) < 0 {
+                return Err(PyErr::fetch(py));
+            }
+        }
+    }
+    Ok(cls)
+}
+
+/// `class name(*bases, **kwds)`: prepares the namespace, fills it with
+/// `namespace_fn(ns, cell)` and calls the metaclass. All object arguments are
+/// borrowed; returns a new reference, or null with an exception set.
+pub unsafe fn create_class(
+    name: ObjPtr,
+    namespace_fn: ObjPtr,
+    bases: ObjPtr,
+    kwds: ObjPtr,
+    requires_class_cell: bool,
+    firstlineno: i64,
+    static_attributes: ObjPtr,
+) -> ObjPtr {
+    let py = Python::assume_attached();
+    let result = build_class(
+        Bound::from_borrowed_ptr(py, name),
+        Bound::from_borrowed_ptr(py, namespace_fn),
+        Bound::from_borrowed_ptr(py, bases),
+        Bound::from_borrowed_ptr(py, kwds),
+        requires_class_cell,
+        firstlineno,
+        Bound::from_borrowed_ptr(py, static_attributes),
+    );
+    match result {
+        Ok(cls) => cls.into_ptr(),
+        Err(err) => {
+            err.restore(py);
+            ptr::null_mut()
+        }
+    }
+}
@@ -417,10 +417,17 @@ impl ModuleConstantCollector {
 
     fn collect_expr(&mut self, expr: &CodegenBlockPyExpr) {
         match expr {
-            CodegenBlockPyExpr::IncrementCounter(_) => {}
+            CodegenBlockPyExpr::IncrementCounter(_) | CodegenBlockPyExpr::PreloadGlobal(_) => {}
+            CodegenBlockPyExpr::GuardedGlobalLoad(op) => {
+                self.constants
+                    .intern_unicode_bytes(op.name.id_str().as_bytes());
+            }
             CodegenBlockPyExpr::CalleeFunctionId(op) => {
                 self.collect_expr(op.value.as_ref());
             }
+            CodegenBlockPyExpr::InlineGuard(op) => {
+                self.collect_expr(op.value.as_ref());
+            }
             CodegenBlockPyExpr::Call(call) => {
                 if let Some(const_bytes) = self.string_constant_bytes_for_specialized_codegen(expr)
                 {
@@ -501,12 +508,36 @@ impl ModuleConstantCollector {
             CodegenBlockPyExpr::DelItem(op) => {
                 op.visit_children(self);
             }
+            CodegenBlockPyExpr::GetIter(op) => op.visit_children(self),
+            CodegenBlockPyExpr::ForIter(op) => op.visit_children(self),
+            CodegenBlockPyExpr::Unpack(op) => op.visit_children(self),
+            CodegenBlockPyExpr::ImportName(op) => {
+                self.constants.intern_unicode_bytes(op.module.as_bytes());
+                for name in &op.fromlist {
+                    self.constants.intern_unicode_bytes(name.as_bytes());
+                }
+                op.visit_children(self);
+            }
+            CodegenBlockPyExpr::ImportFrom(op) => {
+                self.constants.intern_unicode_bytes(op.attr.as_bytes());
+                op.visit_children(self);
+            }
+            CodegenBlockPyExpr::CreateClass(op) => {
+                self.constants.intern_unicode_bytes(op.name.as_bytes());
+                op.visit_children(self);
+            }
+            CodegenBlockPyExpr::FormatValue(op) => op.visit_children(self),
+            CodegenBlockPyExpr::BuildString(op) => op.visit_children(self),
             CodegenBlockPyExpr::MakeCell(op) => {
                 op.visit_children(self);
             }
             CodegenBlockPyExpr::MakeFunction(op) => {
                 op.visit_children(self);
             }
+            CodegenBlockPyExpr::BuildTuple(op) => op.visit_children(self),
+            CodegenBlockPyExpr::BuildList(op) => op.visit_children(self),
+            CodegenBlockPyExpr::BuildSet(op) => op.visit_children(self),
+            CodegenBlockPyExpr::BuildDict(op) => op.visit_children(self),
             CodegenBlockPyExpr::Del(_) | CodegenBlockPyExpr::CellRef(_) => {}
         }
     }
@@ -3,7 +3,7 @@ use std::collections::HashMap;
 use std::ffi::CStr;
 use std::ffi::c_int;
 use std::ptr;
-use std::sync::atomic::{AtomicPtr, Ordering};
+use std::sync::atomic::{AtomicPtr, AtomicU64, Ordering};
 use std::sync::{Arc, Mutex, OnceLock, Weak};
 
 type ObjPtr = *mut ffi::PyObject;
@@ -112,6 +112,10 @@ pub struct ModuleGlobalCache {
     dict_obj: ObjPtr,
     builtins_dict_obj: ObjPtr,
     slots: Box<[AtomicPtr<ffi::PyObject>]>,
+    /// Bumped whenever a slot's value is replaced or cleared (filling an
+    /// empty slot does not count), so an unchanged version means every slot
+    /// that held a value still holds that same object.
+    version: AtomicU64,
     slot_by_name: HashMap<String, u32>,
     pending_self_updates: Mutex<Vec<Vec<ObjPtr>>>,
 }
@@ -163,6 +167,7 @@ impl ModuleGlobalCache {
             dict_obj,
             builtins_dict_obj,
             slots,
+            version: AtomicU64::new(0),
             slot_by_name,
             pending_self_updates: Mutex::new(pending_self_updates),
         });
@@ -197,6 +202,10 @@ impl ModuleGlobalCache {
         self.slots.as_ptr().cast_mut().cast::<ffi::PyObject>()
     }
 
+    pub fn version_ptr(&self) -> *mut u64 {
+        self.version.as_ptr()
+    }
+
     pub unsafe fn store_loaded_value_steal(&self, slot: u32, value: ObjPtr) {
         self.swap_slot(slot, value);
     }
@@ -269,6 +278,7 @@ impl ModuleGlobalCache {
         for entry in &self.slots {
             let old = entry.swap(ptr::null_mut(), Ordering::AcqRel);
             if !old.is_null() {
+                self.version.fetch_add(1, Ordering::Release);
                 ffi::Py_DECREF(old);
             }
         }
@@ -451,6 +461,7 @@ impl ModuleGlobalCache {
         };
         let old = entry.swap(value, Ordering::AcqRel);
         if !old.is_null() {
+            self.version.fetch_add(1, Ordering::Release);
             ffi::Py_DECREF(old);
         }
     }
@@ -614,4 +625,43 @@ mod tests {
             let _ = py;
         });
     }
+
+    #[test]
+    fn version_changes_only_when_a_cached_value_is_replaced_or_cleared() {
+        let _guard = crate::python_runtime_test_lock().lock().unwrap();
+        initialize_test_python();
+        Python::attach(|py| unsafe {
+            let globals = ffi::PyDict_New();
+            assert!(!globals.is_null());
+            {
+                let cache = ModuleGlobalCache::new(globals, &["x".into()])
+                    .expect("global cache should initialize");
+                let version = || cache.version.load(Ordering::Acquire);
+                let x_name = ffi::PyUnicode_FromString(b"x\0".as_ptr() as *const i8);
+                let first_value = ffi::PyLong_FromLongLong(7 as c_longlong);
+                let second_value = ffi::PyLong_FromLongLong(9 as c_longlong);
+                let initial = version();
+
+                assert_eq!(ffi::PyObject_SetItem(globals, x_name, first_value), 0);
+                assert_eq!(cached_long_value(&cache, 0), Some(7));
+                assert_eq!(version(), initial, "filling an empty slot");
+
+                assert_eq!(ffi::PyObject_SetItem(globals, x_name, second_value), 0);
+                assert_eq!(cached_long_value(&cache, 0), Some(9));
+                let replaced = version();
+                assert_ne!(replaced, initial, "replacing a cached value");
+
+                assert_eq!(ffi::PyObject_DelItem(globals, x_name), 0);
+                assert_eq!(cached_long_value(&cache, 0), None);
+                assert_ne!(version(), replaced, "clearing a cached value");
+
+                ffi::Py_DECREF(x_name);
+                ffi::Py_DECREF(first_value);
+                ffi::Py_DECREF(second_value);
+                drop(cache);
+            }
+            ffi::Py_DECREF(globals);
+            let _ = py;
+        });
+    }
 }
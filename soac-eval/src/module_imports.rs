@@ -0,0 +1,411 @@
+//! Runtime side of the `ImportName` / `ImportFrom` operations.
+//!
+//! `ImportName` first asks the per-module [`ImportSiteCache`] for the result
+//! of an earlier execution of the same import. Entries are tagged with a
+//! `sys.modules` version kept by a dict watcher, so any change to the module
+//! table drops every cached result. Misses resolve modules that are already
+//! loaded straight out of `sys.modules` and hand everything else to
+//! `soac.runtime.import_`, which goes through `builtins.__import__`. Nothing is
+//! cached or short-circuited while `__import__` is overridden.
+//!
+//! Lazy top-level imports are registered in a table in `soac.runtime` keyed
+//! by the module's globals dict instead of being bound there, so the first
+//! load of one misses the globals dict and the slow path resolves it with
+//! [`resolve_lazy_global`].
+
+use pyo3::ffi;
+use pyo3::prelude::*;
+use std::collections::HashMap;
+use std::ffi::CStr;
+use std::ffi::c_int;
+use std::ptr;
+use std::sync::atomic::{AtomicPtr, AtomicU64, Ordering};
+use std::sync::{Mutex, OnceLock};
+
+use crate::module_constants::load_runtime_name_owned;
+
+type ObjPtr = *mut ffi::PyObject;
+
+unsafe extern "C" {
+    fn PyDict_AddWatcher(
+        callback: unsafe extern "C" fn(
+            c_int,
+            *mut ffi::PyObject,
+            *mut ffi::PyObject,
+            *mut ffi::PyObject,
+        ) -> c_int,
+    ) -> c_int;
+    fn PyDict_Watch(watcher_id: c_int, dict: *mut ffi::PyObject) -> c_int;
+}
+
+static MODULES_VERSION: AtomicU64 = AtomicU64::new(0);
+static WATCHED_MODULES: AtomicPtr<ffi::PyObject> = AtomicPtr::new(ptr::null_mut());
+
+fn watcher_id() -> Option<c_int> {
+    static WATCHER_ID: OnceLock<Option<c_int>> = OnceLock::new();
+    *WATCHER_ID.get_or_init(|| {
+        let watcher_id = unsafe { PyDict_AddWatcher(modules_watcher_callback) };
+        (watcher_id >= 0).then_some(watcher_id)
+    })
+}
+
+unsafe extern "C" fn modules_watcher_callback(
+    _event: c_int,
+    _dict: *mut ffi::PyObject,
+    _key: *mut ffi::PyObject,
+    _new_value: *mut ffi::PyObject,
+) -> c_int {
+    MODULES_VERSION.fetch_add(1, Ordering::AcqRel);
+    0
+}
+
+/// Current `sys.modules` version, or `None` when the dict cannot be watched
+/// and import results must not be cached.
+unsafe fn modules_version() -> Option<u64> {
+    let watcher_id = watcher_id()?;
+    let modules = ffi::PyImport_GetModuleDict();
+    if modules.is_null() {
+        return None;
+    }
+    if WATCHED_MODULES.load(Ordering::Acquire) != modules {
+        if PyDict_Watch(watcher_id, modules) < 0 {
+            ffi::PyErr_Clear();
+            return None;
+        }
+        WATCHED_MODULES.store(modules, Ordering::Release);
+        MODULES_VERSION.fetch_add(1, Ordering::AcqRel);
+    }
+    Some(MODULES_VERSION.load(Ordering::Acquire))
+}
+
+struct ImportSiteEntry {
+    name: usize,
+    modules_version: u64,
+    module: Py<PyAny>,
+}
+
+/// Last result of each import site in a module, keyed by the site key the JIT
+/// bakes into the call.
+#[derive(Default)]
+pub struct ImportSiteCache {
+    entries: Mutex<HashMap<u64, ImportSiteEntry>>,
+}
+
+impl ImportSiteCache {
+    unsafe fn lookup(&self, site_key: u64, name: ObjPtr, modules_version: u64) -> ObjPtr {
+        let entries = self
+            .entries
+            .lock()
+            .expect("import site cache mutex poisoned");
+        match entries.get(&site_key) {
+            Some(entry)
+                if entry.name == name as usize && entry.modules_version == modules_version =>
+            {
+                let module = entry.module.as_ptr();
+                ffi::Py_INCREF(module);
+                module
+            }
+            _ => ptr::null_mut(),
+        }
+    }
+
+    unsafe fn store(&self, site_key: u64, name: ObjPtr, modules_version: u64, module: ObjPtr) {
+        let module = Bound::from_borrowed_ptr(Python::assume_attached(), module).unbind();
+        let previous = self
+            .entries
+            .lock()
+            .expect("import site cache mutex poisoned")
+            .insert(
+                site_key,
+                ImportSiteEntry {
+                    name: name as usize,
+                    modules_version,
+                    module,
+                },
+            );
+        // Release the old module outside the lock; its decref can run Python code.
+        drop(previous);
+    }
+}
+
+/// Whether `builtins.__import__` is still the interpreter's own function.
+unsafe fn builtin_import_is_default() -> bool {
+    let builtins_dict = ffi::PyEval_GetBuiltins();
+    if builtins_dict.is_null() {
+        return false;
+    }
+    let import_func = ffi::PyDict_GetItemString(builtins_dict, c"__import__".as_ptr());
+    if import_func.is_null() || ffi::PyCFunction_Check(import_func) == 0 {
+        return false;
+    }
+    let function = import_func as *mut ffi::PyCFunctionObject;
+    let method = (*function).m_ml;
+    if method.is_null()
+        || (*method).ml_name.is_null()
+        || CStr::from_ptr((*method).ml_name).to_bytes() != b"__import__"
+    {
+        return false;
+    }
+    let modules = ffi::PyImport_GetModuleDict();
+    if modules.is_null() {
+        return false;
+    }
+    let builtins_module = ffi::PyDict_GetItemString(modules, c"builtins".as_ptr());
+    !builtins_module.is_null() && (*function).m_self == builtins_module
+}
+
+/// Mirrors the `__spec__._initializing` test `__import__` uses to decide
+/// whether it has to wait on the module's import lock. Errors count as
+/// initializing so the caller falls back to the full import.
+unsafe fn module_is_initializing(module: ObjPtr) -> bool {
+    let spec = ffi::PyObject_GetAttrString(module, c"__spec__".as_ptr());
+    if spec.is_null() {
+        ffi::PyErr_Clear();
+        return false;
+    }
+    let initializing = ffi::PyObject_GetAttrString(spec, c"_initializing".as_ptr());
+    ffi::Py_DECREF(spec);
+    if initializing.is_null() {
+        ffi::PyErr_Clear();
+        return false;
+    }
+    let truth = ffi::PyObject_IsTrue(initializing);
+    ffi::Py_DECREF(initializing);
+    if truth < 0 {
+        ffi::PyErr_Clear();
+        return true;
+    }
+    truth == 1
+}
+
+/// New reference to the fully initialized `sys.modules[name]`, or null with no
+/// exception set.
+unsafe fn loaded_module(name: ObjPtr) -> ObjPtr {
+    let modules = ffi::PyImport_GetModuleDict();
+    if modules.is_null() {
+        return ptr::null_mut();
+    }
+    let module = ffi::PyDict_GetItemWithError(modules, name);
+    if module.is_null() {
+        ffi::PyErr_Clear();
+        return ptr::null_mut();
+    }
+    if module == ffi::Py_None() {
+        return ptr::null_mut();
+    }
+    ffi::Py_INCREF(module);
+    if module_is_initializing(module) {
+        ffi::Py_DECREF(module);
+        return ptr::null_mut();
+    }
+    module
+}
+
+/// What the default `__import__` would return for an absolute import whose
+/// modules are all loaded, or null with no exception set.
+unsafe fn import_loaded_module(name: ObjPtr, fromlist: ObjPtr, level: i64) -> ObjPtr {
+    if level != 0 {
+        return ptr::null_mut();
+    }
+    let module = loaded_module(name);
+    if module.is_null() {
+        return ptr::null_mut();
+    }
+    if ffi::PyTuple_Check(fromlist) != 0 && ffi::PyTuple_GET_SIZE(fromlist) > 0 {
+        // Packages may still have submodules in the fromlist to import.
+        if ffi::PyObject_HasAttrString(module, c"__path__".as_ptr()) != 0 {
+            ffi::Py_DECREF(module);
+            return ptr::null_mut();
+        }
+        return module;
+    }
+    let mut name_len: ffi::Py_ssize_t = 0;
+    let name_utf8 = ffi::PyUnicode_AsUTF8AndSize(name, &mut name_len);
+    if name_utf8.is_null() {
+        ffi::PyErr_Clear();
+        ffi::Py_DECREF(module);
+        return ptr::null_mut();
+    }
+    let name_bytes = std::slice::from_raw_parts(name_utf8.cast::<u8>(), name_len as usize);
+    let Some(dot) = name_bytes.iter().position(|byte| *byte == b'.') else {
+        return module;
+    };
+    // `import a.b` binds the top-level package.
+    ffi::Py_DECREF(module);
+    let front = ffi::PyUnicode_FromStringAndSize(name_utf8, dot as ffi::Py_ssize_t);
+    if front.is_null() {
+        ffi::PyErr_Clear();
+        return ptr::null_mut();
+    }
+    let top = loaded_module(front);
+    ffi::Py_DECREF(front);
+    top
+}
+
+unsafe fn call_runtime_helper(helper_name: &CStr, args: &[ObjPtr]) -> ObjPtr {
+    let name_obj = ffi::PyUnicode_InternFromString(helper_name.as_ptr());
+    if name_obj.is_null() {
+        return ptr::null_mut();
+    }
+    let helper = load_runtime_name_owned(name_obj);
+    ffi::Py_DECREF(name_obj);
+    if helper.is_null() {
+        return ptr::null_mut();
+    }
+    let result = ffi::PyObject_Vectorcall(helper, args.as_ptr(), args.len(), ptr::null_mut());
+    ffi::Py_DECREF(helper);
+    result
+}
+
+/// Cached module for the import at `site_key`, or null with no exception set
+/// when the import has to run through [`import_name`].
+pub unsafe fn import_cached(
+    cache: Option<&ImportSiteCache>,
+    name: ObjPtr,
+    site_key: u64,
+) -> ObjPtr {
+    let Some(cache) = cache else {
+        return ptr::null_mut();
+    };
+    let Some(modules_version) = modules_version() else {
+        return ptr::null_mut();
+    };
+    if !builtin_import_is_default() {
+        return ptr::null_mut();
+    }
+    cache.lookup(site_key, name, modules_version)
+}
+
+/// `import name` / `from name import ...` for the module described by
+/// `spec`. `fromlist` is a tuple of names.
+pub unsafe fn import_name(
+    cache: Option<&ImportSiteCache>,
+    name: ObjPtr,
+    spec: ObjPtr,
+    fromlist: ObjPtr,
+    level: i64,
+    site_key: u64,
+) -> ObjPtr {
+    let is_default_import = builtin_import_is_default();
+    let mut module = if is_default_import {
+        import_loaded_module(name, fromlist, level)
+    } else {
+        ptr::null_mut()
+    };
+    if module.is_null() {
+        let level_obj = ffi::PyLong_FromLongLong(level);
+        if level_obj.is_null() {
+            return ptr::null_mut();
+        }
+        module = call_runtime_helper(c"import_", &[name, spec, fromlist, level_obj]);
+        ffi::Py_DECREF(level_obj);
+        if module.is_null() {
+            return ptr::null_mut();
+        }
+    }
+    if let Some(cache) = cache {
+        if is_default_import && !module_is_initializing(module) {
+            if let Some(modules_version) = modules_version() {
+                cache.store(site_key, name, modules_version, module);
+            }
+        }
+    }
+    module
+}
+
+/// `from module import attr`. Only a failed attribute read goes through
+/// `soac.runtime.import_attr`, which owns the submodule fallback and the
+/// circular-import error messages (and reads the attribute once more).
+pub unsafe fn import_from(module: ObjPtr, attr: ObjPtr) -> ObjPtr {
+    let value = ffi::PyObject_GetAttr(module, attr);
+    if !value.is_null() || ffi::PyErr_ExceptionMatches(ffi::PyExc_AttributeError) == 0 {
+        return value;
+    }
+    ffi::PyErr_Clear();
+    call_runtime_helper(c"import_attr", &[module, attr])
+}
+
+static LAZY_IMPORTS: AtomicPtr<ffi::PyObject> = AtomicPtr::new(ptr::null_mut());
+
+/// Borrowed `soac.runtime._LAZY_IMPORTS`, the pending lazy import table keyed
+/// by globals dict id, or null with an exception set. The first successful
+/// lookup is cached for the life of the process.
+unsafe fn lazy_import_table() -> ObjPtr {
+    let mut table = LAZY_IMPORTS.load(Ordering::Acquire);
+    if table.is_null() {
+        let name = ffi::PyUnicode_FromString(c"_LAZY_IMPORTS".as_ptr());
+        if name.is_null() {
+            return ptr::null_mut();
+        }
+        table = load_runtime_name_owned(name);
+        ffi::Py_DECREF(name);
+        if table.is_null() {
+            return ptr::null_mut();
+        }
+        LAZY_IMPORTS.store(table, Ordering::Release);
+    }
+    table
+}
+
+/// Whether `name` has a pending lazy import in `globals`: 1 if so, 0 if
+/// not, -1 with an exception set on error.
+unsafe fn has_lazy_import(globals: ObjPtr, name: ObjPtr) -> c_int {
+    let table = lazy_import_table();
+    if table.is_null() {
+        // Lazy imports register through `soac.runtime`, so none can be
+        // pending before it has loaded.
+        ffi::PyErr_Clear();
+        return 0;
+    }
+    if ffi::PyDict_Check(table) == 0 || ffi::PyDict_Size(table) == 0 {
+        return 0;
+    }
+    let key = ffi::PyLong_FromVoidPtr(globals.cast());
+    if key.is_null() {
+        return -1;
+    }
+    let entry = ffi::PyDict_GetItemWithError(table, key);
+    ffi::Py_DECREF(key);
+    if entry.is_null() {
+        return if ffi::PyErr_Occurred().is_null() {
+            0
+        } else {
+            -1
+        };
+    }
+    let pending = ffi::PyTuple_GetItem(entry, 1);
+    if pending.is_null() {
+        return -1;
+    }
+    ffi::PyDict_Contains(pending, name)
+}
+
+/// Runs the pending lazy import of `name` in `globals`, which binds the
+/// result there so later loads hit the global slot cache. Returns a new
+/// reference, or null with `KeyError` set when nothing is pending.
+pub unsafe fn resolve_lazy_global(globals: ObjPtr, name: ObjPtr) -> ObjPtr {
+    match has_lazy_import(globals, name) {
+        1 => call_runtime_helper(c"resolve_lazy_import", &[globals, name]),
+        0 => {
+            ffi::PyErr_SetObject(ffi::PyExc_KeyError, name);
+            ptr::null_mut()
+        }
+        _ => ptr::null_mut(),
+    }
+}
+
+/// Drops the pending lazy import of `name` in `globals` for a `del` of the
+/// name. Returns 1 if one was pending, 0 if not, -1 with an exception set on
+/// error.
+pub unsafe fn discard_lazy_global(globals: ObjPtr, name: ObjPtr) -> c_int {
+    let pending_import = has_lazy_import(globals, name);
+    if pending_import != 1 {
+        return pending_import;
+    }
+    let result = call_runtime_helper(c"discard_lazy_import", &[globals, name]);
+    if result.is_null() {
+        return -1;
+    }
+    ffi::Py_DECREF(result);
+    1
+}
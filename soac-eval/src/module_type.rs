@@ -1,6 +1,9 @@
 use crate::counter_dump::{CounterDumpRecord, CounterDumpRow};
+use crate::jit::JitSourceMap;
 use crate::module_constants::ModuleCodegenConstants;
 use crate::module_globals::ModuleGlobalCache;
+use crate::module_imports::ImportSiteCache;
+use crate::runtime_options::RuntimeOptions;
 use pyo3::exceptions::{PyRuntimeError, PyTypeError};
 use pyo3::ffi;
 use pyo3::prelude::*;
@@ -10,7 +13,6 @@ use soac_blockpy::block_py::{
 };
 use soac_blockpy::passes::CodegenBlockPyPass;
 use std::collections::HashMap;
-use std::env;
 use std::ffi::{c_int, c_void};
 use std::fs::OpenOptions;
 use std::io::Write;
@@ -29,11 +31,14 @@ pub struct SharedModuleState {
     pub module_name: String,
     pub package_name: String,
     pub codegen_constants: ModuleCodegenConstants,
+    pub source_map: Option<JitSourceMap>,
+    pub runtime_options: RuntimeOptions,
     function_index_by_id: HashMap<FunctionId, usize>,
     module_constant_objs: Vec<Py<PyAny>>,
     counter_slots_by_id: Box<[usize]>,
     counter_values: Box<[u64]>,
     compiled_direct_runner_handles: Mutex<HashMap<FunctionId, DirectRunnerCacheEntry>>,
+    import_site_cache: ImportSiteCache,
 }
 
 #[derive(Clone, Copy)]
@@ -70,6 +75,10 @@ impl SharedModuleState {
             .collect()
     }
 
+    pub fn import_site_cache(&self) -> &ImportSiteCache {
+        &self.import_site_cache
+    }
+
     pub(crate) fn counter_ptrs(&self) -> Vec<*mut u64> {
         self.counter_slots_by_id
             .iter()
@@ -123,7 +132,8 @@ impl SharedModuleState {
                 &self.lowered_module.counter_defs,
                 &module_constant_ptrs,
                 &counter_ptrs,
-                Some(self),
+                self.source_map.as_ref(),
+                self.runtime_options.gdb_jit,
             )?
         };
         let code_ptr = match crate::jit::compiled_direct_code_ptr(handle) {
@@ -302,11 +312,14 @@ pub(crate) fn build_shared_state_for_testing(
         module_name: module_name.to_string(),
         package_name: package_name.to_string(),
         codegen_constants,
+        source_map: None,
+        runtime_options: RuntimeOptions::default(),
         function_index_by_id,
         module_constant_objs,
         counter_slots_by_id,
         counter_values,
         compiled_direct_runner_handles: Mutex::new(HashMap::new()),
+        import_site_cache: ImportSiteCache::default(),
     }))
 }
 
@@ -343,6 +356,8 @@ impl SoacExtModuleState {
         lowered_module: BlockPyModule<CodegenBlockPyPass>,
         module_name: String,
         package_name: String,
+        source_map: Option<JitSourceMap>,
+        runtime_options: RuntimeOptions,
     ) -> PyResult<()> {
         if self.initialized {
             return Err(PyRuntimeError::new_err(
@@ -359,11 +374,14 @@ impl SoacExtModuleState {
             module_name,
             package_name,
             codegen_constants,
+            source_map,
+            runtime_options,
             function_index_by_id,
             module_constant_objs,
             counter_slots_by_id,
             counter_values,
             compiled_direct_runner_handles: Mutex::new(HashMap::new()),
+            import_site_cache: ImportSiteCache::default(),
         }));
         self.initialized = true;
         self.global_cache_initialized = false;
@@ -375,7 +393,7 @@ impl SoacExtModuleState {
             return;
         }
         let shared_state = unsafe { self.shared_state.assume_init_ref().as_ref() };
-        if let Some(path) = counter_dump_file_from_env() {
+        if let Some(path) = &shared_state.runtime_options.counters_file {
             if let Err(err) = shared_state.append_counter_dump_file(path.as_path()) {
                 eprintln!(
                     "[soac counters] failed to append counter dump to {}: {err}",
@@ -444,16 +462,6 @@ impl SoacExtModuleState {
     }
 }
 
-fn counter_dump_file_from_env() -> Option<std::path::PathBuf> {
-    let raw = env::var("DIET_PYTHON_COUNTERS_FILE").ok()?;
-    let trimmed = raw.trim();
-    if trimmed.is_empty() {
-        None
-    } else {
-        Some(trimmed.into())
-    }
-}
-
 unsafe extern "C" fn soac_ext_module_clear(module: *mut ffi::PyObject) -> c_int {
     let state = unsafe { ffi::PyModule_GetState(module) }.cast::<SoacExtModuleState>();
     if state.is_null() {
@@ -534,6 +542,8 @@ impl SoacExtModule {
         py: Python<'_>,
         spec: &Bound<'_, PyAny>,
         lowered_module: BlockPyModule<CodegenBlockPyPass>,
+        source_map: Option<JitSourceMap>,
+        runtime_options: RuntimeOptions,
     ) -> PyResult<Py<PyAny>> {
         let module_name = spec
             .getattr("name")?
@@ -554,7 +564,14 @@ impl SoacExtModule {
         }
         let state = soac_ext_module_state(&module)?;
         unsafe {
-            (*state).init(py, lowered_module, module_name, package_name)?;
+            (*state).init(
+                py,
+                lowered_module,
+                module_name,
+                package_name,
+                source_map,
+                runtime_options,
+            )?;
         }
         Ok(module.unbind())
     }
@@ -615,6 +632,8 @@ def f():
             function_index_by_id: build_function_index_by_id(&lowered)
                 .expect("function index should build"),
             codegen_constants: ModuleCodegenConstants::collect_from_module(&lowered),
+            source_map: None,
+            runtime_options: RuntimeOptions::default(),
             module_constant_objs: Vec::new(),
             counter_slots_by_id: vec![0].into_boxed_slice(),
             counter_values: vec![3].into_boxed_slice(),
@@ -622,6 +641,7 @@ def f():
             module_name: "counter_test".to_string(),
             package_name: String::new(),
             compiled_direct_runner_handles: Mutex::new(HashMap::new()),
+            import_site_cache: ImportSiteCache::default(),
         };
 
         let record = shared_state
@@ -724,6 +744,8 @@ def f():
             function_index_by_id: build_function_index_by_id(&lowered)
                 .expect("function index should build"),
             codegen_constants: ModuleCodegenConstants::collect_from_module(&lowered),
+            source_map: None,
+            runtime_options: RuntimeOptions::default(),
             module_constant_objs: Vec::new(),
             counter_slots_by_id: vec![0, 1].into_boxed_slice(),
             counter_values: vec![5, 8].into_boxed_slice(),
@@ -731,6 +753,7 @@ def f():
             module_name: "counter_test".to_string(),
             package_name: "pkg".to_string(),
             compiled_direct_runner_handles: Mutex::new(HashMap::new()),
+            import_site_cache: ImportSiteCache::default(),
         };
 
         let unique = SystemTime::now()
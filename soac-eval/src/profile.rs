@@ -0,0 +1,481 @@
+//! Sampling profiler for JIT-compiled code.
+//!
+//! A `SIGPROF` interval timer interrupts the process; the signal handler walks
+//! the frame-pointer chain of the interrupted thread and copies raw program
+//! counters into a preallocated buffer without locking or allocating. When
+//! sampling stops, each program counter is attributed either to a registered
+//! JIT code region (qualname, block label, `InstrId`, source line) or to a
+//! native symbol via `dladdr`, and the stacks are aggregated into the
+//! collapsed-stack format read by flamegraph tools and speedscope.
+//!
+//! The timer is process-wide, so stopping discards any `SIGPROF` still
+//! pending and waits until no handler on any thread can still be writing to
+//! the buffer before freeing it.
+
+use crate::jit::{ResolvedJitPc, resolve_jit_pc};
+use soac_blockpy::block_py::InstrId;
+use std::collections::BTreeMap;
+use std::ffi::CStr;
+use std::fmt::Write as _;
+use std::ptr;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicPtr, AtomicUsize, Ordering};
+
+const MAX_STACK_DEPTH: usize = 128;
+/// Room for about four seconds of samples at the default 1ms interval, in
+/// 4MB of frames.
+pub const DEFAULT_SAMPLE_CAPACITY: usize = 1 << 12;
+
+struct SampleBuffer {
+    frames: Box<[AtomicUsize]>,
+    depths: Box<[AtomicUsize]>,
+    next_sample: AtomicUsize,
+    dropped_samples: AtomicUsize,
+    stack_low: usize,
+    stack_high: usize,
+}
+
+impl SampleBuffer {
+    fn new(capacity: usize, stack_low: usize, stack_high: usize) -> Self {
+        Self {
+            frames: (0..capacity * MAX_STACK_DEPTH)
+                .map(|_| AtomicUsize::new(0))
+                .collect(),
+            depths: (0..capacity).map(|_| AtomicUsize::new(0)).collect(),
+            next_sample: AtomicUsize::new(0),
+            dropped_samples: AtomicUsize::new(0),
+            stack_low,
+            stack_high,
+        }
+    }
+
+    fn capacity(&self) -> usize {
+        self.depths.len()
+    }
+
+    fn record(&self, stack: &[usize]) {
+        let index = self.next_sample.fetch_add(1, Ordering::Relaxed);
+        if index >= self.capacity() {
+            self.dropped_samples.fetch_add(1, Ordering::Relaxed);
+            return;
+        }
+        let base = index * MAX_STACK_DEPTH;
+        for (slot, pc) in stack.iter().enumerate() {
+            self.frames[base + slot].store(*pc, Ordering::Relaxed);
+        }
+        self.depths[index].store(stack.len(), Ordering::Release);
+    }
+
+    fn raw_samples(&self) -> Vec<Vec<usize>> {
+        let count = self
+            .next_sample
+            .load(Ordering::Acquire)
+            .min(self.capacity());
+        (0..count)
+            .map(|index| {
+                let depth = self.depths[index].load(Ordering::Acquire);
+                let base = index * MAX_STACK_DEPTH;
+                (0..depth)
+                    .map(|slot| self.frames[base + slot].load(Ordering::Relaxed))
+                    .collect()
+            })
+            .filter(|stack: &Vec<usize>| !stack.is_empty())
+            .collect()
+    }
+}
+
+static ACTIVE_BUFFER: AtomicPtr<SampleBuffer> = AtomicPtr::new(ptr::null_mut());
+/// `SIGPROF` handlers currently running, on any thread. `ITIMER_PROF` is
+/// process-wide, so a handler may still be writing to the buffer after the
+/// profiler has been stopped on another thread.
+static HANDLERS_IN_FLIGHT: AtomicUsize = AtomicUsize::new(0);
+static PROFILER_STATE: Mutex<Option<ActiveProfiler>> = Mutex::new(None);
+
+struct ActiveProfiler {
+    buffer: *mut SampleBuffer,
+    #[cfg(target_os = "linux")]
+    previous_action: libc::sigaction,
+}
+
+unsafe impl Send for ActiveProfiler {}
+
+/// One frame of an attributed sample, innermost first in `Sample::frames`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProfileFrame {
+    Jit(ResolvedJitPc),
+    Native { symbol: Option<String>, pc: usize },
+}
+
+impl ProfileFrame {
+    fn label(&self) -> String {
+        match self {
+            ProfileFrame::Jit(resolved) => {
+                let mut label = resolved.qualname.clone();
+                if let (Some(file_name), Some(line)) = (
+                    resolved.file_name.as_deref(),
+                    resolved.site.and_then(|site| site.line),
+                ) {
+                    let _ = write!(label, " ({file_name}:{line})");
+                }
+                label
+            }
+            ProfileFrame::Native { symbol, .. } => {
+                symbol.clone().unwrap_or_else(|| "[native]".to_string())
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Sample {
+    pub frames: Vec<ProfileFrame>,
+}
+
+/// Self-sample count for one lowered instruction.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InstrHotspot {
+    pub qualname: String,
+    pub block_label: Option<String>,
+    pub instr_id: Option<InstrId>,
+    pub line: Option<u32>,
+    pub samples: usize,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Profile {
+    pub samples: Vec<Sample>,
+    pub dropped_samples: usize,
+}
+
+impl Profile {
+    fn from_raw(raw_samples: Vec<Vec<usize>>, dropped_samples: usize) -> Self {
+        let samples = raw_samples
+            .into_iter()
+            .map(|stack| Sample {
+                frames: stack
+                    .iter()
+                    .enumerate()
+                    .map(|(depth, pc)| {
+                        // Caller frames hold return addresses; step back into the call.
+                        let lookup_pc = if depth == 0 {
+                            *pc
+                        } else {
+                            pc.saturating_sub(1)
+                        };
+                        attribute_pc(lookup_pc)
+                    })
+                    .collect(),
+            })
+            .collect();
+        Self {
+            samples,
+            dropped_samples,
+        }
+    }
+
+    /// Renders `outer;...;inner count` lines, folding runs of native frames
+    /// without a symbol into a single `[native]` frame.
+    pub fn collapsed_stacks(&self) -> String {
+        let mut counts = BTreeMap::<String, usize>::new();
+        for sample in &self.samples {
+            let mut labels: Vec<String> = Vec::with_capacity(sample.frames.len());
+            for frame in sample.frames.iter().rev() {
+                let label = frame.label().replace(';', ":");
+                if label == "[native]" && labels.last().is_some_and(|last| last == "[native]") {
+                    continue;
+                }
+                labels.push(label);
+            }
+            *counts.entry(labels.join(";")).or_default() += 1;
+        }
+        let mut out = String::new();
+        for (stack, count) in counts {
+            let _ = writeln!(out, "{stack} {count}");
+        }
+        out
+    }
+
+    /// Attributes each sample to the innermost JIT frame on its stack.
+    pub fn instr_hotspots(&self) -> Vec<InstrHotspot> {
+        let mut counts = BTreeMap::<(String, Option<InstrId>), InstrHotspot>::new();
+        for sample in &self.samples {
+            let Some(resolved) = sample.frames.iter().find_map(|frame| match frame {
+                ProfileFrame::Jit(resolved) => Some(resolved),
+                ProfileFrame::Native { .. } => None,
+            }) else {
+                continue;
+            };
+            let instr_id = resolved.site.and_then(|site| site.instr_id);
+            counts
+                .entry((resolved.qualname.clone(), instr_id))
+                .or_insert_with(|| InstrHotspot {
+                    qualname: resolved.qualname.clone(),
+                    block_label: resolved.site.map(|site| site.block_label.to_string()),
+                    instr_id,
+                    line: resolved.site.and_then(|site| site.line),
+                    samples: 0,
+                })
+                .samples += 1;
+        }
+        let mut hotspots = counts.into_values().collect::<Vec<_>>();
+        hotspots.sort_by(|left, right| right.samples.cmp(&left.samples));
+        hotspots
+    }
+}
+
+fn attribute_pc(pc: usize) -> ProfileFrame {
+    if let Some(resolved) = resolve_jit_pc(pc) {
+        return ProfileFrame::Jit(resolved);
+    }
+    ProfileFrame::Native {
+        symbol: native_symbol_name(pc),
+        pc,
+    }
+}
+
+fn native_symbol_name(pc: usize) -> Option<String> {
+    unsafe {
+        let mut info: libc::Dl_info = std::mem::zeroed();
+        if libc::dladdr(pc as *const libc::c_void, &mut info) == 0 || info.dli_sname.is_null() {
+            return None;
+        }
+        Some(
+            CStr::from_ptr(info.dli_sname)
+                .to_string_lossy()
+                .into_owned(),
+        )
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn current_thread_stack_bounds() -> Option<(usize, usize)> {
+    unsafe {
+        let mut attr: libc::pthread_attr_t = std::mem::zeroed();
+        if libc::pthread_getattr_np(libc::pthread_self(), &mut attr) != 0 {
+            return None;
+        }
+        let mut stack_addr = ptr::null_mut();
+        let mut stack_size = 0;
+        let status = libc::pthread_attr_getstack(&attr, &mut stack_addr, &mut stack_size);
+        libc::pthread_attr_destroy(&mut attr);
+        if status != 0 {
+            return None;
+        }
+        Some((stack_addr as usize, stack_addr as usize + stack_size))
+    }
+}
+
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+unsafe fn interrupted_registers(context: *mut libc::c_void) -> (usize, usize, usize) {
+    let context = &*(context as *const libc::ucontext_t);
+    let gregs = &context.uc_mcontext.gregs;
+    (
+        gregs[libc::REG_RIP as usize] as usize,
+        gregs[libc::REG_RBP as usize] as usize,
+        gregs[libc::REG_RSP as usize] as usize,
+    )
+}
+
+#[cfg(all(target_os = "linux", target_arch = "aarch64"))]
+unsafe fn interrupted_registers(context: *mut libc::c_void) -> (usize, usize, usize) {
+    let context = &*(context as *const libc::ucontext_t);
+    let mcontext = &context.uc_mcontext;
+    (
+        mcontext.pc as usize,
+        mcontext.regs[29] as usize,
+        mcontext.sp as usize,
+    )
+}
+
+#[cfg(all(
+    target_os = "linux",
+    any(target_arch = "x86_64", target_arch = "aarch64")
+))]
+extern "C" fn sigprof_handler(
+    _signal: libc::c_int,
+    _info: *mut libc::siginfo_t,
+    context: *mut libc::c_void,
+) {
+    // Registered before the buffer is loaded, so `retire_active_buffer`
+    // either sees this handler or the handler sees the null buffer.
+    HANDLERS_IN_FLIGHT.fetch_add(1, Ordering::SeqCst);
+    let buffer = ACTIVE_BUFFER.load(Ordering::SeqCst);
+    if !buffer.is_null() && !context.is_null() {
+        sample_interrupted_stack(unsafe { &*buffer }, context);
+    }
+    HANDLERS_IN_FLIGHT.fetch_sub(1, Ordering::SeqCst);
+}
+
+#[cfg(all(
+    target_os = "linux",
+    any(target_arch = "x86_64", target_arch = "aarch64")
+))]
+fn sample_interrupted_stack(buffer: &SampleBuffer, context: *mut libc::c_void) {
+    let (pc, mut fp, sp) = unsafe { interrupted_registers(context) };
+    let mut stack = [0usize; MAX_STACK_DEPTH];
+    stack[0] = pc;
+    let mut depth = 1;
+    // Only walk frames on the thread that started the profiler; other threads'
+    // stacks fall outside the recorded bounds and contribute their leaf only.
+    let on_profiled_stack = buffer.stack_low <= sp && sp < buffer.stack_high;
+    while on_profiled_stack
+        && depth < MAX_STACK_DEPTH
+        && fp >= sp
+        && fp + 2 * size_of::<usize>() <= buffer.stack_high
+        && fp % size_of::<usize>() == 0
+    {
+        let (next_fp, return_pc) = unsafe {
+            let frame = fp as *const usize;
+            (*frame, *frame.add(1))
+        };
+        if return_pc == 0 {
+            break;
+        }
+        stack[depth] = return_pc;
+        depth += 1;
+        if next_fp <= fp {
+            break;
+        }
+        fp = next_fp;
+    }
+    buffer.record(&stack[..depth]);
+}
+
+/// Starts sampling every `interval_us` microseconds of CPU time.
+#[cfg(all(
+    target_os = "linux",
+    any(target_arch = "x86_64", target_arch = "aarch64")
+))]
+pub fn start_profiling(interval_us: u64, capacity: usize) -> Result<(), String> {
+    let mut state = PROFILER_STATE
+        .lock()
+        .map_err(|_| "profiler state lock poisoned".to_string())?;
+    if state.is_some() {
+        return Err("soac profiler is already running".to_string());
+    }
+    if interval_us == 0 {
+        return Err("profiler interval must be positive".to_string());
+    }
+    let (stack_low, stack_high) = current_thread_stack_bounds()
+        .ok_or_else(|| "failed to read the current thread's stack bounds".to_string())?;
+    let buffer = Box::into_raw(Box::new(SampleBuffer::new(
+        capacity.max(1),
+        stack_low,
+        stack_high,
+    )));
+    ACTIVE_BUFFER.store(buffer, Ordering::SeqCst);
+    unsafe {
+        let mut action: libc::sigaction = std::mem::zeroed();
+        action.sa_sigaction = sigprof_handler as usize;
+        action.sa_flags = libc::SA_SIGINFO | libc::SA_RESTART;
+        libc::sigemptyset(&mut action.sa_mask);
+        let mut previous_action: libc::sigaction = std::mem::zeroed();
+        if libc::sigaction(libc::SIGPROF, &action, &mut previous_action) != 0 {
+            drop(retire_active_buffer(buffer));
+            return Err("failed to install SIGPROF handler".to_string());
+        }
+        let interval = libc::timeval {
+            tv_sec: (interval_us / 1_000_000) as libc::time_t,
+            tv_usec: (interval_us % 1_000_000) as libc::suseconds_t,
+        };
+        let timer = libc::itimerval {
+            it_interval: interval,
+            it_value: interval,
+        };
+        if libc::setitimer(libc::ITIMER_PROF, &timer, ptr::null_mut()) != 0 {
+            libc::sigaction(libc::SIGPROF, &previous_action, ptr::null_mut());
+            drop(retire_active_buffer(buffer));
+            return Err("failed to arm ITIMER_PROF".to_string());
+        }
+        *state = Some(ActiveProfiler {
+            buffer,
+            previous_action,
+        });
+    }
+    Ok(())
+}
+
+#[cfg(not(all(
+    target_os = "linux",
+    any(target_arch = "x86_64", target_arch = "aarch64")
+)))]
+pub fn start_profiling(_interval_us: u64, _capacity: usize) -> Result<(), String> {
+    Err("soac profiler is only supported on Linux x86_64 and aarch64".to_string())
+}
+
+/// Unpublishes `buffer` and waits for handlers that may still be writing to
+/// it before handing back ownership.
+///
+/// # Safety
+///
+/// `buffer` must be the pointer published in `ACTIVE_BUFFER` by
+/// `start_profiling`, and must not have been retired already.
+unsafe fn retire_active_buffer(buffer: *mut SampleBuffer) -> Box<SampleBuffer> {
+    ACTIVE_BUFFER.store(ptr::null_mut(), Ordering::SeqCst);
+    while HANDLERS_IN_FLIGHT.load(Ordering::SeqCst) != 0 {
+        std::hint::spin_loop();
+    }
+    Box::from_raw(buffer)
+}
+
+/// Stops sampling and attributes the collected stacks.
+pub fn stop_profiling() -> Result<Profile, String> {
+    let mut state = PROFILER_STATE
+        .lock()
+        .map_err(|_| "profiler state lock poisoned".to_string())?;
+    let Some(active) = state.take() else {
+        return Err("soac profiler is not running".to_string());
+    };
+    #[cfg(target_os = "linux")]
+    unsafe {
+        let disarm: libc::itimerval = std::mem::zeroed();
+        libc::setitimer(libc::ITIMER_PROF, &disarm, ptr::null_mut());
+        // A SIGPROF raised before the timer was disarmed may still be pending
+        // on any thread. Ignoring the signal discards those, so none of them
+        // reaches the previous (usually default, terminating) disposition.
+        let mut ignore: libc::sigaction = std::mem::zeroed();
+        ignore.sa_sigaction = libc::SIG_IGN;
+        libc::sigemptyset(&mut ignore.sa_mask);
+        libc::sigaction(libc::SIGPROF, &ignore, ptr::null_mut());
+    }
+    let buffer = unsafe { retire_active_buffer(active.buffer) };
+    #[cfg(target_os = "linux")]
+    unsafe {
+        libc::sigaction(libc::SIGPROF, &active.previous_action, ptr::null_mut());
+    }
+    let profile = Profile::from_raw(
+        buffer.raw_samples(),
+        buffer.dropped_samples.load(Ordering::Relaxed),
+    );
+    Ok(profile)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn collapsed_stacks_fold_unnamed_native_frames_and_count_duplicates() {
+        let native = |symbol: Option<&str>| ProfileFrame::Native {
+            symbol: symbol.map(str::to_string),
+            pc: 0,
+        };
+        let stack = Sample {
+            frames: vec![native(None), native(None), native(Some("main"))],
+        };
+        let profile = Profile {
+            samples: vec![stack.clone(), stack],
+            dropped_samples: 0,
+        };
+        assert_eq!(profile.collapsed_stacks(), "main;[native] 2\n");
+    }
+
+    #[test]
+    fn sample_buffer_drops_samples_past_capacity() {
+        let buffer = SampleBuffer::new(1, 0, 0);
+        buffer.record(&[1, 2, 3]);
+        buffer.record(&[4]);
+        assert_eq!(buffer.raw_samples(), vec![vec![1, 2, 3]]);
+        assert_eq!(buffer.dropped_samples.load(Ordering::Relaxed), 1);
+    }
+}
@@ -0,0 +1,40 @@
+//! Settings that change how a loaded module runs and is compiled.
+//!
+//! Like `soac_blockpy::LoweringOptions`, these are read from the process
+//! environment once, by the caller that creates the module (the import hook),
+//! and then travel with the module's shared state.
+
+use std::env;
+use std::path::PathBuf;
+
+#[derive(Debug, Clone, Default)]
+pub struct RuntimeOptions {
+    /// File the module's counters are appended to when it is torn down
+    /// (`DIET_PYTHON_COUNTERS_FILE`).
+    pub counters_file: Option<PathBuf>,
+    /// Compile each function when it is created instead of on its first call
+    /// (`DIET_PYTHON_JIT_COMPILE_MODE=eager`).
+    pub eager_jit_compile: bool,
+    /// Announce compiled functions to GDB through its JIT interface
+    /// (`DIET_PYTHON_GDB_JIT`).
+    pub gdb_jit: bool,
+}
+
+impl RuntimeOptions {
+    /// Options from the `DIET_PYTHON_*` environment variables.
+    pub fn from_env() -> Self {
+        Self {
+            counters_file: env::var("DIET_PYTHON_COUNTERS_FILE")
+                .ok()
+                .map(|raw| raw.trim().to_string())
+                .filter(|path| !path.is_empty())
+                .map(PathBuf::from),
+            eager_jit_compile: env::var("DIET_PYTHON_JIT_COMPILE_MODE")
+                .map(|raw| raw.trim().eq_ignore_ascii_case("eager"))
+                .unwrap_or(false),
+            gdb_jit: env::var("DIET_PYTHON_GDB_JIT")
+                .map(|raw| !matches!(raw.as_str(), "" | "0"))
+                .unwrap_or(false),
+        }
+    }
+}
@@ -157,6 +157,7 @@ pub unsafe fn clone_module_runtime_context(
     if runtime.vmctx.shared_module_state.is_null()
         || runtime.vmctx.globals_obj.is_null()
         || runtime.vmctx.global_slots.is_null()
+        || runtime.vmctx.global_version.is_null()
         || runtime.vmctx.true_obj.is_null()
         || runtime.vmctx.false_obj.is_null()
         || runtime.vmctx.none_obj.is_null()
@@ -180,6 +181,7 @@ pub unsafe fn clone_module_runtime_context(
             shared_module_state: std::sync::Arc::as_ptr(&shared_module_state_owner),
             globals_obj: runtime.vmctx.globals_obj,
             global_slots: runtime.vmctx.global_slots,
+            global_version: runtime.vmctx.global_version,
             true_obj: runtime.vmctx.true_obj,
             false_obj: runtime.vmctx.false_obj,
             none_obj: runtime.vmctx.none_obj,
@@ -255,6 +257,7 @@ pub unsafe fn build_module_runtime_context_for_module(
             shared_module_state: std::sync::Arc::as_ptr(&shared_module_state),
             globals_obj: globals_obj as *mut c_void,
             global_slots: global_cache.slots_ptr() as *mut c_void,
+            global_version: global_cache.version_ptr(),
             true_obj: true_obj as *mut c_void,
             false_obj: false_obj as *mut c_void,
             none_obj: none_obj as *mut c_void,
@@ -356,6 +359,35 @@ pub unsafe fn registered_clif_function_id(
     Ok(Some(data.function.function_id))
 }
 
+/// The id of the CLIF body a call to `function` runs, when a copy of that body
+/// could run in its place inside a function whose module globals are
+/// `globals`: `function` is a plain function with no closure, its vectorcall
+/// still dispatches to the body registered on it, and both it and that body's
+/// module runtime use `globals`. `None` for anything else.
+pub unsafe fn inlinable_clif_function_id(
+    function: *mut ffi::PyObject,
+    globals: *mut ffi::PyObject,
+) -> Result<Option<FunctionId>, ()> {
+    let Some(function_id) = registered_clif_function_id(function)? else {
+        return Ok(None);
+    };
+    let func = &*(function as *const ffi::PyFunctionObject);
+    if !func.func_closure.is_null() || func.func_globals != globals {
+        return Ok(None);
+    }
+    let data = clif_vectorcall_data(function)?;
+    let dispatches_to_data = func.vectorcall.is_some_and(|vectorcall| {
+        vectorcall as usize == lazy_clif_vectorcall as usize
+            || data
+                .compiled_vectorcall_entry
+                .is_some_and(|entry| entry as usize == vectorcall as usize)
+    });
+    if !dispatches_to_data || data.module_runtime.vmctx.globals_obj != globals as jit::ObjPtr {
+        return Ok(None);
+    }
+    Ok(Some(function_id))
+}
+
 unsafe fn ensure_clif_vectorcall_compiled(
     _py: Python<'_>,
     callable: *mut ffi::PyObject,
@@ -384,6 +416,14 @@ unsafe fn ensure_clif_vectorcall_compiled(
                 .counter_defs,
             &module_constant_ptrs,
             &counter_ptrs,
+            data.module_runtime
+                .shared_module_state_owner
+                .source_map
+                .as_ref(),
+            data.module_runtime
+                .shared_module_state_owner
+                .runtime_options
+                .gdb_jit,
         ) {
             Ok(handle) => handle,
             Err(err) => {
@@ -417,6 +457,10 @@ unsafe fn ensure_clif_vectorcall_compiled(
             ptr::addr_of!(data.module_runtime.vmctx) as *mut c_void,
             data.compiled_handle,
             &vectorcall_symbol,
+            data.module_runtime
+                .shared_module_state_owner
+                .runtime_options
+                .gdb_jit,
         ) {
             Ok(value) => value,
             Err(err) => {
@@ -2,6 +2,6 @@ mod eval;
 
 pub use eval::{
     build_module_runtime_context_for_module, clone_module_runtime_context, compile_clif_vectorcall,
-    register_clif_vectorcall, registered_clif_function_id, with_active_module_runtime_context,
-    with_current_module_runtime_context,
+    inlinable_clif_function_id, register_clif_vectorcall, registered_clif_function_id,
+    with_active_module_runtime_context, with_current_module_runtime_context,
 };
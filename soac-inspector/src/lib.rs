@@ -200,8 +200,11 @@ fn ensure_python_support_paths(py: Python<'_>, repo_root: &Path) -> Result<(), A
 }
 
 fn lower_source_recorded(source: &str) -> Result<soac_blockpy::LoweringResult, ApiError> {
-    soac_blockpy::lower_python_to_blockpy_for_testing(source)
-        .map_err(|err| ApiError::internal(err.to_string()))
+    soac_blockpy::lower_python_to_blockpy_for_testing_with_options(
+        source,
+        &soac_blockpy::LoweringOptions::from_env(),
+    )
+    .map_err(|err| ApiError::internal(err.to_string()))
 }
 
 fn inspector_function_payload(function: &BlockPyFunction<CodegenBlockPyPass>) -> Value {
@@ -7,6 +7,8 @@ use syn::{
     Pat, Path, Token, Type,
 };
 
+mod py_template;
+
 fn enum_variants(input: &DeriveInput) -> syn::Result<Vec<&syn::Variant>> {
     let Data::Enum(data_enum) = &input.data else {
         return Err(syn::Error::new_spanned(
@@ -48,6 +50,7 @@ enum EnumBroadcastTarget {
     ChildVisitable,
     Mappable,
     Debug,
+    OperationKind,
 }
 
 impl EnumBroadcastTarget {
@@ -62,9 +65,10 @@ impl EnumBroadcastTarget {
             "ChildVisitable" => Ok(Self::ChildVisitable),
             "Mappable" => Ok(Self::Mappable),
             "Debug" => Ok(Self::Debug),
+            "OperationKind" => Ok(Self::OperationKind),
             _ => Err(syn::Error::new_spanned(
                 segment,
-                "unsupported enum_broadcast target; supported targets are HasMeta, WithMeta, ChildVisitable, Mappable, and Debug",
+                "unsupported enum_broadcast target; supported targets are HasMeta, WithMeta, ChildVisitable, Mappable, Debug, and OperationKind",
             )),
         }
     }
@@ -144,6 +148,13 @@ impl EnumBroadcastTarget {
                 },
             }
         });
+        let operation_kind_arms = variants.iter().map(|variant| {
+            let variant_name = &variant.ident;
+            let kind = variant_name.to_string();
+            quote! {
+                Self::#variant_name(_) => #kind,
+            }
+        });
 
         match self {
             Self::HasMeta => quote! {
@@ -237,6 +248,15 @@ impl EnumBroadcastTarget {
                     }
                 }
             },
+            Self::OperationKind => quote! {
+                impl #impl_generics OperationKind for #enum_name #ty_generics #where_clause {
+                    fn operation_kind(&self) -> &'static str {
+                        match self {
+                            #( #operation_kind_arms )*
+                        }
+                    }
+                }
+            },
         }
     }
 }
@@ -639,3 +659,15 @@ pub fn match_default(input: TokenStream) -> TokenStream {
         Err(error) => error.into_compile_error().into(),
     }
 }
+
+/// Instantiates a `py_expr!`/`py_stmt!`/`py_stmts!` template. The template is
+/// parsed and its placeholders are matched against the bindings at build time;
+/// the expansion parses it again once per call site and clones that AST.
+#[proc_macro]
+pub fn py_template(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as py_template::PyTemplateInput);
+    match py_template::expand_py_template(input) {
+        Ok(tokens) => tokens.into(),
+        Err(error) => error.into_compile_error().into(),
+    }
+}
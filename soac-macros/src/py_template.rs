@@ -0,0 +1,316 @@
+use proc_macro2::{TokenStream, TokenTree};
+use quote::quote;
+use ruff_python_ast::{Expr, Stmt};
+use std::collections::{BTreeMap, HashSet};
+use syn::parse::{Parse, ParseStream};
+use syn::{Ident, LitStr, Token};
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum TemplateKind {
+    Expr,
+    Stmt,
+    Stmts,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum PlaceholderType {
+    Expr,
+    Stmt,
+    Identifier,
+    Literal,
+    Dict,
+}
+
+impl PlaceholderType {
+    fn parse(ty: &str) -> Option<Self> {
+        match ty {
+            "expr" => Some(Self::Expr),
+            "stmt" => Some(Self::Stmt),
+            "id" => Some(Self::Identifier),
+            "literal" => Some(Self::Literal),
+            "dict" => Some(Self::Dict),
+            _ => None,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Expr => "expr",
+            Self::Stmt => "stmt",
+            Self::Identifier => "id",
+            Self::Literal => "literal",
+            Self::Dict => "dict",
+        }
+    }
+
+    /// Syntax placeholders consume their value, so each may appear only once.
+    fn is_syntax(self) -> bool {
+        matches!(self, Self::Expr | Self::Stmt | Self::Dict)
+    }
+}
+
+struct Placeholder<'a> {
+    name: &'a str,
+    ty: &'a str,
+    start: usize,
+    end: usize,
+}
+
+/// Finds `{name:type}` placeholders the same way `SyntaxTemplate::parse`
+/// does: leftmost first, without overlap.
+fn scan_placeholders(template: &str) -> Vec<Placeholder<'_>> {
+    fn ident_end(bytes: &[u8], start: usize) -> Option<usize> {
+        let first = *bytes.get(start)?;
+        if !(first.is_ascii_alphabetic() || first == b'_') {
+            return None;
+        }
+        let mut end = start + 1;
+        while bytes
+            .get(end)
+            .is_some_and(|byte| byte.is_ascii_alphanumeric() || *byte == b'_')
+        {
+            end += 1;
+        }
+        Some(end)
+    }
+
+    let bytes = template.as_bytes();
+    let mut placeholders = Vec::new();
+    let mut index = 0;
+    while index < bytes.len() {
+        if bytes[index] == b'{' {
+            if let Some(name_end) = ident_end(bytes, index + 1) {
+                if bytes.get(name_end) == Some(&b':') {
+                    if let Some(ty_end) = ident_end(bytes, name_end + 1) {
+                        if bytes.get(ty_end) == Some(&b'}') {
+                            placeholders.push(Placeholder {
+                                name: &template[index + 1..name_end],
+                                ty: &template[name_end + 1..ty_end],
+                                start: index,
+                                end: ty_end + 1,
+                            });
+                            index = ty_end + 1;
+                            continue;
+                        }
+                    }
+                }
+            }
+        }
+        index += 1;
+    }
+    placeholders
+}
+
+fn placeholder_var(name: &str, ty: PlaceholderType) -> String {
+    format!("_dp_placeholder_{}_{}__", ty.name(), name)
+}
+
+fn is_stmt_placeholder(stmt: &Stmt) -> bool {
+    let Stmt::Expr(stmt) = stmt else {
+        return false;
+    };
+    let Expr::Name(name) = stmt.value.as_ref() else {
+        return false;
+    };
+    name.id.starts_with("_dp_placeholder_stmt_")
+}
+
+/// Checks a template the way instantiating it would, returning the type of
+/// each placeholder name in first-use order.
+pub(crate) fn analyze_template(
+    kind: TemplateKind,
+    template: &str,
+) -> Result<Vec<(String, PlaceholderType)>, String> {
+    let mut types: Vec<(String, PlaceholderType)> = Vec::new();
+    let mut uses = BTreeMap::<&str, usize>::new();
+    let mut src = String::with_capacity(template.len());
+    let mut last_end = 0;
+    for placeholder in scan_placeholders(template) {
+        let Some(ty) = PlaceholderType::parse(placeholder.ty) else {
+            return Err(format!(
+                "unknown placeholder type `{}` for `{}`",
+                placeholder.ty, placeholder.name
+            ));
+        };
+        match types.iter().find(|(name, _)| name == placeholder.name) {
+            Some((_, existing)) if existing.is_syntax() || ty.is_syntax() => {
+                if *existing != ty {
+                    return Err(format!(
+                        "placeholder `{}` is used as both {} and {}",
+                        placeholder.name,
+                        existing.name(),
+                        ty.name()
+                    ));
+                }
+            }
+            Some(_) => {}
+            None => types.push((placeholder.name.to_string(), ty)),
+        }
+        *uses.entry(placeholder.name).or_default() += 1;
+
+        src.push_str(&template[last_end..placeholder.start]);
+        src.push_str(&placeholder_var(placeholder.name, ty));
+        last_end = placeholder.end;
+    }
+    src.push_str(&template[last_end..]);
+
+    for (name, ty) in &types {
+        if ty.is_syntax() && uses[name.as_str()] > 1 {
+            return Err(format!(
+                "{} placeholder `{name}` may only appear once",
+                ty.name()
+            ));
+        }
+    }
+
+    let module = ruff_python_parser::parse_module(&src)
+        .map_err(|err| format!("template parse error: {err}\n{src}"))?
+        .into_syntax();
+    match kind {
+        TemplateKind::Expr => {
+            if !matches!(module.body.as_slice(), [Stmt::Expr(_)]) {
+                return Err("py_expr template must be a single expression".to_string());
+            }
+        }
+        TemplateKind::Stmt => {
+            if module.body.len() != 1 && !module.body.iter().any(is_stmt_placeholder) {
+                return Err(format!(
+                    "py_stmt template must produce exactly one statement, got {}",
+                    module.body.len()
+                ));
+            }
+        }
+        TemplateKind::Stmts => {}
+    }
+
+    Ok(types)
+}
+
+struct Binding {
+    name: Ident,
+    value: syn::Expr,
+}
+
+pub(crate) struct PyTemplateInput {
+    krate: TokenTree,
+    kind: Ident,
+    template: LitStr,
+    bindings: Vec<Binding>,
+}
+
+impl Parse for PyTemplateInput {
+    fn parse(input: ParseStream<'_>) -> syn::Result<Self> {
+        let krate = input.parse()?;
+        input.parse::<Token![,]>()?;
+        let kind = input.parse()?;
+        input.parse::<Token![,]>()?;
+        let template = input.parse()?;
+        let mut bindings = Vec::new();
+        while input.parse::<Option<Token![,]>>()?.is_some() {
+            if input.is_empty() {
+                break;
+            }
+            let name = input.parse()?;
+            input.parse::<Token![=]>()?;
+            let value = input.parse()?;
+            bindings.push(Binding { name, value });
+        }
+        Ok(Self {
+            krate,
+            kind,
+            template,
+            bindings,
+        })
+    }
+}
+
+pub(crate) fn expand_py_template(input: PyTemplateInput) -> syn::Result<TokenStream> {
+    let PyTemplateInput {
+        krate,
+        kind,
+        template,
+        bindings,
+    } = input;
+    let template_kind = match kind.to_string().as_str() {
+        "expr" => TemplateKind::Expr,
+        "stmt" => TemplateKind::Stmt,
+        "stmts" => TemplateKind::Stmts,
+        _ => {
+            return Err(syn::Error::new_spanned(
+                kind,
+                "expected expr, stmt, or stmts template kind",
+            ))
+        }
+    };
+    let types = analyze_template(template_kind, &template.value())
+        .map_err(|message| syn::Error::new(template.span(), message))?;
+
+    let mut seen = HashSet::new();
+    let mut inserts = Vec::new();
+    for Binding { name, value } in &bindings {
+        let key = name.to_string();
+        if !seen.insert(key.clone()) {
+            return Err(syn::Error::new_spanned(
+                name,
+                format!("placeholder `{key}` is bound more than once"),
+            ));
+        }
+        let Some((_, ty)) = types.iter().find(|(placeholder, _)| *placeholder == key) else {
+            return Err(syn::Error::new_spanned(
+                name,
+                format!("template has no placeholder named `{key}`"),
+            ));
+        };
+        inserts.push(match ty {
+            PlaceholderType::Expr | PlaceholderType::Dict => quote! {
+                values.insert(
+                    #key.to_string(),
+                    #krate::template::IntoExprPlaceholder::into_expr_placeholder(#value),
+                );
+            },
+            PlaceholderType::Stmt => quote! {
+                values.insert(
+                    #key.to_string(),
+                    #krate::template::IntoStmtPlaceholder::into_stmt_placeholder(#value),
+                );
+            },
+            PlaceholderType::Identifier => quote! {
+                ids.insert(
+                    #key.to_string(),
+                    #krate::template::IntoIdPlaceholder::into_id_placeholder(#value),
+                );
+            },
+            PlaceholderType::Literal => quote! {
+                ids.insert(
+                    #key.to_string(),
+                    #krate::template::IntoLiteralPlaceholder::into_literal_placeholder(#value),
+                );
+            },
+        });
+    }
+    if let Some((missing, ty)) = types.iter().find(|(name, _)| !seen.contains(name)) {
+        return Err(syn::Error::new(
+            template.span(),
+            format!("expected value for {} placeholder `{missing}`", ty.name()),
+        ));
+    }
+
+    let instantiate = match template_kind {
+        TemplateKind::Expr | TemplateKind::Stmt => quote!(instantiate),
+        TemplateKind::Stmts => quote!(instantiate_suite),
+    };
+    Ok(quote! {{
+        static TEMPLATE: ::std::sync::LazyLock<#krate::template::SyntaxTemplate> =
+            ::std::sync::LazyLock::new(|| #krate::template::SyntaxTemplate::parse(#template));
+
+        #[allow(unused_mut)]
+        let mut values = ::std::collections::HashMap::new();
+        #[allow(unused_mut)]
+        let mut ids = ::std::collections::HashMap::new();
+        #( #inserts )*
+        (*TEMPLATE).clone().#instantiate(values, ids)
+    }})
+}
+
+#[cfg(test)]
+mod test;
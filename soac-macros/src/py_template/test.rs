@@ -0,0 +1,64 @@
+use super::{analyze_template, PlaceholderType, TemplateKind};
+
+#[test]
+fn reports_placeholder_types_in_first_use_order() {
+    let types = analyze_template(
+        TemplateKind::Stmt,
+        "{target:id} = {value:expr} + {name:id} + {target:literal}",
+    )
+    .expect("template should be valid");
+
+    assert_eq!(
+        types,
+        vec![
+            ("target".to_string(), PlaceholderType::Identifier),
+            ("value".to_string(), PlaceholderType::Expr),
+            ("name".to_string(), PlaceholderType::Identifier),
+        ]
+    );
+}
+
+#[test]
+fn rejects_unknown_placeholder_type() {
+    let err = analyze_template(TemplateKind::Expr, "{value:exp}").unwrap_err();
+    assert_eq!(err, "unknown placeholder type `exp` for `value`");
+}
+
+#[test]
+fn rejects_reused_syntax_placeholder() {
+    let err = analyze_template(TemplateKind::Expr, "{value:expr} + {value:expr}").unwrap_err();
+    assert_eq!(err, "expr placeholder `value` may only appear once");
+
+    let err = analyze_template(TemplateKind::Expr, "{value:expr} + {value:id}").unwrap_err();
+    assert_eq!(err, "placeholder `value` is used as both expr and id");
+}
+
+#[test]
+fn rejects_template_syntax_errors() {
+    let err = analyze_template(TemplateKind::Stmt, "return {value:expr} +").unwrap_err();
+    assert!(err.starts_with("template parse error:"), "{err}");
+    assert!(err.contains("_dp_placeholder_expr_value__"), "{err}");
+}
+
+#[test]
+fn checks_template_shape_for_kind() {
+    let err = analyze_template(TemplateKind::Expr, "x = 1").unwrap_err();
+    assert_eq!(err, "py_expr template must be a single expression");
+
+    let err = analyze_template(TemplateKind::Stmt, "x = 1\ny = 2").unwrap_err();
+    assert_eq!(
+        err,
+        "py_stmt template must produce exactly one statement, got 2"
+    );
+
+    analyze_template(TemplateKind::Stmt, "x = 1\n{body:stmt}")
+        .expect("stmt placeholders may expand to the missing statement count");
+    analyze_template(TemplateKind::Stmts, "x = 1\ny = 2").expect("suites may have any length");
+}
+
+#[test]
+fn ignores_braces_that_are_not_placeholders() {
+    let types = analyze_template(TemplateKind::Expr, "{'a': {value:expr}, 1: {}}")
+        .expect("dict displays should not be treated as placeholders");
+    assert_eq!(types, vec![("value".to_string(), PlaceholderType::Expr)]);
+}
@@ -7,9 +7,11 @@ use pyo3::ffi;
 use pyo3::prelude::*;
 use pyo3::types::{PyAny, PyDict, PyFunction, PyModule, PyString, PyTuple};
 use soac_blockpy::block_py::{BlockPyFunction, BlockPyModule, FunctionId, FunctionKind, ParamKind};
-use soac_blockpy::lower_python_to_blockpy;
 use soac_blockpy::pass_tracker::NoopPassTracker;
 use soac_blockpy::passes::CodegenBlockPyPass;
+use soac_blockpy::{LoweringOptions, OptimizationLevel, lower_python_to_blockpy};
+use soac_eval::RuntimeOptions;
+use soac_eval::jit::JitSourceMap;
 use soac_eval::module_type::SoacExtModule;
 use std::time::Instant;
 
@@ -92,20 +94,17 @@ fn register_clif_vectorcall_raw(
     }
 }
 
-fn eager_clif_compile_requested() -> bool {
-    std::env::var("DIET_PYTHON_JIT_COMPILE_MODE")
-        .ok()
-        .map(|value| value.trim().eq_ignore_ascii_case("eager"))
-        .unwrap_or(false)
-}
-
 fn maybe_eager_compile_clif_entry(
     py: Python<'_>,
     func: &Bound<'_, PyAny>,
     module_runtime: &soac_eval::jit::ModuleRuntimeContext,
     function_id: FunctionId,
 ) -> PyResult<()> {
-    if !eager_clif_compile_requested() {
+    if !module_runtime
+        .shared_module_state_owner
+        .runtime_options
+        .eager_jit_compile
+    {
         return Ok(());
     }
     let start = Instant::now();
@@ -652,10 +651,28 @@ fn make_bb_function(
 #[pyfunction]
 fn create_module(py: Python<'_>, source: &str, spec: Py<PyAny>) -> PyResult<Py<PyAny>> {
     let session = soac_eval::CompileSession::new();
+    let spec = spec.bind(py);
+    let file_name = spec
+        .getattr("origin")
+        .and_then(|origin| origin.extract::<String>())
+        .or_else(|_| spec.getattr("name")?.extract::<String>())?;
+    let optimize = PyModule::import(py, "sys")?
+        .getattr("flags")?
+        .getattr("optimize")?
+        .extract::<i64>()?;
+    let options =
+        LoweringOptions::from_env().with_optimization(OptimizationLevel::from_flag(optimize));
     let output: soac_blockpy::LoweringResult<NoopPassTracker> =
-        lower_python_to_blockpy(source, session.module_name_gen())
-            .map_err(lowering_error_to_pyerr)?;
-    SoacExtModule::new(py, spec.bind(py).as_any(), output.codegen_module)
+        lower_python_to_blockpy(source, session.module_name_gen(), &options)
+            .map_err(|err| lowering_error_to_pyerr(err, &file_name, source))?;
+    let source_map = JitSourceMap::new(file_name, source);
+    SoacExtModule::new(
+        py,
+        spec.as_any(),
+        output.codegen_module,
+        Some(source_map),
+        RuntimeOptions::from_env(),
+    )
 }
 
 fn ensure_module_builtins(globals: &Bound<'_, PyAny>) -> PyResult<()> {
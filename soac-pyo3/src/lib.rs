@@ -1,28 +1,60 @@
 #![allow(unsafe_op_in_unsafe_fn)]
 
 mod jit_runtime;
+mod profile;
 
 use log::trace;
 use pyo3::prelude::*;
-use pyo3::types::PyModule;
-use soac_blockpy::{lower_python_to_blockpy_for_testing, ruff_ast_to_string};
+use pyo3::types::{PyDict, PyList, PyModule};
+use soac_blockpy::diagnostic::{DiagnosticKind, source_location};
+use soac_blockpy::symtable::SymbolTable;
+use soac_blockpy::{
+    LoweringOptions, check_syntax, lower_python_to_blockpy_for_testing_with_options,
+    ruff_ast_to_string,
+};
 
 #[cfg(test)]
 mod test;
 
-pub(crate) fn lowering_error_to_pyerr(err: soac_blockpy::LoweringError) -> PyErr {
-    match err {
-        soac_blockpy::LoweringError::Parse(parse_error) => {
-            pyo3::exceptions::PySyntaxError::new_err(parse_error.to_string())
-        }
-        soac_blockpy::LoweringError::Other(err) => {
-            pyo3::exceptions::PyRuntimeError::new_err(err.to_string())
+/// Raises `SyntaxError` for invalid input, with the usual `filename`/`lineno`
+/// attributes, `NotImplementedError` carrying the rendered diagnostic for
+/// input the lowering cannot handle, and `SystemError` for internal lowering
+/// failures.
+pub(crate) fn lowering_error_to_pyerr(
+    err: soac_blockpy::LoweringError,
+    filename: &str,
+    source: &str,
+) -> PyErr {
+    let diagnostic = err.to_diagnostic();
+    match (diagnostic.kind, diagnostic.range) {
+        (DiagnosticKind::Syntax, Some(range)) => {
+            let start = source_location(source, range.start());
+            let end = source_location(source, range.end());
+            let text = source.lines().nth(start.line - 1).unwrap_or("").to_string();
+            pyo3::exceptions::PySyntaxError::new_err((
+                diagnostic.message,
+                (
+                    filename.to_string(),
+                    start.line,
+                    start.column,
+                    text,
+                    end.line,
+                    end.column,
+                ),
+            ))
         }
+        (DiagnosticKind::Internal, _) => pyo3::exceptions::PySystemError::new_err(
+            diagnostic.render(filename, source).trim_end().to_string(),
+        ),
+        _ => pyo3::exceptions::PyNotImplementedError::new_err(
+            diagnostic.render(filename, source).trim_end().to_string(),
+        ),
     }
 }
 
-fn lower_source(source: &str) -> PyResult<soac_blockpy::LoweringResult> {
-    lower_python_to_blockpy_for_testing(source).map_err(lowering_error_to_pyerr)
+fn lower_source(source: &str, filename: &str) -> PyResult<soac_blockpy::LoweringResult> {
+    lower_python_to_blockpy_for_testing_with_options(source, &LoweringOptions::from_env())
+        .map_err(|err| lowering_error_to_pyerr(err, filename, source))
 }
 
 fn rendered_ast_to_ast_source(source: &str, output: &soac_blockpy::LoweringResult) -> String {
@@ -37,15 +69,83 @@ fn rendered_ast_to_ast_source(source: &str, output: &soac_blockpy::LoweringResul
 fn transform_source_with_name(source: &str, module_name: &str) -> PyResult<String> {
     let preview = source.get(..100).unwrap_or(source);
     trace!("transform_source_with_name({module_name}): {}", preview);
-    let output = lower_source(source)?;
+    let output = lower_source(source, module_name)?;
     jit_runtime::register_lowered_module_plans(&output, module_name)?;
     Ok(rendered_ast_to_ast_source(source, &output))
 }
 
+/// Raises the `SyntaxError` CPython would raise for `source`, without
+/// lowering it.
+#[pyfunction]
+fn check_source_syntax(source: &str, filename: &str) -> PyResult<()> {
+    check_syntax(source).map_err(|err| lowering_error_to_pyerr(err, filename, source))
+}
+
+fn symbol_table_to_py<'py>(py: Python<'py>, table: &SymbolTable) -> PyResult<Bound<'py, PyDict>> {
+    let symbols = PyDict::new(py);
+    for symbol in &table.symbols {
+        let entry = PyDict::new(py);
+        entry.set_item("scope", symbol.scope.as_str())?;
+        entry.set_item("is_parameter", symbol.is_parameter)?;
+        entry.set_item("is_referenced", symbol.is_referenced)?;
+        symbols.set_item(symbol.name.as_str(), entry)?;
+    }
+    let children = PyList::empty(py);
+    for child in &table.children {
+        children.append(symbol_table_to_py(py, child)?)?;
+    }
+    let out = PyDict::new(py);
+    out.set_item("type", table.kind.as_str())?;
+    out.set_item("name", table.name.as_str())?;
+    out.set_item("symbols", symbols)?;
+    out.set_item("children", children)?;
+    Ok(out)
+}
+
+/// Returns the scopes diet-python computes for `source` as nested dicts:
+/// `{"type", "name", "symbols": {name: {"scope", ...}}, "children"}`.
+#[pyfunction]
+fn symbol_table<'py>(
+    py: Python<'py>,
+    source: &str,
+    filename: &str,
+) -> PyResult<Bound<'py, PyDict>> {
+    let table = soac_blockpy::symbol_table(source)
+        .map_err(|err| lowering_error_to_pyerr(err, filename, source))?;
+    symbol_table_to_py(py, &table)
+}
+
+/// Lists the loads of function locals that may run before the local is
+/// assigned, as dicts with `qualname`, `name`, `lineno` and `column`.
+#[pyfunction]
+fn maybe_unbound_locals<'py>(
+    py: Python<'py>,
+    source: &str,
+    filename: &str,
+) -> PyResult<Bound<'py, PyList>> {
+    let loads = soac_blockpy::maybe_unbound_locals(source)
+        .map_err(|err| lowering_error_to_pyerr(err, filename, source))?;
+    let out = PyList::empty(py);
+    for load in loads {
+        let location = source_location(source, load.range.start());
+        let entry = PyDict::new(py);
+        entry.set_item("qualname", load.qualname)?;
+        entry.set_item("name", load.name)?;
+        entry.set_item("lineno", location.line)?;
+        entry.set_item("column", location.column)?;
+        out.append(entry)?;
+    }
+    Ok(out)
+}
+
 #[pymodule]
 fn _soac_ext(_py: Python<'_>, module: &Bound<'_, PyModule>) -> PyResult<()> {
     soac_blockpy::init_logging();
     module.add_function(wrap_pyfunction!(transform_source_with_name, module)?)?;
+    module.add_function(wrap_pyfunction!(check_source_syntax, module)?)?;
+    module.add_function(wrap_pyfunction!(symbol_table, module)?)?;
+    module.add_function(wrap_pyfunction!(maybe_unbound_locals, module)?)?;
     jit_runtime::add_module_functions(module)?;
+    profile::add_module_functions(module)?;
     Ok(())
 }
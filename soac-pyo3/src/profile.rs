@@ -0,0 +1,40 @@
+use pyo3::exceptions::PyRuntimeError;
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyList, PyModule};
+use soac_eval::profile::{DEFAULT_SAMPLE_CAPACITY, start_profiling, stop_profiling};
+
+#[pyfunction]
+#[pyo3(signature = (interval_us=1000, capacity=DEFAULT_SAMPLE_CAPACITY))]
+fn profile_start(interval_us: u64, capacity: usize) -> PyResult<()> {
+    start_profiling(interval_us, capacity).map_err(PyRuntimeError::new_err)
+}
+
+/// Stops the profiler and returns `(collapsed_stacks, hotspots, dropped)`.
+#[pyfunction]
+fn profile_stop(py: Python<'_>) -> PyResult<(String, Py<PyList>, usize)> {
+    let profile = stop_profiling().map_err(PyRuntimeError::new_err)?;
+    let hotspots = PyList::empty(py);
+    for hotspot in profile.instr_hotspots() {
+        let row = PyDict::new(py);
+        row.set_item("qualname", hotspot.qualname)?;
+        row.set_item("block_label", hotspot.block_label)?;
+        row.set_item(
+            "instr_id",
+            hotspot.instr_id.map(|instr_id| instr_id.to_string()),
+        )?;
+        row.set_item("line", hotspot.line)?;
+        row.set_item("samples", hotspot.samples)?;
+        hotspots.append(row)?;
+    }
+    Ok((
+        profile.collapsed_stacks(),
+        hotspots.unbind(),
+        profile.dropped_samples,
+    ))
+}
+
+pub(crate) fn add_module_functions(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_function(wrap_pyfunction!(profile_start, module)?)?;
+    module.add_function(wrap_pyfunction!(profile_stop, module)?)?;
+    Ok(())
+}
@@ -1,4 +1,8 @@
+use crate::lowering_error_to_pyerr;
+use pyo3::exceptions::{PyNotImplementedError, PySystemError};
+use pyo3::prelude::*;
 use soac_blockpy::block_py::FunctionKind;
+use soac_blockpy::diagnostic::Diagnostic;
 use soac_eval::jit;
 use std::any::Any;
 use std::collections::HashSet;
@@ -291,3 +295,27 @@ def exercise():
         storage_layout
     );
 }
+
+#[test]
+fn internal_lowering_diagnostics_raise_system_error() {
+    Python::initialize();
+    Python::attach(|py| {
+        let internal = lowering_error_to_pyerr(
+            soac_blockpy::LoweringError::Diagnostic(Diagnostic::internal("lost a block")),
+            "mod.py",
+            "x = 1\n",
+        );
+        assert!(internal.is_instance_of::<PySystemError>(py), "{internal}");
+
+        let source = "x = 1j\n";
+        let err = match soac_blockpy::lower_python_to_blockpy_for_testing(source) {
+            Ok(_) => panic!("complex literals should not lower"),
+            Err(err) => err,
+        };
+        let unsupported = lowering_error_to_pyerr(err, "mod.py", source);
+        assert!(
+            unsupported.is_instance_of::<PyNotImplementedError>(py),
+            "{unsupported}"
+        );
+    });
+}
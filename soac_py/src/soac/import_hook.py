@@ -31,6 +31,9 @@ def _create_module_from_source(path: str, source: str, spec):
         if err.filename is None:
             err.filename = path
         raise
+    except (NotImplementedError, SystemError):
+        # The message already carries the file, line and source snippet.
+        raise
     except Exception as err:
         raise ImportError(f"diet-python failed for {path}: {err}") from err
 
@@ -0,0 +1,83 @@
+"""Sampling profiler for JIT-compiled soac code.
+
+Usage::
+
+    python -m soac.profile -o out.collapsed script.py [args...]
+
+The output is in collapsed-stack format (``frame;frame;frame count``), which
+speedscope and flamegraph tools load directly. JIT frames are named by
+qualname and source line; the per-instruction hotspot table is written next
+to it as ``<output>.hotspots.json``.
+"""
+
+from __future__ import annotations
+
+import argparse
+import json
+import sys
+from pathlib import Path
+
+from . import _soac_ext
+
+
+class Profile:
+    """Context manager that samples the current thread while active."""
+
+    def __init__(self, interval_us: int = 1000):
+        self.interval_us = interval_us
+        self.collapsed = ""
+        self.hotspots: list[dict] = []
+        self.dropped_samples = 0
+
+    def __enter__(self) -> "Profile":
+        _soac_ext.profile_start(self.interval_us)
+        return self
+
+    def __exit__(self, *exc_info) -> None:
+        self.collapsed, self.hotspots, self.dropped_samples = _soac_ext.profile_stop()
+
+    def write(self, path: str | Path) -> None:
+        path = Path(path)
+        path.write_text(self.collapsed, encoding="utf-8")
+        hotspots_path = path.with_name(path.name + ".hotspots.json")
+        hotspots_path.write_text(json.dumps(self.hotspots, indent=2), encoding="utf-8")
+
+
+def main(argv: list[str] | None = None) -> int:
+    parser = argparse.ArgumentParser(
+        description="Run a module under the soac sampling profiler"
+    )
+    parser.add_argument("-o", "--output", default="soac.collapsed")
+    parser.add_argument("--interval-us", type=int, default=1000)
+    parser.add_argument("module", help="Module name or path to a .py file")
+    parser.add_argument("args", nargs=argparse.REMAINDER)
+    args = parser.parse_args(argv)
+
+    from . import import_hook
+
+    profile = Profile(args.interval_us)
+    status = 1
+    try:
+        with profile:
+            status = import_hook.main([args.module, *args.args])
+    except SystemExit as exc:
+        # A script that calls sys.exit() still gets its profile written.
+        if exc.code is None:
+            status = 0
+        elif isinstance(exc.code, int):
+            status = exc.code
+        else:
+            print(exc.code, file=sys.stderr)
+    finally:
+        profile.write(args.output)
+        if profile.dropped_samples:
+            print(
+                f"soac.profile: dropped {profile.dropped_samples} samples "
+                "(sample buffer full)",
+                file=sys.stderr,
+            )
+    return status
+
+
+if __name__ == "__main__":
+    raise SystemExit(main())
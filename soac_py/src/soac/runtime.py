@@ -68,6 +68,8 @@ _jit_make_bb_function = _soac_ext.make_bb_function
 
 next = _builtins.next
 iter = _builtins.iter
+# The iterator a `for` loop steps through; the JIT lowers this to `GetIter`.
+loop_iter = _builtins.iter
 anext = _builtins.anext
 isinstance = _builtins.isinstance
 getattr = _builtins.getattr
@@ -95,6 +97,21 @@ def tuple_from_iter(value):
     return _builtins.tuple(value)
 
 
+_FORMAT_CONVERSIONS = {"s": str, "r": repr, "a": ascii}
+
+
+def format_value(value, conversion=None, format_spec=""):
+    # One f-string interpolation; `conversion` is "s", "r", "a" or None.
+    if conversion is not None:
+        value = _FORMAT_CONVERSIONS[conversion](value)
+    return format(value, format_spec)
+
+
+def build_string(*parts):
+    # Concatenation of already formatted f-string parts.
+    return "".join(parts)
+
+
 def __deepcopy__(memo):
     # Modules are not pickleable; keep runtime as a singleton during deepcopy().
     return _sys.modules[__name__]
@@ -482,6 +499,8 @@ def class_lookup_global(class_ns, name, globals_dict):
         try:
             return globals_dict[name]
         except KeyError:
+            if name in (_pending_lazy_imports(globals_dict) or ()):
+                return resolve_lazy_import(globals_dict, name)
             try:
                 return _builtins.__dict__[name]
             except KeyError as exc:
@@ -617,6 +636,38 @@ def match_class_attr_value(cls, subject, idx, total):
     return getattr(subject, name)
 
 
+def match_class(cls, subject, total, *keywords):
+    # Like CPython's MATCH_CLASS, fetch every attribute before any
+    # sub-pattern runs, and fail the match if one is missing.
+    if not isinstance(subject, cls):
+        return None
+    values = []
+    names = list(keywords)
+    if total:
+        match_args = getattr(cls, "__match_args__", None)
+        _match_class_validate_arity(cls, match_args, total)
+        if match_args is None:
+            values.append(subject)
+        else:
+            names[:0] = match_args[:total]
+    for name in names:
+        try:
+            values.append(getattr(subject, name))
+        except AttributeError:
+            return None
+    return tuple(values)
+
+
+def match_literal_case(subject, cases):
+    # Only exact ints and strs hash and compare like the literals in `cases`;
+    # anything else may override `__eq__`, so the caller compares it case by
+    # case instead.
+    subject_type = type(subject)
+    if subject_type is int or subject_type is str:
+        return cases.get(subject, -1)
+    return None
+
+
 _DP_CODE_WITH_FREEVARS_CACHE = {}
 _CLIF_ENTRY_RUNTIME_ERROR = "CLIF entry executed without vectorcall interception"
 
@@ -960,6 +1011,127 @@ def import_attr(module, attr):
         raise import_error.with_traceback(exc.__traceback__) from None
 
 
+# Pending lazy top-level imports, keyed by the ``id`` of the globals dict
+# they bind into. Each entry is ``(globals_dict, pending, module)``: holding
+# the dict keeps its id from being reused, ``pending`` maps binding names to
+# their `LazyImport`, and ``module`` is the module switched to `_LazyModule`,
+# if any. An entry goes away once nothing in it is pending. None of this is
+# stored in the module's globals.
+_LAZY_IMPORTS = {}
+
+
+class LazyImport:
+    """Lazy top-level import waiting for the first load of its binding."""
+
+    __slots__ = ("_name", "_spec", "_fromlist", "_level", "_attrs")
+
+    def __init__(self, name, spec, fromlist, level, attrs):
+        self._name = name
+        self._spec = spec
+        self._fromlist = fromlist
+        self._level = level
+        self._attrs = attrs
+
+    def resolve(self):
+        value = import_(self._name, self._spec, self._fromlist, self._level)
+        for attr in self._attrs:
+            value = import_attr(value, attr)
+        return value
+
+    def __repr__(self):
+        return f"<lazy import {self._name!r}>"
+
+
+_object_getattribute = object.__getattribute__
+_module_getattribute = _types.ModuleType.__getattribute__
+_module_dir = _types.ModuleType.__dir__
+
+
+class _LazyModule(_types.ModuleType):
+    """Class of a module while it has pending lazy imports, so access from
+    outside sees them as the bindings they stand for. A ``__getattr__`` or
+    ``__dir__`` the module defines stays in its globals and still runs for
+    every other name. The module turns back into a plain module once nothing
+    is pending.
+    """
+
+    __slots__ = ()
+
+    def __getattribute__(self, name):
+        try:
+            return _object_getattribute(self, name)
+        except AttributeError:
+            pass
+        globals_dict = _object_getattribute(self, "__dict__")
+        pending = _pending_lazy_imports(globals_dict)
+        if pending:
+            if name in pending:
+                return resolve_lazy_import(globals_dict, name)
+            if name == "__all__":
+                # Without `__all__`, `from m import *` binds every public
+                # name in the module dict, so put the pending ones there.
+                for binding in list(pending):
+                    if not binding.startswith("_") and binding in pending:
+                        resolve_lazy_import(globals_dict, binding)
+        return _module_getattribute(self, name)
+
+    def __dir__(self):
+        names = _module_dir(self)
+        globals_dict = _object_getattribute(self, "__dict__")
+        if callable(globals_dict.get("__dir__")):
+            return names
+        pending = _pending_lazy_imports(globals_dict) or ()
+        return [*names, *(binding for binding in pending if binding not in globals_dict)]
+
+
+def _pending_lazy_imports(globals_dict):
+    entry = _LAZY_IMPORTS.get(id(globals_dict))
+    return None if entry is None else entry[1]
+
+
+def _drop_settled_lazy_imports(globals_dict):
+    entry = _LAZY_IMPORTS.get(id(globals_dict))
+    if entry is None or entry[1]:
+        return
+    del _LAZY_IMPORTS[id(globals_dict)]
+    module = entry[2]
+    if module is not None and type(module) is _LazyModule:
+        module.__class__ = _types.ModuleType
+
+
+def lazy_import(globals_dict, binding, name, spec, fromlist=None, level=0, attrs=()):
+    entry = _LAZY_IMPORTS.get(id(globals_dict))
+    if entry is None:
+        module = _sys.modules.get(globals_dict.get("__name__"))
+        if type(module) is _types.ModuleType and module.__dict__ is globals_dict:
+            module.__class__ = _LazyModule
+        else:
+            module = None
+        entry = _LAZY_IMPORTS[id(globals_dict)] = (globals_dict, {}, module)
+    # Like the import it stands for, a lazy import rebinds its name.
+    globals_dict.pop(binding, None)
+    entry[1][binding] = LazyImport(name, spec, fromlist, level, attrs)
+
+
+def resolve_lazy_import(globals_dict, binding):
+    """Runs the pending lazy import of ``binding`` and binds the result in
+    ``globals_dict``, unless the import was replaced or dropped meanwhile."""
+    pending = _pending_lazy_imports(globals_dict)
+    lazy = pending[binding]
+    value = lazy.resolve()
+    if pending.get(binding) is lazy:
+        del pending[binding]
+        globals_dict[binding] = value
+        _drop_settled_lazy_imports(globals_dict)
+    return value
+
+
+def discard_lazy_import(globals_dict, binding):
+    """Drops the pending lazy import of ``binding`` for a ``del`` of the name."""
+    del _pending_lazy_imports(globals_dict)[binding]
+    _drop_settled_lazy_imports(globals_dict)
+
+
 def import_star(name, spec, globals_dict, level=0):
     module = import_(name, spec, ["*"], level)
     try:
@@ -3,6 +3,7 @@ from __future__ import annotations
 import os
 import importlib
 import sys
+import sysconfig
 from contextlib import contextmanager
 from uuid import uuid4
 from pathlib import Path
@@ -19,6 +20,11 @@ if str(PYTHON_SRC) not in sys.path:
 from soac import import_hook
 
 _VALIDATE_DELIMITER = "# diet-python: validate"
+_TEST_SETS = ROOT / "test_sets"
+_CPYTHON_LIB_ROOTS = [
+    ROOT / "vendor" / "cpython" / "Lib",
+    Path(sysconfig.get_paths()["stdlib"]),
+]
 
 _REGISTERED_MODULES: list[Path] = []
 _PRINTED_MODULES: set[Path] = set()
@@ -234,3 +240,27 @@ def integration_module(
 ) -> Iterator[ModuleType]:
     with _load_module(tmp_path, module_name, source, mode=mode) as module:
         yield module
+
+
+def integration_module_paths() -> list[Path]:
+    return sorted((ROOT / "tests" / "integration_modules").glob("*.py"))
+
+
+def cpython_test_module_paths() -> list[Path]:
+    """Source files for the CPython test modules listed in test_sets/."""
+    names = set()
+    for test_set in sorted(_TEST_SETS.glob("*.txt")):
+        for line in test_set.read_text(encoding="utf-8").splitlines():
+            line = line.strip()
+            if line and not line.startswith("#"):
+                names.add(line)
+    paths = []
+    for name in sorted(names):
+        relative = Path(*name.split("."))
+        for root in _CPYTHON_LIB_ROOTS:
+            candidates = [root / relative.with_suffix(".py"), root / relative / "__init__.py"]
+            found = next((path for path in candidates if path.is_file()), None)
+            if found is not None:
+                paths.append(found)
+                break
+    return paths
@@ -8,6 +8,7 @@ from pathlib import Path
 from types import ModuleType
 
 os.environ.setdefault("DIET_PYTHON_VALIDATE_MIN_AST", "1")
+os.environ.setdefault("DIET_PYTHON_VERIFY_PASSES", "1")
 
 ROOT = Path(__file__).resolve().parent.parent
 PYTHON_SRC = ROOT / "soac_py" / "src"
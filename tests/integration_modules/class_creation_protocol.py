@@ -0,0 +1,126 @@
+"""Class statements exercising the `__build_class__` protocol."""
+
+import sys
+
+events = []
+
+
+class Base:
+    def hello(self):
+        return "base"
+
+
+class UsesSuper(Base):
+    def hello(self):
+        return "child of " + super().hello()
+
+    def own_class(self):
+        return __class__
+
+
+class Entry:
+    def __mro_entries__(self, bases):
+        events.append(("mro_entries", len(bases)))
+        return (Base,)
+
+
+entry = Entry()
+
+
+class FromEntry(entry):
+    pass
+
+
+class Meta(type):
+    @classmethod
+    def __prepare__(mcls, name, bases, **kwds):
+        events.append(("prepare", name, sorted(kwds)))
+        return {"prepared": True}
+
+    def __new__(mcls, name, bases, ns, **kwds):
+        events.append(("new", name, sorted(kwds)))
+        cls = super().__new__(mcls, name, bases, ns)
+        cls.flag = kwds.get("flag")
+        return cls
+
+    def __init__(cls, name, bases, ns, **kwds):
+        super().__init__(name, bases, ns)
+
+
+class WithMeta(metaclass=Meta, flag=7):
+    def method(self):
+        return __class__
+
+
+class DerivedMeta(WithMeta, flag=8):
+    pass
+
+
+class OtherMeta(type):
+    pass
+
+
+class Other(metaclass=OtherMeta):
+    pass
+
+
+def conflict():
+    class Both(WithMeta, Other):
+        pass
+
+
+def bad_entries():
+    class BadEntry:
+        def __mro_entries__(self, bases):
+            return [Base]
+
+    class Broken(BadEntry()):
+        pass
+
+
+def returns_none():
+    def fake_meta(name, bases, ns):
+        return None
+
+    class Nothing(metaclass=fake_meta):
+        pass
+
+    return Nothing
+
+
+def error_of(fn):
+    try:
+        fn()
+    except TypeError as exc:
+        return str(exc)
+    raise AssertionError("expected a TypeError")
+
+
+# diet-python: validate
+
+def validate_module(module):
+    assert module.UsesSuper().hello() == "child of base"
+    assert module.UsesSuper().own_class() is module.UsesSuper
+    assert "__classcell__" not in module.UsesSuper.__dict__
+
+    assert module.FromEntry.__bases__ == (module.Base,)
+    assert module.FromEntry.__orig_bases__ == (module.entry,)
+    assert "__orig_bases__" not in module.UsesSuper.__dict__
+    assert ("mro_entries", 1) in module.events
+
+    assert type(module.WithMeta) is module.Meta
+    assert module.WithMeta.flag == 7
+    assert module.WithMeta.prepared is True
+    assert module.WithMeta().method() is module.WithMeta
+    assert module.DerivedMeta.flag == 8
+    assert type(module.DerivedMeta) is module.Meta
+    assert ("prepare", "WithMeta", ["flag"]) in module.events
+    assert ("new", "DerivedMeta", ["flag"]) in module.events
+
+    assert module.error_of(module.conflict).startswith("metaclass conflict")
+    assert module.error_of(module.bad_entries) == "__mro_entries__ must return a tuple"
+    assert module.returns_none() is None
+
+    if sys.version_info >= (3, 13):
+        assert module.UsesSuper.__firstlineno__ == 13
+        assert isinstance(module.UsesSuper.__static_attributes__, tuple)
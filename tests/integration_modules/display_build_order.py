@@ -0,0 +1,94 @@
+"""Tuple, list, set and dict displays with star and double-star segments."""
+
+
+class Key:
+    def __init__(self, name, log):
+        self.name = name
+        self.log = log
+
+    def __hash__(self):
+        return 0
+
+    def __eq__(self, other):
+        self.log.append(("eq", self.name, other.name))
+        return True
+
+    def __repr__(self):
+        return self.name
+
+
+def traced(log, label, value):
+    log.append(label)
+    return value
+
+
+def build_all(log):
+    t = (traced(log, "t0", 0), *traced(log, "t1", [1, 2]), traced(log, "t2", 3))
+    l = [traced(log, "l0", 0), *traced(log, "l1", (1,)), traced(log, "l2", 2)]
+    s = {traced(log, "s0", 0), *traced(log, "s1", {1}), traced(log, "s2", 0)}
+    d = {
+        traced(log, "k0", "a"): traced(log, "v0", 1),
+        **traced(log, "m", {"b": 2, "a": 3}),
+        traced(log, "k1", "c"): traced(log, "v1", 4),
+    }
+    return t, l, s, d
+
+
+def duplicate_keys(log):
+    first = Key("first", log)
+    second = Key("second", log)
+    return {first: 1, second: 2}
+
+
+def star_non_iterable():
+    return [1, *5]
+
+
+def starstar_non_mapping():
+    return {"a": 1, **5}
+
+
+def empty_displays():
+    return (), [], {}
+
+
+# diet-python: validate
+
+def validate_module(module):
+    log = []
+    t, l, s, d = module.build_all(log)
+    assert t == (0, 1, 2, 3)
+    assert l == [0, 1, 2]
+    assert s == {0, 1}
+    assert d == {"a": 3, "b": 2, "c": 4}
+    assert list(d) == ["a", "b", "c"]
+    assert log == [
+        "t0", "t1", "t2",
+        "l0", "l1", "l2",
+        "s0", "s1", "s2",
+        "k0", "v0", "m", "k1", "v1",
+    ]
+
+    log = []
+    d = module.duplicate_keys(log)
+    assert len(d) == 1
+    [(key, value)] = d.items()
+    assert key.name == "first"
+    assert value == 2
+    assert log == [("eq", "first", "second")]
+
+    try:
+        module.star_non_iterable()
+    except TypeError as exc:
+        assert str(exc) == "Value after * must be an iterable, not int", exc
+    else:
+        raise AssertionError("expected TypeError")
+
+    try:
+        module.starstar_non_mapping()
+    except TypeError as exc:
+        assert str(exc) == "'int' object is not a mapping", exc
+    else:
+        raise AssertionError("expected TypeError")
+
+    assert module.empty_displays() == ((), [], {})
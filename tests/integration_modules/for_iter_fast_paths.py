@@ -0,0 +1,146 @@
+"""For loops over ranges, lists, tuples, dict views and custom iterators."""
+
+
+def collect(iterable):
+    out = []
+    for item in iterable:
+        out.append(item)
+    return out
+
+
+def grow_while_iterating():
+    items = [1, 2, 3]
+    seen = []
+    for item in items:
+        seen.append(item)
+        if item < 3:
+            items.append(item + 10)
+    return seen
+
+
+def shrink_while_iterating():
+    items = [1, 2, 3, 4]
+    seen = []
+    for item in items:
+        seen.append(item)
+        items.pop()
+    return seen
+
+
+def mutate_dict_while_iterating():
+    d = {"a": 1}
+    for key in d:
+        d[key + "x"] = 2
+
+
+def nested_ranges(n):
+    pairs = []
+    for i in range(n):
+        for j in range(i):
+            pairs.append((i, j))
+    return pairs
+
+
+def paused_loop(iterable):
+    for item in iterable:
+        yield item
+
+
+class Countdown:
+    def __init__(self, start):
+        self.current = start
+
+    def __iter__(self):
+        return self
+
+    def __next__(self):
+        if self.current <= 0:
+            raise StopIteration
+        self.current -= 1
+        return self.current + 1
+
+
+class BrokenIterable:
+    def __iter__(self):
+        return 5
+
+
+def find_with_else(items, target):
+    for item in items:
+        if item == target:
+            break
+    else:
+        return "missing"
+    return "found"
+
+
+# diet-python: validate
+
+def validate_module(module):
+    assert module.collect(range(5)) == [0, 1, 2, 3, 4]
+    assert module.collect(range(2, 11, 3)) == [2, 5, 8]
+    assert module.collect(range(5, 0, -2)) == [5, 3, 1]
+    assert module.collect(range(3, 3)) == []
+    assert module.collect(range(0, 5, -1)) == []
+    top = 2 ** 63 - 1
+    assert module.collect(range(top - 2, top)) == [top - 2, top - 1]
+    assert module.collect(range(0, top, 2 ** 62)) == [0, 2 ** 62]
+    assert module.collect(range(-top - 1, -top + 1)) == [-top - 1, -top]
+    assert module.nested_ranges(3) == [(1, 0), (2, 0), (2, 1)]
+    big = 2 ** 70
+    assert module.collect(range(big, big + 3)) == [big, big + 1, big + 2]
+
+    assert module.collect([1, "a", None]) == [1, "a", None]
+    assert module.collect((1, 2, 3)) == [1, 2, 3]
+    assert module.grow_while_iterating() == [1, 2, 3, 11, 12]
+    assert module.shrink_while_iterating() == [1, 2]
+
+    d = {"a": 1, "b": 2}
+    assert module.collect(d) == ["a", "b"]
+    assert module.collect(d.keys()) == ["a", "b"]
+    assert module.collect(d.values()) == [1, 2]
+    assert module.collect(d.items()) == [("a", 1), ("b", 2)]
+
+    try:
+        module.mutate_dict_while_iterating()
+    except RuntimeError as exc:
+        assert str(exc) == "dictionary changed size during iteration", exc
+    else:
+        raise AssertionError("expected RuntimeError")
+
+    assert module.collect(module.Countdown(3)) == [3, 2, 1]
+    assert module.collect("ab") == ["a", "b"]
+
+    try:
+        module.collect(module.BrokenIterable())
+    except TypeError:
+        pass
+    else:
+        raise AssertionError("expected TypeError")
+
+    assert module.find_with_else([1, 2, 3], 2) == "found"
+    assert module.find_with_else(range(3), 7) == "missing"
+
+    import copy
+    import gc
+    import operator
+    import pickle
+
+    for iterable, rest in [
+        ([1, 2, 3], [2, 3]),
+        ((1, 2, 3), [2, 3]),
+        (range(1, 7, 2), [3, 5]),
+        ({"a": 1, "b": 2, "c": 3}, ["b", "c"]),
+        ({"a": 1, "b": 2}.items(), [("b", 2)]),
+    ]:
+        before = {id(obj) for obj in gc.get_objects()}
+        gen = module.paused_loop(iterable)
+        next(gen)
+        # Fast iterators are GC-tracked, so a paused loop's shows up here.
+        for it in gc.get_objects():
+            if id(it) in before or type(it).__name__ != "fast_iterator":
+                continue
+            assert operator.length_hint(it) == len(rest), it
+            assert list(copy.copy(it)) == rest, it
+            assert list(pickle.loads(pickle.dumps(it))) == rest, it
+        assert list(gen) == rest
@@ -0,0 +1,91 @@
+"""f-strings exercising conversions, nested specs and `__format__`."""
+
+
+class Point:
+    def __init__(self, x, y):
+        self.x = x
+        self.y = y
+
+    def __repr__(self):
+        return f"Point({self.x!r}, {self.y!r})"
+
+    def __format__(self, spec):
+        return f"<{self.x:{spec}}, {self.y:{spec}}>"
+
+
+class Loud(str):
+    def __str__(self):
+        return "LOUD"
+
+
+class NotStr:
+    def __format__(self, spec):
+        return 42
+
+
+class Exploding:
+    def __format__(self, spec):
+        raise ValueError(f"bad spec {spec!r}")
+
+
+def describe(name, width, value):
+    return f"{name!s:>{width}}|{value!r:^{width + 2}}|{value:.{2}f}"
+
+
+def conversions(value):
+    return f"{value!s} {value!r} {value!a}"
+
+
+def debug(value):
+    return f"{value=} {value = !s} {value=:>6}"
+
+
+def literal_only():
+    return f"plain" f" text" ""
+
+
+def single(value):
+    return f"{value}"
+
+
+def surrogate():
+    lone = "\ud800"
+    return f"{lone}-{'x'}"
+
+
+def format_error(fn):
+    try:
+        fn()
+    except (TypeError, ValueError) as exc:
+        return f"{type(exc).__name__}: {exc}"
+    raise AssertionError("expected an error")
+
+
+# diet-python: validate
+
+def validate_module(module):
+    assert module.describe("ab", 4, 3.14159) == "  ab|3.14159|3.14"
+    assert module.conversions("é") == "é 'é' '\\xe9'"
+    assert module.debug(7) == "value=7 value = 7 value=     7"
+    assert module.literal_only() == "plain text"
+    assert module.single(5) == "5"
+    assert module.single("s") == "s"
+
+    point = module.Point(1.5, 2)
+    assert f"{point}" == "<1.5, 2>"
+    assert module.single(point) == "<1.5, 2>"
+    assert repr(point) == "Point(1.5, 2)"
+
+    loud = module.Loud("quiet")
+    assert module.single(loud) == "LOUD"
+    assert type(module.single(loud)) is str
+    assert module.conversions(loud) == "LOUD 'quiet' 'quiet'"
+
+    assert module.surrogate() == "\ud800-x"
+
+    assert module.format_error(lambda: module.single(module.NotStr())) == (
+        "TypeError: __format__ must return a str, not int"
+    )
+    assert module.format_error(lambda: module.single(module.Exploding())) == (
+        "ValueError: bad spec ''"
+    )
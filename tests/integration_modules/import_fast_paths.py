@@ -0,0 +1,122 @@
+"""Imports inside functions, with `sys.modules` changes and `__import__` overrides."""
+
+import builtins
+import sys
+import types
+
+
+def import_plain():
+    import soac_import_target
+
+    return soac_import_target
+
+
+def import_dotted():
+    import os.path
+
+    return os
+
+
+def import_dotted_as():
+    import os.path as alias
+
+    return alias
+
+
+def import_from_target():
+    from soac_import_target import value
+
+    return value
+
+
+def import_from_package():
+    from soac_import_package import sub
+
+    return sub
+
+
+def import_missing_name():
+    from soac_import_target import missing
+
+    return missing
+
+
+def import_missing_module():
+    import soac_import_missing
+
+    return soac_import_missing
+
+
+def install(name, **attrs):
+    module = types.ModuleType(name)
+    for key, value in attrs.items():
+        setattr(module, key, value)
+    sys.modules[name] = module
+    return module
+
+
+def error_of(fn):
+    try:
+        fn()
+    except ImportError as exc:
+        return type(exc).__name__, str(exc)
+    raise AssertionError("expected an ImportError")
+
+
+# diet-python: validate
+
+def validate_module(module):
+    import os
+
+    first = module.install("soac_import_target", value=1)
+    assert module.import_plain() is first
+    assert module.import_plain() is first
+    assert module.import_from_target() == 1
+    first.value = 2
+    assert module.import_from_target() == 2
+
+    second = module.install("soac_import_target", value=3)
+    assert module.import_plain() is second
+    assert module.import_from_target() == 3
+
+    assert module.import_dotted() is os
+    assert module.import_dotted_as() is os.path
+
+    calls = []
+    original_import = builtins.__import__
+
+    def tracing_import(name, *args, **kwargs):
+        calls.append(name)
+        return original_import(name, *args, **kwargs)
+
+    builtins.__import__ = tracing_import
+    try:
+        assert module.import_plain() is second
+        assert module.import_plain() is second
+    finally:
+        builtins.__import__ = original_import
+    assert calls == ["soac_import_target", "soac_import_target"], calls
+    assert module.import_plain() is second
+
+    module.install("soac_import_package", __path__=[])
+    sub = module.install("soac_import_package.sub")
+    assert module.import_from_package() is sub
+
+    kind, message = module.error_of(module.import_missing_name)
+    assert kind == "ImportError"
+    assert message == "cannot import name 'missing' from 'soac_import_target' (unknown location)", message
+
+    spec = types.SimpleNamespace(_initializing=True)
+    module.install("soac_import_target", __spec__=spec)
+    kind, message = module.error_of(module.import_missing_name)
+    assert kind == "ImportError"
+    # The partially-initialized wording varies across CPython versions.
+    assert message.startswith("cannot import name 'missing' from "), message
+
+    del sys.modules["soac_import_target"]
+    sys.modules["soac_import_missing"] = None
+    kind, message = module.error_of(module.import_missing_module)
+    assert kind == "ModuleNotFoundError"
+    del sys.modules["soac_import_missing"]
+    del sys.modules["soac_import_package"]
+    del sys.modules["soac_import_package.sub"]
@@ -0,0 +1,99 @@
+"""Match statements whose cases share subject tests or dispatch on literals."""
+
+
+class Loose(int):
+    def __eq__(self, other):
+        return other == 3
+
+
+def classify(value, flag=False):
+    match value:
+        case 1:
+            return "one"
+        case 2 | "two":
+            return "two"
+        case 3 if flag:
+            return "three"
+        case 1 | 3:
+            return "one or three"
+        case "four":
+            return "four"
+        case _:
+            return "other"
+
+
+class Point:
+    __match_args__ = ("x", "y")
+
+    def __init__(self, x, y):
+        self.x = x
+        self.y = y
+
+
+def locate(point):
+    match point:
+        case Point(0, 0):
+            return "origin"
+        case Point(0, y):
+            return f"y={y}"
+        case Point(x, 0):
+            return f"x={x}"
+        case Point(x=x, y=y) if x == y:
+            return "diagonal"
+        case Point(x, y):
+            return f"{x},{y}"
+        case _:
+            return "not a point"
+
+
+def shape(value):
+    match value:
+        case []:
+            return "empty"
+        case [x]:
+            return f"single {x}"
+        case [x, y] if x == y:
+            return "pair"
+        case [x, *rest]:
+            return f"{x} then {len(rest)}"
+        case {"kind": kind}:
+            return kind
+        case {}:
+            return "mapping"
+        case _:
+            return "other"
+
+
+# diet-python: validate
+
+def validate_module(module):
+    classify = module.classify
+    assert classify(1) == "one"
+    assert classify(2) == "two"
+    assert classify("two") == "two"
+    assert classify(3) == "one or three"
+    assert classify(3, flag=True) == "three"
+    assert classify("four") == "four"
+    assert classify(5) == "other"
+    assert classify(True) == "one"
+    assert classify(2.0) == "two"
+    assert classify(module.Loose(7)) == "one or three"
+    assert classify(module.Loose(7), flag=True) == "three"
+
+    Point = module.Point
+    locate = module.locate
+    assert locate(Point(0, 0)) == "origin"
+    assert locate(Point(0, 4)) == "y=4"
+    assert locate(Point(5, 0)) == "x=5"
+    assert locate(Point(2, 2)) == "diagonal"
+    assert locate(Point(2, 3)) == "2,3"
+    assert locate((0, 0)) == "not a point"
+
+    shape = module.shape
+    assert shape([]) == "empty"
+    assert shape((7,)) == "single 7"
+    assert shape([1, 1]) == "pair"
+    assert shape([1, 2, 3]) == "1 then 2"
+    assert shape("ab") == "other"
+    assert shape({"kind": "leaf"}) == "leaf"
+    assert shape({}) == "mapping"
@@ -0,0 +1,41 @@
+def conditional(flag):
+    if flag:
+        value = "bound"
+    return value
+
+
+def after_empty_loop(items):
+    for item in items:
+        pass
+    return item
+
+
+def rebound_after_delete():
+    value = 1
+    del value
+    value = 2
+    return value
+
+
+def raises_unbound(fn, *args):
+    try:
+        fn(*args)
+    except UnboundLocalError as exc:
+        return str(exc)
+    raise AssertionError(f"expected UnboundLocalError from {fn.__name__}")
+
+
+BOUND = conditional(True)
+UNBOUND = raises_unbound(conditional, False)
+LAST_ITEM = after_empty_loop([1, 2, 3])
+EMPTY_LOOP = raises_unbound(after_empty_loop, [])
+REBOUND = rebound_after_delete()
+
+# diet-python: validate
+
+def validate_module(module):
+    assert module.BOUND == "bound"
+    assert "'value'" in module.UNBOUND, module.UNBOUND
+    assert module.LAST_ITEM == 3
+    assert "'item'" in module.EMPTY_LOOP, module.EMPTY_LOOP
+    assert module.REBOUND == 2
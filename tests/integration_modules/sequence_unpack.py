@@ -0,0 +1,100 @@
+"""Sequence assignment targets with and without a starred target."""
+
+
+def split_three(value):
+    a, b, c = value
+    return a, b, c
+
+
+def split_star(value):
+    first, *middle, last = value
+    return first, middle, last
+
+
+def split_leading_star(value):
+    *init, last = value
+    return init, last
+
+
+def swap(a, b):
+    a, b = b, a
+    return a, b
+
+
+def rotate(items):
+    items[0], items[1], items[2] = items[1], items[2], items[0]
+    return items
+
+
+def nested(value):
+    (a, b), [c, *d] = value
+    return a, b, c, d
+
+
+def loop_pairs(pairs):
+    out = []
+    for key, *rest in pairs:
+        out.append((key, rest))
+    return out
+
+
+def unpack_error(fn, value):
+    try:
+        fn(value)
+    except (TypeError, ValueError) as exc:
+        return type(exc).__name__, str(exc)
+    raise AssertionError("expected an unpacking error")
+
+
+# diet-python: validate
+
+def validate_module(module):
+    t = (1, 2, 3)
+    assert module.split_three(t) == t
+    assert module.split_three([4, 5, 6]) == (4, 5, 6)
+    assert module.split_three(iter("xyz")) == ("x", "y", "z")
+    assert module.split_three({"a": 1, "b": 2, "c": 3}) == ("a", "b", "c")
+
+    first, middle, last = module.split_star((1, 2, 3, 4))
+    assert (first, middle, last) == (1, [2, 3], 4)
+    assert type(middle) is list
+    assert module.split_star([1, 2]) == (1, [], 2)
+    assert module.split_star(range(5)) == (0, [1, 2, 3], 4)
+    source = [1, 2, 3]
+    _, middle, _ = module.split_star(source)
+    middle.append(99)
+    assert source == [1, 2, 3]
+    assert module.split_leading_star("abc") == (["a", "b"], "c")
+
+    assert module.swap(1, 2) == (2, 1)
+    assert module.rotate([1, 2, 3]) == [2, 3, 1]
+    assert module.nested(((1, 2), [3, 4, 5])) == (1, 2, 3, [4, 5])
+    assert module.loop_pairs([(1, 2, 3), [4]]) == [(1, [2, 3]), (4, [])]
+
+    assert module.unpack_error(module.split_three, (1, 2)) == (
+        "ValueError",
+        "not enough values to unpack (expected 3, got 2)",
+    )
+    assert module.unpack_error(module.split_three, iter([1])) == (
+        "ValueError",
+        "not enough values to unpack (expected 3, got 1)",
+    )
+    assert module.unpack_error(module.split_three, iter(range(4))) == (
+        "ValueError",
+        "too many values to unpack (expected 3)",
+    )
+    kind, message = module.unpack_error(module.split_three, [1, 2, 3, 4])
+    assert kind == "ValueError"
+    assert message.startswith("too many values to unpack (expected 3"), message
+    assert module.unpack_error(module.split_star, [1]) == (
+        "ValueError",
+        "not enough values to unpack (expected at least 2, got 1)",
+    )
+    assert module.unpack_error(module.split_star, iter([])) == (
+        "ValueError",
+        "not enough values to unpack (expected at least 2, got 0)",
+    )
+    assert module.unpack_error(module.split_three, 5) == (
+        "TypeError",
+        "cannot unpack non-iterable int object",
+    )
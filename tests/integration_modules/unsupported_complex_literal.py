@@ -0,0 +1,6 @@
+value = 2j
+
+# diet-python: validate
+
+def validate_module(module):
+    raise AssertionError("unsupported_complex_literal should fail to import")
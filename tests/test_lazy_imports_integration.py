@@ -0,0 +1,160 @@
+from __future__ import annotations
+
+import importlib
+import sys
+from pathlib import Path
+
+from soac import import_hook
+
+
+def _write(path: Path, source: str) -> None:
+    path.write_text(source, encoding="utf-8")
+
+
+def test_lazy_imports_resolve_on_first_global_load(tmp_path: Path) -> None:
+    _write(
+        tmp_path / "lazy_target.py",
+        "import sys\nsys._soac_lazy_log.append('target')\nVALUE = 42\n",
+    )
+    _write(
+        tmp_path / "lazy_eager.py",
+        "import sys\nsys._soac_lazy_log.append('eager')\nVALUE = 7\n",
+    )
+    _write(
+        tmp_path / "lazy_user.py",
+        "# soac: lazy-imports\n"
+        "# soac: eager-imports lazy_eager\n"
+        "import lazy_target\n"
+        "import lazy_eager\n"
+        "from lazy_target import VALUE as TARGET_VALUE\n"
+        "\n"
+        "def use():\n"
+        "    return lazy_target.VALUE, TARGET_VALUE, lazy_eager.VALUE\n",
+    )
+
+    sys.path.insert(0, str(tmp_path))
+    sys._soac_lazy_log = []
+    import_hook.install()
+
+    try:
+        module = importlib.import_module("lazy_user")
+        assert sys._soac_lazy_log == ["eager"]
+        assert "lazy_target" not in module.__dict__
+        assert "TARGET_VALUE" not in module.__dict__
+        assert module.use() == (42, 42, 7)
+        assert sys._soac_lazy_log == ["eager", "target"]
+        assert module.use() == (42, 42, 7)
+        assert module.__dict__["lazy_target"] is sys.modules["lazy_target"]
+        assert module.__dict__["TARGET_VALUE"] == 42
+    finally:
+        for name in ["lazy_user", "lazy_target", "lazy_eager"]:
+            sys.modules.pop(name, None)
+        del sys._soac_lazy_log
+        sys.path.remove(str(tmp_path))
+
+
+def test_lazy_imports_resolve_in_class_bodies_and_from_other_modules(
+    tmp_path: Path,
+) -> None:
+    _write(
+        tmp_path / "lazy_user.py",
+        "# soac: lazy-imports\n"
+        "from collections import OrderedDict\n"
+        "from collections import deque\n"
+        "\n"
+        "class C:\n"
+        "    d = OrderedDict()\n",
+    )
+    _write(
+        tmp_path / "lazy_consumer.py",
+        "from lazy_user import deque\n",
+    )
+
+    sys.path.insert(0, str(tmp_path))
+    import_hook.install()
+
+    try:
+        import collections
+
+        module = importlib.import_module("lazy_user")
+        assert type(module.C.d) is collections.OrderedDict
+        assert module.__dict__["OrderedDict"] is collections.OrderedDict
+        assert "deque" not in module.__dict__
+
+        consumer = importlib.import_module("lazy_consumer")
+        assert consumer.deque is collections.deque
+        assert module.__dict__["deque"] is collections.deque
+        assert module.deque is collections.deque
+    finally:
+        for name in ["lazy_user", "lazy_consumer"]:
+            sys.modules.pop(name, None)
+        sys.path.remove(str(tmp_path))
+
+
+def test_lazy_imports_show_in_dir_and_star_imports_and_keep_user_globals(
+    tmp_path: Path,
+) -> None:
+    _write(
+        tmp_path / "lazy_user.py",
+        "# soac: lazy-imports\n"
+        "def __getattr__(name):\n"
+        "    if name == 'computed':\n"
+        "        return 'from user __getattr__'\n"
+        "    raise AttributeError(name)\n"
+        "\n"
+        "__soac_lazy_imports__ = 'user value'\n"
+        "from collections import OrderedDict\n"
+        "from collections import deque as _deque\n",
+    )
+    _write(
+        tmp_path / "lazy_star_consumer.py",
+        "from lazy_user import *\n",
+    )
+
+    sys.path.insert(0, str(tmp_path))
+    import_hook.install()
+
+    try:
+        import collections
+
+        module = importlib.import_module("lazy_user")
+        assert module.__dict__["__soac_lazy_imports__"] == "user value"
+        assert module.__dict__["__getattr__"].__name__ == "__getattr__"
+        assert module.computed == "from user __getattr__"
+        assert "OrderedDict" in dir(module)
+        assert "OrderedDict" not in module.__dict__
+
+        consumer = importlib.import_module("lazy_star_consumer")
+        assert consumer.OrderedDict is collections.OrderedDict
+        assert "_deque" not in consumer.__dict__
+        assert module.__dict__["OrderedDict"] is collections.OrderedDict
+
+        assert module._deque is collections.deque
+        assert type(module) is type(collections)
+        assert module.computed == "from user __getattr__"
+    finally:
+        for name in ["lazy_user", "lazy_star_consumer"]:
+            sys.modules.pop(name, None)
+        sys.path.remove(str(tmp_path))
+
+
+def test_modules_reading_globals_keep_imports_eager(tmp_path: Path) -> None:
+    _write(
+        tmp_path / "lazy_user.py",
+        "# soac: lazy-imports\n"
+        "from collections import OrderedDict\n"
+        "\n"
+        "def names():\n"
+        "    return sorted(globals())\n",
+    )
+
+    sys.path.insert(0, str(tmp_path))
+    import_hook.install()
+
+    try:
+        module = importlib.import_module("lazy_user")
+        assert "OrderedDict" in module.__dict__
+        assert "OrderedDict" in module.names()
+    finally:
+        sys.modules.pop("lazy_user", None)
+        sys.path.remove(str(tmp_path))
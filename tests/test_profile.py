@@ -0,0 +1,45 @@
+from __future__ import annotations
+
+import sys
+import time
+
+from tests._integration import integration_module
+
+from soac.profile import Profile, main
+
+
+def test_profile_attributes_samples_to_jit_qualname(tmp_path):
+    source = """
+def spin(deadline, clock):
+    total = 0
+    while clock() < deadline:
+        i = 0
+        while i < 1000:
+            i = i + 1
+        total = total + i
+    return total
+"""
+
+    with integration_module(tmp_path, "profile_case", source, mode="transform") as module:
+        with Profile(interval_us=500) as profile:
+            module.spin(time.process_time() + 0.3, time.process_time)
+
+    # Samples taken inside the JIT-compiled loop itself resolve to `spin`
+    # without walking any caller frames, so the hotspot table always has it.
+    assert profile.collapsed
+    assert any(row["qualname"] == "spin" for row in profile.hotspots)
+
+    output = tmp_path / "out.collapsed"
+    profile.write(output)
+    assert output.read_text(encoding="utf-8") == profile.collapsed
+    assert (tmp_path / "out.collapsed.hotspots.json").exists()
+
+
+def test_profile_main_writes_output_when_script_exits(tmp_path, monkeypatch):
+    monkeypatch.setattr(sys, "argv", list(sys.argv))
+    script = tmp_path / "exits.py"
+    script.write_text("import sys\nsys.exit(3)\n", encoding="utf-8")
+    output = tmp_path / "exits.collapsed"
+
+    assert main(["-o", str(output), str(script)]) == 3
+    assert output.exists()
@@ -1,6 +1,17 @@
 import pytest
 
 def test_import_syntax_error_propagates(run_integration_module):
-    with pytest.raises(SyntaxError):
+    with pytest.raises(SyntaxError) as excinfo:
         with run_integration_module("bad_syntax"):
             pass
+    assert excinfo.value.filename.endswith("bad_syntax.py")
+    assert excinfo.value.lineno == 1
+
+
+def test_import_unsupported_syntax_reports_location(run_integration_module):
+    with pytest.raises(NotImplementedError) as excinfo:
+        with run_integration_module("unsupported_complex_literal"):
+            pass
+    message = str(excinfo.value)
+    assert message.startswith("error[unsupported-syntax]: complex literals are not supported")
+    assert "unsupported_complex_literal.py:1:9" in message
@@ -0,0 +1,88 @@
+from __future__ import annotations
+
+import traceback
+import types
+from pathlib import Path
+
+import pytest
+
+from tests._integration import integration_module
+
+SOURCE = """
+FACTOR = 2
+
+def scale(x):
+    return x * FACTOR
+
+def check(x):
+    if x < 0:
+        raise ValueError(x)
+    return x
+
+def run(x):
+    return scale(x)
+
+def run_check(x):
+    return check(x)
+"""
+
+
+def _raised_by(module: types.ModuleType, value: int) -> tuple[BaseException, list[str]]:
+    with pytest.raises(Exception) as exc_info:
+        module.run_check(value)
+    frames = traceback.extract_tb(exc_info.value.__traceback__)
+    return exc_info.value, [frame.name for frame in frames]
+
+
+@pytest.mark.parametrize(
+    "mode",
+    ["stock", "transform"],
+    ids=["stock", "transformed"],
+)
+def test_inlined_call_follows_rebound_global(
+    tmp_path: Path, monkeypatch: pytest.MonkeyPatch, mode: str
+) -> None:
+    monkeypatch.setenv("DIET_PYTHON_INLINE", "1")
+    with integration_module(tmp_path, "inline_guard", SOURCE, mode=mode) as module:
+        original = module.scale
+        for _ in range(3):
+            assert module.run(3) == 6
+        assert module.run_check(4) == 4
+
+        module.FACTOR = 10
+        assert module.run(3) == 30
+
+        module.scale = lambda x: x + 100
+        assert module.run(3) == 103
+
+        # A bound method wraps the same function but passes an extra argument.
+        module.scale = types.MethodType(original, "bound")
+        with pytest.raises(TypeError):
+            module.run(3)
+
+        # The same def made by another module instance reads that module's
+        # globals.
+        with integration_module(tmp_path, "inline_guard", SOURCE, mode=mode) as other:
+            other.FACTOR = 5
+            module.scale = other.scale
+            assert module.run(3) == 15
+
+        module.scale = original
+        assert module.run(3) == 30
+
+
+def test_inlined_call_raises_like_the_call(
+    tmp_path: Path, monkeypatch: pytest.MonkeyPatch
+) -> None:
+    raised = {}
+    for inline in ["0", "1"]:
+        monkeypatch.setenv("DIET_PYTHON_INLINE", inline)
+        with integration_module(
+            tmp_path, "inline_guard", SOURCE, mode="transform"
+        ) as module:
+            raised[inline] = _raised_by(module, -1)
+
+    (called, called_frames), (inlined, inlined_frames) = raised["0"], raised["1"]
+    assert type(inlined) is type(called) is ValueError
+    assert inlined.args == called.args == (-1,)
+    assert inlined_frames == called_frames
@@ -0,0 +1,246 @@
+"""Differential test: the scopes diet-python lowers against must agree with
+CPython's symtable module."""
+
+from __future__ import annotations
+
+import _symtable
+import ast
+import symtable
+from pathlib import Path
+
+import pytest
+
+from soac import _soac_ext
+from tests._integration import cpython_test_module_paths, integration_module_paths
+
+SNIPPETS = {
+    "closure_cell": "def f():\n    x = 1\n    def g():\n        return x\n    return g\n",
+    "pass_through_free": (
+        "def f():\n    x = 1\n    def g():\n        def h():\n            return x\n"
+        "        return h\n    return g\n"
+    ),
+    "nonlocal_rebinding": (
+        "def f():\n    x = 1\n    def g():\n        nonlocal x\n        x += 1\n    return g\n"
+    ),
+    "global_declaration": "def f():\n    global counter\n    counter = 1\n",
+    "class_body_is_not_enclosing": (
+        "x = 0\nclass C:\n    x = 1\n    def m(self):\n        return x\n"
+    ),
+    "class_cell_from_super": (
+        "class C(Base):\n    def m(self):\n        return super().m()\n"
+    ),
+    "class_cell_from_dunder_class": "class C:\n    def m(self):\n        return __class__\n",
+    "parameters": "def f(a, /, b, *args, c, **kwargs):\n    return a, b, args, c, kwargs\n",
+    "lambda": "def f(y):\n    return lambda x: x + y\n",
+    "list_comprehension": "def f(xs):\n    return [x * 2 for x in xs]\n",
+    "comprehension_capture": "def f(xs, n):\n    return {x: n for x in xs}\n",
+    "comprehension_nested_lambda": "def f(xs):\n    return [lambda: x for x in xs]\n",
+    "generator_expression": "def f(xs, n):\n    return sum(x + n for x in xs)\n",
+    "walrus_in_comprehension": "def f(xs):\n    [last := x for x in xs]\n    return last\n",
+    # Function-local annotations are never evaluated, so keep their names
+    # referenced elsewhere too.
+    "annotated_assignment": "def f(tp):\n    x: tp = 1\n    return x, tp\n",
+    "imports": "import os\nfrom sys import path as p\ndef f():\n    import json\n    return json, os, p\n",
+    "except_name": "def f():\n    try:\n        pass\n    except Exception as e:\n        return e\n",
+    "decorated_class": "def deco(c):\n    return c\n@deco\nclass C:\n    y = [i for i in range(3)]\n",
+    "annotation_only_declaration": "class C:\n    x: int\n    y: str = ''\n",
+    "parameter_annotations": "def f(a: int, *args: str, b: bytes = b'', **kw: float) -> None:\n    return a\n",
+    "annotation_closure": (
+        "def f():\n    tp = int\n    def g(x: tp) -> tp:\n        return x\n    return g\n"
+    ),
+    "method_annotations": (
+        "class C:\n    Alias = int\n    def m(self, x: Alias) -> 'C':\n        return x\n"
+    ),
+    "generic_function": "def f[T: int, *Ts, **P](x: T, *args: *Ts) -> T:\n    return x\n",
+    "generic_function_default": "def f[T = int](x: T) -> T:\n    return x\n",
+    "type_param_used_in_body": "def f[T](x):\n    return T\n",
+    "generic_class": (
+        "class Box[T](Base[T], metaclass=Meta):\n    def get(self) -> T:\n"
+        "        return self.value\n"
+    ),
+    "generic_method_closure": (
+        "def f():\n    bound = int\n    class C:\n        def m[T: bound](self, x: T) -> T:\n"
+        "            return x\n    return C\n"
+    ),
+    "type_alias": "Plain = int\ntype Alias = list[Plain]\ntype Pair[T] = tuple[T, T]\n",
+    "super_in_nested_function": (
+        "class C(Base):\n    def m(self):\n        def g():\n            return super()\n"
+        "        return g\n"
+    ),
+    "super_with_arguments": "class C(Base):\n    def m(self):\n        return super(C, self).m()\n",
+}
+
+_SCOPE_NAMES = {
+    _symtable.LOCAL: "local",
+    _symtable.CELL: "cell",
+    _symtable.FREE: "free",
+    _symtable.GLOBAL_EXPLICIT: "global_explicit",
+    _symtable.GLOBAL_IMPLICIT: "global_implicit",
+}
+_COMPREHENSIONS = {"listcomp", "setcomp", "dictcomp"}
+# Names the compiler reads implicitly; `__annotate__` and `__annotate_func__`
+# are bound by the pipeline's rewrite of module and class annotations.
+_SKIPPED_NAMES = {"__classdict__", "__conditional_annotations__", "__type_params__"}
+
+
+def _skip_symbol(name: str) -> bool:
+    return name.startswith(".") or name in _SKIPPED_NAMES or name.startswith("__annotate")
+
+
+def _is_def_annotation_table(
+    table: symtable.SymbolTable, next_sibling: symtable.SymbolTable | None
+) -> bool:
+    # A def's `__annotate__` table comes right before the def's own table.
+    # Annotated assignments get one per module, class or function instead,
+    # which the pipeline replaces with a helper def (or drops in functions).
+    return (
+        next_sibling is not None
+        and str(next_sibling.get_type()) == "function"
+        and next_sibling.get_lineno() == table.get_lineno()
+    )
+
+
+def _cpython_table(table: symtable.SymbolTable) -> dict:
+    symbols = {}
+    annotation_only = set()
+    for sym in table.get_symbols():
+        name = sym.get_name()
+        if _skip_symbol(name):
+            continue
+        symbols[name] = {
+            "scope": _SCOPE_NAMES[sym._Symbol__scope],
+            "is_parameter": sym.is_parameter(),
+            "is_referenced": sym.is_referenced(),
+        }
+        if sym.is_annotated() and not sym.is_referenced():
+            annotation_only.add(name)
+    result = {
+        "type": str(table.get_type()),
+        "name": table.get_name(),
+        "symbols": symbols,
+        "annotation_only": annotation_only,
+        "children": [],
+    }
+    children = table.get_children()
+    for child, next_sibling in zip(children, [*children[1:], None]):
+        if str(child.get_type()) == "annotation" and not _is_def_annotation_table(
+            child, next_sibling
+        ):
+            continue
+        converted = _cpython_table(child)
+        if converted["name"] in _COMPREHENSIONS:
+            _inline_comprehension(result, converted)
+        else:
+            result["children"].append(converted)
+    return result
+
+
+def _free_names(table: dict) -> set[str]:
+    names = {name for name, sym in table["symbols"].items() if sym["scope"] == "free"}
+    for child in table["children"]:
+        names |= _free_names(child)
+    return names
+
+
+def _inline_comprehension(parent: dict, comprehension: dict) -> None:
+    # Before 3.12 comprehensions were their own function scopes; fold them
+    # into the parent the way PEP 709 does so both sides agree.
+    for name, sym in comprehension["symbols"].items():
+        if name not in parent["symbols"]:
+            scope = sym["scope"]
+            if scope == "free" and parent["type"] == "module":
+                scope = "global_implicit"
+            parent["symbols"][name] = {
+                "scope": scope,
+                "is_parameter": False,
+                "is_referenced": sym["is_referenced"],
+            }
+    parent["children"].extend(comprehension["children"])
+    captured = set()
+    for child in parent["children"]:
+        captured |= _free_names(child)
+    for name, sym in parent["symbols"].items():
+        if sym["scope"] == "cell" and name not in captured and name != "__class__":
+            sym["scope"] = "local"
+
+
+def _diet_table(table: dict) -> dict:
+    return {
+        "type": table["type"],
+        "name": table["name"],
+        "symbols": {
+            name: sym for name, sym in table["symbols"].items() if not _skip_symbol(name)
+        },
+        "children": [_diet_table(child) for child in table["children"]],
+    }
+
+
+def _assert_tables_match(actual: dict, expected: dict, path: str) -> None:
+    assert (actual["type"], actual["name"]) == (expected["type"], expected["name"]), path
+    # The annotation rewrite drops declarations like `x: int` that never
+    # assign, so CPython may list those names where the pipeline does not.
+    expected_symbols = {
+        name: sym
+        for name, sym in expected["symbols"].items()
+        if name in actual["symbols"] or name not in expected["annotation_only"]
+    }
+    assert sorted(actual["symbols"]) == sorted(expected_symbols), f"{path}: symbols"
+    for name, sym in expected_symbols.items():
+        assert actual["symbols"][name] == sym, f"{path}: symbol {name!r}"
+    actual_children = [(child["type"], child["name"]) for child in actual["children"]]
+    expected_children = [(child["type"], child["name"]) for child in expected["children"]]
+    assert actual_children == expected_children, f"{path}: child tables"
+    for actual_child, expected_child in zip(actual["children"], expected["children"]):
+        _assert_tables_match(actual_child, expected_child, f"{path}.{expected_child['name']}")
+
+
+def _assert_source_matches_cpython(source: str, filename: str) -> None:
+    expected = _cpython_table(symtable.symtable(source, filename, "exec"))
+    actual = _diet_table(_soac_ext.symbol_table(source, filename))
+    _assert_tables_match(actual, expected, "top")
+
+
+@pytest.mark.parametrize("name", sorted(SNIPPETS))
+def test_snippet_matches_cpython(name: str) -> None:
+    _assert_source_matches_cpython(SNIPPETS[name], f"{name}.py")
+
+
+def _assert_file_matches_cpython(path: Path) -> None:
+    source = path.read_text(encoding="utf-8")
+    try:
+        compile(source, str(path), "exec", dont_inherit=True)
+    except SyntaxError:
+        pytest.skip("rejected by CPython")
+    if any(
+        isinstance(stmt, ast.ImportFrom) and stmt.module == "__future__"
+        for stmt in ast.parse(source).body
+    ):
+        # The parser strips `from __future__` imports and stringifies
+        # annotations before scopes are computed.
+        pytest.skip("uses __future__ imports")
+    try:
+        actual = _soac_ext.symbol_table(source, str(path))
+    except NotImplementedError as err:
+        pytest.skip(f"unsupported by diet-python: {err}")
+    expected = _cpython_table(symtable.symtable(source, str(path), "exec"))
+    _assert_tables_match(_diet_table(actual), expected, "top")
+
+
+@pytest.mark.parametrize(
+    "path", integration_module_paths(), ids=lambda path: path.stem
+)
+def test_integration_module_matches_cpython(path: Path) -> None:
+    _assert_file_matches_cpython(path)
+
+
+CPYTHON_TEST_MODULES = cpython_test_module_paths()
+
+
+@pytest.mark.skipif(not CPYTHON_TEST_MODULES, reason="CPython test sources not found")
+@pytest.mark.parametrize(
+    "path",
+    CPYTHON_TEST_MODULES,
+    ids=lambda path: ".".join(path.with_suffix("").parts[-3:]),
+)
+def test_cpython_test_module_matches_cpython(path: Path) -> None:
+    _assert_file_matches_cpython(path)
@@ -0,0 +1,134 @@
+"""Differential test: diet-python must reject exactly what CPython's compile()
+rejects, with the same message and line number."""
+
+from __future__ import annotations
+
+from pathlib import Path
+
+import pytest
+
+from soac import _soac_ext
+from tests._integration import cpython_test_module_paths, integration_module_paths
+
+# Programs that parse but are rejected by CPython's symbol table or compiler.
+SNIPPETS = {
+    "nonlocal_module_level": "nonlocal x\n",
+    "global_after_use": "def f():\n    print(x)\n    global x\n",
+    "global_after_assign": "def f():\n    x = 1\n    global x\n",
+    "nonlocal_after_use": "def f():\n    x = 1\n    def g():\n        print(x)\n        nonlocal x\n",
+    "global_parameter": "def f(x):\n    global x\n",
+    "nonlocal_parameter": "def f():\n    x = 1\n    def g(x):\n        nonlocal x\n",
+    "annotated_global": "def f():\n    global x\n    x: int = 1\n",
+    "nonlocal_without_binding": "def f():\n    def g():\n        nonlocal x\n",
+    "nonlocal_of_class_attribute": "class C:\n    x = 1\n    def m(self):\n        nonlocal x\n",
+    "nonlocal_and_global": "def f():\n    x = 1\n    def g():\n        global x\n        nonlocal x\n",
+    "import_star_in_function": "def f():\n    from os import *\n",
+    "async_generator_return_value": "async def f():\n    yield 1\n    return 2\n",
+    "yield_in_list_comprehension": "def f():\n    return [(yield x) for x in y]\n",
+    "yield_in_set_comprehension": "def f():\n    return {(yield x) for x in y}\n",
+    "yield_in_dict_comprehension": "def f():\n    return {x: (yield x) for x in y}\n",
+    "yield_in_generator_expression": "def f():\n    return ((yield x) for x in y)\n",
+    "yield_from_in_async_function": "async def f():\n    yield from x\n",
+    "duplicate_parameter": "def f(a, b, a):\n    pass\n",
+    "duplicate_lambda_parameter": "f = lambda a, *, a: a\n",
+    "await_in_sync_function": "def f():\n    await x\n",
+    "await_in_lambda": "async def f():\n    return lambda: await x\n",
+    "await_at_module_level": "x = 1\nawait x\n",
+    "await_in_class_body": "async def f():\n    class C:\n        await x\n",
+    "async_comprehension_in_sync_function": "def f():\n    return [x async for x in y]\n",
+    "await_in_comprehension_in_sync_function": "def f():\n    return [await x for x in y]\n",
+    "async_for_in_sync_function": "def f():\n    async for x in y:\n        pass\n",
+    "async_with_in_sync_function": "def f():\n    async with x:\n        pass\n",
+    "return_at_module_level": "x = 1\nreturn x\n",
+    "return_in_class_body": "class C:\n    return 1\n",
+    "yield_at_module_level": "yield 1\n",
+    "break_outside_loop": "def f():\n    break\n",
+    "break_in_nested_function": "while x:\n    def f():\n        break\n",
+    "continue_in_loop_else": "for x in y:\n    pass\nelse:\n    continue\n",
+    "return_in_except_star": "def f():\n    try:\n        pass\n    except* ValueError:\n        return 1\n",
+    "break_out_of_except_star": "for x in y:\n    try:\n        pass\n    except* ValueError:\n        break\n",
+    "walrus_rebinds_iteration_variable": "[i := 0 for i in range(3)]\n",
+    "walrus_in_class_comprehension": "class C:\n    [(y := i) for i in range(3)]\n",
+    "symbol_table_before_compiler": "return 1\ndef f():\n    x = 1\n    global x\n",
+    "yield_in_parameter_annotation": "def f(x: (yield)):\n    pass\n",
+    "yield_in_nested_return_annotation": "def f():\n    def g() -> (yield):\n        pass\n",
+    "await_in_parameter_annotation": "async def f():\n    def g(*, x: await y):\n        pass\n",
+    "walrus_in_parameter_annotation": "def f(*args: (x := int)):\n    pass\n",
+    "yield_in_type_var_bound": "def f[T: (yield)]():\n    pass\n",
+    "await_in_type_var_default": "async def f():\n    def g[T = await x]():\n        pass\n",
+    "yield_in_generic_class_bases": "class C[T](Base[(yield)]):\n    pass\n",
+    "walrus_comprehension_in_type_alias": "type A = [(y := 1) for _ in ()]\n",
+    "duplicate_type_parameter": "def f[T, U, T]():\n    pass\n",
+    "debug_type_parameter": "class C[__debug__]:\n    pass\n",
+    "nonlocal_type_parameter": "def f[T]():\n    def g():\n        nonlocal T\n",
+    # Accepted by both.
+    "valid_nonlocal": "def f():\n    x = 1\n    def g():\n        nonlocal x\n        x = 2\n",
+    "valid_async_genexp_in_sync_function": "def f():\n    return (await x for x in y)\n",
+    "valid_loop_inside_except_star": "try:\n    pass\nexcept* ValueError:\n    for x in y:\n        break\n",
+    "valid_yield_in_outermost_iterable": "def f():\n    return [x for x in (yield)]\n",
+    "valid_nonlocal_shadowing_type_parameter": "def f[T]():\n    T = 1\n    def g():\n        nonlocal T\n",
+    "valid_generic_method": "class C:\n    def m[T](self, x: T) -> list[T]:\n        return [x]\n",
+}
+
+
+def _cpython_error(source: str, filename: str) -> SyntaxError | None:
+    try:
+        compile(source, filename, "exec", dont_inherit=True)
+    except SyntaxError as err:
+        return err
+    return None
+
+
+def _diet_error(source: str, filename: str) -> SyntaxError | None:
+    try:
+        _soac_ext.check_source_syntax(source, filename)
+    except SyntaxError as err:
+        return err
+    return None
+
+
+@pytest.mark.parametrize("name", sorted(SNIPPETS))
+def test_snippet_matches_cpython(name: str) -> None:
+    source = SNIPPETS[name]
+    expected = _cpython_error(source, f"{name}.py")
+    actual = _diet_error(source, f"{name}.py")
+
+    if expected is None:
+        assert actual is None, f"diet-python rejected valid code: {actual}"
+        return
+    assert actual is not None, f"diet-python accepted code CPython rejects: {expected}"
+    assert (actual.msg, actual.lineno) == (expected.msg, expected.lineno)
+    assert actual.filename == f"{name}.py"
+
+
+def _assert_file_matches_cpython(path: Path) -> None:
+    # Parse errors are reported in ruff's words, so files only need to agree
+    # on whether and where they fail; the snippets above pin exact messages.
+    source = path.read_text(encoding="utf-8")
+    expected = _cpython_error(source, str(path))
+    actual = _diet_error(source, str(path))
+    if expected is None:
+        assert actual is None, f"diet-python rejected valid code: {actual}"
+        return
+    assert actual is not None, f"diet-python accepted code CPython rejects: {expected}"
+    assert actual.lineno == expected.lineno, (actual.msg, expected.msg)
+
+
+@pytest.mark.parametrize(
+    "path", integration_module_paths(), ids=lambda path: path.stem
+)
+def test_integration_module_matches_cpython(path: Path) -> None:
+    _assert_file_matches_cpython(path)
+
+
+CPYTHON_TEST_MODULES = cpython_test_module_paths()
+
+
+@pytest.mark.skipif(not CPYTHON_TEST_MODULES, reason="CPython test sources not found")
+@pytest.mark.parametrize(
+    "path",
+    CPYTHON_TEST_MODULES,
+    ids=lambda path: ".".join(path.with_suffix("").parts[-3:]),
+)
+def test_cpython_test_module_matches_cpython(path: Path) -> None:
+    _assert_file_matches_cpython(path)
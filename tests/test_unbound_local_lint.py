@@ -0,0 +1,52 @@
+"""The static unbound-local report must cover every UnboundLocalError
+CPython raises at runtime for these snippets."""
+
+from __future__ import annotations
+
+import pytest
+
+from soac import _soac_ext
+
+# Each snippet defines `f()`; the value is the set of names reported.
+SNIPPETS = {
+    "straight_line": ("def f():\n    x = 1\n    return x\n", set()),
+    "one_branch": ("def f(flag=False):\n    if flag:\n        x = 1\n    return x\n", {"x"}),
+    "both_branches": (
+        "def f(flag=False):\n    if flag:\n        x = 1\n    else:\n        x = 2\n    return x\n",
+        set(),
+    ),
+    "empty_loop": ("def f(items=()):\n    for item in items:\n        pass\n    return item\n", {"item"}),
+    "delete": ("def f():\n    x = 1\n    del x\n    return x\n", {"x"}),
+    "delete_and_rebind": ("def f():\n    x = 1\n    del x\n    x = 2\n    return x\n", set()),
+    "handler": (
+        "def f():\n    try:\n        x = 1 // 0\n    except ZeroDivisionError:\n        return x\n",
+        {"x"},
+    ),
+    "except_name_cleared": (
+        "def f():\n    try:\n        1 // 0\n    except ZeroDivisionError as exc:\n        pass\n"
+        "    return exc\n",
+        {"exc"},
+    ),
+    "use_before_store": ("def f():\n    print(x)\n    x = 1\n", {"x"}),
+}
+
+
+def _cpython_unbound_name(source: str) -> str | None:
+    namespace: dict = {}
+    exec(compile(source, "<snippet>", "exec"), namespace)
+    try:
+        namespace["f"]()
+    except UnboundLocalError as err:
+        return str(err).split("'")[1]
+    return None
+
+
+@pytest.mark.parametrize("name", sorted(SNIPPETS))
+def test_report_covers_cpython_unbound_locals(name: str) -> None:
+    source, expected = SNIPPETS[name]
+    reported = _soac_ext.maybe_unbound_locals(source, f"{name}.py")
+    assert {load["name"] for load in reported} == expected
+    assert all(load["qualname"] == "f" for load in reported)
+    raised = _cpython_unbound_name(source)
+    if raised is not None:
+        assert raised in expected